@@ -1,7 +1,8 @@
 use {
     crate::{
-        decoration::{Decoration, DecorationType},
+        decoration::{Decoration, DecorationType, GutterMarkerType},
         layout::{BlockElement, WrappedElement},
+        search::SearchQuery,
         selection::Affinity,
         session::{SelectionMode, Session},
         history::{NewGroup},
@@ -26,6 +27,10 @@ live_design! {
         delimiter_highlight: #f,
         error_decoration: #f00,
         warning_decoration: #0f0,
+        search_match_decoration: #ffd70040,
+        added_line_marker: #587C0C,
+        modified_line_marker: #0C7D9D,
+        deleted_line_marker: #94151B,
         
         unknown: #C0C0C0,
         branch_keyword: #C485BE,
@@ -61,6 +66,15 @@ live_design! {
         }
     }
 
+    DrawSearchMatch = {{DrawSearchMatch}} {
+        uniform border_radius: 2.0
+        fn pixel(self) -> vec4 {
+            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+            sdf.box(0.0, 0.0, self.rect_size.x, self.rect_size.y, self.border_radius);
+            return sdf.fill(self.color);
+        }
+    }
+
     DrawSelection = {{DrawSelection}} {
         uniform gloopiness: 8.0
         uniform border_radius: 2.0
@@ -123,6 +137,9 @@ live_design! {
             text_style: <THEME_FONT_CODE> {},
             color: (THEME_COLOR_TEXT_META),
         }
+        draw_gutter_marker: {
+            draw_depth: 1.0,
+        }
         draw_text: {
             draw_depth: 1.0,
             text_style: <THEME_FONT_CODE> {}
@@ -143,6 +160,9 @@ live_design! {
         draw_decoration: {
           //  draw_depth: 2.0,
         }
+        draw_search_match: {
+          //  draw_depth: 2.0,
+        }
         draw_selection: {
            // draw_depth: 3.0,
         }
@@ -216,15 +236,20 @@ struct DrawCodeText {
     outline: f32,
 }
 
+// the width of the gutter markers for changed lines
+const GUTTER_MARKER_WIDTH: f64 = 3.0;
+
 #[derive(Live, LiveRegister)]
 pub struct CodeEditor {
     #[walk] walk: Walk,
     #[live] scroll_bars: ScrollBars,
     #[live] draw_gutter: DrawText,
+    #[live] draw_gutter_marker: DrawColor,
     #[live] draw_text: DrawCodeText,
     #[live] token_colors: TokenColors,
     #[live] draw_indent_guide: DrawIndentGuide,
     #[live] draw_decoration: DrawDecoration,
+    #[live] draw_search_match: DrawSearchMatch,
     #[live] draw_selection: DrawSelection,
     #[live] draw_cursor: DrawColor,
     #[live] draw_cursor_bg: DrawColor,
@@ -469,10 +494,12 @@ impl CodeEditor {
         self.draw_bg.draw_abs(cx, cx.turtle().unscrolled_rect());
 
         self.draw_gutter(cx, session);
+        self.draw_gutter_markers(cx, session);
         self.draw_selection_layer(cx, session);
+        self.draw_decoration_layer(cx, session, &session.search_matches());
         self.draw_text_layer(cx, session);
         self.draw_indent_guide_layer(cx, session);
-        self.draw_decoration_layer(cx, session);
+        self.draw_decoration_layer(cx, session, &session.document().decorations());
         self.draw_selection_layer(cx, session);
 
         // Get the last added selection.
@@ -496,6 +523,20 @@ impl CodeEditor {
         cx.set_key_focus(self.scroll_bars.area());
     }
 
+    pub fn area(&self) -> Area {
+        self.scroll_bars.area()
+    }
+
+    /// The rect of the cursor of the last added selection as of the last draw, in absolute
+    /// coordinates.
+    pub fn cursor_rect(&self) -> Option<Rect> {
+        // the viewport was laid out with the scroll position already subtracted
+        self.last_cursor_screen_pos.map(|pos| Rect {
+            pos: pos + self.viewport_rect.pos + self.scroll_bars.get_scroll_pos(),
+            size: self.cell_size,
+        })
+    }
+
     pub fn set_cursor_and_scroll(
         &mut self,
         cx: &mut Cx,
@@ -850,6 +891,55 @@ impl CodeEditor {
                     keyboard_moved_cursor = true;
                 }
             }
+            Hit::KeyDown(KeyEvent {
+                key_code: KeyCode::KeyF,
+                modifiers: KeyModifiers { control, logo, .. },
+                ..
+            }) => {
+                if control || logo {
+                    // search for the selected text, or stop searching if nothing is selected
+                    let text = session.copy();
+                    if text.is_empty()
+                        || text.contains('\n')
+                        || session
+                            .set_search(&SearchQuery::literal(text).with_case_sensitive(true))
+                            .is_err()
+                    {
+                        session.clear_search();
+                    }
+                    self.redraw(cx);
+                }
+            }
+            Hit::KeyDown(KeyEvent {
+                key_code: KeyCode::F3,
+                modifiers: KeyModifiers { shift, .. },
+                ..
+            }) => {
+                let is_found = if shift {
+                    session.find_prev()
+                } else {
+                    session.find_next()
+                };
+                if is_found {
+                    self.redraw(cx);
+                    keyboard_moved_cursor = true;
+                }
+            }
+            Hit::KeyDown(KeyEvent {
+                key_code: KeyCode::KeyG,
+                modifiers: KeyModifiers { logo: true, shift, .. },
+                ..
+            }) => {
+                let is_found = if shift {
+                    session.find_prev()
+                } else {
+                    session.find_next()
+                };
+                if is_found {
+                    self.redraw(cx);
+                    keyboard_moved_cursor = true;
+                }
+            }
             Hit::FingerDown(FingerDownEvent {
                 abs,
                 tap_count,
@@ -863,6 +953,9 @@ impl CodeEditor {
             }) => {
                 self.animator_play(cx, id!(focus.on));
                 cx.set_key_focus(self.scroll_bars.area());
+                if let Some(line_index) = self.pick_gutter_marker(session, abs) {
+                    actions.push(CodeEditorAction::GutterMarkerClicked(line_index));
+                }
                 let ((cursor, affinity), is_in_gutter) = self.pick(session, abs);
                 session.set_selection(
                     cursor,
@@ -993,6 +1086,63 @@ impl CodeEditor {
         }
     }
 
+    fn draw_gutter_markers(&mut self, cx: &mut Cx2d, session: &Session) {
+        let markers = session.document().gutter_markers();
+        if markers.is_empty() {
+            return;
+        }
+        let mut line_index = self.line_start;
+        let mut origin_y = session.layout().line(self.line_start).y();
+        for element in session
+            .layout()
+            .block_elements(self.line_start, self.line_end)
+        {
+            match element {
+                BlockElement::Line { line, .. } => {
+                    for marker in markers.iter().filter(|marker| marker.contains_line(line_index)) {
+                        self.draw_gutter_marker.color = match marker.ty {
+                            GutterMarkerType::Added => self.token_colors.added_line_marker,
+                            GutterMarkerType::Modified => self.token_colors.modified_line_marker,
+                            GutterMarkerType::Deleted => self.token_colors.deleted_line_marker,
+                        };
+                        let y = self.gutter_rect.pos.y + origin_y * self.cell_size.y;
+                        let rect = if marker.line_count == 0 {
+                            Rect {
+                                pos: dvec2(self.gutter_rect.pos.x - GUTTER_MARKER_WIDTH * 2.0, y - 1.5),
+                                size: dvec2(GUTTER_MARKER_WIDTH * 2.0, 3.0),
+                            }
+                        } else {
+                            Rect {
+                                pos: dvec2(self.gutter_rect.pos.x - GUTTER_MARKER_WIDTH * 2.0, y),
+                                size: dvec2(GUTTER_MARKER_WIDTH, line.height() * self.cell_size.y),
+                            }
+                        };
+                        self.draw_gutter_marker.draw_abs(cx, rect);
+                    }
+                    line_index += 1;
+                    origin_y += line.height();
+                }
+                BlockElement::Widget(widget) => {
+                    origin_y += widget.height;
+                }
+            }
+        }
+    }
+
+    // the line of the gutter marker at `abs`, which sits in the padding left of the line numbers
+    fn pick_gutter_marker(&self, session: &Session, abs: DVec2) -> Option<usize> {
+        if abs.x >= self.gutter_rect.pos.x {
+            return None;
+        }
+        let ((cursor, _), _) = self.pick(session, abs);
+        session
+            .document()
+            .gutter_markers()
+            .iter()
+            .any(|marker| marker.contains_line(cursor.line_index))
+            .then_some(cursor.line_index)
+    }
+
     fn draw_text_layer(&mut self, cx: &mut Cx2d, session: &Session) {
         let highlighted_delimiter_positions = session.highlighted_delimiter_positions();
         let mut line_index = self.line_start;
@@ -1153,9 +1303,13 @@ impl CodeEditor {
         }
     }
 
-    fn draw_decoration_layer(&mut self, cx: &mut Cx2d<'_>, session: &Session) {
+    fn draw_decoration_layer(
+        &mut self,
+        cx: &mut Cx2d<'_>,
+        session: &Session,
+        decorations: &[Decoration],
+    ) {
         let mut active_decoration = None;
-        let decorations = session.document().decorations();
         let mut decorations = decorations.iter();
         while decorations.as_slice().first().map_or(false, |decoration| {
             decoration.end().line_index < self.line_start
@@ -1417,6 +1571,8 @@ impl CodeEditor {
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, DefaultNone)]
 pub enum CodeEditorAction {
     TextDidChange,
+    /// A gutter marker was clicked, on the line with this index.
+    GutterMarkerClicked(usize),
     None
 }
 
@@ -1586,26 +1742,29 @@ impl<'a> DrawDecorationLayer<'a> {
     ) {
         let start_x = mem::take(&mut self.active_decoration.as_mut().unwrap().start_x);
         let (x, y) = line.grid_to_normalized_position(row_index, column_index);
-        self.code_editor.draw_decoration.color =
-            match self.active_decoration.as_mut().unwrap().decoration.ty {
-                DecorationType::Warning => self.code_editor.token_colors.warning_decoration,
-                DecorationType::Error => self.code_editor.token_colors.error_decoration,
-            };
-
-        self.code_editor.draw_decoration.draw_abs(
-            cx,
-            Rect {
-                pos: DVec2 {
-                    x: start_x,
-                    y: origin_y + y,
-                } * self.code_editor.cell_size
-                    + self.code_editor.viewport_rect.pos,
-                size: DVec2 {
-                    x: x - start_x,
-                    y: line.scale(),
-                } * self.code_editor.cell_size,
-            },
-        );
+        let rect = Rect {
+            pos: DVec2 {
+                x: start_x,
+                y: origin_y + y,
+            } * self.code_editor.cell_size
+                + self.code_editor.viewport_rect.pos,
+            size: DVec2 {
+                x: x - start_x,
+                y: line.scale(),
+            } * self.code_editor.cell_size,
+        };
+        let color = match self.active_decoration.as_mut().unwrap().decoration.ty {
+            DecorationType::Warning => self.code_editor.token_colors.warning_decoration,
+            DecorationType::Error => self.code_editor.token_colors.error_decoration,
+            DecorationType::SearchMatch => {
+                self.code_editor.draw_search_match.color =
+                    self.code_editor.token_colors.search_match_decoration;
+                self.code_editor.draw_search_match.draw_abs(cx, rect);
+                return;
+            }
+        };
+        self.code_editor.draw_decoration.color = color;
+        self.code_editor.draw_decoration.draw_abs(cx, rect);
     }
 }
 
@@ -1900,6 +2059,14 @@ struct TokenColors {
     error_decoration: Vec4,
     #[live]
     warning_decoration: Vec4,
+    #[live]
+    search_match_decoration: Vec4,
+    #[live]
+    added_line_marker: Vec4,
+    #[live]
+    modified_line_marker: Vec4,
+    #[live]
+    deleted_line_marker: Vec4,
 }
 
 #[derive(Live, LiveHook, LiveRegister)]
@@ -1919,6 +2086,14 @@ struct DrawDecoration {
     color: Vec4,
 }
 
+#[derive(Live, LiveHook, LiveRegister)]
+struct DrawSearchMatch {
+    #[deref]
+    draw_super: DrawQuad,
+    #[live]
+    color: Vec4,
+}
+
 #[derive(Live, LiveHook, LiveRegister)]
 #[repr(C)]
 struct DrawSelection {
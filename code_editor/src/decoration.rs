@@ -7,6 +7,7 @@ use {
 pub enum DecorationType {
     Error,
     Warning,
+    SearchMatch,
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -59,6 +60,32 @@ impl Decoration {
     }
 }
 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum GutterMarkerType {
+    Added,
+    Modified,
+    Deleted,
+}
+
+/// A marker in the gutter next to the `line_count` lines from `start_line_index`, like a change
+/// against version control. Markers for deleted lines span no lines, and sit at the top of
+/// `start_line_index`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct GutterMarker {
+    pub start_line_index: usize,
+    pub line_count: usize,
+    pub ty: GutterMarkerType,
+}
+
+impl GutterMarker {
+    pub fn contains_line(self, line_index: usize) -> bool {
+        if self.line_count == 0 {
+            return line_index == self.start_line_index;
+        }
+        self.start_line_index <= line_index && line_index < self.start_line_index + self.line_count
+    }
+}
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct DecorationSet {
     decorations: Vec<Decoration>,
@@ -94,6 +121,10 @@ impl DecorationSet {
         self.decorations.clear();
     }
 
+    pub fn retain(&mut self, f: impl FnMut(&Decoration) -> bool) {
+        self.decorations.retain(f);
+    }
+
     pub fn apply_edit(&mut self, edit: &Edit) {
         for decoration in &mut self.decorations {
             *decoration = decoration.apply_edit(edit);
@@ -1,7 +1,7 @@
 use {
     crate::{
         char::CharExt,
-        decoration::{Decoration, DecorationSet},
+        decoration::{Decoration, DecorationSet, GutterMarker},
         history::{EditKind, History},
         inlays::{BlockInlay, InlineInlay},
         iter::IteratorExt,
@@ -20,7 +20,7 @@ use {
         iter,
         ops::Range,
         rc::Rc,
-        sync::mpsc::Sender,
+        sync::{mpsc, mpsc::Receiver, mpsc::Sender},
     },
 };
 
@@ -29,6 +29,12 @@ pub struct Document(Rc<DocumentInner>);
 
 impl Document {
     pub fn new(text: Text, decorations: DecorationSet) -> Self {
+        let tokenizer = Tokenizer::new(text.as_lines().len());
+        Self::with_tokenizer(text, decorations, tokenizer)
+    }
+
+    /// Creates a document that is highlighted by `tokenizer`, see `LanguageRegistry` for picking one.
+    pub fn with_tokenizer(text: Text, decorations: DecorationSet, tokenizer: Tokenizer) -> Self {
         let line_count = text.as_lines().len();
         let tokens: Vec<_> = (0..line_count)
             .map(|line| tokenize(&text.as_lines()[line]).collect::<Vec<_>>())
@@ -41,9 +47,11 @@ impl Document {
                 inline_inlays: (0..line_count).map(|_| Vec::new()).collect(),
                 block_inlays: Vec::new(),
             }),
-            tokenizer: RefCell::new(Tokenizer::new(line_count)),
+            tokenizer: RefCell::new(tokenizer),
             decorations: RefCell::new(decorations),
+            gutter_markers: RefCell::new(Vec::new()),
             edit_senders: RefCell::new(HashMap::new()),
+            edit_listeners: RefCell::new(Vec::new()),
         }));
         inner.update_indent_state();
         inner.0.tokenizer.borrow_mut().update(
@@ -67,6 +75,10 @@ impl Document {
         })
     }
 
+    pub fn gutter_markers(&self) -> Ref<'_, [GutterMarker]> {
+        Ref::map(self.0.gutter_markers.borrow(), |markers| markers.as_slice())
+    }
+
     pub fn edit_selections(
         &self,
         session_id: SessionId,
@@ -174,6 +186,43 @@ impl Document {
         self.update_after_edit(origin_id, None, &edits);
     }
 
+    /// Replaces each `(start, length)` range with its text, as a single undo group. The ranges have
+    /// to be sorted and may not overlap.
+    pub fn replace(
+        &self,
+        session_id: SessionId,
+        selections: &SelectionSet,
+        replacements: &[(Position, Length, Text)],
+    ) {
+        let mut history = self.0.history.borrow_mut();
+        history.force_new_group();
+        history.push_or_extend_group(session_id, EditKind::Other, selections);
+        let mut edits = Vec::new();
+        // back to front, so the positions of the ranges that are still to go don't move
+        for (start, length, text) in replacements.iter().rev() {
+            let mut replacement_edits = Vec::new();
+            if *length != Length::zero() {
+                replacement_edits.push(Edit {
+                    change: Change::Delete(*start, *length),
+                    drift: Drift::Before,
+                });
+            }
+            if !text.is_empty() {
+                replacement_edits.push(Edit {
+                    change: Change::Insert(*start, text.clone()),
+                    drift: Drift::Before,
+                });
+            }
+            for edit in replacement_edits {
+                edits.push(edit.clone());
+                history.apply_edit(edit);
+            }
+        }
+        history.force_new_group();
+        drop(history);
+        self.update_after_edit(session_id, None, &edits);
+    }
+
     pub fn add_decoration(&mut self, decoration: Decoration) {
         self.0.decorations.borrow_mut().add_decoration(decoration);
     }
@@ -182,6 +231,25 @@ impl Document {
         self.0.decorations.borrow_mut().clear()
     }
 
+    pub fn retain_decorations(&mut self, f: impl FnMut(&Decoration) -> bool) {
+        self.0.decorations.borrow_mut().retain(f)
+    }
+
+    /// Replaces the gutter markers. Unlike decorations they don't move with edits, so whoever sets
+    /// them has to set them again after the text changes.
+    pub fn set_gutter_markers(&mut self, markers: Vec<GutterMarker>) {
+        *self.0.gutter_markers.borrow_mut() = markers;
+    }
+
+    /// Returns a receiver for the edits made to this document from now on, for keeping something
+    /// outside the editor, like a language server, in sync with it. Dropping the receiver stops the
+    /// edits from being sent.
+    pub fn add_edit_listener(&self) -> Receiver<Vec<Edit>> {
+        let (edit_sender, edit_receiver) = mpsc::channel();
+        self.0.edit_listeners.borrow_mut().push(edit_sender);
+        edit_receiver
+    }
+
     pub fn add_session(
         &mut self,
         session_id: SessionId,
@@ -407,6 +475,10 @@ impl Document {
                     .unwrap();
             }
         }
+        self.0
+            .edit_listeners
+            .borrow_mut()
+            .retain(|edit_listener| edit_listener.send(edits.to_vec()).is_ok());
     }
 
     fn apply_change_to_tokens(&self, change: &Change) {
@@ -684,7 +756,9 @@ struct DocumentInner {
     layout: RefCell<DocumentLayout>,
     tokenizer: RefCell<Tokenizer>,
     decorations: RefCell<DecorationSet>,
+    gutter_markers: RefCell<Vec<GutterMarker>>,
     edit_senders: RefCell<HashMap<SessionId, Sender<(Option<SelectionSet>, Vec<Edit>)>>>,
+    edit_listeners: RefCell<Vec<Sender<Vec<Edit>>>>,
 }
 
 fn tokenize(text: &str) -> impl Iterator<Item = Token> + '_ {
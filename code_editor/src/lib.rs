@@ -9,6 +9,8 @@ pub mod history;
 pub mod inlays;
 pub mod iter;
 pub mod layout;
+pub mod regex;
+pub mod search;
 pub mod selection;
 pub mod session;
 pub mod settings;
@@ -21,7 +23,8 @@ pub mod wrap;
 
 pub use self::{
     code_editor::CodeEditor, document::Document, history::History, layout::Line,
-    selection::Selection, session::Session, settings::Settings, token::Token, tokenizer::Tokenizer,
+    search::SearchQuery, selection::Selection, session::Session, settings::Settings, token::Token,
+    tokenizer::{LanguageRegistry, LanguageTokenizer, Tokenizer},
 };
 
 pub fn live_design(cx: &mut Cx) {
@@ -0,0 +1,631 @@
+use std::{cell::Cell, error, fmt, ops::Range};
+
+/// A backtracking regular expression matcher for searching a single line.
+///
+/// Supports literals, `.`, classes like `[a-z]` and `[^0-9]`, the escapes `\d \w \s \b` and their
+/// negations, anchors, capturing and `(?:...)` groups, alternation, and greedy or lazy `* + ? {m,n}`
+/// repetition.
+#[derive(Clone, Debug)]
+pub struct Regex {
+    node: Node,
+    group_count: usize,
+    ignore_case: bool,
+}
+
+impl Regex {
+    pub fn new(pattern: &str, ignore_case: bool) -> Result<Self, RegexError> {
+        let mut parser = Parser {
+            chars: pattern.chars().collect(),
+            index: 0,
+            group_count: 1,
+        };
+        let node = parser.parse_alternation()?;
+        if parser.index < parser.chars.len() {
+            return Err(parser.error("unmatched `)`"));
+        }
+        Ok(Self {
+            node,
+            group_count: parser.group_count,
+            ignore_case,
+        })
+    }
+
+    /// Returns a regex that matches `string` literally.
+    pub fn literal(string: &str, ignore_case: bool) -> Self {
+        Self {
+            node: Node::Concat(string.chars().map(Node::Char).collect()),
+            group_count: 1,
+            ignore_case,
+        }
+    }
+
+    /// The number of groups, counting the whole match as group 0.
+    pub fn group_count(&self) -> usize {
+        self.group_count
+    }
+
+    /// Finds the leftmost match that starts at or after byte `start`. Gives up and returns `None`
+    /// once `budget` is used up.
+    pub fn find_at(&self, text: &str, start: usize, budget: &StepBudget) -> Option<Captures> {
+        let mut start = start;
+        loop {
+            if let Some(captures) = self.match_at(text, start, budget) {
+                return Some(captures);
+            }
+            if budget.is_exhausted() {
+                return None;
+            }
+            start += text[start..].chars().next()?.len_utf8();
+        }
+    }
+
+    fn match_at(&self, text: &str, start: usize, budget: &StepBudget) -> Option<Captures> {
+        let mut groups = vec![None; self.group_count];
+        let matcher = Matcher {
+            text,
+            ignore_case: self.ignore_case,
+            budget,
+        };
+        let mut end = None;
+        if matcher.match_node(&self.node, start, &mut groups, &mut |pos, _| {
+            end = Some(pos);
+            true
+        }) {
+            groups[0] = Some(start..end.unwrap());
+            Some(Captures { groups })
+        } else {
+            None
+        }
+    }
+}
+
+/// The number of steps the matcher may take. A search shares one budget between all its matches,
+/// so a pattern that backtracks badly gives up instead of hanging the editor.
+#[derive(Clone, Debug)]
+pub struct StepBudget {
+    steps_left: Cell<usize>,
+}
+
+impl StepBudget {
+    pub fn new(steps: usize) -> Self {
+        Self {
+            steps_left: Cell::new(steps),
+        }
+    }
+
+    /// A budget that is plenty for any sane pattern on `len` bytes of text.
+    pub fn for_text_len(len: usize) -> Self {
+        Self::new(MIN_STEPS.saturating_add(len.saturating_mul(STEPS_PER_BYTE)))
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.steps_left.get() == 0
+    }
+
+    fn take_step(&self) -> bool {
+        match self.steps_left.get().checked_sub(1) {
+            Some(steps_left) => {
+                self.steps_left.set(steps_left);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Captures {
+    groups: Vec<Option<Range<usize>>>,
+}
+
+impl Captures {
+    /// The byte range of group `index` in the matched text, if it took part in the match.
+    pub fn get(&self, index: usize) -> Option<Range<usize>> {
+        self.groups.get(index).cloned().flatten()
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.get(0).unwrap()
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegexError {
+    pub message: String,
+    pub index: usize,
+}
+
+impl fmt::Display for RegexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.message, self.index)
+    }
+}
+
+impl error::Error for RegexError {}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Char(char),
+    Any,
+    Class(Class),
+    LineStart,
+    LineEnd,
+    WordBoundary { negated: bool },
+    Group { node: Box<Node>, index: Option<usize> },
+    Concat(Vec<Node>),
+    Alternate(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+}
+
+impl Node {
+    fn is_single_char(&self) -> bool {
+        matches!(self, Node::Char(_) | Node::Any | Node::Class(_))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Class {
+    items: Vec<ClassItem>,
+    negated: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ClassItem {
+    Range(char, char),
+    Digit { negated: bool },
+    Word { negated: bool },
+    Space { negated: bool },
+}
+
+impl ClassItem {
+    fn matches(self, char: char) -> bool {
+        match self {
+            ClassItem::Range(start, end) => start <= char && char <= end,
+            ClassItem::Digit { negated } => char.is_ascii_digit() != negated,
+            ClassItem::Word { negated } => is_word_char(char) != negated,
+            ClassItem::Space { negated } => char.is_whitespace() != negated,
+        }
+    }
+}
+
+impl Class {
+    fn matches(&self, char: char, ignore_case: bool) -> bool {
+        let matches = |char| self.items.iter().any(|item| item.matches(char));
+        let is_match = matches(char)
+            || ignore_case
+                && (char.to_lowercase().any(matches) || char.to_uppercase().any(matches));
+        is_match != self.negated
+    }
+}
+
+pub fn is_word_char(char: char) -> bool {
+    char.is_alphanumeric() || char == '_'
+}
+
+// a search gets this many steps plus some per byte it searches
+const MIN_STEPS: usize = 1 << 20;
+const STEPS_PER_BYTE: usize = 256;
+
+struct Matcher<'a> {
+    text: &'a str,
+    ignore_case: bool,
+    budget: &'a StepBudget,
+}
+
+type Groups = Vec<Option<Range<usize>>>;
+
+impl<'a> Matcher<'a> {
+    // matches `node` at `pos` and calls `next` with the end of every way it matches, until `next`
+    // accepts one
+    fn match_node(
+        &self,
+        node: &Node,
+        pos: usize,
+        groups: &mut Groups,
+        next: &mut dyn FnMut(usize, &mut Groups) -> bool,
+    ) -> bool {
+        if !self.budget.take_step() {
+            return false;
+        }
+        match node {
+            Node::Char(_) | Node::Any | Node::Class(_) => match self.match_char(node, pos) {
+                Some(end) => next(end, groups),
+                None => false,
+            },
+            Node::LineStart => pos == 0 && next(pos, groups),
+            Node::LineEnd => pos == self.text.len() && next(pos, groups),
+            Node::WordBoundary { negated } => {
+                let before = self.text[..pos].chars().next_back().is_some_and(is_word_char);
+                let after = self.text[pos..].chars().next().is_some_and(is_word_char);
+                (before != after) != *negated && next(pos, groups)
+            }
+            Node::Group { node, index: None } => self.match_node(node, pos, groups, next),
+            Node::Group {
+                node,
+                index: Some(index),
+            } => {
+                let index = *index;
+                self.match_node(node, pos, groups, &mut |end, groups| {
+                    let saved = groups[index].replace(pos..end);
+                    if next(end, groups) {
+                        return true;
+                    }
+                    groups[index] = saved;
+                    false
+                })
+            }
+            Node::Concat(nodes) => self.match_concat(nodes, pos, groups, next),
+            Node::Alternate(nodes) => nodes
+                .iter()
+                .any(|node| self.match_node(node, pos, groups, next)),
+            Node::Repeat {
+                node,
+                min,
+                max,
+                greedy,
+            } if node.is_single_char() => {
+                self.match_char_repeat(node, *min, *max, *greedy, pos, groups, next)
+            }
+            Node::Repeat {
+                node,
+                min,
+                max,
+                greedy,
+            } => self.match_repeat(node, *min, *max, *greedy, 0, pos, groups, next),
+        }
+    }
+
+    fn match_char(&self, node: &Node, pos: usize) -> Option<usize> {
+        let char = self.text[pos..].chars().next()?;
+        let is_match = match node {
+            Node::Char(expected) => {
+                char == *expected
+                    || self.ignore_case && char.to_lowercase().eq(expected.to_lowercase())
+            }
+            Node::Any => true,
+            Node::Class(class) => class.matches(char, self.ignore_case),
+            _ => unreachable!(),
+        };
+        is_match.then_some(pos + char.len_utf8())
+    }
+
+    fn match_concat(
+        &self,
+        nodes: &[Node],
+        pos: usize,
+        groups: &mut Groups,
+        next: &mut dyn FnMut(usize, &mut Groups) -> bool,
+    ) -> bool {
+        match nodes.split_first() {
+            None => next(pos, groups),
+            Some((first, rest)) => self.match_node(first, pos, groups, &mut |pos, groups| {
+                self.match_concat(rest, pos, groups, next)
+            }),
+        }
+    }
+
+    // repeats of a single char are matched in a loop instead of recursing once per char
+    #[allow(clippy::too_many_arguments)]
+    fn match_char_repeat(
+        &self,
+        node: &Node,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+        pos: usize,
+        groups: &mut Groups,
+        next: &mut dyn FnMut(usize, &mut Groups) -> bool,
+    ) -> bool {
+        let mut ends = vec![pos];
+        while max.is_none_or(|max| ends.len() <= max) {
+            match self.match_char(node, *ends.last().unwrap()) {
+                Some(end) => ends.push(end),
+                None => break,
+            }
+        }
+        if ends.len() <= min {
+            return false;
+        }
+        let ends = &ends[min..];
+        if greedy {
+            ends.iter().rev().any(|&end| next(end, groups))
+        } else {
+            ends.iter().any(|&end| next(end, groups))
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn match_repeat(
+        &self,
+        node: &Node,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+        count: usize,
+        pos: usize,
+        groups: &mut Groups,
+        next: &mut dyn FnMut(usize, &mut Groups) -> bool,
+    ) -> bool {
+        let can_stop = count >= min;
+        let can_continue = max.is_none_or(|max| count < max);
+        if can_stop && !greedy && next(pos, groups) {
+            return true;
+        }
+        if can_continue
+            && self.match_node(node, pos, groups, &mut |end, groups| {
+                // an iteration that matched nothing would loop forever
+                if end == pos && can_stop {
+                    return false;
+                }
+                self.match_repeat(node, min, max, greedy, count + 1, end, groups, next)
+            })
+        {
+            return true;
+        }
+        can_stop && greedy && next(pos, groups)
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    index: usize,
+    group_count: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn skip_if(&mut self, char: char) -> bool {
+        if self.peek() == Some(char) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, message: &str) -> RegexError {
+        RegexError {
+            message: message.to_string(),
+            index: self.index,
+        }
+    }
+
+    fn parse_alternation(&mut self) -> Result<Node, RegexError> {
+        let mut nodes = vec![self.parse_concat()?];
+        while self.skip_if('|') {
+            nodes.push(self.parse_concat()?);
+        }
+        Ok(if nodes.len() == 1 {
+            nodes.pop().unwrap()
+        } else {
+            Node::Alternate(nodes)
+        })
+    }
+
+    fn parse_concat(&mut self) -> Result<Node, RegexError> {
+        let mut nodes = Vec::new();
+        while let Some(char) = self.peek() {
+            if char == '|' || char == ')' {
+                break;
+            }
+            let node = self.parse_atom()?;
+            nodes.push(self.parse_repeat(node)?);
+        }
+        Ok(if nodes.len() == 1 {
+            nodes.pop().unwrap()
+        } else {
+            Node::Concat(nodes)
+        })
+    }
+
+    fn parse_repeat(&mut self, node: Node) -> Result<Node, RegexError> {
+        let mut node = node;
+        loop {
+            let start = self.index;
+            let (min, max) = match self.peek() {
+                Some('*') => (0, None),
+                Some('+') => (1, None),
+                Some('?') => (0, Some(1)),
+                Some('{') => match self.parse_bounds() {
+                    Some(bounds) => bounds,
+                    // not a repetition, so the brace is a literal
+                    None => {
+                        self.index = start;
+                        return Ok(node);
+                    }
+                },
+                _ => return Ok(node),
+            };
+            if self.index == start {
+                self.index += 1;
+            }
+            if matches!(
+                node,
+                Node::LineStart | Node::LineEnd | Node::WordBoundary { .. } | Node::Repeat { .. }
+            ) {
+                self.index = start;
+                return Err(self.error("nothing to repeat"));
+            }
+            if max.is_some_and(|max| max < min) {
+                self.index = start;
+                return Err(self.error("repetition bounds are out of order"));
+            }
+            let greedy = !self.skip_if('?');
+            node = Node::Repeat {
+                node: Box::new(node),
+                min,
+                max,
+                greedy,
+            };
+        }
+    }
+
+    // parses `{m}`, `{m,}` or `{m,n}`
+    fn parse_bounds(&mut self) -> Option<(usize, Option<usize>)> {
+        debug_assert!(self.peek() == Some('{'));
+        self.index += 1;
+        let min = self.parse_number()?;
+        let max = if self.skip_if(',') {
+            if self.peek() == Some('}') {
+                None
+            } else {
+                Some(self.parse_number()?)
+            }
+        } else {
+            Some(min)
+        };
+        if self.peek() != Some('}') {
+            return None;
+        }
+        self.index += 1;
+        Some((min, max))
+    }
+
+    fn parse_number(&mut self) -> Option<usize> {
+        let start = self.index;
+        while self.peek().is_some_and(|char| char.is_ascii_digit()) {
+            self.index += 1;
+        }
+        self.chars[start..self.index]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .ok()
+    }
+
+    fn parse_atom(&mut self) -> Result<Node, RegexError> {
+        let char = self.peek().unwrap();
+        self.index += 1;
+        Ok(match char {
+            '.' => Node::Any,
+            '^' => Node::LineStart,
+            '$' => Node::LineEnd,
+            '(' => self.parse_group()?,
+            '[' => Node::Class(self.parse_class()?),
+            '\\' => match self.parse_escape()? {
+                Escape::Char(char) => Node::Char(char),
+                Escape::Item(item) => Node::Class(Class {
+                    items: vec![item],
+                    negated: false,
+                }),
+                Escape::WordBoundary { negated } => Node::WordBoundary { negated },
+            },
+            '*' | '+' | '?' => {
+                self.index -= 1;
+                return Err(self.error("nothing to repeat"));
+            }
+            char => Node::Char(char),
+        })
+    }
+
+    fn parse_group(&mut self) -> Result<Node, RegexError> {
+        let index = if self.skip_if('?') {
+            if !self.skip_if(':') {
+                return Err(self.error("only `(?:` groups are supported"));
+            }
+            None
+        } else {
+            self.group_count += 1;
+            Some(self.group_count - 1)
+        };
+        let node = self.parse_alternation()?;
+        if !self.skip_if(')') {
+            return Err(self.error("unclosed group"));
+        }
+        Ok(Node::Group {
+            node: Box::new(node),
+            index,
+        })
+    }
+
+    fn parse_class(&mut self) -> Result<Class, RegexError> {
+        let negated = self.skip_if('^');
+        let mut items = Vec::new();
+        let mut is_first = true;
+        loop {
+            let char = match self.peek() {
+                Some(']') if !is_first => {
+                    self.index += 1;
+                    break;
+                }
+                Some(char) => char,
+                None => return Err(self.error("unclosed character class")),
+            };
+            is_first = false;
+            self.index += 1;
+            let start = if char == '\\' {
+                match self.parse_escape()? {
+                    Escape::Char(char) => char,
+                    Escape::Item(item) => {
+                        items.push(item);
+                        continue;
+                    }
+                    Escape::WordBoundary { .. } => 'b',
+                }
+            } else {
+                char
+            };
+            let end = if self.peek() == Some('-')
+                && self.chars.get(self.index + 1).is_some_and(|&char| char != ']')
+            {
+                self.index += 1;
+                let char = self.peek().unwrap();
+                self.index += 1;
+                if char == '\\' {
+                    match self.parse_escape()? {
+                        Escape::Char(char) => char,
+                        _ => return Err(self.error("invalid class range")),
+                    }
+                } else {
+                    char
+                }
+            } else {
+                start
+            };
+            if end < start {
+                return Err(self.error("invalid class range"));
+            }
+            items.push(ClassItem::Range(start, end));
+        }
+        Ok(Class { items, negated })
+    }
+
+    fn parse_escape(&mut self) -> Result<Escape, RegexError> {
+        let Some(char) = self.peek() else {
+            return Err(self.error("trailing backslash"));
+        };
+        self.index += 1;
+        Ok(match char {
+            'd' => Escape::Item(ClassItem::Digit { negated: false }),
+            'D' => Escape::Item(ClassItem::Digit { negated: true }),
+            'w' => Escape::Item(ClassItem::Word { negated: false }),
+            'W' => Escape::Item(ClassItem::Word { negated: true }),
+            's' => Escape::Item(ClassItem::Space { negated: false }),
+            'S' => Escape::Item(ClassItem::Space { negated: true }),
+            'b' => Escape::WordBoundary { negated: false },
+            'B' => Escape::WordBoundary { negated: true },
+            't' => Escape::Char('\t'),
+            'n' => Escape::Char('\n'),
+            'r' => Escape::Char('\r'),
+            char if char.is_alphanumeric() => {
+                self.index -= 1;
+                return Err(self.error("unknown escape"));
+            }
+            char => Escape::Char(char),
+        })
+    }
+}
+
+enum Escape {
+    Char(char),
+    Item(ClassItem),
+    WordBoundary { negated: bool },
+}
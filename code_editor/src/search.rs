@@ -0,0 +1,223 @@
+use {
+    crate::{
+        regex::{is_word_char, Captures, Regex, RegexError, StepBudget},
+        text::{Position, Text},
+    },
+    std::{error, fmt, ops::Range},
+};
+
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SearchQuery {
+    pub pattern: String,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    pub regex: bool,
+}
+
+impl SearchQuery {
+    pub fn literal(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn regex(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            regex: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_case_sensitive(self, case_sensitive: bool) -> Self {
+        Self {
+            case_sensitive,
+            ..self
+        }
+    }
+
+    pub fn with_whole_word(self, whole_word: bool) -> Self {
+        Self { whole_word, ..self }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SearchError {
+    Regex(RegexError),
+    /// The search ran out of steps before it got through the text.
+    TooComplex,
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchError::Regex(error) => error.fmt(f),
+            SearchError::TooComplex => write!(f, "pattern too complex"),
+        }
+    }
+}
+
+impl error::Error for SearchError {}
+
+impl From<RegexError> for SearchError {
+    fn from(error: RegexError) -> Self {
+        SearchError::Regex(error)
+    }
+}
+
+/// A compiled `SearchQuery`. Matches never span lines.
+///
+/// Searching takes steps from a budget, set with `set_budget` before searching a text. Once it
+/// runs out nothing more matches and `is_too_complex` returns true.
+#[derive(Clone, Debug)]
+pub struct Searcher {
+    regex: Regex,
+    whole_word: bool,
+    expand_replacements: bool,
+    budget: StepBudget,
+}
+
+impl Searcher {
+    pub fn new(query: &SearchQuery) -> Result<Self, RegexError> {
+        let ignore_case = !query.case_sensitive;
+        Ok(Self {
+            regex: if query.regex {
+                Regex::new(&query.pattern, ignore_case)?
+            } else {
+                Regex::literal(&query.pattern, ignore_case)
+            },
+            whole_word: query.whole_word,
+            expand_replacements: query.regex,
+            budget: StepBudget::for_text_len(0),
+        })
+    }
+
+    /// Gives the searcher a fresh budget for searching `len` bytes.
+    pub fn set_budget(&mut self, len: usize) {
+        self.budget = StepBudget::for_text_len(len);
+    }
+
+    pub fn is_too_complex(&self) -> bool {
+        self.budget.is_exhausted()
+    }
+
+    /// Finds the first match in `line` that starts at or after byte `start`.
+    pub fn find_in_line(&self, line: &str, start: usize) -> Option<Captures> {
+        let mut start = start;
+        loop {
+            let captures = self.regex.find_at(line, start, &self.budget)?;
+            let range = captures.range();
+            if !range.is_empty() && (!self.whole_word || is_whole_word(line, range.clone())) {
+                return Some(captures);
+            }
+            start = range.start + line[range.start..].chars().next()?.len_utf8();
+        }
+    }
+
+    /// Calls `f` with the byte range of every match in `line`, left to right.
+    pub fn for_each_in_line(&self, line: &str, mut f: impl FnMut(Range<usize>)) {
+        let mut start = 0;
+        while let Some(captures) = self.find_in_line(line, start) {
+            let range = captures.range();
+            start = range.end;
+            f(range);
+        }
+    }
+
+    /// Returns the start and end of every match in `text`, with a budget for all of it.
+    pub fn find_all(&mut self, text: &Text) -> Result<Vec<(Position, Position)>, SearchError> {
+        Ok(self
+            .find_all_in_lines(text.as_lines())?
+            .into_iter()
+            .map(|(line_index, captures)| {
+                let range = captures.range();
+                (
+                    Position {
+                        line_index,
+                        byte_index: range.start,
+                    },
+                    Position {
+                        line_index,
+                        byte_index: range.end,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    /// Returns the line index and captures of every match in `lines`, with a budget for all of
+    /// them. The captures are what `expand_replacement` takes.
+    pub fn find_all_in_lines(
+        &mut self,
+        lines: &[String],
+    ) -> Result<Vec<(usize, Captures)>, SearchError> {
+        self.set_budget(lines.iter().map(|line| line.len() + 1).sum());
+        let mut matches = Vec::new();
+        for (line_index, line) in lines.iter().enumerate() {
+            let mut start = 0;
+            while let Some(captures) = self.find_in_line(line, start) {
+                start = captures.range().end;
+                matches.push((line_index, captures));
+            }
+        }
+        if self.is_too_complex() {
+            return Err(SearchError::TooComplex);
+        }
+        Ok(matches)
+    }
+
+    /// Returns what the match `captures` in `line` is replaced with.
+    ///
+    /// For regex queries `$0` to `$9` and `${n}` in `template` insert the text of a group, and `$$`
+    /// inserts a dollar sign. Other queries insert `template` as is.
+    pub fn expand_replacement(&self, line: &str, captures: &Captures, template: &str) -> String {
+        if !self.expand_replacements {
+            return template.to_string();
+        }
+        let mut string = String::new();
+        let mut chars = template.char_indices().peekable();
+        while let Some((_, char)) = chars.next() {
+            if char != '$' {
+                string.push(char);
+                continue;
+            }
+            let group = match chars.peek() {
+                Some(&(_, '$')) => {
+                    chars.next();
+                    string.push('$');
+                    continue;
+                }
+                Some(&(_, digit)) if digit.is_ascii_digit() => {
+                    chars.next();
+                    digit.to_digit(10).map(|digit| digit as usize)
+                }
+                Some(&(start, '{')) => {
+                    let rest = &template[start + 1..];
+                    match rest.find('}').and_then(|end| rest[..end].parse::<usize>().ok()) {
+                        Some(group) => {
+                            while chars.next().is_some_and(|(_, char)| char != '}') {}
+                            Some(group)
+                        }
+                        None => None,
+                    }
+                }
+                _ => None,
+            };
+            match group {
+                Some(group) => {
+                    if let Some(range) = captures.get(group) {
+                        string.push_str(&line[range]);
+                    }
+                }
+                None => string.push('$'),
+            }
+        }
+        string
+    }
+}
+
+fn is_whole_word(line: &str, range: Range<usize>) -> bool {
+    !line[..range.start].chars().next_back().is_some_and(is_word_char)
+        && !line[range.end..].chars().next().is_some_and(is_word_char)
+}
@@ -1,9 +1,11 @@
 use {
     crate::{
         char::CharExt,
+        decoration::{Decoration, DecorationSet, DecorationType},
         document::Document,
         history::{EditKind,NewGroup},
         layout::{BlockElement, Layout, WrappedElement},
+        search::{SearchError, SearchQuery, Searcher},
         selection::{Affinity, Cursor, SelectionSet},
         str::StrExt,
         text::{Change, Drift, Edit, Length, Position, Text},
@@ -30,6 +32,7 @@ pub struct Session {
     selection_state: RefCell<SelectionState>,
     wrap_column: Cell<Option<usize>>,
     fold_state: RefCell<FoldState>,
+    search_state: RefCell<Option<SearchState>>,
     edit_receiver: Receiver<(Option<SelectionSet>, Vec<Edit>)>,
 }
 
@@ -63,6 +66,7 @@ impl Session {
                 folded_lines: HashSet::new(),
                 unfolding_lines: HashSet::new(),
             }),
+            search_state: RefCell::new(None),
             edit_receiver,
         };
         for line in 0..line_count {
@@ -784,6 +788,171 @@ impl Session {
         string
     }
 
+    /// Starts searching for `query` and returns the number of matches. The matches are kept up to
+    /// date as the document changes, until `clear_search` is called.
+    pub fn set_search(&self, query: &SearchQuery) -> Result<usize, SearchError> {
+        let mut searcher = Searcher::new(query)?;
+        let mut matches = DecorationSet::new();
+        for (index, (start, end)) in searcher
+            .find_all(&self.document.as_text())?
+            .into_iter()
+            .enumerate()
+        {
+            matches.add_decoration(Decoration::new(index, start, end, DecorationType::SearchMatch));
+        }
+        let match_count = matches.len();
+        *self.search_state.borrow_mut() = Some(SearchState { searcher, matches });
+        Ok(match_count)
+    }
+
+    pub fn clear_search(&self) {
+        *self.search_state.borrow_mut() = None;
+    }
+
+    pub fn search_matches(&self) -> Ref<'_, [Decoration]> {
+        Ref::map(self.search_state.borrow(), |search_state| {
+            search_state
+                .as_ref()
+                .map_or(&[][..], |search_state| search_state.matches.as_decorations())
+        })
+    }
+
+    /// Selects the first match after the last added selection, wrapping around at the end.
+    pub fn find_next(&self) -> bool {
+        let matches = self.search_matches();
+        let Some(selection) = self.last_added_selection() else {
+            return false;
+        };
+        let index = matches.partition_point(|decoration| decoration.start() < selection.end());
+        let Some(&decoration) = matches.get(index).or(matches.first()) else {
+            return false;
+        };
+        drop(matches);
+        self.select_match(decoration);
+        true
+    }
+
+    /// Selects the last match before the last added selection, wrapping around at the start.
+    pub fn find_prev(&self) -> bool {
+        let matches = self.search_matches();
+        let Some(selection) = self.last_added_selection() else {
+            return false;
+        };
+        let index = matches.partition_point(|decoration| decoration.end() <= selection.start());
+        let Some(&decoration) = index
+            .checked_sub(1)
+            .and_then(|index| matches.get(index))
+            .or(matches.last())
+        else {
+            return false;
+        };
+        drop(matches);
+        self.select_match(decoration);
+        true
+    }
+
+    /// Replaces the match under the last added selection, if it is on one, and selects the next
+    /// match. Returns whether anything was replaced.
+    pub fn replace(&mut self, replacement: &str) -> bool {
+        let Some(selection) = self.last_added_selection() else {
+            return false;
+        };
+        let replacement = self.search_state.borrow_mut().as_mut().and_then(|search_state| {
+            let start = selection.start();
+            if start.line_index != selection.end().line_index {
+                return None;
+            }
+            let text = self.document.as_text();
+            let line = &text.as_lines()[start.line_index];
+            search_state.searcher.set_budget(line.len());
+            let captures = search_state.searcher.find_in_line(line, start.byte_index)?;
+            let range = captures.range();
+            (range.start == start.byte_index && range.end == selection.end().byte_index).then(|| {
+                (
+                    start,
+                    selection.length(),
+                    search_state
+                        .searcher
+                        .expand_replacement(line, &captures, replacement)
+                        .into(),
+                )
+            })
+        });
+        let is_replaced = replacement.is_some();
+        if let Some(replacement) = replacement {
+            self.document.replace(
+                self.id,
+                &self.selection_state.borrow().selections,
+                &[replacement],
+            );
+            self.handle_changes();
+        }
+        self.find_next();
+        is_replaced
+    }
+
+    /// Replaces every match as a single undo group and returns how many there were. Nothing is
+    /// replaced if the search runs out of steps on the way.
+    pub fn replace_all(&mut self, replacement: &str) -> usize {
+        let replacements = match &mut *self.search_state.borrow_mut() {
+            Some(search_state) => {
+                let text = self.document.as_text();
+                let lines = text.as_lines();
+                let Ok(matches) = search_state.searcher.find_all_in_lines(lines) else {
+                    return 0;
+                };
+                matches
+                    .into_iter()
+                    .map(|(line_index, captures)| {
+                        let range = captures.range();
+                        (
+                            Position {
+                                line_index,
+                                byte_index: range.start,
+                            },
+                            Length {
+                                line_count: 0,
+                                byte_count: range.len(),
+                            },
+                            search_state
+                                .searcher
+                                .expand_replacement(&lines[line_index], &captures, replacement)
+                                .into(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            }
+            None => return 0,
+        };
+        if !replacements.is_empty() {
+            self.document.replace(
+                self.id,
+                &self.selection_state.borrow().selections,
+                &replacements,
+            );
+            self.handle_changes();
+        }
+        replacements.len()
+    }
+
+    /// Replaces each range from start to end with its text, as a single undo group. The ranges
+    /// have to be sorted and must not overlap.
+    pub fn replace_ranges(&mut self, replacements: &[(Position, Position, Text)]) {
+        if replacements.is_empty() {
+            return;
+        }
+        let replacements: Vec<_> = replacements
+            .iter()
+            .map(|(start, end, text)| (*start, *end - *start, text.clone()))
+            .collect();
+        self.document.replace(
+            self.id,
+            &self.selection_state.borrow().selections,
+            &replacements,
+        );
+        self.handle_changes();
+    }
+
     pub fn undo(&self) -> bool {
         self.selection_state
             .borrow_mut()
@@ -808,6 +977,31 @@ impl Session {
         }
     }
 
+    fn last_added_selection(&self) -> Option<Selection> {
+        let selection_state = self.selection_state.borrow();
+        selection_state
+            .last_added_selection_index
+            .and_then(|index| selection_state.selections.get(index).copied())
+    }
+
+    fn select_match(&self, decoration: Decoration) {
+        let mut selection_state = self.selection_state.borrow_mut();
+        selection_state.mode = SelectionMode::Simple;
+        selection_state.selections.set_selection(Selection {
+            anchor: decoration.start(),
+            cursor: Cursor {
+                position: decoration.end(),
+                affinity: Affinity::Before,
+                preferred_column_index: None,
+            },
+        });
+        selection_state.last_added_selection_index = Some(0);
+        selection_state.injected_char_stack.clear();
+        drop(selection_state);
+        self.update_highlighted_delimiter_positions();
+        self.document().force_new_group();
+    }
+
     fn modify_selections(
         &self,
         reset_anchor: bool,
@@ -908,9 +1102,76 @@ impl Session {
             }
         }
         drop(selection_state);
+        self.update_search_after_edit(edits);
         self.update_highlighted_delimiter_positions();
     }
 
+    // moves the matches along with the edits and searches the lines they touched again
+    fn update_search_after_edit(&self, edits: &[Edit]) {
+        let mut search_state = self.search_state.borrow_mut();
+        let Some(search_state) = search_state.as_mut() else {
+            return;
+        };
+        let mut dirty_lines: Vec<usize> = Vec::new();
+        for edit in edits {
+            search_state.matches.apply_edit(edit);
+            match edit.change {
+                Change::Insert(position, ref text) => {
+                    let line_count = text.length().line_count;
+                    for line in &mut dirty_lines {
+                        if *line > position.line_index {
+                            *line += line_count;
+                        }
+                    }
+                    dirty_lines.extend(position.line_index..=position.line_index + line_count);
+                }
+                Change::Delete(start, length) => {
+                    let line_count = length.line_count;
+                    for line in &mut dirty_lines {
+                        if *line > start.line_index + line_count {
+                            *line -= line_count;
+                        } else if *line > start.line_index {
+                            *line = start.line_index;
+                        }
+                    }
+                    dirty_lines.push(start.line_index);
+                }
+            }
+        }
+        dirty_lines.sort_unstable();
+        dirty_lines.dedup();
+        search_state.matches.retain(|decoration| {
+            dirty_lines
+                .binary_search(&decoration.start().line_index)
+                .is_err()
+        });
+        let text = self.document.as_text();
+        search_state.searcher.set_budget(
+            dirty_lines
+                .iter()
+                .map(|&line_index| text.as_lines()[line_index].len() + 1)
+                .sum(),
+        );
+        for line_index in dirty_lines {
+            search_state
+                .searcher
+                .for_each_in_line(&text.as_lines()[line_index], |range| {
+                    search_state.matches.add_decoration(Decoration::new(
+                        0,
+                        Position {
+                            line_index,
+                            byte_index: range.start,
+                        },
+                        Position {
+                            line_index,
+                            byte_index: range.end,
+                        },
+                        DecorationType::SearchMatch,
+                    ));
+                });
+        }
+    }
+
     fn update_y(&self) {
         let start = self.layout.borrow().y.len();
         let end = self.document.as_text().as_lines().len();
@@ -1011,6 +1272,12 @@ impl Drop for Session {
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct SessionId(usize);
 
+#[derive(Debug)]
+struct SearchState {
+    searcher: Searcher,
+    matches: DecorationSet,
+}
+
 #[derive(Debug)]
 pub struct SessionLayout {
     pub y: Vec<f64>,
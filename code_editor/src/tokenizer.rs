@@ -1,22 +1,74 @@
-use crate::{
-    text::{Change, Text},
-    token::TokenKind,
-    Token,
+use {
+    crate::{
+        text::{Change, Text},
+        token::TokenKind,
+        Token,
+    },
+    std::{collections::HashMap, fmt, rc::Rc},
 };
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub mod json;
+pub mod live;
+pub mod markdown;
+pub mod rust;
+pub mod toml;
+
+/// Lexes the lines of one language.
+///
+/// A tokenizer sees one line at a time. Anything that carries over to the next line, such as
+/// being inside a block comment, goes in `State`. `Tokenizer` caches the state at the start and end
+/// of every line, so after an edit only the lines whose start state changed are lexed again.
+pub trait LanguageTokenizer: fmt::Debug + 'static {
+    type State: Clone + fmt::Debug + Default + Eq;
+
+    /// Lexes the token at the cursor and returns the state after it along with its kind.
+    ///
+    /// The cursor is never at the end of the line and has to move forward.
+    fn next(&self, state: Self::State, cursor: &mut Cursor<'_>) -> (Self::State, TokenKind);
+}
+
+#[derive(Debug)]
 pub struct Tokenizer {
-    state: Vec<Option<(State, State)>>,
+    lines: Box<dyn LineStates>,
 }
 
 impl Tokenizer {
     pub fn new(line_count: usize) -> Self {
+        Self::with_language(rust::RustTokenizer, line_count)
+    }
+
+    pub fn with_language<L: LanguageTokenizer>(language: L, line_count: usize) -> Self {
         Self {
-            state: (0..line_count).map(|_| None).collect(),
+            lines: Box::new(LanguageLineStates {
+                language,
+                state: (0..line_count).map(|_| None).collect(),
+            }),
         }
     }
 
     pub fn apply_change(&mut self, change: &Change) {
+        self.lines.apply_change(change);
+    }
+
+    pub fn update(&mut self, text: &Text, tokens: &mut [Vec<Token>]) {
+        self.lines.update(text, tokens);
+    }
+}
+
+trait LineStates: fmt::Debug {
+    fn apply_change(&mut self, change: &Change);
+
+    fn update(&mut self, text: &Text, tokens: &mut [Vec<Token>]);
+}
+
+#[derive(Debug)]
+struct LanguageLineStates<L: LanguageTokenizer> {
+    language: L,
+    state: Vec<Option<(L::State, L::State)>>,
+}
+
+impl<L: LanguageTokenizer> LineStates for LanguageLineStates<L> {
+    fn apply_change(&mut self, change: &Change) {
         match *change {
             Change::Insert(point, ref text) => {
                 self.state[point.line_index] = None;
@@ -38,26 +90,29 @@ impl Tokenizer {
         }
     }
 
-    pub fn update(&mut self, text: &Text, tokens: &mut [Vec<Token>]) {
-        let mut state = State::default();
+    fn update(&mut self, text: &Text, tokens: &mut [Vec<Token>]) {
+        let mut state = L::State::default();
         for line in 0..text.as_lines().len() {
-            match self.state[line] {
-                Some((start_state, end_state)) if state == start_state => {
-                    state = end_state;
+            match &self.state[line] {
+                Some((start_state, end_state)) if state == *start_state => {
+                    state = end_state.clone();
                 }
                 _ => {
-                    let start_state = state;
+                    let start_state = state.clone();
                     let mut new_tokens = Vec::new();
                     let mut cursor = Cursor::new(&text.as_lines()[line]);
-                    loop {
-                        let (next_state, token) = state.next(&mut cursor);
+                    while cursor.peek(0) != '\0' {
+                        let start = cursor.index;
+                        let (next_state, kind) = self.language.next(state, &mut cursor);
                         state = next_state;
-                        match token {
-                            Some(token) => new_tokens.push(token),
-                            None => break,
-                        }
+                        let end = cursor.index;
+                        assert!(start < end);
+                        new_tokens.push(Token {
+                            len: end - start,
+                            kind,
+                        });
                     }
-                    self.state[line] = Some((start_state, state));
+                    self.state[line] = Some((start_state, state.clone()));
                     tokens[line] = new_tokens;
                 }
             }
@@ -65,407 +120,80 @@ impl Tokenizer {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-pub enum State {
-    Initial(InitialState),
-    BlockCommentTail(BlockCommentTailState),
-    DoubleQuotedStringTail(DoubleQuotedStringTailState),
-    RawDoubleQuotedStringTail(RawDoubleQuotedStringTailState),
+/// Picks a tokenizer for a file by its extension.
+#[derive(Clone)]
+pub struct LanguageRegistry {
+    languages: HashMap<String, Rc<dyn Fn(usize) -> Tokenizer>>,
 }
 
-impl Default for State {
-    fn default() -> State {
-        State::Initial(InitialState)
-    }
-}
-
-impl State {
-    pub fn next(self, cursor: &mut Cursor) -> (State, Option<Token>) {
-        if cursor.peek(0) == '\0' {
-            return (self, None);
-        }
-        let start = cursor.index;
-        let (next_state, kind) = match self {
-            State::Initial(state) => state.next(cursor),
-            State::BlockCommentTail(state) => state.next(cursor),
-            State::DoubleQuotedStringTail(state) => state.next(cursor),
-            State::RawDoubleQuotedStringTail(state) => state.next(cursor),
-        };
-        let end = cursor.index;
-        assert!(start < end);
-        (
-            next_state,
-            Some(Token {
-                len: end - start,
-                kind,
-            }),
-        )
-    }
-}
-
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-pub struct InitialState;
-
-impl InitialState {
-    fn next(self, cursor: &mut Cursor<'_>) -> (State, TokenKind) {
-        match (cursor.peek(0), cursor.peek(1), cursor.peek(2)) {
-            ('r', '#', '"') | ('r', '#', '#') => self.raw_string(cursor),
-            ('b', 'r', '"') | ('b', 'r', '#') => self.raw_byte_string(cursor),
-            ('/', '/', _) => self.line_comment(cursor),
-            ('/', '*', _) => self.block_comment(cursor),
-            ('b', '\'', _) => self.byte(cursor),
-            ('b', '"', _) => self.byte_string(cursor),
-            ('!', '=', _)
-            | ('%', '=', _)
-            | ('&', '&', _)
-            | ('&', '=', _)
-            | ('*', '=', _)
-            | ('+', '=', _)
-            | ('-', '=', _)
-            | ('-', '>', _)
-            | ('.', '.', _)
-            | ('/', '=', _)
-            | (':', ':', _)
-            | ('<', '<', _)
-            | ('<', '=', _)
-            | ('=', '=', _)
-            | ('=', '>', _)
-            | ('>', '=', _)
-            | ('>', '>', _)
-            | ('^', '=', _)
-            | ('|', '=', _)
-            | ('|', '|', _) => {
-                cursor.skip(2);
-                (State::Initial(InitialState), TokenKind::Punctuator)
-            }
-            ('\'', _, _) => self.char_or_lifetime(cursor),
-            ('"', _, _) => self.string(cursor),
-            ('(', _, _) => {
-                cursor.skip(1);
-                (State::Initial(InitialState), TokenKind::Delimiter)
-            }
-            (')', _, _) => {
-                cursor.skip(1);
-                (State::Initial(InitialState), TokenKind::Delimiter)
-            }
-            ('[', _, _) => {
-                cursor.skip(1);
-                (State::Initial(InitialState), TokenKind::Delimiter)
-            }
-            (']', _, _) => {
-                cursor.skip(1);
-                (State::Initial(InitialState), TokenKind::Delimiter)
-            }
-            ('{', _, _) => {
-                cursor.skip(1);
-                (State::Initial(InitialState), TokenKind::Delimiter)
-            }
-            ('}', _, _) => {
-                cursor.skip(1);
-                (State::Initial(InitialState), TokenKind::Delimiter)
-            }
-            ('.', char, _) if char.is_digit(10) => self.number(cursor),
-            ('!', _, _)
-            | ('#', _, _)
-            | ('$', _, _)
-            | ('%', _, _)
-            | ('&', _, _)
-            | ('*', _, _)
-            | ('+', _, _)
-            | (',', _, _)
-            | ('-', _, _)
-            | ('.', _, _)
-            | ('/', _, _)
-            | (':', _, _)
-            | (';', _, _)
-            | ('<', _, _)
-            | ('=', _, _)
-            | ('>', _, _)
-            | ('?', _, _)
-            | ('@', _, _)
-            | ('^', _, _)
-            | ('_', _, _)
-            | ('|', _, _) => {
-                cursor.skip(1);
-                (State::Initial(InitialState), TokenKind::Punctuator)
-            }
-            (char, _, _) if char.is_identifier_start() => self.identifier_or_keyword(cursor),
-            (char, _, _) if char.is_digit(10) => self.number(cursor),
-            (char, _, _) if char.is_whitespace() => self.whitespace(cursor),
-            _ => {
-                cursor.skip(1);
-                (State::Initial(InitialState), TokenKind::Unknown)
-            }
-        }
-    }
-
-    fn line_comment(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0) == '/' && cursor.peek(1) == '/');
-        cursor.skip(2);
-        while cursor.skip_if(|ch| ch != '\0') {}
-        (State::Initial(InitialState), TokenKind::Comment)
-    }
-
-    fn block_comment(self, cursor: &mut Cursor<'_>) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0) == '/' && cursor.peek(1) == '*');
-        cursor.skip(2);
-        BlockCommentTailState { depth: 0 }.next(cursor)
-    }
-
-    fn identifier_or_keyword(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0).is_identifier_start());
-        let start = cursor.index;
-        cursor.skip(1);
-        while cursor.skip_if(|char| char.is_identifier_continue()) {}
-        let end = cursor.index;
-        let string = &cursor.string[start..end];
-        (
-            State::Initial(InitialState),
-            match string {
-                "else" | "if" | "match" | "return" => TokenKind::BranchKeyword,
-                "break" | "continue" | "for" | "loop" | "while" => TokenKind::LoopKeyword,
-                "Self" | "as" | "async" | "await" | "const" | "crate" | "dyn" | "enum"
-                | "extern" | "false" | "fn" | "impl" | "in" | "let" | "mod" | "move" | "mut"
-                | "pub" | "ref" | "self" | "static" | "struct" | "super" | "trait" | "true"
-                | "type" | "unsafe" | "use" | "where" | "usize" | "isize" | "u8" | "u16"
-                | "u32" | "u64" | "i8" | "i16" | "i32" | "i64" | "vec2" | "vec3" | "vec4"
-                | "bool" | "f32" | "f64" => TokenKind::OtherKeyword,
-                _ => {
-                    let mut chars = string.chars();
-                    if chars.next().unwrap().is_uppercase() {
-                        match chars.next() {
-                            Some(char) if char.is_uppercase() => TokenKind::Constant,
-                            _ => TokenKind::Typename,
-                        }
-                    } else if cursor.peek(0) == '(' {
-                        TokenKind::Function
-                    } else {
-                        TokenKind::Identifier
-                    }
-                }
-            },
-        )
-    }
-
-    fn number(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        match (cursor.peek(0), cursor.peek(1)) {
-            ('0', 'b') => {
-                cursor.skip(2);
-                if !cursor.skip_digits(2) {
-                    return (State::Initial(InitialState), TokenKind::Unknown);
-                }
-                return (State::Initial(InitialState), TokenKind::Number);
-            }
-            ('0', 'o') => {
-                cursor.skip(2);
-                if !cursor.skip_digits(8) {
-                    return (State::Initial(InitialState), TokenKind::Unknown);
-                }
-                return (State::Initial(InitialState), TokenKind::Number);
-            }
-            ('0', 'x') => {
-                cursor.skip(2);
-                if !cursor.skip_digits(16) {
-                    return (State::Initial(InitialState), TokenKind::Unknown);
-                }
-                return (State::Initial(InitialState), TokenKind::Number);
-            }
-            _ => {
-                cursor.skip_digits(10);
-                match cursor.peek(0) {
-                    '.' if cursor.peek(1) != '.' && !cursor.peek(0).is_identifier_start() => {
-                        cursor.skip(1);
-                        if cursor.skip_digits(10) {
-                            if cursor.peek(0) == 'E' || cursor.peek(0) == 'e' {
-                                if !cursor.skip_exponent() {
-                                    return (State::Initial(InitialState), TokenKind::Unknown);
-                                }
-                            }
-                        }
-                        cursor.skip_suffix();
-                        return (State::Initial(InitialState), TokenKind::Number);
-                    }
-                    'E' | 'e' => {
-                        if !cursor.skip_exponent() {
-                            return (State::Initial(InitialState), TokenKind::Unknown);
-                        }
-                        cursor.skip_suffix();
-                        return (State::Initial(InitialState), TokenKind::Number);
-                    }
-                    _ => {
-                        cursor.skip_suffix();
-                        return (State::Initial(InitialState), TokenKind::Number);
-                    }
-                }
-            }
-        };
-    }
-
-    fn char_or_lifetime(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        if cursor.peek(1).is_identifier_start() && cursor.peek(2) != '\'' {
-            debug_assert!(cursor.peek(0) == '\'');
-            cursor.skip(2);
-            while cursor.skip_if(|ch| ch.is_identifier_continue()) {}
-            if cursor.peek(0) == '\'' {
-                cursor.skip(1);
-                cursor.skip_suffix();
-                (State::Initial(InitialState), TokenKind::String)
-            } else {
-                (State::Initial(InitialState), TokenKind::String)
-            }
-        } else {
-            self.single_quoted_string(cursor)
+impl LanguageRegistry {
+    /// Creates a registry without any languages. Use `LanguageRegistry::default` for the built-in ones.
+    pub fn new() -> Self {
+        Self {
+            languages: HashMap::new(),
         }
     }
 
-    fn byte(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0) == 'b');
-        cursor.skip(1);
-        self.single_quoted_string(cursor)
-    }
-
-    fn string(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        self.double_quoted_string(cursor)
-    }
-
-    fn byte_string(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0) == 'b');
-        cursor.skip(1);
-        self.double_quoted_string(cursor)
-    }
-
-    fn raw_string(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0) == 'r');
-        cursor.skip(1);
-        self.raw_double_quoted_string(cursor)
-    }
-
-    fn raw_byte_string(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0) == 'b' && cursor.peek(1) == 'r');
-        cursor.skip(2);
-        self.raw_double_quoted_string(cursor)
-    }
-
-    fn single_quoted_string(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0) == '\'');
-        cursor.skip(1);
-        loop {
-            match (cursor.peek(0), cursor.peek(1)) {
-                ('\'', _) => {
-                    cursor.skip(1);
-                    cursor.skip_suffix();
-                    break;
-                }
-                ('\0', _) => return (State::Initial(InitialState), TokenKind::Unknown),
-                ('\\', '\'') | ('\\', '\\') => cursor.skip(2),
-                _ => cursor.skip(1),
-            }
+    /// Registers `language` for files ending in any of `extensions`, given without the dot.
+    pub fn register<L: LanguageTokenizer + Clone>(&mut self, extensions: &[&str], language: L) {
+        let new_tokenizer: Rc<dyn Fn(usize) -> Tokenizer> =
+            Rc::new(move |line_count| Tokenizer::with_language(language.clone(), line_count));
+        for extension in extensions {
+            self.languages
+                .insert(extension.to_ascii_lowercase(), new_tokenizer.clone());
         }
-        (State::Initial(InitialState), TokenKind::String)
-    }
-
-    fn double_quoted_string(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0) == '"');
-        cursor.skip(1);
-        DoubleQuotedStringTailState.next(cursor)
     }
 
-    fn raw_double_quoted_string(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        let mut start_hash_count = 0;
-        while cursor.skip_if(|ch| ch == '#') {
-            start_hash_count += 1;
-        }
-        RawDoubleQuotedStringTailState { start_hash_count }.next(cursor)
+    pub fn tokenizer_for_extension(&self, extension: &str, line_count: usize) -> Option<Tokenizer> {
+        self.languages
+            .get(&extension.to_ascii_lowercase())
+            .map(|new_tokenizer| new_tokenizer(line_count))
     }
 
-    fn whitespace(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0).is_whitespace());
-        cursor.skip(1);
-        while cursor.skip_if(|char| char.is_whitespace()) {}
-        (State::Initial(InitialState), TokenKind::Whitespace)
+    /// Returns the tokenizer for the extension of `path`, or one for plain text if there is none.
+    pub fn tokenizer_for_path(&self, path: &str, line_count: usize) -> Tokenizer {
+        let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+        file_name
+            .rsplit_once('.')
+            .and_then(|(_, extension)| self.tokenizer_for_extension(extension, line_count))
+            .unwrap_or_else(|| Tokenizer::with_language(PlainTextTokenizer, line_count))
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-pub struct BlockCommentTailState {
-    depth: usize,
-}
-
-impl BlockCommentTailState {
-    fn next(self, cursor: &mut Cursor<'_>) -> (State, TokenKind) {
-        let mut state = self;
-        loop {
-            match (cursor.peek(0), cursor.peek(1)) {
-                ('/', '*') => {
-                    cursor.skip(2);
-                    state.depth += 1;
-                }
-                ('*', '/') => {
-                    cursor.skip(2);
-                    if state.depth == 0 {
-                        break (State::Initial(InitialState), TokenKind::Comment);
-                    }
-                    state.depth -= 1;
-                }
-                ('\0', _) => {
-                    break (State::BlockCommentTail(state), TokenKind::Comment);
-                }
-                _ => cursor.skip(1),
-            }
-        }
+impl Default for LanguageRegistry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register(&["rs"], rust::RustTokenizer);
+        registry.register(&["toml"], toml::TomlTokenizer);
+        registry.register(&["json"], json::JsonTokenizer);
+        registry.register(&["md", "markdown"], markdown::MarkdownTokenizer);
+        registry.register(&["live", "glsl", "vert", "frag"], live::LiveTokenizer);
+        registry
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-pub struct DoubleQuotedStringTailState;
-
-impl DoubleQuotedStringTailState {
-    fn next(self, cursor: &mut Cursor<'_>) -> (State, TokenKind) {
-        loop {
-            match (cursor.peek(0), cursor.peek(1)) {
-                ('"', _) => {
-                    cursor.skip(1);
-                    cursor.skip_suffix();
-                    break (State::Initial(InitialState), TokenKind::String);
-                }
-                ('\0', _) => {
-                    break (
-                        State::DoubleQuotedStringTail(DoubleQuotedStringTailState),
-                        TokenKind::String,
-                    );
-                }
-                ('\\', '"') | ('\\', '\\') => cursor.skip(2),
-                _ => cursor.skip(1),
-            }
-        }
+impl fmt::Debug for LanguageRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.languages.keys()).finish()
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-pub struct RawDoubleQuotedStringTailState {
-    start_hash_count: usize,
-}
+/// Splits lines at whitespace boundaries without highlighting anything.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PlainTextTokenizer;
 
-impl RawDoubleQuotedStringTailState {
-    fn next(self, cursor: &mut Cursor<'_>) -> (State, TokenKind) {
-        loop {
-            match cursor.peek(0) {
-                '"' => {
-                    cursor.skip(1);
-                    let mut end_hash_count = 0;
-                    while end_hash_count < self.start_hash_count && cursor.skip_if(|ch| ch == '#') {
-                        end_hash_count += 1;
-                    }
-                    if end_hash_count == self.start_hash_count {
-                        cursor.skip_suffix();
-                        break (State::Initial(InitialState), TokenKind::String);
-                    }
-                }
-                '\0' => {
-                    break (State::RawDoubleQuotedStringTail(self), TokenKind::String);
-                }
-                _ => cursor.skip(1),
-            }
+impl LanguageTokenizer for PlainTextTokenizer {
+    type State = ();
+
+    fn next(&self, _state: (), cursor: &mut Cursor<'_>) -> ((), TokenKind) {
+        if cursor.peek(0).is_whitespace() {
+            cursor.skip(1);
+            while cursor.skip_if(|char| char.is_whitespace()) {}
+            ((), TokenKind::Whitespace)
+        } else {
+            cursor.skip(1);
+            while cursor.skip_if(|char| char != '\0' && !char.is_whitespace()) {}
+            ((), TokenKind::Unknown)
         }
     }
 }
@@ -481,11 +209,42 @@ impl<'a> Cursor<'a> {
         Cursor { string, index: 0 }
     }
 
-    fn peek(&self, index: usize) -> char {
+    /// Returns the byte offset of the cursor in the line.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the part of the line behind the cursor.
+    pub fn prefix(&self) -> &'a str {
+        &self.string[..self.index]
+    }
+
+    /// Returns the part of the line in front of the cursor.
+    pub fn as_str(&self) -> &'a str {
+        &self.string[self.index..]
+    }
+
+    /// Skips `prefix` if the line continues with it.
+    pub fn skip_str(&mut self, prefix: &str) -> bool {
+        if self.as_str().starts_with(prefix) {
+            self.index += prefix.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Skips to the end of the line.
+    pub fn skip_line(&mut self) {
+        self.index = self.string.len();
+    }
+
+    /// Returns the char `index` chars past the cursor, or `'\0'` past the end of the line.
+    pub fn peek(&self, index: usize) -> char {
         self.string[self.index..].chars().nth(index).unwrap_or('\0')
     }
 
-    fn skip(&mut self, count: usize) {
+    pub fn skip(&mut self, count: usize) {
         self.index = self.string[self.index..]
             .char_indices()
             .nth(count)
@@ -504,7 +263,7 @@ impl<'a> Cursor<'a> {
         }
     }
 
-    fn skip_exponent(&mut self) -> bool {
+    pub fn skip_exponent(&mut self) -> bool {
         debug_assert!(self.peek(0) == 'E' || self.peek(0) == 'e');
         self.skip(1);
         if self.peek(0) == '+' || self.peek(0) == '-' {
@@ -513,7 +272,7 @@ impl<'a> Cursor<'a> {
         self.skip_digits(10)
     }
 
-    fn skip_digits(&mut self, radix: u32) -> bool {
+    pub fn skip_digits(&mut self, radix: u32) -> bool {
         let mut has_skip_digits = false;
         loop {
             match self.peek(0) {
@@ -530,7 +289,7 @@ impl<'a> Cursor<'a> {
         has_skip_digits
     }
 
-    fn skip_suffix(&mut self) -> bool {
+    pub fn skip_suffix(&mut self) -> bool {
         if self.peek(0).is_identifier_start() {
             self.skip(1);
             while self.skip_if(|char| char.is_identifier_continue()) {}
@@ -0,0 +1,121 @@
+use crate::{
+    token::TokenKind,
+    tokenizer::{Cursor, LanguageTokenizer},
+};
+
+/// Lexes JSON. Object keys are identifiers. Comments are accepted too, as many JSON config files
+/// have them.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct JsonTokenizer;
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum State {
+    #[default]
+    Initial,
+    BlockCommentTail,
+}
+
+impl LanguageTokenizer for JsonTokenizer {
+    type State = State;
+
+    fn next(&self, state: State, cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+        match state {
+            State::Initial => initial(cursor),
+            State::BlockCommentTail => block_comment_tail(cursor),
+        }
+    }
+}
+
+fn initial(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    match (cursor.peek(0), cursor.peek(1)) {
+        ('/', '/') => {
+            cursor.skip_line();
+            (State::Initial, TokenKind::Comment)
+        }
+        ('/', '*') => {
+            cursor.skip(2);
+            block_comment_tail(cursor)
+        }
+        ('"', _) => string(cursor),
+        ('[', _) | (']', _) | ('{', _) | ('}', _) => {
+            cursor.skip(1);
+            (State::Initial, TokenKind::Delimiter)
+        }
+        (':', _) | (',', _) => {
+            cursor.skip(1);
+            (State::Initial, TokenKind::Punctuator)
+        }
+        ('-', char) if char.is_ascii_digit() => number(cursor),
+        (char, _) if char.is_ascii_digit() => number(cursor),
+        (char, _) if char.is_ascii_alphabetic() => {
+            let start = cursor.index();
+            while cursor.skip_if(|char| char.is_ascii_alphanumeric()) {}
+            (
+                State::Initial,
+                match &cursor.prefix()[start..] {
+                    "true" | "false" | "null" => TokenKind::OtherKeyword,
+                    _ => TokenKind::Unknown,
+                },
+            )
+        }
+        (char, _) if char.is_whitespace() => {
+            cursor.skip(1);
+            while cursor.skip_if(|char| char.is_whitespace()) {}
+            (State::Initial, TokenKind::Whitespace)
+        }
+        _ => {
+            cursor.skip(1);
+            (State::Initial, TokenKind::Unknown)
+        }
+    }
+}
+
+fn block_comment_tail(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    loop {
+        match (cursor.peek(0), cursor.peek(1)) {
+            ('*', '/') => {
+                cursor.skip(2);
+                break (State::Initial, TokenKind::Comment);
+            }
+            ('\0', _) => break (State::BlockCommentTail, TokenKind::Comment),
+            _ => cursor.skip(1),
+        }
+    }
+}
+
+fn string(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    debug_assert!(cursor.peek(0) == '"');
+    cursor.skip(1);
+    loop {
+        match (cursor.peek(0), cursor.peek(1)) {
+            ('"', _) => {
+                cursor.skip(1);
+                break;
+            }
+            ('\0', _) => return (State::Initial, TokenKind::Unknown),
+            ('\\', '"') | ('\\', '\\') => cursor.skip(2),
+            _ => cursor.skip(1),
+        }
+    }
+    // a string followed by a colon is a key
+    if cursor.as_str().trim_start().starts_with(':') {
+        (State::Initial, TokenKind::Identifier)
+    } else {
+        (State::Initial, TokenKind::String)
+    }
+}
+
+fn number(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    cursor.skip_if(|char| char == '-');
+    cursor.skip_digits(10);
+    if cursor.peek(0) == '.' {
+        cursor.skip(1);
+        if !cursor.skip_digits(10) {
+            return (State::Initial, TokenKind::Unknown);
+        }
+    }
+    if (cursor.peek(0) == 'E' || cursor.peek(0) == 'e') && !cursor.skip_exponent() {
+        return (State::Initial, TokenKind::Unknown);
+    }
+    (State::Initial, TokenKind::Number)
+}
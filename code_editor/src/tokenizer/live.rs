@@ -0,0 +1,253 @@
+use crate::{
+    token::TokenKind,
+    tokenizer::{CharExt, Cursor, LanguageTokenizer},
+};
+
+/// Lexes the Makepad live DSL, including the shader code in it, and GLSL.
+///
+/// `{{Struct}}` and `<Widget>` references are typenames, `#f0a` colors are numbers and GLSL
+/// preprocessor lines are keywords.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct LiveTokenizer;
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum State {
+    #[default]
+    Initial,
+    BlockCommentTail { depth: usize },
+    StringTail,
+}
+
+impl LanguageTokenizer for LiveTokenizer {
+    type State = State;
+
+    fn next(&self, state: State, cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+        match state {
+            State::Initial => initial(cursor),
+            State::BlockCommentTail { depth } => block_comment_tail(cursor, depth),
+            State::StringTail => string_tail(cursor),
+        }
+    }
+}
+
+fn initial(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    match (cursor.peek(0), cursor.peek(1)) {
+        ('/', '/') => {
+            cursor.skip_line();
+            (State::Initial, TokenKind::Comment)
+        }
+        ('/', '*') => {
+            cursor.skip(2);
+            block_comment_tail(cursor, 0)
+        }
+        ('"', _) => {
+            cursor.skip(1);
+            string_tail(cursor)
+        }
+        ('#', _) if is_color(cursor) => {
+            cursor.skip(1);
+            while cursor.skip_if(|char| char.is_ascii_alphanumeric()) {}
+            (State::Initial, TokenKind::Number)
+        }
+        ('#', char) if char.is_ascii_alphabetic() && cursor.prefix().trim().is_empty() => {
+            cursor.skip_line();
+            (State::Initial, TokenKind::OtherKeyword)
+        }
+        ('{', '{') => match struct_reference_len(cursor.as_str()) {
+            Some(len) => {
+                cursor.skip(len);
+                (State::Initial, TokenKind::Typename)
+            }
+            None => {
+                cursor.skip(1);
+                (State::Initial, TokenKind::Delimiter)
+            }
+        },
+        ('<', char) if char.is_identifier_start() => match widget_reference_len(cursor.as_str()) {
+            Some(len) => {
+                cursor.skip(len);
+                (State::Initial, TokenKind::Typename)
+            }
+            None => {
+                cursor.skip(1);
+                (State::Initial, TokenKind::Punctuator)
+            }
+        },
+        ('(', _) | (')', _) | ('[', _) | (']', _) | ('{', _) | ('}', _) => {
+            cursor.skip(1);
+            (State::Initial, TokenKind::Delimiter)
+        }
+        ('!', '=')
+        | ('%', '=')
+        | ('&', '&')
+        | ('*', '=')
+        | ('+', '=')
+        | ('-', '=')
+        | ('-', '>')
+        | ('.', '.')
+        | ('/', '=')
+        | (':', ':')
+        | ('<', '=')
+        | ('=', '=')
+        | ('=', '>')
+        | ('>', '=')
+        | ('|', '|') => {
+            cursor.skip(2);
+            (State::Initial, TokenKind::Punctuator)
+        }
+        ('.', char) if char.is_ascii_digit() => number(cursor),
+        ('!', _)
+        | ('#', _)
+        | ('%', _)
+        | ('&', _)
+        | ('*', _)
+        | ('+', _)
+        | (',', _)
+        | ('-', _)
+        | ('.', _)
+        | ('/', _)
+        | (':', _)
+        | (';', _)
+        | ('<', _)
+        | ('=', _)
+        | ('>', _)
+        | ('?', _)
+        | ('^', _)
+        | ('|', _) => {
+            cursor.skip(1);
+            (State::Initial, TokenKind::Punctuator)
+        }
+        (char, _) if char.is_identifier_start() => identifier_or_keyword(cursor),
+        (char, _) if char.is_ascii_digit() => number(cursor),
+        (char, _) if char.is_whitespace() => {
+            cursor.skip(1);
+            while cursor.skip_if(|char| char.is_whitespace()) {}
+            (State::Initial, TokenKind::Whitespace)
+        }
+        _ => {
+            cursor.skip(1);
+            (State::Initial, TokenKind::Unknown)
+        }
+    }
+}
+
+fn block_comment_tail(cursor: &mut Cursor<'_>, mut depth: usize) -> (State, TokenKind) {
+    loop {
+        match (cursor.peek(0), cursor.peek(1)) {
+            ('/', '*') => {
+                cursor.skip(2);
+                depth += 1;
+            }
+            ('*', '/') => {
+                cursor.skip(2);
+                if depth == 0 {
+                    break (State::Initial, TokenKind::Comment);
+                }
+                depth -= 1;
+            }
+            ('\0', _) => break (State::BlockCommentTail { depth }, TokenKind::Comment),
+            _ => cursor.skip(1),
+        }
+    }
+}
+
+fn string_tail(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    loop {
+        match (cursor.peek(0), cursor.peek(1)) {
+            ('"', _) => {
+                cursor.skip(1);
+                break (State::Initial, TokenKind::String);
+            }
+            ('\0', _) => break (State::StringTail, TokenKind::String),
+            ('\\', '"') | ('\\', '\\') => cursor.skip(2),
+            _ => cursor.skip(1),
+        }
+    }
+}
+
+fn identifier_or_keyword(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    let start = cursor.index();
+    cursor.skip(1);
+    while cursor.skip_if(|char| char.is_identifier_continue()) {}
+    let string = &cursor.prefix()[start..];
+    (
+        State::Initial,
+        match string {
+            "if" | "else" | "return" | "match" | "switch" | "case" | "default" | "discard" => {
+                TokenKind::BranchKeyword
+            }
+            "for" | "while" | "do" | "loop" | "break" | "continue" => TokenKind::LoopKeyword,
+            "import" | "use" | "pub" | "fn" | "let" | "var" | "const" | "struct" | "impl"
+            | "self" | "Self" | "as" | "in" | "out" | "inout" | "true" | "false" | "instance"
+            | "uniform" | "varying" | "texture" | "geometry" | "attribute" | "layout"
+            | "precision" | "highp" | "mediump" | "lowp" | "void" | "bool" | "int" | "uint"
+            | "float" | "double" | "vec2" | "vec3" | "vec4" | "ivec2" | "ivec3" | "ivec4"
+            | "uvec2" | "uvec3" | "uvec4" | "bvec2" | "bvec3" | "bvec4" | "mat2" | "mat3"
+            | "mat4" | "sampler2D" | "samplerCube" | "texture2D" | "f32" | "f64" | "i32"
+            | "u32" => TokenKind::OtherKeyword,
+            _ => {
+                let mut chars = string.chars();
+                if chars.next().unwrap().is_uppercase() {
+                    match chars.next() {
+                        Some(char) if char.is_uppercase() => TokenKind::Constant,
+                        _ => TokenKind::Typename,
+                    }
+                } else if cursor.peek(0) == '(' {
+                    TokenKind::Function
+                } else {
+                    TokenKind::Identifier
+                }
+            }
+        },
+    )
+}
+
+fn number(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    if cursor.skip_str("0x") {
+        if !cursor.skip_digits(16) {
+            return (State::Initial, TokenKind::Unknown);
+        }
+        return (State::Initial, TokenKind::Number);
+    }
+    cursor.skip_digits(10);
+    if cursor.peek(0) == '.' && cursor.peek(1) != '.' {
+        cursor.skip(1);
+        cursor.skip_digits(10);
+    }
+    if (cursor.peek(0) == 'E' || cursor.peek(0) == 'e') && !cursor.skip_exponent() {
+        return (State::Initial, TokenKind::Unknown);
+    }
+    cursor.skip_suffix();
+    (State::Initial, TokenKind::Number)
+}
+
+// `#` followed by hex digits, or by `x` and hex digits for colors that start with a letter
+fn is_color(cursor: &Cursor<'_>) -> bool {
+    let rest = &cursor.as_str()[1..];
+    let len = rest
+        .find(|char: char| !char.is_ascii_alphanumeric())
+        .unwrap_or(rest.len());
+    let digits = rest[..len].strip_prefix('x').unwrap_or(&rest[..len]);
+    !digits.is_empty() && digits.chars().all(|char| char.is_ascii_hexdigit())
+}
+
+// `{{Struct}}`
+fn struct_reference_len(string: &str) -> Option<usize> {
+    let name = string.strip_prefix("{{")?;
+    let len = name.find("}}")?;
+    name[..len]
+        .chars()
+        .all(|char| char.is_identifier_continue())
+        .then_some(len + 4)
+}
+
+// `<Widget>`, possibly with a path like `<crate::Widget>`
+fn widget_reference_len(string: &str) -> Option<usize> {
+    let name = string.strip_prefix('<')?;
+    let len = name.find('>')?;
+    (len > 0
+        && name[..len]
+            .chars()
+            .all(|char| char.is_identifier_continue() || char == ':'))
+    .then_some(len + 2)
+}
@@ -0,0 +1,267 @@
+use crate::{
+    token::TokenKind,
+    tokenizer::{Cursor, LanguageTokenizer},
+};
+
+/// Lexes Markdown.
+///
+/// Headings are typenames, emphasis is a keyword, code spans and fenced code are strings and the
+/// text of links is a function. Plain text is lexed a word at a time as identifiers.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct MarkdownTokenizer;
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum State {
+    #[default]
+    Initial,
+    FencedCode(FencedCodeState),
+    HtmlCommentTail,
+}
+
+/// Inside a fenced code block, which ends at a fence of the same char at least as long.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct FencedCodeState {
+    fence_char: char,
+    fence_len: usize,
+}
+
+impl LanguageTokenizer for MarkdownTokenizer {
+    type State = State;
+
+    fn next(&self, state: State, cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+        match state {
+            State::Initial if cursor.prefix().trim().is_empty() => line_start(cursor),
+            State::Initial => inline(cursor),
+            State::FencedCode(state) => state.next(cursor),
+            State::HtmlCommentTail => html_comment_tail(cursor),
+        }
+    }
+}
+
+impl FencedCodeState {
+    fn next(self, cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+        if cursor.prefix().trim().is_empty() {
+            let line = cursor.as_str().trim();
+            let fence_len = line.len() - line.trim_start_matches(self.fence_char).len();
+            if fence_len >= self.fence_len && fence_len == line.len() {
+                cursor.skip_line();
+                return (State::Initial, TokenKind::Comment);
+            }
+        }
+        cursor.skip_line();
+        (State::FencedCode(self), TokenKind::String)
+    }
+}
+
+fn line_start(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    let line = cursor.as_str();
+    match cursor.peek(0) {
+        char if char.is_whitespace() => {
+            cursor.skip(1);
+            while cursor.skip_if(|char| char.is_whitespace()) {}
+            (State::Initial, TokenKind::Whitespace)
+        }
+        '`' | '~' if fence_len(line) >= 3 => {
+            let fence_char = cursor.peek(0);
+            let fence_len = fence_len(line);
+            cursor.skip_line();
+            (
+                State::FencedCode(FencedCodeState {
+                    fence_char,
+                    fence_len,
+                }),
+                TokenKind::Comment,
+            )
+        }
+        '#' if is_heading(line) => {
+            cursor.skip_line();
+            (State::Initial, TokenKind::Typename)
+        }
+        '-' | '*' | '_' if is_thematic_break(line) => {
+            cursor.skip_line();
+            (State::Initial, TokenKind::Punctuator)
+        }
+        '>' => {
+            cursor.skip(1);
+            (State::Initial, TokenKind::Comment)
+        }
+        '-' | '*' | '+' if cursor.peek(1) == ' ' || cursor.peek(1) == '\0' => {
+            cursor.skip(1);
+            (State::Initial, TokenKind::Punctuator)
+        }
+        '0'..='9' if is_ordered_list_marker(line) => {
+            while cursor.skip_if(|char| char.is_ascii_digit()) {}
+            cursor.skip(1);
+            (State::Initial, TokenKind::Punctuator)
+        }
+        _ => inline(cursor),
+    }
+}
+
+fn inline(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    match (cursor.peek(0), cursor.peek(1)) {
+        ('\\', char) if char != '\0' => {
+            cursor.skip(2);
+            (State::Initial, TokenKind::Identifier)
+        }
+        ('`', _) => code_span(cursor),
+        ('*', _) | ('_', _) => emphasis(cursor),
+        ('!', '[') => {
+            cursor.skip(1);
+            (State::Initial, TokenKind::Punctuator)
+        }
+        ('[', _) => link_text(cursor),
+        ('(', _) if cursor.prefix().ends_with(']') => link_destination(cursor),
+        ('<', '!') if cursor.as_str().starts_with("<!--") => {
+            cursor.skip_str("<!--");
+            html_comment_tail(cursor)
+        }
+        ('<', char) if char.is_ascii_alphabetic() || char == '/' => html_tag(cursor),
+        (char, _) if char.is_whitespace() => {
+            cursor.skip(1);
+            while cursor.skip_if(|char| char.is_whitespace()) {}
+            (State::Initial, TokenKind::Whitespace)
+        }
+        _ => {
+            cursor.skip(1);
+            while cursor.skip_if(|char| !char.is_whitespace() && !is_inline_special(char)) {}
+            (State::Initial, TokenKind::Identifier)
+        }
+    }
+}
+
+fn code_span(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    let rest = cursor.as_str();
+    let ticks = rest.len() - rest.trim_start_matches('`').len();
+    let fence = &rest[..ticks];
+    match rest[ticks..].find(fence) {
+        Some(index) => {
+            cursor.skip_str(&rest[..ticks + index + ticks]);
+            (State::Initial, TokenKind::String)
+        }
+        None => {
+            cursor.skip_str(fence);
+            (State::Initial, TokenKind::Punctuator)
+        }
+    }
+}
+
+fn emphasis(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    let rest = cursor.as_str();
+    let char = cursor.peek(0);
+    let len = rest.len() - rest.trim_start_matches(char).len();
+    let delimiter = &rest[..len];
+    // underscores inside words like snake_case are not emphasis
+    let opens = !rest[len..].starts_with(char::is_whitespace)
+        && (char == '*' || !cursor.prefix().ends_with(|char: char| char.is_alphanumeric()));
+    if opens && len <= 3 {
+        if let Some(index) = rest[len..].find(delimiter) {
+            if index > 0 {
+                cursor.skip_str(&rest[..len + index + len]);
+                return (State::Initial, TokenKind::OtherKeyword);
+            }
+        }
+    }
+    cursor.skip_str(delimiter);
+    (State::Initial, TokenKind::Identifier)
+}
+
+fn link_text(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    let rest = cursor.as_str();
+    match rest.find(']') {
+        Some(index) => {
+            cursor.skip_str(&rest[..index + 1]);
+            (State::Initial, TokenKind::Function)
+        }
+        None => {
+            cursor.skip(1);
+            (State::Initial, TokenKind::Identifier)
+        }
+    }
+}
+
+fn link_destination(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    let rest = cursor.as_str();
+    match rest.find(')') {
+        Some(index) => {
+            cursor.skip_str(&rest[..index + 1]);
+            (State::Initial, TokenKind::String)
+        }
+        None => {
+            cursor.skip(1);
+            (State::Initial, TokenKind::Identifier)
+        }
+    }
+}
+
+fn html_tag(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    let rest = cursor.as_str();
+    match rest.find('>') {
+        Some(index) => {
+            cursor.skip_str(&rest[..index + 1]);
+            (State::Initial, TokenKind::Typename)
+        }
+        None => {
+            cursor.skip(1);
+            (State::Initial, TokenKind::Identifier)
+        }
+    }
+}
+
+fn html_comment_tail(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    let rest = cursor.as_str();
+    match rest.find("-->") {
+        Some(index) => {
+            cursor.skip_str(&rest[..index + 3]);
+            (State::Initial, TokenKind::Comment)
+        }
+        None => {
+            cursor.skip_line();
+            (State::HtmlCommentTail, TokenKind::Comment)
+        }
+    }
+}
+
+fn fence_len(line: &str) -> usize {
+    let Some(char) = line.chars().next() else {
+        return 0;
+    };
+    let len = line.len() - line.trim_start_matches(char).len();
+    // backtick fences can't have backticks in their info string
+    if char == '`' && line[len..].contains('`') {
+        return 0;
+    }
+    len
+}
+
+fn is_heading(line: &str) -> bool {
+    let level = line.len() - line.trim_start_matches('#').len();
+    level <= 6 && line[level..].chars().next().is_none_or(char::is_whitespace)
+}
+
+fn is_thematic_break(line: &str) -> bool {
+    let mut chars = line.chars().filter(|char| !char.is_whitespace());
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    let mut count = 1;
+    for char in chars {
+        if char != first {
+            return false;
+        }
+        count += 1;
+    }
+    count >= 3
+}
+
+fn is_ordered_list_marker(line: &str) -> bool {
+    let digits = line.len() - line.trim_start_matches(|char: char| char.is_ascii_digit()).len();
+    let mut rest = line[digits..].chars();
+    digits <= 9
+        && matches!(rest.next(), Some('.') | Some(')'))
+        && rest.next().is_none_or(char::is_whitespace)
+}
+
+fn is_inline_special(char: char) -> bool {
+    matches!(char, '\0' | '\\' | '`' | '*' | '_' | '!' | '[' | '(' | '<')
+}
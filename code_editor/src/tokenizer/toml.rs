@@ -0,0 +1,162 @@
+use crate::{
+    token::TokenKind,
+    tokenizer::{Cursor, LanguageTokenizer},
+};
+
+/// Lexes TOML. Keys are identifiers, table headers typenames and dates count as numbers.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct TomlTokenizer;
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum State {
+    #[default]
+    Initial,
+    MultiLineBasicStringTail,
+    MultiLineLiteralStringTail,
+}
+
+impl LanguageTokenizer for TomlTokenizer {
+    type State = State;
+
+    fn next(&self, state: State, cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+        match state {
+            State::Initial => initial(cursor),
+            State::MultiLineBasicStringTail => multi_line_string_tail(cursor, '"'),
+            State::MultiLineLiteralStringTail => multi_line_string_tail(cursor, '\''),
+        }
+    }
+}
+
+fn initial(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    match (cursor.peek(0), cursor.peek(1), cursor.peek(2)) {
+        ('#', _, _) => {
+            cursor.skip_line();
+            (State::Initial, TokenKind::Comment)
+        }
+        ('[', _, _) if cursor.prefix().trim().is_empty() => table_header(cursor),
+        ('"', '"', '"') => {
+            cursor.skip(3);
+            multi_line_string_tail(cursor, '"')
+        }
+        ('\'', '\'', '\'') => {
+            cursor.skip(3);
+            multi_line_string_tail(cursor, '\'')
+        }
+        ('"', _, _) | ('\'', _, _) => string(cursor),
+        ('[', _, _) | (']', _, _) | ('{', _, _) | ('}', _, _) => {
+            cursor.skip(1);
+            (State::Initial, TokenKind::Delimiter)
+        }
+        ('=', _, _) | (',', _, _) | ('.', _, _) => {
+            cursor.skip(1);
+            (State::Initial, TokenKind::Punctuator)
+        }
+        (char, _, _) if char.is_whitespace() => {
+            cursor.skip(1);
+            while cursor.skip_if(|char| char.is_whitespace()) {}
+            (State::Initial, TokenKind::Whitespace)
+        }
+        (char, _, _) if is_bare(char) || char == '+' => bare(cursor),
+        _ => {
+            cursor.skip(1);
+            (State::Initial, TokenKind::Unknown)
+        }
+    }
+}
+
+fn table_header(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    debug_assert!(cursor.peek(0) == '[');
+    let is_array = cursor.peek(1) == '[';
+    cursor.skip(if is_array { 2 } else { 1 });
+    while cursor.skip_if(|char| char != ']' && char != '\0') {}
+    cursor.skip_str(if is_array { "]]" } else { "]" });
+    (State::Initial, TokenKind::Typename)
+}
+
+fn string(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    let quote = cursor.peek(0);
+    cursor.skip(1);
+    loop {
+        match (cursor.peek(0), cursor.peek(1)) {
+            (char, _) if char == quote => {
+                cursor.skip(1);
+                break;
+            }
+            ('\0', _) => return (State::Initial, TokenKind::Unknown),
+            ('\\', '\\') | ('\\', '"') if quote == '"' => cursor.skip(2),
+            _ => cursor.skip(1),
+        }
+    }
+    (
+        State::Initial,
+        if is_key(cursor) {
+            TokenKind::Identifier
+        } else {
+            TokenKind::String
+        },
+    )
+}
+
+fn multi_line_string_tail(cursor: &mut Cursor<'_>, quote: char) -> (State, TokenKind) {
+    loop {
+        match (cursor.peek(0), cursor.peek(1), cursor.peek(2)) {
+            (a, b, c) if a == quote && b == quote && c == quote => {
+                cursor.skip(3);
+                // up to two more quotes still belong to the string
+                for _ in 0..2 {
+                    cursor.skip_if(|char| char == quote);
+                }
+                break (State::Initial, TokenKind::String);
+            }
+            ('\0', _, _) => {
+                break (
+                    if quote == '"' {
+                        State::MultiLineBasicStringTail
+                    } else {
+                        State::MultiLineLiteralStringTail
+                    },
+                    TokenKind::String,
+                );
+            }
+            ('\\', _, _) if quote == '"' => cursor.skip(2),
+            _ => cursor.skip(1),
+        }
+    }
+}
+
+fn bare(cursor: &mut Cursor<'_>) -> (State, TokenKind) {
+    let start = cursor.index();
+    cursor.skip(1);
+    while cursor.skip_if(is_bare) {}
+    if is_key(cursor) {
+        return (State::Initial, TokenKind::Identifier);
+    }
+    let string = &cursor.prefix()[start..];
+    match string {
+        "true" | "false" => (State::Initial, TokenKind::OtherKeyword),
+        "inf" | "+inf" | "-inf" | "nan" | "+nan" | "-nan" => (State::Initial, TokenKind::Number),
+        _ if string
+            .trim_start_matches(['+', '-'])
+            .starts_with(|char: char| char.is_ascii_digit()) =>
+        {
+            // numbers, including floats, exponents and dates
+            while cursor.skip_if(|char| is_bare(char) || matches!(char, '.' | ':' | '+')) {}
+            (State::Initial, TokenKind::Number)
+        }
+        _ => (State::Initial, TokenKind::Unknown),
+    }
+}
+
+// a key is followed by `=`, or by `.` and the next part of a dotted key
+fn is_key(cursor: &Cursor<'_>) -> bool {
+    let rest = cursor.as_str().trim_start();
+    match rest.chars().next() {
+        Some('=') => true,
+        Some('.') => !rest[1..].trim_start().starts_with(|char: char| char.is_ascii_digit()),
+        _ => false,
+    }
+}
+
+fn is_bare(char: char) -> bool {
+    char.is_ascii_alphanumeric() || char == '_' || char == '-'
+}
@@ -0,0 +1,169 @@
+// Tests for the regex engine, search queries and replacing matches in a session.
+
+use makepad_code_editor::{
+    decoration::DecorationSet,
+    regex::{Regex, StepBudget},
+    search::{SearchError, SearchQuery, Searcher},
+    text::{Position, Text},
+    Document, Session,
+};
+
+// the text of every group of the first match in `text`, None for groups that didn't take part
+fn captures(pattern: &str, text: &str) -> Option<Vec<Option<String>>> {
+    let regex = Regex::new(pattern, false).unwrap();
+    let captures = regex.find_at(text, 0, &StepBudget::for_text_len(text.len()))?;
+    Some(
+        (0..regex.group_count())
+            .map(|index| captures.get(index).map(|range| text[range].to_string()))
+            .collect(),
+    )
+}
+
+fn matched(pattern: &str, text: &str) -> Option<String> {
+    captures(pattern, text).map(|groups| groups[0].clone().unwrap())
+}
+
+fn find_all(query: &SearchQuery, text: &str) -> Vec<String> {
+    let text = Text::from(text);
+    let mut searcher = Searcher::new(query).unwrap();
+    searcher
+        .find_all(&text)
+        .unwrap()
+        .into_iter()
+        .map(|(start, end)| text.as_lines()[start.line_index][start.byte_index..end.byte_index].to_string())
+        .collect()
+}
+
+fn session(text: &str) -> Session {
+    Session::new(Document::new(Text::from(text), DecorationSet::new()))
+}
+
+#[test]
+fn regex_matches_leftmost() {
+    assert_eq!(matched("b+", "abbbc"), Some("bbb".to_string()));
+    assert_eq!(matched("b+?", "abbbc"), Some("b".to_string()));
+    assert_eq!(matched("a|ab", "xab"), Some("a".to_string()));
+    assert_eq!(matched("x{2,3}", "xxxxx"), Some("xxx".to_string()));
+    assert_eq!(matched("[a-c]+", "zzbcaz"), Some("bca".to_string()));
+    assert_eq!(matched("[^0-9 ]+", "12 ab3"), Some("ab".to_string()));
+    assert_eq!(matched(r"\d+\.\d*", "v 1.25"), Some("1.25".to_string()));
+    assert_eq!(matched(r"\bcat\b", "concat cat"), Some("cat".to_string()));
+    assert_eq!(matched("^a", "ba"), None);
+    assert_eq!(matched("a$", "ab a"), Some("a".to_string()));
+    assert_eq!(matched("é.", "café!"), Some("é!".to_string()));
+}
+
+#[test]
+fn regex_captures_groups() {
+    assert_eq!(
+        captures(r"(\w+)=(?:(\d+)|(\w+))", "key=value"),
+        Some(vec![
+            Some("key=value".to_string()),
+            Some("key".to_string()),
+            None,
+            Some("value".to_string()),
+        ])
+    );
+    // a repeated group keeps its last iteration
+    assert_eq!(captures("(a|b)+", "abab").unwrap()[1], Some("b".to_string()));
+}
+
+#[test]
+fn regex_reports_errors() {
+    assert_eq!(Regex::new("a)", false).unwrap_err().index, 1);
+    assert!(Regex::new("(a", false).is_err());
+    assert!(Regex::new("*a", false).is_err());
+    assert!(Regex::new("a{3,1}", false).is_err());
+    assert!(Regex::new("[a", false).is_err());
+    // a brace that isn't a repetition is a literal
+    assert_eq!(matched("a{x", "a{x"), Some("a{x".to_string()));
+}
+
+#[test]
+fn pathological_pattern_runs_out_of_steps() {
+    let line = "a".repeat(35);
+    let query = SearchQuery::regex("(a*)*b");
+    let mut searcher = Searcher::new(&query).unwrap();
+    let started = std::time::Instant::now();
+    assert_eq!(searcher.find_all(&Text::from(line.as_str())), Err(SearchError::TooComplex));
+    assert!(searcher.is_too_complex());
+    assert!(started.elapsed().as_secs() < 2);
+    assert_eq!(SearchError::TooComplex.to_string(), "pattern too complex");
+
+    // the session reports it instead of finding nothing
+    assert_eq!(session(&line).set_search(&query), Err(SearchError::TooComplex));
+    assert!(session(&line).search_matches().is_empty());
+}
+
+#[test]
+fn whole_word_skips_matches_inside_words() {
+    let query = SearchQuery::literal("cat").with_case_sensitive(true);
+    assert_eq!(find_all(&query, "cat concat cats cat_ cat"), ["cat", "cat", "cat", "cat", "cat"]);
+    assert_eq!(find_all(&query.with_whole_word(true), "cat concat cats cat_ cat"), ["cat", "cat"]);
+}
+
+#[test]
+fn ignore_case_matches_either_case() {
+    let query = SearchQuery::literal("Straße");
+    assert_eq!(find_all(&query, "STRASSE straße STRAßE"), ["straße", "STRAßE"]);
+    assert!(find_all(&query.with_case_sensitive(true), "straße").is_empty());
+    assert_eq!(find_all(&SearchQuery::regex("[a-c]+"), "xABcx"), ["ABc"]);
+}
+
+#[test]
+fn expands_group_references_in_replacements() {
+    let searcher = Searcher::new(&SearchQuery::regex(r"(\w+)=(\w+)")).unwrap();
+    let line = "key=value";
+    let captures = searcher.find_in_line(line, 0).unwrap();
+    assert_eq!(searcher.expand_replacement(line, &captures, "$2=$1"), "value=key");
+    assert_eq!(searcher.expand_replacement(line, &captures, "${2}1 $0"), "value1 key=value");
+    assert_eq!(searcher.expand_replacement(line, &captures, "$$1 costs $"), "$1 costs $");
+    // groups that don't exist insert nothing, references that aren't ones stay as is
+    assert_eq!(searcher.expand_replacement(line, &captures, "[$7] ${x}"), "[] ${x}");
+
+    // literal queries insert the replacement as is
+    let searcher = Searcher::new(&SearchQuery::literal("key")).unwrap();
+    let captures = searcher.find_in_line(line, 0).unwrap();
+    assert_eq!(searcher.expand_replacement(line, &captures, "$1$$"), "$1$$");
+}
+
+#[test]
+fn finds_all_captures_in_lines() {
+    let mut searcher = Searcher::new(&SearchQuery::regex(r"(\w)=(\d)")).unwrap();
+    let lines = ["a=1 b=2".to_string(), "".to_string(), "c=3".to_string()];
+    let matches = searcher.find_all_in_lines(&lines).unwrap();
+    let found: Vec<(usize, String)> = matches
+        .iter()
+        .map(|(line_index, captures)| (*line_index, lines[*line_index][captures.get(2).unwrap()].to_string()))
+        .collect();
+    assert_eq!(found, [(0, "1".to_string()), (0, "2".to_string()), (2, "3".to_string())]);
+}
+
+#[test]
+fn replace_all_is_one_undo_group() {
+    let mut session = session("a=1 b=2\nc=3\n");
+    assert_eq!(session.set_search(&SearchQuery::regex(r"(\w)=(\d)")), Ok(3));
+    assert_eq!(session.replace_all("$2:$1"), 3);
+    assert_eq!(session.document().as_text().to_string(), "1:a 2:b\n3:c\n");
+    assert!(session.undo());
+    assert_eq!(session.document().as_text().to_string(), "a=1 b=2\nc=3\n");
+    assert!(session.redo());
+    assert_eq!(session.document().as_text().to_string(), "1:a 2:b\n3:c\n");
+}
+
+#[test]
+fn matches_follow_edits() {
+    let mut session = session("one two\none\n");
+    assert_eq!(session.set_search(&SearchQuery::literal("one")), Ok(2));
+    session.replace_ranges(&[(
+        Position { line_index: 0, byte_index: 4 },
+        Position { line_index: 0, byte_index: 7 },
+        Text::from("one"),
+    )]);
+    let matches: Vec<_> = session
+        .search_matches()
+        .iter()
+        .map(|decoration| (decoration.start().line_index, decoration.start().byte_index))
+        .collect();
+    assert_eq!(matches, [(0, 0), (0, 4), (1, 0)]);
+}
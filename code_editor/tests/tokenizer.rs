@@ -0,0 +1,151 @@
+// Tests for the tokenizers of each language the registry knows about.
+
+use makepad_code_editor::{
+    text::{Change, Position, Text},
+    token::TokenKind,
+    LanguageRegistry,
+};
+
+// the tokens of `text` lexed as a file called `path`, leaving out whitespace
+fn lex(path: &str, text: &str) -> Vec<(String, TokenKind)> {
+    let text = Text::from(text);
+    let line_count = text.as_lines().len();
+    let mut tokenizer = LanguageRegistry::default().tokenizer_for_path(path, line_count);
+    let mut tokens = vec![Vec::new(); line_count];
+    tokenizer.update(&text, &mut tokens);
+    let mut out = Vec::new();
+    for (line, tokens) in text.as_lines().iter().zip(tokens) {
+        let mut start = 0;
+        for token in tokens {
+            if token.kind != TokenKind::Whitespace {
+                out.push((line[start..start + token.len].to_string(), token.kind));
+            }
+            start += token.len;
+        }
+    }
+    out
+}
+
+fn kind_of(tokens: &[(String, TokenKind)], text: &str) -> TokenKind {
+    tokens
+        .iter()
+        .find(|(token, _)| token == text)
+        .unwrap_or_else(|| panic!("no token {:?} in {:?}", text, tokens))
+        .1
+}
+
+#[test]
+fn rust() {
+    let tokens = lex(
+        "main.rs",
+        "fn main() {\n    let x = r#\"a\"# + 0x1f; /* multi\n line */ for _ in v { break }\n}",
+    );
+    assert_eq!(kind_of(&tokens, "fn"), TokenKind::OtherKeyword);
+    assert_eq!(kind_of(&tokens, "main"), TokenKind::Function);
+    assert_eq!(kind_of(&tokens, "r#\"a\"#"), TokenKind::String);
+    assert_eq!(kind_of(&tokens, "0x1f"), TokenKind::Number);
+    assert_eq!(kind_of(&tokens, "/* multi"), TokenKind::Comment);
+    assert_eq!(kind_of(&tokens, " line */"), TokenKind::Comment);
+    assert_eq!(kind_of(&tokens, "for"), TokenKind::LoopKeyword);
+    assert_eq!(kind_of(&tokens, "break"), TokenKind::LoopKeyword);
+}
+
+#[test]
+fn live_design_in_rust_uses_the_live_tokenizer() {
+    let tokens = lex(
+        "app.rs",
+        "live_design!{\n    App = {{App}} {\n        ui: <Window> {color: #f0a}\n    }\n}\nfn x() -> u32 {#[cfg(x)] 0}",
+    );
+    // inside the macro
+    assert_eq!(kind_of(&tokens, "{{App}}"), TokenKind::Typename);
+    assert_eq!(kind_of(&tokens, "<Window>"), TokenKind::Typename);
+    assert_eq!(kind_of(&tokens, "#f0a"), TokenKind::Number);
+    // and back to rust after the closing brace, where `#` is just punctuation
+    assert_eq!(kind_of(&tokens, "fn"), TokenKind::OtherKeyword);
+    assert_eq!(kind_of(&tokens, "#"), TokenKind::Punctuator);
+    assert_eq!(kind_of(&tokens, "u32"), TokenKind::OtherKeyword);
+}
+
+#[test]
+fn live_design_needs_the_bang_and_brace() {
+    let tokens = lex("lib.rs", "let live_design = 1;\nlet c = <T>::x;");
+    assert!(!tokens.iter().any(|(token, _)| token == "<T>"));
+    // the opening brace may be on the next line
+    let tokens = lex("lib.rs", "live_design!\n{ A = <B> {} }\nlet y = <T>::x;");
+    assert_eq!(kind_of(&tokens, "<B>"), TokenKind::Typename);
+    assert!(!tokens.iter().any(|(token, _)| token == "<T>"));
+}
+
+#[test]
+fn live_design_region_follows_edits() {
+    let text = Text::from("live_design!{\n    a: <B> {}\n}");
+    let line_count = text.as_lines().len();
+    let mut tokenizer = LanguageRegistry::default().tokenizer_for_path("a.rs", line_count);
+    let mut tokens = vec![Vec::new(); line_count];
+    tokenizer.update(&text, &mut tokens);
+    assert!(tokens[1].iter().any(|token| token.kind == TokenKind::Typename && token.len == 3));
+
+    // renaming the macro turns the body back into rust
+    let mut text = text;
+    let change = Change::Delete(Position { line_index: 0, byte_index: 0 }, Text::from("l").length());
+    text.apply_change(change.clone());
+    tokenizer.apply_change(&change);
+    tokenizer.update(&text, &mut tokens);
+    assert!(!tokens[1].iter().any(|token| token.kind == TokenKind::Typename && token.len == 3));
+}
+
+#[test]
+fn live() {
+    let tokens = lex(
+        "theme.live",
+        "#version 100\nFoo = <View> { // note\n    fn pixel(self) -> vec4 { return #x0f0 }\n}",
+    );
+    assert_eq!(kind_of(&tokens, "#version 100"), TokenKind::OtherKeyword);
+    assert_eq!(kind_of(&tokens, "Foo"), TokenKind::Typename);
+    assert_eq!(kind_of(&tokens, "<View>"), TokenKind::Typename);
+    assert_eq!(kind_of(&tokens, "// note"), TokenKind::Comment);
+    assert_eq!(kind_of(&tokens, "pixel"), TokenKind::Function);
+    assert_eq!(kind_of(&tokens, "vec4"), TokenKind::OtherKeyword);
+    assert_eq!(kind_of(&tokens, "return"), TokenKind::BranchKeyword);
+    assert_eq!(kind_of(&tokens, "#x0f0"), TokenKind::Number);
+}
+
+#[test]
+fn toml() {
+    let tokens = lex(
+        "Cargo.toml",
+        "[package]\nname = \"app\" # comment\nversion = 1.5\nfeatures = [true]\ntext = '''\nmulti\n'''",
+    );
+    assert_eq!(kind_of(&tokens, "[package]"), TokenKind::Typename);
+    assert_eq!(kind_of(&tokens, "name"), TokenKind::Identifier);
+    assert_eq!(kind_of(&tokens, "\"app\""), TokenKind::String);
+    assert_eq!(kind_of(&tokens, "# comment"), TokenKind::Comment);
+    assert_eq!(kind_of(&tokens, "1.5"), TokenKind::Number);
+    assert_eq!(kind_of(&tokens, "true"), TokenKind::OtherKeyword);
+    assert_eq!(kind_of(&tokens, "multi"), TokenKind::String);
+}
+
+#[test]
+fn json() {
+    let tokens = lex("data.json", "{\"key\": [1.5e3, true, null, \"v\"]}");
+    assert_eq!(kind_of(&tokens, "\"key\""), TokenKind::Identifier);
+    assert_eq!(kind_of(&tokens, "1.5e3"), TokenKind::Number);
+    assert_eq!(kind_of(&tokens, "true"), TokenKind::OtherKeyword);
+    assert_eq!(kind_of(&tokens, "null"), TokenKind::OtherKeyword);
+    assert_eq!(kind_of(&tokens, "\"v\""), TokenKind::String);
+}
+
+#[test]
+fn markdown() {
+    let tokens = lex("README.md", "# Title\nsome `code` and\n```rust\nlet x;\n```");
+    assert_eq!(kind_of(&tokens, "# Title"), TokenKind::Typename);
+    assert_eq!(kind_of(&tokens, "`code`"), TokenKind::String);
+    assert_eq!(kind_of(&tokens, "let x;"), TokenKind::String);
+}
+
+#[test]
+fn unknown_extensions_are_plain_text() {
+    let tokens = lex("notes.xyz", "fn main() {}");
+    assert!(tokens.iter().all(|(_, kind)| *kind == TokenKind::Unknown));
+    assert_eq!(tokens.len(), 3);
+}
@@ -10,6 +10,7 @@ pub use {
         io::prelude::*,
         fs::File,
         collections::HashMap,
+        ops::Range,
     },
     crate::{
         makepad_platform::*,
@@ -190,6 +191,61 @@ impl CxFontsAtlas {
         font_id
     }
     
+    /// Returns the first font of `font_ids` that has a glyph for every character of `cluster`,
+    /// ignoring joiners and variation selectors. Falls back to the first loaded font, which
+    /// then draws its missing glyph.
+    pub fn font_for_cluster(&self, font_ids: impl Iterator<Item = usize> + Clone, cluster: &str) -> Option<usize> {
+        let mut first = None;
+        for font_id in font_ids {
+            let cxfont = if let Some(Some(cxfont)) = self.fonts.get(font_id) {cxfont} else {continue};
+            if first.is_none() {
+                first = Some(font_id);
+            }
+            let covers = cxfont.owned_font_face.with_ref( | face | {
+                cluster.chars().all( | c | is_default_ignorable(c) || face.glyph_index(c).is_some())
+            });
+            if covers {
+                return Some(font_id)
+            }
+        }
+        first
+    }
+    
+    /// Splits `text` into ranges that are each shaped with a single font of `font_ids`,
+    /// choosing per cluster with `font_for_cluster`.
+    pub fn split_runs_by_font(&self, font_ids: impl Iterator<Item = usize> + Clone, text: &str, runs: &mut Vec<(usize, Range<usize>)>) {
+        runs.clear();
+        if font_ids.clone().nth(1).is_none() {
+            if let Some(font_id) = self.font_for_cluster(font_ids, "") {
+                runs.push((font_id, 0..text.len()));
+            }
+            return
+        }
+        let mut start = 0;
+        while start < text.len() {
+            let end = cluster_end(text, start);
+            if let Some(font_id) = self.font_for_cluster(font_ids.clone(), &text[start..end]) {
+                match runs.last_mut() {
+                    Some((last_id, range)) if *last_id == font_id => range.end = end,
+                    _ => runs.push((font_id, start..end))
+                }
+            }
+            start = end;
+        }
+    }
+    
+    /// The advance of `c` in logical pixels at `font_size`, taken from the first font of
+    /// `font_ids` that has it.
+    pub fn char_advance(&mut self, font_ids: impl Iterator<Item = usize> + Clone, c: char, font_size: f64) -> Option<f64> {
+        let mut buf = [0u8; 4];
+        let font_id = self.font_for_cluster(font_ids, c.encode_utf8(&mut buf))?;
+        let cxfont = self.fonts[font_id].as_mut().unwrap();
+        let units_per_em = cxfont.ttf_font.units_per_em;
+        let glyph_id = cxfont.owned_font_face.with_ref( | face | face.glyph_index(c))?.0 as usize;
+        let glyph = cxfont.get_glyph_by_id(glyph_id).ok()?;
+        Some(glyph.horizontal_metrics.advance_width * font_size * 96.0 / (72.0 * units_per_em))
+    }
+    
     pub fn reset_fonts_atlas(&mut self) {
         for cxfont in &mut self.fonts {
             if let Some(cxfont) = cxfont {
@@ -387,6 +443,40 @@ impl<'a> Cx2d<'a> {
     }
 }
 
+// zero width joiners, variation selectors and tags, which a font doesn't need to cover
+fn is_default_ignorable(c: char) -> bool {
+    matches!(c, '\u{200C}'..='\u{200D}' | '\u{FE00}'..='\u{FE0F}' | '\u{E0020}'..='\u{E007F}' | '\u{E0100}'..='\u{E01EF}')
+}
+
+// characters that stay in the cluster of the character before them
+fn extends_cluster(c: char) -> bool {
+    is_default_ignorable(c) || matches!(c,
+        '\u{0300}'..='\u{036F}' | '\u{0483}'..='\u{0489}' | '\u{0591}'..='\u{05BD}' |
+        '\u{0610}'..='\u{061A}' | '\u{064B}'..='\u{065F}' | '\u{0670}' | '\u{06D6}'..='\u{06DC}' |
+        '\u{0900}'..='\u{0903}' | '\u{093A}'..='\u{094F}' | '\u{1AB0}'..='\u{1AFF}' |
+        '\u{1DC0}'..='\u{1DFF}' | '\u{20D0}'..='\u{20FF}' | '\u{3099}'..='\u{309A}' |
+        '\u{FE20}'..='\u{FE2F}' | '\u{1F3FB}'..='\u{1F3FF}'
+    )
+}
+
+// the end of the cluster starting at `start`. a base character takes its combining marks and
+// modifiers along, and a zero width joiner pulls in the character after it (emoji sequences)
+fn cluster_end(text: &str, start: usize) -> usize {
+    let mut chars = text[start..].char_indices();
+    let mut end = if let Some((_, c)) = chars.next() {start + c.len_utf8()} else {return start};
+    let mut joined = false;
+    for (i, c) in chars {
+        if joined || extends_cluster(c) {
+            joined = c == '\u{200D}';
+            end = start + i + c.len_utf8();
+        }
+        else {
+            break
+        }
+    }
+    end
+}
+
 pub struct CxFont {
     pub ttf_font: makepad_vector::font::TTFFont,
     pub owned_font_face: crate::owned_font_face::OwnedFace,
@@ -396,7 +486,14 @@ pub struct CxFont {
 
 pub struct ShapeCache {
     pub keys: VecDeque<(Direction, Rc<str>)>,
-    pub glyph_ids: HashMap<(Direction, Rc<str>), Vec<usize>>,
+    pub glyphs: HashMap<(Direction, Rc<str>), Vec<ShapedGlyph>>,
+}
+
+/// A glyph produced by shaping, with the byte offset of the cluster it belongs to
+#[derive(Clone, Copy, Debug)]
+pub struct ShapedGlyph {
+    pub id: usize,
+    pub cluster: usize,
 }
 
 impl ShapeCache {
@@ -406,14 +503,15 @@ impl ShapeCache {
     pub fn new() -> Self {
         Self {
             keys: VecDeque::new(),
-            glyph_ids: HashMap::new(),
+            glyphs: HashMap::new(),
         }
     }
 
     // If there is an entry for the given key in the cache, returns the corresponding list of
-    // glyph indices for that key. Otherwise, uses the given UnicodeBuffer and OwnedFace to
-    // compute the list of glyph indices for the key, inserts that in the cache and then returns
-    // the corresponding list.
+    // shaped glyphs for that key. Otherwise, uses the given UnicodeBuffer and OwnedFace to
+    // compute the list of shaped glyphs for the key, inserts that in the cache and then returns
+    // the corresponding list. The glyphs are in visual order, so for a right-to-left run
+    // their clusters go down.
     //
     // This method takes a UnicodeBuffer by value, and then returns the same buffer by value. This
     // is necessary because rustybuzz::shape consumes the UnicodeBuffer and then returns a
@@ -423,16 +521,16 @@ impl ShapeCache {
     //
     // Note that owned_font_face should be the same as the CxFont to which this cache belongs,
     // otherwise you will not get correct results.
-    pub fn get_or_compute_glyph_ids(
+    pub fn get_or_compute_glyphs(
         &mut self, 
         key: (Direction, &str),
         mut rustybuzz_buffer: UnicodeBuffer,
         owned_font_face: &crate::owned_font_face::OwnedFace
-    ) -> (&[usize], UnicodeBuffer) {
-        if !self.glyph_ids.contains_key(&key as &dyn ShapeCacheKey) {
+    ) -> (&[ShapedGlyph], UnicodeBuffer) {
+        if !self.glyphs.contains_key(&key as &dyn ShapeCacheKey) {
             if self.keys.len() == Self::MAX_SIZE {
                 for run in self.keys.drain(..Self::MAX_SIZE / 2) {
-                    self.glyph_ids.remove(&run);
+                    self.glyphs.remove(&run);
                 }
             }
 
@@ -440,14 +538,17 @@ impl ShapeCache {
             rustybuzz_buffer.set_direction(direction);
             rustybuzz_buffer.push_str(string);
             let glyph_buffer = owned_font_face.with_ref( | face | makepad_rustybuzz::shape(face, &[], rustybuzz_buffer));
-            let glyph_ids: Vec<_> = glyph_buffer.glyph_infos().iter().map( | glyph | glyph.glyph_id as usize).collect();
+            let glyphs: Vec<_> = glyph_buffer.glyph_infos().iter().map( | glyph | ShapedGlyph {
+                id: glyph.glyph_id as usize,
+                cluster: glyph.cluster as usize
+            }).collect();
             rustybuzz_buffer = glyph_buffer.clear();
 
             let owned_string: Rc<str> = string.into();
             self.keys.push_back((direction, owned_string.clone()));
-            self.glyph_ids.insert((direction, owned_string), glyph_ids);
+            self.glyphs.insert((direction, owned_string), glyphs);
         }
-        (&self.glyph_ids[&key as &dyn ShapeCacheKey], rustybuzz_buffer)
+        (&self.glyphs[&key as &dyn ShapeCacheKey], rustybuzz_buffer)
     }
 }
 
@@ -24,6 +24,9 @@ pub use crate::{
         Padding,
         Flow,
         Size,
+        GridTrack,
+        GridTracks,
+        GridCell,
         TurtleAlignRange,
         DeferWalk
     },
@@ -35,7 +38,13 @@ pub use crate::{
         NavOrder,
         NavStop,
         NavItem,
-        NavScrollIndex
+        NavScrollIndex,
+        NavDirection,
+        NavStopRect,
+        NavAccessible,
+        nav_tab_order,
+        nav_next,
+        nav_nearest,
     },
     draw_list_2d::{
         DrawList2d,
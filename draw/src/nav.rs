@@ -2,12 +2,15 @@
 use {
     std::rc::Rc,
     std::cell::RefCell,
+    std::collections::HashMap,
     crate::{
         cx_2d::Cx2d,
+        makepad_platform::{AccessibilityNode, AccessibilityRole, AccessibilityState, AccessibilityTree},
         makepad_platform::Area,
         makepad_platform::DrawListId,
         makepad_platform::Margin,
         makepad_platform::Cx,
+        makepad_math::{Rect, dvec2},
     }
 };
 
@@ -37,6 +40,9 @@ impl std::ops::IndexMut<DrawListId> for CxNavTree {
     }
 }
 
+/// Where a stop sits in the Tab order. `Top` stops come first, then `Middle` stops, then the
+/// `Default` stops in drawing order and finally the `Bottom` stops. Within a group the lower
+/// number goes first; equal numbers keep their drawing order.
 #[derive(Debug, Clone)]
 pub enum NavOrder {
     Default,
@@ -58,7 +64,35 @@ pub enum NavItem {
     Child(DrawListId),
     Stop(NavStop),
     BeginScroll(Area),
-    EndScroll(Area)
+    EndScroll(Area),
+    Accessible(NavAccessible),
+}
+
+/// How a widget describes itself in the accessibility tree. A nav stop or scroll area with the
+/// same area gets these details instead of the defaults from its role.
+#[derive(Debug, Clone)]
+pub struct NavAccessible {
+    pub area: Area,
+    pub role: AccessibilityRole,
+    pub name: String,
+    pub value: Option<String>,
+    pub state: AccessibilityState,
+}
+
+impl NavAccessible {
+    pub fn new(area: Area, role: AccessibilityRole, name: &str) -> Self {
+        Self {area, role, name: name.to_string(), value: None, state: AccessibilityState::default()}
+    }
+    
+    pub fn with_value(mut self, value: impl Into<String>) -> Self {
+        self.value = Some(value.into());
+        self
+    }
+    
+    pub fn with_state(mut self, state: AccessibilityState) -> Self {
+        self.state = state;
+        self
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -66,6 +100,124 @@ pub enum NavRole {
     TextInput,
     DropDown,
     Slider,
+    Button,
+    CheckBox,
+    RadioButton,
+    Tab,
+    Tree,
+}
+
+impl NavRole {
+    pub fn accessibility_role(&self) -> AccessibilityRole {
+        match self {
+            Self::TextInput => AccessibilityRole::TextInput,
+            Self::DropDown => AccessibilityRole::ComboBox,
+            Self::Slider => AccessibilityRole::Slider,
+            Self::Button => AccessibilityRole::Button,
+            Self::CheckBox => AccessibilityRole::CheckBox,
+            Self::RadioButton => AccessibilityRole::RadioButton,
+            Self::Tab => AccessibilityRole::Tab,
+            Self::Tree => AccessibilityRole::Tree,
+        }
+    }
+    
+    /// Whether a focused stop of this role handles the arrow key itself, in which case it
+    /// doesn't move the focus.
+    pub fn uses_arrow(&self, direction: NavDirection) -> bool {
+        match self {
+            Self::TextInput => true,
+            Self::Slider => matches!(direction, NavDirection::Left | NavDirection::Right),
+            Self::DropDown | Self::Tree => matches!(direction, NavDirection::Up | NavDirection::Down),
+            Self::Button | Self::CheckBox | Self::RadioButton | Self::Tab => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// A nav stop with its rect on screen (margin included) and the scroll areas it is nested in,
+/// outermost first.
+#[derive(Debug, Clone)]
+pub struct NavStopRect {
+    pub stop: NavStop,
+    pub rect: Rect,
+    pub scroll_stack: Vec<Area>,
+}
+
+impl NavOrder {
+    fn rank(&self) -> (u8, u64) {
+        match self {
+            Self::Top(n) => (0, *n),
+            Self::Middle(n) => (1, *n),
+            Self::Default => (2, 0),
+            Self::Bottom(n) => (3, *n),
+        }
+    }
+}
+
+/// The indices of `stops` in Tab order.
+pub fn nav_tab_order(stops: &[NavStopRect]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..stops.len()).collect();
+    order.sort_by_key( | i | stops[*i].stop.order.rank());
+    order
+}
+
+/// The stop after `current` in Tab order, or before it when going `backward`. Wraps around at
+/// either end; without a current stop it starts at the first (or last) one.
+pub fn nav_next(stops: &[NavStopRect], current: Option<usize>, backward: bool) -> Option<usize> {
+    let order = nav_tab_order(stops);
+    if order.is_empty() {
+        return None
+    }
+    let pos = current.and_then( | current | order.iter().position( | i | *i == current));
+    let next = match (pos, backward) {
+        (None, false) => 0,
+        (None, true) => order.len() - 1,
+        (Some(pos), false) => (pos + 1) % order.len(),
+        (Some(pos), true) => (pos + order.len() - 1) % order.len(),
+    };
+    Some(order[next])
+}
+
+/// The stop nearest to `current` in `direction`. Only stops that lie beyond the current one in
+/// that direction count; the distance along the direction is weighed against twice the
+/// sideways gap, so a stop straight ahead beats a closer one off to the side.
+pub fn nav_nearest(stops: &[NavStopRect], current: usize, direction: NavDirection) -> Option<usize> {
+    // (start, end) along the direction and across it, flipped so the direction points to +
+    let spans = | rect: Rect | {
+        let x = (rect.pos.x, rect.pos.x + rect.size.x);
+        let y = (rect.pos.y, rect.pos.y + rect.size.y);
+        match direction {
+            NavDirection::Right => (x, y),
+            NavDirection::Left => ((-x.1, -x.0), y),
+            NavDirection::Down => (y, x),
+            NavDirection::Up => ((-y.1, -y.0), x),
+        }
+    };
+    let (cur_major, cur_minor) = spans(stops.get(current)?.rect);
+    let mut best: Option<(f64, usize)> = None;
+    for (i, stop) in stops.iter().enumerate() {
+        if i == current {
+            continue
+        }
+        let (major, minor) = spans(stop.rect);
+        if major.0 < cur_major.0 || major.1 <= cur_major.1 || major.0 + major.1 <= cur_major.0 + cur_major.1 {
+            continue
+        }
+        let along = (major.0 - cur_major.1).max(0.0);
+        let across = (minor.0 - cur_minor.1).max(cur_minor.0 - minor.1).max(0.0);
+        let score = along + 2.0 * across;
+        if best.is_none_or( | (best, _) | score < best) {
+            best = Some((score, i));
+        }
+    }
+    best.map( | (_, i) | i)
 }
 
 impl<'a> Cx2d<'a> {
@@ -102,6 +254,7 @@ impl<'a> Cx2d<'a> {
                     NavItem::EndScroll(area)=>{
                         if *area != scroll_stack.pop().unwrap(){panic!()};
                     }
+                    NavItem::Accessible(_) => ()
                 }
             }
             None
@@ -114,6 +267,138 @@ impl<'a> Cx2d<'a> {
         }
     }
     
+    /// All nav stops under `root` in drawing order, with their rects. Stops that aren't on
+    /// screen anymore are left out.
+    pub fn nav_stop_rects(cx: &mut Cx, root: DrawListId) -> Vec<NavStopRect> {
+        let nav_tree_rc = cx.get_global::<CxNavTreeRc>().clone();
+        let nav_tree = &*nav_tree_rc.0.borrow();
+        fn collect(cx: &Cx, scroll_stack: &mut Vec<Area>, nav_tree: &CxNavTree, draw_list_id: DrawListId, out: &mut Vec<NavStopRect>) {
+            let Some(nav_list) = nav_tree.nav_lists.get(draw_list_id.index()) else {
+                return
+            };
+            for nav_item in &nav_list.nav_list {
+                match nav_item {
+                    NavItem::Child(draw_list_id) => collect(cx, scroll_stack, nav_tree, *draw_list_id, out),
+                    NavItem::Stop(stop) => {
+                        if !stop.area.is_valid(cx) {
+                            continue
+                        }
+                        let rect = stop.area.rect(cx);
+                        if rect.size.x <= 0.0 || rect.size.y <= 0.0 {
+                            continue
+                        }
+                        let margin = &stop.margin;
+                        out.push(NavStopRect {
+                            stop: stop.clone(),
+                            rect: Rect {
+                                pos: rect.pos - dvec2(margin.left, margin.top),
+                                size: rect.size + dvec2(margin.left + margin.right, margin.top + margin.bottom),
+                            },
+                            scroll_stack: scroll_stack.clone(),
+                        });
+                    }
+                    NavItem::BeginScroll(area) => scroll_stack.push(*area),
+                    NavItem::EndScroll(_) => {
+                        scroll_stack.pop();
+                    }
+                    NavItem::Accessible(_) => ()
+                }
+            }
+        }
+        let mut out = Vec::new();
+        collect(cx, &mut Vec::new(), nav_tree, root, &mut out);
+        out
+    }
+    
+    /// Assembles the accessibility tree under `root` below the given window node. Scroll areas
+    /// become nodes holding what is drawn in them, nav stops and described widgets become
+    /// leaves; whatever isn't on screen anymore is left out.
+    pub fn accessibility_tree(cx: &mut Cx, root: DrawListId, window: AccessibilityNode) -> AccessibilityTree {
+        let nav_tree_rc = cx.get_global::<CxNavTreeRc>().clone();
+        let nav_tree = &*nav_tree_rc.0.borrow();
+        
+        struct Builder<'a> {
+            cx: &'a Cx,
+            nav_tree: &'a CxNavTree,
+            tree: AccessibilityTree,
+            areas: HashMap<Area, usize>,
+            parents: Vec<usize>,
+        }
+        
+        impl Builder<'_> {
+            // the node for `area`, made with `role` if there is none yet
+            fn node(&mut self, area: Area, role: AccessibilityRole) -> Option<usize> {
+                if let Some(index) = self.areas.get(&area) {
+                    return Some(*index)
+                }
+                if !area.is_valid(self.cx) {
+                    return None
+                }
+                let bounds = area.rect(self.cx);
+                if bounds.size.x <= 0.0 || bounds.size.y <= 0.0 {
+                    return None
+                }
+                let mut node = AccessibilityNode::new(role, "");
+                node.bounds = bounds;
+                let index = self.tree.push(*self.parents.last().unwrap(), node);
+                self.areas.insert(area, index);
+                Some(index)
+            }
+            
+            fn collect(&mut self, draw_list_id: DrawListId) {
+                let Some(nav_list) = self.nav_tree.nav_lists.get(draw_list_id.index()) else {
+                    return
+                };
+                for nav_item in &nav_list.nav_list {
+                    match nav_item {
+                        NavItem::Child(draw_list_id) => self.collect(*draw_list_id),
+                        NavItem::BeginScroll(area) => {
+                            let parent = *self.parents.last().unwrap();
+                            let index = self.node(*area, AccessibilityRole::ScrollArea).unwrap_or(parent);
+                            self.parents.push(index);
+                        }
+                        NavItem::EndScroll(_) => if self.parents.len() > 1 {
+                            self.parents.pop();
+                        }
+                        NavItem::Stop(stop) => if let Some(index) = self.node(stop.area, stop.role.accessibility_role()) {
+                            self.tree.nodes[index].state.focusable = true;
+                        }
+                        NavItem::Accessible(accessible) => if let Some(index) = self.node(accessible.area, accessible.role) {
+                            let node = &mut self.tree.nodes[index];
+                            let focusable = node.state.focusable;
+                            node.role = accessible.role;
+                            node.name = accessible.name.clone();
+                            node.value = accessible.value.clone();
+                            node.state = accessible.state;
+                            node.state.focusable |= focusable;
+                        }
+                    }
+                }
+            }
+        }
+        
+        let mut builder = Builder {
+            cx,
+            nav_tree,
+            tree: AccessibilityTree::new(window),
+            areas: HashMap::new(),
+            parents: vec![0],
+        };
+        builder.collect(root);
+        let Builder {mut tree, areas, ..} = builder;
+        for (area, index) in areas {
+            tree.nodes[index].state.focused = cx.has_key_focus(area);
+        }
+        tree
+    }
+    
+    pub fn add_accessible(&mut self, accessible: NavAccessible) {
+        // root widgets finish outside of any draw list, they are the window itself
+        if let Some(draw_list_id) = self.draw_list_stack.last().cloned() {
+            self.nav_list_item_push(draw_list_id, NavItem::Accessible(accessible));
+        }
+    }
+    
     pub fn nav_list_clear(&mut self, draw_list_id: DrawListId) {
         let mut nav_tree = self.nav_tree_rc.0.borrow_mut();
         if draw_list_id.index() >= nav_tree.nav_lists.len() {
@@ -128,11 +413,15 @@ impl<'a> Cx2d<'a> {
     }
     
     pub fn add_nav_stop(&mut self, area: Area, role: NavRole, margin: Margin) {
+        self.add_nav_stop_with_order(area, role, margin, NavOrder::Default);
+    }
+    
+    pub fn add_nav_stop_with_order(&mut self, area: Area, role: NavRole, margin: Margin, order: NavOrder) {
         let draw_list_id = *self.draw_list_stack.last().unwrap();
         self.nav_list_item_push(draw_list_id, NavItem::Stop(NavStop {
             role,
             area,
-            order: NavOrder::Default,
+            order,
             margin
         }));
     }
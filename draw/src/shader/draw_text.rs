@@ -1,8 +1,9 @@
 use {
+    std::ops::Range,
     crate::{
         makepad_platform::*,
         turtle::{Walk, Size, Align},
-        font_atlas::{CxFontsAtlasTodo, CxFont, CxFontsAtlas, Font},
+        font_atlas::{CxFontsAtlasTodo, CxFontsAtlas, Font},
         draw_list_2d::ManyInstances,
         geometry::GeometryQuad2D,
         cx_2d::Cx2d
@@ -142,6 +143,8 @@ const _: () = assert!(crate::font_atlas::ATLAS_HEIGHT == 4096);
 #[live_ignore]
 pub struct TextStyle {
     #[live()] pub font: Font,
+    /// Fonts tried in order for the characters `font` has no glyph for
+    #[live] pub font_fallbacks: Vec<Font>,
     #[live(9.0)] pub font_size: f64,
     #[live(1.0)] pub brightness: f32,
     #[live(0.5)] pub curve: f32,
@@ -150,6 +153,13 @@ pub struct TextStyle {
     #[live(1.3)] pub height_factor: f64,
 }
 
+impl TextStyle {
+    /// The loaded font ids in fallback order, starting with `font`
+    pub fn font_ids(&self) -> impl Iterator<Item = usize> + Clone + '_ {
+        self.font.font_id.into_iter().chain(self.font_fallbacks.iter().filter_map( | font | font.font_id))
+    }
+}
+
 #[derive(Clone, Live, LiveHook)]
 #[live_ignore]
 pub enum TextWrap {
@@ -158,11 +168,12 @@ pub enum TextWrap {
     Line
 }
 
-struct WordIterator<'a> {
+struct WordIterator<'a, F> {
     char_iter: std::str::CharIndices<'a >,
     eval_width: f64,
     last_char: char,
     last_index: usize,
+    font_ids: F,
     font_size_total: f64,
     ignore_newlines: bool,
     combine_spaces: bool,
@@ -181,13 +192,14 @@ enum WordItem{
     Word{start:usize, end: usize, width: f64}
 }
 
-impl<'a> WordIterator<'a> {
-    fn new(char_iter: std::str::CharIndices<'a>, eval_width: f64, font_size_total: f64, ignore_newlines:bool, combine_spaces:bool) -> Self {
+impl<'a, F> WordIterator<'a, F> where F: Iterator<Item = usize> + Clone {
+    fn new(char_iter: std::str::CharIndices<'a>, eval_width: f64, font_ids: F, font_size_total: f64, ignore_newlines:bool, combine_spaces:bool) -> Self {
         let mut s = Self {
             eval_width,
             char_iter: char_iter,
             last_char:'\0',
             last_index:0,
+            font_ids,
             font_size_total,
             ignore_newlines,
             combine_spaces
@@ -207,21 +219,19 @@ impl<'a> WordIterator<'a> {
         };
     }
     
-    fn next_word(&mut self, font: &mut CxFont) -> Option<WordItem> {
+    fn next_word(&mut self, fonts_atlas: &mut CxFontsAtlas) -> Option<WordItem> {
         if self.last_char == '\0'{
             return None
         }
         else if self.last_char == '\n'{ // return newline
             self.next_char();
             if self.ignore_newlines{
-                return self.next_word(font);
+                return self.next_word(fonts_atlas);
             }
             return Some(WordItem::Newline);
         }
         else if self.last_char == ' '{
-            let adv = if let Some(glyph) = font.get_glyph(' ') {
-                glyph.horizontal_metrics.advance_width * self.font_size_total
-            }else {0.0};
+            let adv = fonts_atlas.char_advance(self.font_ids.clone(), ' ', self.font_size_total).unwrap_or(0.0);
             let start = self.last_index;
             let mut width = 0.0;
             while self.last_char == ' '{
@@ -253,9 +263,7 @@ impl<'a> WordIterator<'a> {
             let start = self.last_index;
             let mut width = 0.0;
             while self.last_char != ' ' && self.last_char != '\0' && self.last_char != '\n' {
-                let adv = if let Some(glyph) = font.get_glyph(self.last_char) {
-                    glyph.horizontal_metrics.advance_width * self.font_size_total
-                }else {0.0};
+                let adv = fonts_atlas.char_advance(self.font_ids.clone(), self.last_char, self.font_size_total).unwrap_or(0.0);
                 if width + adv >= self.eval_width{
                     if start == self.last_index{// advance atleast one char
                         width += adv;
@@ -277,6 +285,92 @@ impl<'a> WordIterator<'a> {
     }
 }
 
+/// Where a drawn glyph comes from. Character indices skip newlines, which aren't drawn
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GlyphCluster {
+    /// Index of the first character of the cluster
+    pub index: usize,
+    /// Number of characters the cluster covers, more than one for ligatures
+    pub len: usize,
+    pub rtl: bool,
+}
+
+// a word or run of spaces on a wrapped line
+struct LineItem {
+    start: usize,
+    end: usize,
+    width: f64,
+    char_offset: usize,
+}
+
+// turns byte offsets, visited in increasing order, into character indices that skip newlines
+struct DrawnCharIndex<'a> {
+    text: &'a str,
+    byte: usize,
+    index: usize,
+}
+
+impl<'a> DrawnCharIndex<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {text, byte: 0, index: 0}
+    }
+    
+    fn at(&mut self, byte: usize) -> usize {
+        self.index += self.text[self.byte..byte].chars().filter( | c | *c != '\n').count();
+        self.byte = byte;
+        self.index
+    }
+}
+
+// a drawn glyph as the cursor functions see it
+struct GlyphBox {
+    x: f64,
+    y: f64,
+    advance: f64,
+    cluster: GlyphCluster,
+}
+
+impl GlyphBox {
+    // the x of the position `t` of the way through the cluster, in reading order
+    fn cursor_x(&self, t: f64) -> f64 {
+        if self.cluster.rtl {
+            self.x + self.advance * (1.0 - t)
+        }
+        else {
+            self.x + self.advance * t
+        }
+    }
+    
+    fn distance_x(&self, x: f64) -> f64 {
+        if x < self.x {self.x - x}
+        else if x > self.x + self.advance {x - self.x - self.advance}
+        else {0.0}
+    }
+}
+
+// splits the glyph boxes into lines
+fn glyph_lines(boxes: &[GlyphBox]) -> Vec<Range<usize>> {
+    let mut lines: Vec<Range<usize>> = Vec::new();
+    for (i, b) in boxes.iter().enumerate() {
+        match lines.last_mut() {
+            Some(line) if (b.y - boxes[line.start].y).abs() < 0.001 => line.end = i + 1,
+            _ => lines.push(i..i + 1)
+        }
+    }
+    lines
+}
+
+// turns a character index that skips newlines back into an index into the text
+fn drawn_to_text_index(newline_indexes: &[usize], index: usize) -> usize {
+    let mut index = index;
+    for &newline in newline_indexes {
+        if newline <= index {
+            index += 1;
+        }
+    }
+    index
+}
+
 pub struct TextGeom {
     pub eval_width: f64,
     pub eval_height: f64,
@@ -289,6 +383,8 @@ pub struct TextGeom {
 #[repr(C)]
 pub struct DrawText {
     #[rust] pub many_instances: Option<ManyInstances>,
+    /// The cluster of every glyph instance in the current batch, in instance order
+    #[rust] pub glyph_clusters: Vec<GlyphCluster>,
     
     #[live] pub geometry: GeometryQuad2D,
     #[live] pub text_style: TextStyle,
@@ -326,21 +422,21 @@ impl LiveHook for DrawText {
 impl DrawText {
     
     pub fn draw(&mut self, cx: &mut Cx2d, pos: DVec2, val: &str) {
-        self.draw_inner(cx, pos, val, &mut *cx.fonts_atlas_rc.clone().0.borrow_mut());
+        self.draw_inner(cx, pos, val, 0, &mut *cx.fonts_atlas_rc.clone().0.borrow_mut());
         if self.many_instances.is_some() {
             self.end_many_instances(cx)
         }
     }
     
     pub fn draw_rel(&mut self, cx: &mut Cx2d, pos: DVec2, val: &str) {
-        self.draw_inner(cx, pos + cx.turtle().origin(), val, &mut *cx.fonts_atlas_rc.clone().0.borrow_mut());
+        self.draw_inner(cx, pos + cx.turtle().origin(), val, 0, &mut *cx.fonts_atlas_rc.clone().0.borrow_mut());
         if self.many_instances.is_some() {
             self.end_many_instances(cx)
         }
     }
     
     pub fn draw_abs(&mut self, cx: &mut Cx2d, pos: DVec2, val: &str) {
-        self.draw_inner(cx, pos, val, &mut *cx.fonts_atlas_rc.clone().0.borrow_mut());
+        self.draw_inner(cx, pos, val, 0, &mut *cx.fonts_atlas_rc.clone().0.borrow_mut());
         if self.many_instances.is_some() {
             self.end_many_instances(cx)
         }
@@ -353,6 +449,7 @@ impl DrawText {
     }
     
     fn begin_many_instances_internal(&mut self, cx: &mut Cx2d, fonts_atlas: &CxFontsAtlas) {
+        self.glyph_clusters.clear();
         self.update_draw_call_vars(fonts_atlas);
         let mi = cx.begin_many_aligned_instances(&self.draw_vars);
         self.many_instances = mi;
@@ -379,49 +476,38 @@ impl DrawText {
         self.draw_vars.user_uniforms[3] = sdf_cutoff;
     }
     
-    fn draw_inner(&mut self, cx: &mut Cx2d, pos: DVec2, chunk: &str, fonts_atlas: &mut CxFontsAtlas) {
+    // checks we can draw at `pos` and makes sure there is an instance batch to draw into
+    fn begin_draw_inner(&mut self, cx: &mut Cx2d, pos: DVec2, fonts_atlas: &mut CxFontsAtlas) -> bool {
         if !self.draw_vars.can_instance()
             || pos.x.is_nan()
             || pos.y.is_nan()
             || self.text_style.font.font_id.is_none() {
-            return
+            return false
         }
         //self.draw_clip = cx.turtle().draw_clip().into();
         //let in_many = self.many_instances.is_some();
         let font_id = self.text_style.font.font_id.unwrap();
         
         if fonts_atlas.fonts[font_id].is_none() {
-            return
+            return false
         }
         
         //cx.debug.rect_r(Rect{pos:dvec2(1.0,2.0), size:dvec2(200.0,300.0)});
-        let mut walk_x = pos.x;
-        if walk_x.is_infinite() || walk_x.is_nan() {
-            return
+        if pos.x.is_infinite() {
+            return false
         }
-        //let mut char_offset = char_offset;
-        if !self.many_instances.is_some() {
+        if self.many_instances.is_none() {
             self.begin_many_instances_internal(cx, fonts_atlas);
         }
-        
-        let cxfont = fonts_atlas.fonts[font_id].as_mut().unwrap();
-        let dpi_factor = cx.current_dpi_factor();
-        
-        let atlas_page_id = cxfont.get_atlas_page_id(dpi_factor, self.text_style.font_size);
-        
-        let font = &mut cxfont.ttf_font;
-        let owned_font_face = &cxfont.owned_font_face;
-        
-        let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * font.units_per_em);
-        let font_size_pixels = font_size_logical * dpi_factor;
-        
-        let atlas_page = &mut cxfont.atlas_pages[atlas_page_id];
-        
-        let mi = if let Some(mi) = &mut self.many_instances {mi} else {return};
-        let zbias_step = 0.00001;
-        let mut char_depth = self.draw_depth;
-        
-        let mut rustybuzz_buffer = makepad_rustybuzz::UnicodeBuffer::new();
+        self.many_instances.is_some()
+    }
+    
+    // draws `chunk` as its own paragraph. `char_offset` is the index of its first character
+    // in the text the cursor functions see
+    fn draw_inner(&mut self, cx: &mut Cx2d, pos: DVec2, chunk: &str, char_offset: usize, fonts_atlas: &mut CxFontsAtlas) {
+        if !self.begin_draw_inner(cx, pos, fonts_atlas) {
+            return
+        }
         
         // This relies on the UBA ("Unicode Bidirectional Algorithm")
         // (see http://www.unicode.org/reports/tr9/#Basic_Display_Algorithm),
@@ -432,82 +518,189 @@ impl DrawText {
         // NOTE(eddyb) the caller of `draw_inner` has already processed the text,
         // such that `chunk` won't contain e.g. any `\n`.
         if bidi_info.paragraphs.len() == 1 {
-            let runs_with_level_and_range = {
-                let para = &bidi_info.paragraphs[0];
-                // Split `chunk` into "runs" (that differ in their LTR/RTL "level").
-                let (adjusted_levels, runs) = bidi_info.visual_runs(para, para.range.clone());
-                runs.into_iter().map(move | run_range | (adjusted_levels[run_range.start], run_range))
-            };
-            
-            for (run_level, run_range) in runs_with_level_and_range {
-                // FIXME(eddyb) UBA/`unicode_bidi` only offers a LTR/RTL distinction,
-                // even if `rustybuzz` has vertical `Direction`s as well.
-                let (glyph_ids, new_rustybuzz_buffer) = cxfont
-                    .shape_cache
-                    .get_or_compute_glyph_ids(
-                    (
-                            if run_level.is_rtl() {
-                                makepad_rustybuzz::Direction::RightToLeft
-                            } else {
-                                makepad_rustybuzz::Direction::LeftToRight
-                            },
-                            &bidi_info.text[run_range]
-                        ),
-                        rustybuzz_buffer,
-                        owned_font_face
-                    );
-                rustybuzz_buffer = new_rustybuzz_buffer;
-                for &glyph_id in glyph_ids {
-                    let glyph = owned_font_face.with_ref(|face| font.get_glyph_by_id(face, glyph_id).unwrap());
-                    
-                    let advance = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
-                    
-                    // HACK(eddyb) this is a different padding from the SDF padding,
-                    // this allows the glyph rasterization to avoid touching the
-                    // edges of the raster area, while the SDF padding exists for
-                    // e.g. bilinear sampling to have excess texels to sample.
-                    let pad_dpx = 2.0;
-                    let w_dpx = ((glyph.bounds.p_max.x - glyph.bounds.p_min.x) * font_size_pixels).ceil() + pad_dpx * 2.0;
-                    let h_dpx = ((glyph.bounds.p_max.y - glyph.bounds.p_min.y) * font_size_pixels).ceil() + pad_dpx * 2.0;
-                    let (w_dpx, h_dpx) = if w_dpx <= pad_dpx * 2.0{(0.0,0.0)}else { (w_dpx, h_dpx) };
-                                        
-                    let tc = *atlas_page.atlas_glyphs.entry(glyph_id).or_insert_with(|| {
-                        // see if we can fit it
-                        // allocate slot
-                        fonts_atlas.alloc.alloc_atlas_glyph(w_dpx, h_dpx, CxFontsAtlasTodo {
-                            font_id,
-                            atlas_page_id,
-                            glyph_id,
-                        })
-                    });
-
-                    let pad = pad_dpx * self.font_scale / dpi_factor;
-                    let w = w_dpx * self.font_scale / dpi_factor;
-                    let h = h_dpx * self.font_scale / dpi_factor;
-                    
-                    let delta_x = font_size_logical * self.font_scale * glyph.bounds.p_min.x - pad;
-                    let delta_y = -(font_size_logical * self.font_scale * glyph.bounds.p_min.y - pad)
-                        + self.text_style.font_size * self.font_scale * self.text_style.top_drop;
-                    // give the callback a chance to do things
-                    //et scaled_min_pos_x = walk_x + delta_x;
-                    //let scaled_min_pos_y = pos.y - delta_y;
-                    self.font_t1 = tc.t1;
-                    self.font_t2 = tc.t2;
-                    self.rect_pos = dvec2(walk_x + delta_x, pos.y + delta_y).into();
-                    self.rect_size = dvec2(w, h).into();
-                    self.char_depth = char_depth;
-                    self.delta.x = delta_x as f32;
-                    self.delta.y = delta_y as f32;
-                    self.shader_font_size = self.text_style.font_size as f32;
-                    self.advance = advance as f32; //char_offset as f32;
-                    char_depth += zbias_step;
-                    mi.instances.extend_from_slice(self.draw_vars.as_slice());
-                    walk_x += advance;
+            let para = &bidi_info.paragraphs[0];
+            // Split `chunk` into "runs" (that differ in their LTR/RTL "level").
+            let (adjusted_levels, runs) = bidi_info.visual_runs(para, para.range.clone());
+            let mut walk_x = pos.x;
+            for run_range in runs {
+                let run_char_offset = char_offset + chunk[..run_range.start].chars().count();
+                walk_x += self.draw_run(
+                    cx,
+                    dvec2(walk_x, pos.y),
+                    &chunk[run_range.clone()],
+                    adjusted_levels[run_range.start].is_rtl(),
+                    run_char_offset,
+                    fonts_atlas
+                );
+            }
+        }
+    }
+    
+    // draws one wrapped line of the text of `bidi_info` in visual order. `items` are the pieces
+    // of the line in logical order. lines of a right-to-left paragraph start at the right of `width`
+    fn draw_line(&mut self, cx: &mut Cx2d, pos: DVec2, width: f64, bidi_info: &unicode_bidi::BidiInfo, items: &[LineItem], fonts_atlas: &mut CxFontsAtlas) {
+        let text = bidi_info.text;
+        let (first, last) = if let (Some(first), Some(last)) = (items.first(), items.last()) {(first, last)} else {return};
+        let line_range = first.start..last.end;
+        let para = bidi_info.paragraphs.iter().find( | para | para.range.contains(&first.start));
+        let para = if let Some(para) = para {para} else {return};
+        
+        if !para.level.is_rtl() && !bidi_info.levels[line_range.clone()].iter().any( | level | level.is_rtl()) {
+            let mut x = 0.0;
+            for item in items {
+                self.draw_inner(cx, pos + dvec2(x, 0.0), &text[item.start..item.end], item.char_offset, fonts_atlas);
+                x += item.width;
+            }
+            return
+        }
+        if !self.begin_draw_inner(cx, pos, fonts_atlas) {
+            return
+        }
+        let (levels, runs) = bidi_info.visual_runs(para, line_range);
+        let mut x = pos.x;
+        if para.level.is_rtl() {
+            x += (width - items.iter().map( | item | item.width).sum::<f64>()).max(0.0);
+        }
+        for run in runs {
+            let rtl = levels[run.start].is_rtl();
+            let mut pieces: Vec<_> = items.iter().filter_map( | item | {
+                let start = item.start.max(run.start);
+                let end = item.end.min(run.end);
+                if start >= end {
+                    return None
                 }
+                Some((start, end, item.char_offset + text[item.start..start].chars().count()))
+            }).collect();
+            if rtl {
+                pieces.reverse();
+            }
+            for (start, end, char_offset) in pieces {
+                x += self.draw_run(cx, dvec2(x, pos.y), &text[start..end], rtl, char_offset, fonts_atlas);
             }
         }
+    }
+    
+    // shapes and draws a run of a single direction, returning its advance.
+    // `begin_draw_inner` must have succeeded before
+    fn draw_run(&mut self, cx: &mut Cx2d, pos: DVec2, run: &str, rtl: bool, char_offset: usize, fonts_atlas: &mut CxFontsAtlas) -> f64 {
+        let dpi_factor = cx.current_dpi_factor();
+        
+        let mi = if let Some(mi) = &mut self.many_instances {mi} else {return 0.0};
+        let zbias_step = 0.00001;
+        let mut char_depth = self.draw_depth;
+        let mut walk_x = pos.x;
+        
+        // FIXME(eddyb) UBA/`unicode_bidi` only offers a LTR/RTL distinction,
+        // even if `rustybuzz` has vertical `Direction`s as well.
+        let direction = if rtl {
+            makepad_rustybuzz::Direction::RightToLeft
+        } else {
+            makepad_rustybuzz::Direction::LeftToRight
+        };
+        let mut rustybuzz_buffer = makepad_rustybuzz::UnicodeBuffer::new();
+        
+        // the byte offset of every char, to turn glyph clusters into char indices
+        let char_starts: Vec<usize> = run.char_indices().map( | (i, _) | i).chain(Some(run.len())).collect();
         
+        // split the run over the fonts of the fallback chain, each part is shaped
+        // with its own font. the parts of a RTL run are laid out back to front
+        let mut font_runs = Vec::new();
+        fonts_atlas.split_runs_by_font(self.text_style.font_ids(), run, &mut font_runs);
+        if rtl {
+            font_runs.reverse();
+        }
+        for (font_id, font_range) in font_runs {
+            let cxfont = fonts_atlas.fonts[font_id].as_mut().unwrap();
+            let atlas_page_id = cxfont.get_atlas_page_id(dpi_factor, self.text_style.font_size);
+            
+            let font = &mut cxfont.ttf_font;
+            let owned_font_face = &cxfont.owned_font_face;
+            
+            let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * font.units_per_em);
+            let font_size_pixels = font_size_logical * dpi_factor;
+            
+            let atlas_page = &mut cxfont.atlas_pages[atlas_page_id];
+            
+            let (glyphs, new_rustybuzz_buffer) = cxfont
+                .shape_cache
+                .get_or_compute_glyphs(
+                    (direction, &run[font_range.clone()]),
+                    rustybuzz_buffer,
+                    owned_font_face
+                );
+            rustybuzz_buffer = new_rustybuzz_buffer;
+            
+            // clusters sorted in logical order, so each one knows where the next starts
+            let mut clusters: Vec<usize> = glyphs.iter().map( | glyph | font_range.start + glyph.cluster).collect();
+            clusters.sort_unstable();
+            clusters.dedup();
+            
+            for glyph_info in glyphs {
+                let glyph = owned_font_face.with_ref(|face| font.get_glyph_by_id(face, glyph_info.id).unwrap());
+                let glyph_id = glyph_info.id;
+                
+                let cluster = font_range.start + glyph_info.cluster;
+                let next_cluster = match clusters.binary_search(&cluster) {
+                    Ok(i) => clusters.get(i + 1).copied().unwrap_or(font_range.end),
+                    Err(_) => font_range.end
+                };
+                let char_index = char_starts.partition_point( | &start | start < cluster);
+                let char_end = char_starts.partition_point( | &start | start < next_cluster);
+                self.glyph_clusters.push(GlyphCluster {
+                    index: char_offset + char_index,
+                    len: (char_end - char_index).max(1),
+                    rtl
+                });
+                
+                let advance = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
+                
+                // HACK(eddyb) this is a different padding from the SDF padding,
+                // this allows the glyph rasterization to avoid touching the
+                // edges of the raster area, while the SDF padding exists for
+                // e.g. bilinear sampling to have excess texels to sample.
+                let pad_dpx = 2.0;
+                let w_dpx = ((glyph.bounds.p_max.x - glyph.bounds.p_min.x) * font_size_pixels).ceil() + pad_dpx * 2.0;
+                let h_dpx = ((glyph.bounds.p_max.y - glyph.bounds.p_min.y) * font_size_pixels).ceil() + pad_dpx * 2.0;
+                let (w_dpx, h_dpx) = if w_dpx <= pad_dpx * 2.0{(0.0,0.0)}else { (w_dpx, h_dpx) };
+                
+                // every font of the chain allocates into the same atlas texture
+                let tc = *atlas_page.atlas_glyphs.entry(glyph_id).or_insert_with(|| {
+                    // see if we can fit it
+                    // allocate slot
+                    fonts_atlas.alloc.alloc_atlas_glyph(w_dpx, h_dpx, CxFontsAtlasTodo {
+                        font_id,
+                        atlas_page_id,
+                        glyph_id,
+                    })
+                });
+                
+                let pad = pad_dpx * self.font_scale / dpi_factor;
+                let w = w_dpx * self.font_scale / dpi_factor;
+                let h = h_dpx * self.font_scale / dpi_factor;
+                
+                let delta_x = font_size_logical * self.font_scale * glyph.bounds.p_min.x - pad;
+                let delta_y = -(font_size_logical * self.font_scale * glyph.bounds.p_min.y - pad)
+                    + self.text_style.font_size * self.font_scale * self.text_style.top_drop;
+                // give the callback a chance to do things
+                //et scaled_min_pos_x = walk_x + delta_x;
+                //let scaled_min_pos_y = pos.y - delta_y;
+                self.font_t1 = tc.t1;
+                self.font_t2 = tc.t2;
+                self.rect_pos = dvec2(walk_x + delta_x, pos.y + delta_y).into();
+                self.rect_size = dvec2(w, h).into();
+                self.char_depth = char_depth;
+                self.delta.x = delta_x as f32;
+                self.delta.y = delta_y as f32;
+                self.shader_font_size = self.text_style.font_size as f32;
+                self.advance = advance as f32; //char_offset as f32;
+                char_depth += zbias_step;
+                mi.instances.extend_from_slice(self.draw_vars.as_slice());
+                walk_x += advance;
+            }
+        }
+        walk_x - pos.x
     }
+    
     pub fn compute_geom(&self, cx: &Cx2d, walk: Walk, text: &str) -> Option<TextGeom> {
         self.compute_geom_inner(cx, walk, text, &mut *cx.fonts_atlas_rc.0.borrow_mut())
     }
@@ -520,19 +713,14 @@ impl DrawText {
             return None
         }
         
-        let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * fonts_atlas.fonts[font_id].as_ref().unwrap().ttf_font.units_per_em);
+        let font_size = self.text_style.font_size * self.font_scale;
         let line_height = self.text_style.font_size * self.text_style.height_factor * self.font_scale;
-        let eval_width = cx.turtle().eval_width(walk.width, walk.margin, cx.turtle().layout().flow);
-        let eval_height = cx.turtle().eval_height(walk.height, walk.margin, cx.turtle().layout().flow);
+        let eval_width = cx.turtle().eval_width(&walk, cx.turtle().layout().flow);
+        let eval_height = cx.turtle().eval_height(&walk, cx.turtle().layout().flow);
         
         match if walk.width.is_fit() {&TextWrap::Line}else {&self.wrap} {
             TextWrap::Ellipsis => {
-                let ellip_width = if let Some(glyph) = fonts_atlas.fonts[font_id].as_mut().unwrap().get_glyph('.') {
-                    glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale
-                }
-                else {
-                    0.0
-                };
+                let ellip_width = fonts_atlas.char_advance(self.text_style.font_ids(), '.', font_size).unwrap_or(0.0);
                 
                 let mut measured_width = 0.0;
                 let mut ellip_pt = None;
@@ -541,8 +729,7 @@ impl DrawText {
                     if measured_width + ellip_width * 3.0 < eval_width {
                         ellip_pt = Some((i, measured_width, 3));
                     }
-                    if let Some(glyph) = fonts_atlas.fonts[font_id].as_mut().unwrap().get_glyph(c) {
-                        let adv = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
+                    if let Some(adv) = fonts_atlas.char_advance(self.text_style.font_ids(), c, font_size) {
                         // ok so now what.
                         if measured_width + adv >= eval_width { // we have to drop back to ellip_pt
                             // if we don't have an ellip_pt, set it to 0
@@ -580,11 +767,13 @@ impl DrawText {
                 
                 let mut iter = WordIterator::new(
                     text.char_indices(),
-                    eval_width, font_size_logical * self.font_scale,
+                    eval_width,
+                    self.text_style.font_ids(),
+                    font_size,
                     self.ignore_newlines,
                     self.combine_spaces,
                 );
-                while let Some(word) = iter.next_word(fonts_atlas.fonts[font_id].as_mut().unwrap()) {
+                while let Some(word) = iter.next_word(fonts_atlas) {
                     match word{
                         WordItem::Newline=>{
                             measured_height += line_height * self.text_style.line_spacing;
@@ -620,8 +809,7 @@ impl DrawText {
                     if c == '\n' {
                         measured_height += line_height * self.text_style.line_spacing;
                     }
-                    if let Some(glyph) = fonts_atlas.fonts[font_id].as_mut().unwrap().get_glyph(c) {
-                        let adv = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
+                    if let Some(adv) = fonts_atlas.char_advance(self.text_style.font_ids(), c, font_size) {
                         measured_width += adv;
                     }
                     if measured_width > max_width {
@@ -648,30 +836,33 @@ impl DrawText {
         if text.len() == 0 {
             return
         }        
-        let font_id = if let Some(font_id) = self.text_style.font.font_id{font_id}else{
+        if self.text_style.font.font_id.is_none() {
             //log!("Draw text without font");
             return
-        };
+        }
         let fonts_atlas_rc = cx.fonts_atlas_rc.clone();
         let mut fonts_atlas = fonts_atlas_rc.0.borrow_mut();
         let fonts_atlas = &mut*fonts_atlas;
                 
-        let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * fonts_atlas.fonts[font_id].as_ref().unwrap().ttf_font.units_per_em);
         let line_drop = self.text_style.font_size * self.text_style.height_factor * self.font_scale * self.text_style.top_drop;
         
         // lets get the width of the current turtle
         // we need it for the next_word item to properly break off
         let padded_rect = cx.turtle().padded_rect();
         
+        // collected so the iterator doesn't hold on to self while we draw
+        let font_ids: Vec<usize> = self.text_style.font_ids().collect();
         let mut iter = WordIterator::new(
             text.char_indices(),
             padded_rect.size.x,
-            font_size_logical * self.font_scale, 
+            font_ids.iter().copied(),
+            self.text_style.font_size * self.font_scale,
             self.ignore_newlines,
             self.combine_spaces,
         );
         let mut last_rect = None;
-        while let Some(word) = iter.next_word(fonts_atlas.fonts[font_id].as_mut().unwrap()) {
+        let mut drawn_index = DrawnCharIndex::new(text);
+        while let Some(word) = iter.next_word(fonts_atlas) {
             match word{
                 WordItem::Newline=>{
                     cx.turtle_new_line();
@@ -681,7 +872,8 @@ impl DrawText {
                         abs_pos: None,
                         margin: Margin::default(),
                         width: Size::Fixed(width),
-                        height: Size::Fixed(line_drop)
+                        height: Size::Fixed(line_drop),
+                        ..Walk::default()
                     });
                     if last_rect.is_none(){
                         last_rect = Some(walk_rect)
@@ -700,7 +892,8 @@ impl DrawText {
                         cb(cx, rect);
                     }
                     // make sure our iterator uses the xpos from the turtle
-                    self.draw_inner(cx, walk_rect.pos, &text[start..end], fonts_atlas);
+                    let char_offset = drawn_index.at(start);
+                    self.draw_inner(cx, walk_rect.pos, &text[start..end], char_offset, fonts_atlas);
                 }
             }
         }
@@ -713,15 +906,14 @@ impl DrawText {
         if text.len() == 0 {
             return
         }        
-        let font_id = if let Some(font_id) = self.text_style.font.font_id{font_id}else{
+        if self.text_style.font.font_id.is_none() {
             //log!("Draw text without font");
             return
-        };
+        }
         let fonts_atlas_rc = cx.fonts_atlas_rc.clone();
         let mut fonts_atlas = fonts_atlas_rc.0.borrow_mut();
         let fonts_atlas = &mut*fonts_atlas;
         
-        let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * fonts_atlas.fonts[font_id].as_ref().unwrap().ttf_font.units_per_em);
         let line_height = self.text_style.font_size * self.text_style.height_factor * self.font_scale;
                 
         //let in_many = self.many_instances.is_some();
@@ -747,7 +939,8 @@ impl DrawText {
                             abs_pos: walk.abs_pos,
                             margin: walk.margin,
                             width: Size::Fixed(geom.eval_width),
-                            height: Size::Fixed(height)
+                            height: Size::Fixed(height),
+                            ..walk
                         });
                         
                         // Ensure the chunk before the ellipsis is aligned down to a char boundary
@@ -761,8 +954,8 @@ impl DrawText {
                             }
                             ""
                         });
-                        self.draw_inner(cx, rect.pos + dvec2(0.0, y_align), chunk, fonts_atlas);
-                        self.draw_inner(cx, rect.pos + dvec2(at_x, y_align), &"..."[0..dots], fonts_atlas);
+                        self.draw_inner(cx, rect.pos + dvec2(0.0, y_align), chunk, 0, fonts_atlas);
+                        self.draw_inner(cx, rect.pos + dvec2(at_x, y_align), &"..."[0..dots], chunk.chars().count(), fonts_atlas);
                     }
                     else { // we might have space to h-align
                         let rect = cx.walk_turtle(Walk {
@@ -775,10 +968,11 @@ impl DrawText {
                                 } else {
                                     geom.eval_height
                                 }
-                            )
+                            ),
+                            ..walk
                         });
                         let x_align = (geom.eval_width - geom.measured_width) * align.x;
-                        self.draw_inner(cx, rect.pos + dvec2(x_align, y_align), text, fonts_atlas);
+                        self.draw_inner(cx, rect.pos + dvec2(x_align, y_align), text, 0, fonts_atlas);
                     }
                 }
                 TextWrap::Word => {
@@ -786,33 +980,46 @@ impl DrawText {
                         abs_pos: walk.abs_pos,
                         margin: walk.margin,
                         width: Size::Fixed(geom.eval_width),
-                        height: Size::Fixed(geom.measured_height)
+                        height: Size::Fixed(geom.measured_height),
+                        ..walk
                     });
-                    let mut pos = dvec2(0.0, 0.0);
+                    let mut line_y = 0.0;
+                    let mut line_width = 0.0;
+                    let mut line = Vec::new();
+                    // resolved over the whole text so the words of a line are ordered by their paragraph
+                    let bidi_info = unicode_bidi::BidiInfo::new(text, None);
+                    let mut drawn_index = DrawnCharIndex::new(text);
                     
+                    let font_ids: Vec<usize> = self.text_style.font_ids().collect();
                     let mut iter = WordIterator::new(
                         text.char_indices(), 
                         geom.eval_width, 
-                        font_size_logical * self.font_scale,
+                        font_ids.iter().copied(),
+                        self.text_style.font_size * self.font_scale,
                         self.ignore_newlines,
                         self.combine_spaces,    
                     );
-                    while let Some(word) = iter.next_word(fonts_atlas.fonts[font_id].as_mut().unwrap()) {
+                    while let Some(word) = iter.next_word(fonts_atlas) {
                         match word{
                             WordItem::Newline=>{
-                                pos.y += line_height * self.text_style.line_spacing;
-                                pos.x = 0.0;
+                                self.draw_line(cx, rect.pos + dvec2(0.0, line_y), geom.eval_width, &bidi_info, &line, fonts_atlas);
+                                line.clear();
+                                line_y += line_height * self.text_style.line_spacing;
+                                line_width = 0.0;
                             }
                             WordItem::Word{start, end, width} | WordItem::Spaces{start, end, width}=>{
-                                if pos.x + width >= geom.eval_width {
-                                    pos.y += line_height * self.text_style.line_spacing;
-                                    pos.x = 0.0;
+                                if line_width + width >= geom.eval_width {
+                                    self.draw_line(cx, rect.pos + dvec2(0.0, line_y), geom.eval_width, &bidi_info, &line, fonts_atlas);
+                                    line.clear();
+                                    line_y += line_height * self.text_style.line_spacing;
+                                    line_width = 0.0;
                                 }
-                                self.draw_inner(cx, rect.pos + pos, &text[start..end], fonts_atlas);
-                                pos.x += width;
+                                line.push(LineItem {start, end, width, char_offset: drawn_index.at(start)});
+                                line_width += width;
                             }
                         }
                     }
+                    self.draw_line(cx, rect.pos + dvec2(0.0, line_y), geom.eval_width, &bidi_info, &line, fonts_atlas);
                 }
                 TextWrap::Line => {
                     // lets just output it and walk it
@@ -820,13 +1027,16 @@ impl DrawText {
                         abs_pos: walk.abs_pos,
                         margin: walk.margin,
                         width: Size::Fixed(geom.measured_width),
-                        height: Size::Fixed(height)
+                        height: Size::Fixed(height),
+                        ..walk
                     });
                     // lets do our y alignment
                     let mut ypos = 0.0;
+                    let mut char_offset = 0;
                     for line in text.split('\n') {
-                        self.draw_inner(cx, rect.pos + dvec2(0.0, y_align + ypos), line, fonts_atlas);
+                        self.draw_inner(cx, rect.pos + dvec2(0.0, y_align + ypos), line, char_offset, fonts_atlas);
                         ypos += line_height * self.text_style.line_spacing;
+                        char_offset += line.chars().count();
                     }
                     
                 }
@@ -838,96 +1048,103 @@ impl DrawText {
         }
     }
     
-    pub fn closest_offset(&self, cx: &Cx, newline_indexes: Vec<usize>, pos: DVec2) -> Option<usize> {
+    // the drawn glyphs of the current area with their clusters, in instance order. lines
+    // follow each other, within a line the glyphs are in visual order
+    fn glyph_boxes(&self, cx: &Cx) -> Vec<GlyphBox> {
         let area = &self.draw_vars.area;
-        
         if !area.is_valid(cx) {
-            return None
+            return Vec::new()
         }
-
-        let line_spacing = self.get_line_spacing();
         let rect_pos = area.get_read_ref(cx, live_id!(rect_pos), ShaderTy::Vec2).unwrap();
         let delta = area.get_read_ref(cx, live_id!(delta), ShaderTy::Vec2).unwrap();
         let advance = area.get_read_ref(cx, live_id!(advance), ShaderTy::Float).unwrap();
-
-        let mut last_y = None;
-        let mut newlines = 0;
-        for i in 0..rect_pos.repeat {
-            if newline_indexes.contains(&(i + newlines)) {
-                newlines += 1;
+        // glyphs drawn some other way than draw_inner have no clusters, treat them as one per char
+        let clusters = if self.glyph_clusters.len() == rect_pos.repeat {Some(&self.glyph_clusters)} else {None};
+        (0..rect_pos.repeat).map( | i | {
+            let index = i * rect_pos.stride;
+            GlyphBox {
+                x: (rect_pos.buffer[index] - delta.buffer[index]) as f64,
+                y: (rect_pos.buffer[index + 1] - delta.buffer[index + 1]) as f64,
+                advance: advance.buffer[i * advance.stride] as f64,
+                cluster: clusters.map_or(GlyphCluster {index: i, len: 1, rtl: false}, | clusters | clusters[i])
             }
-
-            let index = rect_pos.stride * i;
-            let x = rect_pos.buffer[index + 0] as f64 - delta.buffer[index + 0] as f64;
-
-            let y = rect_pos.buffer[index + 1] - delta.buffer[index + 1];
-            if last_y.is_none() {last_y = Some(y)}
-            let advance = advance.buffer[index + 0] as f64;
-            if i > 0 && (y - last_y.unwrap()) > 0.001 && pos.y < last_y.unwrap() as f64 + line_spacing as f64 {
-                return Some(i - 1 + newlines)
-            }
-            if pos.x < x + advance * 0.5 && pos.y < y as f64 + line_spacing as f64 {
-                return Some(i + newlines)
-            }
-            last_y = Some(y)
+        }).collect()
+    }
+    
+    /// Returns the character index closest to `pos`, taking the reading direction of
+    /// the glyph under it into account.
+    pub fn closest_offset(&self, cx: &Cx, newline_indexes: Vec<usize>, pos: DVec2) -> Option<usize> {
+        if !self.draw_vars.area.is_valid(cx) {
+            return None
+        }
+        let boxes = self.glyph_boxes(cx);
+        let line_spacing = self.get_line_spacing();
+        
+        let lines = glyph_lines(&boxes);
+        let line = if let Some(line) = lines.iter().find( | line | pos.y < boxes[line.start].y + line_spacing).or(lines.last()) {
+            &boxes[line.clone()]
         }
-        return Some(rect_pos.repeat + newlines);
+        else {
+            return Some(0)
+        };
+        let nearest = line.iter().min_by( | a, b | a.distance_x(pos.x).total_cmp(&b.distance_x(pos.x))).unwrap();
+        let t = if nearest.advance > 0.0 {((pos.x - nearest.x) / nearest.advance).clamp(0.0, 1.0)} else {0.0};
+        let t = if nearest.cluster.rtl {1.0 - t} else {t};
+        let index = nearest.cluster.index + (t * nearest.cluster.len as f64).round() as usize;
         
+        // a position after a character sits right behind it, so the end of a line stays
+        // in front of its newline
+        let line_start = line.iter().map( | b | b.cluster.index).min().unwrap();
+        if index > line_start {
+            Some(drawn_to_text_index(&newline_indexes, index - 1) + 1)
+        }
+        else {
+            Some(drawn_to_text_index(&newline_indexes, index))
+        }
     }
     
+    /// Returns the rects covering the characters `start..end`. A selection crossing
+    /// reading directions can take more than one rect per line.
     pub fn get_selection_rects(&self, cx: &Cx, newline_indexes: Vec<usize>, start: usize, end: usize, shift: DVec2, pad: DVec2) -> Vec<Rect> {
-        let area = &self.draw_vars.area;
-        
-        if !area.is_valid(cx) {
-            return Vec::new();
-        }
-
         // Adjustments because of newlines characters (they are not in the buffers)
         let start_offset = newline_indexes.iter().filter(|&&i| i < start).count();
         let start = start - start_offset;
         let end_offset = newline_indexes.iter().filter(|&&i| i < end).count();
         let end = end - end_offset;
         
-        let rect_pos = area.get_read_ref(cx, live_id!(rect_pos), ShaderTy::Vec2).unwrap();
-        let delta = area.get_read_ref(cx, live_id!(delta), ShaderTy::Vec2).unwrap();
-        let advance = area.get_read_ref(cx, live_id!(advance), ShaderTy::Float).unwrap();
-        
-        if rect_pos.repeat == 0 || start >= rect_pos.repeat{
-            return Vec::new();
-        }
-        // alright now we go and walk from start to end and collect our selection rects
-        
-        let index = start * rect_pos.stride;
-        let start_x = rect_pos.buffer[index + 0] - delta.buffer[index + 0]; // + advance.buffer[index + 0] * pos;
-        let start_y = rect_pos.buffer[index + 1] - delta.buffer[index + 1];
+        let boxes = self.glyph_boxes(cx);
         let line_spacing = self.get_line_spacing();
-        let mut last_y = start_y;
-        let mut min_x = start_x;
-        let mut last_x = start_x;
-        let mut last_advance = advance.buffer[index + 0];
         let mut out = Vec::new();
-        for index in start..end {
-            if index >= rect_pos.repeat{
-                break;
+        for line in glyph_lines(&boxes) {
+            let mut spans = Vec::new();
+            for b in &boxes[line.clone()] {
+                let from = start.max(b.cluster.index);
+                let to = end.min(b.cluster.index + b.cluster.len);
+                if from >= to {
+                    continue
+                }
+                let len = b.cluster.len as f64;
+                let x1 = b.cursor_x((from - b.cluster.index) as f64 / len);
+                let x2 = b.cursor_x((to - b.cluster.index) as f64 / len);
+                spans.push((x1.min(x2), x1.max(x2)));
+            }
+            spans.sort_by( | a, b | a.0.total_cmp(&b.0));
+            // glyphs next to each other share an edge, merge those into one rect
+            let mut merged: Vec<(f64, f64)> = Vec::new();
+            for (x1, x2) in spans {
+                match merged.last_mut() {
+                    Some(last) if x1 <= last.1 + 0.01 => last.1 = last.1.max(x2),
+                    _ => merged.push((x1, x2))
+                }
             }
-            let index = index * rect_pos.stride;
-            let end_x = rect_pos.buffer[index + 0] - delta.buffer[index + 0];
-            let end_y = rect_pos.buffer[index + 1] - delta.buffer[index + 1];
-            last_advance = advance.buffer[index + 0];
-            if end_y > last_y { // emit rect
+            let y = boxes[line.start].y;
+            for (x1, x2) in merged {
                 out.push(Rect {
-                    pos: dvec2(min_x as f64, last_y as f64) + shift,
-                    size: dvec2((last_x - min_x + last_advance) as f64, line_spacing) + pad
+                    pos: dvec2(x1, y) + shift,
+                    size: dvec2(x2 - x1, line_spacing) + pad
                 });
-                min_x = end_x;
-                last_y = end_y;
             }
-            last_x = end_x;
         }
-        out.push(Rect {
-            pos: dvec2(min_x as f64, last_y as f64) + shift,
-            size: dvec2((last_x - min_x + last_advance) as f64, line_spacing) + pad
-        });
         out
     }
     
@@ -940,40 +1157,30 @@ impl DrawText {
         rect_pos.repeat
     }
     
+    /// Returns the position of the cursor in front of character `index`, `pos` moves it
+    /// that far into the character along its reading direction.
     pub fn get_cursor_pos(&self, cx: &Cx, newline_indexes: Vec<usize>, pos: f32, index: usize) -> Option<DVec2> {
-        let area = &self.draw_vars.area;
-        
-        if !area.is_valid(cx) {
+        if !self.draw_vars.area.is_valid(cx) {
             return None
         }
         // Adjustment because of newlines characters (they are not in the buffers)
         let index_offset = newline_indexes.iter().filter(|&&i| i < index).count();
         let (index, pos) = if newline_indexes.contains(&(index)){
-            (index - index_offset - 1, pos + 1.0)
+            ((index - index_offset).saturating_sub(1), pos as f64 + 1.0)
         } else {
-            (index - index_offset, pos)
+            (index - index_offset, pos as f64)
         };
         
-        let rect_pos = area.get_read_ref(cx, live_id!(rect_pos), ShaderTy::Vec2).unwrap();
-        let delta = area.get_read_ref(cx, live_id!(delta), ShaderTy::Vec2).unwrap();
-        let advance = area.get_read_ref(cx, live_id!(advance), ShaderTy::Float).unwrap();
-        
-        if rect_pos.repeat == 0 {
-            return None
+        let boxes = self.glyph_boxes(cx);
+        if let Some(b) = boxes.iter().find( | b | b.cluster.index <= index && index < b.cluster.index + b.cluster.len) {
+            let t = ((index - b.cluster.index) as f64 + pos) / b.cluster.len as f64;
+            return Some(dvec2(b.cursor_x(t), b.y))
         }
-        if index >= rect_pos.repeat {
-            // lets get the last one and advance
-            let index = (rect_pos.repeat - 1) * rect_pos.stride;
-            let x = rect_pos.buffer[index + 0] - delta.buffer[index + 0] + advance.buffer[index + 0];
-            let y = rect_pos.buffer[index + 1] - delta.buffer[index + 1];
-            Some(dvec2(x as f64, y as f64))
-        }
-        else {
-            let index = index * rect_pos.stride;
-            let x = rect_pos.buffer[index + 0] - delta.buffer[index + 0] + advance.buffer[index + 0] * pos;
-            let y = rect_pos.buffer[index + 1] - delta.buffer[index + 1];
-            Some(dvec2(x as f64, y as f64))
+        // past the end, or on a character that wasn't drawn: behind the closest one before it
+        if let Some(b) = boxes.iter().filter( | b | b.cluster.index <= index).max_by_key( | b | b.cluster.index) {
+            return Some(dvec2(b.cursor_x(1.0), b.y))
         }
+        boxes.iter().min_by_key( | b | b.cluster.index).map( | b | dvec2(b.cursor_x(0.0), b.y))
     }
     
     pub fn get_line_spacing(&self) -> f64 {
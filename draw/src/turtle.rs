@@ -15,7 +15,9 @@ pub struct Layout {
     #[live] pub align: Align,
     #[live] pub flow: Flow,
     #[live] pub spacing: f64,
-    #[live] pub line_spacing: f64
+    #[live] pub line_spacing: f64,
+    #[live] pub grid_columns: GridTracks,
+    #[live] pub grid_rows: GridTracks
 }
 
 impl Default for Layout{
@@ -28,18 +30,44 @@ impl Default for Layout{
             align: Align{x:0.0,y:0.0},
             flow: Flow::Right,
             spacing: 0.0,
-            line_spacing: 0.0
+            line_spacing: 0.0,
+            grid_columns: GridTracks::default(),
+            grid_rows: GridTracks::default()
         }
     }
 }
 
-#[derive(Copy, Clone, Default, Debug, Live, LiveHook, LiveRegister)]
+#[derive(Copy, Clone, Debug, Live, LiveHook, LiveRegister)]
 #[live_ignore]
 pub struct Walk {
     #[live] pub abs_pos: Option<DVec2>,
     #[live] pub margin: Margin,
     #[live] pub width: Size,
     #[live] pub height: Size,
+    #[live] pub min_width: Option<f64>,
+    #[live] pub max_width: Option<f64>,
+    #[live] pub min_height: Option<f64>,
+    #[live] pub max_height: Option<f64>,
+    /// Share of the leftover space a Fill child gets relative to its Fill siblings
+    #[live(1.0)] pub fill_weight: f64,
+    #[live] pub grid_cell: GridCell,
+}
+
+impl Default for Walk {
+    fn default() -> Self {
+        Self {
+            abs_pos: None,
+            margin: Margin::default(),
+            width: Size::Fill,
+            height: Size::Fill,
+            min_width: None,
+            max_width: None,
+            min_height: None,
+            max_height: None,
+            fill_weight: 1.0,
+            grid_cell: GridCell::default()
+        }
+    }
 }
 
 #[derive(Clone, Copy, Default, Debug, Live, LiveHook, LiveRegister)]
@@ -79,7 +107,8 @@ pub enum Flow {
     //Left,
     //Up,
     Overlay, 
-    RightWrap
+    RightWrap,
+    Grid
 }
 
 #[derive(Copy, Clone, Debug, Live)]
@@ -91,14 +120,43 @@ pub enum Size {
     All
 }
 
+/// A column or row of a `Flow::Grid`. A plain number in the DSL is a fixed track
+#[derive(Copy, Clone, Debug, Live)]
+#[live_ignore]
+pub enum GridTrack {
+    #[pick] Fit,
+    #[live(100.0)] Fixed(f64),
+    #[live(1.0)] Fr(f64)
+}
+
+pub const GRID_TRACKS_MAX: usize = 16;
+
+/// The track list of a grid axis, fixed size so Layout stays Copy.
+/// `grid_columns: [100, Fit, Fr(1.0)]` or `grid_columns: 3` for three equal columns
+#[derive(Copy, Clone, Debug)]
+pub struct GridTracks {
+    len: usize,
+    tracks: [GridTrack; GRID_TRACKS_MAX]
+}
+
+/// Where a child goes in its parent's grid, cells without a column and row are placed in order
+#[derive(Copy, Clone, Debug, Live, LiveHook, LiveRegister)]
+#[live_ignore]
+pub struct GridCell {
+    #[live] pub column: Option<usize>,
+    #[live] pub row: Option<usize>,
+    #[live(1usize)] pub column_span: usize,
+    #[live(1usize)] pub row_span: usize,
+}
+
 #[derive(Clone, Debug)]
 pub enum DeferWalk{
     Unresolved{
         defer_index: usize,
-        margin: Margin,
-        other_axis: Size,
+        walk: Walk,
         pos: DVec2
     },
+    UnresolvedGridCell(Walk),
     Resolved(Walk)
 }
 
@@ -116,9 +174,39 @@ pub enum AlignEntry{
 pub struct TurtleWalk {
     align_start: usize,
     defer_index: usize,
+    grid_area: Option<GridArea>,
     rect: Rect,
 }
 
+#[derive(Clone, Copy, Default, Debug)]
+struct GridArea {
+    column: usize,
+    row: usize,
+    columns: usize,
+    rows: usize
+}
+
+// the bounds and weight of a deferred fill child along the flow
+#[derive(Clone, Copy, Debug)]
+struct DeferFill {
+    weight: f64,
+    min: Option<f64>,
+    max: Option<f64>
+}
+
+#[derive(Clone, Default, Debug)]
+struct GridState {
+    // row major, grows a row at a time
+    occupied: Vec<bool>,
+    // auto placement doesn't go back before this cell
+    cursor: usize,
+    // largest item per column and row that sits in a single track, margins included
+    column_fit: Vec<f64>,
+    row_fit: Vec<f64>,
+    // items spanning several tracks, these grow the fit tracks they cover
+    spanning: Vec<(GridArea, DVec2)>,
+}
+
 #[derive(Clone, Default, Debug)]
 pub struct Turtle {
     walk: Walk,
@@ -126,6 +214,7 @@ pub struct Turtle {
     align_start: usize,
     turtle_walks_start: usize,
     defer_count: usize,
+    defer_fills: Vec<DeferFill>,
     shift: DVec2,
     pos: DVec2,
     origin: DVec2,
@@ -133,7 +222,8 @@ pub struct Turtle {
     height: f64,
     width_used: f64,
     height_used: f64,
-    guard_area: Area
+    guard_area: Area,
+    grid: GridState
 }
 
 impl<'a> Cx2d<'a> {
@@ -157,8 +247,8 @@ impl<'a> Cx2d<'a> {
         let defer_index = turtle.defer_count;
         let pos = turtle.pos;
         let size = dvec2(
-            turtle.eval_width(walk.width, walk.margin, turtle.layout.flow),
-            turtle.eval_height(walk.height, walk.margin, turtle.layout.flow)
+            turtle.eval_width(&walk, turtle.layout.flow),
+            turtle.eval_height(&walk, turtle.layout.flow)
         );
         let margin_size = walk.margin.size();
         match turtle.layout.flow {
@@ -168,10 +258,10 @@ impl<'a> Cx2d<'a> {
                 turtle.update_width_max(turtle.pos.x, 0.0);
                 turtle.update_height_max(turtle.pos.y, size.y + margin_size.y);
                 turtle.defer_count += 1;
+                turtle.defer_fills.push(DeferFill {weight: walk.fill_weight, min: walk.min_width, max: walk.max_width});
                 Some(DeferWalk::Unresolved{
                     defer_index,
-                    margin: walk.margin,
-                    other_axis: walk.height,
+                    walk,
                     pos: pos + spacing
                 })
             },
@@ -181,10 +271,10 @@ impl<'a> Cx2d<'a> {
                 turtle.update_width_max(turtle.pos.x, size.x + margin_size.x);
                 turtle.update_height_max(turtle.pos.y, 0.0);
                 turtle.defer_count += 1;
+                turtle.defer_fills.push(DeferFill {weight: walk.fill_weight, min: walk.min_height, max: walk.max_height});
                 Some(DeferWalk::Unresolved {
                     defer_index,
-                    margin: walk.margin,
-                    other_axis: walk.width,
+                    walk,
                     pos: pos + spacing
                 })
             },
@@ -192,6 +282,17 @@ impl<'a> Cx2d<'a> {
                 error!("flow RightWrap does not support fill childnodes");
                 None
             },
+            Flow::Grid if walk.width.is_fill() || walk.height.is_fill() => {
+                // the cell size depends on the fit tracks, so reserve the cell and size it
+                // once the other children are measured
+                let area = turtle.grid_place(&walk.grid_cell);
+                turtle.grid_occupy(area, walk.grid_cell.is_auto());
+                turtle.defer_count += 1;
+                Some(DeferWalk::UnresolvedGridCell(Walk {
+                    grid_cell: area.cell(),
+                    ..walk
+                }))
+            },
             _ => {
                 None
             }
@@ -212,6 +313,7 @@ impl<'a> Cx2d<'a> {
             align_start: self.align_list.len() - 1,
             turtle_walks_start: self.turtle_walks.len(),
             defer_count: 0,
+            defer_fills: Vec::new(),
             pos: DVec2 {
                 x: layout.padding.left,
                 y: layout.padding.top
@@ -223,6 +325,7 @@ impl<'a> Cx2d<'a> {
             width_used: layout.padding.left,
             height_used: layout.padding.top,
             guard_area: Area::Empty,
+            grid: GridState::default(),
         };
         self.turtles.push(turtle);
     }
@@ -264,14 +367,20 @@ impl<'a> Cx2d<'a> {
     
     pub fn begin_turtle_with_guard(&mut self, walk: Walk, layout: Layout, guard_area: Area) {
         let (origin, width, height, draw_clip) = if let Some(parent) = self.turtles.last() {
-            
-            let o = walk.margin.left_top() + if let Some(pos) = walk.abs_pos {pos} else {
-                parent.pos + parent.child_spacing(self.turtle_walks.len())
+
+            let (o, w, h) = if let (Flow::Grid, None) = (parent.layout.flow, walk.abs_pos) {
+                let (_, rect) = parent.grid_walk_rect(&walk);
+                (rect.pos, rect.size.x, rect.size.y)
+            }
+            else {
+                let o = walk.margin.left_top() + if let Some(pos) = walk.abs_pos {pos} else {
+                    parent.pos + parent.child_spacing(self.turtle_walks.len())
+                };
+                let w = parent.eval_width(&walk, parent.layout.flow);
+                let h = parent.eval_height(&walk, parent.layout.flow);
+                (o, w, h)
             };
             
-            let w = parent.eval_width(walk.width, walk.margin, parent.layout.flow);
-            let h = parent.eval_height(walk.height, walk.margin, parent.layout.flow);
-            
             // figure out new clipping rect
             let (x0, x1) = if layout.clip_x {
                 (/*parent.draw_clip.0.x.max(*/o.x/*)*/, if w.is_nan() {
@@ -296,8 +405,8 @@ impl<'a> Cx2d<'a> {
         }
         else {
             let o = DVec2 {x: walk.margin.left, y: walk.margin.top};
-            let w = walk.width.fixed_or_nan();
-            let h = walk.height.fixed_or_nan();
+            let w = walk.clamp_width(walk.width.fixed_or_nan());
+            let h = walk.clamp_height(walk.height.fixed_or_nan());
             
             (o, w, h, (dvec2(o.x, o.y), dvec2(o.x + w, o.y + h)))
         };
@@ -308,6 +417,7 @@ impl<'a> Cx2d<'a> {
             align_start: self.align_list.len()-1,
             turtle_walks_start: self.turtle_walks.len(),
             defer_count: 0,
+            defer_fills: Vec::new(),
             pos: DVec2 {
                 x: origin.x + layout.padding.left,
                 y: origin.y + layout.padding.top
@@ -319,6 +429,7 @@ impl<'a> Cx2d<'a> {
             width_used: layout.padding.left,
             height_used: layout.padding.top,
             guard_area,
+            grid: GridState::default(),
         };
         
         self.turtles.push(turtle);
@@ -338,11 +449,22 @@ impl<'a> Cx2d<'a> {
     }
     
     pub fn end_turtle_with_guard(&mut self, guard_area: Area) -> Rect {
-        let turtle = self.turtles.pop().unwrap();
+        let mut turtle = self.turtles.pop().unwrap();
         if guard_area != turtle.guard_area {
             panic!("End turtle guard area misaligned!, begin/end pair not matched begin {:?} end {:?}", turtle.guard_area, guard_area)
         }
-        
+
+        // the grid tracks are only known now all the children are measured
+        let grid_tracks = if let Flow::Grid = turtle.layout.flow {
+            let (columns, rows) = turtle.grid_tracks();
+            turtle.width_used = turtle.layout.padding.left + grid_track_span(&columns, 0, columns.len(), turtle.layout.spacing);
+            turtle.height_used = turtle.layout.padding.top + grid_track_span(&rows, 0, rows.len(), turtle.layout.line_spacing);
+            Some((columns, rows))
+        }
+        else {
+            None
+        };
+
         // computed width / height
         let w = if turtle.width.is_nan() {
             Size::Fixed(turtle.width_used + turtle.layout.padding.right - turtle.layout.scroll.x)
@@ -361,11 +483,10 @@ impl<'a> Cx2d<'a> {
         match turtle.layout.flow {
             Flow::Right => {
                 if turtle.defer_count > 0 {
-                    let left = turtle.width_left();
-                    let part = left / turtle.defer_count as f64;
+                    let (offsets, free) = turtle.defer_fill_offsets(turtle.width_left());
                     for i in turtle.turtle_walks_start..self.turtle_walks.len() {
                         let walk = &self.turtle_walks[i];
-                        let shift_x = walk.defer_index as f64 * part;
+                        let shift_x = offsets[walk.defer_index] + turtle.layout.align.x * free;
                         let shift_y = turtle.layout.align.y * (turtle.padded_height_or_used() - walk.rect.size.y);
                         let align_start = walk.align_start;
                         let align_end = self.get_turtle_walk_align_end(i);
@@ -389,12 +510,11 @@ impl<'a> Cx2d<'a> {
             }
             Flow::Down => {
                 if turtle.defer_count > 0 {
-                    let left = turtle.height_left();
-                    let part = left / turtle.defer_count as f64;
+                    let (offsets, free) = turtle.defer_fill_offsets(turtle.height_left());
                     for i in turtle.turtle_walks_start..self.turtle_walks.len() {
                         let walk = &self.turtle_walks[i];
                         let shift_x = turtle.layout.align.x * (turtle.padded_width_or_used() - walk.rect.size.x);
-                        let shift_y = walk.defer_index as f64 * part;
+                        let shift_y = offsets[walk.defer_index] + turtle.layout.align.y * free;
                         let align_start = walk.align_start;
                         let align_end = self.get_turtle_walk_align_end(i);
                         self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
@@ -421,6 +541,23 @@ impl<'a> Cx2d<'a> {
                     self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
                 }
             }
+            Flow::Grid => {
+                // children were drawn at their cell as far as it was known, move them to
+                // the final cell and align them in it
+                let (columns, rows) = grid_tracks.unwrap();
+                for i in turtle.turtle_walks_start..self.turtle_walks.len() {
+                    let walk = &self.turtle_walks[i];
+                    if let Some(area) = walk.grid_area {
+                        let cell = turtle.grid_cell_rect(area, &columns, &rows);
+                        let size = dvec2(nan_to_zero(walk.rect.size.x), nan_to_zero(walk.rect.size.y));
+                        let shift_x = cell.pos.x - walk.rect.pos.x + turtle.layout.align.x * (cell.size.x - size.x);
+                        let shift_y = cell.pos.y - walk.rect.pos.y + turtle.layout.align.y * (cell.size.y - size.y);
+                        let align_start = walk.align_start;
+                        let align_end = self.get_turtle_walk_align_end(i);
+                        self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
+                    }
+                }
+            }
         }
 
         self.turtle_walks.truncate(turtle.turtle_walks_start);
@@ -482,21 +619,22 @@ impl<'a> Cx2d<'a> {
         
         let turtle = self.turtles.last_mut().unwrap();
         let size = dvec2(
-            turtle.eval_width(walk.width, walk.margin, turtle.layout.flow),
-            turtle.eval_height(walk.height, walk.margin, turtle.layout.flow)
+            turtle.eval_width(&walk, turtle.layout.flow),
+            turtle.eval_height(&walk, turtle.layout.flow)
         );
         
         if let Some(pos) = walk.abs_pos {
             self.turtle_walks.push(TurtleWalk {
                 align_start,
                 defer_index: 0,
+                grid_area: None,
                 rect: Rect {pos, size: size + walk.margin.size()}
             });
             
             match turtle.layout.flow {
                 Flow::Right=>turtle.update_height_max(pos.y, size.y + walk.margin.size().y),
                 Flow::Down=>turtle.update_width_max(pos.x, size.x + walk.margin.size().x),
-                Flow::Overlay => { // do not walk
+                Flow::Overlay | Flow::Grid => { // do not walk
                     turtle.update_width_max(pos.x, size.x);
                     turtle.update_height_max(pos.y,size.y);
                 }
@@ -506,6 +644,22 @@ impl<'a> Cx2d<'a> {
             }
             Rect {pos: pos + walk.margin.left_top(), size}
         }
+        else if let Flow::Grid = turtle.layout.flow {
+            let (area, rect) = turtle.grid_walk_rect(&walk);
+            let margin_size = walk.margin.size();
+            let pos = rect.pos - walk.margin.left_top();
+            turtle.grid_occupy(area, walk.grid_cell.is_auto());
+            turtle.grid_measure(area, rect.size + margin_size);
+            turtle.update_width_max(pos.x, rect.size.x + margin_size.x);
+            turtle.update_height_max(pos.y, rect.size.y + margin_size.y);
+            self.turtle_walks.push(TurtleWalk {
+                align_start,
+                defer_index: 0,
+                grid_area: Some(area),
+                rect: Rect {pos, size: rect.size + margin_size}
+            });
+            rect
+        }
         else {
             let spacing = turtle.child_spacing(self.turtle_walks.len());
             let mut pos = turtle.pos;
@@ -567,11 +721,13 @@ impl<'a> Cx2d<'a> {
                     turtle.update_width_max(turtle.pos.x, size.x);
                     turtle.update_height_max(turtle.pos.y,size.y);
                 }
+                Flow::Grid => unreachable!()
             };
             
             self.turtle_walks.push(TurtleWalk {
                 align_start,
                 defer_index,
+                grid_area: None,
                 rect: Rect {pos, size: size + margin_size}
             });
             Rect {pos: pos + walk.margin.left_top() + spacing, size}
@@ -584,13 +740,16 @@ impl<'a> Cx2d<'a> {
         }
         let turtle = self.turtles.last().unwrap();
         let size = dvec2(
-            turtle.eval_width(walk.width, walk.margin, turtle.layout.flow),
-            turtle.eval_height(walk.height, walk.margin, turtle.layout.flow)
+            turtle.eval_width(&walk, turtle.layout.flow),
+            turtle.eval_height(&walk, turtle.layout.flow)
         );
         
         if let Some(pos) = walk.abs_pos {
             Rect {pos: pos + walk.margin.left_top(), size}
         }
+        else if let Flow::Grid = turtle.layout.flow {
+            turtle.grid_walk_rect(&walk).1
+        }
         else {
             let spacing = turtle.child_spacing(self.turtle_walks.len());
             let pos = turtle.pos;
@@ -832,6 +991,10 @@ impl Turtle {
                 Flow::RightWrap=>{
                     dvec2(self.layout.spacing, 0.0)
                 }
+                // the gaps are part of the track offsets
+                Flow::Grid => {
+                    dvec2(0.0, 0.0)
+                }
             }
         }
         else {
@@ -871,7 +1034,11 @@ impl Turtle {
         self.layout.scroll
     }
     
-    pub fn eval_width(&self, width: Size, margin: Margin, flow: Flow) -> f64 {
+    pub fn eval_width(&self, walk: &Walk, flow: Flow) -> f64 {
+        walk.clamp_width(self.eval_width_unclamped(walk.width, walk.margin, flow))
+    }
+    
+    fn eval_width_unclamped(&self, width: Size, margin: Margin, flow: Flow) -> f64 {
         return match width {
             Size::Fit => std::f64::NAN,
             Size::Fixed(v) => max_zero_keep_nan(v),
@@ -890,13 +1057,21 @@ impl Turtle {
                         }
                         return r
                     }
+                    Flow::Grid => {
+                        let area = self.grid_place(&GridCell::default());
+                        max_zero_keep_nan(self.grid_area_rect(area).size.x - margin.width())
+                    }
                 }
             },
             Size::All=>self.width
         }
     }
     
-    pub fn eval_height(&self, height: Size, margin: Margin, flow: Flow) -> f64 {
+    pub fn eval_height(&self, walk: &Walk, flow: Flow) -> f64 {
+        walk.clamp_height(self.eval_height_unclamped(walk.height, walk.margin, flow))
+    }
+    
+    fn eval_height_unclamped(&self, height: Size, margin: Margin, flow: Flow) -> f64 {
         return match height {
             Size::Fit => std::f64::NAN,
             Size::Fixed(v) => max_zero_keep_nan(v),
@@ -912,6 +1087,10 @@ impl Turtle {
                     Flow::Down => {
                         max_zero_keep_nan(self.height_left() - margin.height())
                     }
+                    Flow::Grid => {
+                        let area = self.grid_place(&GridCell::default());
+                        max_zero_keep_nan(self.grid_area_rect(area).size.y - margin.height())
+                    }
                 }
             }
             Size::All=>self.height
@@ -956,6 +1135,19 @@ impl Turtle {
         dvec2(self.width, self.height)
     }
     
+    // prefix offsets of the deferred fill children along the flow, plus the space none of them could take
+    fn defer_fill_offsets(&self, left: f64) -> (Vec<f64>, f64) {
+        let sizes = distribute_fill(left, &self.defer_fills);
+        let mut offsets = Vec::with_capacity(sizes.len() + 1);
+        let mut offset = 0.0;
+        offsets.push(offset);
+        for size in sizes {
+            offset += size;
+            offsets.push(offset);
+        }
+        (offsets, max_zero_keep_nan(left - offset).max(0.0))
+    }
+    
     pub fn width_left(&self) -> f64 {
         return max_zero_keep_nan(self.width - self.width_used - self.layout.padding.right);
     }
@@ -983,6 +1175,147 @@ impl Turtle {
             r
         }
     }
+
+    fn grid_column_count(&self) -> usize {
+        self.layout.grid_columns.len().max(1)
+    }
+
+    fn grid_row_count(&self) -> usize {
+        self.grid.occupied.len().div_ceil(self.grid_column_count()).max(self.layout.grid_rows.len())
+    }
+
+    fn grid_is_free(&self, column: usize, row: usize, columns: usize, rows: usize) -> bool {
+        let stride = self.grid_column_count();
+        (row..row + rows).all( | r | (column..column + columns).all( | c | {
+            !self.grid.occupied.get(r * stride + c).copied().unwrap_or(false)
+        }))
+    }
+
+    // finds the cell a child goes in without taking it
+    fn grid_place(&self, cell: &GridCell) -> GridArea {
+        let stride = self.grid_column_count();
+        let columns = cell.column_span.clamp(1, stride);
+        let rows = cell.row_span.max(1);
+        let area = | column: usize, row: usize | GridArea {column, row, columns, rows};
+        match (cell.column, cell.row) {
+            (Some(column), Some(row)) => area(column.min(stride - columns), row),
+            (None, Some(row)) => {
+                let column = (0..=stride - columns).find( | c | self.grid_is_free(*c, row, columns, rows)).unwrap_or(0);
+                area(column, row)
+            }
+            (Some(column), None) => {
+                let column = column.min(stride - columns);
+                let mut row = self.grid.cursor / stride;
+                while !self.grid_is_free(column, row, columns, rows) {
+                    row += 1;
+                }
+                area(column, row)
+            }
+            (None, None) => {
+                let mut index = self.grid.cursor;
+                while index % stride + columns > stride || !self.grid_is_free(index % stride, index / stride, columns, rows) {
+                    index += 1;
+                }
+                area(index % stride, index / stride)
+            }
+        }
+    }
+
+    fn grid_occupy(&mut self, area: GridArea, auto: bool) {
+        let stride = self.grid_column_count();
+        let end = (area.row + area.rows) * stride;
+        if self.grid.occupied.len() < end {
+            self.grid.occupied.resize(end, false);
+        }
+        for r in area.row..area.row + area.rows {
+            for c in area.column..area.column + area.columns {
+                self.grid.occupied[r * stride + c] = true;
+            }
+        }
+        if auto {
+            self.grid.cursor = area.row * stride + area.column + area.columns;
+        }
+    }
+
+    fn grid_measure(&mut self, area: GridArea, size: DVec2) {
+        let size = dvec2(nan_to_zero(size.x), nan_to_zero(size.y));
+        if area.columns == 1 {
+            if self.grid.column_fit.len() <= area.column {
+                self.grid.column_fit.resize(area.column + 1, 0.0);
+            }
+            self.grid.column_fit[area.column] = self.grid.column_fit[area.column].max(size.x);
+        }
+        if area.rows == 1 {
+            if self.grid.row_fit.len() <= area.row {
+                self.grid.row_fit.resize(area.row + 1, 0.0);
+            }
+            self.grid.row_fit[area.row] = self.grid.row_fit[area.row].max(size.y);
+        }
+        if area.columns > 1 || area.rows > 1 {
+            self.grid.spanning.push((area, size));
+        }
+    }
+
+    // the column and row sizes from what has been measured so far
+    fn grid_tracks(&self) -> (Vec<f64>, Vec<f64>) {
+        let columns = grid_track_sizes(
+            &self.layout.grid_columns,
+            // without columns a grid is a single column filling the turtle
+            if self.layout.grid_columns.is_empty() {GridTrack::Fr(1.0)} else {GridTrack::Fit},
+            self.grid_column_count(),
+            &self.grid.column_fit,
+            self.grid.spanning.iter().filter( | (a, _) | a.columns > 1).map( | (a, s) | (a.column, a.columns, s.x)),
+            self.width - self.layout.padding.width(),
+            self.layout.spacing
+        );
+        let rows = grid_track_sizes(
+            &self.layout.grid_rows,
+            GridTrack::Fit,
+            self.grid_row_count(),
+            &self.grid.row_fit,
+            self.grid.spanning.iter().filter( | (a, _) | a.rows > 1).map( | (a, s) | (a.row, a.rows, s.y)),
+            self.height - self.layout.padding.height(),
+            self.layout.line_spacing
+        );
+        (columns, rows)
+    }
+
+    fn grid_cell_rect(&self, area: GridArea, columns: &[f64], rows: &[f64]) -> Rect {
+        let (spacing, line_spacing) = (self.layout.spacing, self.layout.line_spacing);
+        Rect {
+            pos: self.origin + self.layout.padding.left_top() + dvec2(
+                grid_track_offset(columns, area.column, spacing),
+                grid_track_offset(rows, area.row, line_spacing)
+            ),
+            size: dvec2(
+                grid_track_span(columns, area.column, area.columns, spacing),
+                grid_track_span(rows, area.row, area.rows, line_spacing)
+            )
+        }
+    }
+
+    fn grid_area_rect(&self, area: GridArea) -> Rect {
+        let (columns, rows) = self.grid_tracks();
+        self.grid_cell_rect(area, &columns, &rows)
+    }
+
+    // the cell a walk goes in and its rect as far as the tracks are known, end_turtle
+    // moves it once they are final
+    fn grid_walk_rect(&self, walk: &Walk) -> (GridArea, Rect) {
+        let area = self.grid_place(&walk.grid_cell);
+        let cell = self.grid_area_rect(area);
+        let width = if walk.width.is_fill() {
+            walk.clamp_width(max_zero_keep_nan(cell.size.x - walk.margin.width()))
+        } else {
+            self.eval_width(walk, Flow::Grid)
+        };
+        let height = if walk.height.is_fill() {
+            walk.clamp_height(max_zero_keep_nan(cell.size.y - walk.margin.height()))
+        } else {
+            self.eval_height(walk, Flow::Grid)
+        };
+        (area, Rect {pos: cell.pos + walk.margin.left_top(), size: dvec2(width, height)})
+    }
 }
 
 impl DeferWalk {
@@ -990,33 +1323,53 @@ impl DeferWalk {
     pub fn resolve(&mut self, cx: &Cx2d) -> Walk {
         match self{
             Self::Resolved(walk)=>{*walk},
-            Self::Unresolved{pos, defer_index, margin, other_axis}=>{
+            Self::Unresolved{pos, defer_index, walk}=>{
                 let turtle = cx.turtles.last().unwrap();
                 let walk = match turtle.layout.flow {
                     Flow::Right => {
-                        let left = turtle.width_left();
-                        let part = left / turtle.defer_count as f64;
+                        // the alignment of any space left over is applied in end_turtle
+                        let (offsets, _) = turtle.defer_fill_offsets(turtle.width_left());
+                        let shift = offsets[*defer_index];
                         Walk {
-                            abs_pos: Some(*pos + dvec2(part * *defer_index as f64, 0.)),
-                            margin: *margin,
-                            width: Size::Fixed(part),
-                            height: *other_axis
+                            abs_pos: Some(*pos + dvec2(shift, 0.)),
+                            width: Size::Fixed(offsets[*defer_index + 1] - offsets[*defer_index]),
+                            ..*walk
                         }
                     },
                     Flow::RightWrap => {
                         panic!()
                     }
                     Flow::Down => { 
-                        let left = turtle.height_left();
-                        let part = left / turtle.defer_count as f64;
+                        // the alignment of any space left over is applied in end_turtle
+                        let (offsets, _) = turtle.defer_fill_offsets(turtle.height_left());
+                        let shift = offsets[*defer_index];
                         Walk {
-                            abs_pos: Some(*pos + dvec2(0., part * *defer_index as f64)),
-                            margin: *margin,
-                            height: Size::Fixed(part),
-                            width: *other_axis
+                            abs_pos: Some(*pos + dvec2(0., shift)),
+                            height: Size::Fixed(offsets[*defer_index + 1] - offsets[*defer_index]),
+                            ..*walk
                         }
                     }
-                    Flow::Overlay => panic!()
+                    Flow::Overlay | Flow::Grid => panic!()
+                };
+                *self = DeferWalk::Resolved(walk);
+                walk
+            }
+            Self::UnresolvedGridCell(walk)=>{
+                let turtle = cx.turtles.last().unwrap();
+                let area = turtle.grid_place(&walk.grid_cell);
+                let cell = turtle.grid_area_rect(area);
+                let walk = Walk {
+                    width: if walk.width.is_fill() {
+                        Size::Fixed(walk.clamp_width(max_zero_keep_nan(cell.size.x - walk.margin.width())))
+                    } else {
+                        walk.width
+                    },
+                    height: if walk.height.is_fill() {
+                        Size::Fixed(walk.clamp_height(max_zero_keep_nan(cell.size.y - walk.margin.height())))
+                    } else {
+                        walk.height
+                    },
+                    ..*walk
                 };
                 *self = DeferWalk::Resolved(walk);
                 walk
@@ -1048,6 +1401,19 @@ impl Layout {
         }
     }
 
+    pub fn flow_grid(columns: &[GridTrack]) -> Self {
+        Self {
+            flow: Flow::Grid,
+            grid_columns: GridTracks::new(columns),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_grid_rows(mut self, rows: &[GridTrack]) -> Self {
+        self.grid_rows = GridTracks::new(rows);
+        self
+    }
+
     pub fn with_scroll(mut self, v: DVec2) -> Self {
         self.scroll = v;
         self
@@ -1107,6 +1473,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(0.0),
             height: Size::Fixed(0.0),
+            ..Self::default()
         }
     }
     
@@ -1116,6 +1483,7 @@ impl Walk {
             margin: Margin::default(),
             width: w,
             height: h,
+            ..Self::default()
         }
     }
 
@@ -1125,6 +1493,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(rect.size.x),
             height: Size::Fixed(rect.size.y),
+            ..Self::default()
         }
     }
     
@@ -1134,6 +1503,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(w),
             height: Size::Fixed(h),
+            ..Self::default()
         }
     }
         
@@ -1143,6 +1513,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(size.x),
             height: Size::Fixed(size.y),
+            ..Self::default()
         }
     }
     
@@ -1152,6 +1523,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fit,
             height: Size::Fit,
+            ..Self::default()
         }
     }
     
@@ -1161,6 +1533,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fill,
             height: Size::Fill,
+            ..Self::default()
         }
     }
     
@@ -1170,6 +1543,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fill,
             height: Size::Fit,
+            ..Self::default()
         }
     }
     
@@ -1206,6 +1580,46 @@ impl Walk {
         self.margin = v;
         self
     }
+
+    pub fn with_min_width(mut self, v: f64) -> Self {
+        self.min_width = Some(v);
+        self
+    }
+    
+    pub fn with_max_width(mut self, v: f64) -> Self {
+        self.max_width = Some(v);
+        self
+    }
+    
+    pub fn with_min_height(mut self, v: f64) -> Self {
+        self.min_height = Some(v);
+        self
+    }
+    
+    pub fn with_max_height(mut self, v: f64) -> Self {
+        self.max_height = Some(v);
+        self
+    }
+    
+    pub fn with_fill_weight(mut self, v: f64) -> Self {
+        self.fill_weight = v;
+        self
+    }
+    
+    /// Clamps a width to `min_width`/`max_width`, the minimum wins if they conflict
+    pub fn clamp_width(&self, w: f64) -> f64 {
+        clamp_keep_nan(w, self.min_width, self.max_width)
+    }
+    
+    /// Clamps a height to `min_height`/`max_height`, the minimum wins if they conflict
+    pub fn clamp_height(&self, h: f64) -> f64 {
+        clamp_keep_nan(h, self.min_height, self.max_height)
+    }
+    
+    pub fn with_grid_cell(mut self, v: GridCell) -> Self {
+        self.grid_cell = v;
+        self
+    }
     
     pub fn with_add_padding(mut self, v: Padding) -> Self {
         self.margin.top += v.top;
@@ -1325,6 +1739,158 @@ impl Default for Size {
     }
 }
 
+impl LiveHook for GridTrack {
+    fn skip_apply(&mut self, _cx: &mut Cx, _apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> Option<usize> {
+        match &nodes[index].value {
+            LiveValue::Float32(v) => *self = Self::Fixed(*v as f64),
+            LiveValue::Float64(v) => *self = Self::Fixed(*v),
+            LiveValue::Int64(v) => *self = Self::Fixed(*v as f64),
+            _ => return None
+        }
+        Some(index + 1)
+    }
+}
+
+impl Default for GridTrack {
+    fn default() -> Self {
+        GridTrack::Fit
+    }
+}
+
+impl Default for GridTracks {
+    fn default() -> Self {
+        Self {
+            len: 0,
+            tracks: [GridTrack::Fit; GRID_TRACKS_MAX]
+        }
+    }
+}
+
+impl GridTracks {
+    pub fn new(tracks: &[GridTrack]) -> Self {
+        let mut ret = Self::default();
+        for track in tracks.iter().take(GRID_TRACKS_MAX) {
+            ret.tracks[ret.len] = *track;
+            ret.len += 1;
+        }
+        ret
+    }
+
+    /// `count` equal columns or rows
+    pub fn equal(count: usize) -> Self {
+        Self {
+            len: count.min(GRID_TRACKS_MAX),
+            tracks: [GridTrack::Fr(1.0); GRID_TRACKS_MAX]
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<GridTrack> {
+        self.as_slice().get(index).copied()
+    }
+
+    pub fn as_slice(&self) -> &[GridTrack] {
+        &self.tracks[0..self.len]
+    }
+}
+
+impl LiveHook for GridTracks {}
+impl LiveApply for GridTracks {
+    fn apply(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> usize {
+        match &nodes[index].value {
+            LiveValue::Array => {
+                self.len = 0;
+                let mut index = index + 1;
+                while !nodes[index].is_close() {
+                    if self.len < GRID_TRACKS_MAX {
+                        let mut track = GridTrack::Fit;
+                        index = track.apply(cx, apply, index, nodes);
+                        self.tracks[self.len] = track;
+                        self.len += 1;
+                    }
+                    else {
+                        error!("grid can have at most {} tracks", GRID_TRACKS_MAX);
+                        index = nodes.skip_node(index);
+                    }
+                }
+                index + 1
+            }
+            LiveValue::Int64(v) => {
+                *self = Self::equal((*v).max(0) as usize);
+                index + 1
+            }
+            LiveValue::Float64(v) => {
+                *self = Self::equal(v.max(0.0) as usize);
+                index + 1
+            }
+            _ => {
+                cx.apply_error_expected_array(live_error_origin!(), index, nodes);
+                nodes.skip_node(index)
+            }
+        }
+    }
+}
+
+impl LiveNew for GridTracks {
+    fn new(_cx: &mut Cx) -> Self {
+        Self::default()
+    }
+
+    fn live_type_info(_cx: &mut Cx) -> LiveTypeInfo {
+        LiveTypeInfo {
+            module_id: LiveModuleId::from_str(module_path!()).unwrap(),
+            live_type: LiveType::of::<Self>(),
+            fields: Vec::new(),
+            live_ignore: true,
+            type_name: LiveId::from_str_with_lut("GridTracks").unwrap(),
+        }
+    }
+}
+
+impl Default for GridCell {
+    fn default() -> Self {
+        Self {
+            column: None,
+            row: None,
+            column_span: 1,
+            row_span: 1
+        }
+    }
+}
+
+impl GridCell {
+    pub fn at(column: usize, row: usize) -> Self {
+        Self {
+            column: Some(column),
+            row: Some(row),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_span(mut self, columns: usize, rows: usize) -> Self {
+        self.column_span = columns;
+        self.row_span = rows;
+        self
+    }
+
+    pub fn is_auto(&self) -> bool {
+        self.column.is_none() && self.row.is_none()
+    }
+}
+
+impl GridArea {
+    fn cell(&self) -> GridCell {
+        GridCell::at(self.column, self.row).with_span(self.columns, self.rows)
+    }
+}
+
 impl Size {
     pub fn fixed_or_zero(&self) -> f64 {
         match self {
@@ -1370,3 +1936,129 @@ fn max_zero_keep_nan(v: f64) -> f64 {
         f64::max(v, 0.0)
     }
 }
+
+// splits the leftover space between deferred fill children by weight. children that run into
+// their min or max are frozen at that bound and the rest share what remains, like flex-grow
+fn distribute_fill(left: f64, fills: &[DeferFill]) -> Vec<f64> {
+    if left.is_nan() {
+        return fills.iter().map( | fill | clamp_keep_nan(f64::NAN, fill.min, fill.max)).collect();
+    }
+    let mut sizes = vec![0.0; fills.len()];
+    let mut frozen = vec![false; fills.len()];
+    let mut wanted = vec![0.0; fills.len()];
+    loop {
+        let mut space = left;
+        let mut weight = 0.0;
+        for (i, fill) in fills.iter().enumerate() {
+            if frozen[i] {
+                space -= sizes[i];
+            }
+            else {
+                weight += fill.weight.max(0.0);
+            }
+        }
+        let space = space.max(0.0);
+        let mut violation = 0.0;
+        for (i, fill) in fills.iter().enumerate() {
+            if frozen[i] {
+                continue
+            }
+            wanted[i] = if weight > 0.0 {space * fill.weight.max(0.0) / weight} else {0.0};
+            sizes[i] = clamp_keep_nan(wanted[i], fill.min, fill.max);
+            violation += sizes[i] - wanted[i];
+        }
+        if violation.abs() < 1e-9 {
+            return sizes
+        }
+        // freeze the children clamped in the direction of the total violation
+        let mut any_frozen = false;
+        for i in 0..fills.len() {
+            if !frozen[i] && ((violation > 0.0 && sizes[i] > wanted[i]) || (violation < 0.0 && sizes[i] < wanted[i])) {
+                frozen[i] = true;
+                any_frozen = true;
+            }
+        }
+        if !any_frozen {
+            return sizes
+        }
+    }
+}
+
+// applies max then min, so the min wins when they conflict. NaN (unknown size) stays NaN
+fn clamp_keep_nan(v: f64, min: Option<f64>, max: Option<f64>) -> f64 {
+    if v.is_nan() {
+        return v
+    }
+    let v = if let Some(max) = max {v.min(max)} else {v};
+    if let Some(min) = min {v.max(min)} else {v}
+}
+
+fn nan_to_zero(v: f64) -> f64 {
+    if v.is_nan() {0.0} else {v}
+}
+
+fn grid_track_offset(sizes: &[f64], start: usize, gap: f64) -> f64 {
+    sizes.iter().take(start).map( | s | s + gap).sum::<f64>() + start.saturating_sub(sizes.len()) as f64 * gap
+}
+
+fn grid_track_span(sizes: &[f64], start: usize, count: usize, gap: f64) -> f64 {
+    if count == 0 {
+        return 0.0
+    }
+    sizes.iter().skip(start).take(count).sum::<f64>() + (count - 1) as f64 * gap
+}
+
+// sizes the tracks of one axis. Fixed tracks are their size, fit tracks the largest item in them
+// and fr tracks share what is left. When the space isn't known fr tracks size like fit tracks
+fn grid_track_sizes(
+    tracks: &GridTracks,
+    implicit: GridTrack,
+    count: usize,
+    fit: &[f64],
+    spanning: impl Iterator<Item = (usize, usize, f64)>,
+    available: f64,
+    gap: f64
+) -> Vec<f64> {
+    let track = | i: usize | tracks.get(i).unwrap_or(implicit);
+    let is_content_sized = | i: usize | match track(i) {
+        GridTrack::Fit => true,
+        GridTrack::Fixed(_) => false,
+        GridTrack::Fr(_) => available.is_nan()
+    };
+    let mut sizes: Vec<f64> = (0..count).map( | i | match track(i) {
+        GridTrack::Fixed(v) => v.max(0.0),
+        _ if is_content_sized(i) => fit.get(i).copied().unwrap_or(0.0),
+        _ => 0.0
+    }).collect();
+
+    // an item spanning tracks grows the content sized ones it covers evenly. Like css an item
+    // that covers a resolved Fr track is left out, the Fr track takes up what it needs
+    for (start, span, size) in spanning {
+        let end = (start + span).min(count);
+        if !available.is_nan() && (start..end).any( | i | matches!(track(i), GridTrack::Fr(_))) {
+            continue;
+        }
+        let covered = grid_track_span(&sizes, start, end - start, gap);
+        let grow: Vec<usize> = (start..end).filter( | i | is_content_sized(*i)).collect();
+        if size > covered && !grow.is_empty() {
+            let add = (size - covered) / grow.len() as f64;
+            for i in grow {
+                sizes[i] += add;
+            }
+        }
+    }
+
+    if !available.is_nan() {
+        let fr_total: f64 = (0..count).map( | i | if let GridTrack::Fr(fr) = track(i) {fr.max(0.0)} else {0.0}).sum();
+        if fr_total > 0.0 {
+            let used: f64 = (0..count).filter( | i | !matches!(track(*i), GridTrack::Fr(_))).map( | i | sizes[i]).sum();
+            let left = (available - used - count.saturating_sub(1) as f64 * gap).max(0.0);
+            for (i, size) in sizes.iter_mut().enumerate() {
+                if let GridTrack::Fr(fr) = track(i) {
+                    *size = left * fr.max(0.0) / fr_total;
+                }
+            }
+        }
+    }
+    sizes
+}
@@ -0,0 +1,99 @@
+// Assembling the accessibility tree from the nav lists, with a Cx2d that has no GPU behind it.
+
+use makepad_draw::*;
+
+fn cx_with_draw_shaders() -> Cx {
+    let mut cx = Cx::new(Box::new( | _, _ | {}));
+    makepad_draw::live_design(&mut cx);
+    cx.live_expand();
+    cx
+}
+
+fn area(cx: &mut Cx2d, w: f64, h: f64) -> Area {
+    let mut area = Area::Empty;
+    cx.walk_turtle_with_area(&mut area, Walk::fixed(w, h));
+    area
+}
+
+// a scroll area holding a button and a check box, then a label below it
+fn draw(focus_button: bool) -> AccessibilityTree {
+    let mut cx = cx_with_draw_shaders();
+    let draw_list = DrawList2d::new(&mut cx);
+    let draw_event = DrawEvent::default();
+    let cx = &mut Cx2d::new(&mut cx, &draw_event);
+    let redraw_id = cx.redraw_id;
+    cx.draw_lists[draw_list.draw_list_id()].clear_draw_items(redraw_id);
+    cx.draw_list_stack.push(draw_list.draw_list_id());
+    cx.nav_list_clear(draw_list.draw_list_id());
+
+    cx.begin_turtle(Walk::fixed(400.0, 300.0), Layout::flow_down());
+    cx.begin_turtle(Walk::fixed(200.0, 100.0), Layout::flow_down());
+    let scroll_index = cx.add_begin_scroll();
+    let button = area(cx, 80.0, 20.0);
+    cx.add_nav_stop(button, NavRole::Button, Margin::default());
+    cx.add_accessible(NavAccessible::new(button, AccessibilityRole::Button, "OK"));
+    let check = area(cx, 20.0, 20.0);
+    cx.add_accessible(NavAccessible::new(check, AccessibilityRole::CheckBox, "Agree").with_state(AccessibilityState {
+        checked: Some(true),
+        ..Default::default()
+    }));
+    // nothing to see, so nothing to report
+    let hidden = area(cx, 0.0, 20.0);
+    cx.add_nav_stop(hidden, NavRole::TextInput, Margin::default());
+    let mut scroll = Area::Empty;
+    cx.end_turtle_with_area(&mut scroll);
+    cx.add_end_scroll(scroll_index, scroll);
+    let label = area(cx, 100.0, 10.0);
+    cx.add_accessible(NavAccessible::new(label, AccessibilityRole::Label, "Status").with_value("idle"));
+    cx.end_turtle();
+
+    if focus_button {
+        cx.set_key_focus(button);
+        // the focus moves over once the pending triggers are handled
+        cx.send_trigger(button, Trigger {id: live_id!(focus), from: Area::Empty});
+        cx.handle_triggers();
+    }
+    let mut window = AccessibilityNode::new(AccessibilityRole::Window, "Test");
+    window.bounds = Rect {pos: DVec2::default(), size: dvec2(400.0, 300.0)};
+    Cx2d::accessibility_tree(cx, draw_list.draw_list_id(), window)
+}
+
+#[test]
+fn builds_the_tree_from_nav_lists() {
+    let tree = draw(false);
+    let scroll = tree.find( | node | node.role == AccessibilityRole::ScrollArea).unwrap();
+    let button = tree.find_by_name(AccessibilityRole::Button, "OK").unwrap();
+    let check = tree.find_by_name(AccessibilityRole::CheckBox, "Agree").unwrap();
+    let label = tree.find_by_name(AccessibilityRole::Label, "Status").unwrap();
+    assert_eq!(tree.nodes.len(), 5, "{}", tree);
+    assert_eq!(tree.root().children, [scroll, label]);
+    assert_eq!(tree.nodes[scroll].children, [button, check]);
+    assert_eq!(tree.nodes[button].parent, Some(scroll));
+
+    // the stop and the description of the button end up in one node
+    assert!(tree.nodes[button].state.focusable);
+    assert!(!tree.nodes[check].state.focusable);
+    assert_eq!(tree.nodes[check].state.checked, Some(true));
+    assert_eq!(tree.nodes[label].value.as_deref(), Some("idle"));
+    assert_eq!(tree.nodes[label].bounds, Rect {pos: dvec2(0.0, 100.0), size: dvec2(100.0, 10.0)});
+    assert_eq!(tree.focused(), None);
+}
+
+#[test]
+fn finds_nodes_by_position() {
+    let tree = draw(false);
+    let button = tree.find_by_name(AccessibilityRole::Button, "OK").unwrap();
+    let scroll = tree.nodes[button].parent.unwrap();
+    assert_eq!(tree.node_at(dvec2(10.0, 10.0)), Some(button));
+    assert_eq!(tree.node_at(dvec2(150.0, 10.0)), Some(scroll));
+    assert_eq!(tree.node_at(dvec2(300.0, 200.0)), Some(0));
+    assert_eq!(tree.node_at(dvec2(500.0, 10.0)), None);
+}
+
+#[test]
+fn reports_the_key_focus() {
+    let tree = draw(true);
+    let focused = tree.focused().unwrap();
+    assert_eq!(tree.nodes[focused].name, "OK");
+    assert!(tree.to_string().contains("Button \"OK\" [focused]"), "{}", tree);
+}
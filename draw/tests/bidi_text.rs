@@ -0,0 +1,128 @@
+// Bidirectional text tests. They draw through a headless Cx2d and read the glyph instances back.
+
+use makepad_draw::{
+    *,
+    font_atlas::{CxFont, CxFontsAtlasRc},
+    shader::draw_text::TextWrap,
+};
+
+struct Drawn {
+    cx: Cx,
+    draw_text: DrawText,
+}
+
+fn draw(text: &str, walk: Walk, wrap: TextWrap) -> Drawn {
+    let mut cx = Cx::new(Box::new( | _, _ | {}));
+    makepad_draw::live_design(&mut cx);
+    cx.live_expand();
+
+    Cx2d::lazy_construct_font_atlas(&mut cx);
+    let path = format!("{}/../widgets/resources/LiberationMono-Regular.ttf", env!("CARGO_MANIFEST_DIR"));
+    let font = CxFont::load_from_ttf_bytes(std::rc::Rc::new(std::fs::read(path).unwrap())).ok();
+    let font_id = {
+        let atlas = cx.get_global::<CxFontsAtlasRc>().clone();
+        let mut atlas = atlas.0.borrow_mut();
+        atlas.fonts.push(font);
+        atlas.fonts.len() - 1
+    };
+
+    let mut draw_text = DrawText::new_local(&mut cx);
+    draw_text.text_style.font.font_id = Some(font_id);
+    draw_text.wrap = wrap;
+
+    let pass = Pass::new(&mut cx);
+    let draw_list = DrawList2d::new(&mut cx);
+    {
+        let draw_event = DrawEvent::default();
+        let cx = &mut Cx2d::new(&mut cx, &draw_event);
+        cx.begin_pass(&pass, Some(1.0));
+        let redraw_id = cx.redraw_id;
+        cx.draw_lists[draw_list.draw_list_id()].clear_draw_items(redraw_id);
+        cx.draw_list_stack.push(draw_list.draw_list_id());
+        cx.begin_turtle(Walk::fixed(400.0, 300.0), Layout::default());
+        draw_text.draw_walk(cx, walk, Align::default(), text);
+        cx.end_turtle();
+        cx.draw_list_stack.pop();
+        cx.end_pass(&pass);
+    }
+    Drawn {cx, draw_text}
+}
+
+impl Drawn {
+    fn cursor_x(&self, index: usize) -> f64 {
+        self.draw_text.get_cursor_pos(&self.cx, Vec::new(), 0.0, index).unwrap().x
+    }
+
+    fn offset_at(&self, x: f64) -> usize {
+        let y = self.draw_text.get_cursor_pos(&self.cx, Vec::new(), 0.0, 0).unwrap().y;
+        self.draw_text.closest_offset(&self.cx, Vec::new(), dvec2(x, y + 1.0)).unwrap()
+    }
+}
+
+const MIXED: &str = "abc \u{5D0}\u{5D1}\u{5D2}";
+
+#[test]
+fn rtl_runs_are_reversed() {
+    let drawn = draw(MIXED, Walk::fit(), TextWrap::Line);
+    let clusters: Vec<_> = drawn.draw_text.glyph_clusters.iter().map( | c | (c.index, c.rtl)).collect();
+    // the hebrew run is drawn right to left, its last character first
+    assert_eq!(clusters, vec![
+        (0, false), (1, false), (2, false), (3, false),
+        (6, true), (5, true), (4, true),
+    ]);
+}
+
+#[test]
+fn cursor_follows_reading_direction() {
+    let drawn = draw(MIXED, Walk::fit(), TextWrap::Line);
+    // in front of a latin char is its left edge, in front of a hebrew one its right edge
+    assert!(drawn.cursor_x(1) > drawn.cursor_x(0));
+    assert!(drawn.cursor_x(5) < drawn.cursor_x(4));
+    assert!(drawn.cursor_x(6) < drawn.cursor_x(5));
+    // the end of the text is behind the last hebrew char, at the left of it
+    assert!(drawn.cursor_x(7) < drawn.cursor_x(6));
+    assert!(drawn.cursor_x(7) > drawn.cursor_x(3));
+}
+
+#[test]
+fn closest_offset_round_trips() {
+    let drawn = draw(MIXED, Walk::fit(), TextWrap::Line);
+    for index in [0, 1, 2, 5, 6] {
+        assert_eq!(drawn.offset_at(drawn.cursor_x(index)), index);
+    }
+}
+
+#[test]
+fn selection_splits_at_direction_change() {
+    let drawn = draw(MIXED, Walk::fit(), TextWrap::Line);
+    // "c \u{5D0}" is contiguous in logical order but the alef sits at the far right
+    let rects = drawn.draw_text.get_selection_rects(&drawn.cx, Vec::new(), 2, 5, DVec2::default(), DVec2::default());
+    assert_eq!(rects.len(), 2);
+    let all = drawn.draw_text.get_selection_rects(&drawn.cx, Vec::new(), 0, 7, DVec2::default(), DVec2::default());
+    assert_eq!(all.len(), 1);
+}
+
+#[test]
+fn rtl_paragraph_wraps_from_the_right() {
+    let text = "\u{5D0}\u{5D1} \u{5D2}\u{5D3} \u{5D4}\u{5D5}";
+    let drawn = draw(text, Walk::size(Size::Fixed(200.0), Size::Fit), TextWrap::Word);
+    let x0 = drawn.cursor_x(0);
+    // the first character of a right-to-left paragraph is at the right edge of the box
+    assert!(x0 > 150.0, "{}", x0);
+    assert!(drawn.cursor_x(1) < x0);
+    // and the next word goes to its left
+    assert!(drawn.cursor_x(3) < drawn.cursor_x(1));
+}
+
+#[test]
+fn newlines_keep_their_text_index() {
+    let drawn = draw("ab\ncd", Walk::fit(), TextWrap::Line);
+    let newlines = vec![2];
+    let a = drawn.draw_text.get_cursor_pos(&drawn.cx, newlines.clone(), 0.0, 0).unwrap();
+    let c = drawn.draw_text.get_cursor_pos(&drawn.cx, newlines.clone(), 0.0, 3).unwrap();
+    assert_eq!(a.x, c.x);
+    assert!(c.y > a.y);
+    // past the end of the first line is in front of its newline
+    assert_eq!(drawn.draw_text.closest_offset(&drawn.cx, newlines.clone(), dvec2(a.x + 300.0, a.y + 1.0)), Some(2));
+    assert_eq!(drawn.draw_text.closest_offset(&drawn.cx, newlines, dvec2(c.x + 1.0, c.y + 1.0)), Some(3));
+}
@@ -0,0 +1,71 @@
+// Font fallback tests. IBM Plex Sans has no block elements or card suits, Liberation Mono does.
+
+use makepad_draw::{
+    *,
+    font_atlas::{CxFont, CxFontsAtlas},
+};
+
+const PLEX: usize = 0;
+const MONO: usize = 1;
+
+fn atlas_with_fonts() -> CxFontsAtlas {
+    let mut cx = Cx::new(Box::new( | _, _ | {}));
+    let mut atlas = CxFontsAtlas::new(Texture::new(&mut cx));
+    for file in ["IBMPlexSans-Text.ttf", "LiberationMono-Regular.ttf"] {
+        let path = format!("{}/../widgets/resources/{}", env!("CARGO_MANIFEST_DIR"), file);
+        let bytes = std::fs::read(&path).unwrap_or_else( | _ | panic!("missing font {}", path));
+        atlas.fonts.push(CxFont::load_from_ttf_bytes(std::rc::Rc::new(bytes)).ok());
+    }
+    atlas
+}
+
+fn runs(atlas: &CxFontsAtlas, font_ids: &[usize], text: &str) -> Vec<(usize, String)> {
+    let mut runs = Vec::new();
+    atlas.split_runs_by_font(font_ids.iter().copied(), text, &mut runs);
+    runs.into_iter().map( | (font_id, range) | (font_id, text[range].to_string())).collect()
+}
+
+#[test]
+fn splits_runs_by_coverage() {
+    let atlas = atlas_with_fonts();
+    assert_eq!(runs(&atlas, &[PLEX, MONO], "ab█░cd"), vec![
+        (PLEX, "ab".to_string()),
+        (MONO, "█░".to_string()),
+        (PLEX, "cd".to_string()),
+    ]);
+    // the first font that covers a character wins
+    assert_eq!(runs(&atlas, &[MONO, PLEX], "ab█"), vec![(MONO, "ab█".to_string())]);
+}
+
+#[test]
+fn single_font_is_one_run() {
+    let atlas = atlas_with_fonts();
+    // without fallbacks the primary font shapes everything, missing glyphs included
+    assert_eq!(runs(&atlas, &[PLEX], "a█"), vec![(PLEX, "a█".to_string())]);
+}
+
+#[test]
+fn clusters_stay_in_one_font() {
+    let atlas = atlas_with_fonts();
+    // a variation selector and a zero width joiner sequence don't split off from their base
+    assert_eq!(runs(&atlas, &[PLEX, MONO], "a♥\u{FE0F}\u{200D}b"), vec![
+        (PLEX, "a".to_string()),
+        (MONO, "♥\u{FE0F}\u{200D}b".to_string()),
+    ]);
+}
+
+#[test]
+fn uncovered_clusters_use_the_first_font() {
+    let atlas = atlas_with_fonts();
+    assert_eq!(runs(&atlas, &[PLEX, MONO], "a\u{10FFFD}"), vec![(PLEX, "a\u{10FFFD}".to_string())]);
+}
+
+#[test]
+fn advance_comes_from_the_covering_font() {
+    let mut atlas = atlas_with_fonts();
+    assert_eq!(atlas.char_advance([PLEX].into_iter(), '█', 10.0), None);
+    let mono = atlas.char_advance([MONO].into_iter(), '█', 10.0).unwrap();
+    let chained = atlas.char_advance([PLEX, MONO].into_iter(), '█', 10.0).unwrap();
+    assert!(mono > 0.0);
+    assert_eq!(mono, chained);
+}
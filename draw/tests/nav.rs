@@ -0,0 +1,83 @@
+// Tab order and spatial navigation over nav stop rects. The stops here don't point at real
+// areas; only their rects and order are looked at.
+
+use makepad_draw::*;
+
+fn stop(x: f64, y: f64, w: f64, h: f64, order: NavOrder) -> NavStopRect {
+    NavStopRect {
+        stop: NavStop {role: NavRole::Button, order, margin: Margin::default(), area: Area::Empty},
+        rect: Rect {pos: dvec2(x, y), size: dvec2(w, h)},
+        scroll_stack: Vec::new(),
+    }
+}
+
+// a 3x3 grid of 10x10 stops, 20 apart
+fn grid() -> Vec<NavStopRect> {
+    let mut stops = Vec::new();
+    for row in 0..3 {
+        for col in 0..3 {
+            stops.push(stop(col as f64 * 20.0, row as f64 * 20.0, 10.0, 10.0, NavOrder::Default));
+        }
+    }
+    stops
+}
+
+#[test]
+fn orders_tab_stops() {
+    let stops = vec![
+        stop(0.0, 0.0, 1.0, 1.0, NavOrder::Default),
+        stop(0.0, 0.0, 1.0, 1.0, NavOrder::Bottom(0)),
+        stop(0.0, 0.0, 1.0, 1.0, NavOrder::Top(2)),
+        stop(0.0, 0.0, 1.0, 1.0, NavOrder::Default),
+        stop(0.0, 0.0, 1.0, 1.0, NavOrder::Middle(0)),
+        stop(0.0, 0.0, 1.0, 1.0, NavOrder::Top(1)),
+    ];
+    assert_eq!(nav_tab_order(&stops), [5, 2, 4, 0, 3, 1]);
+}
+
+#[test]
+fn tabs_around() {
+    let stops = vec![
+        stop(0.0, 0.0, 1.0, 1.0, NavOrder::Default),
+        stop(0.0, 0.0, 1.0, 1.0, NavOrder::Top(0)),
+        stop(0.0, 0.0, 1.0, 1.0, NavOrder::Default),
+    ];
+    assert_eq!(nav_next(&stops, None, false), Some(1));
+    assert_eq!(nav_next(&stops, None, true), Some(2));
+    assert_eq!(nav_next(&stops, Some(1), false), Some(0));
+    assert_eq!(nav_next(&stops, Some(2), false), Some(1));
+    assert_eq!(nav_next(&stops, Some(1), true), Some(2));
+    assert_eq!(nav_next(&[], None, false), None);
+}
+
+#[test]
+fn moves_across_a_grid() {
+    let stops = grid();
+    assert_eq!(nav_nearest(&stops, 4, NavDirection::Right), Some(5));
+    assert_eq!(nav_nearest(&stops, 4, NavDirection::Left), Some(3));
+    assert_eq!(nav_nearest(&stops, 4, NavDirection::Up), Some(1));
+    assert_eq!(nav_nearest(&stops, 4, NavDirection::Down), Some(7));
+    assert_eq!(nav_nearest(&stops, 0, NavDirection::Left), None);
+    assert_eq!(nav_nearest(&stops, 0, NavDirection::Up), None);
+    assert_eq!(nav_nearest(&stops, 8, NavDirection::Right), None);
+    assert_eq!(nav_nearest(&stops, 8, NavDirection::Down), None);
+}
+
+#[test]
+fn prefers_stops_straight_ahead() {
+    let stops = vec![
+        stop(0.0, 0.0, 10.0, 10.0, NavOrder::Default),
+        // close but well below
+        stop(15.0, 30.0, 10.0, 10.0, NavOrder::Default),
+        // further but in line
+        stop(40.0, 2.0, 10.0, 10.0, NavOrder::Default),
+    ];
+    assert_eq!(nav_nearest(&stops, 0, NavDirection::Right), Some(2));
+    assert_eq!(nav_nearest(&stops, 0, NavDirection::Down), Some(1));
+    // a wide stop overlapping the current one isn't beyond it
+    let stops = vec![
+        stop(0.0, 0.0, 10.0, 10.0, NavOrder::Default),
+        stop(-20.0, 0.0, 100.0, 10.0, NavOrder::Default),
+    ];
+    assert_eq!(nav_nearest(&stops, 0, NavDirection::Right), None);
+}
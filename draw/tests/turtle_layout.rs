@@ -0,0 +1,159 @@
+// Layout tests for the turtle. They drive a Cx2d without a GPU and check the rects it hands out.
+
+use makepad_draw::*;
+
+fn cx_with_draw_shaders() -> Cx {
+    let mut cx = Cx::new(Box::new( | _, _ | {}));
+    makepad_draw::live_design(&mut cx);
+    cx.live_expand();
+    cx
+}
+
+// lays out `walks` in a 400x300 turtle and returns their final rects
+fn layout(layout: Layout, walks: &[Walk]) -> Vec<Rect> {
+    let mut cx = cx_with_draw_shaders();
+    let draw_list = DrawList2d::new(&mut cx);
+    let draw_event = DrawEvent::default();
+    let cx = &mut Cx2d::new(&mut cx, &draw_event);
+    let redraw_id = cx.redraw_id;
+    cx.draw_lists[draw_list.draw_list_id()].clear_draw_items(redraw_id);
+    cx.draw_list_stack.push(draw_list.draw_list_id());
+
+    cx.begin_turtle(Walk::fixed(400.0, 300.0), layout);
+    let mut areas = vec![Area::Empty; walks.len()];
+    let mut defers = Vec::new();
+    for (i, walk) in walks.iter().enumerate() {
+        if let Some(defer) = cx.defer_walk(*walk) {
+            defers.push((i, defer));
+        }
+        else {
+            cx.walk_turtle_with_area(&mut areas[i], *walk);
+        }
+    }
+    for (i, mut defer) in defers {
+        let walk = defer.resolve(cx);
+        cx.walk_turtle_with_area(&mut areas[i], walk);
+    }
+    cx.end_turtle();
+    areas.iter().map( | area | area.rect(cx)).collect()
+}
+
+fn assert_near(a: f64, b: f64) {
+    assert!((a - b).abs() < 1e-6, "{} != {}", a, b);
+}
+
+#[test]
+fn fill_weight_shares_leftover_space() {
+    let rects = layout(Layout::flow_right(), &[
+        Walk::fixed(100.0, 10.0),
+        Walk::fill(),
+        Walk::fill().with_fill_weight(2.0),
+    ]);
+    assert_near(rects[1].pos.x, 100.0);
+    assert_near(rects[1].size.x, 100.0);
+    assert_near(rects[2].pos.x, 200.0);
+    assert_near(rects[2].size.x, 200.0);
+}
+
+#[test]
+fn fill_bounds_redistribute_leftover_space() {
+    let rects = layout(Layout::flow_right(), &[
+        Walk::fill().with_max_width(50.0),
+        Walk::fixed(10.0, 10.0),
+        Walk::fill(),
+        Walk::fill().with_fill_weight(0.0).with_min_width(40.0),
+    ]);
+    // 390 left: the first is capped at 50 and the zero weight one held at 40, the middle fill takes the rest
+    assert_near(rects[0].size.x, 50.0);
+    assert_near(rects[1].pos.x, 50.0);
+    assert_near(rects[2].pos.x, 60.0);
+    assert_near(rects[2].size.x, 300.0);
+    assert_near(rects[3].pos.x, 360.0);
+    assert_near(rects[3].size.x, 40.0);
+}
+
+#[test]
+fn capped_fills_leave_space_to_align() {
+    let rects = layout(Layout::flow_down().with_align_y(1.0), &[
+        Walk::fixed(10.0, 100.0),
+        Walk::fill().with_max_height(50.0),
+    ]);
+    // the fill stops at 50 so the remaining 150 goes to the alignment
+    assert_near(rects[0].pos.y, 150.0);
+    assert_near(rects[1].pos.y, 250.0);
+    assert_near(rects[1].size.y, 50.0);
+}
+
+#[test]
+fn eval_clamps_fixed_and_fill() {
+    let rects = layout(Layout::flow_down(), &[
+        Walk::fixed(500.0, 10.0).with_max_width(120.0),
+        Walk::size(Size::Fill, Size::Fixed(10.0)).with_max_width(80.0).with_min_height(30.0),
+        Walk::fixed(10.0, 5.0).with_min_height(20.0).with_max_height(15.0),
+    ]);
+    assert_near(rects[0].size.x, 120.0);
+    assert_near(rects[1].size.x, 80.0);
+    assert_near(rects[1].size.y, 30.0);
+    // the minimum wins when the bounds conflict
+    assert_near(rects[2].size.y, 20.0);
+    assert_near(rects[2].pos.y, 40.0);
+}
+
+#[test]
+fn grid_fr_columns_share_what_fixed_ones_leave() {
+    let rects = layout(Layout::flow_grid(&[GridTrack::Fixed(100.0), GridTrack::Fr(1.0), GridTrack::Fr(2.0)]), &[
+        Walk::size(Size::Fill, Size::Fixed(10.0)),
+        Walk::size(Size::Fill, Size::Fixed(10.0)),
+        Walk::size(Size::Fill, Size::Fixed(10.0)),
+        Walk::fixed(10.0, 10.0),
+    ]);
+    assert_near(rects[0].size.x, 100.0);
+    assert_near(rects[1].pos.x, 100.0);
+    assert_near(rects[1].size.x, 100.0);
+    assert_near(rects[2].pos.x, 200.0);
+    assert_near(rects[2].size.x, 200.0);
+    // the fourth wraps to the next row
+    assert_near(rects[3].pos.x, 0.0);
+    assert_near(rects[3].pos.y, 10.0);
+}
+
+#[test]
+fn grid_item_spanning_fit_columns_grows_them() {
+    let rects = layout(Layout::flow_grid(&[GridTrack::Fit, GridTrack::Fit]), &[
+        Walk::fixed(50.0, 10.0),
+        Walk::fixed(20.0, 10.0),
+        Walk::fixed(110.0, 10.0).with_grid_cell(GridCell::at(0, 1).with_span(2, 1)),
+    ]);
+    // the 40 the spanning item needs beyond 70 goes to both columns evenly
+    assert_near(rects[1].pos.x, 70.0);
+    assert_near(rects[2].pos.x, 0.0);
+    assert_near(rects[2].size.x, 110.0);
+}
+
+#[test]
+fn grid_item_spanning_fr_column_leaves_fit_column_alone() {
+    let rects = layout(Layout::flow_grid(&[GridTrack::Fit, GridTrack::Fr(1.0)]), &[
+        Walk::fixed(50.0, 10.0),
+        Walk::fixed(20.0, 10.0),
+        Walk::fill().with_grid_cell(GridCell::default().with_span(2, 1)),
+    ]);
+    assert_near(rects[0].size.x, 50.0);
+    assert_near(rects[1].pos.x, 50.0);
+    assert_near(rects[2].pos.x, 0.0);
+    assert_near(rects[2].size.x, 400.0);
+}
+
+#[test]
+fn grid_places_explicit_cells_and_fills_around_them() {
+    let rects = layout(Layout::flow_grid(&[GridTrack::Fixed(100.0), GridTrack::Fixed(100.0)]), &[
+        Walk::fixed(10.0, 10.0).with_grid_cell(GridCell::at(1, 0)),
+        Walk::fixed(10.0, 10.0),
+        Walk::fixed(10.0, 10.0),
+    ]);
+    assert_near(rects[0].pos.x, 100.0);
+    // the auto placed items take the free cells in order
+    assert_near(rects[1].pos.x, 0.0);
+    assert_near(rects[1].pos.y, rects[0].pos.y);
+    assert_near(rects[2].pos.x, 0.0);
+    assert!(rects[2].pos.y > rects[1].pos.y);
+}
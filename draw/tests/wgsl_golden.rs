@@ -0,0 +1,74 @@
+// Golden tests for the WGSL backend of the shader compiler.
+// Run with MAKEPAD_UPDATE_GOLDEN=1 to rewrite the expected output after an intended change.
+
+use makepad_draw::{
+    *,
+    makepad_platform::makepad_shader_compiler::generate_wgsl,
+    shader::draw_trapezoid::DrawTrapezoidVector,
+};
+
+fn cx_with_draw_shaders() -> Cx {
+    let mut cx = Cx::new(Box::new( | _, _ | {}));
+    makepad_draw::live_design(&mut cx);
+    cx.live_expand();
+    cx
+}
+
+fn check_golden(cx: &Cx, name: &str, draw_vars: &DrawVars) {
+    let draw_shader_ptr = draw_vars.draw_shader.as_ref().expect("draw shader failed to compile").draw_shader_ptr;
+    let draw_shader_def = cx.shader_registry.draw_shader_defs.get(&draw_shader_ptr).unwrap();
+    let const_table = cx.shader_registry.compute_const_table(draw_shader_ptr);
+    let wgsl = generate_wgsl::generate_shader(draw_shader_def, &const_table, &cx.shader_registry);
+    
+    let dir = format!("{}/tests/wgsl", env!("CARGO_MANIFEST_DIR"));
+    let path = format!("{}/{}.wgsl", dir, name);
+    if std::env::var("MAKEPAD_UPDATE_GOLDEN").is_ok() {
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&path, &wgsl).unwrap();
+        return
+    }
+    let expected = std::fs::read_to_string(&path).unwrap_or_else( | _ | panic!("missing golden file {}", path));
+    assert!(wgsl == expected, "generated WGSL for {} differs from {}:\n{}", name, path, wgsl);
+}
+
+#[test]
+fn draw_quad() {
+    let mut cx = cx_with_draw_shaders();
+    let draw = DrawQuad::new_local(&mut cx);
+    check_golden(&cx, "draw_quad", &draw.draw_vars);
+}
+
+#[test]
+fn draw_color() {
+    let mut cx = cx_with_draw_shaders();
+    let draw = DrawColor::new_local(&mut cx);
+    check_golden(&cx, "draw_color", &draw.draw_vars);
+}
+
+#[test]
+fn draw_icon() {
+    let mut cx = cx_with_draw_shaders();
+    let draw = DrawIcon::new_local(&mut cx);
+    check_golden(&cx, "draw_icon", &draw.draw_vars);
+}
+
+#[test]
+fn draw_text() {
+    let mut cx = cx_with_draw_shaders();
+    let draw = DrawText::new_local(&mut cx);
+    check_golden(&cx, "draw_text", &draw.draw_vars);
+}
+
+#[test]
+fn draw_line() {
+    let mut cx = cx_with_draw_shaders();
+    let draw = DrawLine::new_local(&mut cx);
+    check_golden(&cx, "draw_line", &draw.draw_vars);
+}
+
+#[test]
+fn draw_trapezoid_vector() {
+    let mut cx = cx_with_draw_shaders();
+    let draw = DrawTrapezoidVector::new_local(&mut cx);
+    check_golden(&cx, "draw_trapezoid_vector", &draw.draw_vars);
+}
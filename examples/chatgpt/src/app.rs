@@ -161,15 +161,19 @@ impl MatchEvent for App {
                        live_id!(SendChatMessage) => {
                            let label = self.ui.label(id!(message_label));
                            if response.status_code == 200 {
-                               let chat_response = response.get_json_body::<ChatResponse>().unwrap();
-                               let assistant_message = chat_response.choices[0].message.content.clone();
-                               
-                               self.conversation_history.push(Message {
-                                   content: assistant_message,
-                                   role: "assistant".to_string()
+                               // only the reply is needed, so any other fields the API adds don't matter
+                               let assistant_message = response.get_json_value().ok().and_then(|value| {
+                                   value.pointer("/choices/0/message/content")?.string().cloned()
                                });
-                               
-                               self.update_message_label(cx);
+                               if let Some(assistant_message) = assistant_message {
+                                   self.conversation_history.push(Message {
+                                       content: assistant_message,
+                                       role: "assistant".to_string()
+                                   });
+                                   self.update_message_label(cx);
+                               } else {
+                                   label.set_text_and_redraw(cx, "Unexpected response from OpenAI");
+                               }
                            } else {
                                label.set_text_and_redraw(cx, "Failed to connect with OpenAI");
                            }
@@ -208,29 +212,3 @@ struct Message {
     pub content: String,
     pub role: String
 }
-
-#[derive(SerJson, DeJson)]
-struct ChatResponse {
-    pub id: String,
-    pub object: String,
-    pub created: i32,
-    pub model: String,
-    pub usage: Usage,
-    pub choices: Vec<Choice>,
-    pub system_fingerprint: Option<String>,
-}
-
-#[derive(SerJson, DeJson)]
-pub struct Usage {
-    prompt_tokens: i32,
-    completion_tokens: i32,
-    total_tokens: i32,
-}
-
-#[derive(SerJson, DeJson)]
-struct Choice {
-    message: Message,
-    finish_reason: String,
-    index: i32,
-    logprobs: Option<String>,
-}
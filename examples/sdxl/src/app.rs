@@ -532,7 +532,9 @@ impl App {
         start_http_server(HttpServer {
             listen_address: addr,
             post_max_size: 1024 * 1024,
-            request: tx_request
+            request: tx_request,
+            static_files: None,
+            worker_threads: 4,
         });
         let remote_screens = self.remote_screens.clone();
         std::thread::spawn(move || {
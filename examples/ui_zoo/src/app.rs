@@ -259,6 +259,26 @@ live_design!{
                             <ZooBlock> {draw_bg:{color: (DEMO_COLOR_3)}}
                         }
                     }
+
+                    <ZooDesc> {text:"This is a view with flow set to Grid. The columns are a fixed, a fit and two fractional tracks, spacing and line_spacing are the gaps between them and grid_cell spans items over several cells."}
+                    <View> {
+                        height: Fit,
+                        flow: Grid,
+                        grid_columns: [50, Fit, Fr(1.0), Fr(2.0)],
+                        padding: 10.
+                        spacing: 10.
+                        line_spacing: 10.
+                        show_bg: true,
+                        draw_bg: { color: (COLOR_CONTAINER) }
+                        <ZooBlock> {draw_bg:{color: (DEMO_COLOR_1)}}
+                        <ZooBlock> {width: 100, draw_bg:{color: (DEMO_COLOR_2)}}
+                        <ZooBlock> {width: Fill, draw_bg:{color: (DEMO_COLOR_3)}}
+                        <ZooBlock> {width: Fill, draw_bg:{color: (DEMO_COLOR_1)}}
+                        <ZooBlock> {width: Fill, height: Fill, grid_cell: {column_span: 2, row_span: 2}, draw_bg:{color: (DEMO_COLOR_3)}}
+                        <ZooBlock> {width: Fill, draw_bg:{color: (DEMO_COLOR_2)}}
+                        <ZooBlock> {width: Fill, draw_bg:{color: (DEMO_COLOR_1)}}
+                        <ZooBlock> {width: Fill, grid_cell: {column_span: 2}, draw_bg:{color: (DEMO_COLOR_2)}}
+                    }
                 }
 
                 <ZooHeader> {
@@ -548,7 +568,7 @@ live_design!{
                                 show_bg: true, draw_bg: { color: (THEME_COLOR_BG_CONTAINER)}, width: 125, height: 250,
                                 <Image> { height: Fill, source: dep("crate://self/resources/ducky.png" ), min_height: 100 }
                             }
-                            <P> { text: "min_height: 100" } // TODO: get this to work correctly
+                            <P> { text: "min_height: 100" }
                         }
                         <View> {
                             width: Fit, height: Fit, flow: Down,
@@ -1,17 +1,30 @@
 // this webserver is serving our site. Why? WHYYY. Because it was fun to write. And MUCH faster and MUCH simpler than anything else imaginable.
 
 use std::net::{TcpListener, TcpStream, SocketAddr, Shutdown};
-use std::io::prelude::*;
-use std::sync::{mpsc, mpsc::{RecvTimeoutError}};
+use std::io::{prelude::*, BufReader};
+use std::sync::{Arc, Mutex, mpsc, mpsc::{RecvTimeoutError}};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 pub use crate::websocket::{SERVER_WEB_SOCKET_PONG_MESSAGE, ServerWebSocket, ServerWebSocketMessage, ServerWebSocketMessageFormat, ServerWebSocketMessageHeader, SERVER_WEB_SOCKET_PING_MESSAGE};
+pub use crate::static_files::HttpStaticFiles;
 use crate::utils::*;
 
+// how long an idle keep-alive connection is kept open, it waits on a small thread of its own not on a worker
+const KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(5);
+// how long a worker waits on a client that is in the middle of sending a request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+// the idle threads only block on a peek, they don't need the default 2MB
+const IDLE_THREAD_STACK_SIZE: usize = 64 * 1024;
+
 #[derive(Clone)]
 pub struct HttpServer {
     pub listen_address: SocketAddr,
     pub request: mpsc::Sender<HttpServerRequest>,
-    pub post_max_size: u64
+    pub post_max_size: u64,
+    /// served on the connection workers, GETs that don't resolve to a file still go to `request`
+    pub static_files: Option<HttpStaticFiles>,
+    /// size of the pool handling connections, websockets get a thread of their own
+    pub worker_threads: usize,
 }
 
 pub struct HttpServerResponse {
@@ -50,92 +63,157 @@ pub fn start_http_server(
     
     let listener = if let Ok(listener) = TcpListener::bind(http_server.listen_address) {listener} else {println!("Cannot bind http server port"); return None};
     
+    // the listener blocks on this when all workers are busy
+    let (tx_connection, rx_connection) = mpsc::sync_channel::<TcpStream>(64);
+    let rx_connection = Arc::new(Mutex::new(rx_connection));
+    let web_socket_counter = Arc::new(AtomicU64::new(0));
+    for _ in 0..http_server.worker_threads.max(1) {
+        let rx_connection = rx_connection.clone();
+        let http_server = http_server.clone();
+        let web_socket_counter = web_socket_counter.clone();
+        let tx_connection = tx_connection.clone();
+        std::thread::spawn(move || {
+            loop {
+                let tcp_stream = rx_connection.lock().unwrap().recv();
+                if let Ok(tcp_stream) = tcp_stream {
+                    handle_connection(&http_server, tcp_stream, &web_socket_counter, &tx_connection);
+                }
+                else {
+                    return
+                }
+            }
+        });
+    }
+    
     let listen_thread = {
         std::thread::spawn(move || {
-            let mut connection_counter = 0u64;
             for tcp_stream in listener.incoming() {
-                let mut tcp_stream = if let Ok(tcp_stream) = tcp_stream {
+                let tcp_stream = if let Ok(tcp_stream) = tcp_stream {
                     tcp_stream
                 }
                 else {
                     println!("Incoming stream failure");
                     continue
                 };
-                let http_server = http_server.clone();
-                connection_counter += 1;
-                let _read_thread = std::thread::spawn(move || {
-                    
-                    let headers = HttpServerHeaders::from_tcp_stream(&mut tcp_stream);
-                    if headers.is_none() {
-                        return http_error_out(tcp_stream, 500);
-                    }
-                    let headers = headers.unwrap();
-                    
-                    if headers.sec_websocket_key.is_some() {
-                        return handle_web_socket(http_server, tcp_stream, headers, connection_counter);
-                    }
-                    if headers.verb == "POST" {
-                        return handle_post(http_server, tcp_stream, headers);
-                    }
-                    if headers.verb == "GET" {
-                        return handle_get(http_server, tcp_stream, headers);
-                    }
-                    http_error_out(tcp_stream, 500)
-                });
+                if tx_connection.send(tcp_stream).is_err() {
+                    return
+                }
             }
         })
     };
     Some(listen_thread)
 }
 
-fn handle_post(http_server: HttpServer, mut tcp_stream: TcpStream, headers: HttpServerHeaders) {
+// keep-alive connections wait here between requests so they don't hold on to a worker.
+// The peek blocks until the next request starts coming in, then the connection goes back on the worker queue
+fn wait_for_next_request(tcp_stream: TcpStream, tx_connection: mpsc::SyncSender<TcpStream>) {
+    let spawned = std::thread::Builder::new().stack_size(IDLE_THREAD_STACK_SIZE).spawn(move || {
+        let _ = tcp_stream.set_read_timeout(Some(KEEP_ALIVE_TIMEOUT));
+        match tcp_stream.peek(&mut [0u8; 1]) {
+            Ok(n) if n > 0 => {
+                let _ = tx_connection.send(tcp_stream);
+            }
+            // closed by the client, timed out or failed
+            _ => {
+                let _ = tcp_stream.shutdown(Shutdown::Both);
+            }
+        }
+    });
+    if spawned.is_err() {
+        println!("Cannot spawn keep-alive thread");
+    }
+}
+
+fn handle_connection(http_server: &HttpServer, tcp_stream: TcpStream, web_socket_counter: &AtomicU64, tx_connection: &mpsc::SyncSender<TcpStream>) {
+    let addr = if let Ok(addr) = tcp_stream.peer_addr() {addr} else {return};
+    let _ = tcp_stream.set_read_timeout(Some(REQUEST_TIMEOUT));
+    let mut reader = BufReader::new(tcp_stream);
+    let mut first_request = true;
+    loop {
+        let headers = HttpServerHeaders::from_reader(addr, &mut reader);
+        if headers.is_none() {
+            // a keep-alive connection closing or timing out is not an error
+            if first_request {
+                return http_error_out(reader.into_inner(), 500);
+            }
+            let _ = reader.get_ref().shutdown(Shutdown::Both);
+            return
+        }
+        let headers = headers.unwrap();
+        first_request = false;
+        
+        if headers.sec_websocket_key.is_some() {
+            let _ = reader.get_ref().set_read_timeout(None);
+            let web_socket_id = web_socket_counter.fetch_add(1, Ordering::Relaxed) + 1;
+            let http_server = http_server.clone();
+            std::thread::spawn(move || handle_web_socket(http_server, reader, headers, web_socket_id));
+            return
+        }
+        let keep_alive = match headers.verb.as_str() {
+            "POST" => handle_post(http_server, &mut reader, headers),
+            "GET" | "HEAD" => handle_get(http_server, &mut reader, headers),
+            _ => return http_error_out(reader.into_inner(), 500)
+        };
+        if !keep_alive {
+            let _ = reader.get_ref().shutdown(Shutdown::Both);
+            return
+        }
+        // a pipelined request is already in our buffer, otherwise wait for the next one off the worker
+        if reader.buffer().is_empty() {
+            wait_for_next_request(reader.into_inner(), tx_connection.clone());
+            return
+        }
+    }
+}
+
+// app responses are preformatted, we can only reuse the connection if they are properly delimited
+fn response_keeps_alive(header: &str) -> bool {
+    let header = header.to_ascii_lowercase();
+    header.contains("content-length:") && !header.contains("connection: close")
+}
+
+fn handle_post(http_server: &HttpServer, reader: &mut BufReader<TcpStream>, headers: HttpServerHeaders) -> bool {
     // we have to have a content-length or bust
     if headers.content_length.is_none() {
-        return http_error_out(tcp_stream, 500);
+        return http_empty_response(reader.get_mut(), "500", "", false);
     }
     let content_length = headers.content_length.unwrap();
     if content_length > http_server.post_max_size {
-        return http_error_out(tcp_stream, 500);
+        return http_empty_response(reader.get_mut(), "500", "", false);
     }
-    let bytes_total = content_length as usize;
-    let mut body = Vec::new();
-    body.resize(bytes_total, 0u8);
-    
-    let mut bytes_left = bytes_total;
-    while bytes_left > 0 {
-        let buf = &mut body[(bytes_total - bytes_left)..bytes_total];
-        let bytes_read = tcp_stream.read(buf);
-        if bytes_read.is_err() {
-            return http_error_out(tcp_stream, 500);
-        }
-        let bytes_read = bytes_read.unwrap();
-        if bytes_read == 0 {
-            return http_error_out(tcp_stream, 500);
-        }
-        bytes_left -= bytes_read;
+    let mut body = vec![0u8; content_length as usize];
+    if reader.read_exact(&mut body).is_err() {
+        return http_empty_response(reader.get_mut(), "500", "", false);
     }
     
+    let keep_alive = headers.keep_alive();
     let (tx_socket, rx_socket) = mpsc::channel::<HttpServerResponse> ();
     if http_server.request.send(HttpServerRequest::Post {
         headers,
         body,
         response: tx_socket
     }).is_err() {
-        return http_error_out(tcp_stream, 500);
+        return http_empty_response(reader.get_mut(), "500", "", false);
     };
     
     if let Ok(response) = rx_socket.recv() {
-        write_bytes_to_tcp_stream_no_error(&mut tcp_stream, response.header.as_bytes());
-        write_bytes_to_tcp_stream_no_error(&mut tcp_stream, &response.body);
+        let tcp_stream = reader.get_mut();
+        if write_bytes_to_tcp_stream_no_error(tcp_stream, response.header.as_bytes())
+            || write_bytes_to_tcp_stream_no_error(tcp_stream, &response.body) {
+            return false
+        }
+        return keep_alive && response_keeps_alive(&response.header)
     }
-    let _ = tcp_stream.shutdown(Shutdown::Both);
+    false
 }
 
-fn handle_web_socket(http_server: HttpServer, mut tcp_stream: TcpStream, headers: HttpServerHeaders, web_socket_id: u64) {
+fn handle_web_socket(http_server: HttpServer, mut reader: BufReader<TcpStream>, headers: HttpServerHeaders, web_socket_id: u64) {
     let upgrade_response = ServerWebSocket::create_upgrade_response(headers.sec_websocket_key.as_ref().unwrap());
 
-    write_bytes_to_tcp_stream_no_error(&mut tcp_stream, upgrade_response.as_bytes());
+    write_bytes_to_tcp_stream_no_error(reader.get_mut(), upgrade_response.as_bytes());
     
+    // reads go through the reader as it may have buffered the first frames already
+    let tcp_stream = reader.get_ref().try_clone().unwrap();
     let mut write_tcp_stream = tcp_stream.try_clone().unwrap();
     let (tx_socket, rx_socket) = mpsc::channel::<Vec<u8 >> ();
     
@@ -174,7 +252,7 @@ fn handle_web_socket(http_server: HttpServer, mut tcp_stream: TcpStream, headers
     let mut web_socket = ServerWebSocket::new();
     loop {
         let mut data = [0u8; 65535];
-        match tcp_stream.read(&mut data) {
+        match reader.read(&mut data) {
             Ok(n) => {
                 if n == 0 {
                     let _ = tcp_stream.shutdown(Shutdown::Both);
@@ -226,19 +304,32 @@ fn handle_web_socket(http_server: HttpServer, mut tcp_stream: TcpStream, headers
     });
 }
 
-fn handle_get(http_server: HttpServer, mut tcp_stream: TcpStream, headers: HttpServerHeaders) {
-    // send our channel the post
+fn handle_get(http_server: &HttpServer, reader: &mut BufReader<TcpStream>, headers: HttpServerHeaders) -> bool {
+    let keep_alive = headers.keep_alive();
+    if let Some(static_files) = &http_server.static_files {
+        if let Some(file_path) = static_files.resolve(&headers.path) {
+            return static_files.serve(reader.get_mut(), &headers, &file_path, keep_alive);
+        }
+    }
+    if headers.verb == "HEAD" {
+        return http_empty_response(reader.get_mut(), "404 Not Found", "", false);
+    }
+    // send our channel the get
     let (tx_socket, rx_socket) = mpsc::channel::<HttpServerResponse> ();
     if http_server.request.send(HttpServerRequest::Get {
         headers,
         response_sender: tx_socket
     }).is_err() {
-        return http_error_out(tcp_stream, 500);
+        return http_empty_response(reader.get_mut(), "500", "", false);
     };
     
     if let Ok(response) = rx_socket.recv() {
-        write_bytes_to_tcp_stream_no_error(&mut tcp_stream, response.header.as_bytes());
-        write_bytes_to_tcp_stream_no_error(&mut tcp_stream, &response.body);
+        let tcp_stream = reader.get_mut();
+        if write_bytes_to_tcp_stream_no_error(tcp_stream, response.header.as_bytes())
+            || write_bytes_to_tcp_stream_no_error(tcp_stream, &response.body) {
+            return false
+        }
+        return keep_alive && response_keeps_alive(&response.header)
     }
-    let _ = tcp_stream.shutdown(Shutdown::Both);
-}
\ No newline at end of file
+    false
+}
@@ -0,0 +1,352 @@
+// serves files straight from disk on the connection workers, so apps don't have to pump
+// every asset through their request channel. Supports Range, ETag and gzip.
+
+use std::fs::File;
+use std::io::{prelude::*, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::net::TcpStream;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+use crate::utils::*;
+
+// files smaller than this aren't worth compressing, bigger ones we don't want to hold in memory
+const GZIP_MIN_SIZE: u64 = 1024;
+const GZIP_MAX_SIZE: u64 = 64 * 1024 * 1024;
+// compressed bodies are kept until they add up to this, then the least recently used ones go first
+const GZIP_CACHE_MAX_SIZE: usize = 128 * 1024 * 1024;
+
+#[derive(Clone, Default)]
+pub struct HttpStaticFiles {
+    /// url prefix to directory, the first matching prefix wins
+    pub mounts: Vec<(String, PathBuf)>,
+    /// extra header lines added to every file response, each ending in \r\n
+    pub extra_headers: String,
+    /// compress text-like files with gzip when the client accepts it
+    pub gzip: bool,
+    pub gzip_cache: HttpGzipCache,
+}
+
+struct GzipCacheEntry {
+    // etag of the file it came from
+    etag: String,
+    body: Arc<Vec<u8>>,
+    last_used: u64,
+}
+
+struct GzipCacheInner {
+    entries: HashMap<PathBuf, GzipCacheEntry>,
+    size: usize,
+    max_size: usize,
+    clock: u64,
+}
+
+/// Compressed file bodies by path, shared between the workers
+#[derive(Clone)]
+pub struct HttpGzipCache(Arc<Mutex<GzipCacheInner>>);
+
+impl Default for HttpGzipCache {
+    fn default() -> Self {
+        Self::with_max_size(GZIP_CACHE_MAX_SIZE)
+    }
+}
+
+impl HttpGzipCache {
+    /// A cache holding at most `max_size` bytes of compressed bodies
+    pub fn with_max_size(max_size: usize) -> Self {
+        Self(Arc::new(Mutex::new(GzipCacheInner {
+            entries: HashMap::new(),
+            size: 0,
+            max_size,
+            clock: 0,
+        })))
+    }
+
+    /// Total size of the compressed bodies held
+    pub fn size(&self) -> usize {
+        self.0.lock().unwrap().size
+    }
+
+    fn get(&self, file_path: &Path, etag: &str) -> Option<Arc<Vec<u8>>> {
+        let mut inner = self.0.lock().unwrap();
+        inner.clock += 1;
+        let clock = inner.clock;
+        let entry = inner.entries.get_mut(file_path).filter( | entry | entry.etag == etag) ?;
+        entry.last_used = clock;
+        Some(entry.body.clone())
+    }
+
+    fn insert(&self, file_path: &Path, etag: &str, body: Arc<Vec<u8>>) {
+        let mut inner = self.0.lock().unwrap();
+        if let Some(old) = inner.entries.remove(file_path) {
+            inner.size -= old.body.len();
+        }
+        if body.len() > inner.max_size {
+            return
+        }
+        while inner.size + body.len() > inner.max_size {
+            let oldest = inner.entries.iter().min_by_key( | (_, entry) | entry.last_used).map( | (path, _) | path.clone());
+            let Some(oldest) = oldest else {break};
+            let old = inner.entries.remove(&oldest).unwrap();
+            inner.size -= old.body.len();
+        }
+        inner.clock += 1;
+        inner.size += body.len();
+        let last_used = inner.clock;
+        inner.entries.insert(file_path.to_path_buf(), GzipCacheEntry {etag: etag.to_string(), body, last_used});
+    }
+}
+
+pub fn mime_type_from_path(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match ext.as_str() {
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" => "text/javascript",
+        "json" | "map" => "application/json",
+        "wasm" => "application/wasm",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        _ => return None
+    })
+}
+
+fn is_compressible(mime_type: &str) -> bool {
+    mime_type.starts_with("text/") || matches!(mime_type,
+        "application/json" | "application/wasm" | "application/xml" | "image/svg+xml" | "font/ttf" | "font/otf"
+    )
+}
+
+fn percent_decode(inp: &str) -> Option<String> {
+    let bytes = inp.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        }
+        else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// Parses a single `bytes=` range against the file length into an inclusive start and end.
+/// Returns None when the header should be ignored and Some(Err) when it can't be satisfied
+pub fn parse_range_header(range: &str, len: u64) -> Option<Result<(u64, u64), ()>> {
+    let range = range.trim().strip_prefix("bytes=")?;
+    // multiple ranges would need a multipart response, serving the whole file is allowed instead
+    if range.contains(',') {
+        return None
+    }
+    let (start, end) = range.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+    if start.is_empty() {
+        // the last n bytes
+        let suffix: u64 = end.parse().ok()?;
+        if suffix == 0 || len == 0 {
+            return Some(Err(()))
+        }
+        return Some(Ok((len.saturating_sub(suffix), len - 1)))
+    }
+    let start: u64 = start.parse().ok()?;
+    let end: u64 = if end.is_empty() {len.saturating_sub(1)} else {end.parse().ok()?};
+    if start >= len {
+        return Some(Err(()))
+    }
+    if end < start {
+        return None
+    }
+    Some(Ok((start, end.min(len - 1))))
+}
+
+fn accepts_gzip(accept_encoding: &str) -> bool {
+    accept_encoding.split(',').any( | enc | {
+        let mut parts = enc.split(';');
+        let name = parts.next().unwrap_or("").trim();
+        let q_zero = parts.any( | p | {
+            let p = p.trim();
+            p == "q=0" || p.starts_with("q=0.") && p[4..].chars().all( | c | c == '0')
+        });
+        (name.eq_ignore_ascii_case("gzip") || name == "*") && !q_zero
+    })
+}
+
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match.split(',').any( | tag | {
+        let tag = tag.trim();
+        tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag
+    })
+}
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {0xedb88320 ^ (c >> 1)} else {c >> 1};
+            k += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+}
+
+static CRC32_TABLE: [u32; 256] = crc32_table();
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc = CRC32_TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+/// Wraps a raw deflate stream from miniz in a gzip header and trailer
+pub fn gzip_compress(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 255];
+    out.extend_from_slice(&makepad_miniz::compress_to_vec(data, 6));
+    out.extend_from_slice(&crc32(data).to_le_bytes());
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out
+}
+
+impl HttpStaticFiles {
+    /// Maps a request path onto a file in one of the mounts. Only files with a known mime type are served
+    pub fn resolve(&self, url_path: &str) -> Option<PathBuf> {
+        let path = percent_decode(url_path)?;
+        if path.split('/').any( | seg | seg == "..") || path.contains(['\\', ':', '\0']) {
+            return None
+        }
+        let (prefix, dir) = self.mounts.iter().find( | (prefix, _) | path.starts_with(prefix.as_str())) ?;
+        let file_path = dir.join(path[prefix.len()..].trim_start_matches('/'));
+        mime_type_from_path(&file_path) ?;
+        if !file_path.is_file() {
+            return None
+        }
+        Some(file_path)
+    }
+
+    fn gzip_body(&self, file: &mut File, file_path: &Path, etag: &str) -> Option<Arc<Vec<u8>>> {
+        if let Some(body) = self.gzip_cache.get(file_path, etag) {
+            return Some(body)
+        }
+        let mut data = Vec::new();
+        file.read_to_end(&mut data).ok() ?;
+        let body = Arc::new(gzip_compress(&data));
+        self.gzip_cache.insert(file_path, etag, body.clone());
+        Some(body)
+    }
+
+    /// Writes the response for a GET or HEAD of a resolved file. Returns false when the connection has to be closed
+    pub fn serve(&self, tcp_stream: &mut TcpStream, headers: &HttpServerHeaders, file_path: &Path, keep_alive: bool) -> bool {
+        let mime_type = mime_type_from_path(file_path).unwrap_or("application/octet-stream");
+        let connection = format!("Connection: {}\r\n", if keep_alive {"keep-alive"} else {"close"});
+        let (mut file, meta) = match File::open(file_path).and_then( | f | f.metadata().map( | m | (f, m))) {
+            Ok(v) => v,
+            Err(_) => return http_empty_response(tcp_stream, "404 Not Found", &connection, keep_alive)
+        };
+        let len = meta.len();
+        let modified = meta.modified().ok().and_then( | t | t.duration_since(UNIX_EPOCH).ok()).map( | d | d.as_nanos()).unwrap_or(0);
+
+        let range = headers.header("Range").filter( | _ | {
+            // a range only applies to the version of the file the client already has
+            headers.header("If-Range").is_none_or( | tag | tag == format!("\"{:x}-{:x}\"", len, modified))
+        }).and_then( | range | parse_range_header(range, len));
+
+        let use_gzip = self.gzip && range.is_none() && is_compressible(mime_type)
+            && (GZIP_MIN_SIZE..=GZIP_MAX_SIZE).contains(&len)
+            && headers.accept_encoding.as_deref().is_some_and(accepts_gzip);
+
+        let etag = if use_gzip {
+            format!("\"{:x}-{:x}-gzip\"", len, modified)
+        }
+        else {
+            format!("\"{:x}-{:x}\"", len, modified)
+        };
+        let common = format!(
+            "Content-Type: {}\r\n\
+            ETag: {}\r\n\
+            Accept-Ranges: bytes\r\n\
+            Vary: Accept-Encoding\r\n\
+            {}{}",
+            mime_type,
+            etag,
+            connection,
+            self.extra_headers
+        );
+
+        if headers.header("If-None-Match").is_some_and( | tags | etag_matches(tags, &etag)) {
+            return http_empty_response(tcp_stream, "304 Not Modified", &common, keep_alive)
+        }
+
+        let (status, content_range, start, body_len) = match range {
+            Some(Err(())) => {
+                let header = format!("{}Content-Range: bytes */{}\r\n", connection, len);
+                return http_empty_response(tcp_stream, "416 Range Not Satisfiable", &header, keep_alive)
+            }
+            Some(Ok((start, end))) => {
+                ("206 Partial Content", format!("Content-Range: bytes {}-{}/{}\r\n", start, end, len), start, end - start + 1)
+            }
+            None if use_gzip => {
+                let body = if let Some(body) = self.gzip_body(&mut file, file_path, &etag) {body} else {
+                    return http_empty_response(tcp_stream, "500 Internal Server Error", "Connection: close\r\n", false)
+                };
+                let header = format!(
+                    "HTTP/1.1 200 OK\r\n{}Content-Encoding: gzip\r\nContent-Length: {}\r\n\r\n",
+                    common,
+                    body.len()
+                );
+                if write_bytes_to_tcp_stream_no_error(tcp_stream, header.as_bytes()) {
+                    return false
+                }
+                if headers.verb != "HEAD" && write_bytes_to_tcp_stream_no_error(tcp_stream, &body) {
+                    return false
+                }
+                return keep_alive
+            }
+            None => ("200 OK", String::new(), 0, len)
+        };
+
+        let header = format!("HTTP/1.1 {}\r\n{}{}Content-Length: {}\r\n\r\n", status, common, content_range, body_len);
+        if write_bytes_to_tcp_stream_no_error(tcp_stream, header.as_bytes()) {
+            return false
+        }
+        if headers.verb == "HEAD" {
+            return keep_alive
+        }
+        // stream the file so large videos don't end up in memory
+        if file.seek(SeekFrom::Start(start)).is_err() {
+            return false
+        }
+        match std::io::copy(&mut file.take(body_len), tcp_stream) {
+            Ok(copied) if copied == body_len => keep_alive,
+            _ => false
+        }
+    }
+}
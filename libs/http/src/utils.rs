@@ -1,5 +1,5 @@
 use std::net::{TcpStream, Shutdown, SocketAddr};
-use std::io::BufReader;
+use std::io::{BufRead, BufReader};
 use std::io::prelude::*;
 
 pub fn write_bytes_to_tcp_stream_no_error(tcp_stream: &mut TcpStream, bytes: &[u8]) -> bool {
@@ -25,6 +25,13 @@ pub fn http_error_out(mut tcp_stream: TcpStream, code: usize) {
     let _ = tcp_stream.shutdown(Shutdown::Both);
 }
 
+// writes a response without a body, returns if the connection can stay open
+pub fn http_empty_response(tcp_stream: &mut TcpStream, status: &str, headers: &str, keep_alive: bool) -> bool {
+    let content_length = if status.starts_with("304") {""} else {"Content-Length: 0\r\n"};
+    let header = format!("HTTP/1.1 {}\r\n{}{}\r\n", status, headers, content_length);
+    !write_bytes_to_tcp_stream_no_error(tcp_stream, header.as_bytes()) && keep_alive
+}
+
 
 pub fn split_header_line<'a>(inp: &'a str, what: &str) -> Option<&'a str> {
     let mut what_lc = what.to_string();
@@ -75,14 +82,21 @@ impl HttpServerHeaders {
     pub fn from_tcp_stream(tcp_stream: &mut TcpStream) -> Option<HttpServerHeaders> {
         let addr = tcp_stream.peer_addr().unwrap();
         let mut reader = BufReader::new(tcp_stream);
-        
+        Self::from_reader(addr, &mut reader)
+    }
+    
+    // reads one request head, keep-alive connections call this repeatedly on the same reader
+    pub fn from_reader(addr: SocketAddr, reader: &mut impl BufRead) -> Option<HttpServerHeaders> {
         let mut lines = Vec::new();
         let mut content_length = None;
         let mut accept_encoding = None;
         let mut sec_websocket_key = None;
         let mut line = String::new();
         
-        while reader.read_line(&mut line).is_ok() { // TODO replace this with a non-line read
+        while let Ok(bytes_read) = reader.read_line(&mut line) { // TODO replace this with a non-line read
+            if bytes_read == 0 { // connection closed
+                return None
+            }
             if line == "\r\n" { // the newline
                 break;
             }
@@ -112,6 +126,10 @@ impl HttpServerHeaders {
             verb = "GET";
             path = parse_url_path(v)
         }
+        else if let Some(v) = split_header_line(&lines[0], "HEAD ") {
+            verb = "HEAD";
+            path = parse_url_path(v)
+        }
         else if let Some(v) = split_header_line(&lines[0], "POST ") {
             verb = "POST";
             path = parse_url_path(v)
@@ -142,4 +160,23 @@ impl HttpServerHeaders {
             sec_websocket_key
         })
     }
+    
+    // case insensitive lookup of a header value
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.lines.iter().skip(1).find_map( | line | {
+            let (key, value) = line.split_once(':') ?;
+            if key.trim().eq_ignore_ascii_case(name) {Some(value.trim())} else {None}
+        })
+    }
+    
+    // HTTP/1.1 connections stay open unless asked otherwise, 1.0 ones only when asked
+    pub fn keep_alive(&self) -> bool {
+        let connection = self.header("Connection").map( | v | v.to_ascii_lowercase());
+        if self.lines[0].trim_end().ends_with("HTTP/1.0") {
+            connection.is_some_and( | v | v.contains("keep-alive"))
+        }
+        else {
+            !connection.is_some_and( | v | v.contains("close"))
+        }
+    }
 }
@@ -0,0 +1,249 @@
+// Tests for the static file responses and keep-alive connections, against a loopback socket.
+
+use makepad_http::server::*;
+use makepad_http::static_files::{parse_range_header, HttpGzipCache};
+use makepad_http::utils::HttpServerHeaders;
+use std::io::{prelude::*, BufReader};
+use std::net::{TcpListener, TcpStream, SocketAddr};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+const BODY: &str = "0123456789abcdefghij";
+
+// a directory of its own per test with index.txt holding BODY and big.js holding something worth compressing
+fn test_files(name: &str) -> HttpStaticFiles {
+    let dir = std::env::temp_dir().join(format!("makepad_http_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("index.txt"), BODY).unwrap();
+    std::fs::write(dir.join("big.js"), "console.log(1);\n".repeat(200)).unwrap();
+    HttpStaticFiles {
+        mounts: vec![("/".to_string(), dir)],
+        ..Default::default()
+    }
+}
+
+// the response head and body `serve` writes for a request head
+fn serve(static_files: &HttpStaticFiles, request: &str) -> (String, Vec<u8>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut server, addr) = listener.accept().unwrap();
+    let headers = HttpServerHeaders::from_reader(addr, &mut request.as_bytes()).unwrap();
+    let file_path = static_files.resolve(&headers.path).unwrap();
+    static_files.serve(&mut server, &headers, &file_path, false);
+    drop(server);
+    let mut response = Vec::new();
+    client.read_to_end(&mut response).unwrap();
+    let split = response.windows(4).position( | w | w == b"\r\n\r\n").unwrap() + 4;
+    (String::from_utf8(response[..split].to_vec()).unwrap(), response[split..].to_vec())
+}
+
+fn header<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+    head.lines().skip(1).find_map( | line | {
+        let (key, value) = line.split_once(':')?;
+        if key.eq_ignore_ascii_case(name) {Some(value.trim())} else {None}
+    })
+}
+
+#[test]
+fn serves_a_whole_file() {
+    let files = test_files("whole");
+    let (head, body) = serve(&files, "GET /index.txt HTTP/1.1\r\nHost: x\r\n\r\n");
+    assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert_eq!(header(&head, "Content-Type"), Some("text/plain"));
+    assert_eq!(header(&head, "Content-Length"), Some("20"));
+    assert_eq!(body, BODY.as_bytes());
+}
+
+#[test]
+fn serves_a_range() {
+    let files = test_files("range");
+    let (head, body) = serve(&files, "GET /index.txt HTTP/1.1\r\nHost: x\r\nRange: bytes=2-5\r\n\r\n");
+    assert!(head.starts_with("HTTP/1.1 206 Partial Content\r\n"));
+    assert_eq!(header(&head, "Content-Range"), Some("bytes 2-5/20"));
+    assert_eq!(header(&head, "Content-Length"), Some("4"));
+    assert_eq!(body, b"2345");
+
+    // an open end runs to the end of the file
+    let (_, body) = serve(&files, "GET /index.txt HTTP/1.1\r\nHost: x\r\nRange: bytes=15-\r\n\r\n");
+    assert_eq!(body, b"fghij");
+}
+
+#[test]
+fn serves_a_suffix_range() {
+    let files = test_files("suffix");
+    let (head, body) = serve(&files, "GET /index.txt HTTP/1.1\r\nHost: x\r\nRange: bytes=-3\r\n\r\n");
+    assert_eq!(header(&head, "Content-Range"), Some("bytes 17-19/20"));
+    assert_eq!(body, b"hij");
+
+    // a suffix longer than the file is the whole file
+    let (head, body) = serve(&files, "GET /index.txt HTTP/1.1\r\nHost: x\r\nRange: bytes=-100\r\n\r\n");
+    assert_eq!(header(&head, "Content-Range"), Some("bytes 0-19/20"));
+    assert_eq!(body, BODY.as_bytes());
+}
+
+#[test]
+fn unsatisfiable_range_is_416() {
+    let files = test_files("416");
+    let (head, body) = serve(&files, "GET /index.txt HTTP/1.1\r\nHost: x\r\nRange: bytes=20-30\r\n\r\n");
+    assert!(head.starts_with("HTTP/1.1 416 Range Not Satisfiable\r\n"));
+    assert_eq!(header(&head, "Content-Range"), Some("bytes */20"));
+    assert!(body.is_empty());
+
+    assert_eq!(parse_range_header("bytes=-0", 20), Some(Err(())));
+    // ranges we don't serve partially are ignored, not refused
+    assert_eq!(parse_range_header("bytes=0-1,4-5", 20), None);
+    assert_eq!(parse_range_header("items=0-1", 20), None);
+}
+
+#[test]
+fn matching_etag_is_304() {
+    let files = test_files("etag");
+    let (head, _) = serve(&files, "GET /index.txt HTTP/1.1\r\nHost: x\r\n\r\n");
+    let etag = header(&head, "ETag").unwrap().to_string();
+
+    let (head, body) = serve(&files, &format!("GET /index.txt HTTP/1.1\r\nHost: x\r\nIf-None-Match: {}\r\n\r\n", etag));
+    assert!(head.starts_with("HTTP/1.1 304 Not Modified\r\n"));
+    assert_eq!(header(&head, "ETag"), Some(etag.as_str()));
+    assert!(body.is_empty());
+
+    let (head, _) = serve(&files, "GET /index.txt HTTP/1.1\r\nHost: x\r\nIf-None-Match: \"other\"\r\n\r\n");
+    assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+}
+
+#[test]
+fn compresses_when_the_client_accepts_gzip() {
+    let mut files = test_files("gzip");
+    files.gzip = true;
+    let source = std::fs::read(files.mounts[0].1.join("big.js")).unwrap();
+
+    let (head, body) = serve(&files, "GET /big.js HTTP/1.1\r\nHost: x\r\nAccept-Encoding: gzip, deflate\r\n\r\n");
+    assert_eq!(header(&head, "Content-Encoding"), Some("gzip"));
+    assert_eq!(header(&head, "Content-Length"), Some(body.len().to_string().as_str()));
+    assert!(header(&head, "ETag").unwrap().ends_with("-gzip\""));
+    assert_eq!(&body[..2], &[0x1f, 0x8b]);
+    let inflated = makepad_miniz::decompress_to_vec(&body[10..body.len() - 8]).unwrap();
+    assert_eq!(inflated, source);
+    assert_eq!(&body[body.len() - 4..], &(source.len() as u32).to_le_bytes());
+
+    // not when refused, and not for files too small to bother
+    let (head, body) = serve(&files, "GET /big.js HTTP/1.1\r\nHost: x\r\nAccept-Encoding: gzip;q=0\r\n\r\n");
+    assert_eq!(header(&head, "Content-Encoding"), None);
+    assert_eq!(body, source);
+    let (head, _) = serve(&files, "GET /index.txt HTTP/1.1\r\nHost: x\r\nAccept-Encoding: gzip\r\n\r\n");
+    assert_eq!(header(&head, "Content-Encoding"), None);
+}
+
+#[test]
+fn gzip_cache_stays_under_its_max_size() {
+    let mut files = test_files("gzip_cache");
+    let dir = files.mounts[0].1.clone();
+    std::fs::write(dir.join("other.js"), "console.log(2);\n".repeat(200)).unwrap();
+    files.gzip = true;
+    let request = | path: &str | format!("GET {} HTTP/1.1\r\nHost: x\r\nAccept-Encoding: gzip\r\n\r\n", path);
+
+    // room for one compressed file at a time
+    let (_, big) = serve(&files, &request("/big.js"));
+    files.gzip_cache = HttpGzipCache::with_max_size(big.len() + 10);
+    assert_eq!(serve(&files, &request("/big.js")).1, big);
+    assert_eq!(files.gzip_cache.size(), big.len());
+    let (_, other) = serve(&files, &request("/other.js"));
+    assert_eq!(files.gzip_cache.size(), other.len());
+    // the evicted one is compressed again
+    assert_eq!(serve(&files, &request("/big.js")).1, big);
+    assert_eq!(files.gzip_cache.size(), big.len());
+
+    // nothing bigger than the cache is kept
+    files.gzip_cache = HttpGzipCache::with_max_size(10);
+    assert_eq!(serve(&files, &request("/big.js")).1, big);
+    assert_eq!(files.gzip_cache.size(), 0);
+}
+
+#[test]
+fn head_has_no_body() {
+    let files = test_files("head");
+    let (head, body) = serve(&files, "HEAD /index.txt HTTP/1.1\r\nHost: x\r\n\r\n");
+    assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert_eq!(header(&head, "Content-Length"), Some("20"));
+    assert!(body.is_empty());
+}
+
+#[test]
+fn refuses_paths_outside_the_mount() {
+    let files = test_files("traversal");
+    assert!(files.resolve("/index.txt").is_some());
+    assert!(files.resolve("/../index.txt").is_none());
+    assert!(files.resolve("/a/../../index.txt").is_none());
+    assert!(files.resolve("/%2e%2e/index.txt").is_none());
+    assert!(files.resolve("/..%5cindex.txt").is_none());
+    assert!(files.resolve("/C:/index.txt").is_none());
+    // unknown mime types aren't served either
+    std::fs::write(files.mounts[0].1.join("secret.key"), "x").unwrap();
+    assert!(files.resolve("/secret.key").is_none());
+}
+
+// starts a server with a single worker on a free port
+fn start_server(static_files: HttpStaticFiles) -> SocketAddr {
+    let listen_address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    // every request in these tests is a static file, nothing reaches the app
+    let (request, _) = mpsc::channel();
+    start_http_server(HttpServer {
+        listen_address,
+        request,
+        post_max_size: 1024,
+        static_files: Some(static_files),
+        worker_threads: 1,
+    }).unwrap();
+    listen_address
+}
+
+// reads one response with a Content-Length off a kept alive connection
+fn read_response(reader: &mut BufReader<TcpStream>) -> (String, Vec<u8>) {
+    let mut head = String::new();
+    loop {
+        let len = head.len();
+        reader.read_line(&mut head).unwrap();
+        if head.len() == len || head[len..] == *"\r\n" {
+            break
+        }
+    }
+    let len: usize = header(&head, "Content-Length").unwrap().parse().unwrap();
+    let mut body = vec![0u8; len];
+    reader.read_exact(&mut body).unwrap();
+    (head, body)
+}
+
+#[test]
+fn idle_keep_alive_connection_leaves_the_worker_free() {
+    let addr = start_server(test_files("keep_alive"));
+    let request = b"GET /index.txt HTTP/1.1\r\nHost: x\r\n\r\n";
+
+    let mut first = BufReader::new(TcpStream::connect(addr).unwrap());
+    first.get_mut().write_all(request).unwrap();
+    let (head, body) = read_response(&mut first);
+    assert!(head.contains("Connection: keep-alive"));
+    assert_eq!(body, BODY.as_bytes());
+
+    // the only worker must not be stuck waiting on the idle first connection
+    let started = Instant::now();
+    let mut second = BufReader::new(TcpStream::connect(addr).unwrap());
+    second.get_mut().write_all(request).unwrap();
+    let (_, body) = read_response(&mut second);
+    assert_eq!(body, BODY.as_bytes());
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    // and the first connection still takes its next request
+    first.get_mut().write_all(request).unwrap();
+    let (_, body) = read_response(&mut first);
+    assert_eq!(body, BODY.as_bytes());
+}
+
+#[test]
+fn pipelined_requests_are_all_answered() {
+    let addr = start_server(test_files("pipelined"));
+    let mut conn = BufReader::new(TcpStream::connect(addr).unwrap());
+    conn.get_mut().write_all(b"GET /index.txt HTTP/1.1\r\nHost: x\r\n\r\nGET /index.txt HTTP/1.1\r\nHost: x\r\nRange: bytes=0-1\r\n\r\n").unwrap();
+    let (_, body) = read_response(&mut conn);
+    assert_eq!(body, BODY.as_bytes());
+    let (_, body) = read_response(&mut conn);
+    assert_eq!(body, b"01");
+}
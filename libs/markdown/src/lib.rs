@@ -7,19 +7,27 @@ pub struct MarkdownDoc{
     pub decoded: String,
     pub nodes: Vec<MarkdownNode>,
 }
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum MarkdownListLabel{
     Plus,
     Minus,
     Star,
     Number{digit:usize, start:usize, end:usize},
 }
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarkdownTableAlign{
+    None,
+    Left,
+    Center,
+    Right,
+}
+#[derive(Debug, PartialEq, Eq)]
 pub enum MarkdownNode{
     BeginHead{level:usize},
     EndHead,
     BeginListItem{label:MarkdownListLabel},
     EndListItem,
+    TaskCheckbox{checked:bool},
     BeginNormal,
     EndNormal,
     Link{start:usize, url_start:usize, end:usize},
@@ -27,8 +35,17 @@ pub enum MarkdownNode{
     BeginQuote,
     EndQuote,
     Separator, 
-    BeginUnderline,
-    EndUnderline,
+    BeginTable{columns:usize},
+    EndTable,
+    BeginTableRow{header:bool},
+    EndTableRow,
+    BeginTableCell{align:MarkdownTableAlign},
+    EndTableCell,
+    FootnoteRef{start:usize, end:usize},
+    BeginFootnoteDef{start:usize, end:usize},
+    EndFootnoteDef,
+    BeginStrikethrough,
+    EndStrikethrough,
     BeginCode,
     EndCode,
     BeginInlineCode,
@@ -41,6 +58,15 @@ pub enum MarkdownNode{
     Text{start:usize, end:usize}
 }
 
+// `~~text~~` used to come out as underline, it is strikethrough like in GFM now
+#[allow(non_upper_case_globals)]
+impl MarkdownNode{
+    #[deprecated(note = "`~~` is strikethrough, use `MarkdownNode::BeginStrikethrough`")]
+    pub const BeginUnderline: MarkdownNode = MarkdownNode::BeginStrikethrough;
+    #[deprecated(note = "`~~` is strikethrough, use `MarkdownNode::EndStrikethrough`")]
+    pub const EndUnderline: MarkdownNode = MarkdownNode::EndStrikethrough;
+}
+
 #[derive(Clone)]
 struct Cursor<'a>{
     iter: Chars<'a>,
@@ -84,6 +110,175 @@ impl <'a> Cursor<'a>{
     }
 }
 
+// splits a table line into its cells, dropping the optional outer pipes
+fn table_cells(line:&str)->Vec<&str>{
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = if line.ends_with('|') && !line.ends_with("\\|"){
+        &line[..line.len()-1]
+    }
+    else{
+        line
+    };
+    let mut cells = Vec::new();
+    let mut start = 0;
+    let mut last = '\0';
+    for (i, c) in line.char_indices(){
+        if c == '|' && last != '\\'{
+            cells.push(&line[start..i]);
+            start = i + 1;
+        }
+        last = c;
+    }
+    cells.push(&line[start..]);
+    cells
+}
+
+fn scan_line(cursor:&mut Cursor)->String{
+    let mut line = String::new();
+    while cursor.chars[0] != '\n' && !cursor.at_end(){
+        line.push(cursor.chars[0]);
+        cursor.next();
+    }
+    cursor.next();
+    line
+}
+
+// a table starts with a header line followed by a delimiter row with the same amount of cells
+fn table_header(cursor:&Cursor)->Option<Vec<MarkdownTableAlign>>{
+    let mut scan = cursor.clone();
+    let header = scan_line(&mut scan);
+    // a code fence ends a table, so it can't start one either
+    if !header.contains('|') || header.trim_start().starts_with("```"){
+        return None
+    }
+    let delimiter = scan_line(&mut scan);
+    if !delimiter.contains('-'){
+        return None
+    }
+    let mut aligns = Vec::new();
+    for cell in table_cells(&delimiter){
+        let cell = cell.trim();
+        let left = cell.starts_with(':');
+        let right = cell.ends_with(':') && cell.len() > 1;
+        let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+        if dashes.is_empty() || dashes.chars().any(|c| c != '-'){
+            return None
+        }
+        aligns.push(match (left, right){
+            (true, true) => MarkdownTableAlign::Center,
+            (true, false) => MarkdownTableAlign::Left,
+            (false, true) => MarkdownTableAlign::Right,
+            (false, false) => MarkdownTableAlign::None,
+        });
+    }
+    if aligns.len() != table_cells(&header).len(){
+        return None
+    }
+    Some(aligns)
+}
+
+fn is_table_row(cursor:&Cursor)->bool{
+    let mut scan = cursor.clone();
+    let line = scan_line(&mut scan);
+    !line.trim().is_empty() && line.contains('|')
+}
+
+// scans a [^label], returning the label and a cursor positioned after the ]
+fn scan_footnote<'a>(cursor:&Cursor<'a>)->Option<(String, Cursor<'a>)>{
+    let mut scan = cursor.clone();
+    scan.skip(2);
+    let mut label = String::new();
+    while scan.chars[0] != ']'{
+        if scan.at_end() || scan.chars[0].is_whitespace(){
+            return None
+        }
+        label.push(scan.chars[0]);
+        scan.next();
+    }
+    if label.is_empty(){
+        return None
+    }
+    scan.next();
+    Some((label, scan))
+}
+
+fn is_footnote_def(cursor:&Cursor)->bool{
+    cursor.chars[0] == '[' && cursor.chars[1] == '^' && matches!(scan_footnote(cursor), Some((_, scan)) if scan.chars[0] == ':')
+}
+
+// scans an autolink between angle brackets, like <https://makepad.dev> or <info@makepad.nl>
+fn scan_angle_autolink<'a>(cursor:&Cursor<'a>)->Option<(String, String, Cursor<'a>)>{
+    let mut scan = cursor.clone();
+    scan.next();
+    let mut name = String::new();
+    while scan.chars[0] != '>'{
+        if scan.at_end() || scan.chars[0].is_whitespace() || scan.chars[0] == '<'{
+            return None
+        }
+        name.push(scan.chars[0]);
+        scan.next();
+    }
+    scan.next();
+    let scheme_len = name.find(':').unwrap_or(0);
+    if scheme_len >= 2 && scheme_len <= 32 && name[..scheme_len].chars().all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '.' || c == '-') && name.starts_with(|c:char| c.is_ascii_alphabetic()){
+        let url = name.clone();
+        return Some((name, url, scan))
+    }
+    if let Some((user, domain)) = name.split_once('@'){
+        if !user.is_empty() && domain.contains('.') && !domain.starts_with('.') && !domain.ends_with('.'){
+            let url = format!("mailto:{}", name);
+            return Some((name, url, scan))
+        }
+    }
+    None
+}
+
+// scans a bare http://, https:// or www. link, leaving off trailing punctuation
+fn scan_bare_autolink<'a>(cursor:&Cursor<'a>)->Option<(String, String, Cursor<'a>)>{
+    let mut scan = cursor.clone();
+    let mut name = String::new();
+    while !scan.at_end() && !scan.chars[0].is_whitespace() && scan.chars[0] != '<'{
+        name.push(scan.chars[0]);
+        scan.next();
+    }
+    let prefix = if name.starts_with("https://"){
+        "https://"
+    }
+    else if name.starts_with("http://"){
+        "http://"
+    }
+    else if name.starts_with("www."){
+        "www."
+    }
+    else{
+        return None
+    };
+    loop{
+        if name.ends_with(|c| matches!(c, '?' | '!' | '.' | ',' | ':' | '*' | '_' | '~' | '\'' | '"')){
+            name.pop();
+        }
+        else if name.ends_with(')') && name.matches(')').count() > name.matches('(').count(){
+            name.pop();
+        }
+        else{
+            break
+        }
+    }
+    if name.len() <= prefix.len(){
+        return None
+    }
+    let mut scan = cursor.clone();
+    scan.skip(name.chars().count());
+    let url = if prefix == "www."{
+        format!("http://{}", name)
+    }
+    else{
+        name.clone()
+    };
+    Some((name, url, scan))
+}
+
 pub fn parse_markdown(body:&str)->MarkdownDoc{
     let mut nodes = Vec::new();
     let mut decoded = String::new();
@@ -93,13 +288,15 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
     let mut cursor = Cursor::new(body);
     enum State{
         Root{spaces:usize},
-        Inline{kind:Kind, bold:usize, italic:usize, underline:usize}, // terminates
+        Inline{kind:Kind, bold:usize, italic:usize, strikethrough:usize}, // terminates
     }
     enum Kind{
         Normal,
         Head,
         Quote(usize),
-        List(usize)
+        List(usize),
+        Table{aligns:Vec<MarkdownTableAlign>, column:usize, header:bool},
+        Footnote,
     }
     
     let mut state = State::Root{spaces:0};
@@ -128,6 +325,64 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
         }
     }
     
+    fn end_styles(nodes: &mut Vec<MarkdownNode>, bold:&mut usize, italic:&mut usize, strikethrough:&mut usize){
+        for _ in 0..*bold{
+            nodes.push(MarkdownNode::EndBold);
+        }
+        for _ in 0..*italic{
+            nodes.push(MarkdownNode::EndItalic);
+        }
+        for _ in 0..*strikethrough{
+            nodes.push(MarkdownNode::EndStrikethrough);
+        }
+        *bold = 0;
+        *italic = 0;
+        *strikethrough = 0;
+    }
+    
+    fn push_task_checkbox(nodes: &mut Vec<MarkdownNode>, cursor:&mut Cursor){
+        // a list item starting with [ ] or [x] is a task
+        if let ['[', c, ']'] = cursor.chars{
+            if c == ' ' || c == 'x' || c == 'X'{
+                let mut scan = cursor.clone();
+                scan.skip(3);
+                if scan.chars[0] == ' '{
+                    scan.next();
+                    nodes.push(MarkdownNode::TaskCheckbox{checked: c != ' '});
+                    *cursor = scan;
+                }
+            }
+        }
+    }
+    
+    fn begin_table_row(nodes: &mut Vec<MarkdownNode>, cursor:&mut Cursor, align:MarkdownTableAlign, header:bool){
+        nodes.push(MarkdownNode::BeginTableRow{header});
+        while cursor.chars[0] == ' '{
+            cursor.next();
+        }
+        if cursor.chars[0] == '|'{
+            cursor.next();
+        }
+        while cursor.chars[0] == ' '{
+            cursor.next();
+        }
+        nodes.push(MarkdownNode::BeginTableCell{align});
+    }
+    
+    fn end_table_cell(nodes: &mut Vec<MarkdownNode>, decoded:&mut String){
+        // cell contents dont keep their trailing space
+        if let Some(MarkdownNode::Text{start, end}) = nodes.last_mut(){
+            if *end == decoded.len() && decoded.ends_with(' '){
+                decoded.pop();
+                *end -= 1;
+                if *start == *end{
+                    nodes.pop();
+                }
+            }
+        }
+        nodes.push(MarkdownNode::EndTableCell);
+    }
+    
     fn code_on_one_line(nodes: &mut Vec<MarkdownNode>, decoded:&mut String, cursor:&mut Cursor){
         // alright we have to check if we are in a code block already
         let already_in_code = if let Some(MarkdownNode::EndCode) = nodes.last(){
@@ -156,24 +411,13 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
     
     loop{
         match &mut state{
-            State::Inline{kind, bold, italic, underline}=> match cursor.chars{
-                [' ',' ','\n']=>{
+            State::Inline{kind, bold, italic, strikethrough}=> match cursor.chars{
+                [' ',' ','\n'] if !matches!(kind, Kind::Table{..})=>{
                     nodes.push(MarkdownNode::NewLine);
                     cursor.skip(2);
                 }
                 ['\n',_,_] | ['\0',_,_]=>{
-                    for _ in 0..*bold{
-                        nodes.push(MarkdownNode::EndBold);
-                    }
-                    for _ in 0..*italic{
-                        nodes.push(MarkdownNode::EndItalic);
-                    }
-                    for _ in 0..*underline{
-                        nodes.push(MarkdownNode::EndUnderline);
-                    }
-                    *bold = 0;
-                    *italic = 0;
-                    *underline = 0;
+                    end_styles(&mut nodes, bold, italic, strikethrough);
                     
                     match kind{
                         Kind::Head=>{
@@ -211,7 +455,13 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                                 state = State::Root{spaces};
                             }
                         }
-                        Kind::Normal=>{
+                        Kind::Normal | Kind::Footnote=>{
+                            let end_node = if let Kind::Footnote = kind{
+                                MarkdownNode::EndFootnoteDef
+                            }
+                            else{
+                                MarkdownNode::EndNormal
+                            };
                             let last_is_space = cursor.last_char == ' ';
                             cursor.next();
                             let mut spaces = 0;
@@ -219,14 +469,14 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                                 cursor.next();
                                 spaces += 1;
                             }
-                            if cursor.chars[0] == '#'{
+                            if cursor.chars[0] == '#' || is_footnote_def(&cursor) || (spaces < 4 && table_header(&cursor).is_some()){
                                 state = State::Root{spaces};
-                                nodes.push(MarkdownNode::EndNormal);
+                                nodes.push(end_node);
                             }
                             else if cursor.chars[0] == '\n' || cursor.chars[0] == '\0'{
                                 cursor.next();
                                 state = State::Root{spaces:0};
-                                nodes.push(MarkdownNode::EndNormal);
+                                nodes.push(end_node);
                             }
                             else if !last_is_space{
                                 push_char(&mut nodes, &mut decoded, ' ');
@@ -263,9 +513,59 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                                 push_char(&mut nodes, &mut decoded, ' ');
                             }
                         }
+                        Kind::Table{aligns, column, header}=>{
+                            if nodes.last() != Some(&MarkdownNode::EndTableCell){
+                                end_table_cell(&mut nodes, &mut decoded);
+                            }
+                            // rows with too few cells are padded with empty ones
+                            for align in aligns.iter().skip(*column + 1){
+                                nodes.push(MarkdownNode::BeginTableCell{align:*align});
+                                nodes.push(MarkdownNode::EndTableCell);
+                            }
+                            nodes.push(MarkdownNode::EndTableRow);
+                            cursor.next();
+                            if *header{ // skip the delimiter row
+                                scan_line(&mut cursor);
+                            }
+                            // the table ends at the first line without a pipe
+                            if is_table_row(&cursor){
+                                begin_table_row(&mut nodes, &mut cursor, aligns[0], false);
+                                *column = 0;
+                                *header = false;
+                            }
+                            else{
+                                nodes.push(MarkdownNode::EndTable);
+                                state = State::Root{spaces:0};
+                            }
+                        }
                     }
                     
                 }
+                ['|',_,_] if matches!(kind, Kind::Table{..})=>{
+                    end_styles(&mut nodes, bold, italic, strikethrough);
+                    end_table_cell(&mut nodes, &mut decoded);
+                    cursor.next();
+                    while cursor.chars[0] == ' '{
+                        cursor.next();
+                    }
+                    if let Kind::Table{aligns, column, ..} = kind{
+                        if cursor.chars[0] != '\n' && !cursor.at_end(){
+                            *column += 1;
+                            if let Some(align) = aligns.get(*column){
+                                nodes.push(MarkdownNode::BeginTableCell{align:*align});
+                            }
+                            else{ // excess cells are ignored
+                                while cursor.chars[0] != '\n' && !cursor.at_end(){
+                                    cursor.next();
+                                }
+                            }
+                        }
+                    }
+                }
+                ['\\','|',_] if matches!(kind, Kind::Table{..})=>{
+                    push_char(&mut nodes, &mut decoded, '|');
+                    cursor.skip(2);
+                }
                 ['*','*',w] | ['_','_',w] if w != ' ' && w != '\n'=>{ // alright so have have 2 *'s
                     // this is the start of a bold block
                     nodes.push(MarkdownNode::BeginBold);
@@ -284,19 +584,19 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                         cursor.next();
                     }
                 }
-                ['~','~',w] if w != ' ' && w != '\n'=>{ // alright so have have 2 *'s
-                    // this is the start of a bold block
-                    nodes.push(MarkdownNode::BeginUnderline);
-                    *underline += 1;
+                ['~','~',w] if w != ' ' && w != '\n'=>{
+                    // this is the start of a strikethrough block
+                    nodes.push(MarkdownNode::BeginStrikethrough);
+                    *strikethrough += 1;
                     cursor.skip(2);
                 }
                 [w,'~','~'] if w != ' '&& w != '\n'=>{
-                    // end of a bold block
+                    // end of a strikethrough block
                     push_char(&mut nodes, &mut decoded, w);
-                    if *underline > 0{
-                        *underline -= 1;
+                    if *strikethrough > 0{
+                        *strikethrough -= 1;
                         cursor.skip(3);
-                        nodes.push(MarkdownNode::EndUnderline);
+                        nodes.push(MarkdownNode::EndStrikethrough);
                     }
                     else{
                         cursor.next();
@@ -359,6 +659,17 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                                 nodes.push(MarkdownNode::EndListItem);
                             }
                         }
+                        Kind::Table{..} => {
+                            end_styles(&mut nodes, bold, italic, strikethrough);
+                            if nodes.last() != Some(&MarkdownNode::EndTableCell){
+                                end_table_cell(&mut nodes, &mut decoded);
+                            }
+                            nodes.push(MarkdownNode::EndTableRow);
+                            nodes.push(MarkdownNode::EndTable);
+                        }
+                        Kind::Footnote => {
+                            nodes.push(MarkdownNode::EndFootnoteDef)
+                        }
                     }
                     state = State::Root{spaces:0};
                 }
@@ -423,6 +734,31 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                     // parse inline image
                     cursor.skip(2);
                 }
+                ['[','^',_] if scan_footnote(&cursor).is_some()=>{ // footnote reference
+                    let (label, scan) = scan_footnote(&cursor).unwrap();
+                    let start = decoded.len();
+                    decoded.push_str(&label);
+                    nodes.push(MarkdownNode::FootnoteRef{start, end:decoded.len()});
+                    cursor = scan;
+                }
+                ['<',_,_] if scan_angle_autolink(&cursor).is_some()=>{
+                    let (name, url, scan) = scan_angle_autolink(&cursor).unwrap();
+                    let start = decoded.len();
+                    decoded.push_str(&name);
+                    let url_start = decoded.len();
+                    decoded.push_str(&url);
+                    nodes.push(MarkdownNode::Link{start, url_start, end:decoded.len()});
+                    cursor = scan;
+                }
+                ['h','t','t'] | ['w','w','w'] if matches!(cursor.last_char, '\0' | ' ' | '\n' | '(' | '*' | '_' | '~') && scan_bare_autolink(&cursor).is_some()=>{
+                    let (name, url, scan) = scan_bare_autolink(&cursor).unwrap();
+                    let start = decoded.len();
+                    decoded.push_str(&name);
+                    let url_start = decoded.len();
+                    decoded.push_str(&url);
+                    nodes.push(MarkdownNode::Link{start, url_start, end:decoded.len()});
+                    cursor = scan;
+                }
                 ['[',_,_]=>{ // possible named link
                     let mut scan = cursor.clone();
                     scan.skip(1);
@@ -477,6 +813,28 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                     state = State::Root{spaces:*spaces + 1};
                     cursor.skip(1)
                 }
+                ['\n',_,_]=>{ // skip it
+                    cursor.skip(1);
+                    state = State::Root{spaces:0};
+                }
+                _ if *spaces < 4 && table_header(&cursor).is_some()=>{
+                    let aligns = table_header(&cursor).unwrap();
+                    nodes.push(MarkdownNode::BeginTable{columns:aligns.len()});
+                    begin_table_row(&mut nodes, &mut cursor, aligns[0], true);
+                    state = State::Inline{kind:Kind::Table{aligns, column:0, header:true}, bold:0, italic:0, strikethrough:0};
+                }
+                ['[','^',_] if *spaces < 4 && is_footnote_def(&cursor)=>{
+                    let (label, scan) = scan_footnote(&cursor).unwrap();
+                    cursor = scan;
+                    cursor.next();
+                    while cursor.chars[0] == ' '{
+                        cursor.next();
+                    }
+                    let start = decoded.len();
+                    decoded.push_str(&label);
+                    nodes.push(MarkdownNode::BeginFootnoteDef{start, end:decoded.len()});
+                    state = State::Inline{kind:Kind::Footnote, bold:0, italic:0, strikethrough:0};
+                }
                 ['>',_,_]=>{
                     // alright lets parse and render the quotes
                     if *spaces>=4{ // its code
@@ -504,7 +862,7 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                         }
                         push_optional_char(&mut nodes, &mut decoded, ' ');
                         // alright now we know how deep in the block stack we need to be
-                        state = State::Inline{kind:Kind::Quote(blocks), bold:0, italic:0, underline:0};
+                        state = State::Inline{kind:Kind::Quote(blocks), bold:0, italic:0, strikethrough:0};
                     }
                 }
                 ['#',_,_]=>{
@@ -526,13 +884,13 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                         else{
                             nodes.push(MarkdownNode::Text{start, end:decoded.len()});
                         }
-                        state = State::Inline{kind:Kind::Normal, bold:0, italic:0, underline:0};
+                        state = State::Inline{kind:Kind::Normal, bold:0, italic:0, strikethrough:0};
                     }
                     else {
                         cursor.next();
                         decoded.truncate(start);
                         nodes.push(MarkdownNode::BeginHead{level});
-                        state = State::Inline{kind:Kind::Head, bold:0, italic:0, underline:0};
+                        state = State::Inline{kind:Kind::Head, bold:0, italic:0, strikethrough:0};
                     }
                 }
                 ['-','-','-']=>{ // separator
@@ -545,7 +903,7 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                         if cursor.chars[2] != '\n'{
                             nodes.push(MarkdownNode::BeginNormal);
                             push_char(&mut nodes, &mut decoded, '-');
-                            state = State::Inline{kind:Kind::Normal, bold:0, italic:0, underline:0};
+                            state = State::Inline{kind:Kind::Normal, bold:0, italic:0, strikethrough:0};
                         }
                         else{
                             cursor.skip(3);
@@ -593,7 +951,7 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                         }
                         else{ // its normal 
                             nodes.push(MarkdownNode::BeginNormal);
-                            state = State::Inline{kind:Kind::Normal, bold:0, italic:0, underline:0};
+                            state = State::Inline{kind:Kind::Normal, bold:0, italic:0, strikethrough:0};
                         }
                     }
                     else{
@@ -610,15 +968,14 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                             _=>panic!()
                         }});
                         
-                        state = State::Inline{kind:Kind::List(depth), bold:0, italic:0, underline:0}
+                        state = State::Inline{kind:Kind::List(depth), bold:0, italic:0, strikethrough:0}
                     }
                     cursor.skip(2);
+                    if let Some(MarkdownNode::BeginListItem{..}) = nodes.last(){
+                        push_task_checkbox(&mut nodes, &mut cursor);
+                    }
                     //push_optional_char(&mut nodes, &mut decoded, ' ');
                 }
-                ['\n',_,_]=>{ // skip it
-                    cursor.skip(1);
-                    state = State::Root{spaces:0};
-                }
                 [a,_b,_c]=>{
                     let mut is_list_digit = None;
                     if a.is_ascii_digit(){
@@ -653,7 +1010,7 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                             }
                             else{ // its normal 
                                 nodes.push(MarkdownNode::BeginNormal);
-                                state = State::Inline{kind:Kind::Normal, bold:0, italic:0, underline:0};
+                                state = State::Inline{kind:Kind::Normal, bold:0, italic:0, strikethrough:0};
                             }
                         }
                         else{ 
@@ -696,8 +1053,9 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                                 start,
                                 end
                             }});
+                            push_task_checkbox(&mut nodes, &mut cursor);
                                                     
-                            state = State::Inline{kind:Kind::List(depth), bold:0, italic:0, underline:0}
+                            state = State::Inline{kind:Kind::List(depth), bold:0, italic:0, strikethrough:0}
                         }
                     }
                     else if *spaces>=4{ // its code
@@ -706,7 +1064,7 @@ pub fn parse_markdown(body:&str)->MarkdownDoc{
                     }
                     else{
                         nodes.push(MarkdownNode::BeginNormal);
-                        state = State::Inline{kind:Kind::Normal, bold:0, italic:0, underline:0};
+                        state = State::Inline{kind:Kind::Normal, bold:0, italic:0, strikethrough:0};
                     }
                 }
             }
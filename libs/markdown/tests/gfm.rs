@@ -0,0 +1,114 @@
+// Parser tests for the GitHub flavored extensions: autolinks, strikethrough, tables and task lists.
+
+use makepad_markdown::*;
+
+// the nodes of `body`, with the text of the ranges filled in
+fn parse(body: &str) -> Vec<String> {
+    let doc = parse_markdown(body);
+    doc.nodes.iter().map( | node | match node {
+        MarkdownNode::Text {start, end} => format!("Text({})", &doc.decoded[*start..*end]),
+        MarkdownNode::Link {start, url_start, end} => {
+            format!("Link({}, {})", &doc.decoded[*start..*url_start], &doc.decoded[*url_start..*end])
+        }
+        node => format!("{:?}", node),
+    }).collect()
+}
+
+#[test]
+fn angle_bracket_autolink() {
+    assert_eq!(parse("see <https://makepad.dev> now"), [
+        "BeginNormal",
+        "Text(see )",
+        "Link(https://makepad.dev, https://makepad.dev)",
+        "Text( now)",
+        "EndNormal",
+    ]);
+}
+
+#[test]
+fn bare_url_leaves_trailing_punctuation() {
+    assert_eq!(parse("go to https://makepad.dev/x. ok"), [
+        "BeginNormal",
+        "Text(go to )",
+        "Link(https://makepad.dev/x, https://makepad.dev/x)",
+        "Text(. ok)",
+        "EndNormal",
+    ]);
+}
+
+#[test]
+fn inline_link() {
+    assert_eq!(parse("[name](http://x.y)"), ["BeginNormal", "Link(name, http://x.y)", "EndNormal"]);
+}
+
+#[test]
+fn strikethrough() {
+    assert_eq!(parse("a ~~gone~~ b"), [
+        "BeginNormal",
+        "Text(a )",
+        "BeginStrikethrough",
+        "Text(gone)",
+        "EndStrikethrough",
+        "Text( b)",
+        "EndNormal",
+    ]);
+    // a single tilde is just text
+    assert_eq!(parse("a ~b~ c"), ["BeginNormal", "Text(a ~b~ c)", "EndNormal"]);
+}
+
+#[test]
+#[allow(deprecated)]
+fn underline_names_still_match_strikethrough() {
+    let doc = parse_markdown("~~gone~~");
+    assert!(doc.nodes.contains(&MarkdownNode::BeginUnderline));
+    assert!(matches!(doc.nodes.iter().rev().nth(1), Some(&MarkdownNode::EndUnderline)));
+}
+
+#[test]
+fn table_with_alignment() {
+    assert_eq!(parse("| a | b |\n|:--|--:|\n| 1 | 2 |\n"), [
+        "BeginTable { columns: 2 }",
+        "BeginTableRow { header: true }",
+        "BeginTableCell { align: Left }",
+        "Text(a)",
+        "EndTableCell",
+        "BeginTableCell { align: Right }",
+        "Text(b)",
+        "EndTableCell",
+        "EndTableRow",
+        "BeginTableRow { header: false }",
+        "BeginTableCell { align: Left }",
+        "Text(1)",
+        "EndTableCell",
+        "BeginTableCell { align: Right }",
+        "Text(2)",
+        "EndTableCell",
+        "EndTableRow",
+        "EndTable",
+    ]);
+}
+
+#[test]
+fn task_list() {
+    assert_eq!(parse("- [ ] todo\n- [x] done\n"), [
+        "BeginListItem { label: Minus }",
+        "TaskCheckbox { checked: false }",
+        "Text(todo)",
+        "EndListItem",
+        "BeginListItem { label: Minus }",
+        "TaskCheckbox { checked: true }",
+        "Text(done)",
+        "EndListItem",
+    ]);
+}
+
+#[test]
+fn code_fence_does_not_start_a_table() {
+    // these used to loop forever, a fence closed the table and the next round opened it again
+    for body in ["```x|\n-", "``````a|b\n-|-", "a ```b|c\n-|-"] {
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || tx.send(parse(body)).unwrap());
+        let nodes = rx.recv_timeout(std::time::Duration::from_secs(5)).unwrap_or_else( | _ | panic!("{:?} doesn't finish", body));
+        assert!(nodes.iter().any( | node | node == "BeginCode"), "{:?}: {:?}", body, nodes);
+    }
+}
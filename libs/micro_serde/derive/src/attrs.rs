@@ -0,0 +1,284 @@
+use proc_macro::TokenStream;
+use makepad_micro_proc_macro::{error, Attribute, StructField, TokenParser};
+
+// the `#[serde(...)]` attributes the derives understand, and the fields and variants they apply to
+
+/// How `rename_all` renames the fields of a struct or the variants of an enum.
+#[derive(Clone, Copy)]
+pub enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "lowercase" => Some(Self::Lower),
+            "UPPERCASE" => Some(Self::Upper),
+            "Pascal" => Some(Self::Pascal),
+            "camelCase" => Some(Self::Camel),
+            "snake_case" => Some(Self::Snake),
+            "SCREAMING_SNAKE_CASE" => Some(Self::ScreamingSnake),
+            "kebab-case" => Some(Self::Kebab),
+            "SCREAMING-KEBAB-CASE" => Some(Self::ScreamingKebab),
+            _ => None
+        }
+    }
+
+    /// Renames a field, which is in snake_case.
+    pub fn apply_to_field(self, name: &str) -> String {
+        match self {
+            Self::Lower | Self::Snake => name.to_string(),
+            Self::Upper | Self::ScreamingSnake => name.to_ascii_uppercase(),
+            Self::Pascal | Self::Camel => {
+                let mut out = String::new();
+                let mut upper = matches!(self, Self::Pascal);
+                for c in name.chars() {
+                    if c == '_' {
+                        upper = !out.is_empty();
+                    }
+                    else if upper {
+                        out.push(c.to_ascii_uppercase());
+                        upper = false;
+                    }
+                    else {
+                        out.push(c);
+                    }
+                }
+                out
+            }
+            Self::Kebab => name.replace('_', "-"),
+            Self::ScreamingKebab => name.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+
+    /// Renames a variant, which is in Pascal.
+    pub fn apply_to_variant(self, name: &str) -> String {
+        match self {
+            Self::Lower => name.to_ascii_lowercase(),
+            Self::Upper => name.to_ascii_uppercase(),
+            Self::Pascal => name.to_string(),
+            Self::Camel => {
+                let mut chars = name.chars();
+                chars.next().map(|c| c.to_ascii_lowercase().to_string() + chars.as_str()).unwrap_or_default()
+            }
+            Self::Snake | Self::ScreamingSnake | Self::Kebab | Self::ScreamingKebab => {
+                let mut snake = String::new();
+                for (index, c) in name.chars().enumerate() {
+                    if index > 0 && c.is_ascii_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(c.to_ascii_lowercase());
+                }
+                self.apply_to_field(&snake)
+            }
+        }
+    }
+}
+
+/// How an enum is written: `{"Variant": content}`, `{"tag": "Variant", ...fields}`,
+/// `{"tag": "Variant", "content": content}` or just the content. RON writes the last three
+/// as structs.
+#[derive(Default)]
+pub enum EnumTagging {
+    #[default]
+    External,
+    Internal {tag: String},
+    Adjacent {tag: String, content: String},
+    Untagged,
+}
+
+#[derive(Default)]
+pub struct ContainerAttrs {
+    pub rename_all: Option<RenameRule>,
+    /// Missing fields are taken from the `Default` of the struct.
+    pub default: bool,
+    pub tagging: EnumTagging,
+}
+
+/// What a field that is missing, or skipped, is set to.
+pub enum FieldDefault {
+    /// Missing fields are an error, or `None` for options.
+    None,
+    Default,
+    Path(TokenStream),
+}
+
+pub struct Field {
+    pub name: String,
+    /// The key the field is written as.
+    pub key: String,
+    pub is_option: bool,
+    pub default: FieldDefault,
+    pub skip: bool,
+    pub skip_serializing_if: Option<TokenStream>,
+    pub flatten: bool,
+}
+
+impl Field {
+    /// Whether reading fails when the field is missing.
+    pub fn is_required(&self, container_default: bool) -> bool {
+        matches!(self.default, FieldDefault::None) && !container_default && !self.skip && !self.is_option
+    }
+}
+
+pub enum VariantKind {
+    Unit,
+    Tuple(usize),
+    Named(Vec<Field>),
+}
+
+pub struct Variant {
+    pub name: String,
+    /// The name the variant is written as.
+    pub key: String,
+    pub kind: VariantKind,
+}
+
+// the items of the `#[serde(...)]` attributes, as a name and the string after a `=` if there is one
+fn serde_items(attrs: &[Attribute]) -> Result<Vec<(String, Option<String>)>, TokenStream> {
+    let mut items = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.name == "serde") {
+        let Some(args) = &attr.args else {
+            return Err(error("Expected #[serde(...)]"))
+        };
+        let mut parser = TokenParser::new(args.clone());
+        while !parser.is_eot() {
+            let Some(name) = parser.eat_any_ident() else {
+                return Err(error("Expected a serde attribute"))
+            };
+            let value = if parser.eat_punct_alone('=') {
+                let value = parser.eat_literal().map(|lit| lit.to_string());
+                match value.as_deref().and_then(|value| value.strip_prefix('"')?.strip_suffix('"')) {
+                    Some(value) => Some(value.to_string()),
+                    None => return Err(error(&format!("Expected a string after serde attribute {}", name)))
+                }
+            }
+            else {
+                None
+            };
+            items.push((name, value));
+            if !parser.eat_punct_alone(',') && !parser.is_eot() {
+                return Err(error("Expected , between serde attributes"))
+            }
+        }
+    }
+    Ok(items)
+}
+
+fn parse_path(name: &str, path: &str) -> Result<TokenStream, TokenStream> {
+    path.parse().map_err(|_| error(&format!("Cannot parse {} path {}", name, path)))
+}
+
+fn unexpected_item(name: &str, what: &str) -> TokenStream {
+    error(&format!("Unexpected serde attribute {} on {}", name, what))
+}
+
+pub fn container_attrs(attrs: &[Attribute], is_enum: bool) -> Result<ContainerAttrs, TokenStream> {
+    let mut container = ContainerAttrs::default();
+    let (mut tag, mut content, mut untagged) = (None, None, false);
+    for (name, value) in serde_items(attrs)? {
+        match (name.as_str(), value) {
+            ("rename_all", Some(value)) => {
+                container.rename_all = Some(RenameRule::from_name(&value).ok_or_else(|| {
+                    error(&format!("Unknown rename_all rule {}", value))
+                })?);
+            }
+            ("default", None) if !is_enum => container.default = true,
+            ("tag", Some(value)) if is_enum => tag = Some(value),
+            ("content", Some(value)) if is_enum => content = Some(value),
+            ("untagged", None) if is_enum => untagged = true,
+            (name, _) => return Err(unexpected_item(name, if is_enum {"enum"} else {"struct"}))
+        }
+    }
+    container.tagging = match (tag, content, untagged) {
+        (None, None, false) => EnumTagging::External,
+        (Some(tag), None, false) => EnumTagging::Internal {tag},
+        (Some(tag), Some(content), false) => EnumTagging::Adjacent {tag, content},
+        (None, None, true) => EnumTagging::Untagged,
+        _ => return Err(error("serde content needs a tag, and untagged enums have neither"))
+    };
+    Ok(container)
+}
+
+/// Reads the attributes of struct fields. Keys are the field names with `rename_all` applied
+/// and, when `strip_underscore` is set, without the leading underscore that lets a field be
+/// named after a keyword.
+pub fn fields(fields: Vec<StructField>, rename_all: Option<RenameRule>, strip_underscore: bool) -> Result<Vec<Field>, TokenStream> {
+    let mut out = Vec::new();
+    for field in fields {
+        let name = if strip_underscore {field.name.strip_prefix('_').unwrap_or(&field.name)} else {&field.name};
+        let mut key = match rename_all {
+            Some(rule) => rule.apply_to_field(name),
+            None => name.to_string(),
+        };
+        let is_option = field.ty.clone().into_iter().next().is_some_and(|tt| tt.to_string() == "Option");
+        let (mut default, mut skip, mut skip_serializing_if, mut flatten) = (FieldDefault::None, false, None, false);
+        for (item, value) in serde_items(&field.attrs)? {
+            match (item.as_str(), value) {
+                ("rename", Some(value)) => key = value,
+                ("default", None) => default = FieldDefault::Default,
+                ("default", Some(value)) => default = FieldDefault::Path(parse_path(&item, &value)?),
+                ("skip", None) => skip = true,
+                ("skip_serializing_if", Some(value)) => skip_serializing_if = Some(parse_path(&item, &value)?),
+                ("flatten", None) => flatten = true,
+                (item, _) => return Err(unexpected_item(item, "field"))
+            }
+        }
+        out.push(Field {
+            name: field.name,
+            key,
+            is_option,
+            default,
+            skip,
+            skip_serializing_if,
+            flatten,
+        });
+    }
+    if out.iter().filter(|field| field.flatten && !field.skip).count() > 1 {
+        return Err(error("Only one field can be flattened"))
+    }
+    Ok(out)
+}
+
+/// Reads the variants of an enum, after its opening brace.
+pub fn variants(parser: &mut TokenParser, rename_all: Option<RenameRule>, strip_underscore: bool) -> Result<Vec<Variant>, TokenStream> {
+    let mut variants = Vec::new();
+    while !parser.eat_eot() {
+        let attrs = parser.eat_attributes();
+        let Some(name) = parser.eat_any_ident() else {
+            return Err(parser.unexpected())
+        };
+        let mut key = match rename_all {
+            Some(rule) => rule.apply_to_variant(&name),
+            None => name.clone(),
+        };
+        for (item, value) in serde_items(&attrs)? {
+            match (item.as_str(), value) {
+                ("rename", Some(value)) => key = value,
+                (item, _) => return Err(unexpected_item(item, "variant"))
+            }
+        }
+        let kind = if let Some(types) = parser.eat_all_types() {
+            VariantKind::Tuple(types.len())
+        }
+        else if let Some(named) = parser.eat_all_struct_fields() {
+            VariantKind::Named(fields(named, None, strip_underscore)?)
+        }
+        else if parser.is_punct_alone(',') || parser.is_eot() {
+            VariantKind::Unit
+        }
+        else {
+            return Err(parser.unexpected())
+        };
+        variants.push(Variant {name, key, kind});
+        parser.eat_punct_alone(',');
+    }
+    Ok(variants)
+}
@@ -1,5 +1,22 @@
 use proc_macro::{TokenStream};
 use makepad_micro_proc_macro::*;
+use crate::attrs::{self, Field, FieldDefault};
+
+// binary is positional, so of the serde attributes only `skip` applies
+fn bin_fields(fields: Vec<StructField>) -> Result<Vec<Field>, TokenStream> {
+    attrs::fields(fields, None, false)
+}
+
+fn skipped_value(tb: &mut TokenBuilder, field: &Field) {
+    match &field.default {
+        FieldDefault::Path(path) => {
+            tb.stream(Some(path.clone())).add("( )");
+        }
+        _ => {
+            tb.add("Default :: default ( )");
+        }
+    }
+}
 
 pub fn derive_ser_bin_impl(input: TokenStream) -> TokenStream {
     let mut parser = TokenParser::new(input);
@@ -24,7 +41,11 @@ pub fn derive_ser_bin_impl(input: TokenStream) -> TokenStream {
                 }
             }
             else if let Some(fields) = parser.eat_all_struct_fields(){ 
-                for field in fields{
+                let fields = match bin_fields(fields){
+                    Ok(fields) => fields,
+                    Err(err) => return err
+                };
+                for field in fields.iter().filter(|field| !field.skip){
                     tb.add("self .").ident(&field.name).add(". ser_bin ( s ) ;");
                 }
             }
@@ -65,12 +86,16 @@ pub fn derive_ser_bin_impl(input: TokenStream) -> TokenStream {
                         tb.add("}");
                     }
                     else if let Some(fields) = parser.eat_all_struct_fields(){ // named variant
+                        let fields = match bin_fields(fields){
+                            Ok(fields) => fields,
+                            Err(err) => return err
+                        };
                         tb.add("Self ::").ident(&variant).add("{");
-                        for field in fields.iter(){
+                        for field in fields.iter().filter(|field| !field.skip){
                             tb.ident(&field.name).add(",");
                         }
-                        tb.add("} => {").suf_u16(index).add(". ser_bin ( s ) ;");
-                        for field in fields{
+                        tb.add(".. } => {").suf_u16(index).add(". ser_bin ( s ) ;");
+                        for field in fields.iter().filter(|field| !field.skip){
                             tb.ident(&field.name).add(". ser_bin ( s ) ;");
                         }
                         tb.add("}");
@@ -122,9 +147,20 @@ pub fn derive_de_bin_impl(input: TokenStream) -> TokenStream {
                 tb.add(")");
             }
             else if let Some(fields) = parser.eat_all_struct_fields(){ 
+                let fields = match bin_fields(fields){
+                    Ok(fields) => fields,
+                    Err(err) => return err
+                };
                 tb.add("{");
                 for field in fields{
-                    tb.ident(&field.name).add(": DeBin :: de_bin ( o , d ) ? ,");
+                    tb.ident(&field.name).add(":");
+                    if field.skip{
+                        skipped_value(&mut tb, &field);
+                    }
+                    else{
+                        tb.add("DeBin :: de_bin ( o , d ) ?");
+                    }
+                    tb.add(",");
                 }
                 tb.add("}");
             }
@@ -165,9 +201,20 @@ pub fn derive_de_bin_impl(input: TokenStream) -> TokenStream {
                         tb.add(")");
                     }
                     else if let Some(fields) = parser.eat_all_struct_fields(){ // named variant
+                        let fields = match bin_fields(fields){
+                            Ok(fields) => fields,
+                            Err(err) => return err
+                        };
                         tb.ident(&variant).add("{");
-                        for field in fields.iter(){
-                            tb.ident(&field.name).add(": DeBin :: de_bin ( o , d ) ? ,");
+                        for field in fields{
+                            tb.ident(&field.name).add(":");
+                            if field.skip{
+                                skipped_value(&mut tb, &field);
+                            }
+                            else{
+                                tb.add("DeBin :: de_bin ( o , d ) ?");
+                            }
+                            tb.add(",");
                         }
                         tb.add("}");
                     }
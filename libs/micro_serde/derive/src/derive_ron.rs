@@ -1,14 +1,205 @@
 use makepad_micro_proc_macro::*;
 use proc_macro::TokenStream;
+use crate::attrs::*;
+
+// how a field of `self` or of a matched variant is reached, as a reference
+fn field_ref(tb: &mut TokenBuilder, field: &Field, in_variant: bool) {
+    if in_variant {
+        tb.ident(&field.name);
+    } else {
+        tb.add("& self .").ident(&field.name);
+    }
+}
+
+// writes the fields of a struct or struct variant, at depth `d`
+fn ser_ron_fields(tb: &mut TokenBuilder, fields: &[Field], in_variant: bool, d: &str) {
+    for field in fields.iter().filter(|field| !field.skip) {
+        if let Some(path) = &field.skip_serializing_if {
+            tb.add("if !").stream(Some(path.clone())).add("(");
+            field_ref(tb, field, in_variant);
+            tb.add(") {");
+        }
+        if field.is_option {
+            tb.add("if let Some ( t ) =");
+            field_ref(tb, field, in_variant);
+            tb.add("{");
+        }
+        if field.flatten {
+            tb.add("s . flatten (").add(d).add(",");
+        } else {
+            tb.add("s . field (").add(d).add("+ 1 ,").string(&field.key).add(") ;");
+            tb.add("(");
+        }
+        if field.is_option {
+            tb.add("t");
+        } else {
+            field_ref(tb, field, in_variant);
+        }
+        if field.flatten {
+            tb.add(") ;");
+        } else {
+            tb.add(") . ser_ron (").add(d).add("+ 1 , s ) ; s . conl ( ) ;");
+        }
+        if field.is_option {
+            tb.add("}");
+        }
+        if field.skip_serializing_if.is_some() {
+            tb.add("}");
+        }
+    }
+}
+
+fn ser_ron_tuple(tb: &mut TokenBuilder, types: usize, d: &str) {
+    tb.add("s . out . push (").chr('(').add(") ;");
+    for i in 0..types {
+        tb.ident(&format!("n{}", i)).add(". ser_ron (").add(d).add(", s ) ;");
+        if i != types - 1 {
+            tb.add("s . out . push_str (").string(", ").add(") ;");
+        }
+    }
+    tb.add("s . out . push (").chr(')').add(") ;");
+}
+
+// writes the tag of an internally or adjacently tagged variant as a string field
+fn ser_ron_tag(tb: &mut TokenBuilder, tag: &str, variant: &Variant) {
+    tb.add("s . field ( d + 1 ,").string(tag).add(") ;");
+    tb.add("s . out . push_str (").string(&format!("\"{}\"", variant.key)).add(") ; s . conl ( ) ;");
+}
+
+fn variant_pattern(tb: &mut TokenBuilder, variant: &Variant) {
+    tb.add("Self ::").ident(&variant.name);
+    match &variant.kind {
+        VariantKind::Unit => (),
+        VariantKind::Tuple(types) => {
+            tb.add("(");
+            for i in 0..*types {
+                tb.ident(&format!("n{}", i)).add(",");
+            }
+            tb.add(")");
+        }
+        VariantKind::Named(fields) => {
+            tb.add("{");
+            for field in fields.iter().filter(|field| !field.skip) {
+                tb.ident(&field.name).add(",");
+            }
+            tb.add(".. }");
+        }
+    }
+}
+
+// the value of a skipped field, or of a missing one that isn't required
+fn default_value(tb: &mut TokenBuilder, field: &Field, container_default: bool) {
+    match &field.default {
+        FieldDefault::Path(path) => {
+            tb.stream(Some(path.clone())).add("( )");
+        }
+        FieldDefault::Default => {
+            tb.add("Default :: default ( )");
+        }
+        FieldDefault::None if container_default => {
+            tb.add("defaults .").ident(&field.name);
+        }
+        FieldDefault::None if field.is_option => {
+            tb.add("None");
+        }
+        FieldDefault::None => {
+            tb.add("Default :: default ( )");
+        }
+    }
+}
+
+// reads the fields of a struct or struct variant and evaluates to `ctor` with them
+fn de_ron_fields(tb: &mut TokenBuilder, fields: &[Field], ctor: &str, container_default: bool) {
+    let has_flatten = fields.iter().any(|field| field.flatten && !field.skip);
+    if container_default {
+        tb.add("let defaults : Self = Default :: default ( ) ;");
+    }
+    tb.add("s . paren_open ( i ) ? ;");
+    for field in fields.iter().filter(|field| !field.skip && !field.flatten) {
+        tb.add("let mut")
+            .ident(&format!("_{}", field.name))
+            .add("= None ;");
+    }
+    if has_flatten {
+        tb.add("let mut rest = Vec :: new ( ) ;");
+    }
+    tb.add("while let Some ( _ ) = s . next_ident ( ) {");
+    tb.add("match s . identbuf . as_ref ( ) {");
+    for field in fields.iter().filter(|field| !field.skip && !field.flatten) {
+        tb.string(&field.key).add("=> { s . next_colon ( i ) ? ;");
+        tb.ident(&format!("_{}", field.name))
+            .add("= Some ( DeRon :: de_ron ( s , i ) ? ) ; } ,");
+    }
+    if has_flatten {
+        // the fields that are left go to the flattened field
+        tb.add("_ => { let key = std :: mem :: take ( & mut s . identbuf ) ; s . next_colon ( i ) ? ;");
+        tb.add("rest . push ( ( key , s . capture_value ( i ) ? ) ) ; }");
+    } else {
+        tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . identbuf ) )");
+    }
+    tb.add("} ; s . eat_comma_paren ( i ) ? ;");
+    tb.add("} ; s . paren_close ( i ) ? ;");
+
+    tb.add(ctor).add("{");
+    for field in fields {
+        tb.ident(&field.name).add(":");
+        if field.skip {
+            default_value(tb, field, container_default);
+        } else if field.flatten {
+            if field.is_option {
+                tb.add("if rest . is_empty ( ) { None } else { Some (");
+            }
+            tb.add("s . de_captured ( & DeRonState :: object_text ( & rest ) ) ?");
+            if field.is_option {
+                tb.add(") }");
+            }
+        } else if field.is_required(container_default) {
+            tb.ident(&format!("_{}", field.name))
+                .add(". ok_or_else ( | | s . err_nf (").string(&field.key).add(") ) ?");
+        } else if field.is_option && matches!(field.default, FieldDefault::None) && !container_default {
+            tb.ident(&format!("_{}", field.name)).add(". unwrap_or ( None )");
+        } else {
+            tb.add("if let Some ( t ) =")
+                .ident(&format!("_{}", field.name))
+                .add("{ t } else {");
+            default_value(tb, field, container_default);
+            tb.add("}");
+        }
+        tb.add(",");
+    }
+    tb.add("}");
+}
+
+// reads the elements of a tuple or tuple variant and evaluates to `ctor` with them
+fn de_ron_tuple(tb: &mut TokenBuilder, types: usize, ctor: &str) {
+    tb.add("s . paren_open ( i ) ? ;");
+    tb.add("let r =").add(ctor).add("(");
+    for _ in 0..types {
+        tb.add("{ let r = DeRon :: de_ron ( s , i ) ? ; s . eat_comma_paren ( i ) ? ; r } ,");
+    }
+    tb.add(") ;");
+    tb.add("s . paren_close ( i ) ? ; r");
+}
+
+// a closure that reads a variant from captured text
+fn de_ron_captured(tb: &mut TokenBuilder, body: impl FnOnce(&mut TokenBuilder)) {
+    tb.add("| s : & mut DeRonState , i : & mut std :: str :: Chars | std :: result :: Result :: Ok ( {");
+    body(tb);
+    tb.add("} )");
+}
 
 pub fn derive_ser_ron_impl(input: TokenStream) -> TokenStream {
     let mut parser = TokenParser::new(input);
     let mut tb = TokenBuilder::new();
 
-    parser.eat_attributes();
+    let attrs = parser.eat_attributes();
     parser.eat_ident("pub");
     if parser.eat_ident("struct") {
         if let Some(name) = parser.eat_any_ident() {
+            let container = match container_attrs(&attrs, false) {
+                Ok(container) => container,
+                Err(err) => return err,
+            };
             let generic = parser.eat_generic();
             let types = parser.eat_all_types();
             let where_clause = parser.eat_where_clause(Some("SerRon"));
@@ -31,26 +222,13 @@ pub fn derive_ser_ron_impl(input: TokenStream) -> TokenStream {
                 }
                 tb.add("s.out.push(").chr(')').add(");");
             } else if let Some(fields) = parser.eat_all_struct_fields() {
-                tb.add("s.st_pre( ) ;");
-
+                let fields = match crate::attrs::fields(fields, container.rename_all, false) {
+                    Ok(fields) => fields,
+                    Err(err) => return err,
+                };
                 // named struct
-                for field in fields {
-                    if field.ty.into_iter().next().unwrap().to_string() == "Option" {
-                        tb.add("if let Some ( t ) = ")
-                            .add("& self .")
-                            .ident(&field.name)
-                            .add("{");
-                        tb.add("s.field ( d + 1 ,").string(&field.name).add(") ;");
-                        tb.add("t.ser_ron ( d + 1 , s ) ; s . conl ( ) ; } ;");
-                    } else {
-                        tb.add("s . field ( d + 1 ,")
-                            .string(&field.name)
-                            .add(" ) ;");
-                        tb.add("self .")
-                            .ident(&field.name)
-                            .add(". ser_ron ( d + 1 , s ) ; s . conl ( ) ;");
-                    }
-                }
+                tb.add("s.st_pre( ) ;");
+                ser_ron_fields(&mut tb, &fields, false, "d");
                 tb.add("s . st_post ( d ) ;");
             } else {
                 return parser.unexpected();
@@ -60,9 +238,21 @@ pub fn derive_ser_ron_impl(input: TokenStream) -> TokenStream {
         }
     } else if parser.eat_ident("enum") {
         if let Some(name) = parser.eat_any_ident() {
+            let container = match container_attrs(&attrs, true) {
+                Ok(container) => container,
+                Err(err) => return err,
+            };
             let generic = parser.eat_generic();
             let where_clause = parser.eat_where_clause(Some("SerRon"));
 
+            if !parser.open_brace() {
+                return parser.unexpected();
+            }
+            let variants = match crate::attrs::variants(&mut parser, container.rename_all, false) {
+                Ok(variants) => variants,
+                Err(err) => return err,
+            };
+
             tb.add("impl").stream(generic.clone());
             tb.add("SerRon for")
                 .ident(&name)
@@ -71,68 +261,78 @@ pub fn derive_ser_ron_impl(input: TokenStream) -> TokenStream {
             tb.add("{ fn ser_ron ( & self , d : usize , s : & mut  SerRonState ) {");
             tb.add("match self {");
 
-            if !parser.open_brace() {
-                return parser.unexpected();
-            }
-
-            while !parser.eat_eot() {
-                // parse ident
-                parser.eat_attributes();
-                if let Some(variant) = parser.eat_any_ident() {
-                    if let Some(types) = parser.eat_all_types() {
-                        tb.add("Self ::").ident(&variant).add("(");
-                        for i in 0..types.len() {
-                            tb.ident(&format!("n{}", i)).add(",");
-                        }
-                        tb.add(") => {");
-                        tb.add("s . out . push_str (").string(&variant).add(") ;");
-                        tb.add("s . out . push (").chr('(').add(") ;");
-
-                        for i in 0..types.len() {
-                            tb.ident(&format!("n{}", i)).add(". ser_ron ( d , s ) ;");
-                            if i != types.len() - 1 {
-                                tb.add("s . out . push_str (").string(", ").add(") ;");
+            for variant in &variants {
+                variant_pattern(&mut tb, variant);
+                tb.add("=> {");
+                match (&container.tagging, &variant.kind) {
+                    (EnumTagging::External, kind) => {
+                        tb.add("s . out . push_str (").string(&variant.key).add(") ;");
+                        match kind {
+                            VariantKind::Unit => (),
+                            VariantKind::Tuple(types) => ser_ron_tuple(&mut tb, *types, "d"),
+                            VariantKind::Named(fields) => {
+                                tb.add("s . st_pre ( ) ;");
+                                ser_ron_fields(&mut tb, fields, true, "d");
+                                tb.add("s . st_post ( d ) ;");
                             }
                         }
-                        tb.add("s . out . push (").chr(')').add(") ;");
-                        tb.add("}");
-                    } else if let Some(fields) = parser.eat_all_struct_fields() {
-                        // named variant
-                        tb.add("Self ::").ident(&variant).add("{");
-                        for field in fields.iter() {
-                            tb.ident(&field.name).add(",");
+                    }
+                    (EnumTagging::Internal {tag}, kind) => {
+                        tb.add("s . st_pre ( ) ;");
+                        ser_ron_tag(&mut tb, tag, variant);
+                        match kind {
+                            VariantKind::Unit => (),
+                            VariantKind::Tuple(1) => {
+                                tb.add("s . flatten ( d , n0 ) ;");
+                            }
+                            VariantKind::Tuple(_) => {
+                                return error("Internally tagged enums can't have tuple variants");
+                            }
+                            VariantKind::Named(fields) => ser_ron_fields(&mut tb, fields, true, "d"),
                         }
-                        tb.add("} => {");
-
-                        tb.add("s . out . push_str (").string(&variant).add(") ;");
+                        tb.add("s . st_post ( d ) ;");
+                    }
+                    (EnumTagging::Adjacent {tag, content}, kind) => {
                         tb.add("s . st_pre ( ) ;");
-
-                        for field in fields {
-                            if field.ty.into_iter().next().unwrap().to_string() == "Option" {
-                                tb.add("if ").ident(&field.name).add(". is_some ( ) {");
-                                tb.add("s . field ( d + 1 ,").string(&field.name).add(") ;");
-                                tb.ident(&field.name)
-                                    .add(" . ser_ron ( d + 1 , s ) ; s . conl ( ) ; } ;");
-                            } else {
-                                tb.add("s . field ( d + 1 ,")
-                                    .string(&field.name)
-                                    .add(" ) ;");
-                                tb.ident(&field.name)
-                                    .add(". ser_ron ( d + 1 , s ) ; s . conl ( ) ;");
+                        ser_ron_tag(&mut tb, tag, variant);
+                        if !matches!(kind, VariantKind::Unit) {
+                            tb.add("s . field ( d + 1 ,").string(content).add(") ;");
+                        }
+                        match kind {
+                            VariantKind::Unit => (),
+                            VariantKind::Tuple(1) => {
+                                tb.add("n0 . ser_ron ( d + 1 , s ) ; s . conl ( ) ;");
+                            }
+                            VariantKind::Tuple(types) => {
+                                ser_ron_tuple(&mut tb, *types, "d + 1");
+                                tb.add("s . conl ( ) ;");
+                            }
+                            VariantKind::Named(fields) => {
+                                tb.add("s . st_pre ( ) ;");
+                                ser_ron_fields(&mut tb, fields, true, "d + 1");
+                                tb.add("s . st_post ( d + 1 ) ; s . conl ( ) ;");
+                            }
+                        }
+                        tb.add("s . st_post ( d ) ;");
+                    }
+                    (EnumTagging::Untagged, kind) => {
+                        match kind {
+                            VariantKind::Unit => {
+                                tb.add("s . out . push_str (").string("()").add(") ;");
+                            }
+                            VariantKind::Tuple(1) => {
+                                tb.add("n0 . ser_ron ( d , s ) ;");
+                            }
+                            VariantKind::Tuple(types) => ser_ron_tuple(&mut tb, *types, "d"),
+                            VariantKind::Named(fields) => {
+                                tb.add("s . st_pre ( ) ;");
+                                ser_ron_fields(&mut tb, fields, true, "d");
+                                tb.add("s . st_post ( d ) ;");
                             }
                         }
-                        tb.add("s . st_post ( d ) ; }");
-                    } else if parser.is_punct_alone(',') || parser.is_eot() {
-                        // bare variant
-                        tb.add("Self ::").ident(&variant).add("=> {");
-                        tb.add("s . out . push_str (").string(&variant).add(") ; }");
-                    } else {
-                        return parser.unexpected();
                     }
-                    parser.eat_punct_alone(',');
-                } else {
-                    return parser.unexpected();
                 }
+                tb.add("}");
             }
             tb.add("}");
             tb.add("} } ;");
@@ -145,10 +345,14 @@ pub fn derive_ser_ron_impl(input: TokenStream) -> TokenStream {
 pub fn derive_de_ron_impl(input: TokenStream) -> TokenStream {
     let mut parser = TokenParser::new(input);
     let mut tb = TokenBuilder::new();
-    parser.eat_attributes();
+    let attrs = parser.eat_attributes();
     parser.eat_ident("pub");
     if parser.eat_ident("struct") {
         if let Some(name) = parser.eat_any_ident() {
+            let container = match container_attrs(&attrs, false) {
+                Ok(container) => container,
+                Err(err) => return err,
+            };
             let generic = parser.eat_generic();
             let types = parser.eat_all_types();
             let where_clause = parser.eat_where_clause(Some("DeRon"));
@@ -162,49 +366,16 @@ pub fn derive_de_ron_impl(input: TokenStream) -> TokenStream {
             tb.add("-> std :: result :: Result < Self , DeRonErr > { ");
 
             if let Some(types) = types {
-                tb.add("s . paren_open ( i ) ? ;");
-                tb.add("let r = Self");
-                tb.add("(");
-                for _ in 0..types.len() {
-                    tb.add("{ let r = DeRon :: de_ron ( s , i ) ? ; s . eat_comma_paren ( i ) ? ; r } ,");
-                }
-                tb.add(") ;");
-                tb.add("s . paren_close ( i ) ? ;");
-                tb.add("std :: result :: Result :: Ok ( r ) ");
+                tb.add("std :: result :: Result :: Ok ( {");
+                de_ron_tuple(&mut tb, types.len(), "Self");
+                tb.add("} )");
             } else if let Some(fields) = parser.eat_all_struct_fields() {
-                tb.add("s . paren_open ( i ) ? ;");
-                for field in &fields {
-                    tb.add("let mut")
-                        .ident(&format!("_{}", field.name))
-                        .add("= None ;");
-                }
-                tb.add("while let Some ( _ ) = s . next_ident ( ) {");
-                tb.add("match s . identbuf . as_ref ( ) {");
-                for field in &fields {
-                    tb.string(&field.name).add("=> { s . next_colon ( i ) ? ;");
-                    tb.ident(&format!("_{}", field.name))
-                        .add("= Some ( DeRon :: de_ron ( s , i ) ? ) ; } ,");
-                }
-                tb.add(
-                    "_ => return std :: result :: Result :: Err ( s . err_exp ( & s . identbuf ) )",
-                );
-                tb.add("} ; s . eat_comma_paren ( i ) ? ;");
-                tb.add("} ; s . paren_close ( i ) ? ;");
-
-                tb.add("std :: result :: Result :: Ok ( Self {");
-                for field in fields {
-                    tb.ident(&field.name).add(":");
-                    if field.ty.into_iter().next().unwrap().to_string() == "Option" {
-                        tb.add("if let Some ( t ) =")
-                            .ident(&format!("_{}", field.name));
-                        tb.add("{ t } else { None } ,");
-                    } else {
-                        tb.add("if let Some ( t ) =")
-                            .ident(&format!("_{}", field.name));
-                        tb.add("{ t } else { return Err ( s . err_nf (");
-                        tb.string(&field.name).add(") ) } ,");
-                    }
-                }
+                let fields = match crate::attrs::fields(fields, container.rename_all, false) {
+                    Ok(fields) => fields,
+                    Err(err) => return err,
+                };
+                tb.add("std :: result :: Result :: Ok ( {");
+                de_ron_fields(&mut tb, &fields, "Self", container.default);
                 tb.add("} )");
             } else {
                 return parser.unexpected();
@@ -214,9 +385,22 @@ pub fn derive_de_ron_impl(input: TokenStream) -> TokenStream {
         }
     } else if parser.eat_ident("enum") {
         if let Some(name) = parser.eat_any_ident() {
+            let container = match container_attrs(&attrs, true) {
+                Ok(container) => container,
+                Err(err) => return err,
+            };
             let generic = parser.eat_generic();
             let where_clause = parser.eat_where_clause(Some("DeRon"));
 
+            if !parser.open_brace() {
+                return parser.unexpected();
+            }
+            let variants = match crate::attrs::variants(&mut parser, container.rename_all, false) {
+                Ok(variants) => variants,
+                Err(err) => return err,
+            };
+            let has_content = variants.iter().any(|variant| !matches!(variant.kind, VariantKind::Unit));
+
             tb.add("impl").stream(generic.clone());
             tb.add("DeRon for")
                 .ident(&name)
@@ -224,76 +408,130 @@ pub fn derive_de_ron_impl(input: TokenStream) -> TokenStream {
                 .stream(where_clause);
             tb.add("{ fn de_ron ( s : & mut  DeRonState , i : & mut std :: str :: Chars )");
             tb.add("-> std :: result :: Result < Self , DeRonErr > { ");
-            tb.add("s . ident ( i ) ? ;");
-            tb.add("std :: result :: Result :: Ok ( match s . identbuf . as_ref ( ) {");
 
-            if !parser.open_brace() {
-                return parser.unexpected();
-            }
-            while !parser.eat_eot() {
-                // parse ident
-                parser.eat_attributes();
-                if let Some(variant) = parser.eat_any_ident() {
-                    tb.string(&variant).add("=> {");
-                    if let Some(types) = parser.eat_all_types() {
-                        tb.add("s . paren_open ( i ) ? ;");
-                        tb.add("let r = Self ::").ident(&variant).add("(");
-                        for _ in 0..types.len() {
-                            tb.add("{ let r = DeRon :: de_ron ( s , i ) ? ; s . eat_comma_paren ( i ) ? ; r } ,");
+            match &container.tagging {
+                EnumTagging::External => {
+                    tb.add("s . ident ( i ) ? ;");
+                    tb.add("std :: result :: Result :: Ok ( match s . identbuf . as_ref ( ) {");
+                    for variant in &variants {
+                        let ctor = format!("Self :: {}", variant.name);
+                        tb.string(&variant.key).add("=> {");
+                        match &variant.kind {
+                            VariantKind::Unit => {
+                                tb.add(&ctor);
+                            }
+                            VariantKind::Tuple(types) => de_ron_tuple(&mut tb, *types, &ctor),
+                            VariantKind::Named(fields) => de_ron_fields(&mut tb, fields, &ctor, false),
                         }
-                        tb.add(") ;");
-                        tb.add("s . paren_close ( i ) ? ; r");
-                    } else if let Some(fields) = parser.eat_all_struct_fields() {
-                        // named variant
-                        tb.add("s . paren_open ( i ) ? ;");
-                        for field in &fields {
-                            tb.add("let mut")
-                                .ident(&format!("_{}", field.name))
-                                .add("= None ;");
+                        tb.add("}");
+                    }
+                    tb.add(
+                        "_ => return std :: result :: Result :: Err ( s . err_enum ( & s . identbuf ) )",
+                    );
+                    tb.add("} )");
+                }
+                EnumTagging::Internal {tag} => {
+                    tb.add("let mut pairs = s . capture_object ( i ) ? ;");
+                    tb.add("let tag : String = match pairs . iter ( ) . position ( | ( key , _ ) | key ==").string(tag).add(") {");
+                    tb.add("Some ( index ) => s . de_captured ( & pairs . remove ( index ) . 1 ) ? ,");
+                    tb.add("None => return std :: result :: Result :: Err ( s . err_nf (").string(tag).add(") )");
+                    tb.add("} ;");
+                    if has_content {
+                        tb.add("let text = DeRonState :: object_text ( & pairs ) ;");
+                    }
+                    tb.add("std :: result :: Result :: Ok ( match tag . as_str ( ) {");
+                    for variant in &variants {
+                        let ctor = format!("Self :: {}", variant.name);
+                        tb.string(&variant.key).add("=>");
+                        match &variant.kind {
+                            VariantKind::Unit => {
+                                tb.add(&ctor);
+                            }
+                            VariantKind::Tuple(1) => {
+                                tb.add(&ctor).add("( s . de_captured ( & text ) ? )");
+                            }
+                            VariantKind::Tuple(_) => {
+                                return error("Internally tagged enums can't have tuple variants");
+                            }
+                            VariantKind::Named(fields) => {
+                                tb.add("s . de_captured_with ( & text ,");
+                                de_ron_captured(&mut tb, |tb| de_ron_fields(tb, fields, &ctor, false));
+                                tb.add(") ?");
+                            }
                         }
-                        tb.add("while let Some ( _ ) = s . next_ident ( ) {");
-                        tb.add("match s . identbuf . as_ref ( ) {");
-                        for field in &fields {
-                            tb.string(&field.name).add("=> { s . next_colon ( i ) ? ;");
-                            tb.ident(&format!("_{}", field.name))
-                                .add("= Some ( DeRon :: de_ron ( s , i ) ? ) ; } ,");
+                        tb.add(",");
+                    }
+                    tb.add("_ => return std :: result :: Result :: Err ( s . err_enum ( & tag ) )");
+                    tb.add("} )");
+                }
+                EnumTagging::Adjacent {tag, content} => {
+                    tb.add("let mut tag : Option < String > = None ;");
+                    if has_content {
+                        tb.add("let mut content = None ;");
+                    }
+                    tb.add("for ( key , value ) in s . capture_object ( i ) ? {");
+                    tb.add("if key ==").string(tag).add("{ tag = Some ( s . de_captured ( & value ) ? ) ; }");
+                    tb.add("else if key ==").string(content).add("{");
+                    if has_content {
+                        tb.add("content = Some ( value ) ;");
+                    }
+                    tb.add("}");
+                    tb.add("else { return std :: result :: Result :: Err ( s . err_exp ( & key ) ) }");
+                    tb.add("}");
+                    tb.add("let tag = match tag { Some ( tag ) => tag , None => return std :: result :: Result :: Err ( s . err_nf (").string(tag).add(") ) } ;");
+                    tb.add("std :: result :: Result :: Ok ( match tag . as_str ( ) {");
+                    for variant in &variants {
+                        let ctor = format!("Self :: {}", variant.name);
+                        tb.string(&variant.key).add("=>");
+                        if let VariantKind::Unit = variant.kind {
+                            tb.add(&ctor).add(",");
+                            continue;
                         }
-                        tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
-                        tb.add("} ; s . eat_comma_paren ( i ) ? ;");
-                        tb.add("} ; s . paren_close ( i ) ? ;");
-
-                        tb.add("Self ::").ident(&variant).add("{");
-                        for field in fields {
-                            tb.ident(&field.name).add(":");
-                            if field.ty.into_iter().next().unwrap().to_string() == "Option" {
-                                tb.add("if let Some ( t ) =")
-                                    .ident(&format!("_{}", field.name));
-                                tb.add("{ t } else { None } ,");
-                            } else {
-                                tb.add("if let Some ( t ) =")
-                                    .ident(&format!("_{}", field.name));
-                                tb.add("{ t } else { return Err ( s . err_nf (");
-                                tb.string(&field.name).add(") ) } ,");
+                        tb.add("{ let content = match & content { Some ( content ) => content ,");
+                        tb.add("None => return std :: result :: Result :: Err ( s . err_nf (").string(content).add(") ) } ;");
+                        match &variant.kind {
+                            VariantKind::Unit => (),
+                            VariantKind::Tuple(1) => {
+                                tb.add(&ctor).add("( s . de_captured ( content ) ? )");
+                            }
+                            VariantKind::Tuple(types) => {
+                                tb.add("s . de_captured_with ( content ,");
+                                de_ron_captured(&mut tb, |tb| de_ron_tuple(tb, *types, &ctor));
+                                tb.add(") ?");
+                            }
+                            VariantKind::Named(fields) => {
+                                tb.add("s . de_captured_with ( content ,");
+                                de_ron_captured(&mut tb, |tb| de_ron_fields(tb, fields, &ctor, false));
+                                tb.add(") ?");
                             }
                         }
                         tb.add("}");
-                    } else if parser.is_punct_alone(',') || parser.is_eot() {
-                        // bare variant
-                        tb.add("Self ::").ident(&variant);
-                    } else {
-                        return parser.unexpected();
                     }
-
-                    tb.add("}");
-                    parser.eat_punct_alone(',');
-                } else {
-                    return parser.unexpected();
+                    tb.add("_ => return std :: result :: Result :: Err ( s . err_enum ( & tag ) )");
+                    tb.add("} )");
+                }
+                EnumTagging::Untagged => {
+                    // the first variant that can be read from the value wins
+                    tb.add("let text = s . capture_value ( i ) ? ;");
+                    for variant in &variants {
+                        let ctor = format!("Self :: {}", variant.name);
+                        tb.add("if let std :: result :: Result :: Ok ( r ) = s . de_captured_with ( & text ,");
+                        de_ron_captured(&mut tb, |tb| match &variant.kind {
+                            VariantKind::Unit => {
+                                tb.add("s . paren_open ( i ) ? ; s . paren_close ( i ) ? ;").add(&ctor);
+                            }
+                            VariantKind::Tuple(1) => {
+                                tb.add(&ctor).add("( DeRon :: de_ron ( s , i ) ? )");
+                            }
+                            VariantKind::Tuple(types) => de_ron_tuple(tb, *types, &ctor),
+                            VariantKind::Named(fields) => de_ron_fields(tb, fields, &ctor, false),
+                        });
+                        tb.add(") { return std :: result :: Result :: Ok ( r ) }");
+                    }
+                    tb.add("std :: result :: Result :: Err ( s . err_msg (").string(&format!("Value matches no variant of untagged enum {}", name)).add(") )");
                 }
             }
-            tb.add(
-                "_ => return std :: result :: Result :: Err ( s . err_enum ( & s . identbuf ) )",
-            );
-            tb.add("} ) } }");
+            tb.add("} }");
             return tb.end();
         }
     }
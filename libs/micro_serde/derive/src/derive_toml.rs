@@ -0,0 +1,143 @@
+use proc_macro::TokenStream;
+use makepad_micro_proc_macro::{TokenBuilder, TokenParser, StructField};
+
+fn de_toml_fields(tb: &mut TokenBuilder, fields: &[StructField], table: &str) {
+    for field in fields {
+        let field_strip = if let Some(v) = field.name.strip_prefix("_") {v}else {&field.name};
+        tb.ident(&field.name).add(": de_toml_field (").ident(table).add(",").string(field_strip).add(") ? ,");
+    }
+}
+
+fn de_toml_types(tb: &mut TokenBuilder, types: usize, toml: &str) {
+    tb.add("let v = de_toml_tuple (").ident(toml).add(",").unsuf_usize(types).add(") ? ;");
+}
+
+fn de_toml_values(tb: &mut TokenBuilder, types: usize) {
+    tb.add("(");
+    for i in 0..types {
+        tb.add("DeToml :: de_toml ( & v [").unsuf_usize(i).add("] ) ? ,");
+    }
+    tb.add(")");
+}
+
+enum Variant {
+    Unit(String),
+    Tuple(String, usize),
+    Named(String, Vec<StructField>)
+}
+
+pub fn derive_de_toml_impl(input: TokenStream) -> TokenStream {
+    let mut parser = TokenParser::new(input);
+    let mut tb = TokenBuilder::new();
+
+    parser.eat_attributes();
+    parser.eat_ident("pub");
+    if parser.eat_ident("struct") {
+        if let Some(name) = parser.eat_any_ident() {
+            let generic = parser.eat_generic();
+            let types = parser.eat_all_types();
+            let where_clause = parser.eat_where_clause(Some("DeToml"));
+
+            tb.add("impl").stream(generic.clone());
+            tb.add("DeToml for").ident(&name).stream(generic).stream(where_clause);
+            tb.add("{ fn de_toml ( t : & Toml )");
+            tb.add("-> std :: result :: Result < Self , DeTomlErr > { ");
+
+            if let Some(types) = types {
+                // tuple structs read from an array
+                de_toml_types(&mut tb, types.len(), "t");
+                tb.add("std :: result :: Result :: Ok ( Self");
+                de_toml_values(&mut tb, types.len());
+                tb.add(")");
+            }
+            else if let Some(fields) = parser.eat_all_struct_fields() {
+                tb.add("std :: result :: Result :: Ok ( Self {");
+                de_toml_fields(&mut tb, &fields, "t");
+                tb.add("} )");
+            }
+            else if parser.is_punct_alone(';') {
+                tb.add("let _ = t ; std :: result :: Result :: Ok ( Self )");
+            }
+            else {
+                return parser.unexpected()
+            }
+            tb.add("} } ;");
+            return tb.end();
+        }
+    }
+    else if parser.eat_ident("enum") {
+        if let Some(name) = parser.eat_any_ident() {
+            let generic = parser.eat_generic();
+            let where_clause = parser.eat_where_clause(Some("DeToml"));
+
+            if !parser.open_brace() {
+                return parser.unexpected()
+            }
+            let mut variants = Vec::new();
+            while !parser.eat_eot() {
+                parser.eat_attributes();
+                if let Some(variant) = parser.eat_any_ident() {
+                    if let Some(types) = parser.eat_all_types() {
+                        variants.push(Variant::Tuple(variant, types.len()));
+                    }
+                    else if let Some(fields) = parser.eat_all_struct_fields() {
+                        variants.push(Variant::Named(variant, fields));
+                    }
+                    else if parser.is_punct_alone(',') || parser.is_eot() {
+                        variants.push(Variant::Unit(variant));
+                    }
+                    else {
+                        return parser.unexpected();
+                    }
+                    parser.eat_punct_alone(',');
+                }
+                else {
+                    return parser.unexpected()
+                }
+            }
+
+            tb.add("impl").stream(generic.clone());
+            tb.add("DeToml for").ident(&name).stream(generic).stream(where_clause);
+            tb.add("{ fn de_toml ( t : & Toml )");
+            tb.add("-> std :: result :: Result < Self , DeTomlErr > { ");
+
+            // unit variants are a plain string, the others a table with the variant as its only key
+            tb.add("match t {");
+            tb.add("Toml :: Str ( s , _ ) => match s . as_ref ( ) {");
+            for variant in &variants {
+                if let Variant::Unit(variant) = variant {
+                    tb.string(variant).add("=> return std :: result :: Result :: Ok ( Self ::").ident(variant).add(") ,");
+                }
+            }
+            tb.add("s => return std :: result :: Result :: Err ( DeTomlErr :: err_enum ( t , s ) )");
+            tb.add("} ,");
+            tb.add("Toml :: Table ( table , _ ) if table . len ( ) == 1 => {");
+            tb.add("let ( k , v ) = table . iter ( ) . next ( ) . unwrap ( ) ;");
+            tb.add("let _ = v ;");
+            tb.add("match k . as_ref ( ) {");
+            for variant in &variants {
+                match variant {
+                    Variant::Unit(_) => (),
+                    Variant::Tuple(variant, types) => {
+                        tb.string(variant).add("=> {");
+                        de_toml_types(&mut tb, *types, "v");
+                        tb.add("return std :: result :: Result :: Ok ( Self ::").ident(variant);
+                        de_toml_values(&mut tb, *types);
+                        tb.add(") } ,");
+                    }
+                    Variant::Named(variant, fields) => {
+                        tb.string(variant).add("=> return std :: result :: Result :: Ok ( Self ::").ident(variant).add("{");
+                        de_toml_fields(&mut tb, fields, "v");
+                        tb.add("} ) ,");
+                    }
+                }
+            }
+            tb.add("k => return std :: result :: Result :: Err ( DeTomlErr :: err_enum ( t , k ) )");
+            tb.add("} }");
+            tb.add("_ => std :: result :: Result :: Err ( DeTomlErr :: err_type ( t ,").string("enum").add(") )");
+            tb.add("} } }");
+            return tb.end();
+        }
+    }
+    parser.unexpected()
+}
@@ -2,7 +2,7 @@
 
 use makepad_micro_serde::*;
 
-#[derive(SerBin, DeBin, SerJson, DeJson, SerRon, DeRon, PartialEq)]
+#[derive(SerBin, DeBin, SerJson, DeJson, SerRon, DeRon, DeToml, PartialEq)]
 struct MyStruct<T> where T: Clone {
     pub a: T,
     b: u32,
@@ -17,7 +17,7 @@ struct MyStruct<T> where T: Clone {
     k: [u32;2]
 } 
 
-#[derive(SerBin, DeBin, SerJson, DeJson, SerRon, DeRon, PartialEq)]
+#[derive(SerBin, DeBin, SerJson, DeJson, SerRon, DeRon, DeToml, PartialEq)]
 enum MyEnum<T> where T: Clone {
     One,
     Two(T, u32),
@@ -55,4 +55,22 @@ fn main() {
     println!("RON Output {}", ron);
     let y:MyStruct<usize> = DeRon::deserialize_ron(&ron).unwrap();
     println!("RON roundtrip equality {}", x == y);
+    
+    let toml = r#"
+        a = 1
+        b = 2
+        c = [3]
+        e = "One"
+        f = { Two = [4, 5] }
+        g.Three = { x = 6, y = 7 }
+        j = "Hello"
+        k = [10, 11]
+        [h.Four]
+        w = 8
+        [i.Four]
+        z = 9
+        w = 8
+    "#;
+    let y:MyStruct<usize> = DeToml::deserialize_toml(toml).unwrap();
+    println!("TOML equality {}", x == y);
 }
@@ -0,0 +1,420 @@
+use {
+    std::borrow::Cow,
+    std::collections::HashMap,
+    crate::serde_json::{DeJsonErr, JsonValue},
+};
+
+/// One step through a JSON document, as read by a `JsonPullParser`. Strings without escapes
+/// are borrowed from the parser's buffer rather than copied.
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonEvent<'a> {
+    ObjectStart,
+    ObjectEnd,
+    ArrayStart,
+    ArrayEnd,
+    Key(Cow<'a, str>),
+    String(Cow<'a, str>),
+    U64(u64),
+    I64(i64),
+    F64(f64),
+    Bool(bool),
+    Null,
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+enum Expect {
+    #[default]
+    Value,
+    // a key, or the end of an empty object
+    FirstKey,
+    Key,
+    Colon,
+    // a value, or the end of an empty array
+    FirstItem,
+    ObjectComma,
+    ArrayComma,
+}
+
+#[derive(Clone, Copy)]
+enum Scope {
+    Object,
+    Array,
+}
+
+/// An incremental JSON parser that is fed bytes as they arrive, for instance from a streaming
+/// HTTP response, and hands out a `JsonEvent` whenever a complete token is in. Top level values
+/// may follow each other, as in a stream of JSON lines.
+#[derive(Default)]
+pub struct JsonPullParser {
+    buf: Vec<u8>,
+    pos: usize,
+    scopes: Vec<Scope>,
+    expect: Expect,
+    finished: bool,
+    // how far a string that hasn't fully arrived is scanned, after its opening quote
+    scanned: usize,
+    escaped: bool,
+    line: usize,
+    col: usize,
+    values: JsonValueBuilder,
+}
+
+impl JsonPullParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the next chunk of input, which may end anywhere, even inside a UTF-8 character.
+    pub fn feed(&mut self, chunk: &[u8]) {
+        if self.pos > 0 {
+            self.buf.drain(..self.pos);
+            self.pos = 0;
+        }
+        self.buf.extend_from_slice(chunk);
+    }
+
+    /// Marks the end of the input, after which incomplete tokens are errors.
+    pub fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    /// How many objects and arrays the parser is inside of.
+    pub fn depth(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// Returns the next event, or `None` when more input is needed, or when the input is
+    /// finished and fully read.
+    pub fn next_event(&mut self) -> Result<Option<JsonEvent<'_>>, DeJsonErr> {
+        loop {
+            self.skip_whitespace();
+            let Some(&byte) = self.buf.get(self.pos) else {
+                if self.finished && !self.scopes.is_empty() {
+                    return Err(self.err("Unexpected end of input"))
+                }
+                return Ok(None)
+            };
+            match (self.expect, byte) {
+                (Expect::Colon, b':') => {
+                    self.advance(1);
+                    self.expect = Expect::Value;
+                }
+                (Expect::ObjectComma, b',') => {
+                    self.advance(1);
+                    self.expect = Expect::Key;
+                }
+                (Expect::ArrayComma, b',') => {
+                    self.advance(1);
+                    self.expect = Expect::Value;
+                }
+                (Expect::FirstKey | Expect::ObjectComma, b'}') => {
+                    return Ok(Some(self.close(JsonEvent::ObjectEnd)))
+                }
+                (Expect::FirstItem | Expect::ArrayComma, b']') => {
+                    return Ok(Some(self.close(JsonEvent::ArrayEnd)))
+                }
+                (Expect::FirstKey | Expect::Key, b'"') => return self.key(),
+                (Expect::Value | Expect::FirstItem, _) => return self.value(byte),
+                (_, byte) => return Err(self.unexpected(byte)),
+            }
+        }
+    }
+
+    /// Returns the next complete top level value, or `None` when more input is needed. Events
+    /// read with `next_event` in between are not part of it.
+    pub fn next_value(&mut self) -> Result<Option<JsonValue>, DeJsonErr> {
+        loop {
+            let Some(event) = self.next_event()? else {
+                return Ok(None)
+            };
+            let event = event.into_owned();
+            if let Some(value) = self.values.push(event) {
+                return Ok(Some(value))
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\r' | b'\n') = self.buf.get(self.pos) {
+            self.advance(1);
+        }
+    }
+
+    fn advance(&mut self, len: usize) {
+        for &byte in &self.buf[self.pos..self.pos + len] {
+            if byte == b'\n' {
+                self.line += 1;
+                self.col = 0;
+            }
+            else {
+                self.col += 1;
+            }
+        }
+        self.pos += len;
+    }
+
+    fn err(&self, msg: &str) -> DeJsonErr {
+        DeJsonErr {msg: msg.to_string(), line: self.line, col: self.col}
+    }
+
+    fn unexpected(&self, byte: u8) -> DeJsonErr {
+        self.err(&format!("Unexpected {}", byte as char))
+    }
+
+    fn after_value(&mut self) {
+        self.expect = match self.scopes.last() {
+            Some(Scope::Object) => Expect::ObjectComma,
+            Some(Scope::Array) => Expect::ArrayComma,
+            None => Expect::Value,
+        };
+    }
+
+    fn open(&mut self, scope: Scope) -> JsonEvent<'static> {
+        self.advance(1);
+        self.scopes.push(scope);
+        match scope {
+            Scope::Object => {
+                self.expect = Expect::FirstKey;
+                JsonEvent::ObjectStart
+            }
+            Scope::Array => {
+                self.expect = Expect::FirstItem;
+                JsonEvent::ArrayStart
+            }
+        }
+    }
+
+    fn close(&mut self, event: JsonEvent<'static>) -> JsonEvent<'static> {
+        self.advance(1);
+        self.scopes.pop();
+        self.after_value();
+        event
+    }
+
+    fn key(&mut self) -> Result<Option<JsonEvent<'_>>, DeJsonErr> {
+        let Some((start, end)) = self.scan_string()? else {
+            return Ok(None)
+        };
+        self.expect = Expect::Colon;
+        Ok(Some(JsonEvent::Key(self.string_at(start, end)?)))
+    }
+
+    fn value(&mut self, byte: u8) -> Result<Option<JsonEvent<'_>>, DeJsonErr> {
+        match byte {
+            b'{' => Ok(Some(self.open(Scope::Object))),
+            b'[' => Ok(Some(self.open(Scope::Array))),
+            b'"' => {
+                let Some((start, end)) = self.scan_string()? else {
+                    return Ok(None)
+                };
+                self.after_value();
+                Ok(Some(JsonEvent::String(self.string_at(start, end)?)))
+            }
+            b'-' | b'0'..=b'9' => self.number(),
+            b't' => self.literal("true", JsonEvent::Bool(true)),
+            b'f' => self.literal("false", JsonEvent::Bool(false)),
+            b'n' => self.literal("null", JsonEvent::Null),
+            byte => Err(self.unexpected(byte)),
+        }
+    }
+
+    // the range of the string at `pos` without its quotes, once it has fully arrived
+    fn scan_string(&mut self) -> Result<Option<(usize, usize)>, DeJsonErr> {
+        let start = self.pos + 1;
+        let mut index = start + self.scanned;
+        loop {
+            match self.buf.get(index) {
+                Some(b'"') => break,
+                Some(b'\\') if index + 1 < self.buf.len() => {
+                    self.escaped = true;
+                    index += 2;
+                }
+                Some(b'\\') | None => {
+                    if self.finished {
+                        return Err(self.err("Unterminated string"))
+                    }
+                    self.scanned = index - start;
+                    return Ok(None)
+                }
+                Some(_) => index += 1,
+            }
+        }
+        self.scanned = 0;
+        self.advance(index + 1 - self.pos);
+        Ok(Some((start, index)))
+    }
+
+    fn string_at(&mut self, start: usize, end: usize) -> Result<Cow<'_, str>, DeJsonErr> {
+        let escaped = std::mem::take(&mut self.escaped);
+        let Ok(text) = std::str::from_utf8(&self.buf[start..end]) else {
+            return Err(self.err("Invalid UTF-8 in string"))
+        };
+        if !escaped {
+            return Ok(Cow::Borrowed(text))
+        }
+        match unescape(text) {
+            Some(text) => Ok(Cow::Owned(text)),
+            None => Err(self.err("Invalid escape in string")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Option<JsonEvent<'_>>, DeJsonErr> {
+        let len = self.buf[self.pos..]
+            .iter()
+            .position(|byte| !matches!(byte, b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E'))
+            .unwrap_or(self.buf.len() - self.pos);
+        // a number is only complete once something else follows it
+        if self.pos + len == self.buf.len() && !self.finished {
+            return Ok(None)
+        }
+        let text = std::str::from_utf8(&self.buf[self.pos..self.pos + len]).unwrap();
+        let event = if text.contains(['.', 'e', 'E']) {
+            text.parse().ok().map(JsonEvent::F64)
+        }
+        else if text.starts_with('-') {
+            text.parse().ok().map(JsonEvent::I64).or_else(|| text.parse().ok().map(JsonEvent::F64))
+        }
+        else {
+            text.parse().ok().map(JsonEvent::U64).or_else(|| text.parse().ok().map(JsonEvent::F64))
+        };
+        let Some(event) = event else {
+            return Err(self.err(&format!("Invalid number {}", text)))
+        };
+        self.advance(len);
+        self.after_value();
+        Ok(Some(event))
+    }
+
+    fn literal(&mut self, word: &str, event: JsonEvent<'static>) -> Result<Option<JsonEvent<'_>>, DeJsonErr> {
+        let word = word.as_bytes();
+        let available = &self.buf[self.pos..self.buf.len().min(self.pos + word.len())];
+        if available != &word[..available.len()] {
+            return Err(self.unexpected(self.buf[self.pos]))
+        }
+        if available.len() < word.len() {
+            if self.finished {
+                return Err(self.err("Unexpected end of input"))
+            }
+            return Ok(None)
+        }
+        self.advance(word.len());
+        self.after_value();
+        Ok(Some(event))
+    }
+}
+
+impl JsonEvent<'_> {
+    pub fn into_owned(self) -> JsonEvent<'static> {
+        match self {
+            Self::Key(key) => JsonEvent::Key(Cow::Owned(key.into_owned())),
+            Self::String(string) => JsonEvent::String(Cow::Owned(string.into_owned())),
+            Self::ObjectStart => JsonEvent::ObjectStart,
+            Self::ObjectEnd => JsonEvent::ObjectEnd,
+            Self::ArrayStart => JsonEvent::ArrayStart,
+            Self::ArrayEnd => JsonEvent::ArrayEnd,
+            Self::U64(v) => JsonEvent::U64(v),
+            Self::I64(v) => JsonEvent::I64(v),
+            Self::F64(v) => JsonEvent::F64(v),
+            Self::Bool(v) => JsonEvent::Bool(v),
+            Self::Null => JsonEvent::Null,
+        }
+    }
+}
+
+// the text of a string with its escapes replaced
+fn unescape(text: &str) -> Option<String> {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        out.push(match chars.next()? {
+            '"' => '"',
+            '\\' => '\\',
+            '/' => '/',
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => {
+                let high = hex4(&mut chars)?;
+                if (0xD800..0xDC00).contains(&high) {
+                    if chars.next()? != '\\' || chars.next()? != 'u' {
+                        return None
+                    }
+                    let low = hex4(&mut chars)?;
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return None
+                    }
+                    char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))?
+                }
+                else {
+                    char::from_u32(high)?
+                }
+            }
+            _ => return None,
+        });
+    }
+    Some(out)
+}
+
+fn hex4(chars: &mut std::str::Chars) -> Option<u32> {
+    let mut value = 0;
+    for _ in 0..4 {
+        value = value * 16 + chars.next()?.to_digit(16)?;
+    }
+    Some(value)
+}
+
+/// Builds `JsonValue`s out of the events of a `JsonPullParser`.
+#[derive(Default)]
+pub struct JsonValueBuilder {
+    // the objects and arrays being built, with the key each one goes under in its parent
+    stack: Vec<(Option<String>, JsonValue)>,
+    key: Option<String>,
+}
+
+impl JsonValueBuilder {
+    /// Adds an event, and returns the value when it completes a top level one.
+    pub fn push(&mut self, event: JsonEvent) -> Option<JsonValue> {
+        let value = match event {
+            JsonEvent::ObjectStart => {
+                self.stack.push((self.key.take(), JsonValue::Object(HashMap::new())));
+                return None
+            }
+            JsonEvent::ArrayStart => {
+                self.stack.push((self.key.take(), JsonValue::Array(Vec::new())));
+                return None
+            }
+            JsonEvent::Key(key) => {
+                self.key = Some(key.into_owned());
+                return None
+            }
+            JsonEvent::ObjectEnd | JsonEvent::ArrayEnd => {
+                let (key, value) = self.stack.pop()?;
+                self.key = key;
+                value
+            }
+            JsonEvent::String(string) => JsonValue::String(string.into_owned()),
+            JsonEvent::U64(v) => JsonValue::U64(v),
+            JsonEvent::I64(v) => JsonValue::I64(v),
+            JsonEvent::F64(v) => JsonValue::F64(v),
+            JsonEvent::Bool(v) => JsonValue::Bool(v),
+            JsonEvent::Null => JsonValue::Null,
+        };
+        match self.stack.last_mut() {
+            Some((_, JsonValue::Object(object))) => {
+                object.insert(self.key.take().unwrap_or_default(), value);
+                None
+            }
+            Some((_, JsonValue::Array(array))) => {
+                array.push(value);
+                None
+            }
+            _ => Some(value),
+        }
+    }
+}
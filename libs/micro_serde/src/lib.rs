@@ -6,5 +6,11 @@ pub use crate::serde_bin::*;
 mod serde_json;
 pub use crate::serde_json::*;
 
+mod json_pull;
+pub use crate::json_pull::*;
+
 mod serde_ron;
-pub use crate::serde_ron::*;
\ No newline at end of file
+pub use crate::serde_ron::*;
+
+mod serde_toml;
+pub use crate::serde_toml::*;
@@ -36,10 +36,27 @@ impl SerJsonState {
     }
     
     pub fn st_post(&mut self, d: usize) {
+        // derived structs end every field with a comma, as some of them may be skipped
+        if self.out.ends_with(',') {
+            self.out.pop();
+        }
         self.indent(d);
         self.out.push('}');
     }
     
+    /// Writes the fields of `value`, which serializes to an object, into the object being
+    /// written, for `#[serde(flatten)]`.
+    pub fn flatten<T>(&mut self, d: usize, value: &T) where T: SerJson + ?Sized {
+        let start = self.out.len();
+        value.ser_json(d, self);
+        if self.out[start..].starts_with('{') && self.out.ends_with('}') {
+            self.out.pop();
+            self.out.remove(start);
+            if self.out.len() > start {
+                self.conl();
+            }
+        }
+    }
 }
 
 pub trait SerJson {
@@ -270,6 +287,84 @@ impl DeJsonState {
         Err(self.err_token("}"))
     }
     
+    /// Reads the next value, whatever its type, and returns it as JSON text to deserialize later,
+    /// for the enum representations and fields that need to look ahead.
+    pub fn capture_value(&mut self, i: &mut Chars) -> Result<String, DeJsonErr> {
+        let mut s = SerJsonState {out: String::new()};
+        let mut depth = 0;
+        loop {
+            match self.tok {
+                DeJsonTok::Str => self.strbuf.ser_json(0, &mut s),
+                DeJsonTok::Char(c) => c.to_string().ser_json(0, &mut s),
+                DeJsonTok::U64(_) | DeJsonTok::I64(_) | DeJsonTok::F64(_) => s.out.push_str(&self.numbuf),
+                DeJsonTok::Bool(value) => value.ser_json(0, &mut s),
+                DeJsonTok::Null => s.out.push_str("null"),
+                DeJsonTok::CurlyOpen | DeJsonTok::BlockOpen => {
+                    s.out.push(if self.tok == DeJsonTok::CurlyOpen {'{'} else {'['});
+                    depth += 1;
+                }
+                DeJsonTok::CurlyClose | DeJsonTok::BlockClose if depth > 0 => {
+                    s.out.push(if self.tok == DeJsonTok::CurlyClose {'}'} else {']'});
+                    depth -= 1;
+                }
+                DeJsonTok::Colon if depth > 0 => s.out.push(':'),
+                DeJsonTok::Comma if depth > 0 => s.conl(),
+                _ => return Err(self.err_token("value"))
+            }
+            self.next_tok(i) ?;
+            if depth == 0 {
+                return Ok(s.out)
+            }
+        }
+    }
+    
+    /// Reads an object and returns its keys with their values as JSON text.
+    pub fn capture_object(&mut self, i: &mut Chars) -> Result<Vec<(String, String)>, DeJsonErr> {
+        let mut pairs = Vec::new();
+        self.curly_open(i) ?;
+        while self.next_str().is_some() {
+            let key = self.as_string() ?;
+            self.next_colon(i) ?;
+            pairs.push((key, self.capture_value(i) ?));
+            self.eat_comma_curly(i) ?;
+        }
+        self.curly_close(i) ?;
+        Ok(pairs)
+    }
+    
+    /// Puts keys and values that `capture_object` returned back together as an object.
+    pub fn object_text(pairs: &[(String, String)]) -> String {
+        let mut s = SerJsonState {out: String::new()};
+        s.st_pre();
+        for (key, value) in pairs {
+            key.ser_json(0, &mut s);
+            s.out.push(':');
+            s.out.push_str(value);
+            s.conl();
+        }
+        s.st_post(0);
+        s.out
+    }
+    
+    /// Deserializes text that `capture_value` returned with `f`, which has to read all of it.
+    /// Errors are reported at the current position, as the text has none of its own.
+    pub fn de_captured_with<T>(&self, text: &str, f: impl FnOnce(&mut DeJsonState, &mut Chars) -> Result<T, DeJsonErr>) -> Result<T, DeJsonErr> {
+        let mut state = DeJsonState::default();
+        let mut chars = text.chars();
+        state.next(&mut chars);
+        let result = state.next_tok(&mut chars).and_then( | _ | f(&mut state, &mut chars)).and_then( | value | {
+            if state.tok != DeJsonTok::Eof {
+                return Err(state.err_token("end of value"))
+            }
+            Ok(value)
+        });
+        result.map_err( | err | self.err_msg(&err.msg))
+    }
+    
+    pub fn de_captured<T>(&self, text: &str) -> Result<T, DeJsonErr> where T: DeJson {
+        self.de_captured_with(text, T::de_json)
+    }
+    
     pub fn u64_range(&mut self, max: u64) -> Result<u64, DeJsonErr> {
         if let DeJsonTok::U64(value) = self.tok {
             if value > max {
@@ -334,6 +429,38 @@ impl DeJsonState {
         Err(self.err_token("ident"))
     }
     
+    // the 4 hex digits after \u, and the low surrogate after it if it is a high one
+    fn unicode_escape(&mut self, i: &mut Chars) -> Result<char, DeJsonErr> {
+        let high = self.hex4(i) ?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high).ok_or_else(|| self.err_parse("unicode escape"));
+        }
+        self.next(i);
+        if self.cur != '\\' {
+            return Err(self.err_parse("unicode escape"));
+        }
+        self.next(i);
+        if self.cur != 'u' {
+            return Err(self.err_parse("unicode escape"));
+        }
+        let low = self.hex4(i) ?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err(self.err_parse("unicode escape"));
+        }
+        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+            .ok_or_else(|| self.err_parse("unicode escape"))
+    }
+    
+    fn hex4(&mut self, i: &mut Chars) -> Result<u32, DeJsonErr> {
+        let mut value = 0;
+        for _ in 0..4 {
+            self.next(i);
+            let digit = self.cur.to_digit(16).ok_or_else(|| self.err_parse("unicode escape")) ?;
+            value = value * 16 + digit;
+        }
+        Ok(value)
+    }
+    
     pub fn next_tok(&mut self, i: &mut Chars) -> Result<(), DeJsonErr> {
         while self.cur == '\n' || self.cur == '\r' || self.cur == '\t' || self.cur == ' ' {
             self.next(i);
@@ -454,7 +581,13 @@ impl DeJsonState {
                             'n'=>self.strbuf.push('\n'),
                             'r'=>self.strbuf.push('\r'),
                             't'=>self.strbuf.push('\t'),
+                            'b'=>self.strbuf.push('\u{8}'),
+                            'f'=>self.strbuf.push('\u{c}'),
                             '0'=>self.strbuf.push('\0'),
+                            'u'=>{
+                                let c = self.unicode_escape(i) ?;
+                                self.strbuf.push(c);
+                            }
                             '\0'=>{
                                 return Err(self.err_parse("string"));
                             },
@@ -606,6 +739,7 @@ impl SerJson for String {
                 '\0'=>{s.out.push('\\');s.out.push('0');},
                 '\\'=>{s.out.push('\\');s.out.push('\\');},
                 '"'=>{s.out.push('\\');s.out.push('"');},
+                c if (c as u32) < 0x20 => s.out.push_str(&format!("\\u{:04x}", c as u32)),
                 _=>s.out.push(c)
             }
         }
@@ -680,12 +814,176 @@ impl JsonValue{
         }
         None
     }
+    pub fn array(&self)->Option<&Vec<JsonValue>>{
+        if let JsonValue::Array(arr) = self{
+            return Some(arr)
+        }
+        None
+    }
+    pub fn u64(&self)->Option<u64>{
+        match self{
+            JsonValue::U64(v)=>Some(*v),
+            JsonValue::I64(v)=>u64::try_from(*v).ok(),
+            _=>None
+        }
+    }
+    pub fn i64(&self)->Option<i64>{
+        match self{
+            JsonValue::U64(v)=>i64::try_from(*v).ok(),
+            JsonValue::I64(v)=>Some(*v),
+            _=>None
+        }
+    }
+    pub fn f64(&self)->Option<f64>{
+        match self{
+            JsonValue::U64(v)=>Some(*v as f64),
+            JsonValue::I64(v)=>Some(*v as f64),
+            JsonValue::F64(v)=>Some(*v),
+            _=>None
+        }
+    }
+    pub fn bool(&self)->Option<bool>{
+        if let JsonValue::Bool(v) = self{
+            return Some(*v)
+        }
+        None
+    }
+    pub fn is_null(&self)->bool{
+        matches!(self, JsonValue::Null | JsonValue::Undefined)
+    }
     pub fn key(&self, key:&str)->Option<&JsonValue>{
         if let JsonValue::Object(obj) = self{
             return obj.get(key)
         }
         None
     }
+    pub fn item(&self, index:usize)->Option<&JsonValue>{
+        self.array()?.get(index)
+    }
+    pub fn object_mut(&mut self)->Option<&mut HashMap<String, JsonValue>>{
+        if let JsonValue::Object(obj) = self{
+            return Some(obj)
+        }
+        None
+    }
+    pub fn array_mut(&mut self)->Option<&mut Vec<JsonValue>>{
+        if let JsonValue::Array(arr) = self{
+            return Some(arr)
+        }
+        None
+    }
+    pub fn key_mut(&mut self, key:&str)->Option<&mut JsonValue>{
+        self.object_mut()?.get_mut(key)
+    }
+    pub fn item_mut(&mut self, index:usize)->Option<&mut JsonValue>{
+        self.array_mut()?.get_mut(index)
+    }
+    
+    /// Looks up a value by a JSON pointer like `/choices/0/message`, where `~1` stands for
+    /// `/` and `~0` for `~` in keys. The empty pointer is the value itself.
+    pub fn pointer(&self, pointer:&str)->Option<&JsonValue>{
+        let mut value = self;
+        for token in pointer_tokens(pointer)?{
+            value = match value{
+                JsonValue::Object(obj)=>obj.get(&token)?,
+                JsonValue::Array(arr)=>arr.get(pointer_index(&token)?)?,
+                _=>return None
+            };
+        }
+        Some(value)
+    }
+    
+    pub fn pointer_mut(&mut self, pointer:&str)->Option<&mut JsonValue>{
+        let mut value = self;
+        for token in pointer_tokens(pointer)?{
+            value = match value{
+                JsonValue::Object(obj)=>obj.get_mut(&token)?,
+                JsonValue::Array(arr)=>arr.get_mut(pointer_index(&token)?)?,
+                _=>return None
+            };
+        }
+        Some(value)
+    }
+    
+    /// Replaces the value with `Null`, and returns it.
+    pub fn take(&mut self)->JsonValue{
+        std::mem::replace(self, JsonValue::Null)
+    }
+}
+
+fn pointer_tokens(pointer:&str)->Option<Vec<String>>{
+    if pointer.is_empty(){
+        return Some(Vec::new())
+    }
+    Some(pointer.strip_prefix('/')?.split('/').map(|token| token.replace("~1", "/").replace("~0", "~")).collect())
+}
+
+fn pointer_index(token:&str)->Option<usize>{
+    if token.is_empty() || !token.bytes().all(|b| b.is_ascii_digit()) || (token.len() > 1 && token.starts_with('0')){
+        return None
+    }
+    token.parse().ok()
+}
+
+impl std::fmt::Display for JsonValue{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.serialize_json())
+    }
+}
+
+impl From<&str> for JsonValue{
+    fn from(v: &str) -> Self {JsonValue::String(v.to_string())}
+}
+
+impl From<String> for JsonValue{
+    fn from(v: String) -> Self {JsonValue::String(v)}
+}
+
+impl From<u64> for JsonValue{
+    fn from(v: u64) -> Self {JsonValue::U64(v)}
+}
+
+impl From<i64> for JsonValue{
+    fn from(v: i64) -> Self {JsonValue::I64(v)}
+}
+
+impl From<f64> for JsonValue{
+    fn from(v: f64) -> Self {JsonValue::F64(v)}
+}
+
+impl From<bool> for JsonValue{
+    fn from(v: bool) -> Self {JsonValue::Bool(v)}
+}
+
+impl From<Vec<JsonValue>> for JsonValue{
+    fn from(v: Vec<JsonValue>) -> Self {JsonValue::Array(v)}
+}
+
+impl SerJson for JsonValue{
+    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+        match self{
+            JsonValue::String(v)=>v.ser_json(d, s),
+            JsonValue::Char(c)=>c.to_string().ser_json(d, s),
+            JsonValue::U64(v)=>v.ser_json(d, s),
+            JsonValue::I64(v)=>v.ser_json(d, s),
+            JsonValue::F64(v)=>v.ser_json(d, s),
+            JsonValue::Bool(v)=>v.ser_json(d, s),
+            JsonValue::BareIdent(v)=>s.out.push_str(v),
+            JsonValue::Null | JsonValue::Undefined=>s.out.push_str("null"),
+            JsonValue::Object(obj)=>{
+                s.st_pre();
+                for (index, (key, value)) in obj.iter().enumerate(){
+                    if index != 0{
+                        s.conl();
+                    }
+                    s.field(d + 1, key);
+                    value.ser_json(d + 1, s);
+                }
+                s.st_post(d);
+            }
+            JsonValue::Array(values)=>values.ser_json(d, s),
+        }
+    }
 }
 
 impl DeJson for JsonValue{
@@ -776,12 +1074,11 @@ impl DeJson for JsonValue{
 impl<T> SerJson for [T] where T: SerJson {
     fn ser_json(&self, d: usize, s: &mut SerJsonState) {
         s.out.push('[');
-        let last = self.len() -1;
         for (index,item) in self.iter().enumerate() {
-            item.ser_json(d + 1, s);
-            if index != last{
+            if index != 0{
                 s.out.push(',');
             }
+            item.ser_json(d + 1, s);
         }
         s.out.push(']');
     }
@@ -894,15 +1191,14 @@ impl<K, V> SerJson for HashMap<K, V> where K: SerJson,
 V: SerJson {
     fn ser_json(&self, d: usize, s: &mut SerJsonState) {
         s.out.push('{');
-        let last = self.len() - 1;
         for (index, (k, v)) in self.iter().enumerate() {
+            if index != 0 {
+                s.conl();
+            }
             s.indent(d + 1);
             k.ser_json(d + 1, s);
             s.out.push(':');
             v.ser_json(d + 1, s);
-            if index != last{
-                s.conl();
-            }
         }
         s.indent(d);
         s.out.push('}');
@@ -32,6 +32,19 @@ impl SerRonState {
         self.out.push(')');
     }
     
+    /// Writes the fields of `value`, which serializes to a struct, into the struct being
+    /// written, for `#[serde(flatten)]`.
+    pub fn flatten<T>(&mut self, d: usize, value: &T) where T: SerRon + ?Sized {
+        let start = self.out.len();
+        value.ser_ron(d, self);
+        // a struct is "(\n", a line for each field and then the indented ")"
+        if self.out[start..].starts_with("(\n") && self.out.ends_with(')') {
+            self.out.pop();
+            let len = self.out.trim_end_matches(' ').len();
+            self.out.truncate(len);
+            self.out.replace_range(start..start + 2, "");
+        }
+    }
 }
 
 pub trait SerRon {
@@ -131,6 +144,10 @@ impl DeRonState {
         DeRonErr {msg: format!("Unexpected key {}", name), line: self.line, col: self.col}
     }
     
+    pub fn err_msg(&self, msg: &str) -> DeRonErr {
+        DeRonErr {msg: msg.to_string(), line: self.line, col: self.col}
+    }
+    
     pub fn err_nf(&self, name: &str) -> DeRonErr {
         DeRonErr {msg: format!("Key not found {}", name), line: self.line, col: self.col}
     }
@@ -290,6 +307,92 @@ impl DeRonState {
         Err(self.err_token("}"))
     }
     
+    /// Reads the next value, whatever its type, and returns it as RON text to deserialize later,
+    /// for the enum representations and fields that need to look ahead.
+    pub fn capture_value(&mut self, i: &mut Chars) -> Result<String, DeRonErr> {
+        let mut s = SerRonState {out: String::new()};
+        let mut depth = 0;
+        loop {
+            let is_ident = self.tok == DeRonTok::Ident;
+            match self.tok {
+                DeRonTok::Ident => s.out.push_str(&self.identbuf),
+                DeRonTok::Str => self.strbuf.ser_ron(0, &mut s),
+                DeRonTok::U64(_) | DeRonTok::I64(_) | DeRonTok::F64(_) => s.out.push_str(&self.numbuf),
+                DeRonTok::Bool(value) => value.ser_ron(0, &mut s),
+                DeRonTok::Char(c) => {
+                    s.out.push('\'');
+                    if c == '\'' || c == '\\' {
+                        s.out.push('\\');
+                    }
+                    s.out.push(c);
+                    s.out.push('\'');
+                }
+                DeRonTok::ParenOpen | DeRonTok::BlockOpen | DeRonTok::CurlyOpen => {
+                    s.out.push(match self.tok {DeRonTok::ParenOpen => '(', DeRonTok::BlockOpen => '[', _ => '{'});
+                    depth += 1;
+                }
+                DeRonTok::ParenClose | DeRonTok::BlockClose | DeRonTok::CurlyClose if depth > 0 => {
+                    s.out.push(match self.tok {DeRonTok::ParenClose => ')', DeRonTok::BlockClose => ']', _ => '}'});
+                    depth -= 1;
+                }
+                DeRonTok::Colon if depth > 0 => s.out.push(':'),
+                DeRonTok::Comma if depth > 0 => s.out.push(','),
+                _ => return Err(self.err_token("value"))
+            }
+            self.next_tok(i) ?;
+            // an enum variant with fields is its name followed by them
+            if depth == 0 && !(is_ident && self.tok == DeRonTok::ParenOpen) {
+                return Ok(s.out)
+            }
+        }
+    }
+    
+    /// Reads a struct and returns its fields with their values as RON text.
+    pub fn capture_object(&mut self, i: &mut Chars) -> Result<Vec<(String, String)>, DeRonErr> {
+        let mut pairs = Vec::new();
+        self.paren_open(i) ?;
+        while self.next_ident().is_some() {
+            let key = std::mem::take(&mut self.identbuf);
+            self.next_colon(i) ?;
+            pairs.push((key, self.capture_value(i) ?));
+            self.eat_comma_paren(i) ?;
+        }
+        self.paren_close(i) ?;
+        Ok(pairs)
+    }
+    
+    /// Puts fields and values that `capture_object` returned back together as a struct.
+    pub fn object_text(pairs: &[(String, String)]) -> String {
+        let mut out = String::from("(");
+        for (key, value) in pairs {
+            out.push_str(key);
+            out.push(':');
+            out.push_str(value);
+            out.push(',');
+        }
+        out.push(')');
+        out
+    }
+    
+    /// Deserializes text that `capture_value` returned with `f`, which has to read all of it.
+    /// Errors are reported at the current position, as the text has none of its own.
+    pub fn de_captured_with<T>(&self, text: &str, f: impl FnOnce(&mut DeRonState, &mut Chars) -> Result<T, DeRonErr>) -> Result<T, DeRonErr> {
+        let mut state = DeRonState::default();
+        let mut chars = text.chars();
+        state.next(&mut chars);
+        let result = state.next_tok(&mut chars).and_then( | _ | f(&mut state, &mut chars)).and_then( | value | {
+            if state.tok != DeRonTok::Eof {
+                return Err(state.err_token("end of value"))
+            }
+            Ok(value)
+        });
+        result.map_err( | err | self.err_msg(&err.msg))
+    }
+    
+    pub fn de_captured<T>(&self, text: &str) -> Result<T, DeRonErr> where T: DeRon {
+        self.de_captured_with(text, T::de_ron)
+    }
+    
     
     pub fn u64_range(&mut self, max: u64) -> Result<u64, DeRonErr> {
         if let DeRonTok::U64(value) = self.tok {
@@ -477,6 +580,7 @@ impl DeRonState {
                     self.identbuf.clear();
                     while self.cur >= 'a' && self.cur <= 'z'
                         || self.cur >= 'A' && self.cur <= 'Z'
+                        || self.cur >= '0' && self.cur <= '9'
                         || self.cur == '_' {
                         self.identbuf.push(self.cur);
                         self.next(i);
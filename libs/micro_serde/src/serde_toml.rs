@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+pub use makepad_toml_parser::{parse_toml, Toml, TomlErr, TomlSpan};
+
+/// Deserializes from a parsed TOML tree. Unlike the other formats toml tables can be
+/// spread out over a document, so we parse the whole thing first and walk the tree.
+pub trait DeToml: Sized {
+
+    fn deserialize_toml(input: &str) -> Result<Self,
+    DeTomlErr> {
+        let toml = parse_toml(input) ?;
+        DeToml::de_toml(&toml)
+    }
+
+    fn de_toml(t: &Toml) -> Result<Self,
+    DeTomlErr>;
+
+    /// Called when a struct field is absent from its table, only Option overrides this
+    fn de_toml_missing(t: &Toml, name: &str) -> Result<Self,
+    DeTomlErr> {
+        Err(DeTomlErr::err_nf(t, name))
+    }
+}
+
+pub struct DeTomlErr {
+    pub msg: String,
+    pub span: TomlSpan,
+}
+
+impl std::fmt::Debug for DeTomlErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Toml Deserialize error: {}, start:{} len:{}", self.msg, self.span.start, self.span.len)
+    }
+}
+
+impl From<TomlErr> for DeTomlErr {
+    fn from(err: TomlErr) -> Self {
+        DeTomlErr {msg: err.msg, span: err.span}
+    }
+}
+
+impl DeTomlErr {
+    pub fn err_nf(t: &Toml, name: &str) -> DeTomlErr {
+        DeTomlErr {msg: format!("Key not found {}", name), span: t.span().clone()}
+    }
+
+    pub fn err_enum(t: &Toml, name: &str) -> DeTomlErr {
+        DeTomlErr {msg: format!("Enum not defined {}", name), span: t.span().clone()}
+    }
+
+    pub fn err_type(t: &Toml, what: &str) -> DeTomlErr {
+        DeTomlErr {msg: format!("Value wrong type, expected {} ", what), span: t.span().clone()}
+    }
+
+    pub fn err_range(t: &Toml, what: &str) -> DeTomlErr {
+        DeTomlErr {msg: format!("Value out of range {} ", what), span: t.span().clone()}
+    }
+}
+
+/// Reads a struct field from a table. Fields are looked up by their name and then by its
+/// kebab-case form, so `opt_level` also matches the `opt-level` key cargo uses.
+pub fn de_toml_field<T>(t: &Toml, name: &str) -> Result<T, DeTomlErr> where T: DeToml {
+    let table = t.as_table().ok_or_else( || DeTomlErr::err_type(t, "table")) ?;
+    if let Some(value) = table.get(name) {
+        return DeToml::de_toml(value)
+    }
+    if let Some(value) = table.get(&name.replace('_', "-")) {
+        return DeToml::de_toml(value)
+    }
+    T::de_toml_missing(t, name)
+}
+
+/// Returns the values of a fixed length array, used for tuples
+pub fn de_toml_tuple(t: &Toml, len: usize) -> Result<&[Toml], DeTomlErr> {
+    match t {
+        Toml::Array(values, _) if values.len() == len => Ok(values),
+        _ => Err(DeTomlErr::err_type(t, &format!("array of length {}", len)))
+    }
+}
+
+macro_rules!impl_de_toml_int {
+    ( $ ty: ident) => {
+        impl DeToml for $ ty {
+            fn de_toml(t: &Toml) -> Result< $ ty,
+            DeTomlErr> {
+                let val = t.as_i64().ok_or_else( || DeTomlErr::err_type(t, "integer")) ?;
+                $ ty::try_from(val).map_err( | _ | DeTomlErr::err_range(t, stringify!( $ ty)))
+            }
+        }
+    }
+}
+
+macro_rules!impl_de_toml_float {
+    ( $ ty: ident) => {
+        impl DeToml for $ ty {
+            fn de_toml(t: &Toml) -> Result< $ ty,
+            DeTomlErr> {
+                let val = t.as_f64().ok_or_else( || DeTomlErr::err_type(t, "number")) ?;
+                Ok(val as $ ty)
+            }
+        }
+    }
+}
+
+impl_de_toml_int!(usize);
+impl_de_toml_int!(u64);
+impl_de_toml_int!(u32);
+impl_de_toml_int!(u16);
+impl_de_toml_int!(u8);
+impl_de_toml_int!(i64);
+impl_de_toml_int!(i32);
+impl_de_toml_int!(i16);
+impl_de_toml_int!(i8);
+impl_de_toml_float!(f64);
+impl_de_toml_float!(f32);
+
+impl<T> DeToml for Option<T> where T: DeToml {
+    fn de_toml(t: &Toml) -> Result<Self, DeTomlErr> {
+        Ok(Some(DeToml::de_toml(t) ?))
+    }
+
+    fn de_toml_missing(_t: &Toml, _name: &str) -> Result<Self, DeTomlErr> {
+        Ok(None)
+    }
+}
+
+impl DeToml for bool {
+    fn de_toml(t: &Toml) -> Result<bool, DeTomlErr> {
+        t.as_bool().ok_or_else( || DeTomlErr::err_type(t, "bool"))
+    }
+}
+
+impl DeToml for String {
+    fn de_toml(t: &Toml) -> Result<String, DeTomlErr> {
+        match t {
+            // dates have no type of their own here, hand them out as written
+            Toml::Str(s, _) | Toml::Date(s, _) => Ok(s.clone()),
+            _ => Err(DeTomlErr::err_type(t, "string"))
+        }
+    }
+}
+
+impl<T> DeToml for Vec<T> where T: DeToml {
+    fn de_toml(t: &Toml) -> Result<Vec<T>, DeTomlErr> {
+        let values = t.as_array().ok_or_else( || DeTomlErr::err_type(t, "array")) ?;
+        values.iter().map(DeToml::de_toml).collect()
+    }
+}
+
+impl<T, const N: usize> DeToml for [T; N] where T: DeToml {
+    fn de_toml(t: &Toml) -> Result<Self, DeTomlErr> {
+        let values = de_toml_tuple(t, N) ?;
+        let values: Vec<T> = values.iter().map(DeToml::de_toml).collect::<Result<_, _>>() ?;
+        Ok(values.try_into().unwrap_or_else( | _ | unreachable!()))
+    }
+}
+
+impl<A, B> DeToml for (A, B) where A: DeToml,
+B: DeToml {
+    fn de_toml(t: &Toml) -> Result<(A, B), DeTomlErr> {
+        let v = de_toml_tuple(t, 2) ?;
+        Ok((DeToml::de_toml(&v[0]) ?, DeToml::de_toml(&v[1]) ?))
+    }
+}
+
+impl<K, V> DeToml for HashMap<K, V> where K: From<String> + Eq + Hash,
+V: DeToml {
+    fn de_toml(t: &Toml) -> Result<Self, DeTomlErr> {
+        let table = t.as_table().ok_or_else( || DeTomlErr::err_type(t, "table")) ?;
+        let mut h = HashMap::new();
+        for (k, v) in table {
+            h.insert(K::from(k.clone()), DeToml::de_toml(v) ?);
+        }
+        Ok(h)
+    }
+}
+
+impl<T> DeToml for Box<T> where T: DeToml {
+    fn de_toml(t: &Toml) -> Result<Box<T>, DeTomlErr> {
+        Ok(Box::new(DeToml::de_toml(t) ?))
+    }
+}
+
+impl DeToml for Toml {
+    fn de_toml(t: &Toml) -> Result<Toml, DeTomlErr> {
+        Ok(t.clone())
+    }
+}
@@ -0,0 +1,232 @@
+// Tests for the `#[serde(...)]` attributes of the derives. JSON output is checked through
+// `JsonValue`, so key order and whitespace don't matter; RON is checked by round tripping.
+
+use makepad_micro_serde::*;
+use std::collections::HashMap;
+
+fn json(value: &impl SerJson) -> JsonValue {
+    JsonValue::deserialize_json(&value.serialize_json()).unwrap()
+}
+
+fn keys(value: &JsonValue) -> Vec<String> {
+    let mut keys: Vec<String> = value.object().unwrap().keys().cloned().collect();
+    keys.sort();
+    keys
+}
+
+fn round_trip_ron<T: SerRon + DeRon + PartialEq + std::fmt::Debug>(value: T) {
+    let ron = value.serialize_ron();
+    assert_eq!(T::deserialize_ron(&ron).unwrap(), value, "{}", ron);
+}
+
+fn round_trip_json<T: SerJson + DeJson + PartialEq + std::fmt::Debug>(value: T) {
+    let json = value.serialize_json();
+    assert_eq!(T::deserialize_json(&json).unwrap(), value, "{}", json);
+}
+
+fn is_empty(value: &[u32]) -> bool {
+    value.is_empty()
+}
+
+fn seven() -> u32 {
+    7
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, SerBin, DeBin, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Settings {
+    font_size: u32,
+    #[serde(rename = "theme")]
+    color_theme: String,
+    #[serde(default = "seven")]
+    tab_width: u32,
+    #[serde(skip)]
+    cache: Vec<u32>,
+    #[serde(skip_serializing_if = "is_empty", default)]
+    recent_files: Vec<u32>,
+    last_file: Option<String>,
+}
+
+fn settings() -> Settings {
+    Settings {
+        font_size: 12,
+        color_theme: "dark".to_string(),
+        tab_width: 4,
+        cache: vec![1, 2],
+        recent_files: vec![],
+        last_file: None,
+    }
+}
+
+#[test]
+fn renames_and_skips_fields() {
+    let value = json(&settings());
+    assert_eq!(keys(&value), ["fontSize", "tabWidth", "theme"]);
+    assert_eq!(value.key("theme").unwrap().string().unwrap(), "dark");
+
+    let value = json(&Settings {recent_files: vec![3], ..settings()});
+    assert_eq!(keys(&value), ["fontSize", "recentFiles", "tabWidth", "theme"]);
+
+    let cleared = Settings {cache: vec![], ..settings()};
+    assert_eq!(Settings::deserialize_json(&settings().serialize_json()).unwrap(), cleared);
+    assert_eq!(Settings::deserialize_ron(&settings().serialize_ron()).unwrap(), cleared);
+    assert_eq!(Settings::deserialize_bin(&settings().serialize_bin()).unwrap(), cleared);
+}
+
+#[test]
+fn fills_in_defaults() {
+    let value = Settings::deserialize_json(r#"{"fontSize": 10, "theme": "light"}"#).unwrap();
+    assert_eq!(value.tab_width, 7);
+    assert_eq!(value.recent_files, []);
+    assert_eq!(value.last_file, None);
+    assert!(Settings::deserialize_json(r#"{"theme": "light"}"#).is_err());
+    assert!(Settings::deserialize_json(r#"{"fontSize": 10, "theme": "light", "other": 1}"#).is_err());
+
+    let value = Settings::deserialize_ron("(fontSize: 10, theme: \"light\")").unwrap();
+    assert_eq!(value.tab_width, 7);
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, PartialEq, Debug)]
+#[serde(default)]
+struct Window {
+    width: u32,
+    height: u32,
+    title: String,
+}
+
+impl Default for Window {
+    fn default() -> Self {
+        Self {width: 800, height: 600, title: "untitled".to_string()}
+    }
+}
+
+#[test]
+fn fills_in_container_defaults() {
+    let value = Window::deserialize_json(r#"{"height": 100}"#).unwrap();
+    assert_eq!(value, Window {height: 100, ..Window::default()});
+    let value = Window::deserialize_ron("(title: \"main\")").unwrap();
+    assert_eq!(value, Window {title: "main".to_string(), ..Window::default()});
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, PartialEq, Debug)]
+struct Position {
+    x: i32,
+    y: i32,
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, PartialEq, Debug)]
+struct Placed {
+    name: String,
+    #[serde(flatten)]
+    position: Position,
+}
+
+#[derive(SerJson, DeJson, PartialEq, Debug)]
+struct WithExtra {
+    name: String,
+    #[serde(flatten)]
+    extra: HashMap<String, u32>,
+}
+
+#[test]
+fn flattens_fields() {
+    let placed = Placed {name: "a".to_string(), position: Position {x: 1, y: -2}};
+    let value = json(&placed);
+    assert_eq!(keys(&value), ["name", "x", "y"]);
+    assert_eq!(value.key("x").unwrap().u64(), Some(1));
+    round_trip_json(placed);
+    round_trip_ron(Placed {name: "b".to_string(), position: Position {x: 3, y: 4}});
+
+    let value = WithExtra::deserialize_json(r#"{"a": 1, "name": "n", "b": 2}"#).unwrap();
+    assert_eq!(value.name, "n");
+    assert_eq!(value.extra.len(), 2);
+    assert_eq!(value.extra["b"], 2);
+    round_trip_json(value);
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, PartialEq, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Internal {
+    Quit,
+    MoveTo {x: i32, y: i32},
+    Place(Position),
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, PartialEq, Debug)]
+#[serde(tag = "t", content = "c")]
+enum Adjacent {
+    Quit,
+    Write(String),
+    Resize(u32, u32),
+    #[serde(rename = "move")]
+    MoveTo {x: i32, y: i32},
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, PartialEq, Debug)]
+#[serde(untagged)]
+enum Untagged {
+    Nothing,
+    Number(u32),
+    Pair(u32, String),
+    Point {x: i32, y: i32},
+}
+
+#[test]
+fn tags_enums_internally() {
+    let value = json(&Internal::MoveTo {x: 1, y: 2});
+    assert_eq!(keys(&value), ["type", "x", "y"]);
+    assert_eq!(value.key("type").unwrap().string().unwrap(), "move_to");
+    let value = json(&Internal::Place(Position {x: 3, y: 4}));
+    assert_eq!(keys(&value), ["type", "x", "y"]);
+    assert_eq!(keys(&json(&Internal::Quit)), ["type"]);
+
+    let value = Internal::deserialize_json(r#"{"x": 5, "type": "move_to", "y": 6}"#).unwrap();
+    assert_eq!(value, Internal::MoveTo {x: 5, y: 6});
+    assert!(Internal::deserialize_json(r#"{"type": "jump"}"#).is_err());
+    assert!(Internal::deserialize_json(r#"{"x": 5}"#).is_err());
+
+    for value in [Internal::Quit, Internal::MoveTo {x: -1, y: 2}, Internal::Place(Position {x: 0, y: 9})] {
+        let ron = value.serialize_ron();
+        assert_eq!(Internal::deserialize_ron(&ron).unwrap(), value, "{}", ron);
+        round_trip_json(value);
+    }
+}
+
+#[test]
+fn tags_enums_adjacently() {
+    let value = json(&Adjacent::Resize(3, 4));
+    assert_eq!(keys(&value), ["c", "t"]);
+    assert_eq!(value.key("t").unwrap().string().unwrap(), "Resize");
+    assert_eq!(value.key("c").unwrap().array().unwrap().len(), 2);
+    let value = json(&Adjacent::MoveTo {x: 1, y: 2});
+    assert_eq!(value.key("t").unwrap().string().unwrap(), "move");
+    assert_eq!(keys(value.key("c").unwrap()), ["x", "y"]);
+    assert_eq!(keys(&json(&Adjacent::Quit)), ["t"]);
+
+    let value = Adjacent::deserialize_json(r#"{"c": "hello", "t": "Write"}"#).unwrap();
+    assert_eq!(value, Adjacent::Write("hello".to_string()));
+    assert!(Adjacent::deserialize_json(r#"{"t": "Write"}"#).is_err());
+
+    for value in [Adjacent::Quit, Adjacent::Write("a, (b)".to_string()), Adjacent::Resize(1, 2), Adjacent::MoveTo {x: 3, y: 4}] {
+        let ron = value.serialize_ron();
+        assert_eq!(Adjacent::deserialize_ron(&ron).unwrap(), value, "{}", ron);
+        round_trip_json(value);
+    }
+}
+
+#[test]
+fn leaves_enums_untagged() {
+    assert!(matches!(json(&Untagged::Nothing), JsonValue::Null));
+    assert_eq!(json(&Untagged::Number(5)).u64(), Some(5));
+    assert_eq!(json(&Untagged::Pair(1, "a".to_string())).array().unwrap().len(), 2);
+    assert_eq!(keys(&json(&Untagged::Point {x: 1, y: 2})), ["x", "y"]);
+
+    assert_eq!(Untagged::deserialize_json(r#"{"y": 2, "x": 1}"#).unwrap(), Untagged::Point {x: 1, y: 2});
+    assert!(Untagged::deserialize_json(r#""text""#).is_err());
+
+    for value in [Untagged::Nothing, Untagged::Number(5), Untagged::Pair(1, "a".to_string()), Untagged::Point {x: 1, y: 2}] {
+        let ron = value.serialize_ron();
+        assert_eq!(Untagged::deserialize_ron(&ron).unwrap(), value, "{}", ron);
+        round_trip_json(value);
+    }
+}
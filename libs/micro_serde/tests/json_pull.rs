@@ -0,0 +1,171 @@
+// Tests for the incremental JSON parser, fed whole and in pieces, and for looking up and
+// changing `JsonValue`s.
+
+use makepad_micro_serde::*;
+use std::borrow::Cow;
+
+const DOCUMENT: &str = r#"{
+    "id": "chat-1",
+    "choices": [{"index": 0, "message": {"role": "assistant", "content": "Hi \"there\"\né😀"}}],
+    "usage": {"total": 12, "delta": -3, "ratio": 0.25, "big": 1e3},
+    "done": true,
+    "error": null,
+    "tags": [],
+    "a/b": {"c~d": "escaped"}
+}"#;
+
+// all events of the input, fed `chunk` bytes at a time
+fn events(input: &str, chunk: usize) -> Result<Vec<JsonEvent<'static>>, DeJsonErr> {
+    let mut parser = JsonPullParser::new();
+    let mut events = Vec::new();
+    for bytes in input.as_bytes().chunks(chunk) {
+        parser.feed(bytes);
+        while let Some(event) = parser.next_event()? {
+            events.push(event.into_owned());
+        }
+    }
+    parser.finish();
+    while let Some(event) = parser.next_event()? {
+        events.push(event.into_owned());
+    }
+    Ok(events)
+}
+
+fn parse(input: &str) -> JsonValue {
+    let mut parser = JsonPullParser::new();
+    parser.feed(input.as_bytes());
+    parser.finish();
+    parser.next_value().unwrap().unwrap()
+}
+
+#[test]
+fn reads_events() {
+    let events = events(r#"{"a": [1, -2, 3.5, "x"], "b": {}, "c": false}"#, 1000).unwrap();
+    assert_eq!(events, [
+        JsonEvent::ObjectStart,
+        JsonEvent::Key("a".into()),
+        JsonEvent::ArrayStart,
+        JsonEvent::U64(1),
+        JsonEvent::I64(-2),
+        JsonEvent::F64(3.5),
+        JsonEvent::String("x".into()),
+        JsonEvent::ArrayEnd,
+        JsonEvent::Key("b".into()),
+        JsonEvent::ObjectStart,
+        JsonEvent::ObjectEnd,
+        JsonEvent::Key("c".into()),
+        JsonEvent::Bool(false),
+        JsonEvent::ObjectEnd,
+    ]);
+}
+
+#[test]
+fn reads_any_chunking() {
+    let whole = events(DOCUMENT, DOCUMENT.len()).unwrap();
+    for chunk in [1, 2, 3, 7, 64] {
+        assert_eq!(events(DOCUMENT, chunk).unwrap(), whole, "chunks of {}", chunk);
+    }
+    assert!(whole.contains(&JsonEvent::String("Hi \"there\"\n\u{e9}\u{1f600}".into())));
+}
+
+#[test]
+fn borrows_unescaped_strings() {
+    let mut parser = JsonPullParser::new();
+    parser.feed(br#"["plain", "esc\taped"]"#);
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ArrayStart));
+    assert!(matches!(parser.next_event().unwrap(), Some(JsonEvent::String(Cow::Borrowed("plain")))));
+    assert!(matches!(parser.next_event().unwrap(), Some(JsonEvent::String(Cow::Owned(_)))));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ArrayEnd));
+    assert_eq!(parser.next_event().unwrap(), None);
+}
+
+#[test]
+fn waits_for_complete_tokens() {
+    let mut parser = JsonPullParser::new();
+    parser.feed(b"[12");
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ArrayStart));
+    assert_eq!(parser.next_event().unwrap(), None);
+    parser.feed(b"34, tr");
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::U64(1234)));
+    assert_eq!(parser.next_event().unwrap(), None);
+    parser.feed(b"ue]");
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::Bool(true)));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ArrayEnd));
+    assert_eq!(parser.depth(), 0);
+
+    // a top level number ends with the input
+    let mut parser = JsonPullParser::new();
+    parser.feed(b"42");
+    assert_eq!(parser.next_event().unwrap(), None);
+    parser.finish();
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::U64(42)));
+}
+
+#[test]
+fn reports_errors() {
+    assert!(events(r#"{"a" 1}"#, 100).is_err());
+    assert!(events(r#"[1, 2}"#, 100).is_err());
+    assert!(events(r#"{"a": 1,}"#, 100).is_err());
+    assert!(events(r#"[1, 2"#, 100).is_err());
+    assert!(events(r#""open"#, 100).is_err());
+    assert!(events(r#"[nul]"#, 100).is_err());
+    assert!(events(r#"["\q"]"#, 100).is_err());
+
+    let err = events("[1,\n  x]", 100).unwrap_err();
+    assert_eq!((err.line, err.col), (1, 2));
+}
+
+#[test]
+fn builds_values_from_a_stream() {
+    let mut parser = JsonPullParser::new();
+    let mut values = Vec::new();
+    for bytes in b"{\"n\": 1}\n{\"n\": 2}\n[3]".chunks(3) {
+        parser.feed(bytes);
+        while let Some(value) = parser.next_value().unwrap() {
+            values.push(value);
+        }
+    }
+    parser.finish();
+    while let Some(value) = parser.next_value().unwrap() {
+        values.push(value);
+    }
+    assert_eq!(values.len(), 3);
+    assert_eq!(values[1].key("n").unwrap().u64(), Some(2));
+    assert_eq!(values[2].item(0).unwrap().u64(), Some(3));
+}
+
+#[test]
+fn looks_up_pointers() {
+    let value = parse(DOCUMENT);
+    assert_eq!(value.pointer("/choices/0/message/role").unwrap().string().unwrap(), "assistant");
+    assert_eq!(value.pointer("/usage/total").unwrap().u64(), Some(12));
+    assert_eq!(value.pointer("/usage/delta").unwrap().i64(), Some(-3));
+    assert_eq!(value.pointer("/usage/ratio").unwrap().f64(), Some(0.25));
+    assert_eq!(value.pointer("/usage/big").unwrap().f64(), Some(1000.0));
+    assert_eq!(value.pointer("/done").unwrap().bool(), Some(true));
+    assert!(value.pointer("/error").unwrap().is_null());
+    assert_eq!(value.pointer("/a~1b/c~0d").unwrap().string().unwrap(), "escaped");
+    assert!(value.pointer("").unwrap().object().is_some());
+    assert!(value.pointer("/choices/1").is_none());
+    assert!(value.pointer("/choices/01").is_none());
+    assert!(value.pointer("/id/0").is_none());
+    assert!(value.pointer("id").is_none());
+}
+
+#[test]
+fn changes_and_writes_values() {
+    let mut value = parse(DOCUMENT);
+    *value.pointer_mut("/choices/0/message/content").unwrap() = "bye".into();
+    value.key_mut("tags").unwrap().array_mut().unwrap().push(true.into());
+    value.object_mut().unwrap().insert("count".to_string(), 3u64.into());
+    let usage = value.key_mut("usage").unwrap().take();
+    assert!(value.key("usage").unwrap().is_null());
+
+    let text = value.to_string();
+    let value = parse(&text);
+    assert_eq!(value.pointer("/choices/0/message/content").unwrap().string().unwrap(), "bye");
+    assert_eq!(value.pointer("/tags/0").unwrap().bool(), Some(true));
+    assert_eq!(value.key("count").unwrap().u64(), Some(3));
+    assert_eq!(usage.key("total").unwrap().u64(), Some(12));
+    assert_eq!(JsonValue::deserialize_json(&text).unwrap().pointer("/a~1b/c~0d").unwrap().string().unwrap(), "escaped");
+}
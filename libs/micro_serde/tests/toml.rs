@@ -0,0 +1,134 @@
+// Tests for the `DeToml` derive. There is no toml serializer, so documents are written out
+// by hand and the value they give is round tripped through RON and JSON to compare with.
+
+use makepad_micro_serde::*;
+use std::collections::HashMap;
+
+#[derive(SerJson, DeJson, SerRon, DeRon, DeToml, PartialEq, Debug)]
+struct Manifest {
+    package: Package,
+    bin: Vec<Target>,
+    dependencies: HashMap<String, Dependency>,
+    profile: Option<Profile>,
+    edition: Edition,
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, DeToml, PartialEq, Debug)]
+struct Package {
+    name: String,
+    version: String,
+    description: Option<String>,
+    authors: Vec<String>,
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, DeToml, PartialEq, Debug)]
+struct Target {
+    name: String,
+    path: Option<String>,
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, DeToml, PartialEq, Debug)]
+enum Dependency {
+    Version(String),
+    Path {path: String, optional: Option<bool>},
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, DeToml, PartialEq, Debug)]
+struct Profile {
+    opt_level: u8,
+    lto: bool,
+    range: (u32, i64),
+    weights: [f32; 2],
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, DeToml, PartialEq, Debug)]
+enum Edition {
+    E2018,
+    E2021,
+}
+
+const MANIFEST: &str = r#"
+edition = "E2021"
+
+[package]
+name = "app"
+version = "0.1.0"
+authors = ["a", 'b']
+description = """
+multi
+line"""
+
+[[bin]]
+name = "first"
+
+[[bin]]
+name = "second"
+path = 'src\second.rs'
+
+[dependencies]
+log.Version = ["0.4"]
+local = {Path = {path = "../local", optional = true}}
+
+[profile]
+opt-level = 0x3
+lto = true
+range = [0b101, -15]
+weights = [0.5, 2e1]
+"#;
+
+fn manifest() -> Manifest {
+    Manifest {
+        package: Package {
+            name: "app".to_string(),
+            version: "0.1.0".to_string(),
+            description: Some("multi\nline".to_string()),
+            authors: vec!["a".to_string(), "b".to_string()],
+        },
+        bin: vec![
+            Target {name: "first".to_string(), path: None},
+            Target {name: "second".to_string(), path: Some("src\\second.rs".to_string())},
+        ],
+        dependencies: HashMap::from([
+            ("log".to_string(), Dependency::Version("0.4".to_string())),
+            ("local".to_string(), Dependency::Path {path: "../local".to_string(), optional: Some(true)}),
+        ]),
+        profile: Some(Profile {opt_level: 3, lto: true, range: (5, -15), weights: [0.5, 20.0]}),
+        edition: Edition::E2021,
+    }
+}
+
+#[test]
+fn toml_round_trip() {
+    let value = Manifest::deserialize_toml(MANIFEST).unwrap();
+    assert_eq!(value, manifest());
+    assert_eq!(Manifest::deserialize_ron(&value.serialize_ron()).unwrap(), value);
+    assert_eq!(Manifest::deserialize_json(&value.serialize_json()).unwrap(), value);
+}
+
+#[test]
+fn missing_options_are_none() {
+    let value = Manifest::deserialize_toml("edition = \"E2018\"\n[package]\nname = \"x\"\nversion = \"1\"\nauthors = []\nbin = []\ndependencies = {}\n");
+    // `bin` and `dependencies` landed in the package table, so they are missing from the root
+    assert!(value.unwrap_err().msg.contains("Key not found bin"));
+
+    let value = Manifest::deserialize_toml("edition = \"E2018\"\nbin = []\ndependencies = {}\n[package]\nname = \"x\"\nversion = \"1\"\nauthors = []\n").unwrap();
+    assert_eq!(value.package.description, None);
+    assert_eq!(value.profile, None);
+    assert_eq!(value.edition, Edition::E2018);
+}
+
+#[test]
+fn reports_errors() {
+    // from the parser
+    let err = Manifest::deserialize_toml("[package]\nname = 1\nname = 2").unwrap_err();
+    assert!(err.msg.contains("key name defined twice"));
+    // and from the values
+    let doc = MANIFEST.replace("opt-level = 0x3", "opt-level = 300");
+    assert!(Manifest::deserialize_toml(&doc).unwrap_err().msg.contains("out of range u8"));
+    let doc = MANIFEST.replace("lto = true", "lto = \"yes\"");
+    assert!(Manifest::deserialize_toml(&doc).unwrap_err().msg.contains("expected bool"));
+    let doc = MANIFEST.replace("\"E2021\"", "\"E2024\"");
+    assert!(Manifest::deserialize_toml(&doc).unwrap_err().msg.contains("Enum not defined E2024"));
+    let doc = MANIFEST.replace("range = [0b101, -15]", "range = [1]");
+    assert!(Manifest::deserialize_toml(&doc).unwrap_err().msg.contains("array of length 2"));
+}
@@ -4,7 +4,8 @@ use {
         code,
         code::{
             BinOpInfo, BlockType, CompiledCode, InstrSlot, InstrVisitor, LoadInfo, MemArg,
-            StoreInfo, UnOpInfo, UncompiledCode,
+            SimdLaneInfo, SimdMemInfo, SimdMemLaneInfo, SimdOpInfo, StoreInfo, UnOpInfo,
+            UncompiledCode,
         },
         decode::DecodeError,
         exec,
@@ -16,6 +17,7 @@ use {
         ref_::RefType,
         stack::StackSlot,
         store::Store,
+        v128::V128,
         val::{UnguardedVal, ValType},
     },
     std::{mem, ops::Deref},
@@ -28,6 +30,7 @@ pub(crate) struct Compiler {
     blocks: Vec<Block>,
     opds: Vec<Opd>,
     fixup_idxs: Vec<usize>,
+    checkpoints: Vec<Checkpoint>,
 }
 
 impl Compiler {
@@ -38,6 +41,7 @@ impl Compiler {
             blocks: Vec::new(),
             opds: Vec::new(),
             fixup_idxs: Vec::new(),
+            checkpoints: Vec::new(),
         }
     }
 
@@ -54,6 +58,7 @@ impl Compiler {
         self.blocks.clear();
         self.opds.clear();
         self.fixup_idxs.clear();
+        self.checkpoints.clear();
 
         let type_ = func.type_(store);
         let locals = &mut self.locals;
@@ -78,6 +83,7 @@ impl Compiler {
             blocks: &mut self.blocks,
             opds: &mut self.opds,
             fixup_idxs: &mut self.fixup_idxs,
+            checkpoints: &mut self.checkpoints,
             first_param_result_stack_idx: -(type_.call_frame_size() as isize),
             first_temp_stack_idx: local_count,
             max_stack_height: local_count,
@@ -97,11 +103,16 @@ impl Compiler {
                 .mem(0)
                 .map(|mem| mem.to_unguarded(store.id())),
         );
+        compile.emit_checkpoint();
 
         let mut decoder = Decoder::new(&code.expr);
         while !compile.blocks.is_empty() {
+            if let Some(checkpoint) = compile.checkpoints.last_mut() {
+                checkpoint.cost += 1;
+            }
             code::decode_instr(&mut decoder, &mut self.label_idxs, &mut compile).unwrap();
         }
+        compile.patch_checkpoint();
 
         for (result_idx, result_type) in type_.clone().results().iter().copied().enumerate().rev() {
             compile.emit(select_copy_stack(result_type));
@@ -138,6 +149,7 @@ struct Compile<'a> {
     blocks: &'a mut Vec<Block>,
     opds: &'a mut Vec<Opd>,
     fixup_idxs: &'a mut Vec<usize>,
+    checkpoints: &'a mut Vec<Checkpoint>,
     first_param_result_stack_idx: isize,
     first_temp_stack_idx: usize,
     max_stack_height: usize,
@@ -495,11 +507,23 @@ impl<'a> Compile<'a> {
             UnguardedVal::I64(val) => self.emit(val),
             UnguardedVal::F32(val) => self.emit(val),
             UnguardedVal::F64(val) => self.emit(val),
+            UnguardedVal::V128(val) => self.emit_v128(val),
             UnguardedVal::FuncRef(val) => self.emit(val),
             UnguardedVal::ExternRef(val) => self.emit(val),
         }
     }
 
+    /// Emits a [`V128`].
+    ///
+    /// A [`V128`] does not fit in a single instruction slot, so we spread it out over several
+    /// consecutive slots.
+    fn emit_v128(&mut self, val: V128) {
+        for chunk in val.to_le_bytes().chunks_exact(mem::size_of::<InstrSlot>()) {
+            self.code
+                .push(InstrSlot::from_ne_bytes(chunk.try_into().unwrap()));
+        }
+    }
+
     /// Emits the offset of the stack slot with the given index.
     fn emit_stack_offset(&mut self, stack_idx: isize) {
         self.emit(stack_idx * mem::size_of::<StackSlot>() as isize);
@@ -542,6 +566,66 @@ impl<'a> Compile<'a> {
         self.fixup_idxs.push(self.code.len());
         self.emit(instr_idx * mem::size_of::<InstrSlot>());
     }
+
+    /// Emits a checkpoint, if the engine requires them.
+    ///
+    /// A checkpoint consumes fuel and checks for interrupts. We emit one at the start of each
+    /// function and each loop, so that every path of execution passes through a checkpoint within
+    /// a bounded number of instructions. The cost of a checkpoint is the number of instructions
+    /// in the function or loop that are not nested within an inner loop. We don't know this number
+    /// until the end of the function or loop, so we emit a placeholder for now, which is patched
+    /// by [`Compile::patch_checkpoint`].
+    fn emit_checkpoint(&mut self) {
+        if !self.store.engine().config().needs_checkpoints() {
+            return;
+        }
+        self.emit(exec::checkpoint as ThreadedInstr);
+        let cost_idx = self.code.len();
+        self.emit(0usize);
+        self.checkpoints.push(Checkpoint { cost_idx, cost: 0 });
+    }
+
+    /// Patches the cost of the innermost checkpoint, if any.
+    fn patch_checkpoint(&mut self) {
+        let Some(checkpoint) = self.checkpoints.pop() else {
+            return;
+        };
+        let cost = if self.store.engine().config().has_consume_fuel() {
+            checkpoint.cost
+        } else {
+            0
+        };
+        self.code[checkpoint.cost_idx] = cost;
+    }
+
+    /// Emits a vector instruction, and then emits its inputs and pops them from the stack.
+    fn emit_simd_instr(&mut self, info: SimdOpInfo) {
+        let input_count = info.input_types.len();
+
+        // Vector instructions have only one variant, which reads all its operands from the stack,
+        // so we need to ensure that all operands are neither immediate nor register operands.
+        for opd_depth in 0..input_count {
+            self.ensure_opd_not_imm(opd_depth);
+            self.ensure_opd_not_reg(opd_depth);
+        }
+
+        // Emit the instruction.
+        self.emit(info.instr);
+
+        // Emit the inputs and pop them from the stack.
+        for _ in 0..input_count {
+            self.emit_and_pop_opd();
+        }
+    }
+
+    /// Pushes the output of a vector instruction onto the stack, if it has one, and emits its stack
+    /// offset.
+    fn emit_simd_output(&mut self, info: SimdOpInfo) {
+        if let Some(output_type) = info.output_type {
+            self.push_opd(output_type);
+            self.emit_stack_offset(self.opd_stack_idx(0));
+        }
+    }
 }
 
 impl<'a> InstrVisitor for Compile<'a> {
@@ -612,6 +696,7 @@ impl<'a> InstrVisitor for Compile<'a> {
         }
 
         self.push_block(BlockKind::Loop, type_);
+        self.emit_checkpoint();
 
         Ok(())
     }
@@ -762,6 +847,9 @@ impl<'a> InstrVisitor for Compile<'a> {
 
         // Pop the block from the stack.
         let block = self.pop_block();
+        if let BlockKind::Loop = block.kind {
+            self.patch_checkpoint();
+        }
 
         // Push the outputs of the block onto the stack.
         for result_type in block.type_.results().iter().copied() {
@@ -1151,6 +1239,34 @@ impl<'a> InstrVisitor for Compile<'a> {
 
         let type_ = type_.unwrap_or_else(|| self.opd(1).type_);
 
+        // A [`V128`] does not fit in a register, so for vector types, we use a variant of `select`
+        // that reads all its operands from the stack, and writes its output to the stack.
+        if type_.is_vec() {
+            for opd_depth in 0..3 {
+                self.ensure_opd_not_imm(opd_depth);
+                self.ensure_opd_not_reg(opd_depth);
+            }
+
+            // Emit the instruction.
+            self.emit(select_select(
+                type_,
+                self.opd(2).kind(),
+                self.opd(1).kind(),
+                self.opd(0).kind(),
+            ));
+
+            // Emit the inputs and pop them from the stack.
+            for _ in 0..3 {
+                self.emit_and_pop_opd();
+            }
+
+            // Push the output onto the stack and emit its stack offset.
+            self.push_opd(type_);
+            self.emit_stack_offset(self.opd_stack_idx(0));
+
+            return Ok(());
+        }
+
         // The `select` instruction does not have any _{sri}{sri}i variants.
         //
         // For instance, the following sequence of instructions:
@@ -2049,6 +2165,137 @@ impl<'a> InstrVisitor for Compile<'a> {
 
         Ok(())
     }
+
+    // Vector instructions
+
+    /// Compiles a `v128.const` instruction.
+    fn visit_v128_const(&mut self, val: V128) -> Result<(), DecodeError> {
+        // Skip this instruction if it is unreachable.
+        if self.block(0).is_unreachable {
+            return Ok(());
+        }
+
+        // Unlike other constants, a [`V128`] does not fit in a single instruction slot, so we
+        // don't represent it as an immediate operand. Instead, we copy it to the stack right away.
+        //
+        // The cast to [`ThreadedInstr`] is necessary here, because otherwise we would emit a
+        // function item instead of a function pointer.
+        self.emit(exec::copy_imm_to_stack_v128 as ThreadedInstr);
+
+        // Emit the value.
+        self.emit_v128(val);
+
+        // Push the output onto the stack and emit its stack offset.
+        self.push_opd(ValType::V128);
+        self.emit_stack_offset(self.opd_stack_idx(0));
+
+        Ok(())
+    }
+
+    /// Compiles an `i8x16.shuffle` instruction.
+    fn visit_i8x16_shuffle(&mut self, lane_idxs: [u8; 16]) -> Result<(), DecodeError> {
+        // Skip this instruction if it is unreachable.
+        if self.block(0).is_unreachable {
+            return Ok(());
+        }
+
+        let info = SimdOpInfo {
+            _name: "i8x16_shuffle",
+            input_types: &[ValType::V128, ValType::V128],
+            output_type: Some(ValType::V128),
+            instr: exec::i8x16_shuffle,
+        };
+
+        // Emit the instruction and its inputs.
+        self.emit_simd_instr(info);
+
+        // Emit the lane indices.
+        self.emit_v128(V128::from_le_bytes(lane_idxs));
+
+        // Push the output onto the stack and emit its stack offset.
+        self.emit_simd_output(info);
+
+        Ok(())
+    }
+
+    /// Compiles a vector operation.
+    fn visit_simd_op(&mut self, info: SimdOpInfo) -> Result<(), DecodeError> {
+        // Skip this instruction if it is unreachable.
+        if self.block(0).is_unreachable {
+            return Ok(());
+        }
+
+        // Emit the instruction and its inputs.
+        self.emit_simd_instr(info);
+
+        // Push the output onto the stack and emit its stack offset.
+        self.emit_simd_output(info);
+
+        Ok(())
+    }
+
+    /// Compiles a vector operation on a single lane.
+    fn visit_simd_lane_op(&mut self, lane_idx: u8, info: SimdLaneInfo) -> Result<(), DecodeError> {
+        // Skip this instruction if it is unreachable.
+        if self.block(0).is_unreachable {
+            return Ok(());
+        }
+
+        // Emit the instruction and its inputs.
+        self.emit_simd_instr(info.op);
+
+        // Emit the lane index.
+        self.emit(lane_idx);
+
+        // Push the output onto the stack and emit its stack offset.
+        self.emit_simd_output(info.op);
+
+        Ok(())
+    }
+
+    /// Compiles a vector load or store instruction.
+    fn visit_simd_mem_op(&mut self, arg: MemArg, info: SimdMemInfo) -> Result<(), DecodeError> {
+        // Skip this instruction if it is unreachable.
+        if self.block(0).is_unreachable {
+            return Ok(());
+        }
+
+        // Emit the instruction and its inputs.
+        self.emit_simd_instr(info.op);
+
+        // Emit the static offset.
+        self.emit(arg.offset);
+
+        // Push the output onto the stack and emit its stack offset.
+        self.emit_simd_output(info.op);
+
+        Ok(())
+    }
+
+    /// Compiles a vector load or store instruction on a single lane.
+    fn visit_simd_mem_lane_op(
+        &mut self,
+        arg: MemArg,
+        lane_idx: u8,
+        info: SimdMemLaneInfo,
+    ) -> Result<(), DecodeError> {
+        // Skip this instruction if it is unreachable.
+        if self.block(0).is_unreachable {
+            return Ok(());
+        }
+
+        // Emit the instruction and its inputs.
+        self.emit_simd_instr(info.op);
+
+        // Emit the static offset and the lane index.
+        self.emit(arg.offset);
+        self.emit(lane_idx);
+
+        // Push the output onto the stack and emit its stack offset.
+        self.emit_simd_output(info.op);
+
+        Ok(())
+    }
 }
 
 /// A local on the stack.
@@ -2105,6 +2352,15 @@ enum BlockKind {
     Loop,
 }
 
+/// A checkpoint whose cost has not yet been patched.
+#[derive(Clone, Copy, Debug)]
+struct Checkpoint {
+    // The index of the cost of the checkpoint in the code.
+    cost_idx: usize,
+    // The number of instructions attributed to the checkpoint so far.
+    cost: usize,
+}
+
 /// The type of the label of a [`Block`].
 ///
 /// This is either the type of the inputs of the block, or the type of the outputs of the block,
@@ -2343,6 +2599,9 @@ fn select_select(
         (ValType::F64, OpdKind::Imm, OpdKind::Reg, OpdKind::Reg) => exec::select_f64_irr,
         (ValType::F64, OpdKind::Reg, OpdKind::Imm, OpdKind::Reg) => exec::select_f64_rir,
 
+        (ValType::V128, OpdKind::Stack, OpdKind::Stack, OpdKind::Stack) => exec::select_v128_sss,
+        (ValType::V128, _, _, _) => panic!("no suitable instruction found"),
+
         (ValType::FuncRef, OpdKind::Stack, OpdKind::Stack, OpdKind::Stack) => {
             exec::select_func_ref_sss
         }
@@ -2434,6 +2693,7 @@ fn select_global_get(type_: ValType) -> ThreadedInstr {
         ValType::I64 => exec::global_get_i64,
         ValType::F32 => exec::global_get_f32,
         ValType::F64 => exec::global_get_f64,
+        ValType::V128 => exec::global_get_v128,
         ValType::FuncRef => exec::global_get_func_ref,
         ValType::ExternRef => exec::global_get_extern_ref,
     }
@@ -2453,6 +2713,8 @@ fn select_global_set(type_: ValType, kind: OpdKind) -> ThreadedInstr {
         (ValType::F64, OpdKind::Stack) => exec::global_set_f64_s,
         (ValType::F64, OpdKind::Reg) => exec::global_set_f64_r,
         (ValType::F64, OpdKind::Imm) => exec::global_set_f64_i,
+        (ValType::V128, OpdKind::Stack) => exec::global_set_v128_s,
+        (ValType::V128, OpdKind::Reg | OpdKind::Imm) => panic!("no suitable instruction found"),
         (ValType::FuncRef, OpdKind::Stack) => exec::global_set_func_ref_s,
         (ValType::FuncRef, OpdKind::Reg) => exec::global_set_func_ref_r,
         (ValType::FuncRef, OpdKind::Imm) => exec::global_set_func_ref_i,
@@ -2583,6 +2845,7 @@ fn select_copy_imm_to_stack(type_: ValType) -> ThreadedInstr {
         ValType::I64 => exec::copy_imm_to_stack_i64,
         ValType::F32 => exec::copy_imm_to_stack_f32,
         ValType::F64 => exec::copy_imm_to_stack_f64,
+        ValType::V128 => exec::copy_imm_to_stack_v128,
         ValType::FuncRef => exec::copy_imm_to_stack_func_ref,
         ValType::ExternRef => exec::copy_imm_to_stack_extern_ref,
     }
@@ -2594,6 +2857,7 @@ fn select_copy_stack(type_: ValType) -> ThreadedInstr {
         ValType::I64 => exec::copy_stack_i64,
         ValType::F32 => exec::copy_stack_f32,
         ValType::F64 => exec::copy_stack_f64,
+        ValType::V128 => exec::copy_stack_v128,
         ValType::FuncRef => exec::copy_stack_func_ref,
         ValType::ExternRef => exec::copy_stack_extern_ref,
     }
@@ -2605,6 +2869,7 @@ fn select_copy_reg_to_stack(type_: ValType) -> ThreadedInstr {
         ValType::I64 => exec::copy_reg_to_stack_i64,
         ValType::F32 => exec::copy_reg_to_stack_f32,
         ValType::F64 => exec::copy_reg_to_stack_f64,
+        ValType::V128 => panic!("no suitable instruction found"),
         ValType::FuncRef => exec::copy_reg_to_stack_func_ref,
         ValType::ExternRef => exec::copy_reg_to_stack_extern_ref,
     }
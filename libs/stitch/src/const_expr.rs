@@ -6,6 +6,7 @@ use crate::{
     module::ModuleBuilder,
     ref_::{Ref, RefType},
     store::Store,
+    v128::V128,
     val::{Val, ValType},
 };
 
@@ -34,6 +35,7 @@ impl ConstExpr {
             ConstInstr::I64Const(_) => Ok(ValType::I64),
             ConstInstr::F32Const(_) => Ok(ValType::F32),
             ConstInstr::F64Const(_) => Ok(ValType::F64),
+            ConstInstr::V128Const(_) => Ok(ValType::V128),
             ConstInstr::RefNull(type_) => Ok(type_.into()),
             ConstInstr::RefFunc(func_idx) => {
                 module.func(func_idx)?;
@@ -55,6 +57,7 @@ impl ConstExpr {
             ConstInstr::I64Const(val) => val.into(),
             ConstInstr::F32Const(val) => val.into(),
             ConstInstr::F64Const(val) => val.into(),
+            ConstInstr::V128Const(val) => val.into(),
             ConstInstr::RefNull(ref_ty) => Ref::null(ref_ty).into(),
             ConstInstr::RefFunc(func_idx) => FuncRef::new(context.func(func_idx).unwrap()).into(),
             ConstInstr::GlobalGet(global_idx) => {
@@ -85,6 +88,7 @@ enum ConstInstr {
     I64Const(i64),
     F32Const(f32),
     F64Const(f64),
+    V128Const(V128),
     RefNull(RefType),
     RefFunc(u32),
     GlobalGet(u32),
@@ -100,6 +104,10 @@ impl Decode for ConstInstr {
             0x44 => Ok(Self::F64Const(decoder.decode()?)),
             0xD0 => Ok(Self::RefNull(decoder.decode()?)),
             0xD2 => Ok(Self::RefFunc(decoder.decode()?)),
+            0xFD => match decoder.decode::<u32>()? {
+                12 => Ok(Self::V128Const(decoder.decode()?)),
+                _ => Err(DecodeError::new("illegal const opcode")),
+            },
             _ => Err(DecodeError::new("illegal const opcode")),
         }
     }
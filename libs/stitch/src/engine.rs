@@ -21,14 +21,25 @@ pub struct Engine {
 impl Engine {
     /// Creates a new [`Engine`].
     pub fn new() -> Engine {
+        Engine::with_config(EngineConfig::new())
+    }
+
+    /// Creates a new [`Engine`] with the given [`EngineConfig`].
+    pub fn with_config(config: EngineConfig) -> Engine {
         Engine {
             inner: Arc::new(EngineInner {
+                config,
                 validators: Mutex::new(Pool::new()),
                 compilers: Mutex::new(Pool::new()),
             }),
         }
     }
 
+    /// Returns the [`EngineConfig`] of this [`Engine`].
+    pub fn config(&self) -> &EngineConfig {
+        &self.inner.config
+    }
+
     pub(crate) fn validate(
         &self,
         type_: &FuncType,
@@ -61,8 +72,55 @@ impl Default for Engine {
     }
 }
 
+/// Configuration for an [`Engine`].
+///
+/// Both fuel metering and interruption require the compiled code to check in with the [`Store`]
+/// at function entries and loop headers. Since this has a cost, both are disabled by default.
+#[derive(Clone, Debug, Default)]
+pub struct EngineConfig {
+    consume_fuel: bool,
+    interruptible: bool,
+}
+
+impl EngineConfig {
+    /// Creates a new [`EngineConfig`] with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether executing Wasm code consumes fuel from the [`Store`].
+    ///
+    /// See [`Store::set_fuel`].
+    pub fn consume_fuel(mut self, consume_fuel: bool) -> Self {
+        self.consume_fuel = consume_fuel;
+        self
+    }
+
+    /// Sets whether executing Wasm code can be interrupted.
+    ///
+    /// See [`Store::interrupt_handle`].
+    pub fn interruptible(mut self, interruptible: bool) -> Self {
+        self.interruptible = interruptible;
+        self
+    }
+
+    pub(crate) fn has_consume_fuel(&self) -> bool {
+        self.consume_fuel
+    }
+
+    pub(crate) fn has_interruptible(&self) -> bool {
+        self.interruptible
+    }
+
+    /// Returns `true` if compiled code needs to contain checkpoints.
+    pub(crate) fn needs_checkpoints(&self) -> bool {
+        self.consume_fuel || self.interruptible
+    }
+}
+
 #[derive(Debug)]
 struct EngineInner {
+    config: EngineConfig,
     validators: Mutex<Pool<Validator>>,
     compilers: Mutex<Pool<Compiler>>,
 }
@@ -16,6 +16,7 @@ pub enum Error {
     Memory(MemError),
     Global(GlobalError),
     Trap(Trap),
+    Exit(i32),
 }
 
 impl error::Error for Error {
@@ -28,6 +29,7 @@ impl error::Error for Error {
             Error::Memory(error) => Some(error),
             Error::Global(error) => Some(error),
             Error::Trap(error) => Some(error),
+            Error::Exit(_) => None,
         }
     }
 }
@@ -42,6 +44,7 @@ impl fmt::Display for Error {
             Error::Memory(_) => write!(f, "memory error"),
             Error::Global(_) => write!(f, "global error"),
             Error::Trap(_) => write!(f, "trap"),
+            Error::Exit(code) => write!(f, "exited with code {}", code),
         }
     }
 }
@@ -17,6 +17,7 @@ use {
         store::{Handle, Store, UnguardedInternedFuncType},
         table::UnguardedTable,
         trap::Trap,
+        v128::V128,
         val::{UnguardedVal, Val},
     },
     std::{hint, mem, ptr},
@@ -248,7 +249,8 @@ pub(crate) fn exec(
                         return Err(trap)?;
                     }
                     ControlFlow::Error => {
-                        stack = context.stack.take().unwrap();
+                        // A host function that failed has already released the stack.
+                        stack = context.stack.take().unwrap_or_else(Stack::lock);
 
                         // Reset the stack to the start of the call frame.
                         stack.set_ptr(ptr);
@@ -1306,6 +1308,31 @@ select!(
     UnguardedExternRef
 );
 
+threaded_instr!(select_v128_sss(
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    cx: Cx,
+) -> ControlFlowBits {
+    // Read operands
+    let (cond, ip): (u32, _) = read_stack(ip, sp);
+    let (x1, ip): (V128, _) = read_stack(ip, sp);
+    let (x0, ip): (V128, _) = read_stack(ip, sp);
+
+    // Perform operation
+    let y = if cond != 0 { x0 } else { x1 };
+
+    // Write result
+    let ip = write_stack(ip, sp, y);
+
+    // Execute next instruction
+    next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+});
+
 // Variable instructions
 
 macro_rules! global_get {
@@ -1343,6 +1370,7 @@ global_get!(global_get_i32, i32);
 global_get!(global_get_i64, i64);
 global_get!(global_get_f32, f32);
 global_get!(global_get_f64, f64);
+global_get!(global_get_v128, V128);
 global_get!(global_get_func_ref, UnguardedFuncRef);
 global_get!(global_get_extern_ref, UnguardedExternRef);
 
@@ -1442,6 +1470,31 @@ global_set!(
     UnguardedExternRef
 );
 
+threaded_instr!(global_set_v128_s(
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    cx: Cx,
+) -> ControlFlowBits {
+    // Read operands
+    let (val, ip) = read_stack(ip, sp);
+    let (mut global, ip): (UnguardedGlobal, _) = read_imm(ip);
+
+    // Perform operation
+    global
+        .as_mut()
+        .downcast_mut::<V128>()
+        .unwrap_unchecked()
+        .set(val);
+
+    // Execute next instruction
+    next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+});
+
 // Table instructions
 
 macro_rules! table_get {
@@ -3817,6 +3870,1215 @@ un_op!(
     <u64 as Trunc<f64>>::trunc_sat
 );
 
+// Vector instructions
+//
+// Vector instructions always read their operands from the stack, and write their result to the
+// stack. This is because a [`V128`] does not fit in any of our virtual registers.
+
+macro_rules! simd_un_op {
+    ($simd_un_op:ident, $f:expr) => {
+        threaded_instr!($simd_un_op(
+            ip: Ip,
+            sp: Sp,
+            md: Md,
+            ms: Ms,
+            ix: Ix,
+            sx: Sx,
+            dx: Dx,
+            cx: Cx,
+        ) -> ControlFlowBits {
+            // Read operands
+            let (x, ip) = read_stack(ip, sp);
+
+            // Perform operation
+            let y = r#try!($f(x));
+
+            // Write result
+            let ip = write_stack(ip, sp, y);
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        });
+    };
+}
+
+macro_rules! simd_bin_op {
+    ($simd_bin_op:ident, $f:expr) => {
+        threaded_instr!($simd_bin_op(
+            ip: Ip,
+            sp: Sp,
+            md: Md,
+            ms: Ms,
+            ix: Ix,
+            sx: Sx,
+            dx: Dx,
+            cx: Cx,
+        ) -> ControlFlowBits {
+            // Read operands
+            let (x1, ip) = read_stack(ip, sp);
+            let (x0, ip) = read_stack(ip, sp);
+
+            // Perform operation
+            let y = r#try!($f(x0, x1));
+
+            // Write result
+            let ip = write_stack(ip, sp, y);
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        });
+    };
+}
+
+macro_rules! simd_ternary_op {
+    ($simd_ternary_op:ident, $f:expr) => {
+        threaded_instr!($simd_ternary_op(
+            ip: Ip,
+            sp: Sp,
+            md: Md,
+            ms: Ms,
+            ix: Ix,
+            sx: Sx,
+            dx: Dx,
+            cx: Cx,
+        ) -> ControlFlowBits {
+            // Read operands
+            let (x2, ip) = read_stack(ip, sp);
+            let (x1, ip) = read_stack(ip, sp);
+            let (x0, ip) = read_stack(ip, sp);
+
+            // Perform operation
+            let y = r#try!($f(x0, x1, x2));
+
+            // Write result
+            let ip = write_stack(ip, sp, y);
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        });
+    };
+}
+
+macro_rules! simd_extract_lane {
+    ($simd_extract_lane:ident, $f:expr) => {
+        threaded_instr!($simd_extract_lane(
+            ip: Ip,
+            sp: Sp,
+            md: Md,
+            ms: Ms,
+            ix: Ix,
+            sx: Sx,
+            dx: Dx,
+            cx: Cx,
+        ) -> ControlFlowBits {
+            // Read operands
+            let (x, ip) = read_stack(ip, sp);
+            let (lane_idx, ip): (u8, _) = read_imm(ip);
+
+            // Perform operation
+            let y = r#try!($f(x, lane_idx));
+
+            // Write result
+            let ip = write_stack(ip, sp, y);
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        });
+    };
+}
+
+macro_rules! simd_replace_lane {
+    ($simd_replace_lane:ident, $f:expr) => {
+        threaded_instr!($simd_replace_lane(
+            ip: Ip,
+            sp: Sp,
+            md: Md,
+            ms: Ms,
+            ix: Ix,
+            sx: Sx,
+            dx: Dx,
+            cx: Cx,
+        ) -> ControlFlowBits {
+            // Read operands
+            let (x1, ip) = read_stack(ip, sp);
+            let (x0, ip) = read_stack(ip, sp);
+            let (lane_idx, ip): (u8, _) = read_imm(ip);
+
+            // Perform operation
+            let y = r#try!($f(x0, lane_idx, x1));
+
+            // Write result
+            let ip = write_stack(ip, sp, y);
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        });
+    };
+}
+
+macro_rules! simd_load {
+    ($simd_load:ident, $T:ty, $f:expr) => {
+        threaded_instr!($simd_load(
+            ip: Ip,
+            sp: Sp,
+            md: Md,
+            ms: Ms,
+            ix: Ix,
+            sx: Sx,
+            dx: Dx,
+            cx: Cx,
+        ) -> ControlFlowBits {
+            // Read operands
+            let (dyn_offset, ip): (u32, _) = read_stack(ip, sp);
+            let (static_offset, ip): (u32, _) = read_imm(ip);
+
+            // Perform operation
+            let offset = dyn_offset as u64 + static_offset as u64;
+            if offset + mem::size_of::<$T>() as u64 > ms as u64 {
+                return ControlFlow::Trap(Trap::MemAccessOutOfBounds).to_bits();
+            }
+            let mut bytes = [0u8; mem::size_of::<$T>()];
+            ptr::copy_nonoverlapping(md.add(offset as usize), bytes.as_mut_ptr(), bytes.len());
+            let y = r#try!($f(<$T>::from_le_bytes(bytes)));
+
+            // Write result
+            let ip = write_stack(ip, sp, y);
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        });
+    };
+}
+
+macro_rules! simd_load_lane {
+    ($simd_load_lane:ident, $T:ty, $N:literal) => {
+        threaded_instr!($simd_load_lane(
+            ip: Ip,
+            sp: Sp,
+            md: Md,
+            ms: Ms,
+            ix: Ix,
+            sx: Sx,
+            dx: Dx,
+            cx: Cx,
+        ) -> ControlFlowBits {
+            // Read operands
+            let (x, ip) = read_stack(ip, sp);
+            let (dyn_offset, ip): (u32, _) = read_stack(ip, sp);
+            let (static_offset, ip): (u32, _) = read_imm(ip);
+            let (lane_idx, ip): (u8, _) = read_imm(ip);
+
+            // Perform operation
+            let offset = dyn_offset as u64 + static_offset as u64;
+            if offset + mem::size_of::<$T>() as u64 > ms as u64 {
+                return ControlFlow::Trap(Trap::MemAccessOutOfBounds).to_bits();
+            }
+            let mut bytes = [0u8; mem::size_of::<$T>()];
+            ptr::copy_nonoverlapping(md.add(offset as usize), bytes.as_mut_ptr(), bytes.len());
+            let y = r#try!(v128_replace_lane::<$T, $N>(
+                x,
+                lane_idx,
+                <$T>::from_le_bytes(bytes)
+            ));
+
+            // Write result
+            let ip = write_stack(ip, sp, y);
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        });
+    };
+}
+
+macro_rules! simd_store_lane {
+    ($simd_store_lane:ident, $T:ty, $N:literal) => {
+        threaded_instr!($simd_store_lane(
+            ip: Ip,
+            sp: Sp,
+            md: Md,
+            ms: Ms,
+            ix: Ix,
+            sx: Sx,
+            dx: Dx,
+            cx: Cx,
+        ) -> ControlFlowBits {
+            // Read operands
+            let (x, ip) = read_stack(ip, sp);
+            let (dyn_offset, ip): (u32, _) = read_stack(ip, sp);
+            let (static_offset, ip): (u32, _) = read_imm(ip);
+            let (lane_idx, ip): (u8, _) = read_imm(ip);
+
+            // Perform operation
+            let offset = dyn_offset as u64 + static_offset as u64;
+            if offset + mem::size_of::<$T>() as u64 > ms as u64 {
+                return ControlFlow::Trap(Trap::MemAccessOutOfBounds).to_bits();
+            }
+            let bytes = r#try!(v128_extract_lane::<$T, $N>(x, lane_idx)).to_le_bytes();
+            ptr::copy_nonoverlapping(bytes.as_ptr(), md.add(offset as usize), bytes.len());
+
+            // Execute next instruction
+            next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+        });
+    };
+}
+
+threaded_instr!(v128_store(
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    cx: Cx,
+) -> ControlFlowBits {
+    // Read operands
+    let (x, ip): (V128, _) = read_stack(ip, sp);
+    let (dyn_offset, ip): (u32, _) = read_stack(ip, sp);
+    let (static_offset, ip): (u32, _) = read_imm(ip);
+
+    // Perform operation
+    let offset = dyn_offset as u64 + static_offset as u64;
+    if offset + mem::size_of::<V128>() as u64 > ms as u64 {
+        return ControlFlow::Trap(Trap::MemAccessOutOfBounds).to_bits();
+    }
+    let bytes = x.to_le_bytes();
+    ptr::copy_nonoverlapping(bytes.as_ptr(), md.add(offset as usize), bytes.len());
+
+    // Execute next instruction
+    next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+});
+
+threaded_instr!(i8x16_shuffle(
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    cx: Cx,
+) -> ControlFlowBits {
+    // Read operands
+    let (x1, ip) = read_stack(ip, sp);
+    let (x0, ip) = read_stack(ip, sp);
+    let (lane_idxs, ip) = read_imm_v128(ip);
+
+    // Perform operation
+    let y = r#try!(v128_shuffle(x0, x1, lane_idxs));
+
+    // Write result
+    let ip = write_stack(ip, sp, y);
+
+    // Execute next instruction
+    next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+});
+
+simd_load!(v128_load, u128, |x: u128| Ok(V128::from_bits(x)));
+simd_load!(v128_load8x8_s, u64, |x: u64| {
+    v128_convert_low::<i8, i16, 16, 8>(V128::from_bits(x.into()), |x| Ok(x.into()))
+});
+simd_load!(v128_load8x8_u, u64, |x: u64| {
+    v128_convert_low::<u8, u16, 16, 8>(V128::from_bits(x.into()), |x| Ok(x.into()))
+});
+simd_load!(v128_load16x4_s, u64, |x: u64| {
+    v128_convert_low::<i16, i32, 8, 4>(V128::from_bits(x.into()), |x| Ok(x.into()))
+});
+simd_load!(v128_load16x4_u, u64, |x: u64| {
+    v128_convert_low::<u16, u32, 8, 4>(V128::from_bits(x.into()), |x| Ok(x.into()))
+});
+simd_load!(v128_load32x2_s, u64, |x: u64| {
+    v128_convert_low::<i32, i64, 4, 2>(V128::from_bits(x.into()), |x| Ok(x.into()))
+});
+simd_load!(v128_load32x2_u, u64, |x: u64| {
+    v128_convert_low::<u32, u64, 4, 2>(V128::from_bits(x.into()), |x| Ok(x.into()))
+});
+simd_load!(v128_load8_splat, u8, v128_splat::<u8, 16>);
+simd_load!(v128_load16_splat, u16, v128_splat::<u16, 8>);
+simd_load!(v128_load32_splat, u32, v128_splat::<u32, 4>);
+simd_load!(v128_load64_splat, u64, v128_splat::<u64, 2>);
+simd_bin_op!(i8x16_swizzle, v128_swizzle);
+simd_un_op!(i8x16_splat, |x: u32| v128_splat::<u8, 16>(x as u8));
+simd_un_op!(i16x8_splat, |x: u32| v128_splat::<u16, 8>(x as u16));
+simd_un_op!(i32x4_splat, v128_splat::<u32, 4>);
+simd_un_op!(i64x2_splat, v128_splat::<u64, 2>);
+simd_un_op!(f32x4_splat, v128_splat::<f32, 4>);
+simd_un_op!(f64x2_splat, v128_splat::<f64, 2>);
+simd_extract_lane!(i8x16_extract_lane_s, |x: V128, lane_idx| Ok(
+    v128_extract_lane::<i8, 16>(x, lane_idx)? as i32
+));
+simd_extract_lane!(i8x16_extract_lane_u, |x: V128, lane_idx| Ok(
+    v128_extract_lane::<u8, 16>(x, lane_idx)? as u32
+));
+simd_replace_lane!(i8x16_replace_lane, |x0: V128, lane_idx, x1: u32| {
+    v128_replace_lane::<u8, 16>(x0, lane_idx, x1 as u8)
+});
+simd_extract_lane!(i16x8_extract_lane_s, |x: V128, lane_idx| Ok(
+    v128_extract_lane::<i16, 8>(x, lane_idx)? as i32
+));
+simd_extract_lane!(i16x8_extract_lane_u, |x: V128, lane_idx| Ok(
+    v128_extract_lane::<u16, 8>(x, lane_idx)? as u32
+));
+simd_replace_lane!(i16x8_replace_lane, |x0: V128, lane_idx, x1: u32| {
+    v128_replace_lane::<u16, 8>(x0, lane_idx, x1 as u16)
+});
+simd_extract_lane!(i32x4_extract_lane, v128_extract_lane::<u32, 4>);
+simd_replace_lane!(i32x4_replace_lane, v128_replace_lane::<u32, 4>);
+simd_extract_lane!(i64x2_extract_lane, v128_extract_lane::<u64, 2>);
+simd_replace_lane!(i64x2_replace_lane, v128_replace_lane::<u64, 2>);
+simd_extract_lane!(f32x4_extract_lane, v128_extract_lane::<f32, 4>);
+simd_replace_lane!(f32x4_replace_lane, v128_replace_lane::<f32, 4>);
+simd_extract_lane!(f64x2_extract_lane, v128_extract_lane::<f64, 2>);
+simd_replace_lane!(f64x2_replace_lane, v128_replace_lane::<f64, 2>);
+simd_bin_op!(i8x16_eq, |x0: V128, x1: V128| {
+    v128_bin_op::<i8, i8, 16>(x0, x1, |x0, x1| lane_mask(x0 == x1))
+});
+simd_bin_op!(i8x16_ne, |x0: V128, x1: V128| {
+    v128_bin_op::<i8, i8, 16>(x0, x1, |x0, x1| lane_mask(x0 != x1))
+});
+simd_bin_op!(i8x16_lt_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i8, i8, 16>(x0, x1, |x0, x1| lane_mask(x0 < x1))
+});
+simd_bin_op!(i8x16_lt_u, |x0: V128, x1: V128| {
+    v128_bin_op::<u8, i8, 16>(x0, x1, |x0, x1| lane_mask(x0 < x1))
+});
+simd_bin_op!(i8x16_gt_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i8, i8, 16>(x0, x1, |x0, x1| lane_mask(x0 > x1))
+});
+simd_bin_op!(i8x16_gt_u, |x0: V128, x1: V128| {
+    v128_bin_op::<u8, i8, 16>(x0, x1, |x0, x1| lane_mask(x0 > x1))
+});
+simd_bin_op!(i8x16_le_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i8, i8, 16>(x0, x1, |x0, x1| lane_mask(x0 <= x1))
+});
+simd_bin_op!(i8x16_le_u, |x0: V128, x1: V128| {
+    v128_bin_op::<u8, i8, 16>(x0, x1, |x0, x1| lane_mask(x0 <= x1))
+});
+simd_bin_op!(i8x16_ge_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i8, i8, 16>(x0, x1, |x0, x1| lane_mask(x0 >= x1))
+});
+simd_bin_op!(i8x16_ge_u, |x0: V128, x1: V128| {
+    v128_bin_op::<u8, i8, 16>(x0, x1, |x0, x1| lane_mask(x0 >= x1))
+});
+simd_bin_op!(i16x8_eq, |x0: V128, x1: V128| {
+    v128_bin_op::<i16, i16, 8>(x0, x1, |x0, x1| lane_mask(x0 == x1))
+});
+simd_bin_op!(i16x8_ne, |x0: V128, x1: V128| {
+    v128_bin_op::<i16, i16, 8>(x0, x1, |x0, x1| lane_mask(x0 != x1))
+});
+simd_bin_op!(i16x8_lt_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i16, i16, 8>(x0, x1, |x0, x1| lane_mask(x0 < x1))
+});
+simd_bin_op!(i16x8_lt_u, |x0: V128, x1: V128| {
+    v128_bin_op::<u16, i16, 8>(x0, x1, |x0, x1| lane_mask(x0 < x1))
+});
+simd_bin_op!(i16x8_gt_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i16, i16, 8>(x0, x1, |x0, x1| lane_mask(x0 > x1))
+});
+simd_bin_op!(i16x8_gt_u, |x0: V128, x1: V128| {
+    v128_bin_op::<u16, i16, 8>(x0, x1, |x0, x1| lane_mask(x0 > x1))
+});
+simd_bin_op!(i16x8_le_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i16, i16, 8>(x0, x1, |x0, x1| lane_mask(x0 <= x1))
+});
+simd_bin_op!(i16x8_le_u, |x0: V128, x1: V128| {
+    v128_bin_op::<u16, i16, 8>(x0, x1, |x0, x1| lane_mask(x0 <= x1))
+});
+simd_bin_op!(i16x8_ge_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i16, i16, 8>(x0, x1, |x0, x1| lane_mask(x0 >= x1))
+});
+simd_bin_op!(i16x8_ge_u, |x0: V128, x1: V128| {
+    v128_bin_op::<u16, i16, 8>(x0, x1, |x0, x1| lane_mask(x0 >= x1))
+});
+simd_bin_op!(i32x4_eq, |x0: V128, x1: V128| {
+    v128_bin_op::<i32, i32, 4>(x0, x1, |x0, x1| lane_mask(x0 == x1))
+});
+simd_bin_op!(i32x4_ne, |x0: V128, x1: V128| {
+    v128_bin_op::<i32, i32, 4>(x0, x1, |x0, x1| lane_mask(x0 != x1))
+});
+simd_bin_op!(i32x4_lt_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i32, i32, 4>(x0, x1, |x0, x1| lane_mask(x0 < x1))
+});
+simd_bin_op!(i32x4_lt_u, |x0: V128, x1: V128| {
+    v128_bin_op::<u32, i32, 4>(x0, x1, |x0, x1| lane_mask(x0 < x1))
+});
+simd_bin_op!(i32x4_gt_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i32, i32, 4>(x0, x1, |x0, x1| lane_mask(x0 > x1))
+});
+simd_bin_op!(i32x4_gt_u, |x0: V128, x1: V128| {
+    v128_bin_op::<u32, i32, 4>(x0, x1, |x0, x1| lane_mask(x0 > x1))
+});
+simd_bin_op!(i32x4_le_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i32, i32, 4>(x0, x1, |x0, x1| lane_mask(x0 <= x1))
+});
+simd_bin_op!(i32x4_le_u, |x0: V128, x1: V128| {
+    v128_bin_op::<u32, i32, 4>(x0, x1, |x0, x1| lane_mask(x0 <= x1))
+});
+simd_bin_op!(i32x4_ge_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i32, i32, 4>(x0, x1, |x0, x1| lane_mask(x0 >= x1))
+});
+simd_bin_op!(i32x4_ge_u, |x0: V128, x1: V128| {
+    v128_bin_op::<u32, i32, 4>(x0, x1, |x0, x1| lane_mask(x0 >= x1))
+});
+simd_bin_op!(f32x4_eq, |x0: V128, x1: V128| {
+    v128_bin_op::<f32, i32, 4>(x0, x1, |x0, x1| lane_mask(x0 == x1))
+});
+simd_bin_op!(f32x4_ne, |x0: V128, x1: V128| {
+    v128_bin_op::<f32, i32, 4>(x0, x1, |x0, x1| lane_mask(x0 != x1))
+});
+simd_bin_op!(f32x4_lt, |x0: V128, x1: V128| {
+    v128_bin_op::<f32, i32, 4>(x0, x1, |x0, x1| lane_mask(x0 < x1))
+});
+simd_bin_op!(f32x4_gt, |x0: V128, x1: V128| {
+    v128_bin_op::<f32, i32, 4>(x0, x1, |x0, x1| lane_mask(x0 > x1))
+});
+simd_bin_op!(f32x4_le, |x0: V128, x1: V128| {
+    v128_bin_op::<f32, i32, 4>(x0, x1, |x0, x1| lane_mask(x0 <= x1))
+});
+simd_bin_op!(f32x4_ge, |x0: V128, x1: V128| {
+    v128_bin_op::<f32, i32, 4>(x0, x1, |x0, x1| lane_mask(x0 >= x1))
+});
+simd_bin_op!(f64x2_eq, |x0: V128, x1: V128| {
+    v128_bin_op::<f64, i64, 2>(x0, x1, |x0, x1| lane_mask(x0 == x1))
+});
+simd_bin_op!(f64x2_ne, |x0: V128, x1: V128| {
+    v128_bin_op::<f64, i64, 2>(x0, x1, |x0, x1| lane_mask(x0 != x1))
+});
+simd_bin_op!(f64x2_lt, |x0: V128, x1: V128| {
+    v128_bin_op::<f64, i64, 2>(x0, x1, |x0, x1| lane_mask(x0 < x1))
+});
+simd_bin_op!(f64x2_gt, |x0: V128, x1: V128| {
+    v128_bin_op::<f64, i64, 2>(x0, x1, |x0, x1| lane_mask(x0 > x1))
+});
+simd_bin_op!(f64x2_le, |x0: V128, x1: V128| {
+    v128_bin_op::<f64, i64, 2>(x0, x1, |x0, x1| lane_mask(x0 <= x1))
+});
+simd_bin_op!(f64x2_ge, |x0: V128, x1: V128| {
+    v128_bin_op::<f64, i64, 2>(x0, x1, |x0, x1| lane_mask(x0 >= x1))
+});
+simd_un_op!(v128_not, |x: V128| Ok(V128::from_bits(!x.to_bits())));
+simd_bin_op!(v128_and, |x0: V128, x1: V128| Ok(V128::from_bits(
+    x0.to_bits() & x1.to_bits()
+)));
+simd_bin_op!(v128_andnot, |x0: V128, x1: V128| Ok(V128::from_bits(
+    x0.to_bits() & !x1.to_bits()
+)));
+simd_bin_op!(v128_or, |x0: V128, x1: V128| Ok(V128::from_bits(
+    x0.to_bits() | x1.to_bits()
+)));
+simd_bin_op!(v128_xor, |x0: V128, x1: V128| Ok(V128::from_bits(
+    x0.to_bits() ^ x1.to_bits()
+)));
+simd_ternary_op!(v128_bitselect, |x0: V128, x1: V128, x2: V128| {
+    Ok(V128::from_bits(
+        x0.to_bits() & x2.to_bits() | x1.to_bits() & !x2.to_bits(),
+    ))
+});
+simd_un_op!(v128_any_true, |x: V128| Ok(i32::from(x.to_bits() != 0)));
+simd_load_lane!(v128_load8_lane, u8, 16);
+simd_load_lane!(v128_load16_lane, u16, 8);
+simd_load_lane!(v128_load32_lane, u32, 4);
+simd_load_lane!(v128_load64_lane, u64, 2);
+simd_store_lane!(v128_store8_lane, u8, 16);
+simd_store_lane!(v128_store16_lane, u16, 8);
+simd_store_lane!(v128_store32_lane, u32, 4);
+simd_store_lane!(v128_store64_lane, u64, 2);
+simd_load!(v128_load32_zero, u32, |x: u32| Ok(V128::from_bits(
+    x.into()
+)));
+simd_load!(v128_load64_zero, u64, |x: u64| Ok(V128::from_bits(
+    x.into()
+)));
+simd_un_op!(f32x4_demote_f64x2_zero, |x: V128| v128_convert_low::<
+    f64,
+    f32,
+    2,
+    4,
+>(
+    x,
+    <f32 as Demote<f64>>::demote
+));
+simd_un_op!(f64x2_promote_low_f32x4, |x: V128| v128_convert_low::<
+    f32,
+    f64,
+    4,
+    2,
+>(
+    x,
+    <f64 as Promote<f32>>::promote
+));
+simd_un_op!(i8x16_abs, |x: V128| v128_un_op::<i8, i8, 16>(x, |x| Ok(
+    x.wrapping_abs()
+)));
+simd_un_op!(i8x16_neg, |x: V128| v128_un_op::<i8, i8, 16>(x, |x| Ok(
+    x.wrapping_neg()
+)));
+simd_un_op!(i8x16_popcnt, |x: V128| v128_un_op::<u8, u8, 16>(x, |x| Ok(
+    x.count_ones() as u8
+)));
+simd_un_op!(i8x16_all_true, v128_all_true::<u8, 16>);
+simd_un_op!(i8x16_bitmask, v128_bitmask::<i8, 16>);
+simd_bin_op!(i8x16_narrow_i16x8_s, |x0: V128, x1: V128| {
+    v128_narrow::<i16, i8, 8, 16>(
+        x0,
+        x1,
+        |x| Ok(x.clamp(i8::MIN as i16, i8::MAX as i16) as i8),
+    )
+});
+simd_bin_op!(i8x16_narrow_i16x8_u, |x0: V128, x1: V128| {
+    v128_narrow::<i16, u8, 8, 16>(
+        x0,
+        x1,
+        |x| Ok(x.clamp(u8::MIN as i16, u8::MAX as i16) as u8),
+    )
+});
+simd_un_op!(f32x4_ceil, |x: V128| v128_un_op::<f32, f32, 4>(
+    x,
+    <f32 as FloatOps>::ceil
+));
+simd_un_op!(f32x4_floor, |x: V128| v128_un_op::<f32, f32, 4>(
+    x,
+    <f32 as FloatOps>::floor
+));
+simd_un_op!(f32x4_trunc, |x: V128| v128_un_op::<f32, f32, 4>(
+    x,
+    <f32 as FloatOps>::trunc
+));
+simd_un_op!(f32x4_nearest, |x: V128| v128_un_op::<f32, f32, 4>(
+    x,
+    <f32 as FloatOps>::nearest
+));
+simd_bin_op!(i8x16_shl, |x0: V128, x1: u32| v128_un_op::<u8, u8, 16>(
+    x0,
+    |x0| Ok(x0.wrapping_shl(x1))
+));
+simd_bin_op!(i8x16_shr_s, |x0: V128, x1: u32| v128_un_op::<i8, i8, 16>(
+    x0,
+    |x0| Ok(x0.wrapping_shr(x1))
+));
+simd_bin_op!(i8x16_shr_u, |x0: V128, x1: u32| v128_un_op::<u8, u8, 16>(
+    x0,
+    |x0| Ok(x0.wrapping_shr(x1))
+));
+simd_bin_op!(i8x16_add, |x0: V128, x1: V128| v128_bin_op::<u8, u8, 16>(
+    x0,
+    x1,
+    |x0, x1| Ok(x0.wrapping_add(x1))
+));
+simd_bin_op!(i8x16_add_sat_s, |x0: V128, x1: V128| v128_bin_op::<
+    i8,
+    i8,
+    16,
+>(x0, x1, |x0, x1| Ok(
+    x0.saturating_add(x1)
+)));
+simd_bin_op!(i8x16_add_sat_u, |x0: V128, x1: V128| v128_bin_op::<
+    u8,
+    u8,
+    16,
+>(x0, x1, |x0, x1| Ok(
+    x0.saturating_add(x1)
+)));
+simd_bin_op!(i8x16_sub, |x0: V128, x1: V128| v128_bin_op::<u8, u8, 16>(
+    x0,
+    x1,
+    |x0, x1| Ok(x0.wrapping_sub(x1))
+));
+simd_bin_op!(i8x16_sub_sat_s, |x0: V128, x1: V128| v128_bin_op::<
+    i8,
+    i8,
+    16,
+>(x0, x1, |x0, x1| Ok(
+    x0.saturating_sub(x1)
+)));
+simd_bin_op!(i8x16_sub_sat_u, |x0: V128, x1: V128| v128_bin_op::<
+    u8,
+    u8,
+    16,
+>(x0, x1, |x0, x1| Ok(
+    x0.saturating_sub(x1)
+)));
+simd_un_op!(f64x2_ceil, |x: V128| v128_un_op::<f64, f64, 2>(
+    x,
+    <f64 as FloatOps>::ceil
+));
+simd_un_op!(f64x2_floor, |x: V128| v128_un_op::<f64, f64, 2>(
+    x,
+    <f64 as FloatOps>::floor
+));
+simd_bin_op!(i8x16_min_s, |x0: V128, x1: V128| v128_bin_op::<i8, i8, 16>(
+    x0,
+    x1,
+    |x0, x1| Ok(x0.min(x1))
+));
+simd_bin_op!(i8x16_min_u, |x0: V128, x1: V128| v128_bin_op::<u8, u8, 16>(
+    x0,
+    x1,
+    |x0, x1| Ok(x0.min(x1))
+));
+simd_bin_op!(i8x16_max_s, |x0: V128, x1: V128| v128_bin_op::<i8, i8, 16>(
+    x0,
+    x1,
+    |x0, x1| Ok(x0.max(x1))
+));
+simd_bin_op!(i8x16_max_u, |x0: V128, x1: V128| v128_bin_op::<u8, u8, 16>(
+    x0,
+    x1,
+    |x0, x1| Ok(x0.max(x1))
+));
+simd_un_op!(f64x2_trunc, |x: V128| v128_un_op::<f64, f64, 2>(
+    x,
+    <f64 as FloatOps>::trunc
+));
+simd_bin_op!(
+    i8x16_avgr_u,
+    |x0: V128, x1: V128| v128_bin_op::<u8, u8, 16>(x0, x1, |x0, x1| {
+        Ok(((u16::from(x0) + u16::from(x1) + 1) >> 1) as u8)
+    })
+);
+simd_un_op!(i16x8_extadd_pairwise_i8x16_s, |x: V128| {
+    v128_pairwise_op::<i8, i16, 16, 8>(x, |x0, x1| Ok(i16::from(x0) + i16::from(x1)))
+});
+simd_un_op!(i16x8_extadd_pairwise_i8x16_u, |x: V128| {
+    v128_pairwise_op::<u8, u16, 16, 8>(x, |x0, x1| Ok(u16::from(x0) + u16::from(x1)))
+});
+simd_un_op!(i32x4_extadd_pairwise_i16x8_s, |x: V128| {
+    v128_pairwise_op::<i16, i32, 8, 4>(x, |x0, x1| Ok(i32::from(x0) + i32::from(x1)))
+});
+simd_un_op!(i32x4_extadd_pairwise_i16x8_u, |x: V128| {
+    v128_pairwise_op::<u16, u32, 8, 4>(x, |x0, x1| Ok(u32::from(x0) + u32::from(x1)))
+});
+simd_un_op!(i16x8_abs, |x: V128| v128_un_op::<i16, i16, 8>(x, |x| Ok(
+    x.wrapping_abs()
+)));
+simd_un_op!(i16x8_neg, |x: V128| v128_un_op::<i16, i16, 8>(x, |x| Ok(
+    x.wrapping_neg()
+)));
+simd_bin_op!(i16x8_q15mulr_sat_s, |x0: V128, x1: V128| v128_bin_op::<
+    i16,
+    i16,
+    8,
+>(
+    x0,
+    x1,
+    |x0, x1| {
+        Ok(
+            ((i32::from(x0) * i32::from(x1) + 0x4000) >> 15).clamp(i16::MIN.into(), i16::MAX.into())
+                as i16,
+        )
+    }
+));
+simd_un_op!(i16x8_all_true, v128_all_true::<u16, 8>);
+simd_un_op!(i16x8_bitmask, v128_bitmask::<i16, 8>);
+simd_bin_op!(i16x8_narrow_i32x4_s, |x0: V128, x1: V128| {
+    v128_narrow::<i32, i16, 4, 8>(x0, x1, |x| {
+        Ok(x.clamp(i16::MIN as i32, i16::MAX as i32) as i16)
+    })
+});
+simd_bin_op!(i16x8_narrow_i32x4_u, |x0: V128, x1: V128| {
+    v128_narrow::<i32, u16, 4, 8>(x0, x1, |x| {
+        Ok(x.clamp(u16::MIN as i32, u16::MAX as i32) as u16)
+    })
+});
+simd_un_op!(i16x8_extend_low_i8x16_s, |x: V128| v128_convert_low::<
+    i8,
+    i16,
+    16,
+    8,
+>(x, |x| Ok(x.into())));
+simd_un_op!(i16x8_extend_high_i8x16_s, |x: V128| v128_convert_high::<
+    i8,
+    i16,
+    16,
+    8,
+>(x, |x| Ok(x.into())));
+simd_un_op!(i16x8_extend_low_i8x16_u, |x: V128| v128_convert_low::<
+    u8,
+    u16,
+    16,
+    8,
+>(x, |x| Ok(x.into())));
+simd_un_op!(i16x8_extend_high_i8x16_u, |x: V128| v128_convert_high::<
+    u8,
+    u16,
+    16,
+    8,
+>(x, |x| Ok(x.into())));
+simd_bin_op!(i16x8_shl, |x0: V128, x1: u32| v128_un_op::<u16, u16, 8>(
+    x0,
+    |x0| Ok(x0.wrapping_shl(x1))
+));
+simd_bin_op!(i16x8_shr_s, |x0: V128, x1: u32| v128_un_op::<i16, i16, 8>(
+    x0,
+    |x0| Ok(x0.wrapping_shr(x1))
+));
+simd_bin_op!(i16x8_shr_u, |x0: V128, x1: u32| v128_un_op::<u16, u16, 8>(
+    x0,
+    |x0| Ok(x0.wrapping_shr(x1))
+));
+simd_bin_op!(i16x8_add, |x0: V128, x1: V128| v128_bin_op::<u16, u16, 8>(
+    x0,
+    x1,
+    |x0, x1| Ok(x0.wrapping_add(x1))
+));
+simd_bin_op!(i16x8_add_sat_s, |x0: V128, x1: V128| v128_bin_op::<
+    i16,
+    i16,
+    8,
+>(x0, x1, |x0, x1| Ok(
+    x0.saturating_add(x1)
+)));
+simd_bin_op!(i16x8_add_sat_u, |x0: V128, x1: V128| v128_bin_op::<
+    u16,
+    u16,
+    8,
+>(x0, x1, |x0, x1| Ok(
+    x0.saturating_add(x1)
+)));
+simd_bin_op!(i16x8_sub, |x0: V128, x1: V128| v128_bin_op::<u16, u16, 8>(
+    x0,
+    x1,
+    |x0, x1| Ok(x0.wrapping_sub(x1))
+));
+simd_bin_op!(i16x8_sub_sat_s, |x0: V128, x1: V128| v128_bin_op::<
+    i16,
+    i16,
+    8,
+>(x0, x1, |x0, x1| Ok(
+    x0.saturating_sub(x1)
+)));
+simd_bin_op!(i16x8_sub_sat_u, |x0: V128, x1: V128| v128_bin_op::<
+    u16,
+    u16,
+    8,
+>(x0, x1, |x0, x1| Ok(
+    x0.saturating_sub(x1)
+)));
+simd_un_op!(f64x2_nearest, |x: V128| v128_un_op::<f64, f64, 2>(
+    x,
+    <f64 as FloatOps>::nearest
+));
+simd_bin_op!(i16x8_mul, |x0: V128, x1: V128| v128_bin_op::<u16, u16, 8>(
+    x0,
+    x1,
+    |x0, x1| Ok(x0.wrapping_mul(x1))
+));
+simd_bin_op!(
+    i16x8_min_s,
+    |x0: V128, x1: V128| v128_bin_op::<i16, i16, 8>(x0, x1, |x0, x1| Ok(x0.min(x1)))
+);
+simd_bin_op!(
+    i16x8_min_u,
+    |x0: V128, x1: V128| v128_bin_op::<u16, u16, 8>(x0, x1, |x0, x1| Ok(x0.min(x1)))
+);
+simd_bin_op!(
+    i16x8_max_s,
+    |x0: V128, x1: V128| v128_bin_op::<i16, i16, 8>(x0, x1, |x0, x1| Ok(x0.max(x1)))
+);
+simd_bin_op!(
+    i16x8_max_u,
+    |x0: V128, x1: V128| v128_bin_op::<u16, u16, 8>(x0, x1, |x0, x1| Ok(x0.max(x1)))
+);
+simd_bin_op!(
+    i16x8_avgr_u,
+    |x0: V128, x1: V128| v128_bin_op::<u16, u16, 8>(x0, x1, |x0, x1| {
+        Ok(((u32::from(x0) + u32::from(x1) + 1) >> 1) as u16)
+    })
+);
+simd_bin_op!(i16x8_extmul_low_i8x16_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i16, i16, 8>(
+        v128_convert_low::<i8, i16, 16, 8>(x0, |x| Ok(x.into()))?,
+        v128_convert_low::<i8, i16, 16, 8>(x1, |x| Ok(x.into()))?,
+        |x0, x1| Ok(x0 * x1),
+    )
+});
+simd_bin_op!(i16x8_extmul_high_i8x16_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i16, i16, 8>(
+        v128_convert_high::<i8, i16, 16, 8>(x0, |x| Ok(x.into()))?,
+        v128_convert_high::<i8, i16, 16, 8>(x1, |x| Ok(x.into()))?,
+        |x0, x1| Ok(x0 * x1),
+    )
+});
+simd_bin_op!(i16x8_extmul_low_i8x16_u, |x0: V128, x1: V128| {
+    v128_bin_op::<u16, u16, 8>(
+        v128_convert_low::<u8, u16, 16, 8>(x0, |x| Ok(x.into()))?,
+        v128_convert_low::<u8, u16, 16, 8>(x1, |x| Ok(x.into()))?,
+        |x0, x1| Ok(x0 * x1),
+    )
+});
+simd_bin_op!(i16x8_extmul_high_i8x16_u, |x0: V128, x1: V128| {
+    v128_bin_op::<u16, u16, 8>(
+        v128_convert_high::<u8, u16, 16, 8>(x0, |x| Ok(x.into()))?,
+        v128_convert_high::<u8, u16, 16, 8>(x1, |x| Ok(x.into()))?,
+        |x0, x1| Ok(x0 * x1),
+    )
+});
+simd_un_op!(i32x4_abs, |x: V128| v128_un_op::<i32, i32, 4>(x, |x| Ok(
+    x.wrapping_abs()
+)));
+simd_un_op!(i32x4_neg, |x: V128| v128_un_op::<i32, i32, 4>(x, |x| Ok(
+    x.wrapping_neg()
+)));
+simd_un_op!(i32x4_all_true, v128_all_true::<u32, 4>);
+simd_un_op!(i32x4_bitmask, v128_bitmask::<i32, 4>);
+simd_un_op!(i32x4_extend_low_i16x8_s, |x: V128| v128_convert_low::<
+    i16,
+    i32,
+    8,
+    4,
+>(x, |x| Ok(x.into())));
+simd_un_op!(i32x4_extend_high_i16x8_s, |x: V128| v128_convert_high::<
+    i16,
+    i32,
+    8,
+    4,
+>(x, |x| Ok(x.into())));
+simd_un_op!(i32x4_extend_low_i16x8_u, |x: V128| v128_convert_low::<
+    u16,
+    u32,
+    8,
+    4,
+>(x, |x| Ok(x.into())));
+simd_un_op!(i32x4_extend_high_i16x8_u, |x: V128| v128_convert_high::<
+    u16,
+    u32,
+    8,
+    4,
+>(x, |x| Ok(x.into())));
+simd_bin_op!(i32x4_shl, |x0: V128, x1: u32| v128_un_op::<u32, u32, 4>(
+    x0,
+    |x0| Ok(x0.wrapping_shl(x1))
+));
+simd_bin_op!(i32x4_shr_s, |x0: V128, x1: u32| v128_un_op::<i32, i32, 4>(
+    x0,
+    |x0| Ok(x0.wrapping_shr(x1))
+));
+simd_bin_op!(i32x4_shr_u, |x0: V128, x1: u32| v128_un_op::<u32, u32, 4>(
+    x0,
+    |x0| Ok(x0.wrapping_shr(x1))
+));
+simd_bin_op!(i32x4_add, |x0: V128, x1: V128| v128_bin_op::<u32, u32, 4>(
+    x0,
+    x1,
+    <u32 as IntOps>::add
+));
+simd_bin_op!(i32x4_sub, |x0: V128, x1: V128| v128_bin_op::<u32, u32, 4>(
+    x0,
+    x1,
+    <u32 as IntOps>::sub
+));
+simd_bin_op!(i32x4_mul, |x0: V128, x1: V128| v128_bin_op::<u32, u32, 4>(
+    x0,
+    x1,
+    <u32 as IntOps>::mul
+));
+simd_bin_op!(
+    i32x4_min_s,
+    |x0: V128, x1: V128| v128_bin_op::<i32, i32, 4>(x0, x1, |x0, x1| Ok(x0.min(x1)))
+);
+simd_bin_op!(
+    i32x4_min_u,
+    |x0: V128, x1: V128| v128_bin_op::<u32, u32, 4>(x0, x1, |x0, x1| Ok(x0.min(x1)))
+);
+simd_bin_op!(
+    i32x4_max_s,
+    |x0: V128, x1: V128| v128_bin_op::<i32, i32, 4>(x0, x1, |x0, x1| Ok(x0.max(x1)))
+);
+simd_bin_op!(
+    i32x4_max_u,
+    |x0: V128, x1: V128| v128_bin_op::<u32, u32, 4>(x0, x1, |x0, x1| Ok(x0.max(x1)))
+);
+simd_bin_op!(i32x4_dot_i16x8_s, v128_dot_i16x8_s);
+simd_bin_op!(i32x4_extmul_low_i16x8_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i32, i32, 4>(
+        v128_convert_low::<i16, i32, 8, 4>(x0, |x| Ok(x.into()))?,
+        v128_convert_low::<i16, i32, 8, 4>(x1, |x| Ok(x.into()))?,
+        |x0, x1| Ok(x0 * x1),
+    )
+});
+simd_bin_op!(i32x4_extmul_high_i16x8_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i32, i32, 4>(
+        v128_convert_high::<i16, i32, 8, 4>(x0, |x| Ok(x.into()))?,
+        v128_convert_high::<i16, i32, 8, 4>(x1, |x| Ok(x.into()))?,
+        |x0, x1| Ok(x0 * x1),
+    )
+});
+simd_bin_op!(i32x4_extmul_low_i16x8_u, |x0: V128, x1: V128| {
+    v128_bin_op::<u32, u32, 4>(
+        v128_convert_low::<u16, u32, 8, 4>(x0, |x| Ok(x.into()))?,
+        v128_convert_low::<u16, u32, 8, 4>(x1, |x| Ok(x.into()))?,
+        |x0, x1| Ok(x0 * x1),
+    )
+});
+simd_bin_op!(i32x4_extmul_high_i16x8_u, |x0: V128, x1: V128| {
+    v128_bin_op::<u32, u32, 4>(
+        v128_convert_high::<u16, u32, 8, 4>(x0, |x| Ok(x.into()))?,
+        v128_convert_high::<u16, u32, 8, 4>(x1, |x| Ok(x.into()))?,
+        |x0, x1| Ok(x0 * x1),
+    )
+});
+simd_un_op!(i64x2_abs, |x: V128| v128_un_op::<i64, i64, 2>(x, |x| Ok(
+    x.wrapping_abs()
+)));
+simd_un_op!(i64x2_neg, |x: V128| v128_un_op::<i64, i64, 2>(x, |x| Ok(
+    x.wrapping_neg()
+)));
+simd_un_op!(i64x2_all_true, v128_all_true::<u64, 2>);
+simd_un_op!(i64x2_bitmask, v128_bitmask::<i64, 2>);
+simd_un_op!(i64x2_extend_low_i32x4_s, |x: V128| v128_convert_low::<
+    i32,
+    i64,
+    4,
+    2,
+>(x, |x| Ok(x.into())));
+simd_un_op!(i64x2_extend_high_i32x4_s, |x: V128| v128_convert_high::<
+    i32,
+    i64,
+    4,
+    2,
+>(x, |x| Ok(x.into())));
+simd_un_op!(i64x2_extend_low_i32x4_u, |x: V128| v128_convert_low::<
+    u32,
+    u64,
+    4,
+    2,
+>(x, |x| Ok(x.into())));
+simd_un_op!(i64x2_extend_high_i32x4_u, |x: V128| v128_convert_high::<
+    u32,
+    u64,
+    4,
+    2,
+>(x, |x| Ok(x.into())));
+simd_bin_op!(i64x2_shl, |x0: V128, x1: u32| v128_un_op::<u64, u64, 2>(
+    x0,
+    |x0| Ok(x0.wrapping_shl(x1))
+));
+simd_bin_op!(i64x2_shr_s, |x0: V128, x1: u32| v128_un_op::<i64, i64, 2>(
+    x0,
+    |x0| Ok(x0.wrapping_shr(x1))
+));
+simd_bin_op!(i64x2_shr_u, |x0: V128, x1: u32| v128_un_op::<u64, u64, 2>(
+    x0,
+    |x0| Ok(x0.wrapping_shr(x1))
+));
+simd_bin_op!(i64x2_add, |x0: V128, x1: V128| v128_bin_op::<u64, u64, 2>(
+    x0,
+    x1,
+    <u64 as IntOps>::add
+));
+simd_bin_op!(i64x2_sub, |x0: V128, x1: V128| v128_bin_op::<u64, u64, 2>(
+    x0,
+    x1,
+    <u64 as IntOps>::sub
+));
+simd_bin_op!(i64x2_mul, |x0: V128, x1: V128| v128_bin_op::<u64, u64, 2>(
+    x0,
+    x1,
+    <u64 as IntOps>::mul
+));
+simd_bin_op!(i64x2_eq, |x0: V128, x1: V128| {
+    v128_bin_op::<i64, i64, 2>(x0, x1, |x0, x1| lane_mask(x0 == x1))
+});
+simd_bin_op!(i64x2_ne, |x0: V128, x1: V128| {
+    v128_bin_op::<i64, i64, 2>(x0, x1, |x0, x1| lane_mask(x0 != x1))
+});
+simd_bin_op!(i64x2_lt_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i64, i64, 2>(x0, x1, |x0, x1| lane_mask(x0 < x1))
+});
+simd_bin_op!(i64x2_gt_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i64, i64, 2>(x0, x1, |x0, x1| lane_mask(x0 > x1))
+});
+simd_bin_op!(i64x2_le_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i64, i64, 2>(x0, x1, |x0, x1| lane_mask(x0 <= x1))
+});
+simd_bin_op!(i64x2_ge_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i64, i64, 2>(x0, x1, |x0, x1| lane_mask(x0 >= x1))
+});
+simd_bin_op!(i64x2_extmul_low_i32x4_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i64, i64, 2>(
+        v128_convert_low::<i32, i64, 4, 2>(x0, |x| Ok(x.into()))?,
+        v128_convert_low::<i32, i64, 4, 2>(x1, |x| Ok(x.into()))?,
+        |x0, x1| Ok(x0 * x1),
+    )
+});
+simd_bin_op!(i64x2_extmul_high_i32x4_s, |x0: V128, x1: V128| {
+    v128_bin_op::<i64, i64, 2>(
+        v128_convert_high::<i32, i64, 4, 2>(x0, |x| Ok(x.into()))?,
+        v128_convert_high::<i32, i64, 4, 2>(x1, |x| Ok(x.into()))?,
+        |x0, x1| Ok(x0 * x1),
+    )
+});
+simd_bin_op!(i64x2_extmul_low_i32x4_u, |x0: V128, x1: V128| {
+    v128_bin_op::<u64, u64, 2>(
+        v128_convert_low::<u32, u64, 4, 2>(x0, |x| Ok(x.into()))?,
+        v128_convert_low::<u32, u64, 4, 2>(x1, |x| Ok(x.into()))?,
+        |x0, x1| Ok(x0 * x1),
+    )
+});
+simd_bin_op!(i64x2_extmul_high_i32x4_u, |x0: V128, x1: V128| {
+    v128_bin_op::<u64, u64, 2>(
+        v128_convert_high::<u32, u64, 4, 2>(x0, |x| Ok(x.into()))?,
+        v128_convert_high::<u32, u64, 4, 2>(x1, |x| Ok(x.into()))?,
+        |x0, x1| Ok(x0 * x1),
+    )
+});
+simd_un_op!(f32x4_abs, |x: V128| v128_un_op::<f32, f32, 4>(
+    x,
+    <f32 as FloatOps>::abs
+));
+simd_un_op!(f32x4_neg, |x: V128| v128_un_op::<f32, f32, 4>(
+    x,
+    <f32 as FloatOps>::neg
+));
+simd_un_op!(f32x4_sqrt, |x: V128| v128_un_op::<f32, f32, 4>(
+    x,
+    <f32 as FloatOps>::sqrt
+));
+simd_bin_op!(f32x4_add, |x0: V128, x1: V128| v128_bin_op::<f32, f32, 4>(
+    x0,
+    x1,
+    <f32 as FloatOps>::add
+));
+simd_bin_op!(f32x4_sub, |x0: V128, x1: V128| v128_bin_op::<f32, f32, 4>(
+    x0,
+    x1,
+    <f32 as FloatOps>::sub
+));
+simd_bin_op!(f32x4_mul, |x0: V128, x1: V128| v128_bin_op::<f32, f32, 4>(
+    x0,
+    x1,
+    <f32 as FloatOps>::mul
+));
+simd_bin_op!(f32x4_div, |x0: V128, x1: V128| v128_bin_op::<f32, f32, 4>(
+    x0,
+    x1,
+    <f32 as FloatOps>::div
+));
+simd_bin_op!(f32x4_min, |x0: V128, x1: V128| v128_bin_op::<f32, f32, 4>(
+    x0,
+    x1,
+    <f32 as FloatOps>::min
+));
+simd_bin_op!(f32x4_max, |x0: V128, x1: V128| v128_bin_op::<f32, f32, 4>(
+    x0,
+    x1,
+    <f32 as FloatOps>::max
+));
+simd_bin_op!(f32x4_pmin, |x0: V128, x1: V128| v128_bin_op::<f32, f32, 4>(
+    x0,
+    x1,
+    |x0, x1| Ok(if x1 < x0 { x1 } else { x0 })
+));
+simd_bin_op!(f32x4_pmax, |x0: V128, x1: V128| v128_bin_op::<f32, f32, 4>(
+    x0,
+    x1,
+    |x0, x1| Ok(if x0 < x1 { x1 } else { x0 })
+));
+simd_un_op!(f64x2_abs, |x: V128| v128_un_op::<f64, f64, 2>(
+    x,
+    <f64 as FloatOps>::abs
+));
+simd_un_op!(f64x2_neg, |x: V128| v128_un_op::<f64, f64, 2>(
+    x,
+    <f64 as FloatOps>::neg
+));
+simd_un_op!(f64x2_sqrt, |x: V128| v128_un_op::<f64, f64, 2>(
+    x,
+    <f64 as FloatOps>::sqrt
+));
+simd_bin_op!(f64x2_add, |x0: V128, x1: V128| v128_bin_op::<f64, f64, 2>(
+    x0,
+    x1,
+    <f64 as FloatOps>::add
+));
+simd_bin_op!(f64x2_sub, |x0: V128, x1: V128| v128_bin_op::<f64, f64, 2>(
+    x0,
+    x1,
+    <f64 as FloatOps>::sub
+));
+simd_bin_op!(f64x2_mul, |x0: V128, x1: V128| v128_bin_op::<f64, f64, 2>(
+    x0,
+    x1,
+    <f64 as FloatOps>::mul
+));
+simd_bin_op!(f64x2_div, |x0: V128, x1: V128| v128_bin_op::<f64, f64, 2>(
+    x0,
+    x1,
+    <f64 as FloatOps>::div
+));
+simd_bin_op!(f64x2_min, |x0: V128, x1: V128| v128_bin_op::<f64, f64, 2>(
+    x0,
+    x1,
+    <f64 as FloatOps>::min
+));
+simd_bin_op!(f64x2_max, |x0: V128, x1: V128| v128_bin_op::<f64, f64, 2>(
+    x0,
+    x1,
+    <f64 as FloatOps>::max
+));
+simd_bin_op!(f64x2_pmin, |x0: V128, x1: V128| v128_bin_op::<f64, f64, 2>(
+    x0,
+    x1,
+    |x0, x1| Ok(if x1 < x0 { x1 } else { x0 })
+));
+simd_bin_op!(f64x2_pmax, |x0: V128, x1: V128| v128_bin_op::<f64, f64, 2>(
+    x0,
+    x1,
+    |x0, x1| Ok(if x0 < x1 { x1 } else { x0 })
+));
+simd_un_op!(
+    i32x4_trunc_sat_f32x4_s,
+    |x: V128| v128_un_op::<f32, i32, 4>(x, <i32 as Trunc<f32>>::trunc_sat)
+);
+simd_un_op!(
+    i32x4_trunc_sat_f32x4_u,
+    |x: V128| v128_un_op::<f32, u32, 4>(x, <u32 as Trunc<f32>>::trunc_sat)
+);
+simd_un_op!(f32x4_convert_i32x4_s, |x: V128| v128_un_op::<i32, f32, 4>(
+    x,
+    <f32 as Convert<i32>>::convert
+));
+simd_un_op!(f32x4_convert_i32x4_u, |x: V128| v128_un_op::<u32, f32, 4>(
+    x,
+    <f32 as Convert<u32>>::convert
+));
+simd_un_op!(i32x4_trunc_sat_f64x2_s_zero, |x: V128| v128_convert_low::<
+    f64,
+    i32,
+    2,
+    4,
+>(
+    x,
+    <i32 as Trunc<f64>>::trunc_sat
+));
+simd_un_op!(i32x4_trunc_sat_f64x2_u_zero, |x: V128| v128_convert_low::<
+    f64,
+    u32,
+    2,
+    4,
+>(
+    x,
+    <u32 as Trunc<f64>>::trunc_sat
+));
+simd_un_op!(f64x2_convert_low_i32x4_s, |x: V128| v128_convert_low::<
+    i32,
+    f64,
+    4,
+    2,
+>(
+    x,
+    <f64 as Convert<i32>>::convert
+));
+simd_un_op!(f64x2_convert_low_i32x4_u, |x: V128| v128_convert_low::<
+    u32,
+    f64,
+    4,
+    2,
+>(
+    x,
+    <f64 as Convert<u32>>::convert
+));
+
 // Miscellaneous instructions
 
 macro_rules! copy_imm_to_stack {
@@ -3850,6 +5112,26 @@ copy_imm_to_stack!(copy_imm_to_stack_f64, f64);
 copy_imm_to_stack!(copy_imm_to_stack_func_ref, UnguardedFuncRef);
 copy_imm_to_stack!(copy_imm_to_stack_extern_ref, UnguardedExternRef);
 
+threaded_instr!(copy_imm_to_stack_v128(
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    cx: Cx,
+) -> ControlFlowBits {
+    // Read immediate value
+    let (x, ip) = read_imm_v128(ip);
+
+    // Write value to stack
+    let ip = write_stack(ip, sp, x);
+
+    // Execute next instruction
+    next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+});
+
 macro_rules! copy_stack {
     ($copy_stack_t:ident, $T:ty) => {
         threaded_instr!($copy_stack_t(
@@ -3878,6 +5160,7 @@ copy_stack!(copy_stack_i32, i32);
 copy_stack!(copy_stack_i64, i64);
 copy_stack!(copy_stack_f32, f32);
 copy_stack!(copy_stack_f64, f64);
+copy_stack!(copy_stack_v128, V128);
 copy_stack!(copy_stack_func_ref, UnguardedFuncRef);
 copy_stack!(copy_stack_extern_ref, UnguardedExternRef);
 
@@ -3992,6 +5275,26 @@ threaded_instr!(enter(
     next_instr(ip, sp, md, ms, ix, sx, dx, cx)
 });
 
+threaded_instr!(checkpoint(
+    ip: Ip,
+    sp: Sp,
+    md: Md,
+    ms: Ms,
+    ix: Ix,
+    sx: Sx,
+    dx: Dx,
+    cx: Cx,
+) -> ControlFlowBits {
+    // Read operands
+    let (cost, ip): (usize, _) = read_imm(ip);
+
+    // Consume fuel and check for interrupts.
+    r#try!((*(*cx).store).checkpoint(cost as u64));
+
+    // Execute the next instruction.
+    next_instr(ip, sp, md, ms, ix, sx, dx, cx)
+});
+
 // Helper functions
 
 /// Executes the next instruction.
@@ -4019,6 +5322,17 @@ where
     (val, ip)
 }
 
+/// Reads an immediate [`V128`].
+///
+/// A [`V128`] does not fit in a single instruction slot, so it is spread out over several
+/// consecutive slots.
+unsafe fn read_imm_v128(ip: Ip) -> (V128, Ip) {
+    let mut bytes = [0u8; 16];
+    ptr::copy_nonoverlapping(ip.cast::<u8>(), bytes.as_mut_ptr(), bytes.len());
+    let ip = ip.add(bytes.len() / mem::size_of::<InstrSlot>());
+    (V128::from_le_bytes(bytes), ip)
+}
+
 /// Reads a value from the stack.
 unsafe fn read_stack<T>(ip: Ip, sp: Sp) -> (T, Ip)
 where
@@ -5,6 +5,7 @@ use crate::{
     func_ref::{FuncRef, UnguardedFuncRef},
     stack::{Stack, StackGuard, StackSlot},
     store::{Store, StoreId},
+    v128::V128,
     val::ValType,
 };
 
@@ -259,5 +260,6 @@ impl_host_val!(i64, I64);
 impl_host_val!(u64, I64);
 impl_host_val!(f32, F32);
 impl_host_val!(f64, F64);
+impl_host_val!(V128, V128);
 impl_host_val_raw!(FuncRef, UnguardedFuncRef, FuncRef);
 impl_host_val_raw!(ExternRef, UnguardedExternRef, ExternRef);
@@ -28,12 +28,16 @@ mod stack;
 mod store;
 mod table;
 mod trap;
+mod v128;
 mod val;
 mod validate;
 
+#[cfg(feature = "wasi")]
+pub mod wasi;
+
 pub use self::{
     decode::DecodeError,
-    engine::Engine,
+    engine::{Engine, EngineConfig},
     error::Error,
     extern_ref::ExternRef,
     extern_val::{ExternType, ExternVal},
@@ -46,7 +50,9 @@ pub use self::{
     mem::{Mem, MemError, MemType},
     module::{Module, ModuleExports, ModuleImports},
     ref_::{Ref, RefType},
-    store::Store,
+    store::{InterruptHandle, Store},
     table::{Table, TableError, TableType},
+    trap::Trap,
+    v128::V128,
     val::{Val, ValType},
 };
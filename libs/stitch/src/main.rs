@@ -1,19 +1,63 @@
 use {
-    makepad_stitch::{Engine, Linker, Module, Store, Val, ValType},
+    makepad_stitch::{Engine, Linker, Module, Store, Val, ValType, V128},
     std::{env, fs},
 };
 
+#[cfg(feature = "wasi")]
+use {
+    makepad_stitch::wasi::{Wasi, WasiConfig, WASI_MODULE},
+    std::process,
+};
+
 fn main() {
-    let mut args = env::args();
+    let mut args = env::args().peekable();
     args.next().unwrap();
+    #[cfg(feature = "wasi")]
+    let mut wasi_config = WasiConfig::new();
+    while let Some(option) = args.next_if(|arg| arg.starts_with("--")) {
+        #[cfg(feature = "wasi")]
+        if let Some(dir) = option.strip_prefix("--dir=") {
+            // --dir=<host path>[::<guest path>]
+            wasi_config = match dir.split_once("::") {
+                Some((host_path, guest_path)) => wasi_config.preopen_dir(host_path, guest_path),
+                None => wasi_config.preopen_dir(dir, dir),
+            };
+            continue;
+        }
+        #[cfg(feature = "wasi")]
+        if let Some((key, value)) = option
+            .strip_prefix("--env=")
+            .and_then(|env| env.split_once('='))
+        {
+            wasi_config = wasi_config.env(key, value);
+            continue;
+        }
+        panic!("unknown option {}", option);
+    }
     let wasm_file = args.next().unwrap();
-    let func_name = args.next().unwrap();
-    let args: Vec<_> = args.collect();
     let engine = Engine::new();
     let mut store = Store::new(engine);
-    let bytes = fs::read(wasm_file).unwrap();
+    let bytes = fs::read(&wasm_file).unwrap();
     let module = Module::new(store.engine(), &bytes).unwrap();
-    let linker = Linker::new();
+    #[allow(unused_mut)]
+    let mut linker = Linker::new();
+
+    // Modules that import WASI are commands: everything after the module is passed on to them as
+    // arguments, and we exit with their exit code.
+    #[cfg(feature = "wasi")]
+    if module
+        .imports()
+        .any(|((module, _), _)| module == WASI_MODULE)
+    {
+        let wasi = Wasi::new(wasi_config.arg(wasm_file).args(args)).unwrap();
+        wasi.define(&mut store, &mut linker);
+        let instance = linker.instantiate(&mut store, &module).unwrap();
+        let code = wasi.run(&mut store, &instance).unwrap();
+        process::exit(code);
+    }
+
+    let func_name = args.next().unwrap();
+    let args: Vec<_> = args.collect();
     let instance = linker.instantiate(&mut store, &module).unwrap();
     let func = instance.exported_func(&func_name).unwrap();
     let args: Vec<_> = func
@@ -41,6 +85,7 @@ fn parse_val(type_: ValType, string: &str) -> Val {
         ValType::I64 => string.parse::<i64>().unwrap().into(),
         ValType::F32 => string.parse::<f32>().unwrap().into(),
         ValType::F64 => string.parse::<f64>().unwrap().into(),
+        ValType::V128 => V128::from_bits(string.parse::<u128>().unwrap()).into(),
         ValType::FuncRef => unimplemented!(),
         ValType::ExternRef => unimplemented!(),
     }
@@ -52,6 +97,7 @@ fn print_val(val: Val) {
         Val::I64(val) => println!("{}", val),
         Val::F32(val) => println!("{}", val),
         Val::F64(val) => println!("{}", val),
+        Val::V128(val) => println!("{}", val.to_bits()),
         Val::FuncRef(_) => unimplemented!(),
         Val::ExternRef(_) => unimplemented!(),
     }
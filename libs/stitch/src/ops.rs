@@ -1,4 +1,7 @@
-use {crate::trap::Trap, std::mem};
+use {
+    crate::{trap::Trap, v128::V128},
+    std::mem,
+};
 
 pub(crate) trait RelOps {
     fn eq(self, other: Self) -> Result<i32, Trap>;
@@ -449,3 +452,238 @@ impl_extend_n!(i8, i64);
 impl_extend_n!(i16, i32);
 impl_extend_n!(i16, i64);
 impl_extend_n!(i32, i64);
+
+// Vector operations
+//
+// Vector operations are defined in terms of operations on their lanes. The functions below lift
+// operations on lanes to operations on [`V128`]s. The lane type and the number of lanes are given
+// by the type parameters `T` and `N` for the input, and `U` and `M` for the output, if they differ.
+
+/// Creates a [`V128`] with all lanes set to the given value.
+pub(crate) fn v128_splat<T, const N: usize>(x: T) -> Result<V128, Trap>
+where
+    T: Copy,
+    V128: From<[T; N]>,
+{
+    Ok([x; N].into())
+}
+
+/// Extracts the lane with the given index from a [`V128`].
+pub(crate) fn v128_extract_lane<T, const N: usize>(x: V128, lane_idx: u8) -> Result<T, Trap>
+where
+    T: Copy,
+    [T; N]: From<V128>,
+{
+    Ok(<[T; N]>::from(x)[lane_idx as usize])
+}
+
+/// Replaces the lane with the given index in a [`V128`].
+pub(crate) fn v128_replace_lane<T, const N: usize>(
+    x: V128,
+    lane_idx: u8,
+    y: T,
+) -> Result<V128, Trap>
+where
+    T: Copy,
+    [T; N]: From<V128>,
+    V128: From<[T; N]>,
+{
+    let mut x = <[T; N]>::from(x);
+    x[lane_idx as usize] = y;
+    Ok(x.into())
+}
+
+/// Applies a unary operation to each lane of a [`V128`].
+pub(crate) fn v128_un_op<T, U, const N: usize>(
+    x: V128,
+    f: impl Fn(T) -> Result<U, Trap>,
+) -> Result<V128, Trap>
+where
+    T: Copy,
+    U: Copy + Default,
+    [T; N]: From<V128>,
+    V128: From<[U; N]>,
+{
+    let x = <[T; N]>::from(x);
+    let mut y = [U::default(); N];
+    for lane_idx in 0..N {
+        y[lane_idx] = f(x[lane_idx])?;
+    }
+    Ok(y.into())
+}
+
+/// Applies a binary operation to each pair of corresponding lanes of two [`V128`]s.
+pub(crate) fn v128_bin_op<T, U, const N: usize>(
+    x0: V128,
+    x1: V128,
+    f: impl Fn(T, T) -> Result<U, Trap>,
+) -> Result<V128, Trap>
+where
+    T: Copy,
+    U: Copy + Default,
+    [T; N]: From<V128>,
+    V128: From<[U; N]>,
+{
+    let x0 = <[T; N]>::from(x0);
+    let x1 = <[T; N]>::from(x1);
+    let mut y = [U::default(); N];
+    for lane_idx in 0..N {
+        y[lane_idx] = f(x0[lane_idx], x1[lane_idx])?;
+    }
+    Ok(y.into())
+}
+
+/// Applies a binary operation to each pair of adjacent lanes of a [`V128`].
+pub(crate) fn v128_pairwise_op<T, U, const N: usize, const M: usize>(
+    x: V128,
+    f: impl Fn(T, T) -> Result<U, Trap>,
+) -> Result<V128, Trap>
+where
+    T: Copy,
+    U: Copy + Default,
+    [T; N]: From<V128>,
+    V128: From<[U; M]>,
+{
+    let x = <[T; N]>::from(x);
+    let mut y = [U::default(); M];
+    for lane_idx in 0..M {
+        y[lane_idx] = f(x[2 * lane_idx], x[2 * lane_idx + 1])?;
+    }
+    Ok(y.into())
+}
+
+/// Applies a conversion to the low lanes of a [`V128`].
+///
+/// If the output has more lanes than the input, the remaining lanes of the output are set to zero.
+pub(crate) fn v128_convert_low<T, U, const N: usize, const M: usize>(
+    x: V128,
+    f: impl Fn(T) -> Result<U, Trap>,
+) -> Result<V128, Trap>
+where
+    T: Copy,
+    U: Copy + Default,
+    [T; N]: From<V128>,
+    V128: From<[U; M]>,
+{
+    let x = <[T; N]>::from(x);
+    let mut y = [U::default(); M];
+    for lane_idx in 0..M.min(N) {
+        y[lane_idx] = f(x[lane_idx])?;
+    }
+    Ok(y.into())
+}
+
+/// Applies a conversion to the high lanes of a [`V128`].
+pub(crate) fn v128_convert_high<T, U, const N: usize, const M: usize>(
+    x: V128,
+    f: impl Fn(T) -> Result<U, Trap>,
+) -> Result<V128, Trap>
+where
+    T: Copy,
+    U: Copy + Default,
+    [T; N]: From<V128>,
+    V128: From<[U; M]>,
+{
+    let x = <[T; N]>::from(x);
+    let mut y = [U::default(); M];
+    for lane_idx in 0..M {
+        y[lane_idx] = f(x[N - M + lane_idx])?;
+    }
+    Ok(y.into())
+}
+
+/// Applies a conversion to each lane of two [`V128`]s, and concatenates the results.
+pub(crate) fn v128_narrow<T, U, const N: usize, const M: usize>(
+    x0: V128,
+    x1: V128,
+    f: impl Fn(T) -> Result<U, Trap>,
+) -> Result<V128, Trap>
+where
+    T: Copy,
+    U: Copy + Default,
+    [T; N]: From<V128>,
+    V128: From<[U; M]>,
+{
+    let x0 = <[T; N]>::from(x0);
+    let x1 = <[T; N]>::from(x1);
+    let mut y = [U::default(); M];
+    for lane_idx in 0..N {
+        y[lane_idx] = f(x0[lane_idx])?;
+        y[N + lane_idx] = f(x1[lane_idx])?;
+    }
+    Ok(y.into())
+}
+
+/// Returns 1 if all lanes of a [`V128`] are non-zero, and 0 otherwise.
+pub(crate) fn v128_all_true<T, const N: usize>(x: V128) -> Result<i32, Trap>
+where
+    T: Copy + Default + PartialEq,
+    [T; N]: From<V128>,
+{
+    Ok(<[T; N]>::from(x).iter().all(|&x| x != T::default()).into())
+}
+
+/// Returns a mask with bit `i` set if lane `i` of a [`V128`] is negative.
+pub(crate) fn v128_bitmask<T, const N: usize>(x: V128) -> Result<i32, Trap>
+where
+    T: Copy + Default + PartialOrd,
+    [T; N]: From<V128>,
+{
+    Ok(<[T; N]>::from(x)
+        .iter()
+        .enumerate()
+        .fold(0, |mask, (lane_idx, &x)| {
+            mask | (i32::from(x < T::default()) << lane_idx)
+        }))
+}
+
+/// Returns a lane with all bits set if the given condition is `true`, and all bits cleared
+/// otherwise.
+pub(crate) fn lane_mask<T>(cond: bool) -> Result<T, Trap>
+where
+    T: From<bool> + std::ops::Neg<Output = T>,
+{
+    Ok(-T::from(cond))
+}
+
+/// Selects lanes from two [`V128`]s using the given lane indices.
+///
+/// Indices 0 to 15 select lanes from the first [`V128`], and indices 16 to 31 select lanes from
+/// the second [`V128`].
+pub(crate) fn v128_shuffle(x0: V128, x1: V128, lane_idxs: V128) -> Result<V128, Trap> {
+    let x0 = x0.to_le_bytes();
+    let x1 = x1.to_le_bytes();
+    Ok(V128::from_le_bytes(lane_idxs.to_le_bytes().map(
+        |lane_idx| {
+            let lane_idx = lane_idx as usize;
+            if lane_idx < 16 {
+                x0[lane_idx]
+            } else {
+                x1[lane_idx - 16]
+            }
+        },
+    )))
+}
+
+/// Selects lanes from a [`V128`] using the lanes of another [`V128`] as indices.
+///
+/// Indices that are out of range select zero.
+pub(crate) fn v128_swizzle(x0: V128, x1: V128) -> Result<V128, Trap> {
+    let x0 = x0.to_le_bytes();
+    Ok(V128::from_le_bytes(x1.to_le_bytes().map(|lane_idx| {
+        x0.get(lane_idx as usize).copied().unwrap_or(0)
+    })))
+}
+
+/// Multiplies the signed 16-bit lanes of two [`V128`]s, and adds each pair of adjacent products.
+pub(crate) fn v128_dot_i16x8_s(x0: V128, x1: V128) -> Result<V128, Trap> {
+    let x0 = <[i16; 8]>::from(x0);
+    let x1 = <[i16; 8]>::from(x1);
+    let mut y = [0i32; 4];
+    for lane_idx in 0..4 {
+        let y0 = i32::from(x0[2 * lane_idx]) * i32::from(x1[2 * lane_idx]);
+        let y1 = i32::from(x0[2 * lane_idx + 1]) * i32::from(x1[2 * lane_idx + 1]);
+        y[lane_idx] = y0.wrapping_add(y1);
+    }
+    Ok(y.into())
+}
@@ -70,7 +70,7 @@ impl Drop for StackGuard {
     }
 }
 
-pub(crate) type StackSlot = u64;
+pub(crate) type StackSlot = u128;
 
 thread_local! {
     static STACK: Cell<Option<Stack>> = Cell::new(Some(Stack::new()));
@@ -9,12 +9,17 @@ use {
         global::GlobalEntity,
         mem::MemEntity,
         table::TableEntity,
+        trap::Trap,
     },
     std::{
         collections::HashMap,
         fmt,
         hash::{Hash, Hasher},
         ptr::NonNull,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
     },
 };
 
@@ -31,6 +36,8 @@ pub struct Store {
     elems: Vec<AliasableBox<ElemEntity>>,
     datas: Vec<AliasableBox<DataEntity>>,
     externs: Vec<AliasableBox<ExternEntity>>,
+    fuel: u64,
+    interrupted: Arc<AtomicBool>,
 }
 
 impl Store {
@@ -47,6 +54,8 @@ impl Store {
             elems: Vec::new(),
             datas: Vec::new(),
             externs: Vec::new(),
+            fuel: u64::MAX,
+            interrupted: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -54,6 +63,50 @@ impl Store {
         &self.engine
     }
 
+    /// Returns the amount of fuel remaining in this [`Store`].
+    pub fn fuel(&self) -> u64 {
+        self.fuel
+    }
+
+    /// Sets the amount of fuel in this [`Store`].
+    ///
+    /// If fuel consumption is enabled in the [`EngineConfig`](crate::EngineConfig) of the
+    /// [`Engine`], executing Wasm code consumes fuel, roughly one unit per instruction. Once the
+    /// fuel runs out, execution traps with [`Trap::OutOfFuel`]. The fuel is only checked at
+    /// function entries and loop headers, so a call can run slightly past its budget.
+    ///
+    /// A new [`Store`] starts with [`u64::MAX`] fuel.
+    pub fn set_fuel(&mut self, fuel: u64) {
+        self.fuel = fuel;
+    }
+
+    /// Returns an [`InterruptHandle`] for this [`Store`].
+    ///
+    /// Interruption must be enabled in the [`EngineConfig`](crate::EngineConfig) of the
+    /// [`Engine`] for the handle to have any effect.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        InterruptHandle {
+            interrupted: self.interrupted.clone(),
+        }
+    }
+
+    /// Consumes the given amount of fuel, or checks for a pending interrupt.
+    ///
+    /// Called by the checkpoints that are emitted into compiled code.
+    pub(crate) fn checkpoint(&mut self, cost: u64) -> Result<(), Trap> {
+        if self.engine.config().has_interruptible()
+            && self.interrupted.swap(false, Ordering::Relaxed)
+        {
+            return Err(Trap::Interrupted);
+        }
+        if self.fuel < cost {
+            self.fuel = 0;
+            return Err(Trap::OutOfFuel);
+        }
+        self.fuel -= cost;
+        Ok(())
+    }
+
     pub(crate) fn id(&self) -> StoreId {
         self.id
     }
@@ -138,6 +191,24 @@ impl Store {
     }
 }
 
+/// A handle for interrupting Wasm code that is executing in a [`Store`].
+///
+/// An [`InterruptHandle`] can be sent to other threads. Calling [`InterruptHandle::interrupt`]
+/// causes the Wasm code that is currently executing in the [`Store`] to trap with
+/// [`Trap::Interrupted`] at its next function entry or loop header. If no Wasm code is currently
+/// executing, the next call into the [`Store`] is interrupted instead.
+#[derive(Clone, Debug)]
+pub struct InterruptHandle {
+    interrupted: Arc<AtomicBool>,
+}
+
+impl InterruptHandle {
+    /// Interrupts the Wasm code that is executing in the [`Store`].
+    pub fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::Relaxed);
+    }
+}
+
 /// A unique identifier for a [`Store`].
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct StoreId(usize);
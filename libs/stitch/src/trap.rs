@@ -11,6 +11,8 @@ pub enum Trap {
     TableAccessOutOfBounds,
     MemAccessOutOfBounds,
     StackOverflow,
+    OutOfFuel,
+    Interrupted,
 }
 
 impl Trap {
@@ -25,6 +27,8 @@ impl Trap {
             6 => Some(Self::TableAccessOutOfBounds),
             7 => Some(Self::MemAccessOutOfBounds),
             8 => Some(Self::StackOverflow),
+            9 => Some(Self::OutOfFuel),
+            10 => Some(Self::Interrupted),
             _ => None,
         }
     }
@@ -46,6 +50,8 @@ impl fmt::Display for Trap {
             Self::TableAccessOutOfBounds => write!(f, "table access out of bounds"),
             Self::MemAccessOutOfBounds => write!(f, "memory access out of bounds"),
             Self::StackOverflow => write!(f, "stack overflow"),
+            Self::OutOfFuel => write!(f, "out of fuel"),
+            Self::Interrupted => write!(f, "interrupted"),
         }
     }
 }
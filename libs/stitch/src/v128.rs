@@ -0,0 +1,88 @@
+use {
+    crate::decode::{Decode, DecodeError, Decoder},
+    std::fmt,
+};
+
+/// A 128-bit vector value.
+///
+/// A [`V128`] is an opaque 128-bit value that can be interpreted as a vector of lanes of various
+/// shapes. Lanes are stored in little-endian order, so lane 0 occupies the lowest bits.
+#[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+#[repr(transparent)]
+pub struct V128(u128);
+
+impl V128 {
+    /// Creates a [`V128`] from its bit representation.
+    pub fn from_bits(bits: u128) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the bit representation of this [`V128`].
+    pub fn to_bits(self) -> u128 {
+        self.0
+    }
+
+    /// Creates a [`V128`] from its little-endian byte representation.
+    pub fn from_le_bytes(bytes: [u8; 16]) -> Self {
+        Self(u128::from_le_bytes(bytes))
+    }
+
+    /// Returns the little-endian byte representation of this [`V128`].
+    pub fn to_le_bytes(self) -> [u8; 16] {
+        self.0.to_le_bytes()
+    }
+}
+
+impl Decode for V128 {
+    fn decode(decoder: &mut Decoder<'_>) -> Result<Self, DecodeError> {
+        Ok(Self::from_le_bytes(
+            decoder.read_bytes(16)?.try_into().unwrap(),
+        ))
+    }
+}
+
+impl fmt::Debug for V128 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "V128({:#034x})", self.0)
+    }
+}
+
+macro_rules! impl_lanes {
+    ($T:ty, $N:literal) => {
+        impl From<[$T; $N]> for V128 {
+            fn from(lanes: [$T; $N]) -> Self {
+                const SIZE: usize = 16 / $N;
+
+                let mut bytes = [0; 16];
+                for (chunk, lane) in bytes.chunks_exact_mut(SIZE).zip(lanes) {
+                    chunk.copy_from_slice(&lane.to_le_bytes());
+                }
+                Self::from_le_bytes(bytes)
+            }
+        }
+
+        impl From<V128> for [$T; $N] {
+            fn from(val: V128) -> Self {
+                const SIZE: usize = 16 / $N;
+
+                let bytes = val.to_le_bytes();
+                std::array::from_fn(|lane_idx| {
+                    let mut lane = [0; SIZE];
+                    lane.copy_from_slice(&bytes[lane_idx * SIZE..][..SIZE]);
+                    <$T>::from_le_bytes(lane)
+                })
+            }
+        }
+    };
+}
+
+impl_lanes!(i8, 16);
+impl_lanes!(u8, 16);
+impl_lanes!(i16, 8);
+impl_lanes!(u16, 8);
+impl_lanes!(i32, 4);
+impl_lanes!(u32, 4);
+impl_lanes!(i64, 2);
+impl_lanes!(u64, 2);
+impl_lanes!(f32, 4);
+impl_lanes!(f64, 2);
@@ -0,0 +1,1089 @@
+//! A host implementation of the WASI preview1 (`wasi_snapshot_preview1`) interface.
+//!
+//! This covers the functions that programs built for `wasm32-wasi` with a stock Rust toolchain
+//! typically import: standard I/O, files in preopened directories, arguments, environment
+//! variables, clocks, random numbers and `proc_exit`. Imports that are not supported are still
+//! defined, but fail with `ENOSYS`, so that modules importing them can be instantiated.
+//!
+//! Guest code can only reach the host file system through the directories that were preopened
+//! with [`WasiConfig::preopen_dir`]. Paths that would escape a preopened directory, either
+//! through `..` components or through symbolic links, are rejected with `ENOTCAPABLE`.
+
+use {
+    crate::{
+        error::Error,
+        func::Func,
+        instance::Instance,
+        linker::{InstantiateError, Linker},
+        mem::Mem,
+        store::Store,
+    },
+    std::{
+        collections::{hash_map::RandomState, BTreeMap},
+        env, fmt,
+        fs::{self, File, OpenOptions},
+        hash::{BuildHasher, Hasher},
+        io::{self, Read, Seek, SeekFrom, Write},
+        path::{Component, Path, PathBuf},
+        sync::{Arc, Mutex},
+        thread,
+        time::{Instant, SystemTime, UNIX_EPOCH},
+    },
+};
+
+/// The name of the module that WASI preview1 functions are imported from.
+pub const WASI_MODULE: &str = "wasi_snapshot_preview1";
+
+/// The configuration of a [`Wasi`] instance.
+#[derive(Clone, Debug, Default)]
+pub struct WasiConfig {
+    args: Vec<String>,
+    envs: Vec<(String, String)>,
+    preopens: Vec<(PathBuf, String)>,
+}
+
+impl WasiConfig {
+    /// Creates a new [`WasiConfig`] with no arguments, no environment variables and no
+    /// preopened directories.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an argument to the arguments passed to the guest.
+    ///
+    /// The first argument is conventionally the name of the program.
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Appends the given arguments to the arguments passed to the guest.
+    pub fn args(mut self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.args.extend(args.into_iter().map(|arg| arg.into()));
+        self
+    }
+
+    /// Sets an environment variable for the guest.
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.envs.push((key.into(), value.into()));
+        self
+    }
+
+    /// Passes all environment variables of the host process on to the guest.
+    pub fn inherit_env(mut self) -> Self {
+        self.envs.extend(env::vars());
+        self
+    }
+
+    /// Makes the host directory at `host_path` available to the guest as `guest_path`.
+    pub fn preopen_dir(
+        mut self,
+        host_path: impl Into<PathBuf>,
+        guest_path: impl Into<String>,
+    ) -> Self {
+        self.preopens.push((host_path.into(), guest_path.into()));
+        self
+    }
+}
+
+/// A WASI preview1 host, shared by all functions it defines in a [`Linker`].
+#[derive(Clone, Debug)]
+pub struct Wasi {
+    state: Arc<Mutex<WasiState>>,
+}
+
+impl Wasi {
+    /// Creates a new [`Wasi`] host with the given [`WasiConfig`].
+    ///
+    /// # Errors
+    ///
+    /// If one of the preopened directories does not exist.
+    pub fn new(config: WasiConfig) -> io::Result<Self> {
+        let mut fds = BTreeMap::new();
+        fds.insert(0, Fd::Stdin);
+        fds.insert(1, Fd::Stdout);
+        fds.insert(2, Fd::Stderr);
+        for (host_path, guest_path) in config.preopens {
+            let root = fs::canonicalize(&host_path)?;
+            if !root.is_dir() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} is not a directory", host_path.display()),
+                ));
+            }
+            let fd = fds.len() as u32;
+            fds.insert(
+                fd,
+                Fd::Dir {
+                    path: root.clone(),
+                    root,
+                    preopen: Some(guest_path),
+                },
+            );
+        }
+        Ok(Self {
+            state: Arc::new(Mutex::new(WasiState {
+                args: config.args,
+                envs: config
+                    .envs
+                    .into_iter()
+                    .map(|(key, value)| format!("{}={}", key, value))
+                    .collect(),
+                fds,
+                mem: MemSlot(None),
+                start_time: Instant::now(),
+            })),
+        })
+    }
+
+    /// Defines all WASI preview1 functions in the given [`Linker`].
+    pub fn define(&self, store: &mut Store, linker: &mut Linker) {
+        macro_rules! define {
+            ($name:ident($($arg:ident: $T:ty),*)) => {{
+                let state = self.state.clone();
+                linker.define(
+                    WASI_MODULE,
+                    stringify!($name),
+                    Func::wrap(store, move |store: &mut Store, $($arg: $T),*| -> u32 {
+                        match state.lock().unwrap().$name(store, $($arg),*) {
+                            Ok(()) => ERRNO_SUCCESS,
+                            Err(errno) => errno.0,
+                        }
+                    }),
+                );
+            }};
+        }
+
+        macro_rules! unsupported {
+            ($name:ident($($T:ty),*)) => {
+                linker.define(
+                    WASI_MODULE,
+                    stringify!($name),
+                    Func::wrap(store, |$(_: $T),*| -> u32 { ERRNO_NOSYS }),
+                );
+            };
+        }
+
+        define!(args_get(argv: u32, argv_buf: u32));
+        define!(args_sizes_get(argc: u32, argv_buf_size: u32));
+        define!(environ_get(environ: u32, environ_buf: u32));
+        define!(environ_sizes_get(environc: u32, environ_buf_size: u32));
+        define!(clock_res_get(id: u32, resolution: u32));
+        define!(clock_time_get(id: u32, precision: u64, time: u32));
+        define!(fd_close(fd: u32));
+        define!(fd_datasync(fd: u32));
+        define!(fd_fdstat_get(fd: u32, stat: u32));
+        define!(fd_fdstat_set_flags(fd: u32, flags: u32));
+        define!(fd_filestat_get(fd: u32, stat: u32));
+        define!(fd_filestat_set_size(fd: u32, size: u64));
+        define!(fd_pread(fd: u32, iovs: u32, iovs_len: u32, offset: u64, nread: u32));
+        define!(fd_prestat_get(fd: u32, prestat: u32));
+        define!(fd_prestat_dir_name(fd: u32, path: u32, path_len: u32));
+        define!(fd_pwrite(fd: u32, iovs: u32, iovs_len: u32, offset: u64, nwritten: u32));
+        define!(fd_read(fd: u32, iovs: u32, iovs_len: u32, nread: u32));
+        define!(fd_readdir(fd: u32, buf: u32, buf_len: u32, cookie: u64, bufused: u32));
+        define!(fd_seek(fd: u32, offset: u64, whence: u32, newoffset: u32));
+        define!(fd_sync(fd: u32));
+        define!(fd_tell(fd: u32, offset: u32));
+        define!(fd_write(fd: u32, iovs: u32, iovs_len: u32, nwritten: u32));
+        define!(path_create_directory(fd: u32, path: u32, path_len: u32));
+        define!(path_filestat_get(fd: u32, flags: u32, path: u32, path_len: u32, stat: u32));
+        define!(path_open(
+            fd: u32,
+            dirflags: u32,
+            path: u32,
+            path_len: u32,
+            oflags: u32,
+            rights_base: u64,
+            rights_inheriting: u64,
+            fdflags: u32,
+            opened_fd: u32
+        ));
+        define!(path_remove_directory(fd: u32, path: u32, path_len: u32));
+        define!(path_rename(
+            fd: u32,
+            old_path: u32,
+            old_path_len: u32,
+            new_fd: u32,
+            new_path: u32,
+            new_path_len: u32
+        ));
+        define!(path_unlink_file(fd: u32, path: u32, path_len: u32));
+        define!(random_get(buf: u32, buf_len: u32));
+
+        linker.define(
+            WASI_MODULE,
+            "proc_exit",
+            Func::wrap(store, |code: u32| -> Result<(), Error> {
+                Err(Error::Exit(code as i32))
+            }),
+        );
+        linker.define(
+            WASI_MODULE,
+            "sched_yield",
+            Func::wrap(store, || -> u32 {
+                thread::yield_now();
+                ERRNO_SUCCESS
+            }),
+        );
+
+        unsupported!(fd_advise(u32, u64, u64, u32));
+        unsupported!(fd_allocate(u32, u64, u64));
+        unsupported!(fd_fdstat_set_rights(u32, u64, u64));
+        unsupported!(fd_filestat_set_times(u32, u64, u64, u32));
+        unsupported!(fd_renumber(u32, u32));
+        unsupported!(path_filestat_set_times(u32, u32, u32, u32, u64, u64, u32));
+        unsupported!(path_link(u32, u32, u32, u32, u32, u32, u32));
+        unsupported!(path_readlink(u32, u32, u32, u32, u32, u32));
+        unsupported!(path_symlink(u32, u32, u32, u32, u32));
+        unsupported!(poll_oneoff(u32, u32, u32, u32));
+        unsupported!(proc_raise(u32));
+        unsupported!(sock_accept(u32, u32, u32));
+        unsupported!(sock_recv(u32, u32, u32, u32, u32, u32));
+        unsupported!(sock_send(u32, u32, u32, u32, u32));
+        unsupported!(sock_shutdown(u32, u32));
+    }
+
+    /// Sets the [`Mem`] that WASI functions read their arguments from and write their results to.
+    ///
+    /// This is usually the memory exported by the instance as `memory`.
+    pub fn set_mem(&self, mem: Mem) {
+        self.state.lock().unwrap().mem = MemSlot(Some(mem));
+    }
+
+    /// Runs the given WASI command [`Instance`] by calling its `_start` function.
+    ///
+    /// Returns the exit code of the command: either the code it passed to `proc_exit`, or 0 if
+    /// `_start` returned normally.
+    ///
+    /// # Errors
+    ///
+    /// - If the [`Instance`] does not export a `_start` function.
+    /// - If `_start` traps.
+    pub fn run(&self, store: &mut Store, instance: &Instance) -> Result<i32, Error> {
+        if let Some(mem) = instance.exported_mem("memory") {
+            self.set_mem(mem);
+        }
+        let start = instance
+            .exported_func("_start")
+            .ok_or(InstantiateError::DefNotFound)?;
+        match start.call(store, &[], &mut []) {
+            Ok(()) => Ok(0),
+            Err(Error::Exit(code)) => Ok(code),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+const ERRNO_SUCCESS: u32 = 0;
+const ERRNO_NOSYS: u32 = 52;
+
+const CLOCKID_REALTIME: u32 = 0;
+const CLOCKID_MONOTONIC: u32 = 1;
+const CLOCKID_PROCESS_CPUTIME_ID: u32 = 2;
+const CLOCKID_THREAD_CPUTIME_ID: u32 = 3;
+
+const FILETYPE_UNKNOWN: u8 = 0;
+const FILETYPE_CHARACTER_DEVICE: u8 = 2;
+const FILETYPE_DIRECTORY: u8 = 3;
+const FILETYPE_REGULAR_FILE: u8 = 4;
+const FILETYPE_SYMBOLIC_LINK: u8 = 7;
+
+const FDFLAGS_APPEND: u32 = 1 << 0;
+
+const LOOKUPFLAGS_SYMLINK_FOLLOW: u32 = 1 << 0;
+
+const OFLAGS_CREAT: u32 = 1 << 0;
+const OFLAGS_DIRECTORY: u32 = 1 << 1;
+const OFLAGS_EXCL: u32 = 1 << 2;
+const OFLAGS_TRUNC: u32 = 1 << 3;
+
+const RIGHTS_FD_READ: u64 = 1 << 1;
+const RIGHTS_FD_WRITE: u64 = 1 << 6;
+const RIGHTS_ALL: u64 = (1 << 30) - 1;
+
+const WHENCE_SET: u32 = 0;
+const WHENCE_CUR: u32 = 1;
+const WHENCE_END: u32 = 2;
+
+/// A WASI error number.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Errno(u32);
+
+impl Errno {
+    const ACCES: Self = Self(2);
+    const BADF: Self = Self(8);
+    const EXIST: Self = Self(20);
+    const FAULT: Self = Self(21);
+    const INVAL: Self = Self(28);
+    const IO: Self = Self(29);
+    const ISDIR: Self = Self(31);
+    const LOOP: Self = Self(32);
+    const NOENT: Self = Self(44);
+    const NOTDIR: Self = Self(54);
+    const NOTEMPTY: Self = Self(55);
+    const NOTSUP: Self = Self(58);
+    const SPIPE: Self = Self(70);
+    const NOTCAPABLE: Self = Self(76);
+}
+
+impl From<io::Error> for Errno {
+    fn from(error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::NotFound => Errno::NOENT,
+            io::ErrorKind::PermissionDenied => Errno::ACCES,
+            io::ErrorKind::AlreadyExists => Errno::EXIST,
+            io::ErrorKind::InvalidInput => Errno::INVAL,
+            io::ErrorKind::Unsupported => Errno::NOTSUP,
+            _ => Errno::IO,
+        }
+    }
+}
+
+/// An entry in the file descriptor table of a [`Wasi`] host.
+#[derive(Debug)]
+enum Fd {
+    Stdin,
+    Stdout,
+    Stderr,
+    File {
+        file: File,
+        append: bool,
+    },
+    Dir {
+        path: PathBuf,
+        root: PathBuf,
+        preopen: Option<String>,
+    },
+}
+
+/// The [`Mem`] of the instance that a [`Wasi`] host is attached to.
+struct MemSlot(Option<Mem>);
+
+// SAFETY: A `Mem` is only a handle. Its bytes can only be accessed through the `Store` that owns
+// it, which is passed to every WASI function.
+unsafe impl Send for MemSlot {}
+
+impl fmt::Debug for MemSlot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[derive(Debug)]
+struct WasiState {
+    args: Vec<String>,
+    envs: Vec<String>,
+    fds: BTreeMap<u32, Fd>,
+    mem: MemSlot,
+    start_time: Instant,
+}
+
+impl WasiState {
+    fn mem<'a>(&self, store: &'a mut Store) -> Result<GuestMem<'a>, Errno> {
+        match self.mem.0 {
+            Some(mem) => Ok(GuestMem(mem.bytes_mut(store))),
+            None => Err(Errno::FAULT),
+        }
+    }
+
+    fn args_get(&mut self, store: &mut Store, argv: u32, argv_buf: u32) -> Result<(), Errno> {
+        write_strings(&mut self.mem(store)?, &self.args, argv, argv_buf)
+    }
+
+    fn args_sizes_get(
+        &mut self,
+        store: &mut Store,
+        argc: u32,
+        argv_buf_size: u32,
+    ) -> Result<(), Errno> {
+        write_string_sizes(&mut self.mem(store)?, &self.args, argc, argv_buf_size)
+    }
+
+    fn environ_get(
+        &mut self,
+        store: &mut Store,
+        environ: u32,
+        environ_buf: u32,
+    ) -> Result<(), Errno> {
+        write_strings(&mut self.mem(store)?, &self.envs, environ, environ_buf)
+    }
+
+    fn environ_sizes_get(
+        &mut self,
+        store: &mut Store,
+        environc: u32,
+        environ_buf_size: u32,
+    ) -> Result<(), Errno> {
+        write_string_sizes(
+            &mut self.mem(store)?,
+            &self.envs,
+            environc,
+            environ_buf_size,
+        )
+    }
+
+    fn clock_res_get(&mut self, store: &mut Store, id: u32, resolution: u32) -> Result<(), Errno> {
+        match id {
+            CLOCKID_REALTIME
+            | CLOCKID_MONOTONIC
+            | CLOCKID_PROCESS_CPUTIME_ID
+            | CLOCKID_THREAD_CPUTIME_ID => self.mem(store)?.write_u64(resolution, 1),
+            _ => Err(Errno::INVAL),
+        }
+    }
+
+    fn clock_time_get(
+        &mut self,
+        store: &mut Store,
+        id: u32,
+        _precision: u64,
+        time: u32,
+    ) -> Result<(), Errno> {
+        let nanos = match id {
+            CLOCKID_REALTIME => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|_| Errno::IO)?
+                .as_nanos() as u64,
+            // We have no portable way to measure CPU time, so the CPU time clocks measure the
+            // time since the host was created instead.
+            CLOCKID_MONOTONIC | CLOCKID_PROCESS_CPUTIME_ID | CLOCKID_THREAD_CPUTIME_ID => {
+                self.start_time.elapsed().as_nanos() as u64
+            }
+            _ => return Err(Errno::INVAL),
+        };
+        self.mem(store)?.write_u64(time, nanos)
+    }
+
+    fn fd_close(&mut self, _store: &mut Store, fd: u32) -> Result<(), Errno> {
+        match self.fds.get(&fd) {
+            Some(Fd::Dir {
+                preopen: Some(_), ..
+            }) => Err(Errno::NOTSUP),
+            Some(_) => {
+                self.fds.remove(&fd);
+                Ok(())
+            }
+            None => Err(Errno::BADF),
+        }
+    }
+
+    fn fd_datasync(&mut self, _store: &mut Store, fd: u32) -> Result<(), Errno> {
+        match self.fds.get_mut(&fd) {
+            Some(Fd::File { file, .. }) => Ok(file.sync_data()?),
+            Some(_) => Err(Errno::INVAL),
+            None => Err(Errno::BADF),
+        }
+    }
+
+    fn fd_fdstat_get(&mut self, store: &mut Store, fd: u32, stat: u32) -> Result<(), Errno> {
+        let (filetype, flags) = match self.fds.get(&fd).ok_or(Errno::BADF)? {
+            Fd::Stdin | Fd::Stdout | Fd::Stderr => (FILETYPE_CHARACTER_DEVICE, 0),
+            Fd::File { append, .. } => (
+                FILETYPE_REGULAR_FILE,
+                if *append { FDFLAGS_APPEND } else { 0 },
+            ),
+            Fd::Dir { .. } => (FILETYPE_DIRECTORY, 0),
+        };
+        let mut mem = self.mem(store)?;
+        mem.slice_mut(stat, 24)?.fill(0);
+        mem.write_u8(stat, filetype)?;
+        mem.write_u16(stat + 2, flags as u16)?;
+        mem.write_u64(stat + 8, RIGHTS_ALL)?;
+        mem.write_u64(stat + 16, RIGHTS_ALL)
+    }
+
+    fn fd_fdstat_set_flags(
+        &mut self,
+        _store: &mut Store,
+        fd: u32,
+        flags: u32,
+    ) -> Result<(), Errno> {
+        match self.fds.get_mut(&fd) {
+            Some(Fd::File { append, .. }) if flags & !FDFLAGS_APPEND == 0 => {
+                *append = flags & FDFLAGS_APPEND != 0;
+                Ok(())
+            }
+            Some(_) => Err(Errno::NOTSUP),
+            None => Err(Errno::BADF),
+        }
+    }
+
+    fn fd_filestat_get(&mut self, store: &mut Store, fd: u32, stat: u32) -> Result<(), Errno> {
+        let metadata = match self.fds.get(&fd).ok_or(Errno::BADF)? {
+            Fd::Stdin | Fd::Stdout | Fd::Stderr => None,
+            Fd::File { file, .. } => Some(file.metadata()?),
+            Fd::Dir { path, .. } => Some(fs::metadata(path)?),
+        };
+        let mut mem = self.mem(store)?;
+        match metadata {
+            Some(metadata) => write_filestat(&mut mem, stat, &metadata),
+            None => {
+                mem.slice_mut(stat, 64)?.fill(0);
+                mem.write_u8(stat + 16, FILETYPE_CHARACTER_DEVICE)
+            }
+        }
+    }
+
+    fn fd_filestat_set_size(
+        &mut self,
+        _store: &mut Store,
+        fd: u32,
+        size: u64,
+    ) -> Result<(), Errno> {
+        match self.fds.get_mut(&fd) {
+            Some(Fd::File { file, .. }) => Ok(file.set_len(size)?),
+            Some(_) => Err(Errno::INVAL),
+            None => Err(Errno::BADF),
+        }
+    }
+
+    fn fd_pread(
+        &mut self,
+        store: &mut Store,
+        fd: u32,
+        iovs: u32,
+        iovs_len: u32,
+        offset: u64,
+        nread: u32,
+    ) -> Result<(), Errno> {
+        let file = self.file(fd)?;
+        let position = file.stream_position()?;
+        file.seek(SeekFrom::Start(offset))?;
+        let result = self.fd_read(store, fd, iovs, iovs_len, nread);
+        self.file(fd)?.seek(SeekFrom::Start(position))?;
+        result
+    }
+
+    fn fd_prestat_get(&mut self, store: &mut Store, fd: u32, prestat: u32) -> Result<(), Errno> {
+        let name_len = match self.fds.get(&fd).ok_or(Errno::BADF)? {
+            Fd::Dir {
+                preopen: Some(name),
+                ..
+            } => name.len() as u32,
+            _ => return Err(Errno::BADF),
+        };
+        let mut mem = self.mem(store)?;
+        // The only kind of preopen is a directory, which has tag 0.
+        mem.write_u32(prestat, 0)?;
+        mem.write_u32(offset(prestat, 4)?, name_len)
+    }
+
+    fn fd_prestat_dir_name(
+        &mut self,
+        store: &mut Store,
+        fd: u32,
+        path: u32,
+        path_len: u32,
+    ) -> Result<(), Errno> {
+        let Some(Fd::Dir {
+            preopen: Some(name),
+            ..
+        }) = self.fds.get(&fd)
+        else {
+            return Err(Errno::BADF);
+        };
+        if name.len() > path_len as usize {
+            return Err(Errno::INVAL);
+        }
+        let name = name.clone();
+        self.mem(store)?
+            .slice_mut(path, name.len() as u32)?
+            .copy_from_slice(name.as_bytes());
+        Ok(())
+    }
+
+    fn fd_pwrite(
+        &mut self,
+        store: &mut Store,
+        fd: u32,
+        iovs: u32,
+        iovs_len: u32,
+        offset: u64,
+        nwritten: u32,
+    ) -> Result<(), Errno> {
+        let file = self.file(fd)?;
+        let position = file.stream_position()?;
+        file.seek(SeekFrom::Start(offset))?;
+        let result = self.fd_write(store, fd, iovs, iovs_len, nwritten);
+        self.file(fd)?.seek(SeekFrom::Start(position))?;
+        result
+    }
+
+    fn fd_read(
+        &mut self,
+        store: &mut Store,
+        fd: u32,
+        iovs: u32,
+        iovs_len: u32,
+        nread: u32,
+    ) -> Result<(), Errno> {
+        let Some(mem) = self.mem.0 else {
+            return Err(Errno::FAULT);
+        };
+        let mut mem = GuestMem(mem.bytes_mut(store));
+        let mut stdin = io::stdin();
+        let reader: &mut dyn Read = match self.fds.get_mut(&fd).ok_or(Errno::BADF)? {
+            Fd::Stdin => &mut stdin,
+            Fd::File { file, .. } => file,
+            Fd::Dir { .. } => return Err(Errno::ISDIR),
+            Fd::Stdout | Fd::Stderr => return Err(Errno::BADF),
+        };
+        let mut total = 0u32;
+        for index in 0..iovs_len {
+            let iov = offset(iovs, index.checked_mul(8).ok_or(Errno::FAULT)?)?;
+            let buf = mem.read_u32(iov)?;
+            let buf_len = mem.read_u32(offset(iov, 4)?)?;
+            let count = reader.read(mem.slice_mut(buf, buf_len)?)? as u32;
+            total = total.checked_add(count).ok_or(Errno::INVAL)?;
+            // A short read means that there is no more data available right now.
+            if count < buf_len {
+                break;
+            }
+        }
+        mem.write_u32(nread, total)
+    }
+
+    fn fd_readdir(
+        &mut self,
+        store: &mut Store,
+        fd: u32,
+        buf: u32,
+        buf_len: u32,
+        cookie: u64,
+        bufused: u32,
+    ) -> Result<(), Errno> {
+        let path = match self.fds.get(&fd).ok_or(Errno::BADF)? {
+            Fd::Dir { path, .. } => path.clone(),
+            _ => return Err(Errno::NOTDIR),
+        };
+        let mut entries = vec![
+            (".".to_string(), FILETYPE_DIRECTORY),
+            ("..".to_string(), FILETYPE_DIRECTORY),
+        ];
+        for entry in fs::read_dir(&path)? {
+            let entry = entry?;
+            let filetype = entry.file_type().map(filetype).unwrap_or(FILETYPE_UNKNOWN);
+            entries.push((entry.file_name().to_string_lossy().into_owned(), filetype));
+        }
+        // Sort the entries so that cookies stay valid between calls.
+        entries[2..].sort();
+
+        // Each entry is a 24 byte header followed by its name. If the buffer fills up, the last
+        // entry is truncated, which tells the guest to call us again with a larger buffer.
+        let mut mem = self.mem(store)?;
+        let mut out = Vec::new();
+        for (index, (name, filetype)) in entries.iter().enumerate().skip(cookie as usize) {
+            out.extend_from_slice(&(index as u64 + 1).to_le_bytes());
+            out.extend_from_slice(&0u64.to_le_bytes());
+            out.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            out.extend_from_slice(&[*filetype, 0, 0, 0]);
+            out.extend_from_slice(name.as_bytes());
+            if out.len() >= buf_len as usize {
+                break;
+            }
+        }
+        let used = out.len().min(buf_len as usize) as u32;
+        mem.slice_mut(buf, used)?
+            .copy_from_slice(&out[..used as usize]);
+        mem.write_u32(bufused, used)
+    }
+
+    fn fd_seek(
+        &mut self,
+        store: &mut Store,
+        fd: u32,
+        offset: u64,
+        whence: u32,
+        newoffset: u32,
+    ) -> Result<(), Errno> {
+        let pos = match whence {
+            WHENCE_SET => SeekFrom::Start(offset),
+            WHENCE_CUR => SeekFrom::Current(offset as i64),
+            WHENCE_END => SeekFrom::End(offset as i64),
+            _ => return Err(Errno::INVAL),
+        };
+        let position = self.file(fd)?.seek(pos)?;
+        self.mem(store)?.write_u64(newoffset, position)
+    }
+
+    fn fd_sync(&mut self, _store: &mut Store, fd: u32) -> Result<(), Errno> {
+        match self.fds.get_mut(&fd) {
+            Some(Fd::File { file, .. }) => Ok(file.sync_all()?),
+            Some(_) => Err(Errno::INVAL),
+            None => Err(Errno::BADF),
+        }
+    }
+
+    fn fd_tell(&mut self, store: &mut Store, fd: u32, offset: u32) -> Result<(), Errno> {
+        let position = self.file(fd)?.stream_position()?;
+        self.mem(store)?.write_u64(offset, position)
+    }
+
+    fn fd_write(
+        &mut self,
+        store: &mut Store,
+        fd: u32,
+        iovs: u32,
+        iovs_len: u32,
+        nwritten: u32,
+    ) -> Result<(), Errno> {
+        let Some(mem) = self.mem.0 else {
+            return Err(Errno::FAULT);
+        };
+        let mut mem = GuestMem(mem.bytes_mut(store));
+        let mut stdout = io::stdout();
+        let mut stderr = io::stderr();
+        let writer: &mut dyn Write = match self.fds.get_mut(&fd).ok_or(Errno::BADF)? {
+            Fd::Stdout => &mut stdout,
+            Fd::Stderr => &mut stderr,
+            Fd::File { file, append, .. } => {
+                if *append {
+                    file.seek(SeekFrom::End(0))?;
+                }
+                file
+            }
+            Fd::Dir { .. } => return Err(Errno::ISDIR),
+            Fd::Stdin => return Err(Errno::BADF),
+        };
+        let mut total = 0u32;
+        for index in 0..iovs_len {
+            let iov = offset(iovs, index.checked_mul(8).ok_or(Errno::FAULT)?)?;
+            let buf = mem.read_u32(iov)?;
+            let buf_len = mem.read_u32(offset(iov, 4)?)?;
+            // The count we report has to fit, so check before writing anything more.
+            total = total.checked_add(buf_len).ok_or(Errno::INVAL)?;
+            writer.write_all(mem.slice(buf, buf_len)?)?;
+        }
+        writer.flush()?;
+        mem.write_u32(nwritten, total)
+    }
+
+    fn path_create_directory(
+        &mut self,
+        store: &mut Store,
+        fd: u32,
+        path: u32,
+        path_len: u32,
+    ) -> Result<(), Errno> {
+        let (path, _) = self.resolve(store, fd, path, path_len, false)?;
+        Ok(fs::create_dir(path)?)
+    }
+
+    fn path_filestat_get(
+        &mut self,
+        store: &mut Store,
+        fd: u32,
+        flags: u32,
+        path: u32,
+        path_len: u32,
+        stat: u32,
+    ) -> Result<(), Errno> {
+        let follow = flags & LOOKUPFLAGS_SYMLINK_FOLLOW != 0;
+        let (path, _) = self.resolve(store, fd, path, path_len, follow)?;
+        let metadata = fs::symlink_metadata(path)?;
+        write_filestat(&mut self.mem(store)?, stat, &metadata)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn path_open(
+        &mut self,
+        store: &mut Store,
+        fd: u32,
+        dirflags: u32,
+        path: u32,
+        path_len: u32,
+        oflags: u32,
+        rights_base: u64,
+        _rights_inheriting: u64,
+        fdflags: u32,
+        opened_fd: u32,
+    ) -> Result<(), Errno> {
+        let follow = dirflags & LOOKUPFLAGS_SYMLINK_FOLLOW != 0;
+        let (path, root) = self.resolve(store, fd, path, path_len, follow)?;
+        // the host would follow the link when opening it
+        if !follow && fs::symlink_metadata(&path).is_ok_and(|metadata| metadata.is_symlink()) {
+            return Err(Errno::LOOP);
+        }
+        let new_fd = if oflags & OFLAGS_DIRECTORY != 0 || path.is_dir() {
+            if oflags & (OFLAGS_CREAT | OFLAGS_TRUNC) != 0 {
+                return Err(Errno::ISDIR);
+            }
+            if !path.is_dir() {
+                return Err(if path.exists() {
+                    Errno::NOTDIR
+                } else {
+                    Errno::NOENT
+                });
+            }
+            Fd::Dir {
+                path,
+                root,
+                preopen: None,
+            }
+        } else {
+            let append = fdflags & FDFLAGS_APPEND != 0;
+            let write = rights_base & RIGHTS_FD_WRITE != 0 || append;
+            let file = OpenOptions::new()
+                .read(rights_base & RIGHTS_FD_READ != 0 || !write)
+                .write(write)
+                .create(oflags & OFLAGS_CREAT != 0 && oflags & OFLAGS_EXCL == 0)
+                .create_new(oflags & OFLAGS_CREAT != 0 && oflags & OFLAGS_EXCL != 0)
+                .truncate(oflags & OFLAGS_TRUNC != 0)
+                .open(path)?;
+            Fd::File { file, append }
+        };
+        let mut fd = 3;
+        while self.fds.contains_key(&fd) {
+            fd += 1;
+        }
+        self.mem(store)?.write_u32(opened_fd, fd)?;
+        self.fds.insert(fd, new_fd);
+        Ok(())
+    }
+
+    fn path_remove_directory(
+        &mut self,
+        store: &mut Store,
+        fd: u32,
+        path: u32,
+        path_len: u32,
+    ) -> Result<(), Errno> {
+        let (path, root) = self.resolve(store, fd, path, path_len, false)?;
+        if path == root {
+            return Err(Errno::NOTCAPABLE);
+        }
+        if fs::read_dir(&path)?.next().is_some() {
+            return Err(Errno::NOTEMPTY);
+        }
+        Ok(fs::remove_dir(path)?)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn path_rename(
+        &mut self,
+        store: &mut Store,
+        fd: u32,
+        old_path: u32,
+        old_path_len: u32,
+        new_fd: u32,
+        new_path: u32,
+        new_path_len: u32,
+    ) -> Result<(), Errno> {
+        let (old_path, _) = self.resolve(store, fd, old_path, old_path_len, false)?;
+        let (new_path, _) = self.resolve(store, new_fd, new_path, new_path_len, false)?;
+        Ok(fs::rename(old_path, new_path)?)
+    }
+
+    fn path_unlink_file(
+        &mut self,
+        store: &mut Store,
+        fd: u32,
+        path: u32,
+        path_len: u32,
+    ) -> Result<(), Errno> {
+        let (path, _) = self.resolve(store, fd, path, path_len, false)?;
+        if fs::symlink_metadata(&path)?.is_dir() {
+            return Err(Errno::ISDIR);
+        }
+        Ok(fs::remove_file(path)?)
+    }
+
+    fn random_get(&mut self, store: &mut Store, buf: u32, buf_len: u32) -> Result<(), Errno> {
+        fill_random(self.mem(store)?.slice_mut(buf, buf_len)?);
+        Ok(())
+    }
+
+    fn file(&mut self, fd: u32) -> Result<&mut File, Errno> {
+        match self.fds.get_mut(&fd).ok_or(Errno::BADF)? {
+            Fd::File { file, .. } => Ok(file),
+            Fd::Dir { .. } => Err(Errno::ISDIR),
+            _ => Err(Errno::SPIPE),
+        }
+    }
+
+    /// Resolves a guest path relative to the directory `fd` to a host path.
+    ///
+    /// Returns the host path, together with the root of the preopened directory it lives in. A
+    /// symbolic link at the end of the path is only followed if `follow` is set.
+    fn resolve(
+        &self,
+        store: &mut Store,
+        fd: u32,
+        path: u32,
+        path_len: u32,
+        follow: bool,
+    ) -> Result<(PathBuf, PathBuf), Errno> {
+        let (dir, root) = match self.fds.get(&fd).ok_or(Errno::BADF)? {
+            Fd::Dir { path, root, .. } => (path, root),
+            _ => return Err(Errno::NOTDIR),
+        };
+        let mem = self.mem(store)?;
+        let path = std::str::from_utf8(mem.slice(path, path_len)?).map_err(|_| Errno::INVAL)?;
+        let host_path = resolve_path(dir, root, Path::new(path), follow)?;
+        Ok((host_path, root.clone()))
+    }
+}
+
+/// Joins `path` onto `dir`, making sure that the result does not escape `root`.
+///
+/// The result has all symbolic links resolved, except for one at the end of the path when
+/// `follow` is not set, so that it's the link itself that gets renamed or removed.
+fn resolve_path(dir: &Path, root: &Path, path: &Path, follow: bool) -> Result<PathBuf, Errno> {
+    // First resolve `.` and `..` components lexically.
+    let mut host_path = dir.to_path_buf();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if host_path == root || !host_path.pop() {
+                    return Err(Errno::NOTCAPABLE);
+                }
+            }
+            Component::Normal(name) => host_path.push(name),
+            Component::RootDir | Component::Prefix(_) => return Err(Errno::NOTCAPABLE),
+        }
+    }
+
+    // Then resolve the symbolic links along the way, and make sure that none of them point
+    // outside the root. The last component may not exist yet, because we're about to create it.
+    let real_path = match (host_path.parent(), host_path.file_name()) {
+        (Some(parent), Some(name)) if host_path != root => fs::canonicalize(parent)?.join(name),
+        _ => fs::canonicalize(&host_path)?,
+    };
+    if !real_path.starts_with(root) {
+        return Err(Errno::NOTCAPABLE);
+    }
+    if !follow || !fs::symlink_metadata(&real_path).is_ok_and(|metadata| metadata.is_symlink()) {
+        return Ok(real_path);
+    }
+
+    // A link that points to nothing is not followed, as creating its target could create a file
+    // outside the root.
+    let real_path = fs::canonicalize(&real_path)?;
+    if !real_path.starts_with(root) {
+        return Err(Errno::NOTCAPABLE);
+    }
+    Ok(real_path)
+}
+
+/// Writes a list of strings as an array of pointers to nul-terminated strings.
+fn write_strings(
+    mem: &mut GuestMem,
+    strings: &[String],
+    mut ptrs: u32,
+    mut buf: u32,
+) -> Result<(), Errno> {
+    for string in strings {
+        mem.write_u32(ptrs, buf)?;
+        let len = u32::try_from(string.len() + 1).map_err(|_| Errno::INVAL)?;
+        let bytes = mem.slice_mut(buf, len)?;
+        bytes[..string.len()].copy_from_slice(string.as_bytes());
+        bytes[string.len()] = 0;
+        ptrs = offset(ptrs, 4)?;
+        buf = offset(buf, len)?;
+    }
+    Ok(())
+}
+
+/// Writes the number of strings in a list, and the total size of their nul-terminated bytes.
+fn write_string_sizes(
+    mem: &mut GuestMem,
+    strings: &[String],
+    count: u32,
+    buf_size: u32,
+) -> Result<(), Errno> {
+    mem.write_u32(count, strings.len() as u32)?;
+    mem.write_u32(
+        buf_size,
+        strings.iter().map(|string| string.len() as u32 + 1).sum(),
+    )
+}
+
+fn write_filestat(mem: &mut GuestMem, stat: u32, metadata: &fs::Metadata) -> Result<(), Errno> {
+    fn nanos(time: io::Result<SystemTime>) -> u64 {
+        time.ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_nanos() as u64)
+    }
+
+    mem.slice_mut(stat, 64)?.fill(0);
+    mem.write_u8(stat + 16, filetype(metadata.file_type()))?;
+    mem.write_u64(stat + 24, 1)?;
+    mem.write_u64(stat + 32, metadata.len())?;
+    mem.write_u64(stat + 40, nanos(metadata.accessed()))?;
+    mem.write_u64(stat + 48, nanos(metadata.modified()))?;
+    mem.write_u64(stat + 56, nanos(metadata.modified()))
+}
+
+fn filetype(file_type: fs::FileType) -> u8 {
+    if file_type.is_dir() {
+        FILETYPE_DIRECTORY
+    } else if file_type.is_file() {
+        FILETYPE_REGULAR_FILE
+    } else if file_type.is_symlink() {
+        FILETYPE_SYMBOLIC_LINK
+    } else {
+        FILETYPE_UNKNOWN
+    }
+}
+
+fn fill_random(bytes: &mut [u8]) {
+    if let Ok(mut file) = File::open("/dev/urandom") {
+        if file.read_exact(bytes).is_ok() {
+            return;
+        }
+    }
+    // Without /dev/urandom we fall back on `RandomState`. Its keys come from the OS only once per
+    // thread and are then counted up, so this gives distinct bytes but not unpredictable ones.
+    for chunk in bytes.chunks_mut(8) {
+        let random = RandomState::new().build_hasher().finish().to_le_bytes();
+        chunk.copy_from_slice(&random[..chunk.len()]);
+    }
+}
+
+/// A pointer `offset` bytes past `ptr`, a fault when that is outside the 32-bit address space.
+fn offset(ptr: u32, offset: u32) -> Result<u32, Errno> {
+    ptr.checked_add(offset).ok_or(Errno::FAULT)
+}
+
+/// The bytes of the memory of a guest, with bounds checked accessors.
+struct GuestMem<'a>(&'a mut [u8]);
+
+impl GuestMem<'_> {
+    fn slice(&self, ptr: u32, len: u32) -> Result<&[u8], Errno> {
+        let start = ptr as usize;
+        let end = start.checked_add(len as usize).ok_or(Errno::FAULT)?;
+        self.0.get(start..end).ok_or(Errno::FAULT)
+    }
+
+    fn slice_mut(&mut self, ptr: u32, len: u32) -> Result<&mut [u8], Errno> {
+        let start = ptr as usize;
+        let end = start.checked_add(len as usize).ok_or(Errno::FAULT)?;
+        self.0.get_mut(start..end).ok_or(Errno::FAULT)
+    }
+
+    fn read_u32(&self, ptr: u32) -> Result<u32, Errno> {
+        Ok(u32::from_le_bytes(self.slice(ptr, 4)?.try_into().unwrap()))
+    }
+
+    fn write_u8(&mut self, ptr: u32, val: u8) -> Result<(), Errno> {
+        self.slice_mut(ptr, 1)?[0] = val;
+        Ok(())
+    }
+
+    fn write_u16(&mut self, ptr: u32, val: u16) -> Result<(), Errno> {
+        self.slice_mut(ptr, 2)?.copy_from_slice(&val.to_le_bytes());
+        Ok(())
+    }
+
+    fn write_u32(&mut self, ptr: u32, val: u32) -> Result<(), Errno> {
+        self.slice_mut(ptr, 4)?.copy_from_slice(&val.to_le_bytes());
+        Ok(())
+    }
+
+    fn write_u64(&mut self, ptr: u32, val: u64) -> Result<(), Errno> {
+        self.slice_mut(ptr, 8)?.copy_from_slice(&val.to_le_bytes());
+        Ok(())
+    }
+}
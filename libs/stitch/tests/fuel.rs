@@ -0,0 +1,93 @@
+use {
+    makepad_stitch::{Engine, EngineConfig, Error, Instance, Linker, Module, Store, Trap, Val},
+    std::{thread, time::Duration},
+    wast::{parser, parser::ParseBuffer, Wat},
+};
+
+const WAT: &str = r#"
+(module
+    (func (export "spin")
+        (loop $loop
+            (br $loop)
+        )
+    )
+    (func (export "count") (param $n i32) (result i32)
+        (local $i i32)
+        (loop $loop
+            (local.set $i (i32.add (local.get $i) (i32.const 1)))
+            (br_if $loop (i32.lt_u (local.get $i) (local.get $n)))
+        )
+        (local.get $i)
+    )
+)
+"#;
+
+fn instantiate(config: EngineConfig) -> (Store, Instance) {
+    let buf = ParseBuffer::new(WAT).unwrap();
+    let mut wat = parser::parse::<Wat>(&buf).unwrap();
+    let bytes = wat.encode().unwrap();
+    let mut store = Store::new(Engine::with_config(config));
+    let module = Module::new(store.engine(), &bytes).unwrap();
+    let instance = Linker::new().instantiate(&mut store, &module).unwrap();
+    (store, instance)
+}
+
+fn count(store: &mut Store, instance: &Instance, n: i32) -> Result<i32, Error> {
+    let func = instance.exported_func("count").unwrap();
+    let mut results = [Val::I32(0)];
+    func.call(store, &[Val::I32(n)], &mut results)?;
+    Ok(results[0].to_i32().unwrap())
+}
+
+#[test]
+fn infinite_loop_runs_out_of_fuel() {
+    let (mut store, instance) = instantiate(EngineConfig::new().consume_fuel(true));
+    store.set_fuel(10_000);
+    let spin = instance.exported_func("spin").unwrap();
+    let result = spin.call(&mut store, &[], &mut []);
+    assert!(matches!(result, Err(Error::Trap(Trap::OutOfFuel))));
+    assert_eq!(store.fuel(), 0);
+}
+
+#[test]
+fn fuel_is_consumed_per_iteration() {
+    let (mut store, instance) = instantiate(EngineConfig::new().consume_fuel(true));
+    store.set_fuel(1_000_000);
+    assert_eq!(count(&mut store, &instance, 100).unwrap(), 100);
+    let used = 1_000_000 - store.fuel();
+    assert!(used >= 100 * 9, "used {} fuel", used);
+
+    // Running out of fuel leaves the store usable once it is refueled.
+    store.set_fuel(100);
+    assert!(matches!(
+        count(&mut store, &instance, 1_000),
+        Err(Error::Trap(Trap::OutOfFuel))
+    ));
+    store.set_fuel(1_000_000);
+    assert_eq!(count(&mut store, &instance, 1_000).unwrap(), 1_000);
+}
+
+#[test]
+fn fuel_is_not_consumed_by_default() {
+    let (mut store, instance) = instantiate(EngineConfig::new());
+    store.set_fuel(10);
+    assert_eq!(count(&mut store, &instance, 1_000).unwrap(), 1_000);
+    assert_eq!(store.fuel(), 10);
+}
+
+#[test]
+fn interrupt_from_another_thread() {
+    let (mut store, instance) = instantiate(EngineConfig::new().interruptible(true));
+    let handle = store.interrupt_handle();
+    let thread = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        handle.interrupt();
+    });
+    let spin = instance.exported_func("spin").unwrap();
+    let result = spin.call(&mut store, &[], &mut []);
+    thread.join().unwrap();
+    assert!(matches!(result, Err(Error::Trap(Trap::Interrupted))));
+
+    // The interrupt is consumed, so the next call runs normally.
+    assert_eq!(count(&mut store, &instance, 10).unwrap(), 10);
+}
@@ -0,0 +1,187 @@
+#![cfg(feature = "wasi")]
+
+use {
+    makepad_stitch::{
+        wasi::{Wasi, WasiConfig},
+        Engine, Linker, Module, Store,
+    },
+    std::{env, fs, path::PathBuf, process},
+    wast::{parser, parser::ParseBuffer, Wat},
+};
+
+const ERRNO_NOTCAPABLE: i32 = 76;
+
+fn run(wat: &str, config: WasiConfig) -> (i32, Vec<u8>) {
+    let buf = ParseBuffer::new(wat).unwrap();
+    let mut wat = parser::parse::<Wat>(&buf).unwrap();
+    let bytes = wat.encode().unwrap();
+    let mut store = Store::new(Engine::new());
+    let module = Module::new(store.engine(), &bytes).unwrap();
+    let mut linker = Linker::new();
+    let wasi = Wasi::new(config).unwrap();
+    wasi.define(&mut store, &mut linker);
+    let instance = linker.instantiate(&mut store, &module).unwrap();
+    let code = wasi.run(&mut store, &instance).unwrap();
+    let mem = instance.exported_mem("memory").unwrap();
+    (code, mem.bytes(&store).to_vec())
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("stitch_wasi_{}_{}", name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn args_and_environ() {
+    let (code, mem) = run(
+        r#"
+        (module
+            (import "wasi_snapshot_preview1" "args_sizes_get" (func $args_sizes_get (param i32 i32) (result i32)))
+            (import "wasi_snapshot_preview1" "args_get" (func $args_get (param i32 i32) (result i32)))
+            (import "wasi_snapshot_preview1" "environ_get" (func $environ_get (param i32 i32) (result i32)))
+            (import "wasi_snapshot_preview1" "proc_exit" (func $proc_exit (param i32)))
+            (memory (export "memory") 1)
+            (func (export "_start")
+                (drop (call $args_sizes_get (i32.const 0) (i32.const 4)))
+                (drop (call $args_get (i32.const 16) (i32.const 64)))
+                (drop (call $environ_get (i32.const 32) (i32.const 128)))
+                (call $proc_exit (i32.load (i32.const 0)))
+            )
+        )
+        "#,
+        WasiConfig::new()
+            .args(["prog", "hello"])
+            .env("KEY", "value"),
+    );
+    assert_eq!(code, 2);
+    assert_eq!(&mem[4..8], &11u32.to_le_bytes());
+    assert_eq!(&mem[16..20], &64u32.to_le_bytes());
+    assert_eq!(&mem[20..24], &69u32.to_le_bytes());
+    assert_eq!(&mem[64..75], b"prog\0hello\0");
+    assert_eq!(&mem[128..138], b"KEY=value\0");
+}
+
+#[test]
+fn start_returning_exits_with_zero() {
+    let (code, _) = run(
+        r#"
+        (module
+            (memory (export "memory") 1)
+            (func (export "_start"))
+        )
+        "#,
+        WasiConfig::new(),
+    );
+    assert_eq!(code, 0);
+}
+
+#[test]
+fn writes_file_in_preopened_dir() {
+    let dir = temp_dir("write");
+    let (code, mem) = run(
+        r#"
+        (module
+            (import "wasi_snapshot_preview1" "fd_prestat_get" (func $fd_prestat_get (param i32 i32) (result i32)))
+            (import "wasi_snapshot_preview1" "fd_prestat_dir_name" (func $fd_prestat_dir_name (param i32 i32 i32) (result i32)))
+            (import "wasi_snapshot_preview1" "path_open" (func $path_open (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))
+            (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+            (import "wasi_snapshot_preview1" "fd_close" (func $fd_close (param i32) (result i32)))
+            (import "wasi_snapshot_preview1" "proc_exit" (func $proc_exit (param i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 100) "out.txt")
+            (data (i32.const 200) "hello wasi")
+            (data (i32.const 300) "\c8\00\00\00\0a\00\00\00")
+            (func (export "_start")
+                (local $errno i32)
+                (drop (call $fd_prestat_get (i32.const 3) (i32.const 0)))
+                (drop (call $fd_prestat_dir_name (i32.const 3) (i32.const 400) (i32.load (i32.const 4))))
+                (local.set $errno (call $path_open
+                    (i32.const 3) (i32.const 0) (i32.const 100) (i32.const 7)
+                    (i32.const 9) (i64.const 64) (i64.const 0) (i32.const 0) (i32.const 16)))
+                (if (local.get $errno) (then (call $proc_exit (local.get $errno))))
+                (local.set $errno (call $fd_write (i32.load (i32.const 16)) (i32.const 300) (i32.const 1) (i32.const 20)))
+                (if (local.get $errno) (then (call $proc_exit (local.get $errno))))
+                (call $proc_exit (call $fd_close (i32.load (i32.const 16))))
+            )
+        )
+        "#,
+        WasiConfig::new().preopen_dir(&dir, "sandbox"),
+    );
+    assert_eq!(code, 0);
+    assert_eq!(&mem[4..8], &7u32.to_le_bytes());
+    assert_eq!(&mem[400..407], b"sandbox");
+    assert_eq!(&mem[20..24], &10u32.to_le_bytes());
+    assert_eq!(
+        fs::read_to_string(dir.join("out.txt")).unwrap(),
+        "hello wasi"
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn rejects_paths_outside_preopened_dir() {
+    let dir = temp_dir("escape");
+    for path in ["../escape.txt", "sub/../../escape.txt", "/etc/passwd"] {
+        let wat = format!(
+            r#"
+            (module
+                (import "wasi_snapshot_preview1" "path_open" (func $path_open (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))
+                (import "wasi_snapshot_preview1" "proc_exit" (func $proc_exit (param i32)))
+                (memory (export "memory") 1)
+                (data (i32.const 100) "{}")
+                (func (export "_start")
+                    (call $proc_exit (call $path_open
+                        (i32.const 3) (i32.const 0) (i32.const 100) (i32.const {})
+                        (i32.const 1) (i64.const 64) (i64.const 0) (i32.const 0) (i32.const 16)))
+                )
+            )
+            "#,
+            path,
+            path.len()
+        );
+        let (code, _) = run(&wat, WasiConfig::new().preopen_dir(&dir, "."));
+        assert_eq!(code, ERRNO_NOTCAPABLE, "{}", path);
+    }
+    assert!(!dir.parent().unwrap().join("escape.txt").exists());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn does_not_follow_symlinks_outside_preopened_dir() {
+    let dir = temp_dir("symlink");
+    let outside = temp_dir("symlink_outside");
+    fs::write(outside.join("existing.txt"), "secret").unwrap();
+    std::os::unix::fs::symlink(outside.join("existing.txt"), dir.join("existing")).unwrap();
+    std::os::unix::fs::symlink(outside.join("created.txt"), dir.join("dangling")).unwrap();
+    // with and without following the link at the end, creating the file if it's missing
+    for path in ["existing", "dangling"] {
+        for dirflags in [0, 1] {
+            let wat = format!(
+                r#"
+                (module
+                    (import "wasi_snapshot_preview1" "path_open" (func $path_open (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))
+                    (import "wasi_snapshot_preview1" "proc_exit" (func $proc_exit (param i32)))
+                    (memory (export "memory") 1)
+                    (data (i32.const 100) "{}")
+                    (func (export "_start")
+                        (call $proc_exit (call $path_open
+                            (i32.const 3) (i32.const {}) (i32.const 100) (i32.const {})
+                            (i32.const 1) (i64.const 64) (i64.const 0) (i32.const 0) (i32.const 16)))
+                    )
+                )
+                "#,
+                path,
+                dirflags,
+                path.len()
+            );
+            let (code, _) = run(&wat, WasiConfig::new().preopen_dir(&dir, "."));
+            assert_ne!(code, 0, "{} {}", path, dirflags);
+        }
+    }
+    assert!(!outside.join("created.txt").exists());
+    fs::remove_dir_all(dir).unwrap();
+    fs::remove_dir_all(outside).unwrap();
+}
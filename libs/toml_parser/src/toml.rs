@@ -1,56 +1,94 @@
-use std::collections::{HashMap};
-use std::str::Chars;
+use std::collections::HashMap;
 
-#[derive(Default)]
-pub struct TomlParser {
-    pub cur: char,
-    pub pos: usize,
-}
-
-#[derive(PartialEq, Debug, Clone)]
+/// A range of characters in the source, `start` is a 0 based char index.
+#[derive(PartialEq, Debug, Clone, Default)]
 pub struct TomlSpan {
     pub start: usize,
     pub len: usize
 }
 
-#[derive(PartialEq, Debug)]
-pub struct TomlTokWithSpan {
-    pub span: TomlSpan,
-    pub tok: TomlTok
-}
-
-#[derive(PartialEq, Debug)]
-pub enum TomlTok {
-    Ident(String),
-    Str(String),
-    U64(u64),
-    I64(i64),
-    F64(f64),
-    Bool(bool),
-    Nan(bool),
-    Inf(bool),
-    Date(String),
-    Equals,
-    BlockOpen,
-    BlockClose,
-    ObjectOpen,
-    ObjectClose,
-    Comma,
-    Bof,
-    Eof
-}
-
-
 #[derive(PartialEq, Debug, Clone)]
 pub enum Toml {
     Str(String, TomlSpan),
     Bool(bool, TomlSpan),
+    /// A float
     Num(f64, TomlSpan),
+    Int(i64, TomlSpan),
+    /// An offset/local date-time, local date or local time as written in the source
     Date(String, TomlSpan),
-    Array(Vec<Toml>),
+    Array(Vec<Toml>, TomlSpan),
+    Table(HashMap<String, Toml>, TomlSpan),
 }
 
 impl Toml {
+    pub fn span(&self) -> &TomlSpan {
+        match self {
+            Self::Str(_, span) | Self::Bool(_, span) | Self::Num(_, span) | Self::Int(_, span)
+                | Self::Date(_, span) | Self::Array(_, span) | Self::Table(_, span) => span
+        }
+    }
+
+    /// Looks up a key in a table
+    pub fn get(&self, key: &str) -> Option<&Toml> {
+        if let Self::Table(table, _) = self {
+            return table.get(key)
+        }
+        None
+    }
+
+    /// Looks up a value through nested tables, `toml.path(&["package", "version"])`
+    pub fn path(&self, path: &[&str]) -> Option<&Toml> {
+        let mut toml = self;
+        for key in path {
+            toml = toml.get(key) ?;
+        }
+        Some(toml)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Str(v, _) => Some(v),
+            _ => None
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(v, _) => Some(*v),
+            _ => None
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Int(v, _) => Some(*v),
+            _ => None
+        }
+    }
+
+    /// Returns floats as well as integers
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Num(v, _) => Some(*v),
+            Self::Int(v, _) => Some(*v as f64),
+            _ => None
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Toml>> {
+        match self {
+            Self::Array(v, _) => Some(v),
+            _ => None
+        }
+    }
+
+    pub fn as_table(&self) -> Option<&HashMap<String, Toml>> {
+        match self {
+            Self::Table(v, _) => Some(v),
+            _ => None
+        }
+    }
+
     pub fn into_str(self) -> Option<String> {
         match self {
             Self::Str(v, _) => Some(v),
@@ -70,328 +108,653 @@ impl std::fmt::Debug for TomlErr {
     }
 }
 
-pub fn parse_toml(data: &str) -> Result<HashMap<String, Toml>, TomlErr> {
-    let i = &mut data.chars();
-    let mut t = TomlParser::default();
-    t.next(i);
-    let mut out = HashMap::new();
-    let mut local_scope = String::new();
+/// Parses a TOML 1.0 document into a tree of tables, the returned value is always a `Toml::Table`
+pub fn parse_toml(data: &str) -> Result<Toml, TomlErr> {
+    let mut t = TomlParser {
+        chars: data.chars().collect(),
+        pos: 0
+    };
+    if t.cur() == '\u{feff}' {
+        t.next();
+    }
+    let mut root = TomlTable::new(TableKind::Header, TomlSpan {start: 0, len: t.chars.len()});
+    let mut scope = Vec::new();
     loop {
-        let tok = t.next_tok(i) ?;
-        match tok.tok {
-            TomlTok::Eof => { // at eof.
-                return Ok(out);
-            },
-            TomlTok::BlockOpen => { // its a scope
-                // we should expect an ident or a string
-                let tok = t.next_tok(i) ?;
-                let (tok, double_block) = if let TomlTok::BlockOpen = tok.tok {
-                    (t.next_tok(i) ?, true)
-                }
-                else {(tok, false)};
-                
-                match tok.tok {
-                    TomlTok::Str(key) => { // a key
-                        local_scope = key;
-                    },
-                    TomlTok::Ident(key) => { // also a key
-                        local_scope = key;
-                    },
-                    _ => return Err(t.err_token(tok))
-                }
-                let tok = t.next_tok(i) ?;
-                
-                if tok.tok != TomlTok::BlockClose {
-                    return Err(t.err_token(tok))
-                }
-                if double_block {
-                    let tok = t.next_tok(i) ?;
-                    if tok.tok != TomlTok::BlockClose {
-                        return Err(t.err_token(tok))
-                    }
+        t.skip_ws();
+        match t.cur() {
+            '\0' if t.at_end() => {
+                return Ok(root.into_toml());
+            }
+            '#' | '\r' | '\n' => (),
+            '[' => {
+                scope = t.parse_header(&mut root) ?;
+            }
+            _ => {
+                let table = root.scope_mut(&scope);
+                t.parse_key_value(table) ?;
+            }
+        }
+        t.skip_ws();
+        t.skip_comment() ?;
+        if !t.eat_newline() && !t.at_end() {
+            return Err(t.err_parse("expected newline"));
+        }
+    }
+}
+
+// how a table came to be, this decides if it can be defined or extended later on
+#[derive(PartialEq, Clone, Copy)]
+enum TableKind {
+    // created as the parent of a [a.b] header
+    Implicit,
+    // defined by a [header], or the root
+    Header,
+    // created by a dotted key a.b = 1
+    Dotted,
+    // an inline table, these are stored as values so they can never be extended
+    Inline,
+}
+
+struct TomlTable {
+    kind: TableKind,
+    span: TomlSpan,
+    entries: HashMap<String, TomlEntry>,
+}
+
+enum TomlEntry {
+    Value(Toml),
+    Table(TomlTable),
+    ArrayOfTables(Vec<TomlTable>, TomlSpan),
+}
+
+impl TomlTable {
+    fn new(kind: TableKind, span: TomlSpan) -> Self {
+        Self {kind, span, entries: HashMap::new()}
+    }
+
+    // the table a [header] or [[header]] points to
+    fn scope_mut(&mut self, scope: &[String]) -> &mut TomlTable {
+        let mut table = self;
+        for key in scope {
+            table = match table.entries.get_mut(key) {
+                Some(TomlEntry::Table(table)) => table,
+                Some(TomlEntry::ArrayOfTables(tables, _)) => tables.last_mut().unwrap(),
+                _ => unreachable!()
+            };
+        }
+        table
+    }
+
+    fn into_toml(self) -> Toml {
+        let mut out = HashMap::new();
+        for (key, entry) in self.entries {
+            out.insert(key, match entry {
+                TomlEntry::Value(value) => value,
+                TomlEntry::Table(table) => table.into_toml(),
+                TomlEntry::ArrayOfTables(tables, span) => {
+                    Toml::Array(tables.into_iter().map( | table | table.into_toml()).collect(), span)
                 }
-            },
-            TomlTok::Str(key) => { // a key
-                t.parse_key_value(&local_scope, key, i, &mut out) ?;
-            },
-            TomlTok::Ident(key) => { // also a key
-                t.parse_key_value(&local_scope, key, i, &mut out) ?;
-            },
-            _ => return Err(t.err_token(tok))
+            });
         }
+        Toml::Table(out, self.span)
     }
 }
 
+struct TomlParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
 impl TomlParser {
-    pub fn to_val(&mut self, tok: TomlTokWithSpan, i: &mut Chars) -> Result<Toml, TomlErr> {
-        match tok.tok {
-            TomlTok::BlockOpen => {
-                let mut vals = Vec::new();
-                loop {
-                    let tok = self.next_tok(i) ?;
-                    match tok.tok {
-                        TomlTok::BlockClose | TomlTok::Eof => {
-                            break;
-                        }
-                        TomlTok::Comma => {}
-                        _ => {
-                            vals.push(self.to_val(tok, i) ?);
-                        }
-                    }
-                }
-                Ok(Toml::Array(vals))
-            },
-            TomlTok::Str(v) => Ok(Toml::Str(v, tok.span)),
-            TomlTok::U64(v) => Ok(Toml::Num(v as f64, tok.span)),
-            TomlTok::I64(v) => Ok(Toml::Num(v as f64, tok.span)),
-            TomlTok::F64(v) => Ok(Toml::Num(v, tok.span)),
-            TomlTok::Bool(v) => Ok(Toml::Bool(v, tok.span)),
-            TomlTok::Nan(v) => Ok(Toml::Num(if v {-std::f64::NAN}else {std::f64::NAN}, tok.span)),
-            TomlTok::Inf(v) => Ok(Toml::Num(if v {-std::f64::INFINITY}else {std::f64::INFINITY}, tok.span)),
-            TomlTok::Date(v) => Ok(Toml::Date(v, tok.span)),
-            _ => Err(self.err_token(tok))
-        }
-    }
-    
-    pub fn parse_key_value(&mut self, local_scope: &str, key: String, i: &mut Chars, out: &mut HashMap<String, Toml>) -> Result<(), TomlErr> {
-        let tok = self.next_tok(i) ?;
-        if tok.tok != TomlTok::Equals {
-            return Err(self.err_token(tok));
-        }
-        let tok = self.next_tok(i) ?;
-        // if we are an ObjectOpen we do a subscope
-        if let TomlTok::ObjectOpen = tok.tok {
-            let local_scope = if !local_scope.is_empty() {
-                format!("{}.{}", local_scope, key)
+    fn at_end(&self) -> bool {
+        self.pos >= self.chars.len()
+    }
+
+    fn cur(&self) -> char {
+        self.peek(0)
+    }
+
+    fn peek(&self, offset: usize) -> char {
+        *self.chars.get(self.pos + offset).unwrap_or(&'\0')
+    }
+
+    fn next(&mut self) {
+        if self.pos < self.chars.len() {
+            self.pos += 1;
+        }
+    }
+
+    fn span_from(&self, start: usize) -> TomlSpan {
+        TomlSpan {start, len: self.pos - start}
+    }
+
+    fn err_parse(&self, what: &str) -> TomlErr {
+        TomlErr {msg: format!("Cannot parse toml {} ", what), span: TomlSpan {start: self.pos, len: 0}}
+    }
+
+    fn err_span(&self, what: &str, span: TomlSpan) -> TomlErr {
+        TomlErr {msg: what.to_string(), span}
+    }
+
+    fn is_control(c: char) -> bool {
+        c != '\t' && (c < ' ' || c == '\u{7f}')
+    }
+
+    fn skip_ws(&mut self) {
+        while self.cur() == ' ' || self.cur() == '\t' {
+            self.next();
+        }
+    }
+
+    fn skip_comment(&mut self) -> Result<(), TomlErr> {
+        if self.cur() != '#' {
+            return Ok(())
+        }
+        while !self.at_end() && self.cur() != '\n' {
+            if Self::is_control(self.cur()) && !(self.cur() == '\r' && self.peek(1) == '\n') {
+                return Err(self.err_parse("control character in comment"));
             }
-            else {
-                key
+            self.next();
+        }
+        Ok(())
+    }
+
+    fn eat_newline(&mut self) -> bool {
+        if self.cur() == '\n' {
+            self.next();
+            return true
+        }
+        if self.cur() == '\r' && self.peek(1) == '\n' {
+            self.next();
+            self.next();
+            return true
+        }
+        false
+    }
+
+    // whitespace, comments and newlines as found between array values
+    fn skip_ws_comment_newline(&mut self) -> Result<(), TomlErr> {
+        loop {
+            self.skip_ws();
+            self.skip_comment() ?;
+            if !self.eat_newline() {
+                return Ok(())
+            }
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), TomlErr> {
+        if self.cur() != c {
+            return Err(self.err_parse(&format!("expected {:?}", c)));
+        }
+        self.next();
+        Ok(())
+    }
+
+    // parses a [table] or [[array.of.tables]] header and returns the scope for the keys that follow
+    fn parse_header(&mut self, root: &mut TomlTable) -> Result<Vec<String>, TomlErr> {
+        let start = self.pos;
+        self.next();
+        let is_array = self.cur() == '[';
+        if is_array {
+            self.next();
+        }
+        self.skip_ws();
+        let keys = self.parse_key() ?;
+        self.skip_ws();
+        self.expect(']') ?;
+        if is_array {
+            self.expect(']') ?;
+        }
+        let span = self.span_from(start);
+
+        let mut table = root;
+        let (last, parents) = keys.split_last().unwrap();
+        for (key, key_span) in parents {
+            let entry = table.entries.entry(key.clone()).or_insert_with( || {
+                TomlEntry::Table(TomlTable::new(TableKind::Implicit, key_span.clone()))
+            });
+            table = match entry {
+                TomlEntry::Table(table) => table,
+                TomlEntry::ArrayOfTables(tables, _) => tables.last_mut().unwrap(),
+                TomlEntry::Value(_) => return Err(self.err_span(&format!("key {} is not a table", key), key_span.clone()))
             };
-            loop {
-                let tok = self.next_tok(i) ?;
-                match tok.tok {
-                    TomlTok::ObjectClose | TomlTok::Eof => {
-                        break;
-                    }
-                    TomlTok::Comma => {}
-                    TomlTok::Str(key) => { // a key
-                        self.parse_key_value(&local_scope, key, i, out) ?;
-                    },
-                    TomlTok::Ident(key) => { // also a key
-                        self.parse_key_value(&local_scope, key, i, out) ?;
-                    },
-                    _ => return Err(self.err_token(tok))
+        }
+        let (key, _) = last;
+        if is_array {
+            match table.entries.get_mut(key) {
+                None => {
+                    let tables = vec![TomlTable::new(TableKind::Header, span.clone())];
+                    table.entries.insert(key.clone(), TomlEntry::ArrayOfTables(tables, span));
+                }
+                Some(TomlEntry::ArrayOfTables(tables, _)) => {
+                    tables.push(TomlTable::new(TableKind::Header, span));
                 }
+                Some(_) => return Err(self.err_span(&format!("key {} is not an array of tables", key), span))
             }
         }
         else {
-            let val = self.to_val(tok, i) ?;
-            let key = if !local_scope.is_empty() {
-                format!("{}.{}", local_scope, key)
+            match table.entries.get_mut(key) {
+                None => {
+                    table.entries.insert(key.clone(), TomlEntry::Table(TomlTable::new(TableKind::Header, span)));
+                }
+                Some(TomlEntry::Table(table)) if table.kind == TableKind::Implicit => {
+                    table.kind = TableKind::Header;
+                    table.span = span;
+                }
+                Some(_) => return Err(self.err_span(&format!("table {} defined twice", key), span))
             }
-            else {
-                key
-            };
-            out.insert(key, val);
         }
-        Ok(())
+        Ok(keys.into_iter().map( | (key, _) | key).collect())
     }
-    
-    pub fn next(&mut self, i: &mut Chars) {
-        if let Some(c) = i.next() {
-            self.cur = c;
-            self.pos += 1;
+
+    // parses a key = value pair into the table
+    fn parse_key_value(&mut self, table: &mut TomlTable) -> Result<(), TomlErr> {
+        let keys = self.parse_key() ?;
+        self.skip_ws();
+        self.expect('=') ?;
+        self.skip_ws();
+        let value = self.parse_value() ?;
+
+        let mut table = table;
+        let (last, parents) = keys.split_last().unwrap();
+        for (key, key_span) in parents {
+            let entry = table.entries.entry(key.clone()).or_insert_with( || {
+                TomlEntry::Table(TomlTable::new(TableKind::Dotted, key_span.clone()))
+            });
+            table = match entry {
+                TomlEntry::Table(table) if table.kind == TableKind::Dotted => table,
+                _ => return Err(self.err_span(&format!("dotted key cannot extend {}", key), key_span.clone()))
+            };
         }
-        else {
-            self.cur = '\0';
+        let (key, key_span) = last;
+        if table.entries.contains_key(key) {
+            return Err(self.err_span(&format!("key {} defined twice", key), key_span.clone()));
         }
+        table.entries.insert(key.clone(), TomlEntry::Value(value));
+        Ok(())
     }
-    
-    pub fn err_token(&self, tok: TomlTokWithSpan) -> TomlErr {
-        TomlErr {msg: format!("Unexpected token {:?} ", tok), span: tok.span}
-    }
-    
-    pub fn err_parse(&self, what: &str) -> TomlErr {
-        TomlErr {msg: format!("Cannot parse toml {} ", what), span: TomlSpan {start: self.pos, len: 0}}
-    }
-    
-    pub fn next_tok(&mut self, i: &mut Chars) -> Result<TomlTokWithSpan, TomlErr> {
-        
-        while self.cur == '\n' || self.cur == '\r' || self.cur == '\t' || self.cur == ' ' || self.cur == '#' {
-            if self.cur == '#' {
-                while self.cur != '\n' && self.cur != '\0' {
-                    self.next(i);
+
+    // parses a dotted key, a.b."c"
+    fn parse_key(&mut self) -> Result<Vec<(String, TomlSpan)>, TomlErr> {
+        let mut keys = Vec::new();
+        loop {
+            let start = self.pos;
+            let key = match self.cur() {
+                '"' => self.parse_basic_string() ?,
+                '\'' => self.parse_literal_string() ?,
+                _ => {
+                    let mut key = String::new();
+                    while self.cur().is_ascii_alphanumeric() || self.cur() == '_' || self.cur() == '-' {
+                        key.push(self.cur());
+                        self.next();
+                    }
+                    if key.is_empty() {
+                        return Err(self.err_parse("key"));
+                    }
+                    key
                 }
-                continue
+            };
+            keys.push((key, self.span_from(start)));
+            self.skip_ws();
+            if self.cur() != '.' {
+                return Ok(keys)
             }
-            self.next(i);
+            self.next();
+            self.skip_ws();
         }
+    }
+
+    fn parse_value(&mut self) -> Result<Toml, TomlErr> {
         let start = self.pos;
-        match self.cur {
-            '\0' => {
-                Ok(TomlTokWithSpan {tok: TomlTok::Eof, span: TomlSpan {start, len: 0}})
-            }
-            ',' => {
-                self.next(i);
-                Ok(TomlTokWithSpan {tok: TomlTok::Comma, span: TomlSpan {start, len: 1}})
-            }
-            '[' => {
-                self.next(i);
-                Ok(TomlTokWithSpan {tok: TomlTok::BlockOpen, span: TomlSpan {start, len: 1}})
-            }
-            ']' => {
-                self.next(i);
-                Ok(TomlTokWithSpan {tok: TomlTok::BlockClose, span: TomlSpan {start, len: 1}})
-            }
-            '{' => {
-                self.next(i);
-                Ok(TomlTokWithSpan {tok: TomlTok::ObjectOpen, span: TomlSpan {start, len: 1}})
-            }
-            '}' => {
-                self.next(i);
-                Ok(TomlTokWithSpan {tok: TomlTok::ObjectClose, span: TomlSpan {start, len: 1}})
-            }
-            '=' => {
-                self.next(i);
-                Ok(TomlTokWithSpan {tok: TomlTok::Equals, span: TomlSpan {start, len: 1}})
+        match self.cur() {
+            '"' => {
+                let s = if self.peek(1) == '"' && self.peek(2) == '"' {
+                    self.parse_multiline_string('"') ?
+                }
+                else {
+                    self.parse_basic_string() ?
+                };
+                Ok(Toml::Str(s, self.span_from(start)))
             }
-            '+' | '-' | '0'..='9' => {
-                let mut num = String::new();
-                let is_neg = if self.cur == '-' {
-                    num.push(self.cur);
-                    self.next(i);
-                    true
+            '\'' => {
+                let s = if self.peek(1) == '\'' && self.peek(2) == '\'' {
+                    self.parse_multiline_string('\'') ?
                 }
                 else {
-                    if self.cur == '+' {
-                        self.next(i);
-                    }
-                    false
+                    self.parse_literal_string() ?
                 };
-                if self.cur == 'n' {
-                    self.next(i);
-                    if self.cur == 'a' {
-                        self.next(i);
-                        if self.cur == 'n' {
-                            self.next(i);
-                            return Ok(TomlTokWithSpan {tok: TomlTok::Nan(is_neg), span: TomlSpan {start, len: self.pos - start}})
-                        }
-                        else {
-                            return Err(self.err_parse("nan"))
-                        }
-                    }
-                    else {
-                        return Err(self.err_parse("nan"))
+                Ok(Toml::Str(s, self.span_from(start)))
+            }
+            '[' => self.parse_array(),
+            '{' => self.parse_inline_table(),
+            _ => {
+                // numbers, dates and keywords are all a run of these
+                let mut word = String::new();
+                while self.cur().is_ascii_alphanumeric() || matches!(self.cur(), '_' | '+' | '-' | '.' | ':') {
+                    word.push(self.cur());
+                    self.next();
+                    // a date-time can use a space to separate the date and the time
+                    if word.len() == 10 && is_date(&word) && self.cur() == ' ' && self.peek(1).is_ascii_digit() {
+                        word.push(' ');
+                        self.next();
                     }
                 }
-                if self.cur == 'i' {
-                    self.next(i);
-                    if self.cur == 'n' {
-                        self.next(i);
-                        if self.cur == 'f' {
-                            self.next(i);
-                            return Ok(TomlTokWithSpan {tok: TomlTok::Inf(is_neg), span: TomlSpan {start, len: self.pos - start}})
-                        }
-                        else {
-                            return Err(self.err_parse("inf"))
-                        }
-                    }
-                    else {
-                        return Err(self.err_parse("nan"))
-                    }
+                let span = self.span_from(start);
+                match word.as_str() {
+                    "true" => return Ok(Toml::Bool(true, span)),
+                    "false" => return Ok(Toml::Bool(false, span)),
+                    "inf" | "+inf" => return Ok(Toml::Num(f64::INFINITY, span)),
+                    "-inf" => return Ok(Toml::Num(f64::NEG_INFINITY, span)),
+                    "nan" | "+nan" => return Ok(Toml::Num(f64::NAN, span)),
+                    "-nan" => return Ok(Toml::Num(-f64::NAN, span)),
+                    _ => ()
                 }
-                while self.cur >= '0' && self.cur <= '9' || self.cur == '_' {
-                    if self.cur != '_' {
-                        num.push(self.cur);
-                    }
-                    self.next(i);
+                if is_date_time(&word) {
+                    return Ok(Toml::Date(word, span))
                 }
-                if self.cur == '.' {
-                    num.push(self.cur);
-                    self.next(i);
-                    while self.cur >= '0' && self.cur <= '9' || self.cur == '_' {
-                        if self.cur != '_' {
-                            num.push(self.cur);
-                        }
-                        self.next(i);
-                    }
-                    if let Ok(num) = num.parse() {
-                        Ok(TomlTokWithSpan {tok: TomlTok::F64(num), span: TomlSpan {start, len: self.pos - start}})
-                    }
-                    else {
-                        Err(self.err_parse("number"))
-                    }
+                if let Some(v) = parse_int(&word) {
+                    return Ok(Toml::Int(v, span))
                 }
-                else if self.cur == '-' { // lets assume its a date. whatever. i don't feel like more parsing today
-                    num.push(self.cur);
-                    self.next(i);
-                    while self.cur >= '0' && self.cur <= '9' || self.cur == ':' || self.cur == '-' || self.cur == 'T' {
-                        num.push(self.cur);
-                        self.next(i);
-                    }
-                    return Ok(TomlTokWithSpan {tok: TomlTok::Date(num), span: TomlSpan {start, len: self.pos - start}})
+                if let Some(v) = parse_float(&word) {
+                    return Ok(Toml::Num(v, span))
                 }
-                else {
-                    if is_neg {
-                        if let Ok(num) = num.parse() {
-                            return Ok(TomlTokWithSpan {tok: TomlTok::I64(num), span: TomlSpan {start, len: self.pos - start}})
-                        }
-                        else {
-                            return Err(self.err_parse("number"));
-                        }
-                    }
-                    if let Ok(num) = num.parse() {
-                        return Ok(TomlTokWithSpan {tok: TomlTok::U64(num), span: TomlSpan {start, len: self.pos - start}})
-                    }
-                    else {
-                        return Err(self.err_parse("number"));
-                    }
+                Err(self.err_span(&format!("Cannot parse toml value {}", word), span))
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Toml, TomlErr> {
+        let start = self.pos;
+        self.next();
+        let mut vals = Vec::new();
+        loop {
+            self.skip_ws_comment_newline() ?;
+            if self.cur() == ']' {
+                break;
+            }
+            vals.push(self.parse_value() ?);
+            self.skip_ws_comment_newline() ?;
+            if self.cur() == ',' {
+                self.next();
+            }
+            else if self.cur() != ']' {
+                return Err(self.err_parse("array"));
+            }
+        }
+        self.next();
+        Ok(Toml::Array(vals, self.span_from(start)))
+    }
+
+    fn parse_inline_table(&mut self) -> Result<Toml, TomlErr> {
+        let start = self.pos;
+        self.next();
+        let mut table = TomlTable::new(TableKind::Inline, TomlSpan::default());
+        self.skip_ws();
+        if self.cur() != '}' {
+            loop {
+                self.parse_key_value(&mut table) ?;
+                self.skip_ws();
+                if self.cur() == '}' {
+                    break;
                 }
-            },
-            'a'..='z' | 'A'..='Z' | '_' => {
-                let mut ident = String::new();
-                while self.cur >= 'a' && self.cur <= 'z'
-                    || self.cur >= 'A' && self.cur <= 'Z'
-                    || self.cur >= '0' && self.cur <= '9'
-                    || self.cur == '_' || self.cur == '-' || self.cur == '.'
-                    || self.cur == '\'' || self.cur == '(' || self.cur == ')' {
-                    ident.push(self.cur);
-                    self.next(i);
+                self.expect(',') ?;
+                self.skip_ws();
+            }
+        }
+        self.next();
+        table.span = self.span_from(start);
+        Ok(table.into_toml())
+    }
+
+    fn parse_escape(&mut self, out: &mut String) -> Result<(), TomlErr> {
+        // we are past the backslash
+        let c = match self.cur() {
+            'b' => '\u{8}',
+            't' => '\t',
+            'n' => '\n',
+            'f' => '\u{c}',
+            'r' => '\r',
+            '"' => '"',
+            '\\' => '\\',
+            'u' | 'U' => {
+                let len = if self.cur() == 'u' {4} else {8};
+                let mut hex = String::new();
+                for _ in 0..len {
+                    self.next();
+                    hex.push(self.cur());
                 }
-                if ident == "true" {
-                    return Ok(TomlTokWithSpan {tok: TomlTok::Bool(true), span: TomlSpan {start, len: self.pos - start}})
+                u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32).ok_or_else( || self.err_parse("unicode escape")) ?
+            }
+            _ => return Err(self.err_parse("escape"))
+        };
+        self.next();
+        out.push(c);
+        Ok(())
+    }
+
+    fn parse_basic_string(&mut self) -> Result<String, TomlErr> {
+        let mut val = String::new();
+        self.next();
+        while self.cur() != '"' {
+            if self.at_end() || Self::is_control(self.cur()) {
+                return Err(self.err_parse("string"));
+            }
+            if self.cur() == '\\' {
+                self.next();
+                self.parse_escape(&mut val) ?;
+            }
+            else {
+                val.push(self.cur());
+                self.next();
+            }
+        }
+        self.next();
+        Ok(val)
+    }
+
+    fn parse_literal_string(&mut self) -> Result<String, TomlErr> {
+        let mut val = String::new();
+        self.next();
+        while self.cur() != '\'' {
+            if self.at_end() || Self::is_control(self.cur()) {
+                return Err(self.err_parse("string"));
+            }
+            val.push(self.cur());
+            self.next();
+        }
+        self.next();
+        Ok(val)
+    }
+
+    // """basic""" or '''literal''' strings that can span lines
+    fn parse_multiline_string(&mut self, quote: char) -> Result<String, TomlErr> {
+        let mut val = String::new();
+        self.next();
+        self.next();
+        self.next();
+        // a newline right after the opening quotes is trimmed
+        self.eat_newline();
+        loop {
+            if self.at_end() {
+                return Err(self.err_parse("string"));
+            }
+            let c = self.cur();
+            if c == quote {
+                let mut quotes = 0;
+                while self.cur() == quote {
+                    quotes += 1;
+                    self.next();
                 }
-                if ident == "false" {
-                    return Ok(TomlTokWithSpan {tok: TomlTok::Bool(false), span: TomlSpan {start, len: self.pos - start}})
+                if quotes >= 3 {
+                    // up to two quotes can sit right in front of the closing ones
+                    if quotes > 5 {
+                        return Err(self.err_parse("string"));
+                    }
+                    for _ in 3..quotes {
+                        val.push(quote);
+                    }
+                    return Ok(val)
                 }
-                if ident == "inf" {
-                    return Ok(TomlTokWithSpan {tok: TomlTok::Inf(false), span: TomlSpan {start, len: self.pos - start}})
+                for _ in 0..quotes {
+                    val.push(quote);
                 }
-                if ident == "nan" {
-                    return Ok(TomlTokWithSpan {tok: TomlTok::Nan(false), span: TomlSpan {start, len: self.pos - start}})
+            }
+            else if self.eat_newline() {
+                val.push('\n');
+            }
+            else if c == '\\' && quote == '"' {
+                self.next();
+                // a backslash at the end of a line trims all whitespace up to the next content
+                let mut scan = self.pos;
+                while self.chars.get(scan) == Some(&' ') || self.chars.get(scan) == Some(&'\t') {
+                    scan += 1;
                 }
-                Ok(TomlTokWithSpan {tok: TomlTok::Ident(ident), span: TomlSpan {start, len: self.pos - start}})
-            },
-            
-            '"' => {
-                let mut val = String::new();
-                self.next(i);
-                while self.cur != '"' {
-                    if self.cur == '\\' {
-                        self.next(i);
-                    }
-                    if self.cur == '\0' {
+                if self.chars.get(scan) == Some(&'\n') || self.chars.get(scan) == Some(&'\r') {
+                    self.pos = scan;
+                    if !self.eat_newline() {
                         return Err(self.err_parse("string"));
                     }
-                    val.push(self.cur);
-                    self.next(i);
+                    while self.cur() == ' ' || self.cur() == '\t' || self.eat_newline() {
+                        if self.cur() == ' ' || self.cur() == '\t' {
+                            self.next();
+                        }
+                    }
+                }
+                else {
+                    self.parse_escape(&mut val) ?;
                 }
-                self.next(i);
-                Ok(TomlTokWithSpan {tok: TomlTok::Str(val), span: TomlSpan {start, len: self.pos - start}})
-            },
-            _ => {
-                Err(self.err_parse("tokenizer"))
+            }
+            else if Self::is_control(c) {
+                return Err(self.err_parse("string"));
+            }
+            else {
+                val.push(c);
+                self.next();
             }
         }
     }
-}
\ No newline at end of file
+}
+
+// digits with single underscores between them
+fn is_digits(s: &str, radix: u32) -> bool {
+    !s.is_empty() && !s.starts_with('_') && !s.ends_with('_') && !s.contains("__")
+        && s.chars().all( | c | c == '_' || c.is_digit(radix))
+}
+
+// a decimal integer without leading zeroes
+fn is_dec_int(s: &str) -> bool {
+    let s = s.strip_prefix(['+', '-']).unwrap_or(s);
+    is_digits(s, 10) && (s == "0" || !s.starts_with('0'))
+}
+
+fn parse_int(s: &str) -> Option<i64> {
+    for (prefix, radix) in [("0x", 16), ("0o", 8), ("0b", 2)] {
+        if let Some(digits) = s.strip_prefix(prefix) {
+            if !is_digits(digits, radix) {
+                return None
+            }
+            return u64::from_str_radix(&digits.replace('_', ""), radix).ok().and_then( | v | i64::try_from(v).ok())
+        }
+    }
+    if !is_dec_int(s) {
+        return None
+    }
+    s.replace('_', "").parse().ok()
+}
+
+fn parse_float(s: &str) -> Option<f64> {
+    let (mantissa, exp) = match s.find(['e', 'E']) {
+        Some(i) => (&s[..i], Some(&s[i + 1..])),
+        None => (s, None)
+    };
+    let (int, frac) = match mantissa.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (mantissa, None)
+    };
+    if !is_dec_int(int) || (frac.is_none() && exp.is_none()) {
+        return None
+    }
+    if let Some(frac) = frac {
+        if !is_digits(frac, 10) {
+            return None
+        }
+    }
+    if let Some(exp) = exp {
+        if !is_digits(exp.strip_prefix(['+', '-']).unwrap_or(exp), 10) {
+            return None
+        }
+    }
+    s.replace('_', "").parse().ok()
+}
+
+fn digits_in_range(s: &str, min: u32, max: u32) -> bool {
+    s.len() == 2 && s.chars().all( | c | c.is_ascii_digit()) && (min..=max).contains(&s.parse().unwrap())
+}
+
+// YYYY-MM-DD
+fn is_date(s: &str) -> bool {
+    let b = s.as_bytes();
+    if b.len() != 10 || b[4] != b'-' || b[7] != b'-' || !s[..4].chars().all( | c | c.is_ascii_digit()) {
+        return false
+    }
+    if !digits_in_range(&s[5..7], 1, 12) || !digits_in_range(&s[8..10], 1, 31) {
+        return false
+    }
+    let year: u32 = s[..4].parse().unwrap();
+    let month: u32 = s[5..7].parse().unwrap();
+    let day: u32 = s[8..10].parse().unwrap();
+    let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    let days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31
+    };
+    day <= days
+}
+
+// HH:MM:SS with optional fractional seconds
+fn is_time(s: &str) -> bool {
+    let (time, frac) = match s.split_once('.') {
+        Some((time, frac)) => (time, Some(frac)),
+        None => (s, None)
+    };
+    let b = time.as_bytes();
+    if b.len() != 8 || b[2] != b':' || b[5] != b':' {
+        return false
+    }
+    if let Some(frac) = frac {
+        if frac.is_empty() || !frac.chars().all( | c | c.is_ascii_digit()) {
+            return false
+        }
+    }
+    digits_in_range(&time[0..2], 0, 23) && digits_in_range(&time[3..5], 0, 59) && digits_in_range(&time[6..8], 0, 60)
+}
+
+// Z or +HH:MM
+fn is_offset(s: &str) -> bool {
+    if s == "Z" || s == "z" {
+        return true
+    }
+    let b = s.as_bytes();
+    b.len() == 6 && (b[0] == b'+' || b[0] == b'-') && b[3] == b':'
+        && digits_in_range(&s[1..3], 0, 23) && digits_in_range(&s[4..6], 0, 59)
+}
+
+fn is_date_time(s: &str) -> bool {
+    if is_date(s) || is_time(s) {
+        return true
+    }
+    if s.len() < 11 || !is_date(&s[..10]) || !matches!(s.as_bytes()[10], b'T' | b't' | b' ') {
+        return false
+    }
+    let time = &s[11..];
+    if is_time(time) {
+        return true
+    }
+    match time.find(['Z', 'z', '+', '-']) {
+        Some(i) => is_time(&time[..i]) && is_offset(&time[i..]),
+        None => false
+    }
+}
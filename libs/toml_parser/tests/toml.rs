@@ -0,0 +1,133 @@
+// Tests for the TOML parser, one per part of the spec we had to get right.
+
+use makepad_toml_parser::*;
+
+fn parse(data: &str) -> Toml {
+    parse_toml(data).unwrap_or_else( | err | panic!("{:?} in {:?}", err, data))
+}
+
+fn err(data: &str) -> String {
+    parse_toml(data).expect_err(data).msg
+}
+
+#[test]
+fn tables_and_keys() {
+    let toml = parse("title = \"x\"\n[package]\nname = \"app\"\n\"quoted key\" = 1\n[package.metadata]\nauto = true\n");
+    assert_eq!(toml.get("title").unwrap().as_str(), Some("x"));
+    assert_eq!(toml.path(&["package", "name"]).unwrap().as_str(), Some("app"));
+    assert_eq!(toml.path(&["package", "quoted key"]).unwrap().as_i64(), Some(1));
+    assert_eq!(toml.path(&["package", "metadata", "auto"]).unwrap().as_bool(), Some(true));
+}
+
+#[test]
+fn dotted_keys() {
+    let toml = parse("a.b.c = 1\na.b.d = 2\nsite.\"google.com\" = true\n[dependencies]\nserde.version = \"1\"\nserde.features = [\"derive\"]\n");
+    assert_eq!(toml.path(&["a", "b", "c"]).unwrap().as_i64(), Some(1));
+    assert_eq!(toml.path(&["a", "b", "d"]).unwrap().as_i64(), Some(2));
+    assert_eq!(toml.path(&["site", "google.com"]).unwrap().as_bool(), Some(true));
+    assert_eq!(toml.path(&["dependencies", "serde", "version"]).unwrap().as_str(), Some("1"));
+    assert_eq!(toml.path(&["dependencies", "serde", "features"]).unwrap().as_array().unwrap().len(), 1);
+    // a dotted key can't extend a value
+    assert!(err("a = 1\na.b = 2").contains("dotted key cannot extend a"));
+}
+
+#[test]
+fn arrays_of_tables() {
+    let toml = parse("[[bin]]\nname = \"a\"\n[[bin]]\nname = \"b\"\n[bin.extra]\nx = 1\n[[bin.example]]\ny = 2\n");
+    let bins = toml.get("bin").unwrap().as_array().unwrap();
+    assert_eq!(bins.len(), 2);
+    assert_eq!(bins[0].get("name").unwrap().as_str(), Some("a"));
+    assert_eq!(bins[1].get("name").unwrap().as_str(), Some("b"));
+    // sub tables go into the last element
+    assert_eq!(bins[1].path(&["extra", "x"]).unwrap().as_i64(), Some(1));
+    assert_eq!(bins[1].path(&["example"]).unwrap().as_array().unwrap()[0].get("y").unwrap().as_i64(), Some(2));
+    assert!(bins[0].get("extra").is_none());
+}
+
+#[test]
+fn inline_tables_and_arrays() {
+    let toml = parse("dep = {version = \"1\", features = [\"a\", \"b\",], opt.level = 3}\nnested = [[1, 2], [\"x\"],\n  # comment\n]\n");
+    assert_eq!(toml.path(&["dep", "version"]).unwrap().as_str(), Some("1"));
+    assert_eq!(toml.path(&["dep", "features"]).unwrap().as_array().unwrap().len(), 2);
+    assert_eq!(toml.path(&["dep", "opt", "level"]).unwrap().as_i64(), Some(3));
+    let nested = toml.get("nested").unwrap().as_array().unwrap();
+    assert_eq!(nested[0].as_array().unwrap()[1].as_i64(), Some(2));
+    assert_eq!(nested[1].as_array().unwrap()[0].as_str(), Some("x"));
+}
+
+#[test]
+fn strings() {
+    let toml = parse(concat!(
+        "basic = \"tab\\tquote\\\" \\u00e9 \\U0001F600\"\n",
+        "literal = 'C:\\path\\no \"escapes\"'\n",
+        "multi = \"\"\"\nfirst\nsecond \\\n    joined\"\"\"\n",
+        "multi_literal = '''\nraw \\n\n'' quotes'''\n",
+        "quotes = \"\"\"two \"\" inside\"\"\"\"\n",
+    ));
+    assert_eq!(toml.get("basic").unwrap().as_str(), Some("tab\tquote\" é 😀"));
+    assert_eq!(toml.get("literal").unwrap().as_str(), Some("C:\\path\\no \"escapes\""));
+    // the newline right after the opening quotes is trimmed, a backslash at the end of a line joins it
+    assert_eq!(toml.get("multi").unwrap().as_str(), Some("first\nsecond joined"));
+    assert_eq!(toml.get("multi_literal").unwrap().as_str(), Some("raw \\n\n'' quotes"));
+    assert_eq!(toml.get("quotes").unwrap().as_str(), Some("two \"\" inside\""));
+    assert!(parse_toml("bad = \"\\q\"").is_err());
+    assert!(parse_toml("open = \"no end\nx = 1").is_err());
+}
+
+#[test]
+fn numbers() {
+    let toml = parse(concat!(
+        "int = +1_000\nneg = -17\nhex = 0xdead_BEEF\noct = 0o755\nbin = 0b1101\n",
+        "float = 6.626e-34\nexp = 5E+2\nunder = 9_224.5\n",
+        "inf = inf\nninf = -inf\nnan = nan\n",
+    ));
+    assert_eq!(toml.get("int").unwrap().as_i64(), Some(1000));
+    assert_eq!(toml.get("neg").unwrap().as_i64(), Some(-17));
+    assert_eq!(toml.get("hex").unwrap().as_i64(), Some(0xdeadbeef));
+    assert_eq!(toml.get("oct").unwrap().as_i64(), Some(0o755));
+    assert_eq!(toml.get("bin").unwrap().as_i64(), Some(13));
+    assert!(matches!(toml.get("hex").unwrap(), Toml::Int(..)));
+    assert_eq!(toml.get("float").unwrap().as_f64(), Some(6.626e-34));
+    assert_eq!(toml.get("exp").unwrap().as_f64(), Some(500.0));
+    assert_eq!(toml.get("under").unwrap().as_f64(), Some(9224.5));
+    assert!(matches!(toml.get("under").unwrap(), Toml::Num(..)));
+    assert_eq!(toml.get("inf").unwrap().as_f64(), Some(f64::INFINITY));
+    assert_eq!(toml.get("ninf").unwrap().as_f64(), Some(f64::NEG_INFINITY));
+    assert!(toml.get("nan").unwrap().as_f64().unwrap().is_nan());
+    // leading zeros and stray underscores aren't numbers
+    assert!(parse_toml("x = 012").is_err());
+    assert!(parse_toml("x = 1__0").is_err());
+    assert!(parse_toml("x = 0xg").is_err());
+}
+
+#[test]
+fn dates_are_kept_as_written() {
+    let toml = parse("odt = 1979-05-27T07:32:00Z\nld = 1979-05-27\nlt = 07:32:00.999\n");
+    assert_eq!(toml.get("odt"), Some(&Toml::Date("1979-05-27T07:32:00Z".to_string(), toml.get("odt").unwrap().span().clone())));
+    assert!(matches!(toml.get("ld").unwrap(), Toml::Date(date, _) if date == "1979-05-27"));
+    assert!(matches!(toml.get("lt").unwrap(), Toml::Date(time, _) if time == "07:32:00.999"));
+}
+
+#[test]
+fn duplicate_keys_are_errors() {
+    assert!(err("a = 1\na = 2").contains("key a defined twice"));
+    assert!(err("[t]\nx = 1\n[u]\n[t]\ny = 2").contains("table t defined twice"));
+    assert!(err("[t]\nx = 1\n[t.x.y]").contains("key x is not a table"));
+    assert!(err("t = 1\n[[t]]").contains("key t is not an array of tables"));
+    assert!(parse_toml("inline = {a = 1, a = 2}").is_err());
+    // but a super table can be defined after its sub table
+    let toml = parse("[a.b]\nc = 1\n[a]\nd = 2\n");
+    assert_eq!(toml.path(&["a", "b", "c"]).unwrap().as_i64(), Some(1));
+    assert_eq!(toml.path(&["a", "d"]).unwrap().as_i64(), Some(2));
+}
+
+#[test]
+fn errors_point_at_the_source() {
+    let data = "a = 1\nb = 1\nb = 2\n";
+    let err = parse_toml(data).unwrap_err();
+    // the second `b`
+    assert_eq!(err.span.start, 12);
+    assert_eq!(err.span.len, 1);
+    let span = parse("a = 1\nb = 'x'").get("b").unwrap().span().clone();
+    assert_eq!((span.start, span.len), (10, 3));
+}
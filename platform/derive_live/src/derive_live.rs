@@ -167,8 +167,8 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
         
         tb.add("impl").stream(generic.clone());
         tb.add("LiveApplyReset for").ident(&struct_name).stream(generic.clone()).stream(where_clause.clone()).add("{");
-        let walk_fields = ["abs_pos","margin","width","height"];
-        let layout_fields = ["scroll","clip_x","clip_y","padding","align","flow","spacing","line_spacing"];
+        let walk_fields = ["abs_pos","margin","width","height","min_width","max_width","min_height","max_height","fill_weight","grid_cell"];
+        let layout_fields = ["scroll","clip_x","clip_y","padding","align","flow","spacing","line_spacing","grid_columns","grid_rows"];
                 
         tb.add("    fn apply_reset(&mut self, cx: &mut Cx, apply:&mut Apply, start_index:usize, nodes:&[LiveNode]) {");
         
@@ -419,7 +419,7 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
         tb.add("<Self as LiveRegister>::live_register(cx);");
         // we need this here for shader enums to register without hassle
         for field in &fields {
-            if  field.attrs.iter().any( | a | a.name == "live" || a.name == "calc" ||a.name == "deref"){
+            if  field.attrs.iter().any( | a | a.name == "live" || a.name == "calc" ||a.name == "deref" || a.name == "walk" || a.name == "layout"){
                 match unwrap_option(field.ty.clone()) {
                     Ok(inside) => {
                         tb.add("<").stream(Some(inside)).add("as LiveNew>::live_design_with(cx);");
@@ -565,8 +565,16 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
         tb.add("    }");
         
         tb.add("    fn live_design_with(cx: &mut Cx) {");
-        
-        
+
+        // so editors can offer the variants that fit
+        tb.add("        cx.live_registry.borrow_mut().register_enum_variants(LiveType::of::<Self>(), vec![");
+        for item in &items {
+            if let EnumKind::Bare = item.kind {
+                tb.add("LiveId::from_str_with_lut(").string(&item.name).add(").unwrap(),");
+            }
+        }
+        tb.add("        ]);");
+
         let is_u32_enum = main_attribs.iter().any( | attr | attr.name == "repr" && attr.args.as_ref().unwrap().to_string().to_lowercase() == "u32");
         if is_u32_enum {
             tb.add("        let mut variants = Vec::new();");
@@ -43,6 +43,8 @@ pub struct LiveRegistry {
     pub module_id_to_file_id: BTreeMap<LiveModuleId, LiveFileId>,
     pub live_files: Vec<LiveFile>,
     pub live_type_infos: BTreeMap<LiveType, LiveTypeInfo>,
+    /// The bare variants of the enums that derive `Live`, for editors that offer a choice of them.
+    pub enum_variants: BTreeMap<LiveType, Vec<LiveId>>,
     //pub ignore_no_dsl: HashSet<LiveId>,
     pub main_module: Option<LiveTypeInfo>,
     pub components: LiveComponentRegistries,
@@ -57,6 +59,7 @@ impl Default for LiveRegistry {
             module_id_to_file_id: Default::default(),
             live_files: Vec::new(),
             live_type_infos: Default::default(),
+            enum_variants: Default::default(),
             components: LiveComponentRegistries::default(),
             package_root: None
         }
@@ -721,6 +724,83 @@ impl LiveRegistry {
         }
     }
 
+    /// Replaces the tokens `range` of a file with the tokens of `replace` and re-expands, like
+    /// `process_file_changes` does for a whole file. An empty range inserts before its start.
+    /// Returns the text range that was replaced and how many tokens took its place, or `None`
+    /// if the patched file doesn't parse, in which case it is left as it was.
+    pub fn patch_tokens(&mut self, file_id: LiveFileId, range: std::ops::Range<usize>, replace: &str, errors: &mut Vec<LiveError>) -> Option<(TextPos, TextPos, usize)> {
+        let module_id = self.file_id_to_module_id(file_id).unwrap();
+        let live_file = self.file_id_to_file_mut(file_id);
+        let mut tokens = live_file.original.tokens.clone();
+        let start = tokens[range.start].span.start;
+        let end = if range.is_empty() {start} else {tokens[range.end - 1].span.end};
+        let mut new_tokens = match Self::tokenize_from_str(replace, start, file_id) {
+            Err(msg) => {
+                errors.push(msg);
+                return None
+            }
+            Ok(new_tokens) => new_tokens
+        };
+        new_tokens.pop(); // the Eof
+        // the tokenizer counts the columns of the first line from 0
+        for token in &mut new_tokens {
+            if token.span.start.line == start.line {
+                token.span.start.column += start.column;
+            }
+            if token.span.end.line == start.line {
+                token.span.end.column += start.column;
+            }
+        }
+        let new_end = match replace.rfind('\n') {
+            Some(last) => TextPos {
+                line: start.line + replace.matches('\n').count() as u32,
+                column: replace[last + 1..].chars().count() as u32
+            },
+            None => TextPos {line: start.line, column: start.column + replace.chars().count() as u32}
+        };
+        let count = new_tokens.len();
+        tokens.splice(range.clone(), new_tokens);
+        // and move everything after it along
+        let shift = | pos: &mut TextPos | {
+            if pos.line == end.line {
+                pos.column = pos.column - end.column + new_end.column;
+            }
+            pos.line = pos.line - end.line + new_end.line;
+        };
+        for token in &mut tokens[range.start + count..] {
+            if token.token != LiveToken::Eof {
+                shift(&mut token.span.start);
+                shift(&mut token.span.end);
+            }
+        }
+        let mut parser = LiveParser::new(&tokens, &live_file.live_type_infos, file_id);
+        match parser.parse_live_document() {
+            Err(msg) => {
+                errors.push(msg);
+                None
+            }
+            Ok(mut ld) => {
+                for node in &mut ld.nodes {
+                    if let LiveValue::Import(live_import) = &mut node.value {
+                        if live_import.module_id.0 == live_id!(crate) { // patch up crate refs
+                            live_import.module_id.0 = module_id.0
+                        }
+                    }
+                }
+                ld.tokens = tokens;
+                live_file.original = ld;
+                live_file.reexpand = true;
+                live_file.generation.next_gen();
+                self.expand_all_documents(errors);
+                Some((start, end, count))
+            }
+        }
+    }
+    
+    pub fn register_enum_variants(&mut self, live_type: LiveType, variants: Vec<LiveId>) {
+        self.enum_variants.insert(live_type, variants);
+    }
+    
     pub fn register_live_file(
         &mut self,
         file_name: &str,
@@ -0,0 +1,81 @@
+// Editing a registered live file in place, the way the designer writes property edits back.
+
+use makepad_live_compiler::{*, makepad_live_id::*};
+
+const SOURCE: &str = "
+    Thing = {
+        width: 100.0
+        height: 20.0, label: \"hi\"
+    }
+";
+
+fn registry() -> LiveRegistry {
+    let mut registry = LiveRegistry::default();
+    registry.register_live_file(
+        "src/thing.rs",
+        "",
+        LiveModuleId::from_str("thing").unwrap(),
+        SOURCE.to_string(),
+        Vec::new(),
+        TextPos {line: 10, column: 0}
+    ).ok().unwrap();
+    let mut errors = Vec::new();
+    registry.expand_all_documents(&mut errors);
+    assert!(errors.is_empty(), "{:?}", errors);
+    registry
+}
+
+fn token_index(registry: &LiveRegistry, token: LiveToken) -> usize {
+    registry.live_files[0].original.tokens.iter().position( | t | t.token == token).unwrap()
+}
+
+fn value(registry: &LiveRegistry, id: LiveId) -> Option<LiveValue> {
+    registry.live_files[0].expanded.nodes.iter().find( | node | node.id == id).map( | node | node.value.clone())
+}
+
+#[test]
+fn replaces_a_value() {
+    let mut registry = registry();
+    let file_id = LiveFileId::new(0);
+    let generation = registry.live_files[0].generation;
+    let height = token_index(&registry, LiveToken::Float(20.0));
+    let mut errors = Vec::new();
+    let (start, end, count) = registry.patch_tokens(file_id, height..height + 1, "5.5", &mut errors).unwrap();
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!((start, end, count), (TextPos {line: 13, column: 16}, TextPos {line: 13, column: 20}, 1));
+    assert_eq!(value(&registry, live_id!(height)), Some(LiveValue::Float64(5.5)));
+    assert_ne!(registry.live_files[0].generation, generation);
+    // the tokens after it on the same line move over
+    let label = token_index(&registry, LiveToken::Ident(live_id!(label)));
+    assert_eq!(registry.live_files[0].original.tokens[label].span.start, TextPos {line: 13, column: 21});
+}
+
+#[test]
+fn inserts_lines() {
+    let mut registry = registry();
+    let file_id = LiveFileId::new(0);
+    let width = token_index(&registry, LiveToken::Ident(live_id!(width)));
+    let mut errors = Vec::new();
+    let (start, end, count) = registry.patch_tokens(file_id, width..width, "spacing: 4.0\n        ", &mut errors).unwrap();
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!((start, end, count), (TextPos {line: 12, column: 8}, TextPos {line: 12, column: 8}, 3));
+    assert_eq!(value(&registry, live_id!(spacing)), Some(LiveValue::Float64(4.0)));
+    let tokens = &registry.live_files[0].original.tokens;
+    assert_eq!(tokens[width + 2].span.start, TextPos {line: 12, column: 17});
+    assert_eq!(tokens[width + 3].span.start, TextPos {line: 13, column: 8});
+    let label = token_index(&registry, LiveToken::Ident(live_id!(label)));
+    assert_eq!(registry.live_files[0].original.tokens[label].span.start, TextPos {line: 14, column: 22});
+}
+
+#[test]
+fn keeps_the_file_when_the_patch_does_not_parse() {
+    let mut registry = registry();
+    let file_id = LiveFileId::new(0);
+    let generation = registry.live_files[0].generation;
+    let height = token_index(&registry, LiveToken::Float(20.0));
+    let mut errors = Vec::new();
+    assert_eq!(registry.patch_tokens(file_id, height..height + 1, "{", &mut errors), None);
+    assert!(!errors.is_empty());
+    assert_eq!(registry.live_files[0].generation, generation);
+    assert_eq!(value(&registry, live_id!(height)), Some(LiveValue::Float64(20.0)));
+}
@@ -0,0 +1,1499 @@
+// A tree walking interpreter for draw shaders, used by the software renderer.
+// Floats carry their screen space derivatives along (forward mode), which gives
+// us dFdx/dFdy and texture lod selection without having to run pixel quads.
+use {
+    std::{
+        cell::RefCell,
+        ops::{Add, Sub, Mul, Div, Neg},
+    },
+    crate::{
+        makepad_live_id::{LiveId, live_id},
+        shader_ast::*,
+        swizzle::Swizzle,
+        shader_registry::ShaderRegistry,
+    }
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CpuFloat {
+    pub v: f32,
+    pub dx: f32,
+    pub dy: f32,
+}
+
+impl CpuFloat {
+    pub const ZERO: CpuFloat = CpuFloat {v: 0.0, dx: 0.0, dy: 0.0};
+
+    pub fn new(v: f32) -> Self {
+        Self {v, dx: 0.0, dy: 0.0}
+    }
+
+    // applies the chain rule for a function with value v and derivative d at self
+    fn chain(self, v: f32, d: f32) -> Self {
+        Self {v, dx: self.dx * d, dy: self.dy * d}
+    }
+
+    fn select(self, other: Self, take_self: bool) -> Self {
+        if take_self {self} else {other}
+    }
+
+    pub fn abs(self) -> Self {self.chain(self.v.abs(), if self.v < 0.0 {-1.0} else {1.0})}
+    pub fn sign(self) -> Self {Self::new(if self.v > 0.0 {1.0} else if self.v < 0.0 {-1.0} else {0.0})}
+    pub fn floor(self) -> Self {Self::new(self.v.floor())}
+    pub fn ceil(self) -> Self {Self::new(self.v.ceil())}
+    pub fn fract(self) -> Self {self.chain(self.v - self.v.floor(), 1.0)}
+    pub fn sin(self) -> Self {self.chain(self.v.sin(), self.v.cos())}
+    pub fn cos(self) -> Self {self.chain(self.v.cos(), -self.v.sin())}
+    pub fn tan(self) -> Self {let c = self.v.cos(); self.chain(self.v.tan(), 1.0 / (c * c))}
+    pub fn asin(self) -> Self {self.chain(self.v.asin(), 1.0 / (1.0 - self.v * self.v).sqrt())}
+    pub fn acos(self) -> Self {self.chain(self.v.acos(), -1.0 / (1.0 - self.v * self.v).sqrt())}
+    pub fn atan(self) -> Self {self.chain(self.v.atan(), 1.0 / (1.0 + self.v * self.v))}
+    pub fn exp(self) -> Self {let e = self.v.exp(); self.chain(e, e)}
+    pub fn exp2(self) -> Self {let e = self.v.exp2(); self.chain(e, e * std::f32::consts::LN_2)}
+    pub fn ln(self) -> Self {self.chain(self.v.ln(), 1.0 / self.v)}
+    pub fn log2(self) -> Self {self.chain(self.v.log2(), 1.0 / (self.v * std::f32::consts::LN_2))}
+    pub fn radians(self) -> Self {self * Self::new(std::f32::consts::PI / 180.0)}
+    pub fn degrees(self) -> Self {self * Self::new(180.0 / std::f32::consts::PI)}
+
+    pub fn sqrt(self) -> Self {
+        let s = self.v.sqrt();
+        self.chain(s, if s > 0.0 {0.5 / s} else {0.0})
+    }
+
+    pub fn inversesqrt(self) -> Self {
+        let s = 1.0 / self.v.sqrt();
+        self.chain(s, -0.5 * s / self.v)
+    }
+
+    pub fn atan2(self, x: Self) -> Self {
+        let y = self;
+        let den = x.v * x.v + y.v * y.v;
+        if den == 0.0 {
+            return Self::new(y.v.atan2(x.v))
+        }
+        Self {
+            v: y.v.atan2(x.v),
+            dx: (x.v * y.dx - y.v * x.dx) / den,
+            dy: (x.v * y.dy - y.v * x.dy) / den,
+        }
+    }
+
+    pub fn pow(self, e: Self) -> Self {
+        let v = self.v.powf(e.v);
+        let db = if self.v != 0.0 {e.v * self.v.powf(e.v - 1.0)} else {0.0};
+        let de = if self.v > 0.0 {v * self.v.ln()} else {0.0};
+        Self {
+            v,
+            dx: db * self.dx + de * e.dx,
+            dy: db * self.dy + de * e.dy,
+        }
+    }
+
+    pub fn min(self, other: Self) -> Self {self.select(other, self.v <= other.v)}
+    pub fn max(self, other: Self) -> Self {self.select(other, self.v >= other.v)}
+
+    pub fn clamp(self, lo: Self, hi: Self) -> Self {
+        self.max(lo).min(hi)
+    }
+
+    // glsl mod, x - y * floor(x / y)
+    pub fn modulo(self, y: Self) -> Self {
+        self - y * (self / y).floor()
+    }
+
+    pub fn mix(self, b: Self, t: Self) -> Self {
+        self + (b - self) * t
+    }
+
+    pub fn step(edge: Self, x: Self) -> Self {
+        Self::new(if x.v < edge.v {0.0} else {1.0})
+    }
+
+    pub fn smoothstep(e0: Self, e1: Self, x: Self) -> Self {
+        let t = ((x - e0) / (e1 - e0)).clamp(Self::new(0.0), Self::new(1.0));
+        t * t * (Self::new(3.0) - Self::new(2.0) * t)
+    }
+}
+
+impl Add for CpuFloat {
+    type Output = CpuFloat;
+    fn add(self, o: CpuFloat) -> CpuFloat {
+        CpuFloat {v: self.v + o.v, dx: self.dx + o.dx, dy: self.dy + o.dy}
+    }
+}
+
+impl Sub for CpuFloat {
+    type Output = CpuFloat;
+    fn sub(self, o: CpuFloat) -> CpuFloat {
+        CpuFloat {v: self.v - o.v, dx: self.dx - o.dx, dy: self.dy - o.dy}
+    }
+}
+
+impl Mul for CpuFloat {
+    type Output = CpuFloat;
+    fn mul(self, o: CpuFloat) -> CpuFloat {
+        CpuFloat {
+            v: self.v * o.v,
+            dx: self.dx * o.v + self.v * o.dx,
+            dy: self.dy * o.v + self.v * o.dy
+        }
+    }
+}
+
+impl Div for CpuFloat {
+    type Output = CpuFloat;
+    fn div(self, o: CpuFloat) -> CpuFloat {
+        let v = self.v / o.v;
+        if o.dx == 0.0 && o.dy == 0.0 {
+            return CpuFloat {v, dx: self.dx / o.v, dy: self.dy / o.v}
+        }
+        let den = o.v * o.v;
+        CpuFloat {
+            v,
+            dx: (self.dx * o.v - self.v * o.dx) / den,
+            dy: (self.dy * o.v - self.v * o.dy) / den
+        }
+    }
+}
+
+impl Neg for CpuFloat {
+    type Output = CpuFloat;
+    fn neg(self) -> CpuFloat {
+        CpuFloat {v: -self.v, dx: -self.dx, dy: -self.dy}
+    }
+}
+
+// matrices are column major like in glsl, and don't carry derivatives
+#[derive(Clone, Debug, Default)]
+pub enum CpuValue {
+    #[default]
+    Void,
+    Bool(bool),
+    Int(i32),
+    Float(CpuFloat),
+    Bvec(usize, [bool; 4]),
+    Ivec(usize, [i32; 4]),
+    Vec(usize, [CpuFloat; 4]),
+    Mat(usize, [f32; 16]),
+    Array(Vec<CpuValue>),
+    Struct(Vec<CpuValue>),
+    Texture(usize),
+}
+
+impl CpuValue {
+    pub fn default_for_ty(ty: &Ty, shader_registry: &ShaderRegistry) -> CpuValue {
+        match ty {
+            Ty::Bool => CpuValue::Bool(false),
+            Ty::Int => CpuValue::Int(0),
+            Ty::Float | Ty::Enum(_) => CpuValue::Float(CpuFloat::ZERO),
+            Ty::Bvec2 => CpuValue::Bvec(2, [false; 4]),
+            Ty::Bvec3 => CpuValue::Bvec(3, [false; 4]),
+            Ty::Bvec4 => CpuValue::Bvec(4, [false; 4]),
+            Ty::Ivec2 => CpuValue::Ivec(2, [0; 4]),
+            Ty::Ivec3 => CpuValue::Ivec(3, [0; 4]),
+            Ty::Ivec4 => CpuValue::Ivec(4, [0; 4]),
+            Ty::Vec2 => CpuValue::Vec(2, [CpuFloat::ZERO; 4]),
+            Ty::Vec3 => CpuValue::Vec(3, [CpuFloat::ZERO; 4]),
+            Ty::Vec4 => CpuValue::Vec(4, [CpuFloat::ZERO; 4]),
+            Ty::Mat2 => CpuValue::Mat(2, [0.0; 16]),
+            Ty::Mat3 => CpuValue::Mat(3, [0.0; 16]),
+            Ty::Mat4 => CpuValue::Mat(4, [0.0; 16]),
+            Ty::Texture2D | Ty::TextureOES => CpuValue::Texture(usize::MAX),
+            Ty::Array {elem_ty, len} => {
+                CpuValue::Array(vec![CpuValue::default_for_ty(elem_ty, shader_registry); *len])
+            }
+            Ty::Struct(struct_ptr) => {
+                let struct_def = shader_registry.structs.get(struct_ptr).unwrap();
+                CpuValue::Struct(struct_def.fields.iter().map( | field | {
+                    CpuValue::default_for_ty(field.ty_expr.ty.borrow().as_ref().unwrap(), shader_registry)
+                }).collect())
+            }
+            Ty::Void | Ty::DrawShader(_) | Ty::ClosureDef(_) | Ty::ClosureDecl => CpuValue::Void
+        }
+    }
+
+    // reads a value from a packed float buffer, like an instance or uniform buffer
+    pub fn from_slots(ty: &Ty, slots: &[f32]) -> CpuValue {
+        let slot = | i: usize | slots.get(i).cloned().unwrap_or(0.0);
+        match ty {
+            Ty::Bool => CpuValue::Bool(slot(0) != 0.0),
+            Ty::Int => CpuValue::Int(slot(0) as i32),
+            Ty::Float | Ty::Enum(_) => CpuValue::Float(CpuFloat::new(slot(0))),
+            Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 => {
+                let mut v = [false; 4];
+                for (i, v) in v.iter_mut().enumerate() {*v = slot(i) != 0.0}
+                CpuValue::Bvec(ty.slots(), v)
+            }
+            Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 => {
+                let mut v = [0; 4];
+                for (i, v) in v.iter_mut().enumerate() {*v = slot(i) as i32}
+                CpuValue::Ivec(ty.slots(), v)
+            }
+            Ty::Vec2 | Ty::Vec3 | Ty::Vec4 => {
+                let n = ty.slots();
+                let mut v = [CpuFloat::ZERO; 4];
+                for (i, v) in v.iter_mut().enumerate().take(n) {*v = CpuFloat::new(slot(i))}
+                CpuValue::Vec(n, v)
+            }
+            Ty::Mat2 | Ty::Mat3 | Ty::Mat4 => {
+                let n = mat_dim(ty);
+                let mut m = [0.0; 16];
+                for (i, m) in m.iter_mut().enumerate().take(n * n) {*m = slot(i)}
+                CpuValue::Mat(n, m)
+            }
+            _ => CpuValue::Void
+        }
+    }
+
+    // rebuilds a value from interpolated floats, keeping their derivatives
+    pub fn from_floats(ty: &Ty, floats: &[CpuFloat]) -> CpuValue {
+        let float = | i: usize | floats.get(i).cloned().unwrap_or(CpuFloat::ZERO);
+        match ty {
+            Ty::Float | Ty::Enum(_) => CpuValue::Float(float(0)),
+            Ty::Vec2 | Ty::Vec3 | Ty::Vec4 => {
+                let n = ty.slots();
+                let mut v = [CpuFloat::ZERO; 4];
+                for (i, v) in v.iter_mut().enumerate().take(n) {*v = float(i)}
+                CpuValue::Vec(n, v)
+            }
+            _ => {
+                let values: Vec<f32> = floats.iter().map( | f | f.v).collect();
+                CpuValue::from_slots(ty, &values)
+            }
+        }
+    }
+
+    pub fn from_val(val: &Val) -> CpuValue {
+        match val {
+            Val::Bool(v) => CpuValue::Bool(*v),
+            Val::Int(v) => CpuValue::Int(*v),
+            Val::Float(v) => CpuValue::Float(CpuFloat::new(*v)),
+            Val::Vec4(v) => CpuValue::Vec(4, [
+                CpuFloat::new(v.x),
+                CpuFloat::new(v.y),
+                CpuFloat::new(v.z),
+                CpuFloat::new(v.w)
+            ]),
+        }
+    }
+
+    // flattens the value into float components, used by constructors and varyings
+    pub fn push_floats(&self, out: &mut Vec<CpuFloat>) {
+        match self {
+            CpuValue::Bool(v) => out.push(CpuFloat::new(if *v {1.0} else {0.0})),
+            CpuValue::Int(v) => out.push(CpuFloat::new(*v as f32)),
+            CpuValue::Float(v) => out.push(*v),
+            CpuValue::Bvec(n, v) => out.extend(v[0..*n].iter().map( | v | CpuFloat::new(if *v {1.0} else {0.0}))),
+            CpuValue::Ivec(n, v) => out.extend(v[0..*n].iter().map( | v | CpuFloat::new(*v as f32))),
+            CpuValue::Vec(n, v) => out.extend_from_slice(&v[0..*n]),
+            CpuValue::Mat(n, m) => out.extend(m[0..n * n].iter().map( | v | CpuFloat::new(*v))),
+            CpuValue::Array(values) | CpuValue::Struct(values) => {
+                for value in values {
+                    value.push_floats(out);
+                }
+            }
+            CpuValue::Void | CpuValue::Texture(_) => ()
+        }
+    }
+
+    pub fn to_float(&self) -> CpuFloat {
+        match self {
+            CpuValue::Bool(v) => CpuFloat::new(if *v {1.0} else {0.0}),
+            CpuValue::Int(v) => CpuFloat::new(*v as f32),
+            CpuValue::Float(v) => *v,
+            CpuValue::Vec(_, v) => v[0],
+            CpuValue::Ivec(_, v) => CpuFloat::new(v[0] as f32),
+            _ => CpuFloat::ZERO
+        }
+    }
+
+    pub fn to_f32(&self) -> f32 {
+        self.to_float().v
+    }
+
+    pub fn to_i32(&self) -> i32 {
+        match self {
+            CpuValue::Int(v) => *v,
+            CpuValue::Bool(v) => *v as i32,
+            _ => self.to_f32() as i32
+        }
+    }
+
+    pub fn to_bool(&self) -> bool {
+        match self {
+            CpuValue::Bool(v) => *v,
+            CpuValue::Int(v) => *v != 0,
+            _ => self.to_f32() != 0.0
+        }
+    }
+
+    pub fn to_vec4(&self) -> [f32; 4] {
+        let mut floats = Vec::with_capacity(4);
+        self.push_floats(&mut floats);
+        let mut out = [0.0, 0.0, 0.0, 1.0];
+        for (o, f) in out.iter_mut().zip(floats.iter()) {
+            *o = f.v;
+        }
+        out
+    }
+
+    fn is_equal(&self, other: &CpuValue) -> bool {
+        match (self, other) {
+            (CpuValue::Bool(a), CpuValue::Bool(b)) => a == b,
+            (CpuValue::Int(a), CpuValue::Int(b)) => a == b,
+            (CpuValue::Bvec(n, a), CpuValue::Bvec(_, b)) => a[0..*n] == b[0..*n],
+            (CpuValue::Ivec(n, a), CpuValue::Ivec(_, b)) => a[0..*n] == b[0..*n],
+            (CpuValue::Array(a), CpuValue::Array(b)) | (CpuValue::Struct(a), CpuValue::Struct(b)) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all( | (a, b) | a.is_equal(b))
+            }
+            _ => {
+                let mut a = Vec::new();
+                let mut b = Vec::new();
+                self.push_floats(&mut a);
+                other.push_floats(&mut b);
+                a.len() == b.len() && a.iter().zip(b.iter()).all( | (a, b) | a.v == b.v)
+            }
+        }
+    }
+
+    // float shaped components, scalars are vectors of length 1
+    fn float_lanes(&self) -> Option<(usize, [CpuFloat; 4])> {
+        match self {
+            CpuValue::Float(v) => Some((1, [*v; 4])),
+            CpuValue::Vec(n, v) => Some((*n, *v)),
+            CpuValue::Int(v) => Some((1, [CpuFloat::new(*v as f32); 4])),
+            CpuValue::Ivec(n, v) => {
+                let mut out = [CpuFloat::ZERO; 4];
+                for i in 0..*n {out[i] = CpuFloat::new(v[i] as f32)}
+                Some((*n, out))
+            }
+            CpuValue::Bool(v) => Some((1, [CpuFloat::new(if *v {1.0} else {0.0}); 4])),
+            _ => None
+        }
+    }
+
+    fn int_lanes(&self) -> Option<(usize, [i32; 4])> {
+        match self {
+            CpuValue::Int(v) => Some((1, [*v; 4])),
+            CpuValue::Ivec(n, v) => Some((*n, *v)),
+            _ => None
+        }
+    }
+
+    fn from_float_lanes(n: usize, lanes: [CpuFloat; 4]) -> CpuValue {
+        if n == 1 {CpuValue::Float(lanes[0])} else {CpuValue::Vec(n, lanes)}
+    }
+
+    fn from_int_lanes(n: usize, lanes: [i32; 4]) -> CpuValue {
+        if n == 1 {CpuValue::Int(lanes[0])} else {CpuValue::Ivec(n, lanes)}
+    }
+
+    fn map1(&self, f: impl Fn(CpuFloat) -> CpuFloat) -> CpuValue {
+        match self {
+            CpuValue::Mat(n, m) => {
+                let mut out = [0.0; 16];
+                for i in 0..n * n {out[i] = f(CpuFloat::new(m[i])).v}
+                CpuValue::Mat(*n, out)
+            }
+            _ => if let Some((n, a)) = self.float_lanes() {
+                let mut out = [CpuFloat::ZERO; 4];
+                for i in 0..n {out[i] = f(a[i])}
+                CpuValue::from_float_lanes(n, out)
+            }
+            else {
+                CpuValue::Void
+            }
+        }
+    }
+
+    fn map2(&self, b: &CpuValue, f: impl Fn(CpuFloat, CpuFloat) -> CpuFloat) -> CpuValue {
+        let (Some((na, a)), Some((nb, b))) = (self.float_lanes(), b.float_lanes()) else {
+            return CpuValue::Void
+        };
+        let n = na.max(nb);
+        let mut out = [CpuFloat::ZERO; 4];
+        for i in 0..n {
+            out[i] = f(a[if na == 1 {0} else {i}], b[if nb == 1 {0} else {i}]);
+        }
+        CpuValue::from_float_lanes(n, out)
+    }
+
+    fn map3(&self, b: &CpuValue, c: &CpuValue, f: impl Fn(CpuFloat, CpuFloat, CpuFloat) -> CpuFloat) -> CpuValue {
+        let (Some((na, a)), Some((nb, b)), Some((nc, c))) = (self.float_lanes(), b.float_lanes(), c.float_lanes()) else {
+            return CpuValue::Void
+        };
+        let n = na.max(nb).max(nc);
+        let mut out = [CpuFloat::ZERO; 4];
+        for i in 0..n {
+            out[i] = f(
+                a[if na == 1 {0} else {i}],
+                b[if nb == 1 {0} else {i}],
+                c[if nc == 1 {0} else {i}]
+            );
+        }
+        CpuValue::from_float_lanes(n, out)
+    }
+
+    fn map_int2(&self, b: &CpuValue, f: impl Fn(i32, i32) -> i32) -> CpuValue {
+        let (Some((na, a)), Some((nb, b))) = (self.int_lanes(), b.int_lanes()) else {
+            return CpuValue::Void
+        };
+        let n = na.max(nb);
+        let mut out = [0; 4];
+        for i in 0..n {
+            out[i] = f(a[if na == 1 {0} else {i}], b[if nb == 1 {0} else {i}]);
+        }
+        CpuValue::from_int_lanes(n, out)
+    }
+
+    fn compare(&self, b: &CpuValue, f: impl Fn(f32, f32) -> bool) -> CpuValue {
+        let (Some((n, a)), Some((_, b))) = (self.float_lanes(), b.float_lanes()) else {
+            return CpuValue::Void
+        };
+        let mut out = [false; 4];
+        for i in 0..n {out[i] = f(a[i].v, b[i].v)}
+        CpuValue::Bvec(n, out)
+    }
+
+    fn dot(&self, b: &CpuValue) -> CpuFloat {
+        let (Some((n, a)), Some((_, b))) = (self.float_lanes(), b.float_lanes()) else {
+            return CpuFloat::ZERO
+        };
+        let mut sum = CpuFloat::ZERO;
+        for i in 0..n {sum = sum + a[i] * b[i]}
+        sum
+    }
+
+    fn length(&self) -> CpuFloat {
+        self.dot(self).sqrt()
+    }
+}
+
+fn mat_dim(ty: &Ty) -> usize {
+    match ty {
+        Ty::Mat2 => 2,
+        Ty::Mat3 => 3,
+        _ => 4
+    }
+}
+
+fn float_op(op: BinOp, a: CpuFloat, b: CpuFloat) -> CpuFloat {
+    match op {
+        BinOp::Add | BinOp::AddAssign => a + b,
+        BinOp::Sub | BinOp::SubAssign => a - b,
+        BinOp::Mul | BinOp::MulAssign => a * b,
+        BinOp::Div | BinOp::DivAssign => a / b,
+        _ => b
+    }
+}
+
+fn int_op(op: BinOp, a: i32, b: i32) -> i32 {
+    match op {
+        BinOp::Add | BinOp::AddAssign => a.wrapping_add(b),
+        BinOp::Sub | BinOp::SubAssign => a.wrapping_sub(b),
+        BinOp::Mul | BinOp::MulAssign => a.wrapping_mul(b),
+        BinOp::Div | BinOp::DivAssign => if b == 0 {0} else {a.wrapping_div(b)},
+        _ => b
+    }
+}
+
+fn is_mul(op: BinOp) -> bool {
+    matches!(op, BinOp::Mul | BinOp::MulAssign)
+}
+
+fn arith(op: BinOp, a: &CpuValue, b: &CpuValue) -> CpuValue {
+    match (a, b) {
+        (CpuValue::Mat(n, a), CpuValue::Mat(_, b)) if is_mul(op) => {
+            let n = *n;
+            let mut out = [0.0; 16];
+            for col in 0..n {
+                for row in 0..n {
+                    let mut sum = 0.0;
+                    for k in 0..n {
+                        sum += a[k * n + row] * b[col * n + k];
+                    }
+                    out[col * n + row] = sum;
+                }
+            }
+            CpuValue::Mat(n, out)
+        }
+        (CpuValue::Mat(n, m), CpuValue::Vec(_, v)) if is_mul(op) => {
+            let n = *n;
+            let mut out = [CpuFloat::ZERO; 4];
+            for row in 0..n {
+                for col in 0..n {
+                    out[row] = out[row] + CpuFloat::new(m[col * n + row]) * v[col];
+                }
+            }
+            CpuValue::Vec(n, out)
+        }
+        (CpuValue::Vec(_, v), CpuValue::Mat(n, m)) if is_mul(op) => {
+            let n = *n;
+            let mut out = [CpuFloat::ZERO; 4];
+            for col in 0..n {
+                for row in 0..n {
+                    out[col] = out[col] + v[row] * CpuFloat::new(m[col * n + row]);
+                }
+            }
+            CpuValue::Vec(n, out)
+        }
+        (CpuValue::Mat(n, a), CpuValue::Mat(_, b)) => {
+            let mut out = [0.0; 16];
+            for i in 0..n * n {
+                out[i] = float_op(op, CpuFloat::new(a[i]), CpuFloat::new(b[i])).v;
+            }
+            CpuValue::Mat(*n, out)
+        }
+        (CpuValue::Mat(n, a), s) => {
+            let s = s.to_float();
+            let mut out = [0.0; 16];
+            for i in 0..n * n {out[i] = float_op(op, CpuFloat::new(a[i]), s).v}
+            CpuValue::Mat(*n, out)
+        }
+        (s, CpuValue::Mat(n, b)) => {
+            let s = s.to_float();
+            let mut out = [0.0; 16];
+            for i in 0..n * n {out[i] = float_op(op, s, CpuFloat::new(b[i])).v}
+            CpuValue::Mat(*n, out)
+        }
+        (CpuValue::Int(_) | CpuValue::Ivec(..), CpuValue::Int(_) | CpuValue::Ivec(..)) => {
+            a.map_int2(b, | a, b | int_op(op, a, b))
+        }
+        _ => a.map2(b, | a, b | float_op(op, a, b))
+    }
+}
+
+fn cons(ty_lit: TyLit, args: &[CpuValue]) -> CpuValue {
+    let ty = ty_lit.to_ty();
+    let mut floats = Vec::with_capacity(16);
+    for arg in args {
+        arg.push_floats(&mut floats);
+    }
+    let splat = args.len() == 1 && floats.len() == 1;
+    match ty {
+        Ty::Bool => CpuValue::Bool(args.first().map( | a | a.to_bool()).unwrap_or(false)),
+        Ty::Int => CpuValue::Int(args.first().map( | a | a.to_i32()).unwrap_or(0)),
+        Ty::Float => CpuValue::Float(floats.first().cloned().unwrap_or(CpuFloat::ZERO)),
+        Ty::Vec2 | Ty::Vec3 | Ty::Vec4 | Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 | Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 => {
+            let n = ty.slots();
+            let mut lanes = [CpuFloat::ZERO; 4];
+            for (i, lane) in lanes.iter_mut().enumerate().take(n) {
+                *lane = floats.get(if splat {0} else {i}).cloned().unwrap_or(CpuFloat::ZERO);
+            }
+            match ty {
+                Ty::Vec2 | Ty::Vec3 | Ty::Vec4 => CpuValue::Vec(n, lanes),
+                Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 => {
+                    let mut out = [0; 4];
+                    for i in 0..n {out[i] = lanes[i].v as i32}
+                    CpuValue::Ivec(n, out)
+                }
+                _ => {
+                    let mut out = [false; 4];
+                    for i in 0..n {out[i] = lanes[i].v != 0.0}
+                    CpuValue::Bvec(n, out)
+                }
+            }
+        }
+        Ty::Mat2 | Ty::Mat3 | Ty::Mat4 => {
+            let n = mat_dim(&ty);
+            let mut out = [0.0; 16];
+            if splat {
+                for i in 0..n {out[i * n + i] = floats[0].v}
+            }
+            else if let [CpuValue::Mat(src_n, src)] = args {
+                for col in 0..n {
+                    for row in 0..n {
+                        out[col * n + row] = if col < *src_n && row < *src_n {
+                            src[col * src_n + row]
+                        }
+                        else if col == row {1.0} else {0.0};
+                    }
+                }
+            }
+            else {
+                for (out, f) in out.iter_mut().zip(floats).take(n * n) {
+                    *out = f.v;
+                }
+            }
+            CpuValue::Mat(n, out)
+        }
+        _ => CpuValue::Void
+    }
+}
+
+fn mat_inverse(n: usize, m: &[f32; 16]) -> [f32; 16] {
+    // gauss-jordan on a row major copy
+    let mut a = [[0.0f64; 8]; 4];
+    for row in 0..n {
+        for col in 0..n {
+            a[row][col] = m[col * n + row] as f64;
+        }
+        a[row][n + row] = 1.0;
+    }
+    for col in 0..n {
+        let mut pivot = col;
+        for row in col + 1..n {
+            if a[row][col].abs() > a[pivot][col].abs() {
+                pivot = row;
+            }
+        }
+        if a[pivot][col] == 0.0 {
+            return [0.0; 16]
+        }
+        a.swap(col, pivot);
+        let p = a[col][col];
+        for v in &mut a[col][..2 * n] {
+            *v /= p;
+        }
+        let pivot_row = a[col];
+        for (row, values) in a.iter_mut().enumerate().take(n) {
+            if row != col {
+                let f = values[col];
+                for (v, p) in values[..2 * n].iter_mut().zip(&pivot_row) {
+                    *v -= f * p;
+                }
+            }
+        }
+    }
+    let mut out = [0.0; 16];
+    for row in 0..n {
+        for col in 0..n {
+            out[col * n + row] = a[row][n + col] as f32;
+        }
+    }
+    out
+}
+
+// the software renderer provides texture sampling through this
+pub trait CpuShaderTextures {
+    fn sample2d(&self, slot: usize, x: CpuFloat, y: CpuFloat) -> [f32; 4];
+}
+
+#[derive(Clone, Debug)]
+struct CpuLocal {
+    ident: Ident,
+    // for loop counters have no shadow index
+    shadow: Option<ScopeSymShadow>,
+    value: CpuValue,
+}
+
+// the fields and live values of one shader invocation
+#[derive(Clone, Debug, Default)]
+pub struct CpuShaderState {
+    pub fields: Vec<CpuValue>,
+    pub live_values: Vec<CpuValue>,
+    locals: Vec<CpuLocal>,
+}
+
+pub struct CpuShader<'a> {
+    pub shader_registry: &'a ShaderRegistry,
+    pub draw_shader_def: &'a DrawShaderDef,
+    vertex_fn: &'a FnDef,
+    pixel_fn: &'a FnDef,
+    live_refs: Vec<ValuePtr>,
+    swizzles: RefCell<Vec<(Ident, Swizzle)>>,
+}
+
+impl<'a> CpuShader<'a> {
+    pub fn new(shader_registry: &'a ShaderRegistry, draw_shader_def: &'a DrawShaderDef) -> Option<Self> {
+        let vertex_fn = shader_registry.draw_shader_method_decl_from_ident(draw_shader_def, Ident(live_id!(vertex))) ?;
+        let pixel_fn = shader_registry.draw_shader_method_decl_from_ident(draw_shader_def, Ident(live_id!(pixel))) ?;
+        Some(Self {
+            shader_registry,
+            draw_shader_def,
+            vertex_fn,
+            pixel_fn,
+            live_refs: draw_shader_def.all_live_refs.borrow().keys().cloned().collect(),
+            swizzles: RefCell::new(Vec::new()),
+        })
+    }
+
+    pub fn field_index(&self, ident: Ident) -> Option<usize> {
+        self.draw_shader_def.fields.iter().position( | field | field.ident == ident)
+    }
+
+    pub fn new_state(&self) -> CpuShaderState {
+        CpuShaderState {
+            fields: self.draw_shader_def.fields.iter().map( | field | {
+                CpuValue::default_for_ty(field.ty_expr.ty.borrow().as_ref().unwrap(), self.shader_registry)
+            }).collect(),
+            live_values: self.draw_shader_def.all_live_refs.borrow().values().map( | ty | {
+                CpuValue::default_for_ty(ty, self.shader_registry)
+            }).collect(),
+            locals: Vec::new(),
+        }
+    }
+
+    // the fields that get passed from the vertex to the pixel shader
+    pub fn varying_fields(&self) -> Vec<usize> {
+        let mut out = Vec::new();
+        for (index, field) in self.draw_shader_def.fields.iter().enumerate() {
+            match &field.kind {
+                DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} |
+                DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    out.push(index);
+                }
+                DrawShaderFieldKind::Varying {..} => {
+                    out.push(index);
+                }
+                _ => ()
+            }
+        }
+        out
+    }
+
+    pub fn field_ty(&self, index: usize) -> Ty {
+        self.draw_shader_def.fields[index].ty_expr.ty.borrow().as_ref().unwrap().clone()
+    }
+
+    // returns the clip space position
+    pub fn run_vertex(&self, state: &mut CpuShaderState, textures: &dyn CpuShaderTextures) -> [f32; 4] {
+        self.run_fn(self.vertex_fn, state, textures).to_vec4()
+    }
+
+    // returns the premultiplied color
+    pub fn run_pixel(&self, state: &mut CpuShaderState, textures: &dyn CpuShaderTextures) -> [f32; 4] {
+        self.run_fn(self.pixel_fn, state, textures).to_vec4()
+    }
+
+    fn run_fn(&self, fn_def: &FnDef, state: &mut CpuShaderState, textures: &dyn CpuShaderTextures) -> CpuValue {
+        state.locals.clear();
+        let mut exec = CpuExec {
+            shader: self,
+            fields: &mut state.fields,
+            live_values: &state.live_values,
+            locals: &mut state.locals,
+            textures,
+        };
+        let ctx = CallCtx {
+            fn_def,
+            closure: None,
+            frame_base: 0,
+        };
+        match exec.exec_block(&ctx, &fn_def.block) {
+            Flow::Return(value) => value,
+            _ => CpuValue::Void
+        }
+    }
+
+    fn swizzle(&self, ident: Ident) -> Option<Swizzle> {
+        let mut swizzles = self.swizzles.borrow_mut();
+        if let Some((_, swizzle)) = swizzles.iter().find( | (i, _) | *i == ident) {
+            return Some(swizzle.clone())
+        }
+        let swizzle = Swizzle::parse(ident) ?;
+        swizzles.push((ident, swizzle.clone()));
+        Some(swizzle)
+    }
+}
+
+enum Flow {
+    Next,
+    Break,
+    Continue,
+    Return(CpuValue),
+}
+
+struct ClosureCtx<'c> {
+    site: &'c ClosureSite,
+    call_def: &'c FnDef,
+    captured: Vec<CpuLocal>,
+}
+
+struct CallCtx<'c> {
+    fn_def: &'c FnDef,
+    closure: Option<ClosureCtx<'c >>,
+    frame_base: usize,
+}
+
+struct CpuExec<'s, 'a> {
+    shader: &'s CpuShader<'a>,
+    fields: &'s mut Vec<CpuValue>,
+    live_values: &'s [CpuValue],
+    locals: &'s mut Vec<CpuLocal>,
+    textures: &'s dyn CpuShaderTextures,
+}
+
+// draw shader and closure arguments aren't passed as values
+fn is_hidden_ty(ty: &Option<Ty>) -> bool {
+    matches!(ty, Some(Ty::DrawShader(_)) | Some(Ty::ClosureDef(_)) | Some(Ty::ClosureDecl) | None)
+}
+
+impl<'s, 'a> CpuExec<'s, 'a> {
+    fn find_local(&self, ctx: &CallCtx, ident: Ident, shadow: ScopeSymShadow) -> Option<usize> {
+        (ctx.frame_base..self.locals.len()).rev().find( | index | {
+            let local = &self.locals[*index];
+            local.ident == ident && local.shadow.is_none_or( | s | s == shadow)
+        })
+    }
+
+    fn exec_block(&mut self, ctx: &CallCtx, block: &Block) -> Flow {
+        let base = self.locals.len();
+        for stmt in &block.stmts {
+            let flow = self.exec_stmt(ctx, stmt);
+            if !matches!(flow, Flow::Next) {
+                self.locals.truncate(base);
+                return flow
+            }
+        }
+        self.locals.truncate(base);
+        Flow::Next
+    }
+
+    fn exec_stmt(&mut self, ctx: &CallCtx, stmt: &Stmt) -> Flow {
+        match stmt {
+            Stmt::Break {..} => Flow::Break,
+            Stmt::Continue {..} => Flow::Continue,
+            Stmt::For {ident, from_expr, to_expr, step_expr, block, ..} => {
+                let const_int = | expr: &Expr | expr.const_val.borrow().as_ref()
+                    .and_then( | v | v.as_ref()).and_then( | v | v.to_int()).unwrap_or(0);
+                let from = const_int(from_expr);
+                let to = const_int(to_expr);
+                let step = if let Some(step_expr) = step_expr {
+                    const_int(step_expr)
+                } else if from < to {1} else {-1};
+                if step == 0 {
+                    return Flow::Next
+                }
+                // same iteration order as the generated glsl loops
+                let mut i = if from <= to {from} else {from - 1};
+                let slot = self.locals.len();
+                self.locals.push(CpuLocal {
+                    ident: *ident,
+                    shadow: None,
+                    value: CpuValue::Int(i)
+                });
+                while if from <= to {i < to} else {i >= to} {
+                    self.locals[slot].value = CpuValue::Int(i);
+                    match self.exec_block(ctx, block) {
+                        Flow::Break => break,
+                        Flow::Return(value) => {
+                            self.locals.truncate(slot);
+                            return Flow::Return(value)
+                        }
+                        _ => ()
+                    }
+                    i += step;
+                }
+                self.locals.truncate(slot);
+                Flow::Next
+            }
+            Stmt::If {expr, block_if_true, block_if_false, ..} => {
+                if self.eval_expr(ctx, expr).to_bool() {
+                    self.exec_block(ctx, block_if_true)
+                }
+                else if let Some(block_if_false) = block_if_false {
+                    self.exec_block(ctx, block_if_false)
+                }
+                else {
+                    Flow::Next
+                }
+            }
+            Stmt::Match {expr, matches, ..} => {
+                let value = self.eval_expr(ctx, expr).to_f32();
+                for match_item in matches {
+                    if let Some(enum_value) = match_item.enum_value.get() {
+                        if (value - enum_value as f32).abs() < 0.5 {
+                            return self.exec_block(ctx, &match_item.block)
+                        }
+                    }
+                }
+                Flow::Next
+            }
+            Stmt::Let {ty, shadow, ident, expr, ..} => {
+                let value = if let Some(expr) = expr {
+                    self.eval_expr(ctx, expr)
+                }
+                else {
+                    CpuValue::default_for_ty(ty.borrow().as_ref().unwrap(), self.shader.shader_registry)
+                };
+                self.locals.push(CpuLocal {
+                    ident: *ident,
+                    shadow: shadow.get(),
+                    value
+                });
+                Flow::Next
+            }
+            Stmt::Return {expr, ..} => {
+                Flow::Return(if let Some(expr) = expr {
+                    self.eval_expr(ctx, expr)
+                }
+                else {
+                    CpuValue::Void
+                })
+            }
+            Stmt::Block {block, ..} => self.exec_block(ctx, block),
+            Stmt::Expr {expr, ..} => {
+                self.eval_expr(ctx, expr);
+                Flow::Next
+            }
+        }
+    }
+
+    fn eval_expr(&mut self, ctx: &CallCtx, expr: &Expr) -> CpuValue {
+        if let Some(Some(val)) = &*expr.const_val.borrow() {
+            return CpuValue::from_val(val)
+        }
+        match &expr.kind {
+            ExprKind::Cond {expr, expr_if_true, expr_if_false, ..} => {
+                if self.eval_expr(ctx, expr).to_bool() {
+                    self.eval_expr(ctx, expr_if_true)
+                }
+                else {
+                    self.eval_expr(ctx, expr_if_false)
+                }
+            }
+            ExprKind::Bin {op, left_expr, right_expr, ..} => self.eval_bin_expr(ctx, *op, left_expr, right_expr),
+            ExprKind::Un {op, expr, ..} => {
+                let value = self.eval_expr(ctx, expr);
+                match op {
+                    UnOp::Not => match value {
+                        CpuValue::Bvec(n, mut v) => {
+                            v.iter_mut().for_each( | v | *v = !*v);
+                            CpuValue::Bvec(n, v)
+                        }
+                        value => CpuValue::Bool(!value.to_bool())
+                    },
+                    UnOp::Neg => match value {
+                        CpuValue::Int(v) => CpuValue::Int(v.wrapping_neg()),
+                        CpuValue::Ivec(n, mut v) => {
+                            v.iter_mut().for_each( | v | *v = v.wrapping_neg());
+                            CpuValue::Ivec(n, v)
+                        }
+                        value => value.map1( | v | -v)
+                    }
+                }
+            }
+            ExprKind::Field {expr, field_ident, ..} => self.eval_field_expr(ctx, expr, *field_ident),
+            ExprKind::Index {expr, index_expr, ..} => {
+                let value = self.eval_expr(ctx, expr);
+                let index = self.eval_expr(ctx, index_expr).to_i32().max(0) as usize;
+                match value {
+                    CpuValue::Array(mut values) if index < values.len() => values.swap_remove(index),
+                    CpuValue::Vec(n, v) if index < n => CpuValue::Float(v[index]),
+                    CpuValue::Ivec(n, v) if index < n => CpuValue::Int(v[index]),
+                    CpuValue::Bvec(n, v) if index < n => CpuValue::Bool(v[index]),
+                    CpuValue::Mat(n, m) if index < n => {
+                        let mut out = [CpuFloat::ZERO; 4];
+                        for row in 0..n {out[row] = CpuFloat::new(m[index * n + row])}
+                        CpuValue::Vec(n, out)
+                    }
+                    _ => CpuValue::Void
+                }
+            }
+            ExprKind::MethodCall {ident, arg_exprs, closure_site_index, ..} => {
+                let registry = self.shader.shader_registry;
+                let fn_def = match arg_exprs[0].ty.borrow().as_ref() {
+                    Some(Ty::Struct(struct_ptr)) => {
+                        registry.struct_method_decl_from_ident(registry.structs.get(struct_ptr).unwrap(), *ident)
+                    }
+                    Some(Ty::DrawShader(shader_ptr)) => {
+                        registry.draw_shader_method_decl_from_ident(registry.draw_shader_defs.get(shader_ptr).unwrap(), *ident)
+                    }
+                    _ => None
+                };
+                if let Some(fn_def) = fn_def {
+                    self.call_fn(ctx, fn_def, arg_exprs, closure_site_index.get())
+                }
+                else {
+                    CpuValue::Void
+                }
+            }
+            ExprKind::PlainCall {fn_ptr, arg_exprs, closure_site_index, param_index, ..} => {
+                if let Some(param_index) = param_index.get() {
+                    self.call_closure(ctx, arg_exprs, param_index)
+                }
+                else if let Some(fn_def) = fn_ptr.and_then( | fn_ptr | self.shader.shader_registry.all_fns.get(&fn_ptr)) {
+                    self.call_fn(ctx, fn_def, arg_exprs, closure_site_index.get())
+                }
+                else {
+                    CpuValue::Void
+                }
+            }
+            ExprKind::BuiltinCall {ident, arg_exprs, ..} => {
+                let args: Vec<CpuValue> = arg_exprs.iter().map( | arg | self.eval_expr(ctx, arg)).collect();
+                self.eval_builtin(*ident, &args)
+            }
+            ExprKind::ClosureDef(_) => CpuValue::Void,
+            ExprKind::ConsCall {ty_lit, arg_exprs, ..} => {
+                let args: Vec<CpuValue> = arg_exprs.iter().map( | arg | self.eval_expr(ctx, arg)).collect();
+                cons(*ty_lit, &args)
+            }
+            ExprKind::StructCons {struct_ptr, args, ..} => {
+                let struct_def = self.shader.shader_registry.structs.get(struct_ptr).unwrap();
+                let mut values = Vec::with_capacity(struct_def.fields.len());
+                for field in &struct_def.fields {
+                    values.push(if let Some((_, expr)) = args.iter().find( | (ident, _) | *ident == field.ident) {
+                        self.eval_expr(ctx, expr)
+                    }
+                    else {
+                        CpuValue::default_for_ty(field.ty_expr.ty.borrow().as_ref().unwrap(), self.shader.shader_registry)
+                    });
+                }
+                CpuValue::Struct(values)
+            }
+            ExprKind::Var {kind, ..} => match kind.get() {
+                Some(VarKind::Local {ident, shadow}) | Some(VarKind::MutLocal {ident, shadow}) => {
+                    if let Some(index) = self.find_local(ctx, ident, shadow) {
+                        self.locals[index].value.clone()
+                    }
+                    else {
+                        CpuValue::Void
+                    }
+                }
+                Some(VarKind::LiveValue(value_ptr)) => {
+                    if let Some(index) = self.shader.live_refs.iter().position( | p | *p == value_ptr) {
+                        self.live_values.get(index).cloned().unwrap_or_default()
+                    }
+                    else {
+                        CpuValue::Void
+                    }
+                }
+                None => CpuValue::Void
+            },
+            ExprKind::Lit {lit, ..} => CpuValue::from_val(&lit.to_val()),
+        }
+    }
+
+    fn eval_bin_expr(&mut self, ctx: &CallCtx, op: BinOp, left_expr: &Expr, right_expr: &Expr) -> CpuValue {
+        match op {
+            BinOp::Or => {
+                CpuValue::Bool(self.eval_expr(ctx, left_expr).to_bool() || self.eval_expr(ctx, right_expr).to_bool())
+            }
+            BinOp::And => {
+                CpuValue::Bool(self.eval_expr(ctx, left_expr).to_bool() && self.eval_expr(ctx, right_expr).to_bool())
+            }
+            BinOp::Assign => {
+                let value = self.eval_expr(ctx, right_expr);
+                self.assign(ctx, left_expr, value.clone());
+                value
+            }
+            BinOp::AddAssign | BinOp::SubAssign | BinOp::MulAssign | BinOp::DivAssign => {
+                let right = self.eval_expr(ctx, right_expr);
+                let left = self.eval_expr(ctx, left_expr);
+                let value = arith(op, &left, &right);
+                self.assign(ctx, left_expr, value.clone());
+                value
+            }
+            BinOp::Eq | BinOp::Ne => {
+                let left = self.eval_expr(ctx, left_expr);
+                let right = self.eval_expr(ctx, right_expr);
+                let eq = left.is_equal(&right);
+                CpuValue::Bool(if let BinOp::Eq = op {eq} else {!eq})
+            }
+            BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
+                let left = self.eval_expr(ctx, left_expr);
+                let right = self.eval_expr(ctx, right_expr);
+                let (a, b) = (left.to_f32(), right.to_f32());
+                CpuValue::Bool(match op {
+                    BinOp::Lt => a < b,
+                    BinOp::Le => a <= b,
+                    BinOp::Gt => a > b,
+                    _ => a >= b
+                })
+            }
+            BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div => {
+                let left = self.eval_expr(ctx, left_expr);
+                let right = self.eval_expr(ctx, right_expr);
+                arith(op, &left, &right)
+            }
+        }
+    }
+
+    fn eval_field_expr(&mut self, ctx: &CallCtx, expr: &Expr, field_ident: Ident) -> CpuValue {
+        let ty = expr.ty.borrow().clone();
+        match ty {
+            Some(Ty::DrawShader(_)) => {
+                if let Some(index) = self.shader.field_index(field_ident) {
+                    self.fields[index].clone()
+                }
+                else {
+                    CpuValue::Void
+                }
+            }
+            Some(Ty::Struct(struct_ptr)) => {
+                let struct_def = self.shader.shader_registry.structs.get(&struct_ptr).unwrap();
+                let index = struct_def.fields.iter().position( | field | field.ident == field_ident);
+                match (self.eval_expr(ctx, expr), index) {
+                    (CpuValue::Struct(mut values), Some(index)) if index < values.len() => values.swap_remove(index),
+                    _ => CpuValue::Void
+                }
+            }
+            _ => {
+                let value = self.eval_expr(ctx, expr);
+                let Some(swizzle) = self.shader.swizzle(field_ident) else {
+                    return CpuValue::Void
+                };
+                let n = swizzle.len();
+                match value {
+                    CpuValue::Bvec(_, v) => {
+                        let mut out = [false; 4];
+                        for (o, i) in out.iter_mut().zip(swizzle.iter()) {*o = v[*i]}
+                        if n == 1 {CpuValue::Bool(out[0])} else {CpuValue::Bvec(n, out)}
+                    }
+                    CpuValue::Ivec(_, v) => {
+                        let mut out = [0; 4];
+                        for (o, i) in out.iter_mut().zip(swizzle.iter()) {*o = v[*i]}
+                        CpuValue::from_int_lanes(n, out)
+                    }
+                    value => if let Some((_, v)) = value.float_lanes() {
+                        let mut out = [CpuFloat::ZERO; 4];
+                        for (o, i) in out.iter_mut().zip(swizzle.iter()) {*o = v[*i]}
+                        CpuValue::from_float_lanes(n, out)
+                    }
+                    else {
+                        CpuValue::Void
+                    }
+                }
+            }
+        }
+    }
+
+    fn assign(&mut self, ctx: &CallCtx, expr: &Expr, value: CpuValue) {
+        match &expr.kind {
+            ExprKind::Var {kind, ..} => match kind.get() {
+                Some(VarKind::Local {ident, shadow}) | Some(VarKind::MutLocal {ident, shadow}) => {
+                    if let Some(index) = self.find_local(ctx, ident, shadow) {
+                        self.locals[index].value = value;
+                    }
+                }
+                _ => ()
+            },
+            ExprKind::Field {expr: base_expr, field_ident, ..} => {
+                let ty = base_expr.ty.borrow().clone();
+                match ty {
+                    Some(Ty::DrawShader(_)) => {
+                        if let Some(index) = self.shader.field_index(*field_ident) {
+                            self.fields[index] = value;
+                        }
+                    }
+                    Some(Ty::Struct(struct_ptr)) => {
+                        let struct_def = self.shader.shader_registry.structs.get(&struct_ptr).unwrap();
+                        let index = struct_def.fields.iter().position( | field | field.ident == *field_ident);
+                        if let (CpuValue::Struct(mut values), Some(index)) = (self.eval_expr(ctx, base_expr), index) {
+                            if index < values.len() {
+                                values[index] = value;
+                                self.assign(ctx, base_expr, CpuValue::Struct(values));
+                            }
+                        }
+                    }
+                    _ => {
+                        let Some(swizzle) = self.shader.swizzle(*field_ident) else {
+                            return
+                        };
+                        let mut base = self.eval_expr(ctx, base_expr);
+                        let mut floats = Vec::with_capacity(4);
+                        value.push_floats(&mut floats);
+                        for (src, dst) in swizzle.iter().enumerate() {
+                            let f = floats.get(src).cloned().unwrap_or(CpuFloat::ZERO);
+                            match &mut base {
+                                CpuValue::Vec(_, v) => v[*dst] = f,
+                                CpuValue::Ivec(_, v) => v[*dst] = f.v as i32,
+                                CpuValue::Bvec(_, v) => v[*dst] = f.v != 0.0,
+                                CpuValue::Float(v) => *v = f,
+                                CpuValue::Int(v) => *v = f.v as i32,
+                                CpuValue::Bool(v) => *v = f.v != 0.0,
+                                _ => ()
+                            }
+                        }
+                        self.assign(ctx, base_expr, base);
+                    }
+                }
+            }
+            ExprKind::Index {expr: base_expr, index_expr, ..} => {
+                let index = self.eval_expr(ctx, index_expr).to_i32().max(0) as usize;
+                let mut base = self.eval_expr(ctx, base_expr);
+                match &mut base {
+                    CpuValue::Array(values) if index < values.len() => values[index] = value,
+                    CpuValue::Vec(n, v) if index < *n => v[index] = value.to_float(),
+                    CpuValue::Ivec(n, v) if index < *n => v[index] = value.to_i32(),
+                    CpuValue::Bvec(n, v) if index < *n => v[index] = value.to_bool(),
+                    CpuValue::Mat(n, m) if index < *n => {
+                        let mut floats = Vec::with_capacity(4);
+                        value.push_floats(&mut floats);
+                        for row in 0..*n {
+                            m[index * *n + row] = floats.get(row).map( | f | f.v).unwrap_or(0.0);
+                        }
+                    }
+                    _ => return
+                }
+                self.assign(ctx, base_expr, base);
+            }
+            _ => ()
+        }
+    }
+
+    fn call_fn(&mut self, ctx: &CallCtx, fn_def: &FnDef, arg_exprs: &[Expr], closure_site_index: Option<usize>) -> CpuValue {
+        // arguments are evaluated in the frame of the caller
+        let mut args = Vec::with_capacity(arg_exprs.len());
+        for arg_expr in arg_exprs {
+            if is_hidden_ty(&arg_expr.ty.borrow()) {
+                continue;
+            }
+            args.push((arg_expr, self.eval_expr(ctx, arg_expr)));
+        }
+        // closure args get the closed over values of the caller passed along
+        let closure_sites = ctx.fn_def.closure_sites.borrow();
+        let closure = closure_site_index.and_then( | site_index | {
+            let site = closure_sites.as_ref()?.get(site_index) ?;
+            let mut captured = Vec::new();
+            for sym in &site.all_closed_over {
+                if let Ty::DrawShader(_) = sym.ty {
+                    continue;
+                }
+                let value = self.find_local(ctx, sym.ident, sym.shadow)
+                    .map( | index | self.locals[index].value.clone()).unwrap_or_default();
+                captured.push(CpuLocal {ident: sym.ident, shadow: Some(sym.shadow), value});
+            }
+            Some(ClosureCtx {
+                site,
+                call_def: ctx.fn_def,
+                captured
+            })
+        });
+
+        let frame_base = self.locals.len();
+        let mut inouts = Vec::new();
+        let mut args = args.into_iter();
+        for param in &fn_def.params {
+            let param_ty = param.ty_expr.ty.borrow().clone();
+            if param.shadow.get().is_none() || is_hidden_ty(&param_ty) {
+                continue;
+            }
+            let Some((arg_expr, value)) = args.next() else {
+                break
+            };
+            if param.is_inout {
+                inouts.push((self.locals.len(), arg_expr));
+            }
+            self.locals.push(CpuLocal {
+                ident: param.ident,
+                shadow: param.shadow.get(),
+                value
+            });
+        }
+        let callee_ctx = CallCtx {
+            fn_def,
+            closure,
+            frame_base,
+        };
+        let ret = match self.exec_block(&callee_ctx, &fn_def.block) {
+            Flow::Return(value) => value,
+            _ => CpuValue::Void
+        };
+        let outs: Vec<(&Expr, CpuValue)> = inouts.into_iter().map( | (slot, arg_expr) | {
+            (arg_expr, std::mem::take(&mut self.locals[slot].value))
+        }).collect();
+        self.locals.truncate(frame_base);
+        for (arg_expr, value) in outs {
+            self.assign(ctx, arg_expr, value);
+        }
+        ret
+    }
+
+    fn call_closure(&mut self, ctx: &CallCtx, arg_exprs: &[Expr], param_index: usize) -> CpuValue {
+        let Some(closure) = &ctx.closure else {
+            return CpuValue::Void
+        };
+        let Some(site_arg) = closure.site.closure_args.iter().find( | arg | arg.param_index == param_index) else {
+            return CpuValue::Void
+        };
+        let closure_def = &closure.call_def.closure_defs[site_arg.closure_def_index.0];
+        let decl_params = match &ctx.fn_def.params[param_index].ty_expr.kind {
+            TyExprKind::ClosureDecl {params, ..} => params,
+            _ => return CpuValue::Void
+        };
+        let args: Vec<CpuValue> = arg_exprs.iter().map( | arg | self.eval_expr(ctx, arg)).collect();
+
+        let frame_base = self.locals.len();
+        self.locals.extend(closure.captured.iter().cloned());
+        let mut inouts = Vec::new();
+        for ((def_param, decl_param), value) in closure_def.params.iter().zip(decl_params.iter()).zip(args) {
+            if decl_param.is_inout {
+                inouts.push(self.locals.len());
+            }
+            self.locals.push(CpuLocal {
+                ident: def_param.ident,
+                shadow: def_param.shadow.get(),
+                value
+            });
+        }
+        let closure_ctx = CallCtx {
+            fn_def: closure.call_def,
+            closure: None,
+            frame_base,
+        };
+        let ret = match &closure_def.kind {
+            ClosureDefKind::Expr(expr) => self.eval_expr(&closure_ctx, expr),
+            ClosureDefKind::Block(block) => match self.exec_block(&closure_ctx, block) {
+                Flow::Return(value) => value,
+                _ => CpuValue::Void
+            }
+        };
+        let outs: Vec<CpuValue> = inouts.iter().map( | slot | std::mem::take(&mut self.locals[*slot].value)).collect();
+        self.locals.truncate(frame_base);
+        for (slot, value) in inouts.iter().zip(outs) {
+            let arg_index = slot - frame_base - closure.captured.len();
+            if let Some(arg_expr) = arg_exprs.get(arg_index) {
+                self.assign(ctx, arg_expr, value);
+            }
+        }
+        ret
+    }
+
+    fn eval_builtin(&mut self, ident: Ident, args: &[CpuValue]) -> CpuValue {
+        let arg = | i: usize | args.get(i).cloned().unwrap_or_default();
+        let a = arg(0);
+        match ident.0 {
+            live_id!(abs) => match &a {
+                CpuValue::Int(v) => CpuValue::Int(v.wrapping_abs()),
+                CpuValue::Ivec(n, v) => {
+                    let mut out = *v;
+                    out.iter_mut().for_each( | v | *v = v.wrapping_abs());
+                    CpuValue::Ivec(*n, out)
+                }
+                _ => a.map1(CpuFloat::abs)
+            },
+            live_id!(sign) => a.map1(CpuFloat::sign),
+            live_id!(floor) => a.map1(CpuFloat::floor),
+            live_id!(ceil) => a.map1(CpuFloat::ceil),
+            live_id!(fract) => a.map1(CpuFloat::fract),
+            live_id!(sin) => a.map1(CpuFloat::sin),
+            live_id!(cos) => a.map1(CpuFloat::cos),
+            live_id!(tan) => a.map1(CpuFloat::tan),
+            live_id!(asin) => a.map1(CpuFloat::asin),
+            live_id!(acos) => a.map1(CpuFloat::acos),
+            live_id!(atan) => if args.len() == 2 {
+                a.map2(&arg(1), CpuFloat::atan2)
+            }
+            else {
+                a.map1(CpuFloat::atan)
+            },
+            live_id!(exp) => a.map1(CpuFloat::exp),
+            live_id!(exp2) => a.map1(CpuFloat::exp2),
+            live_id!(log) => a.map1(CpuFloat::ln),
+            live_id!(log2) => a.map1(CpuFloat::log2),
+            live_id!(sqrt) => a.map1(CpuFloat::sqrt),
+            live_id!(inversesqrt) => a.map1(CpuFloat::inversesqrt),
+            live_id!(radians) => a.map1(CpuFloat::radians),
+            live_id!(degrees) => a.map1(CpuFloat::degrees),
+            live_id!(pow) => a.map2(&arg(1), CpuFloat::pow),
+            live_id!(mod) => a.map2(&arg(1), CpuFloat::modulo),
+            live_id!(step) => a.map2(&arg(1), CpuFloat::step),
+            live_id!(min) => match (&a, &arg(1)) {
+                (CpuValue::Int(_) | CpuValue::Ivec(..), b @ (CpuValue::Int(_) | CpuValue::Ivec(..))) => a.map_int2(b, i32::min),
+                (_, b) => a.map2(b, CpuFloat::min)
+            },
+            live_id!(max) => match (&a, &arg(1)) {
+                (CpuValue::Int(_) | CpuValue::Ivec(..), b @ (CpuValue::Int(_) | CpuValue::Ivec(..))) => a.map_int2(b, i32::max),
+                (_, b) => a.map2(b, CpuFloat::max)
+            },
+            live_id!(clamp) => match (&a, &arg(1), &arg(2)) {
+                (CpuValue::Int(_) | CpuValue::Ivec(..), lo, hi) => a.map_int2(lo, i32::max).map_int2(hi, i32::min),
+                (_, lo, hi) => a.map3(lo, hi, CpuFloat::clamp)
+            },
+            live_id!(mix) => a.map3(&arg(1), &arg(2), CpuFloat::mix),
+            live_id!(smoothstep) => a.map3(&arg(1), &arg(2), CpuFloat::smoothstep),
+            live_id!(length) => CpuValue::Float(a.length()),
+            live_id!(distance) => CpuValue::Float(arith(BinOp::Sub, &a, &arg(1)).length()),
+            live_id!(dot) => CpuValue::Float(a.dot(&arg(1))),
+            live_id!(normalize) => {
+                let len = a.length();
+                a.map1( | v | v / len)
+            }
+            live_id!(cross) => {
+                let (Some((_, x)), Some((_, y))) = (a.float_lanes(), arg(1).float_lanes()) else {
+                    return CpuValue::Void
+                };
+                CpuValue::Vec(3, [
+                    x[1] * y[2] - y[1] * x[2],
+                    x[2] * y[0] - y[2] * x[0],
+                    x[0] * y[1] - y[0] * x[1],
+                    CpuFloat::ZERO
+                ])
+            }
+            live_id!(reflect) => {
+                let n = arg(1);
+                let d = n.dot(&a);
+                a.map2(&n, | i, n | i - CpuFloat::new(2.0) * d * n)
+            }
+            live_id!(refract) => {
+                let n = arg(1);
+                let eta = arg(2).to_float();
+                let d = n.dot(&a);
+                let k = CpuFloat::new(1.0) - eta * eta * (CpuFloat::new(1.0) - d * d);
+                if k.v < 0.0 {
+                    a.map1( | _ | CpuFloat::ZERO)
+                }
+                else {
+                    let s = eta * d + k.sqrt();
+                    a.map2(&n, | i, n | eta * i - s * n)
+                }
+            }
+            live_id!(faceforward) => {
+                if arg(2).dot(&arg(1)).v < 0.0 {a} else {a.map1( | v | -v)}
+            }
+            live_id!(dFdx) => a.map1( | v | CpuFloat::new(v.dx)),
+            live_id!(dFdy) => a.map1( | v | CpuFloat::new(v.dy)),
+            live_id!(lessThan) => a.compare(&arg(1), | a, b | a < b),
+            live_id!(lessThanEqual) => a.compare(&arg(1), | a, b | a <= b),
+            live_id!(greaterThan) => a.compare(&arg(1), | a, b | a > b),
+            live_id!(greaterThanEqual) => a.compare(&arg(1), | a, b | a >= b),
+            live_id!(equal) => a.compare(&arg(1), | a, b | a == b),
+            live_id!(notEqual) => a.compare(&arg(1), | a, b | a != b),
+            live_id!(all) => match a {
+                CpuValue::Bvec(n, v) => CpuValue::Bool(v[0..n].iter().all( | v | *v)),
+                a => CpuValue::Bool(a.to_bool())
+            },
+            live_id!(any) => match a {
+                CpuValue::Bvec(n, v) => CpuValue::Bool(v[0..n].iter().any( | v | *v)),
+                a => CpuValue::Bool(a.to_bool())
+            },
+            live_id!(not) => match a {
+                CpuValue::Bvec(n, mut v) => {
+                    v.iter_mut().for_each( | v | *v = !*v);
+                    CpuValue::Bvec(n, v)
+                }
+                a => CpuValue::Bool(!a.to_bool())
+            },
+            live_id!(transpose) => match a {
+                CpuValue::Mat(n, m) => {
+                    let mut out = [0.0; 16];
+                    for col in 0..n {
+                        for row in 0..n {
+                            out[row * n + col] = m[col * n + row];
+                        }
+                    }
+                    CpuValue::Mat(n, out)
+                }
+                _ => CpuValue::Void
+            },
+            live_id!(inverse) => match a {
+                CpuValue::Mat(n, m) => CpuValue::Mat(n, mat_inverse(n, &m)),
+                _ => CpuValue::Void
+            },
+            live_id!(matrixCompMult) => match (a, arg(1)) {
+                (CpuValue::Mat(n, a), CpuValue::Mat(_, b)) => {
+                    let mut out = [0.0; 16];
+                    for i in 0..n * n {out[i] = a[i] * b[i]}
+                    CpuValue::Mat(n, out)
+                }
+                _ => CpuValue::Void
+            },
+            live_id!(sample2d) | live_id!(sample2d_rt) => {
+                let CpuValue::Texture(slot) = a else {
+                    return CpuValue::Void
+                };
+                let (x, y) = match arg(1) {
+                    CpuValue::Vec(_, v) => (v[0], v[1]),
+                    _ => (CpuFloat::ZERO, CpuFloat::ZERO)
+                };
+                let c = self.textures.sample2d(slot, x, y);
+                CpuValue::Vec(4, [CpuFloat::new(c[0]), CpuFloat::new(c[1]), CpuFloat::new(c[2]), CpuFloat::new(c[3])])
+            }
+            // external (video) textures aren't available in software
+            live_id!(sample2dOES) => CpuValue::Vec(4, [CpuFloat::ZERO; 4]),
+            _ => CpuValue::Void
+        }
+    }
+}
@@ -76,7 +76,63 @@ pub trait BackendWriter {
     
     fn write_ty_lit(&self, string: &mut String, ty_lit: TyLit);
     fn write_builtin_call_ident(&self, string: &mut String, ident: Ident, arg_exprs: &[Expr]);
-    
+
+    // the defaults below write c-style syntax, backends that look different override them
+
+    fn write_fn_def_ident(&self, string: &mut String, ident: &dyn fmt::Display, return_ty: &Ty) {
+        self.write_var_decl(string, "", false, false, ident, return_ty);
+    }
+
+    fn write_fn_def_param(&self, string: &mut String, sep: &'static str, is_inout: bool, ident: &dyn fmt::Display, ty: &Ty) -> bool {
+        self.write_var_decl(string, sep, is_inout, false, ident, ty)
+    }
+
+    fn write_fn_def_return_ty(&self, _string: &mut String, _return_ty: &Ty) {
+    }
+
+    // called at the top of a fn body for every param that isnt inout, for languages with immutable params
+    fn write_fn_def_param_copy(&self, _string: &mut String, _ident: &dyn fmt::Display, _ty: &Ty) {
+    }
+
+    fn write_let_decl(&self, string: &mut String, ident: &dyn fmt::Display, ty: &Ty) {
+        self.write_var_decl(string, "", false, false, ident, ty);
+    }
+
+    fn write_for_stmt_header(&self, string: &mut String, ident: Ident, from: i32, to: i32, step: i32) {
+        write!(
+            string,
+            "for (int {0} = {1}; {0} {2} {3}; {0} {4} {5}) ",
+            ident,
+            if from <= to {from} else {from - 1},
+            if from <= to {"<"} else {">="},
+            to,
+            if step > 0 {"+="} else {"-="},
+            step.abs()
+        ).unwrap();
+    }
+
+    fn write_cond_expr(&self, string: &mut String, expr: &str, expr_if_true: &str, expr_if_false: &str) {
+        write!(string, "({} ? {} : {})", expr, expr_if_true, expr_if_false).unwrap();
+    }
+
+    fn write_builtin_call(&self, string: &mut String, ident: Ident, arg_exprs: &[Expr], args: &[String]) {
+        self.write_builtin_call_ident(string, ident, arg_exprs);
+        write!(string, "({})", args.join(", ")).unwrap();
+    }
+
+    // false if assignments are statements and cant be wrapped in parens
+    fn assign_is_expr(&self) -> bool {
+        true
+    }
+
+    // written in front of args passed to inout params
+    fn inout_arg_prefix(&self) -> &'static str {
+        ""
+    }
+
+    fn write_inout_param_var(&self, string: &mut String, ident: &dyn fmt::Display) {
+        write!(string, "{}", ident).unwrap();
+    }
 }
 
 pub struct BlockGenerator<'a> {
@@ -100,25 +156,31 @@ pub fn generate_cons_fn(backend_writer:&dyn BackendWriter, string: &mut String,
     if !backend_writer.use_cons_fn(&cons_name) {
         return
     }
-    backend_writer.write_ty_lit(string, ty_lit);
-    write!(string, " {}(", cons_name).unwrap();
-    
+    let ty = ty_lit.to_ty();
+    backend_writer.write_fn_def_ident(string, &cons_name, &ty);
+    write!(string, "(").unwrap();
+
     let mut sep = "";
+    let mut param_copies = String::new();
     if param_tys.len() == 1 {
-        backend_writer.write_var_decl(string, sep, false, false, &Ident(live_id!(x)), &param_tys[0]);
+        backend_writer.write_fn_def_param(string, sep, false, &Ident(live_id!(x)), &param_tys[0]);
+        write_param_copy(backend_writer, &mut param_copies, &Ident(live_id!(x)), &param_tys[0]);
     } else {
         for (index, param_ty) in param_tys.iter().enumerate() {
             //write!(string, "{}", sep).unwrap();
-            backend_writer.write_var_decl(string, sep, false, false,&DisplaConstructorArg(index), param_ty);
+            backend_writer.write_fn_def_param(string, sep, false, &DisplaConstructorArg(index), param_ty);
+            write_param_copy(backend_writer, &mut param_copies, &DisplaConstructorArg(index), param_ty);
             sep = ", ";
         }
     }
     
-    writeln!(string, ") {{").unwrap();
+    write!(string, ")").unwrap();
+    backend_writer.write_fn_def_return_ty(string, &ty);
+    writeln!(string, " {{").unwrap();
+    write!(string, "{}", param_copies).unwrap();
     write!(string, "    return ").unwrap();
     backend_writer.write_ty_lit(string, ty_lit);
     write!(string, "(").unwrap();
-    let ty = ty_lit.to_ty();
     if param_tys.len() == 1 {
         let param_ty = &param_tys[0];
         match param_ty {
@@ -181,9 +243,22 @@ pub fn generate_cons_fn(backend_writer:&dyn BackendWriter, string: &mut String,
     writeln!(string, "}}\n").unwrap();
 }
 
+fn write_param_copy(backend_writer: &dyn BackendWriter, param_copies: &mut String, ident: &dyn fmt::Display, ty: &Ty) {
+    let mut param_copy = String::new();
+    backend_writer.write_fn_def_param_copy(&mut param_copy, ident, ty);
+    if !param_copy.is_empty() {
+        write!(param_copies, "    {}\n", param_copy).unwrap();
+    }
+}
+
 impl<'a> BlockGenerator<'a> {
     pub fn generate_block(&mut self, block: &Block) {
+        self.generate_block_with_param_copies(block, "")
+    }
+    
+    pub fn generate_block_with_param_copies(&mut self, block: &Block, param_copies: &str) {
         write!(self.string, "{{\n").unwrap();
+        write!(self.string, "{}", param_copies).unwrap();
         self.write_indent();
         if !block.stmts.is_empty() {
             self.indent_level += 1;
@@ -285,17 +360,7 @@ impl<'a> BlockGenerator<'a> {
         } else {
             -1
         };
-        write!(
-            self.string,
-            "for (int {0} = {1}; {0} {2} {3}; {0} {4} {5}) ",
-            ident,
-            if from <= to {from} else {from - 1},
-            if from <= to {"<"} else {">="},
-            to,
-            if step > 0 {"+="} else {"-="},
-            step.abs()
-        )
-            .unwrap();
+        self.backend_writer.write_for_stmt_header(&mut self.string, ident, from, to, step);
         self.generate_block(block);
         writeln!(self.string).unwrap();
     }
@@ -358,11 +423,8 @@ impl<'a> BlockGenerator<'a> {
         expr: &Option<Expr>,
         shadow: &Cell<Option<ScopeSymShadow >>
     ) {
-        self.backend_writer.write_var_decl(
+        self.backend_writer.write_let_decl(
             &mut self.string,
-            "",
-            false, 
-            false,
             &DisplayVarName(ident, shadow.get().unwrap()),
             ty.borrow().as_ref().unwrap()
         );
@@ -566,13 +628,24 @@ impl<'a> ExprGenerator<'a> {
         expr_if_true: &Expr,
         expr_if_false: &Expr,
     ) {
-        write!(self.string, "(").unwrap();
-        self.generate_expr(expr);
-        write!(self.string, " ? ").unwrap();
-        self.generate_expr(expr_if_true);
-        write!(self.string, " : ").unwrap();
-        self.generate_expr(expr_if_false);
-        write!(self.string, ")").unwrap();
+        let expr = self.generate_expr_to_string(expr);
+        let expr_if_true = self.generate_expr_to_string(expr_if_true);
+        let expr_if_false = self.generate_expr_to_string(expr_if_false);
+        self.backend_writer.write_cond_expr(self.string, &expr, &expr_if_true, &expr_if_false);
+    }
+    
+    fn generate_expr_to_string(&mut self, expr: &Expr) -> String {
+        let mut string = String::new();
+        ExprGenerator {
+            closure_site_info: self.closure_site_info.clone(),
+            fn_def: self.fn_def,
+            shader_registry: self.shader_registry,
+            backend_writer: self.backend_writer,
+            const_table_offset: self.const_table_offset,
+            string: &mut string,
+        }
+        .generate_expr(expr);
+        string
     }
     
     fn generate_bin_expr(&mut self, _span: TokenSpan, op: BinOp, left_expr: &Expr, right_expr: &Expr) {
@@ -650,6 +723,17 @@ impl<'a> ExprGenerator<'a> {
             }
         }
         
+        let is_assign = match op {
+            BinOp::Assign | BinOp::AddAssign | BinOp::SubAssign | BinOp::MulAssign | BinOp::DivAssign => true,
+            _ => false
+        };
+        if is_assign && !self.backend_writer.assign_is_expr() {
+            self.generate_expr(left_expr);
+            write!(self.string, " {} ", op).unwrap();
+            self.generate_expr(right_expr);
+            return
+        }
+        
         write!(self.string, "(").unwrap();
         self.generate_expr(left_expr);
         write!(self.string, " {} ", op).unwrap();
@@ -711,7 +795,7 @@ impl<'a> ExprGenerator<'a> {
             )).unwrap();
             
             let mut sep = "";
-            for arg_expr in arg_exprs {
+            for (index, arg_expr) in arg_exprs.iter().enumerate() {
                 // check if the args is a closure, ifso skip it
                 match arg_expr.ty.borrow().as_ref().unwrap(){
                     Ty::ClosureDef(_)=>{
@@ -721,7 +805,7 @@ impl<'a> ExprGenerator<'a> {
                 }
                 
                 write!(self.string, "{}", sep).unwrap();
-                self.generate_expr(arg_expr);
+                self.generate_call_arg(fn_def, arg_exprs.len(), index, arg_expr);
                 sep = ", ";
             }
             // and now the closed over values
@@ -748,9 +832,9 @@ impl<'a> ExprGenerator<'a> {
         else {
             write!(self.string, "{}_{} (", fn_def.fn_ptr, fn_def.ident).unwrap();
             let mut sep = "";
-            for arg_expr in arg_exprs {
+            for (index, arg_expr) in arg_exprs.iter().enumerate() {
                 write!(self.string, "{}", sep).unwrap();
-                self.generate_expr(arg_expr);
+                self.generate_call_arg(fn_def, arg_exprs.len(), index, arg_expr);
                 sep = ", ";
             }
 
@@ -760,6 +844,15 @@ impl<'a> ExprGenerator<'a> {
         }
     }
     
+    fn generate_call_arg(&mut self, fn_def: &FnDef, arg_count: usize, index: usize, arg_expr: &Expr) {
+        // draw shader method calls dont pass self, so line up the args with the params from the end
+        let param = &fn_def.params[index + fn_def.params.len() - arg_count];
+        if param.is_inout {
+            write!(self.string, "{}", self.backend_writer.inout_arg_prefix()).unwrap();
+        }
+        self.generate_expr(arg_expr);
+    }
+    
     fn generate_field_expr(&mut self, _span: TokenSpan, expr: &Expr, field_ident: Ident, ty:&Ty) {
         match expr.ty.borrow().as_ref() {
             Some(Ty::DrawShader(_)) => {
@@ -821,19 +914,8 @@ impl<'a> ExprGenerator<'a> {
     fn generate_builtin_call_expr(&mut self, _span: TokenSpan, ident: Ident, arg_exprs: &[Expr]) {
         // lets create a fn name for this thing.
         
-        self.backend_writer.write_builtin_call_ident(&mut self.string, ident, arg_exprs);
-        
-        write!(self.string, "(").unwrap();
-        let mut sep = "";
-        for arg_expr in arg_exprs {
-            write!(self.string, "{}", sep).unwrap();
-            
-            self.generate_expr(arg_expr);
-            
-            sep = ", ";
-        }
-        
-        write!(self.string, ")").unwrap();
+        let args: Vec<String> = arg_exprs.iter().map( | arg_expr | self.generate_expr_to_string(arg_expr)).collect();
+        self.backend_writer.write_builtin_call(&mut self.string, ident, arg_exprs, &args);
     }
     
     
@@ -926,7 +1008,15 @@ impl<'a> ExprGenerator<'a> {
                 write!(self.string, "{}", DisplayVarName(ident, shadow)).unwrap();
             }
             VarKind::MutLocal {ident, shadow} => {
-                write!(self.string, "{}", DisplayVarName(ident, shadow)).unwrap();
+                let is_inout_param = self.fn_def.map_or(false, | fn_def | fn_def.params.iter().any( | param | {
+                    param.is_inout && param.ident == ident && param.shadow.get() == Some(shadow)
+                }));
+                if is_inout_param {
+                    self.backend_writer.write_inout_param_var(self.string, &DisplayVarName(ident, shadow));
+                }
+                else {
+                    write!(self.string, "{}", DisplayVarName(ident, shadow)).unwrap();
+                }
             }
             VarKind::LiveValue(value_node_ptr) => {
                 // this is a live value.. also prefix needed
@@ -960,38 +1050,43 @@ pub struct FnDefGenerator<'a> {
 impl<'a> FnDefGenerator<'a> {
     pub fn generate_fn_def(&mut self) {
         
-        self.backend_writer.write_var_decl(
+        let return_ty = self.fn_def.return_ty.borrow();
+        self.backend_writer.write_fn_def_ident(
             &mut self.string,
-            "",
-            false,
-            false,
             &DisplayFnName(self.fn_def.fn_ptr, self.fn_def.ident), // here we must expand IdentPath to something
-            self.fn_def.return_ty.borrow().as_ref().unwrap()
+            return_ty.as_ref().unwrap()
         );
         write!(self.string, "(").unwrap();
         let mut sep = "";
+        let mut param_copies = String::new();
         for param in &self.fn_def.params {
             if !param.shadow.get().is_none() {
-                if self.backend_writer.write_var_decl(
+                let ident = DisplayVarName(param.ident, param.shadow.get().unwrap());
+                let ty = param.ty_expr.ty.borrow();
+                if self.backend_writer.write_fn_def_param(
                     &mut self.string,
                     sep,
                     param.is_inout,
-                    false,
-                    &DisplayVarName(param.ident, param.shadow.get().unwrap()),
-                    param.ty_expr.ty.borrow().as_ref().unwrap(),
+                    &ident,
+                    ty.as_ref().unwrap(),
                 ) {
                     sep = ", ";
+                    if !param.is_inout {
+                        write_param_copy(self.backend_writer, &mut param_copies, &ident, ty.as_ref().unwrap());
+                    }
                 }
             }
         }
         self.backend_writer.write_fn_def_hidden_params(self.string, self.fn_def.hidden_args.borrow().as_ref().unwrap(), sep);
-        write!(self.string, ") ").unwrap();
-        self.generate_block(&self.fn_def.block);
+        write!(self.string, ")").unwrap();
+        self.backend_writer.write_fn_def_return_ty(&mut self.string, return_ty.as_ref().unwrap());
+        write!(self.string, " ").unwrap();
+        self.generate_block(&self.fn_def.block, &param_copies);
         writeln!(self.string).unwrap();
         //self.visited.insert(self.decl.ident_path);
     }
     
-    fn generate_block(&mut self, block: &Block) {
+    fn generate_block(&mut self, block: &Block, param_copies: &str) {
         BlockGenerator {
             shader_registry: self.shader_registry,
             closure_site_info: None,
@@ -1002,7 +1097,7 @@ impl<'a> FnDefGenerator<'a> {
             indent_level: 0,
             string: self.string,
         }
-        .generate_block(block)
+        .generate_block_with_param_copies(block, param_copies)
     }
 }
 
@@ -1077,46 +1172,50 @@ impl<'a> FnDefWithClosureArgsGenerator<'a> {
     
     pub fn generate_fn_def_with_closure_args(&mut self) {
         
-        self.backend_writer.write_var_decl(
+        let return_ty = self.fn_def.return_ty.borrow();
+        self.backend_writer.write_fn_def_ident(
             &mut self.string,
-            "",
-            false,
-            false,
             &DisplayFnNameWithClosureArgs(
                 self.closure_site_info.site_index,
                 self.call_def.fn_ptr,
                 self.fn_def.ident
             ), // here we must expand IdentPath to something
-            self.fn_def.return_ty.borrow().as_ref().unwrap()
+            return_ty.as_ref().unwrap()
         );
         write!(self.string, "(").unwrap();
         let mut sep = "";
+        let mut param_copies = String::new();
         for param in &self.fn_def.params {
             if !param.shadow.get().is_none() {
-                if self.backend_writer.write_var_decl(
+                let ident = DisplayVarName(param.ident, param.shadow.get().unwrap());
+                let ty = param.ty_expr.ty.borrow();
+                if self.backend_writer.write_fn_def_param(
                     &mut self.string,
                     sep,
                     param.is_inout,
-                    false,
-                    &DisplayVarName(param.ident, param.shadow.get().unwrap()),
-                    param.ty_expr.ty.borrow().as_ref().unwrap(),
+                    &ident,
+                    ty.as_ref().unwrap(),
                 ) {
                     sep = ", ";
+                    if !param.is_inout {
+                        write_param_copy(self.backend_writer, &mut param_copies, &ident, ty.as_ref().unwrap());
+                    }
                 }
             }
         }
         // now we iterate over the closures in our site,
         // and we need to merge the set of closed over args.
         for sym in &self.closure_site_info.closure_site.all_closed_over {
-            if self.backend_writer.write_var_decl(
+            let ident = DisplayClosedOverArg(sym.ident, sym.shadow);
+            if self.backend_writer.write_fn_def_param(
                 &mut self.string,
                 sep,
                 false,
-                false,
-                &DisplayClosedOverArg(sym.ident, sym.shadow),
+                &ident,
                 &sym.ty,
             ) {
                 sep = ", ";
+                write_param_copy(self.backend_writer, &mut param_copies, &ident, &sym.ty);
             }
         }
         // we need the union of the call def and the fn def
@@ -1125,17 +1224,19 @@ impl<'a> FnDefWithClosureArgsGenerator<'a> {
         merged_hidden_args.extend(self.call_def.hidden_args.borrow().as_ref().unwrap().iter().cloned());
         self.backend_writer.write_fn_def_hidden_params(self.string, &merged_hidden_args, sep);
         
-        write!(self.string, ") ").unwrap();
+        write!(self.string, ")").unwrap();
+        self.backend_writer.write_fn_def_return_ty(&mut self.string, return_ty.as_ref().unwrap());
+        write!(self.string, " ").unwrap();
         // alright so here the block is generated.. however
         // we need to know the names and the closed-over-args passthrough
-        self.generate_block(&self.fn_def.block);
+        self.generate_block(&self.fn_def.block, &param_copies);
         
         
         writeln!(self.string).unwrap();
         //self.visited.insert(self.decl.ident_path);
     }
     
-    fn generate_block(&mut self, block: &Block) {
+    fn generate_block(&mut self, block: &Block, param_copies: &str) {
         BlockGenerator {
             shader_registry: self.shader_registry,
             closure_site_info: Some(self.closure_site_info.clone()),
@@ -1146,7 +1247,7 @@ impl<'a> FnDefWithClosureArgsGenerator<'a> {
             indent_level: 0,
             string: self.string,
         }
-        .generate_block(block)
+        .generate_block_with_param_copies(block, param_copies)
     }
 }
 
@@ -1168,15 +1269,15 @@ impl<'a> ClosureDefGenerator<'a> {
         
         let mut sep = "";
         
-        if let TyExprKind::ClosureDecl {params, return_ty, ..} = &fn_param.ty_expr.kind {
+        let mut param_copies = String::new();
+        
+        let return_ty = if let TyExprKind::ClosureDecl {params, return_ty, ..} = &fn_param.ty_expr.kind {
             
-            self.backend_writer.write_var_decl(
+            let return_ty = return_ty.borrow().as_ref().unwrap().clone();
+            self.backend_writer.write_fn_def_ident(
                 &mut self.string,
-                "",
-                false,
-                false,
                 &DisplayClosureName(self.call_def.fn_ptr, self.closure_site_arg.closure_def_index), // here we must expand IdentPath to something
-                return_ty.borrow().as_ref().unwrap(),
+                &return_ty,
             );
             write!(self.string, "(").unwrap();
             
@@ -1185,32 +1286,38 @@ impl<'a> ClosureDefGenerator<'a> {
                 // lets fetch the name of this thing
                 let closure_param = &self.closure_def.params[param_index];
                 let shadow = closure_param.shadow.get().unwrap();
-                if self.backend_writer.write_var_decl(
+                let ident = DisplayVarName(closure_param.ident, shadow);
+                let ty = param.ty_expr.ty.borrow();
+                if self.backend_writer.write_fn_def_param(
                     &mut self.string,
                     sep,
                     param.is_inout,
-                    false,
-                    &DisplayVarName(closure_param.ident, shadow),
-                    param.ty_expr.ty.borrow().as_ref().unwrap(),
+                    &ident,
+                    ty.as_ref().unwrap(),
                 ) {
                     sep = ", ";
+                    if !param.is_inout {
+                        write_param_copy(self.backend_writer, &mut param_copies, &ident, ty.as_ref().unwrap());
+                    }
                 }
             }
+            return_ty
         }
         else {
             panic!()
-        }
+        };
         
         for sym in self.closure_def.closed_over_syms.borrow().as_ref().unwrap() {
-            if self.backend_writer.write_var_decl(
+            let ident = DisplayVarName(sym.ident, sym.shadow);
+            if self.backend_writer.write_fn_def_param(
                 &mut self.string,
                 sep,
                 false,
-                false,
-                &DisplayVarName(sym.ident, sym.shadow),
+                &ident,
                 &sym.ty,
             ) {
                 sep = ", ";
+                write_param_copy(self.backend_writer, &mut param_copies, &ident, &sym.ty);
             }
         } 
 
@@ -1219,24 +1326,28 @@ impl<'a> ClosureDefGenerator<'a> {
         merged_hidden_args.extend(self.call_def.hidden_args.borrow().as_ref().unwrap().iter().cloned());
         self.backend_writer.write_fn_def_hidden_params(self.string, &merged_hidden_args, sep);
         
-        writeln!(self.string, ") {{").unwrap();
+        write!(self.string, ")").unwrap();
+        self.backend_writer.write_fn_def_return_ty(&mut self.string, &return_ty);
+        write!(self.string, " ").unwrap();
         
         match &self.closure_def.kind {
             ClosureDefKind::Expr(expr) => {
+                writeln!(self.string, "{{").unwrap();
+                write!(self.string, "{}", param_copies).unwrap();
                 write!(self.string, "    return ").unwrap();
                 self.generate_expr(expr);
                 writeln!(self.string, ";").unwrap();
                 writeln!(self.string, "}}").unwrap();
             }
             ClosureDefKind::Block(block) => {
-                self.generate_block(block);
+                self.generate_block(block, &param_copies);
                 writeln!(self.string).unwrap();
             }
         }
         //self.visited.insert(self.decl.ident_path);
     }
     
-    fn generate_block(&mut self, block: &Block) {
+    fn generate_block(&mut self, block: &Block, param_copies: &str) {
         BlockGenerator {
             shader_registry: self.shader_registry,
             closure_site_info: None,
@@ -1247,7 +1358,7 @@ impl<'a> ClosureDefGenerator<'a> {
             indent_level: 0,
             string: self.string,
         }
-        .generate_block(block)
+        .generate_block_with_param_copies(block, param_copies)
     }
     
     
@@ -0,0 +1,819 @@
+use {
+    std::{
+        fmt,
+        fmt::Write,
+        collections::{BTreeMap, BTreeSet},
+    },
+    crate::{
+        makepad_live_id::{
+            live_id,
+            LiveId,
+        },
+        generate::*,
+        shader_ast::*,
+        shader_registry::ShaderRegistry
+    }
+};
+
+// Generates a single WGSL module with a vertex_main and a fragment_main entrypoint.
+// Everything lives in bind group 0, in this order:
+// const_table, live_table, one {block}_table per uniform block, the textures and then one sampler.
+// The tables are flat float tables like the GLSL backend uses, rounded up to whole vec4's.
+// Geometries and instances are packed into vec4 vertex attributes (geometries first),
+// varyings are packed into vec4 locations of the Varyings struct.
+
+pub fn generate_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> String {
+    let mut string = String::new();
+    let fields_as_uniform_blocks = draw_shader_def.fields_as_uniform_blocks();
+    DrawShaderGenerator {
+        draw_shader_def,
+        shader_registry,
+        const_table,
+        string: &mut string,
+        fields_as_uniform_blocks: &fields_as_uniform_blocks,
+        backend_writer: &WgslBackendWriter {}
+    }
+    .generate_shader();
+    string
+}
+
+const INVERSE_FNS: &str = "fn inverse_mat2(m: mat2x2<f32>) -> mat2x2<f32> {
+    let det = m[0][0] * m[1][1] - m[1][0] * m[0][1];
+    return mat2x2<f32>(m[1][1], -m[0][1], -m[1][0], m[0][0]) * (1.0 / det);
+}
+
+fn inverse_mat3(m: mat3x3<f32>) -> mat3x3<f32> {
+    let r0 = cross(m[1], m[2]);
+    let r1 = cross(m[2], m[0]);
+    let r2 = cross(m[0], m[1]);
+    return transpose(mat3x3<f32>(r0, r1, r2)) * (1.0 / dot(m[0], r0));
+}
+
+fn inverse_mat4(m: mat4x4<f32>) -> mat4x4<f32> {
+    let a00 = m[0][0]; let a01 = m[0][1]; let a02 = m[0][2]; let a03 = m[0][3];
+    let a10 = m[1][0]; let a11 = m[1][1]; let a12 = m[1][2]; let a13 = m[1][3];
+    let a20 = m[2][0]; let a21 = m[2][1]; let a22 = m[2][2]; let a23 = m[2][3];
+    let a30 = m[3][0]; let a31 = m[3][1]; let a32 = m[3][2]; let a33 = m[3][3];
+    let b00 = a00 * a11 - a01 * a10;
+    let b01 = a00 * a12 - a02 * a10;
+    let b02 = a00 * a13 - a03 * a10;
+    let b03 = a01 * a12 - a02 * a11;
+    let b04 = a01 * a13 - a03 * a11;
+    let b05 = a02 * a13 - a03 * a12;
+    let b06 = a20 * a31 - a21 * a30;
+    let b07 = a20 * a32 - a22 * a30;
+    let b08 = a20 * a33 - a23 * a30;
+    let b09 = a21 * a32 - a22 * a31;
+    let b10 = a21 * a33 - a23 * a31;
+    let b11 = a22 * a33 - a23 * a32;
+    let det = b00 * b11 - b01 * b10 + b02 * b09 + b03 * b08 - b04 * b07 + b05 * b06;
+    return mat4x4<f32>(
+        a11 * b11 - a12 * b10 + a13 * b09,
+        a02 * b10 - a01 * b11 - a03 * b09,
+        a31 * b05 - a32 * b04 + a33 * b03,
+        a22 * b04 - a21 * b05 - a23 * b03,
+        a12 * b08 - a10 * b11 - a13 * b07,
+        a00 * b11 - a02 * b08 + a03 * b07,
+        a32 * b02 - a30 * b05 - a33 * b01,
+        a20 * b05 - a22 * b02 + a23 * b01,
+        a10 * b10 - a11 * b08 + a13 * b06,
+        a01 * b08 - a00 * b10 - a03 * b06,
+        a30 * b04 - a31 * b02 + a33 * b00,
+        a21 * b02 - a20 * b04 - a23 * b00,
+        a11 * b07 - a10 * b09 - a12 * b06,
+        a00 * b09 - a01 * b07 + a02 * b06,
+        a31 * b01 - a30 * b03 - a32 * b00,
+        a20 * b03 - a21 * b01 + a22 * b00
+    ) * (1.0 / det);
+}
+
+";
+
+struct DrawShaderGenerator<'a> {
+    draw_shader_def: &'a DrawShaderDef,
+    shader_registry: &'a ShaderRegistry,
+    string: &'a mut String,
+    fields_as_uniform_blocks: &'a BTreeMap<Ident, Vec<(usize, Ident) >>,
+    backend_writer: &'a dyn BackendWriter,
+    const_table: &'a DrawShaderConstTable
+}
+
+impl<'a> DrawShaderGenerator<'a> {
+    fn generate_shader(&mut self) {
+        // textureSample needs uniform control flow, which the shader dsl doesnt know about
+        writeln!(self.string, "diagnostic(off, derivative_uniformity);").unwrap();
+        writeln!(self.string).unwrap();
+
+        let mut all_constructor_fns = BTreeSet::new();
+        let mut all_builtin_deps = BTreeSet::new();
+        for fn_iter in self.draw_shader_def.all_fns.borrow().iter() {
+            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
+            all_constructor_fns.extend(fn_def.constructor_fn_deps.borrow().as_ref().unwrap().iter().cloned());
+            all_builtin_deps.extend(fn_def.builtin_deps.borrow().as_ref().unwrap().iter().cloned());
+        }
+
+        self.generate_bindings();
+        self.generate_private_decls();
+        self.generate_struct_defs();
+        self.generate_builtin_fns(&all_builtin_deps);
+
+        for (ty_lit, ref param_tys) in all_constructor_fns {
+            generate_cons_fn(self.backend_writer, self.string, ty_lit, &param_tys);
+        }
+
+        let all_fns = self.draw_shader_def.all_fns.borrow();
+        for fn_iter in all_fns.iter().rev() {
+            let const_table_offset = self.const_table.offsets.get(fn_iter).cloned();
+            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
+            if fn_def.has_closure_args() {
+                for call_iter in all_fns.iter().rev() {
+                    // any function that depends on us, will have the closures we need
+                    let call_def = self.shader_registry.all_fns.get(call_iter).unwrap();
+                    if call_def.callees.borrow().as_ref().unwrap().contains(&fn_iter) {
+                        FnDefWithClosureArgsGenerator::generate_fn_def_with_all_closures(
+                            &mut self.string,
+                            self.shader_registry,
+                            fn_def,
+                            call_def,
+                            self.backend_writer,
+                            const_table_offset
+                        );
+                    }
+                }
+                continue
+            }
+            FnDefGenerator {
+                fn_def,
+                const_table_offset,
+                shader_registry: self.shader_registry,
+                backend_writer: self.backend_writer,
+                string: self.string,
+            }
+            .generate_fn_def();
+            writeln!(self.string).unwrap();
+        }
+
+        self.generate_varying_struct();
+        self.generate_vertex_main();
+        self.generate_pixel_main();
+    }
+
+    fn generate_bindings(&mut self) {
+        let mut binding = 0;
+        if self.const_table.table.len() > 0 {
+            writeln!(
+                self.string,
+                "@group(0) @binding({}) var<uniform> const_table: array<vec4<f32>, {}>;",
+                binding,
+                vec4_count(self.const_table.table.len())
+            ).unwrap();
+            binding += 1;
+        }
+
+        let live_slots = self.calc_live_slots();
+        if live_slots > 0 {
+            writeln!(self.string, "@group(0) @binding({}) var<uniform> live_table: array<vec4<f32>, {}>;", binding, vec4_count(live_slots)).unwrap();
+            binding += 1;
+        }
+
+        for (ident, vec) in self.fields_as_uniform_blocks {
+            let mut slots = 0;
+            for (index, _item) in vec {
+                slots += self.draw_shader_def.fields[*index].ty_expr.ty.borrow().as_ref().unwrap().slots();
+            }
+            writeln!(self.string, "@group(0) @binding({}) var<uniform> {}_table: array<vec4<f32>, {}>;", binding, ident, vec4_count(slots)).unwrap();
+            binding += 1;
+        }
+
+        let mut has_textures = false;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Texture {..} = field.kind {
+                write!(self.string, "@group(0) @binding({}) var ", binding).unwrap();
+                self.write_var_decl(&DisplayDsIdent(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap());
+                writeln!(self.string, ";").unwrap();
+                binding += 1;
+                has_textures = true;
+            }
+        }
+        if has_textures {
+            writeln!(self.string, "@group(0) @binding({}) var default_sampler: sampler;", binding).unwrap();
+        }
+        writeln!(self.string).unwrap();
+    }
+
+    fn generate_private_decls(&mut self) {
+        for (live_ref, ty) in self.draw_shader_def.all_live_refs.borrow().iter() {
+            self.write_private_decl(live_ref, ty);
+        }
+        for field in &self.draw_shader_def.fields {
+            match field.kind {
+                DrawShaderFieldKind::Texture {..} => (),
+                _ if field.ident == Ident(LiveId(0)) => (), // instance padding
+                _ => self.write_private_decl(&DisplayDsIdent(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap())
+            }
+        }
+        writeln!(self.string).unwrap();
+    }
+
+    fn write_private_decl(&mut self, ident: &dyn fmt::Display, ty: &Ty) {
+        write!(self.string, "var<private> ").unwrap();
+        self.write_var_decl(ident, ty);
+        writeln!(self.string, ";").unwrap();
+    }
+
+    fn generate_struct_defs(&mut self) {
+        // we have all the structs already from analyse
+        for struct_ptr in self.draw_shader_def.all_structs.borrow().iter().rev() {
+            let struct_def = self.shader_registry.structs.get(struct_ptr).unwrap();
+            writeln!(self.string, "struct {} {{", struct_ptr).unwrap();
+            for field in &struct_def.fields {
+                write!(self.string, "    ").unwrap();
+                self.write_var_decl(&DisplayStructField(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap());
+                writeln!(self.string, ",").unwrap();
+            }
+            writeln!(self.string, "}}\n").unwrap();
+        }
+    }
+
+    fn generate_builtin_fns(&mut self, all_builtin_deps: &BTreeSet<Ident>) {
+        // textureSample is fragment only, so if the vertex shader samples we pin the lod
+        let mut vertex_samples = false;
+        for fn_iter in self.draw_shader_def.vertex_fns.borrow().iter() {
+            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
+            let builtin_deps = fn_def.builtin_deps.borrow();
+            let builtin_deps = builtin_deps.as_ref().unwrap();
+            if builtin_deps.contains(&Ident(live_id!(sample2d))) || builtin_deps.contains(&Ident(live_id!(sample2d_rt))) {
+                vertex_samples = true;
+            }
+        }
+        let sample = if vertex_samples {"textureSampleLevel(tex, default_sampler, pos, 0.0)"} else {"textureSample(tex, default_sampler, pos)"};
+
+        // render targets and textures share the same orientation in webgpu
+        for ident in [Ident(live_id!(sample2d)), Ident(live_id!(sample2d_rt))] {
+            if all_builtin_deps.contains(&ident) {
+                writeln!(self.string, "fn {}(tex: texture_2d<f32>, pos: vec2<f32>) -> vec4<f32> {{", ident).unwrap();
+                writeln!(self.string, "    return {};", sample).unwrap();
+                writeln!(self.string, "}}\n").unwrap();
+            }
+        }
+        if all_builtin_deps.contains(&Ident(live_id!(sample2dOES))) {
+            writeln!(self.string, "fn sample2dOES(tex: texture_external, pos: vec2<f32>) -> vec4<f32> {{").unwrap();
+            writeln!(self.string, "    return textureSampleBaseClampToEdge(tex, default_sampler, pos);").unwrap();
+            writeln!(self.string, "}}\n").unwrap();
+        }
+        if all_builtin_deps.contains(&Ident(live_id!(inverse))) {
+            write!(self.string, "{}", INVERSE_FNS).unwrap();
+        }
+    }
+
+    fn generate_varying_struct(&mut self) {
+        writeln!(self.string, "struct Varyings {{").unwrap();
+        writeln!(self.string, "    @builtin(position) position: vec4<f32>,").unwrap();
+        for (index, size) in packed_sizes(self.compute_packed_varyings_slots()).iter().enumerate() {
+            writeln!(self.string, "    @location({0}) packed_varying_{0}: {1},", index, packed_ty(*size)).unwrap();
+        }
+        writeln!(self.string, "}}\n").unwrap();
+    }
+
+    fn generate_vertex_main(&mut self) {
+        let packed_geometries = packed_sizes(self.compute_packed_geometries_slots());
+        let packed_instances = packed_sizes(self.compute_packed_instances_slots());
+
+        writeln!(self.string, "@vertex").unwrap();
+        writeln!(self.string, "fn vertex_main(").unwrap();
+        let mut location = 0;
+        for (index, size) in packed_geometries.iter().enumerate() {
+            writeln!(self.string, "    @location({}) packed_geometry_{}: {},", location, index, packed_ty(*size)).unwrap();
+            location += 1;
+        }
+        for (index, size) in packed_instances.iter().enumerate() {
+            writeln!(self.string, "    @location({}) packed_instance_{}: {},", location, index, packed_ty(*size)).unwrap();
+            location += 1;
+        }
+        writeln!(self.string, ") -> Varyings {{").unwrap();
+
+        self.generate_uniform_block_unpack();
+        self.generate_live_unpack();
+
+        let mut slot = 0;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Geometry {..} = field.kind {
+                slot = self.generate_packed_unpack(field, "packed_geometry", &packed_geometries, slot);
+            }
+        }
+        let mut slot = 0;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Instance {..} = field.kind {
+                slot = self.generate_packed_unpack(field, "packed_instance", &packed_instances, slot);
+            }
+        }
+        writeln!(self.string).unwrap();
+
+        let vertex_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(vertex))).unwrap();
+        writeln!(self.string, "    var varyings: Varyings;").unwrap();
+        writeln!(self.string, "    varyings.position = {}();", DisplayFnName(vertex_def.fn_ptr, vertex_def.ident)).unwrap();
+
+        let mut components = Vec::new();
+        for field in self.varying_fields() {
+            let ident = DisplayDsIdent(field.ident);
+            match field.ty_expr.ty.borrow().as_ref().unwrap() {
+                Ty::Float | Ty::Enum(_) => components.push(format!("{}", ident)),
+                Ty::Vec2 | Ty::Vec3 | Ty::Vec4 => {
+                    for index in 0..field.ty_expr.ty.borrow().as_ref().unwrap().slots() {
+                        components.push(format!("{}.{}", ident, swizzle_component(index)));
+                    }
+                }
+                Ty::Mat2 | Ty::Mat3 | Ty::Mat4 => {
+                    let size = mat_size(field.ty_expr.ty.borrow().as_ref().unwrap());
+                    for index in 0..size * size {
+                        components.push(format!("{}[{}][{}]", ident, index / size, index % size));
+                    }
+                }
+                ty => panic!("unsupported varying type {:?}", ty)
+            }
+        }
+        for (index, chunk) in components.chunks(4).enumerate() {
+            if chunk.len() == 1 {
+                writeln!(self.string, "    varyings.packed_varying_{} = {};", index, chunk[0]).unwrap();
+            }
+            else {
+                writeln!(self.string, "    varyings.packed_varying_{} = {}({});", index, packed_ty(chunk.len()), chunk.join(", ")).unwrap();
+            }
+        }
+        writeln!(self.string, "    return varyings;").unwrap();
+        writeln!(self.string, "}}\n").unwrap();
+    }
+
+    fn generate_pixel_main(&mut self) {
+        let packed_varyings = packed_sizes(self.compute_packed_varyings_slots());
+
+        writeln!(self.string, "@fragment").unwrap();
+        writeln!(self.string, "fn fragment_main(varyings: Varyings) -> @location(0) vec4<f32> {{").unwrap();
+
+        self.generate_uniform_block_unpack();
+        self.generate_live_unpack();
+
+        let mut slot = 0;
+        for field in self.varying_fields() {
+            slot = self.generate_packed_unpack(field, "varyings.packed_varying", &packed_varyings, slot);
+        }
+        writeln!(self.string).unwrap();
+
+        let pixel_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(pixel))).unwrap();
+        writeln!(self.string, "    return {}();", DisplayFnName(pixel_def.fn_ptr, pixel_def.ident)).unwrap();
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn varying_fields(&self) -> Vec<&'a DrawShaderFieldDef> {
+        self.draw_shader_def.fields.iter().filter( | field | match &field.kind {
+            DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} => is_used_in_pixel_shader.get(),
+            DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} => is_used_in_pixel_shader.get(),
+            DrawShaderFieldKind::Varying {..} => true,
+            _ => false
+        }).collect()
+    }
+
+    fn generate_uniform_block_unpack(&mut self) {
+        for (ident, vec) in self.fields_as_uniform_blocks {
+            let table = format!("{}_table", ident);
+            let mut slots = 0;
+            for (index, _item) in vec {
+                let field = &self.draw_shader_def.fields[*index];
+                let ty = field.ty_expr.ty.borrow();
+                let ty = ty.as_ref().unwrap();
+                let components: Vec<String> = (slots..slots + ty.slots()).map( | slot | {
+                    format!("{}[{}].{}", table, slot >> 2, swizzle_component(slot & 3))
+                }).collect();
+                write!(self.string, "    {} = ", DisplayDsIdent(field.ident)).unwrap();
+                self.write_ty_unpack(ty, &components);
+                writeln!(self.string, ";").unwrap();
+                slots += ty.slots();
+            }
+        }
+    }
+
+    fn generate_live_unpack(&mut self) {
+        let mut slots = 0;
+        for (live_ref, ty) in self.draw_shader_def.all_live_refs.borrow().iter() {
+            let components: Vec<String> = (slots..slots + ty.slots()).map( | slot | {
+                format!("live_table[{}].{}", slot >> 2, swizzle_component(slot & 3))
+            }).collect();
+            write!(self.string, "    {} = ", live_ref).unwrap();
+            self.write_ty_unpack(ty, &components);
+            writeln!(self.string, ";").unwrap();
+            slots += ty.slots();
+        }
+    }
+
+    fn generate_packed_unpack(&mut self, field: &DrawShaderFieldDef, packed_var_name: &str, packed_sizes: &[usize], slot: usize) -> usize {
+        let ty = field.ty_expr.ty.borrow();
+        let ty = ty.as_ref().unwrap();
+        if field.ident != Ident(LiveId(0)) {
+            let components: Vec<String> = (slot..slot + ty.slots()).map( | slot | {
+                if packed_sizes[slot >> 2] == 1 {
+                    format!("{}_{}", packed_var_name, slot >> 2)
+                }
+                else {
+                    format!("{}_{}.{}", packed_var_name, slot >> 2, swizzle_component(slot & 3))
+                }
+            }).collect();
+            write!(self.string, "    {} = ", DisplayDsIdent(field.ident)).unwrap();
+            self.write_ty_unpack(ty, &components);
+            writeln!(self.string, ";").unwrap();
+        }
+        slot + ty.slots()
+    }
+
+    fn write_ty_unpack(&mut self, ty: &Ty, components: &[String]) {
+        let components = components.join(", ");
+        match ty {
+            Ty::Bool => write!(self.string, "({} > 0.5)", components),
+            Ty::Int => write!(self.string, "i32({})", components),
+            Ty::Float | Ty::Enum(_) => write!(self.string, "{}", components),
+            Ty::Bvec2 => write!(self.string, "(vec2<f32>({}) > vec2<f32>(0.5))", components),
+            Ty::Bvec3 => write!(self.string, "(vec3<f32>({}) > vec3<f32>(0.5))", components),
+            Ty::Bvec4 => write!(self.string, "(vec4<f32>({}) > vec4<f32>(0.5))", components),
+            Ty::Ivec2 => write!(self.string, "vec2<i32>(vec2<f32>({}))", components),
+            Ty::Ivec3 => write!(self.string, "vec3<i32>(vec3<f32>({}))", components),
+            Ty::Ivec4 => write!(self.string, "vec4<i32>(vec4<f32>({}))", components),
+            Ty::Vec2 | Ty::Vec3 | Ty::Vec4 | Ty::Mat2 | Ty::Mat3 | Ty::Mat4 => {
+                self.backend_writer.write_ty_lit(self.string, ty.maybe_ty_lit().unwrap());
+                write!(self.string, "({})", components)
+            }
+            _ => panic!("unexpected as unpackable type {:?}", ty),
+        }.unwrap()
+    }
+
+    pub fn calc_live_slots(&self) -> usize {
+        let mut slots = 0;
+        for (_, ty) in self.draw_shader_def.all_live_refs.borrow().iter() {
+            slots += ty.slots();
+        }
+        slots
+    }
+
+    fn compute_packed_geometries_slots(&self) -> usize {
+        let mut packed_attributes_size = 0;
+        for field in &self.draw_shader_def.fields {
+            packed_attributes_size += match field.kind {
+                DrawShaderFieldKind::Geometry {..} => field.ty_expr.ty.borrow().as_ref().unwrap().slots(),
+                _ => 0,
+            }
+        }
+        packed_attributes_size
+    }
+
+    fn compute_packed_instances_slots(&self) -> usize {
+        let mut packed_instances_size = 0;
+        for field in &self.draw_shader_def.fields {
+            packed_instances_size += match field.kind {
+                DrawShaderFieldKind::Instance {..} => field.ty_expr.ty.borrow().as_ref().unwrap().slots(),
+                _ => 0,
+            }
+        }
+        packed_instances_size
+    }
+
+    fn compute_packed_varyings_slots(&self) -> usize {
+        let mut packed_varyings_size = 0;
+        for field in self.varying_fields() {
+            packed_varyings_size += field.ty_expr.ty.borrow().as_ref().unwrap().slots();
+        }
+        packed_varyings_size
+    }
+
+    fn write_var_decl(&mut self, ident: &dyn fmt::Display, ty: &Ty) {
+        self.backend_writer.write_var_decl(&mut self.string, "", false, false, ident, ty);
+    }
+}
+
+fn vec4_count(slots: usize) -> usize {
+    (slots + 3) >> 2
+}
+
+fn packed_sizes(mut slots: usize) -> Vec<usize> {
+    let mut sizes = Vec::new();
+    while slots > 0 {
+        sizes.push(slots.min(4));
+        slots -= slots.min(4);
+    }
+    sizes
+}
+
+fn packed_ty(size: usize) -> &'static str {
+    match size {
+        1 => "f32",
+        2 => "vec2<f32>",
+        3 => "vec3<f32>",
+        4 => "vec4<f32>",
+        _ => panic!(),
+    }
+}
+
+fn swizzle_component(index: usize) -> char {
+    ['x', 'y', 'z', 'w'][index]
+}
+
+fn mat_size(ty: &Ty) -> usize {
+    match ty {
+        Ty::Mat2 => 2,
+        Ty::Mat3 => 3,
+        Ty::Mat4 => 4,
+        _ => panic!(),
+    }
+}
+
+fn vec_size(ty: &Ty) -> Option<usize> {
+    match ty {
+        Ty::Bvec2 | Ty::Ivec2 | Ty::Vec2 => Some(2),
+        Ty::Bvec3 | Ty::Ivec3 | Ty::Vec3 => Some(3),
+        Ty::Bvec4 | Ty::Ivec4 | Ty::Vec4 => Some(4),
+        _ => None
+    }
+}
+
+struct WgslBackendWriter {
+}
+
+impl WgslBackendWriter {
+    fn write_ty(&self, string: &mut String, ty: &Ty) {
+        match ty {
+            Ty::Array {elem_ty, len} => {
+                write!(string, "array<").unwrap();
+                self.write_ty(string, elem_ty);
+                write!(string, ", {}>", len).unwrap();
+            }
+            Ty::Struct(ptr) => write!(string, "{}", ptr).unwrap(),
+            Ty::Enum(_) => write!(string, "f32").unwrap(),
+            Ty::Texture2D => self.write_ty_lit(string, TyLit::Texture2D),
+            Ty::TextureOES => self.write_ty_lit(string, TyLit::TextureOES),
+            ty => self.write_ty_lit(string, ty.maybe_ty_lit().unwrap()),
+        }
+    }
+
+    fn is_writable_ty(ty: &Ty) -> bool {
+        match ty {
+            Ty::Void | Ty::DrawShader(_) | Ty::ClosureDef {..} | Ty::ClosureDecl => false,
+            _ => true
+        }
+    }
+
+    fn is_handle_ty(ty: &Ty) -> bool {
+        match ty {
+            Ty::Texture2D | Ty::TextureOES => true,
+            _ => false
+        }
+    }
+
+    // wgsl doesnt mix scalars and vectors in builtins like glsl does, so we splat them
+    fn splat_args(&self, ident: Ident, arg_exprs: &[Expr], args: &[String]) -> Vec<String> {
+        let vec_ty = arg_exprs.iter().find_map( | arg_expr | {
+            let ty = arg_expr.ty.borrow();
+            let ty = ty.as_ref().unwrap();
+            vec_size(ty).map( | size | (size, ty.clone()))
+        });
+        args.iter().zip(arg_exprs.iter()).enumerate().map( | (index, (arg, arg_expr)) | {
+            match (&vec_ty, arg_expr.ty.borrow().as_ref().unwrap()) {
+                (Some((size, _)), Ty::Float) if !(ident == Ident(live_id!(refract)) && index == 2) => {
+                    format!("vec{}<f32>({})", size, arg)
+                }
+                (Some((size, _)), Ty::Int) => format!("vec{}<i32>({})", size, arg),
+                _ => arg.clone()
+            }
+        }).collect()
+    }
+}
+
+impl BackendWriter for WgslBackendWriter {
+    fn get_struct_cons_type(&self) -> StructConsType {
+        StructConsType::Paren
+    }
+
+    fn enum_is_float(&self) -> bool {
+        true
+    }
+
+    fn needs_mul_fn_for_matrix_multiplication(&self) -> bool {
+        false
+    }
+
+    fn needs_unpack_for_matrix_multiplication(&self) -> bool {
+        false
+    }
+
+    fn const_table_is_vec4(&self) -> bool {
+        true
+    }
+
+    fn use_cons_fn(&self, what: &str) -> bool {
+        // wgsl has no constructors that resize matrices
+        match what {
+            "consfn_mat2_mat3" | "consfn_mat2_mat4" => true,
+            "consfn_mat3_mat2" | "consfn_mat3_mat4" => true,
+            "consfn_mat4_mat2" | "consfn_mat4_mat3" => true,
+            _ => false
+        }
+    }
+
+    fn write_var_decl(
+        &self,
+        string: &mut String,
+        sep: &'static str,
+        is_inout: bool,
+        _is_packed: bool,
+        ident: &dyn fmt::Display,
+        ty: &Ty,
+    ) -> bool {
+        if !Self::is_writable_ty(ty) {
+            return false
+        }
+        write!(string, "{}{}: ", sep, ident).unwrap();
+        if is_inout {
+            write!(string, "ptr<function, ").unwrap();
+            self.write_ty(string, ty);
+            write!(string, ">").unwrap();
+        }
+        else {
+            self.write_ty(string, ty);
+        }
+        true
+    }
+
+    fn write_fn_def_ident(&self, string: &mut String, ident: &dyn fmt::Display, _return_ty: &Ty) {
+        write!(string, "fn {}", ident).unwrap();
+    }
+
+    fn write_fn_def_param(&self, string: &mut String, sep: &'static str, is_inout: bool, ident: &dyn fmt::Display, ty: &Ty) -> bool {
+        // params are immutable in wgsl, so we pass them in under another name and copy them
+        if is_inout || Self::is_handle_ty(ty) {
+            self.write_var_decl(string, sep, is_inout, false, ident, ty)
+        }
+        else {
+            self.write_var_decl(string, sep, false, false, &format!("in_{}", ident), ty)
+        }
+    }
+
+    fn write_fn_def_return_ty(&self, string: &mut String, return_ty: &Ty) {
+        if let Ty::Void = return_ty {
+            return
+        }
+        write!(string, " -> ").unwrap();
+        self.write_ty(string, return_ty);
+    }
+
+    fn write_fn_def_param_copy(&self, string: &mut String, ident: &dyn fmt::Display, ty: &Ty) {
+        if !Self::is_writable_ty(ty) || Self::is_handle_ty(ty) {
+            return
+        }
+        write!(string, "var ").unwrap();
+        self.write_var_decl(string, "", false, false, ident, ty);
+        write!(string, " = in_{};", ident).unwrap();
+    }
+
+    fn write_let_decl(&self, string: &mut String, ident: &dyn fmt::Display, ty: &Ty) {
+        write!(string, "var ").unwrap();
+        self.write_var_decl(string, "", false, false, ident, ty);
+    }
+
+    fn write_for_stmt_header(&self, string: &mut String, ident: Ident, from: i32, to: i32, step: i32) {
+        write!(
+            string,
+            "for (var {0}: i32 = {1}; {0} {2} {3}; {0} {4} {5}) ",
+            ident,
+            if from <= to {from} else {from - 1},
+            if from <= to {"<"} else {">="},
+            to,
+            if step > 0 {"+="} else {"-="},
+            step.abs()
+        ).unwrap();
+    }
+
+    fn write_cond_expr(&self, string: &mut String, expr: &str, expr_if_true: &str, expr_if_false: &str) {
+        write!(string, "select({}, {}, {})", expr_if_false, expr_if_true, expr).unwrap();
+    }
+
+    fn assign_is_expr(&self) -> bool {
+        false
+    }
+
+    fn inout_arg_prefix(&self) -> &'static str {
+        "&"
+    }
+
+    fn write_inout_param_var(&self, string: &mut String, ident: &dyn fmt::Display) {
+        write!(string, "(*{})", ident).unwrap();
+    }
+
+    fn write_call_expr_hidden_args(&self, _string: &mut String, _hidden_args: &BTreeSet<HiddenArgKind >, _sep: &str) {
+    }
+
+    fn write_fn_def_hidden_params(&self, _string: &mut String, _hidden_args: &BTreeSet<HiddenArgKind >, _sep: &str) {
+    }
+
+    fn generate_live_value_prefix(&self, _string: &mut String) {
+    }
+
+    fn generate_draw_shader_field_expr(&self, string: &mut String, field_ident: Ident, _ty: &Ty) {
+        write!(string, "{}", &DisplayDsIdent(field_ident)).unwrap();
+    }
+
+    fn write_ty_lit(&self, string: &mut String, ty_lit: TyLit) {
+        write!(
+            string,
+            "{}",
+            match ty_lit {
+                TyLit::Bool => "bool",
+                TyLit::Int => "i32",
+                TyLit::Float => "f32",
+                TyLit::Bvec2 => "vec2<bool>",
+                TyLit::Bvec3 => "vec3<bool>",
+                TyLit::Bvec4 => "vec4<bool>",
+                TyLit::Ivec2 => "vec2<i32>",
+                TyLit::Ivec3 => "vec3<i32>",
+                TyLit::Ivec4 => "vec4<i32>",
+                TyLit::Vec2 => "vec2<f32>",
+                TyLit::Vec3 => "vec3<f32>",
+                TyLit::Vec4 => "vec4<f32>",
+                TyLit::Mat2 => "mat2x2<f32>",
+                TyLit::Mat3 => "mat3x3<f32>",
+                TyLit::Mat4 => "mat4x4<f32>",
+                TyLit::Texture2D => "texture_2d<f32>",
+                TyLit::TextureOES => "texture_external",
+            }
+        )
+            .unwrap();
+    }
+
+    fn write_builtin_call_ident(&self, string: &mut String, ident: Ident, arg_exprs: &[Expr]) {
+        match ident {
+            Ident(live_id!(atan)) => {
+                if arg_exprs.len() == 2 {
+                    write!(string, "atan2").unwrap();
+                }
+                else {
+                    write!(string, "atan").unwrap();
+                }
+            }
+            Ident(live_id!(dFdx)) => {
+                write!(string, "dpdx").unwrap();
+            }
+            Ident(live_id!(dFdy)) => {
+                write!(string, "dpdy").unwrap();
+            }
+            Ident(live_id!(inversesqrt)) => {
+                write!(string, "inverseSqrt").unwrap();
+            }
+            Ident(live_id!(faceforward)) => {
+                write!(string, "faceForward").unwrap();
+            }
+            Ident(live_id!(inverse)) => {
+                write!(string, "inverse_mat{}", mat_size(arg_exprs[0].ty.borrow().as_ref().unwrap())).unwrap();
+            }
+            _ => {
+                write!(string, "{}", ident).unwrap()
+            }
+        }
+    }
+
+    fn write_builtin_call(&self, string: &mut String, ident: Ident, arg_exprs: &[Expr], args: &[String]) {
+        let args = self.splat_args(ident, arg_exprs, args);
+        let compare_op = match ident {
+            Ident(live_id!(lessThan)) => Some("<"),
+            Ident(live_id!(lessThanEqual)) => Some("<="),
+            Ident(live_id!(greaterThan)) => Some(">"),
+            Ident(live_id!(greaterThanEqual)) => Some(">="),
+            Ident(live_id!(equal)) => Some("=="),
+            Ident(live_id!(notEqual)) => Some("!="),
+            _ => None
+        };
+        if let Some(compare_op) = compare_op {
+            write!(string, "({} {} {})", args[0], compare_op, args[1]).unwrap();
+            return
+        }
+        match ident {
+            Ident(live_id!(mod)) => {
+                write!(string, "({0} - {1} * floor({0} / {1}))", args[0], args[1]).unwrap();
+            }
+            Ident(live_id!(not)) => {
+                write!(string, "(!{})", args[0]).unwrap();
+            }
+            Ident(live_id!(matrixCompMult)) => {
+                let ty = arg_exprs[0].ty.borrow();
+                let ty = ty.as_ref().unwrap();
+                self.write_ty_lit(string, ty.maybe_ty_lit().unwrap());
+                write!(string, "(").unwrap();
+                for col in 0..mat_size(ty) {
+                    if col != 0 {
+                        write!(string, ", ").unwrap();
+                    }
+                    write!(string, "{0}[{2}] * {1}[{2}]", args[0], args[1], col).unwrap();
+                }
+                write!(string, ")").unwrap();
+            }
+            _ => {
+                self.write_builtin_call_ident(string, ident, arg_exprs);
+                write!(string, "({})", args.join(", ")).unwrap();
+            }
+        }
+    }
+}
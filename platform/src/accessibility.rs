@@ -0,0 +1,199 @@
+use {
+    std::fmt,
+    crate::{
+        makepad_math::{Rect, DVec2},
+        window::WindowId,
+    }
+};
+
+/// What a node in the accessibility tree is, as far as screen readers and UI tests care.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessibilityRole {
+    Window,
+    Group,
+    ScrollArea,
+    Label,
+    Button,
+    CheckBox,
+    RadioButton,
+    Slider,
+    TextInput,
+    ComboBox,
+    Tab,
+    Tree,
+    TreeItem,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AccessibilityState {
+    pub focusable: bool,
+    pub focused: bool,
+    pub disabled: bool,
+    pub selected: bool,
+    /// `None` when the node can't be checked at all.
+    pub checked: Option<bool>,
+    /// `None` when the node can't be expanded at all.
+    pub expanded: Option<bool>,
+    pub secret: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessibilityNode {
+    pub role: AccessibilityRole,
+    pub name: String,
+    pub value: Option<String>,
+    pub state: AccessibilityState,
+    /// In window coordinates.
+    pub bounds: Rect,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+impl AccessibilityNode {
+    pub fn new(role: AccessibilityRole, name: &str) -> Self {
+        Self {
+            role,
+            name: name.to_string(),
+            value: None,
+            state: AccessibilityState::default(),
+            bounds: Rect::default(),
+            parent: None,
+            children: Vec::new(),
+        }
+    }
+}
+
+/// The accessibility tree of one window. `nodes[0]` is the window itself; the other nodes are
+/// in drawing order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessibilityTree {
+    pub nodes: Vec<AccessibilityNode>,
+}
+
+impl AccessibilityTree {
+    pub fn new(root: AccessibilityNode) -> Self {
+        Self {nodes: vec![root]}
+    }
+
+    /// Adds `node` as the last child of `parent` and returns its index.
+    pub fn push(&mut self, parent: usize, mut node: AccessibilityNode) -> usize {
+        let index = self.nodes.len();
+        node.parent = Some(parent);
+        node.children.clear();
+        self.nodes.push(node);
+        self.nodes[parent].children.push(index);
+        index
+    }
+
+    pub fn root(&self) -> &AccessibilityNode {
+        &self.nodes[0]
+    }
+
+    pub fn node(&self, index: usize) -> Option<&AccessibilityNode> {
+        self.nodes.get(index)
+    }
+
+    pub fn children(&self, index: usize) -> impl Iterator<Item = &AccessibilityNode> {
+        self.nodes[index].children.iter().map(move | child | &self.nodes[*child])
+    }
+
+    pub fn find(&self, pred: impl FnMut(&AccessibilityNode) -> bool) -> Option<usize> {
+        self.nodes.iter().position(pred)
+    }
+
+    pub fn find_all(&self, mut pred: impl FnMut(&AccessibilityNode) -> bool) -> Vec<usize> {
+        (0..self.nodes.len()).filter( | index | pred(&self.nodes[*index])).collect()
+    }
+
+    pub fn find_by_name(&self, role: AccessibilityRole, name: &str) -> Option<usize> {
+        self.find( | node | node.role == role && node.name == name)
+    }
+
+    pub fn focused(&self) -> Option<usize> {
+        self.find( | node | node.state.focused)
+    }
+
+    /// The innermost node under `pos`, preferring the one drawn last where nodes overlap.
+    pub fn node_at(&self, pos: DVec2) -> Option<usize> {
+        let mut index = 0;
+        if !self.nodes[0].bounds.contains(pos) {
+            return None
+        }
+        while let Some(child) = self.nodes[index].children.iter().rev().find( | child | self.nodes[**child].bounds.contains(pos)) {
+            index = *child;
+        }
+        Some(index)
+    }
+
+    fn fmt_node(&self, f: &mut fmt::Formatter, index: usize, depth: usize) -> fmt::Result {
+        let node = &self.nodes[index];
+        write!(f, "{:indent$}{:?} {:?}", "", node.role, node.name, indent = depth * 2)?;
+        if let Some(value) = &node.value {
+            write!(f, " = {:?}", value)?;
+        }
+        let state = &node.state;
+        for (set, name) in [
+            (state.focused, "focused"),
+            (state.disabled, "disabled"),
+            (state.selected, "selected"),
+            (state.checked == Some(true), "checked"),
+            (state.expanded == Some(true), "expanded"),
+        ] {
+            if set {
+                write!(f, " [{}]", name)?;
+            }
+        }
+        writeln!(f)?;
+        for child in &node.children {
+            self.fmt_node(f, *child, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// An indented outline of the tree, handy in test failure messages.
+impl fmt::Display for AccessibilityTree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_node(f, 0, 0)
+    }
+}
+
+/// The accessibility trees of all windows, as last drawn.
+#[derive(Default)]
+pub struct CxAccessibility {
+    trees: Vec<(WindowId, AccessibilityTree)>,
+    changed: Vec<WindowId>,
+}
+
+impl CxAccessibility {
+    pub fn tree(&self, window_id: WindowId) -> Option<&AccessibilityTree> {
+        self.trees.iter().find( | (id, _) | *id == window_id).map( | (_, tree) | tree)
+    }
+
+    pub fn trees(&self) -> impl Iterator<Item = (WindowId, &AccessibilityTree)> {
+        self.trees.iter().map( | (id, tree) | (*id, tree))
+    }
+
+    pub fn set_tree(&mut self, window_id: WindowId, tree: AccessibilityTree) {
+        match self.trees.iter_mut().find( | (id, _) | *id == window_id) {
+            Some((_, old)) if *old == tree => return,
+            Some((_, old)) => *old = tree,
+            None => self.trees.push((window_id, tree)),
+        }
+        if !self.changed.contains(&window_id) {
+            self.changed.push(window_id);
+        }
+    }
+
+    pub fn remove_tree(&mut self, window_id: WindowId) {
+        self.trees.retain( | (id, _) | *id != window_id);
+        if !self.changed.contains(&window_id) {
+            self.changed.push(window_id);
+        }
+    }
+
+    /// The windows whose tree changed since the last call, for exporting them to the OS.
+    pub fn take_changed(&mut self) -> Vec<WindowId> {
+        std::mem::take(&mut self.changed)
+    }
+}
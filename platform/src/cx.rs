@@ -21,6 +21,8 @@ use {
         os::{CxOs},
         debug::Debug,
         performance_stats::PerformanceStats,
+        accessibility::CxAccessibility,
+        live_cx::LiveTokenPatch,
         event::{
             DrawEvent,
             CxFingers,
@@ -96,6 +98,7 @@ pub struct Cx {
 
     pub (crate) live_file_change_receiver: std::sync::mpsc::Receiver<Vec<LiveFileChange>>,
     pub (crate) live_file_change_sender: std::sync::mpsc::Sender<Vec<LiveFileChange >>,
+    pub (crate) live_token_patches: Vec<LiveTokenPatch>,
 
     pub shader_registry: ShaderRegistry,
     
@@ -117,6 +120,8 @@ pub struct Cx {
     pub(crate) studio_http: String,
     
     pub performance_stats: PerformanceStats,
+    
+    pub accessibility: CxAccessibility,
 }
 
 #[derive(Clone)]
@@ -156,6 +161,7 @@ pub enum OsType {
     Android(AndroidParams),
     LinuxWindow (LinuxWindowParams),
     LinuxDirect,
+    LinuxHeadless,
     Web(WebParams)
 }
 
@@ -251,6 +257,7 @@ impl Cx {
             
             live_file_change_receiver: recv,
             live_file_change_sender: send,
+            live_token_patches: Vec::new(),
             
             shader_registry: ShaderRegistry::new(),
             
@@ -267,6 +274,7 @@ impl Cx {
 
             self_ref: None,
             performance_stats: Default::default(),
+            accessibility: Default::default(),
         }
     }
 }
\ No newline at end of file
@@ -41,7 +41,7 @@ pub struct KeyModifiers {
 }
 
 impl KeyModifiers{
-    fn any(&self)->bool{
+    pub fn any(&self)->bool{
         self.shift || self.control || self.alt || self.logo
     }
 }
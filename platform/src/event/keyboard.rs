@@ -48,6 +48,10 @@ impl CxKeyboard {
         self.key_focus == focus_area
     }
 
+    pub fn key_focus(&self) -> Area {
+        self.key_focus
+    }
+
     pub fn set_text_ime_dismissed(&mut self) {
         self.text_ime_dismissed = true;
     }
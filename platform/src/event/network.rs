@@ -195,6 +195,19 @@ impl HttpResponse {
         }
     }
 
+    /// Reads the body as a dynamic `JsonValue`, for responses that only need a few fields
+    /// picked out of them.
+    pub fn get_json_value(&self) -> Result<JsonValue, DeJsonErr> {
+        let mut parser = JsonPullParser::new();
+        parser.feed(self.body.as_deref().unwrap_or_default());
+        parser.finish();
+        parser.next_value()?.ok_or_else(|| DeJsonErr{
+            msg:"No body present".to_string(),
+            line:0,
+            col:0
+        })
+    }
+
     fn parse_headers(headers_string: String) -> BTreeMap<String, Vec<String>> {
         let mut headers = BTreeMap::new();
         for line in headers_string.lines() {
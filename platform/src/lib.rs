@@ -43,6 +43,7 @@ mod debug;
 mod component_map;
 mod component_list;
 mod performance_stats;
+mod accessibility;
 pub mod studio;
 
 pub mod web_socket;
@@ -65,6 +66,9 @@ pub use makepad_objc_sys;
 #[cfg(target_os = "windows")]
 pub use ::makepad_windows as windows;
 
+#[cfg(target_os = "linux")]
+pub use makepad_image_formats;
+
 pub use makepad_futures;
  
 pub use {
@@ -216,7 +220,14 @@ pub use {
         cursor::MouseCursor,
         macos_menu::MacosMenu,
         draw_matrix::DrawMatrix,
-        window::WindowHandle,
+        window::{WindowHandle, WindowId},
+        accessibility::{
+            AccessibilityRole,
+            AccessibilityState,
+            AccessibilityNode,
+            AccessibilityTree,
+            CxAccessibility,
+        },
         pass::{
             PassId,
             CxPassParent,
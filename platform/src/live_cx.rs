@@ -17,7 +17,7 @@ use {
             /*LiveTokenId,*/
             LiveFileId,
         },
-        studio::{StudioToAppVec,StudioToApp},
+        studio::{StudioToAppVec,StudioToApp,AppToStudio,PatchFile,EditFile},
         web_socket::WebSocketMessage,
         makepad_live_compiler::LiveTypeInfo,
         /*makepad_math::*,*/
@@ -26,6 +26,14 @@ use {
     },
 };
 
+/// A change to some tokens of a `live_design!` block, queued by `Cx::patch_live_tokens`.
+pub struct LiveTokenPatch {
+    pub file_id: LiveFileId,
+    pub tokens: std::ops::Range<usize>,
+    pub replace: String,
+    pub undo_group: u64,
+}
+
 pub struct LiveBody {
     pub file: String,
     pub cargo_manifest_path: String,
@@ -255,10 +263,46 @@ impl Cx {
         while let Ok(changes) = self.live_file_change_receiver.try_recv(){
             all_changes.extend(changes);
         }
-        if all_changes.len()>0{
+        let patches = std::mem::take(&mut self.live_token_patches);
+        if !all_changes.is_empty() || !patches.is_empty(){
             let mut live_registry = self.live_registry.borrow_mut();
             let mut errs = Vec::new();
-            live_registry.process_file_changes(all_changes, &mut errs);
+            if !all_changes.is_empty(){
+                live_registry.process_file_changes(all_changes, &mut errs);
+            }
+            // the patches all point at the tokens from before any of them, so every patch moves
+            // the ones after it in the same file
+            let mut moved: Vec<(LiveFileId, usize, isize)> = Vec::new();
+            for patch in patches{
+                let delta: isize = moved.iter()
+                    .filter(|(file_id, end, _)| *file_id == patch.file_id && *end <= patch.tokens.start)
+                    .map(|(_, _, delta)| delta).sum();
+                let tokens = (patch.tokens.start as isize + delta) as usize..(patch.tokens.end as isize + delta) as usize;
+                if let Some((start, end, count)) = live_registry.patch_tokens(patch.file_id, tokens, &patch.replace, &mut errs){
+                    moved.push((patch.file_id, patch.tokens.end, count as isize - patch.tokens.len() as isize));
+                    let file_name = live_registry.file_id_to_file(patch.file_id).file_name.clone();
+                    if start.line == end.line && !patch.replace.contains('\n'){
+                        Cx::send_studio_message(AppToStudio::PatchFile(PatchFile{
+                            file_name,
+                            line: start.line,
+                            column_start: start.column,
+                            column_end: end.column,
+                            undo_group: patch.undo_group,
+                            replace: patch.replace
+                        }));
+                    }
+                    else{
+                        Cx::send_studio_message(AppToStudio::EditFile(EditFile{
+                            file_name,
+                            line_start: start.line,
+                            line_end: end.line,
+                            column_start: start.column,
+                            column_end: end.column,
+                            replace: patch.replace
+                        }));
+                    }
+                }
+            }
             for err in errs {
                 
                 // alright we need to output the correct error
@@ -285,6 +329,15 @@ impl Cx {
         }
     }
     
+    /// Replaces the tokens `tokens` of a `live_design!` block with `replace` and writes the change
+    /// through to its source file in the studio. It is applied with the next live edit, so that
+    /// nothing still points into the old document while it happens. Patching the same tokens
+    /// again before then replaces the earlier patch.
+    pub fn patch_live_tokens(&mut self, file_id: LiveFileId, tokens: std::ops::Range<usize>, replace: String, undo_group: u64){
+        self.live_token_patches.retain(|patch| patch.file_id != file_id || patch.tokens != tokens);
+        self.live_token_patches.push(LiveTokenPatch{file_id, tokens, replace, undo_group});
+    }
+    
     // ok so now what. now we should run the expansion
     pub fn live_expand(&mut self) {
         let mut errs = Vec::new();
@@ -326,6 +326,10 @@ pub(crate) fn android_to_makepad_key_code(key_code: u32) -> KeyCode {
         20 => KeyCode::ArrowDown,
         21 => KeyCode::ArrowLeft,
         22 => KeyCode::ArrowRight,
+        // dpad center and the gamepad A / B buttons, for TV remotes and controllers
+        23 => KeyCode::ReturnKey,
+        96 => KeyCode::ReturnKey,
+        97 => KeyCode::Back,
 
         29 => KeyCode::KeyA,
         30 => KeyCode::KeyB,
@@ -0,0 +1,793 @@
+// Exports the accessibility trees over AT-SPI, the accessibility bus that Orca and the other
+// Linux assistive tools talk to. Every node gets an object path; the answers come straight
+// from the last trees the windows drew.
+
+use {
+    std::{
+        ffi::{CStr, CString},
+        os::raw::{c_char, c_int, c_void},
+        ptr,
+        sync::mpsc,
+    },
+    self::super::dbus_sys::*,
+    crate::{
+        makepad_math::{DVec2, Rect, dvec2},
+        accessibility::{AccessibilityNode, AccessibilityRole, AccessibilityTree},
+        window::{CxWindowPool, WindowId},
+    }
+};
+
+const ROOT_PATH: &str = "/org/a11y/atspi/accessible/root";
+const NULL_PATH: &str = "/org/a11y/atspi/null";
+const NODE_PATH: &str = "/org/makepad/a11y/";
+
+const ROLE_APPLICATION: u32 = 75;
+
+const STATE_CHECKED: u32 = 4;
+const STATE_COLLAPSED: u32 = 5;
+const STATE_EDITABLE: u32 = 7;
+const STATE_ENABLED: u32 = 8;
+const STATE_EXPANDABLE: u32 = 9;
+const STATE_EXPANDED: u32 = 10;
+const STATE_FOCUSABLE: u32 = 11;
+const STATE_FOCUSED: u32 = 12;
+const STATE_SELECTABLE: u32 = 22;
+const STATE_SELECTED: u32 = 23;
+const STATE_SENSITIVE: u32 = 24;
+const STATE_SHOWING: u32 = 25;
+const STATE_VISIBLE: u32 = 30;
+const STATE_CHECKABLE: u32 = 41;
+
+const COORD_SCREEN: u32 = 0;
+const COORD_WINDOW: u32 = 1;
+
+const LAYER_WIDGET: u32 = 3;
+const LAYER_WINDOW: u32 = 7;
+
+fn role(role: AccessibilityRole, secret: bool) -> (u32, &'static str) {
+    match role {
+        AccessibilityRole::Window => (23, "frame"),
+        AccessibilityRole::Group => (39, "panel"),
+        AccessibilityRole::ScrollArea => (49, "scroll pane"),
+        AccessibilityRole::Label => (29, "label"),
+        AccessibilityRole::Button => (43, "push button"),
+        AccessibilityRole::CheckBox => (7, "check box"),
+        AccessibilityRole::RadioButton => (44, "radio button"),
+        AccessibilityRole::Slider => (51, "slider"),
+        AccessibilityRole::TextInput if secret => (40, "password text"),
+        AccessibilityRole::TextInput => (79, "entry"),
+        AccessibilityRole::ComboBox => (11, "combo box"),
+        AccessibilityRole::Tab => (37, "page tab"),
+        AccessibilityRole::Tree => (65, "tree"),
+        AccessibilityRole::TreeItem => (91, "tree item"),
+    }
+}
+
+fn state(node: &AccessibilityNode) -> [u32; 2] {
+    let mut bits = 0u64;
+    let mut set = | bit: u32 | bits |= 1 << bit;
+    let state = &node.state;
+    set(STATE_VISIBLE);
+    set(STATE_SHOWING);
+    if !state.disabled {
+        set(STATE_ENABLED);
+        set(STATE_SENSITIVE);
+    }
+    if state.focusable {
+        set(STATE_FOCUSABLE);
+    }
+    if state.focused {
+        set(STATE_FOCUSED);
+    }
+    if let AccessibilityRole::Tab | AccessibilityRole::TreeItem | AccessibilityRole::RadioButton = node.role {
+        set(STATE_SELECTABLE);
+    }
+    if state.selected {
+        set(STATE_SELECTED);
+    }
+    if let Some(checked) = state.checked {
+        set(STATE_CHECKABLE);
+        if checked {
+            set(STATE_CHECKED);
+        }
+    }
+    if let Some(expanded) = state.expanded {
+        set(STATE_EXPANDABLE);
+        set(if expanded {STATE_EXPANDED} else {STATE_COLLAPSED});
+    }
+    if node.role == AccessibilityRole::TextInput {
+        set(STATE_EDITABLE);
+    }
+    [bits as u32, (bits >> 32) as u32]
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Object {
+    Root,
+    // index into the bridge windows, index into that window's tree
+    Node(usize, usize),
+}
+
+struct AtSpiWindow {
+    window_id: WindowId,
+    tree: AccessibilityTree,
+}
+
+struct Writer<'a> {
+    dbus: &'a LibDbus,
+    iter: DBusMessageIter,
+}
+
+impl<'a> Writer<'a> {
+    fn new(dbus: &'a LibDbus, message: *mut DBusMessage) -> Self {
+        let mut iter = DBusMessageIter::default();
+        unsafe {(dbus.dbus_message_iter_init_append)(message, &mut iter)};
+        Self {dbus, iter}
+    }
+
+    fn basic<T>(&mut self, ty: c_int, value: &T) {
+        unsafe {(self.dbus.dbus_message_iter_append_basic)(&mut self.iter, ty, value as *const T as *const c_void)};
+    }
+
+    fn string(&mut self, value: &str) {
+        let value = CString::new(value.replace('\0', "")).unwrap();
+        self.basic(DBUS_TYPE_STRING, &value.as_ptr());
+    }
+
+    fn path(&mut self, value: &str) {
+        let value = CString::new(value).unwrap();
+        self.basic(DBUS_TYPE_OBJECT_PATH, &value.as_ptr());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.basic(DBUS_TYPE_UINT32, &value);
+    }
+
+    fn i32(&mut self, value: i32) {
+        self.basic(DBUS_TYPE_INT32, &value);
+    }
+
+    fn i16(&mut self, value: i16) {
+        self.basic(DBUS_TYPE_INT16, &value);
+    }
+
+    fn f64(&mut self, value: f64) {
+        self.basic(DBUS_TYPE_DOUBLE, &value);
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.basic(DBUS_TYPE_BOOLEAN, &(value as dbus_bool_t));
+    }
+
+    fn container(&mut self, ty: c_int, signature: Option<&str>, f: impl FnOnce(&mut Writer)) {
+        let signature = signature.map( | s | CString::new(s).unwrap());
+        let mut sub = Writer {dbus: self.dbus, iter: DBusMessageIter::default()};
+        unsafe {
+            (self.dbus.dbus_message_iter_open_container)(
+                &mut self.iter,
+                ty,
+                signature.as_ref().map_or(ptr::null(), | s | s.as_ptr()),
+                &mut sub.iter
+            );
+            f(&mut sub);
+            (self.dbus.dbus_message_iter_close_container)(&mut self.iter, &mut sub.iter);
+        }
+    }
+
+    fn variant(&mut self, signature: &str, f: impl FnOnce(&mut Writer)) {
+        self.container(DBUS_TYPE_VARIANT, Some(signature), f)
+    }
+
+    // an object reference, `(so)`
+    fn object(&mut self, object: &(String, String)) {
+        self.container(DBUS_TYPE_STRUCT, None, | w | {
+            w.string(&object.0);
+            w.path(&object.1);
+        })
+    }
+
+    fn empty_dict(&mut self, signature: &str) {
+        self.container(DBUS_TYPE_ARRAY, Some(signature), | _ | ());
+    }
+}
+
+struct Reader<'a> {
+    dbus: &'a LibDbus,
+    iter: DBusMessageIter,
+    done: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn new(dbus: &'a LibDbus, message: *mut DBusMessage) -> Self {
+        let mut iter = DBusMessageIter::default();
+        let done = unsafe {(dbus.dbus_message_iter_init)(message, &mut iter)} == 0;
+        Self {dbus, iter, done}
+    }
+
+    fn basic<T: Default>(&mut self, ty: c_int) -> Option<T> {
+        if self.done || unsafe {(self.dbus.dbus_message_iter_get_arg_type)(&mut self.iter)} != ty {
+            return None
+        }
+        let mut value = T::default();
+        unsafe {
+            (self.dbus.dbus_message_iter_get_basic)(&mut self.iter, &mut value as *mut T as *mut c_void);
+            self.done = (self.dbus.dbus_message_iter_next)(&mut self.iter) == 0;
+        }
+        Some(value)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let ty = if self.done {DBUS_TYPE_INVALID} else {unsafe {(self.dbus.dbus_message_iter_get_arg_type)(&mut self.iter)}};
+        if ty != DBUS_TYPE_STRING && ty != DBUS_TYPE_OBJECT_PATH {
+            return None
+        }
+        let value: *const c_char = self.basic(ty).unwrap_or(ptr::null());
+        if value.is_null() {
+            return None
+        }
+        Some(unsafe {CStr::from_ptr(value)}.to_string_lossy().into_owned())
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.basic(DBUS_TYPE_UINT32)
+    }
+
+    fn i32(&mut self) -> Option<i32> {
+        self.basic(DBUS_TYPE_INT32)
+    }
+
+    fn object(&mut self) -> Option<(String, String)> {
+        if self.done || unsafe {(self.dbus.dbus_message_iter_get_arg_type)(&mut self.iter)} != DBUS_TYPE_STRUCT {
+            return None
+        }
+        let mut sub = Reader {dbus: self.dbus, iter: DBusMessageIter::default(), done: false};
+        unsafe {
+            (self.dbus.dbus_message_iter_recurse)(&mut self.iter, &mut sub.iter);
+            self.done = (self.dbus.dbus_message_iter_next)(&mut self.iter) == 0;
+        }
+        Some((sub.string()?, sub.string()?))
+    }
+}
+
+fn c_str<'a>(value: *const c_char) -> &'a str {
+    if value.is_null() {
+        return ""
+    }
+    unsafe {CStr::from_ptr(value)}.to_str().unwrap_or("")
+}
+
+pub struct AtSpiBridge {
+    dbus: LibDbus,
+    connection: *mut DBusConnection,
+    bus_name: String,
+    // the desktop we are embedded in, the parent of our application object
+    desktop: (String, String),
+    windows: Vec<AtSpiWindow>,
+}
+
+// the connection is private to the bridge, it is only used by the thread that connects it and
+// then by the event loop it gets handed to
+unsafe impl Send for AtSpiBridge {}
+
+impl AtSpiBridge {
+    /// Connects on a thread of its own, as asking the session bus for the accessibility bus
+    /// can block for a second. The bridge, or `None`, arrives on the receiver when it's done.
+    pub fn connect_in_background() -> mpsc::Receiver<Option<Self>> {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(Self::connect());
+        });
+        receiver
+    }
+
+    /// Connects to the accessibility bus and registers the application with the AT-SPI
+    /// registry. `None` when there is no libdbus or no accessibility bus.
+    pub fn connect() -> Option<Self> {
+        let dbus = LibDbus::try_load()?;
+        unsafe {
+            let mut error = DBusError::default();
+            (dbus.dbus_error_init)(&mut error);
+
+            // the accessibility bus is its own bus, the session bus knows where it is
+            let session = (dbus.dbus_bus_get_private)(DBUS_BUS_SESSION, &mut error);
+            if session.is_null() {
+                (dbus.dbus_error_free)(&mut error);
+                return None
+            }
+            (dbus.dbus_connection_set_exit_on_disconnect)(session, 0);
+            let call = Self::method_call(&dbus, "org.a11y.Bus", "/org/a11y/bus", "org.a11y.Bus", "GetAddress");
+            let reply = (dbus.dbus_connection_send_with_reply_and_block)(session, call, 1000, &mut error);
+            (dbus.dbus_message_unref)(call);
+            (dbus.dbus_connection_close)(session);
+            (dbus.dbus_connection_unref)(session);
+            if reply.is_null() {
+                (dbus.dbus_error_free)(&mut error);
+                return None
+            }
+            let address = Reader::new(&dbus, reply).string();
+            (dbus.dbus_message_unref)(reply);
+            let address = CString::new(address?).ok()?;
+
+            let connection = (dbus.dbus_connection_open_private)(address.as_ptr(), &mut error);
+            if connection.is_null() {
+                (dbus.dbus_error_free)(&mut error);
+                return None
+            }
+            (dbus.dbus_connection_set_exit_on_disconnect)(connection, 0);
+            if (dbus.dbus_bus_register)(connection, &mut error) == 0 {
+                (dbus.dbus_error_free)(&mut error);
+                (dbus.dbus_connection_close)(connection);
+                (dbus.dbus_connection_unref)(connection);
+                return None
+            }
+            let bus_name = c_str((dbus.dbus_bus_get_unique_name)(connection)).to_string();
+
+            let mut bridge = Self {
+                dbus,
+                connection,
+                bus_name,
+                desktop: (String::new(), NULL_PATH.to_string()),
+                windows: Vec::new(),
+            };
+            bridge.embed();
+            Some(bridge)
+        }
+    }
+
+    fn method_call(dbus: &LibDbus, destination: &str, path: &str, iface: &str, method: &str) -> *mut DBusMessage {
+        let destination = CString::new(destination).unwrap();
+        let path = CString::new(path).unwrap();
+        let iface = CString::new(iface).unwrap();
+        let method = CString::new(method).unwrap();
+        unsafe {(dbus.dbus_message_new_method_call)(destination.as_ptr(), path.as_ptr(), iface.as_ptr(), method.as_ptr())}
+    }
+
+    fn embed(&mut self) {
+        let call = Self::method_call(&self.dbus, "org.a11y.atspi.Registry", ROOT_PATH, "org.a11y.atspi.Socket", "Embed");
+        Writer::new(&self.dbus, call).object(&(self.bus_name.clone(), ROOT_PATH.to_string()));
+        unsafe {
+            let mut error = DBusError::default();
+            (self.dbus.dbus_error_init)(&mut error);
+            let reply = (self.dbus.dbus_connection_send_with_reply_and_block)(self.connection, call, 1000, &mut error);
+            (self.dbus.dbus_message_unref)(call);
+            if reply.is_null() {
+                (self.dbus.dbus_error_free)(&mut error);
+                return
+            }
+            if let Some(desktop) = Reader::new(&self.dbus, reply).object() {
+                self.desktop = desktop;
+            }
+            (self.dbus.dbus_message_unref)(reply);
+        }
+    }
+
+    /// Takes over the new tree of a window, or forgets the window when `tree` is `None`, and
+    /// tells the listeners what changed.
+    pub fn set_tree(&mut self, window_id: WindowId, tree: Option<AccessibilityTree>) {
+        let index = self.windows.iter().position( | w | w.window_id == window_id);
+        match (index, tree) {
+            (Some(index), Some(tree)) => {
+                let old_focus = self.windows[index].tree.focused();
+                let new_focus = tree.focused();
+                self.windows[index].tree = tree;
+                if old_focus != new_focus {
+                    if let Some(old_focus) = old_focus.filter( | node | *node < self.windows[index].tree.nodes.len()) {
+                        self.emit_state_changed(Object::Node(index, old_focus), "focused", false);
+                    }
+                    if let Some(new_focus) = new_focus {
+                        self.emit_state_changed(Object::Node(index, new_focus), "focused", true);
+                    }
+                }
+            }
+            (None, Some(tree)) => {
+                self.windows.push(AtSpiWindow {window_id, tree});
+                self.emit_children_changed("add", self.windows.len() - 1);
+            }
+            (Some(index), None) => {
+                self.emit_children_changed("remove", index);
+                self.windows.remove(index);
+            }
+            (None, None) => ()
+        }
+    }
+
+    /// Answers whatever the assistive tools asked since the last call.
+    pub fn poll(&mut self, windows: &CxWindowPool) {
+        unsafe {
+            (self.dbus.dbus_connection_read_write)(self.connection, 0);
+            loop {
+                let message = (self.dbus.dbus_connection_pop_message)(self.connection);
+                if message.is_null() {
+                    break
+                }
+                if (self.dbus.dbus_message_get_type)(message) == DBUS_MESSAGE_TYPE_METHOD_CALL {
+                    self.handle_call(message, windows);
+                }
+                (self.dbus.dbus_message_unref)(message);
+            }
+            (self.dbus.dbus_connection_flush)(self.connection);
+        }
+    }
+
+    fn send(&self, message: *mut DBusMessage) {
+        unsafe {
+            (self.dbus.dbus_connection_send)(self.connection, message, ptr::null_mut());
+            (self.dbus.dbus_message_unref)(message);
+        }
+    }
+
+    fn signal(&self, object: Object, iface: &str, name: &str) -> *mut DBusMessage {
+        let path = CString::new(self.path(object)).unwrap();
+        let iface = CString::new(iface).unwrap();
+        let name = CString::new(name).unwrap();
+        unsafe {(self.dbus.dbus_message_new_signal)(path.as_ptr(), iface.as_ptr(), name.as_ptr())}
+    }
+
+    fn emit_state_changed(&self, object: Object, state: &str, on: bool) {
+        let signal = self.signal(object, "org.a11y.atspi.Event.Object", "StateChanged");
+        let mut w = Writer::new(&self.dbus, signal);
+        w.string(state);
+        w.i32(on as i32);
+        w.i32(0);
+        w.variant("i", | w | w.i32(0));
+        w.empty_dict("{sv}");
+        self.send(signal);
+    }
+
+    fn emit_children_changed(&self, change: &str, window: usize) {
+        let signal = self.signal(Object::Root, "org.a11y.atspi.Event.Object", "ChildrenChanged");
+        let mut w = Writer::new(&self.dbus, signal);
+        w.string(change);
+        w.i32(window as i32);
+        w.i32(0);
+        let child = self.reference(Object::Node(window, 0));
+        w.variant("(so)", | w | w.object(&child));
+        w.empty_dict("{sv}");
+        self.send(signal);
+    }
+
+    fn path(&self, object: Object) -> String {
+        match object {
+            Object::Root => ROOT_PATH.to_string(),
+            Object::Node(window, node) => format!("{}{}/{}", NODE_PATH, self.windows[window].window_id.id(), node),
+        }
+    }
+
+    fn object(&self, path: &str) -> Option<Object> {
+        if path == ROOT_PATH {
+            return Some(Object::Root)
+        }
+        let (window_id, node) = path.strip_prefix(NODE_PATH)?.split_once('/')?;
+        let window_id: usize = window_id.parse().ok()?;
+        let node: usize = node.parse().ok()?;
+        let window = self.windows.iter().position( | w | w.window_id.id() == window_id)?;
+        if node >= self.windows[window].tree.nodes.len() {
+            return None
+        }
+        Some(Object::Node(window, node))
+    }
+
+    fn reference(&self, object: Object) -> (String, String) {
+        (self.bus_name.clone(), self.path(object))
+    }
+
+    fn null_reference(&self) -> (String, String) {
+        (String::new(), NULL_PATH.to_string())
+    }
+
+    fn node(&self, object: Object) -> Option<&AccessibilityNode> {
+        match object {
+            Object::Root => None,
+            Object::Node(window, node) => Some(&self.windows[window].tree.nodes[node]),
+        }
+    }
+
+    fn children(&self, object: Object) -> Vec<Object> {
+        match object {
+            Object::Root => (0..self.windows.len()).map( | window | Object::Node(window, 0)).collect(),
+            Object::Node(window, node) => self.windows[window].tree.nodes[node].children.iter().map( | child | Object::Node(window, *child)).collect(),
+        }
+    }
+
+    fn parent(&self, object: Object) -> (String, String) {
+        match object {
+            Object::Root => self.desktop.clone(),
+            Object::Node(window, node) => match self.windows[window].tree.nodes[node].parent {
+                Some(parent) => self.reference(Object::Node(window, parent)),
+                None => self.reference(Object::Root),
+            }
+        }
+    }
+
+    fn index_in_parent(&self, object: Object) -> i32 {
+        match object {
+            Object::Root => -1,
+            Object::Node(window, node) => match self.windows[window].tree.nodes[node].parent {
+                Some(parent) => self.windows[window].tree.nodes[parent].children.iter().position( | c | *c == node).map_or(-1, | i | i as i32),
+                None => window as i32,
+            }
+        }
+    }
+
+    fn name(&self, object: Object) -> String {
+        match self.node(object) {
+            Some(node) => node.name.clone(),
+            None => std::env::args().next()
+                .and_then( | arg | std::path::Path::new(&arg).file_name().map( | name | name.to_string_lossy().into_owned()))
+                .unwrap_or_default(),
+        }
+    }
+
+    fn role(&self, object: Object) -> (u32, &'static str) {
+        match self.node(object) {
+            Some(node) => role(node.role, node.state.secret),
+            None => (ROLE_APPLICATION, "application"),
+        }
+    }
+
+    fn interfaces(&self, object: Object) -> Vec<&'static str> {
+        match self.node(object) {
+            None => vec!["org.a11y.atspi.Accessible", "org.a11y.atspi.Application"],
+            Some(node) if node.value.is_some() => vec!["org.a11y.atspi.Accessible", "org.a11y.atspi.Component", "org.a11y.atspi.Text"],
+            Some(_) => vec!["org.a11y.atspi.Accessible", "org.a11y.atspi.Component"],
+        }
+    }
+
+    // the bounds in physical pixels, relative to the screen or to the window
+    fn extents(&self, object: Object, coord_type: u32, windows: &CxWindowPool) -> Rect {
+        let Object::Node(window, node) = object else {
+            return Rect::default()
+        };
+        let geom = &windows[self.windows[window].window_id].window_geom;
+        let bounds = self.windows[window].tree.nodes[node].bounds;
+        let mut pos = bounds.pos * geom.dpi_factor;
+        if coord_type != COORD_WINDOW {
+            pos += geom.position;
+        }
+        Rect {pos, size: bounds.size * geom.dpi_factor}
+    }
+
+    // a point from the assistive tool, in the window's own coordinates
+    fn window_point(&self, window: usize, x: i32, y: i32, coord_type: u32, windows: &CxWindowPool) -> DVec2 {
+        let geom = &windows[self.windows[window].window_id].window_geom;
+        let mut pos = dvec2(x as f64, y as f64);
+        if coord_type == COORD_SCREEN {
+            pos -= geom.position;
+        }
+        pos / geom.dpi_factor
+    }
+
+    fn accessible_at_point(&self, object: Object, x: i32, y: i32, coord_type: u32, windows: &CxWindowPool) -> Option<Object> {
+        let Object::Node(window, mut node) = object else {
+            return None
+        };
+        let pos = self.window_point(window, x, y, coord_type, windows);
+        let nodes = &self.windows[window].tree.nodes;
+        if !nodes[node].bounds.contains(pos) {
+            return None
+        }
+        while let Some(child) = nodes[node].children.iter().rev().find( | child | nodes[**child].bounds.contains(pos)) {
+            node = *child;
+        }
+        Some(Object::Node(window, node))
+    }
+
+    fn text(&self, object: Object) -> String {
+        self.node(object).and_then( | node | node.value.clone()).unwrap_or_default()
+    }
+
+    fn property(&self, object: Object, iface: &str, property: &str, w: &mut Writer) -> bool {
+        match (iface, property) {
+            ("org.a11y.atspi.Accessible", "Name") => w.variant("s", | w | w.string(&self.name(object))),
+            ("org.a11y.atspi.Accessible", "Description") => w.variant("s", | w | w.string("")),
+            ("org.a11y.atspi.Accessible", "Locale") => w.variant("s", | w | w.string("")),
+            ("org.a11y.atspi.Accessible", "AccessibleId") => w.variant("s", | w | w.string("")),
+            ("org.a11y.atspi.Accessible", "Parent") => w.variant("(so)", | w | w.object(&self.parent(object))),
+            ("org.a11y.atspi.Accessible", "ChildCount") => w.variant("i", | w | w.i32(self.children(object).len() as i32)),
+            ("org.a11y.atspi.Application", "ToolkitName") => w.variant("s", | w | w.string("makepad")),
+            ("org.a11y.atspi.Application", "Version") => w.variant("s", | w | w.string(env!("CARGO_PKG_VERSION"))),
+            ("org.a11y.atspi.Application", "AtspiVersion") => w.variant("s", | w | w.string("2.1")),
+            ("org.a11y.atspi.Application", "Id") => w.variant("i", | w | w.i32(0)),
+            ("org.a11y.atspi.Text", "CharacterCount") => w.variant("i", | w | w.i32(self.text(object).chars().count() as i32)),
+            ("org.a11y.atspi.Text", "CaretOffset") => w.variant("i", | w | w.i32(self.text(object).chars().count() as i32)),
+            _ => return false
+        }
+        true
+    }
+
+    fn properties(iface: &str) -> &'static [&'static str] {
+        match iface {
+            "org.a11y.atspi.Accessible" => &["Name", "Description", "Locale", "AccessibleId", "Parent", "ChildCount"],
+            "org.a11y.atspi.Application" => &["ToolkitName", "Version", "AtspiVersion", "Id"],
+            "org.a11y.atspi.Text" => &["CharacterCount", "CaretOffset"],
+            _ => &[]
+        }
+    }
+
+    fn reply_error(&self, message: *mut DBusMessage, name: &str, text: &str) {
+        let name = CString::new(name).unwrap();
+        let text = CString::new(text).unwrap();
+        self.send(unsafe {(self.dbus.dbus_message_new_error)(message, name.as_ptr(), text.as_ptr())});
+    }
+
+    fn handle_call(&self, message: *mut DBusMessage, windows: &CxWindowPool) {
+        let (path, iface, member) = unsafe {(
+            c_str((self.dbus.dbus_message_get_path)(message)),
+            c_str((self.dbus.dbus_message_get_interface)(message)),
+            c_str((self.dbus.dbus_message_get_member)(message)),
+        )};
+        let Some(object) = self.object(path) else {
+            return self.reply_error(message, "org.freedesktop.DBus.Error.UnknownObject", path)
+        };
+        let mut args = Reader::new(&self.dbus, message);
+        let reply = unsafe {(self.dbus.dbus_message_new_method_return)(message)};
+        let mut w = Writer::new(&self.dbus, reply);
+        let handled = match (iface, member) {
+            ("org.freedesktop.DBus.Properties", "Get") => {
+                let iface = args.string().unwrap_or_default();
+                let property = args.string().unwrap_or_default();
+                self.property(object, &iface, &property, &mut w)
+            }
+            ("org.freedesktop.DBus.Properties", "GetAll") => {
+                let iface = args.string().unwrap_or_default();
+                w.container(DBUS_TYPE_ARRAY, Some("{sv}"), | w | {
+                    for property in Self::properties(&iface) {
+                        w.container(DBUS_TYPE_DICT_ENTRY, None, | w | {
+                            w.string(property);
+                            self.property(object, &iface, property, w);
+                        });
+                    }
+                });
+                true
+            }
+            // we don't let the tools change anything, but the registry sets our Id
+            ("org.freedesktop.DBus.Properties", "Set") => true,
+            ("org.a11y.atspi.Accessible", "GetChildAtIndex") => {
+                let index = args.i32().unwrap_or(-1);
+                let child = usize::try_from(index).ok().and_then( | index | self.children(object).get(index).copied());
+                w.object(&child.map_or_else( || self.null_reference(), | child | self.reference(child)));
+                true
+            }
+            ("org.a11y.atspi.Accessible", "GetChildren") => {
+                w.container(DBUS_TYPE_ARRAY, Some("(so)"), | w | {
+                    for child in self.children(object) {
+                        w.object(&self.reference(child));
+                    }
+                });
+                true
+            }
+            ("org.a11y.atspi.Accessible", "GetIndexInParent") => {
+                w.i32(self.index_in_parent(object));
+                true
+            }
+            ("org.a11y.atspi.Accessible", "GetRelationSet") => {
+                w.container(DBUS_TYPE_ARRAY, Some("(ua(so))"), | _ | ());
+                true
+            }
+            ("org.a11y.atspi.Accessible", "GetRole") => {
+                w.u32(self.role(object).0);
+                true
+            }
+            ("org.a11y.atspi.Accessible", "GetRoleName") | ("org.a11y.atspi.Accessible", "GetLocalizedRoleName") => {
+                w.string(self.role(object).1);
+                true
+            }
+            ("org.a11y.atspi.Accessible", "GetState") => {
+                let state = self.node(object).map_or([0, 0], state);
+                w.container(DBUS_TYPE_ARRAY, Some("u"), | w | {
+                    w.u32(state[0]);
+                    w.u32(state[1]);
+                });
+                true
+            }
+            ("org.a11y.atspi.Accessible", "GetAttributes") => {
+                w.container(DBUS_TYPE_ARRAY, Some("{ss}"), | w | {
+                    w.container(DBUS_TYPE_DICT_ENTRY, None, | w | {
+                        w.string("toolkit");
+                        w.string("makepad");
+                    });
+                });
+                true
+            }
+            ("org.a11y.atspi.Accessible", "GetApplication") => {
+                w.object(&self.reference(Object::Root));
+                true
+            }
+            ("org.a11y.atspi.Accessible", "GetInterfaces") => {
+                w.container(DBUS_TYPE_ARRAY, Some("s"), | w | {
+                    for iface in self.interfaces(object) {
+                        w.string(iface);
+                    }
+                });
+                true
+            }
+            ("org.a11y.atspi.Application", "GetLocale") => {
+                w.string("");
+                true
+            }
+            ("org.a11y.atspi.Component", "GetExtents") => {
+                let rect = self.extents(object, args.u32().unwrap_or(COORD_SCREEN), windows);
+                w.container(DBUS_TYPE_STRUCT, None, | w | {
+                    w.i32(rect.pos.x as i32);
+                    w.i32(rect.pos.y as i32);
+                    w.i32(rect.size.x as i32);
+                    w.i32(rect.size.y as i32);
+                });
+                true
+            }
+            ("org.a11y.atspi.Component", "GetPosition") => {
+                let rect = self.extents(object, args.u32().unwrap_or(COORD_SCREEN), windows);
+                w.i32(rect.pos.x as i32);
+                w.i32(rect.pos.y as i32);
+                true
+            }
+            ("org.a11y.atspi.Component", "GetSize") => {
+                let rect = self.extents(object, COORD_WINDOW, windows);
+                w.i32(rect.size.x as i32);
+                w.i32(rect.size.y as i32);
+                true
+            }
+            ("org.a11y.atspi.Component", "Contains") => {
+                let (x, y, coord_type) = (args.i32().unwrap_or(0), args.i32().unwrap_or(0), args.u32().unwrap_or(COORD_SCREEN));
+                w.bool(match object {
+                    Object::Node(window, node) => {
+                        let pos = self.window_point(window, x, y, coord_type, windows);
+                        self.windows[window].tree.nodes[node].bounds.contains(pos)
+                    }
+                    Object::Root => false
+                });
+                true
+            }
+            ("org.a11y.atspi.Component", "GetAccessibleAtPoint") => {
+                let (x, y, coord_type) = (args.i32().unwrap_or(0), args.i32().unwrap_or(0), args.u32().unwrap_or(COORD_SCREEN));
+                let found = self.accessible_at_point(object, x, y, coord_type, windows);
+                w.object(&found.map_or_else( || self.null_reference(), | found | self.reference(found)));
+                true
+            }
+            ("org.a11y.atspi.Component", "GetLayer") => {
+                w.u32(if let Object::Node(_, 0) = object {LAYER_WINDOW} else {LAYER_WIDGET});
+                true
+            }
+            ("org.a11y.atspi.Component", "GetMDIZOrder") => {
+                w.i16(0);
+                true
+            }
+            ("org.a11y.atspi.Component", "GetAlpha") => {
+                w.f64(1.0);
+                true
+            }
+            ("org.a11y.atspi.Component", "GrabFocus") => {
+                w.bool(false);
+                true
+            }
+            ("org.a11y.atspi.Text", "GetText") => {
+                let text = self.text(object);
+                let count = text.chars().count();
+                let start = args.i32().unwrap_or(0).clamp(0, count as i32) as usize;
+                let end = match args.i32().unwrap_or(-1) {
+                    end if end < 0 => count,
+                    end => (end as usize).clamp(start, count)
+                };
+                w.string(&text.chars().skip(start).take(end - start).collect::<String>());
+                true
+            }
+            _ => false
+        };
+        if handled {
+            self.send(reply);
+        }
+        else {
+            unsafe {(self.dbus.dbus_message_unref)(reply)};
+            self.reply_error(message, "org.freedesktop.DBus.Error.UnknownMethod", member);
+        }
+    }
+}
+
+impl Drop for AtSpiBridge {
+    fn drop(&mut self) {
+        unsafe {
+            (self.dbus.dbus_connection_close)(self.connection);
+            (self.dbus.dbus_connection_unref)(self.connection);
+        }
+    }
+}
@@ -0,0 +1,201 @@
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+
+// libdbus-1, loaded at runtime so apps still start on systems without it
+
+use {
+    std::{
+        ffi::CString,
+        os::raw::{c_char, c_int, c_uint, c_void},
+        ptr::NonNull,
+    },
+    self::super::libc_sys::{dlclose, dlopen, dlsym, RTLD_LAZY, RTLD_LOCAL},
+};
+
+pub type dbus_bool_t = c_uint;
+
+#[repr(C)]
+pub struct DBusConnection {_private: [u8; 0]}
+
+#[repr(C)]
+pub struct DBusMessage {_private: [u8; 0]}
+
+#[repr(C)]
+pub struct DBusError {
+    pub name: *const c_char,
+    pub message: *const c_char,
+    pub dummy: c_uint,
+    pub padding1: *mut c_void,
+}
+
+impl Default for DBusError {
+    fn default() -> Self {
+        Self {name: std::ptr::null(), message: std::ptr::null(), dummy: 0, padding1: std::ptr::null_mut()}
+    }
+}
+
+// larger than libdbus' own definition, it only ever lives on our stack
+#[repr(C)]
+#[derive(Default)]
+pub struct DBusMessageIter {
+    _data: [usize; 16],
+}
+
+pub const DBUS_BUS_SESSION: c_int = 0;
+
+pub const DBUS_MESSAGE_TYPE_METHOD_CALL: c_int = 1;
+pub const DBUS_MESSAGE_TYPE_SIGNAL: c_int = 4;
+
+pub const DBUS_TYPE_INVALID: c_int = 0;
+pub const DBUS_TYPE_BYTE: c_int = b'y' as c_int;
+pub const DBUS_TYPE_BOOLEAN: c_int = b'b' as c_int;
+pub const DBUS_TYPE_INT16: c_int = b'n' as c_int;
+pub const DBUS_TYPE_INT32: c_int = b'i' as c_int;
+pub const DBUS_TYPE_UINT32: c_int = b'u' as c_int;
+pub const DBUS_TYPE_DOUBLE: c_int = b'd' as c_int;
+pub const DBUS_TYPE_STRING: c_int = b's' as c_int;
+pub const DBUS_TYPE_OBJECT_PATH: c_int = b'o' as c_int;
+pub const DBUS_TYPE_ARRAY: c_int = b'a' as c_int;
+pub const DBUS_TYPE_VARIANT: c_int = b'v' as c_int;
+pub const DBUS_TYPE_STRUCT: c_int = b'r' as c_int;
+pub const DBUS_TYPE_DICT_ENTRY: c_int = b'e' as c_int;
+
+pub type PFN_dbus_error_init = unsafe extern "C" fn(error: *mut DBusError);
+pub type PFN_dbus_error_free = unsafe extern "C" fn(error: *mut DBusError);
+pub type PFN_dbus_error_is_set = unsafe extern "C" fn(error: *const DBusError) -> dbus_bool_t;
+pub type PFN_dbus_bus_get_private = unsafe extern "C" fn(ty: c_int, error: *mut DBusError) -> *mut DBusConnection;
+pub type PFN_dbus_bus_register = unsafe extern "C" fn(connection: *mut DBusConnection, error: *mut DBusError) -> dbus_bool_t;
+pub type PFN_dbus_bus_get_unique_name = unsafe extern "C" fn(connection: *mut DBusConnection) -> *const c_char;
+pub type PFN_dbus_connection_open_private = unsafe extern "C" fn(address: *const c_char, error: *mut DBusError) -> *mut DBusConnection;
+pub type PFN_dbus_connection_close = unsafe extern "C" fn(connection: *mut DBusConnection);
+pub type PFN_dbus_connection_unref = unsafe extern "C" fn(connection: *mut DBusConnection);
+pub type PFN_dbus_connection_set_exit_on_disconnect = unsafe extern "C" fn(connection: *mut DBusConnection, exit_on_disconnect: dbus_bool_t);
+pub type PFN_dbus_connection_read_write = unsafe extern "C" fn(connection: *mut DBusConnection, timeout_milliseconds: c_int) -> dbus_bool_t;
+pub type PFN_dbus_connection_pop_message = unsafe extern "C" fn(connection: *mut DBusConnection) -> *mut DBusMessage;
+pub type PFN_dbus_connection_send = unsafe extern "C" fn(connection: *mut DBusConnection, message: *mut DBusMessage, serial: *mut c_uint) -> dbus_bool_t;
+pub type PFN_dbus_connection_send_with_reply_and_block = unsafe extern "C" fn(connection: *mut DBusConnection, message: *mut DBusMessage, timeout_milliseconds: c_int, error: *mut DBusError) -> *mut DBusMessage;
+pub type PFN_dbus_connection_flush = unsafe extern "C" fn(connection: *mut DBusConnection);
+pub type PFN_dbus_message_new_method_call = unsafe extern "C" fn(destination: *const c_char, path: *const c_char, iface: *const c_char, method: *const c_char) -> *mut DBusMessage;
+pub type PFN_dbus_message_new_method_return = unsafe extern "C" fn(method_call: *mut DBusMessage) -> *mut DBusMessage;
+pub type PFN_dbus_message_new_error = unsafe extern "C" fn(reply_to: *mut DBusMessage, error_name: *const c_char, error_message: *const c_char) -> *mut DBusMessage;
+pub type PFN_dbus_message_new_signal = unsafe extern "C" fn(path: *const c_char, iface: *const c_char, name: *const c_char) -> *mut DBusMessage;
+pub type PFN_dbus_message_unref = unsafe extern "C" fn(message: *mut DBusMessage);
+pub type PFN_dbus_message_get_type = unsafe extern "C" fn(message: *mut DBusMessage) -> c_int;
+pub type PFN_dbus_message_get_path = unsafe extern "C" fn(message: *mut DBusMessage) -> *const c_char;
+pub type PFN_dbus_message_get_interface = unsafe extern "C" fn(message: *mut DBusMessage) -> *const c_char;
+pub type PFN_dbus_message_get_member = unsafe extern "C" fn(message: *mut DBusMessage) -> *const c_char;
+pub type PFN_dbus_message_iter_init = unsafe extern "C" fn(message: *mut DBusMessage, iter: *mut DBusMessageIter) -> dbus_bool_t;
+pub type PFN_dbus_message_iter_init_append = unsafe extern "C" fn(message: *mut DBusMessage, iter: *mut DBusMessageIter);
+pub type PFN_dbus_message_iter_get_arg_type = unsafe extern "C" fn(iter: *mut DBusMessageIter) -> c_int;
+pub type PFN_dbus_message_iter_get_basic = unsafe extern "C" fn(iter: *mut DBusMessageIter, value: *mut c_void);
+pub type PFN_dbus_message_iter_recurse = unsafe extern "C" fn(iter: *mut DBusMessageIter, sub: *mut DBusMessageIter);
+pub type PFN_dbus_message_iter_next = unsafe extern "C" fn(iter: *mut DBusMessageIter) -> dbus_bool_t;
+pub type PFN_dbus_message_iter_append_basic = unsafe extern "C" fn(iter: *mut DBusMessageIter, ty: c_int, value: *const c_void) -> dbus_bool_t;
+pub type PFN_dbus_message_iter_open_container = unsafe extern "C" fn(iter: *mut DBusMessageIter, ty: c_int, contained_signature: *const c_char, sub: *mut DBusMessageIter) -> dbus_bool_t;
+pub type PFN_dbus_message_iter_close_container = unsafe extern "C" fn(iter: *mut DBusMessageIter, sub: *mut DBusMessageIter) -> dbus_bool_t;
+
+struct Module(NonNull<c_void>);
+
+impl Module {
+    fn load(path: &str) -> Result<Self, ()> {
+        let path = CString::new(path).unwrap();
+        let module = unsafe {dlopen(path.as_ptr(), RTLD_LAZY | RTLD_LOCAL)};
+        NonNull::new(module).map(Module).ok_or(())
+    }
+
+    fn get_symbol<F: Sized>(&self, name: &str) -> Result<F, ()> {
+        let name = CString::new(name).unwrap();
+        let symbol = unsafe {dlsym(self.0.as_ptr(), name.as_ptr())};
+        if symbol.is_null() {
+            return Err(());
+        }
+        Ok(unsafe {std::mem::transmute_copy::<_, F>(&symbol)})
+    }
+}
+
+impl Drop for Module {
+    fn drop(&mut self) {
+        unsafe {dlclose(self.0.as_ptr())};
+    }
+}
+
+pub struct LibDbus {
+    pub dbus_error_init: PFN_dbus_error_init,
+    pub dbus_error_free: PFN_dbus_error_free,
+    pub dbus_error_is_set: PFN_dbus_error_is_set,
+    pub dbus_bus_get_private: PFN_dbus_bus_get_private,
+    pub dbus_bus_register: PFN_dbus_bus_register,
+    pub dbus_bus_get_unique_name: PFN_dbus_bus_get_unique_name,
+    pub dbus_connection_open_private: PFN_dbus_connection_open_private,
+    pub dbus_connection_close: PFN_dbus_connection_close,
+    pub dbus_connection_unref: PFN_dbus_connection_unref,
+    pub dbus_connection_set_exit_on_disconnect: PFN_dbus_connection_set_exit_on_disconnect,
+    pub dbus_connection_read_write: PFN_dbus_connection_read_write,
+    pub dbus_connection_pop_message: PFN_dbus_connection_pop_message,
+    pub dbus_connection_send: PFN_dbus_connection_send,
+    pub dbus_connection_send_with_reply_and_block: PFN_dbus_connection_send_with_reply_and_block,
+    pub dbus_connection_flush: PFN_dbus_connection_flush,
+    pub dbus_message_new_method_call: PFN_dbus_message_new_method_call,
+    pub dbus_message_new_method_return: PFN_dbus_message_new_method_return,
+    pub dbus_message_new_error: PFN_dbus_message_new_error,
+    pub dbus_message_new_signal: PFN_dbus_message_new_signal,
+    pub dbus_message_unref: PFN_dbus_message_unref,
+    pub dbus_message_get_type: PFN_dbus_message_get_type,
+    pub dbus_message_get_path: PFN_dbus_message_get_path,
+    pub dbus_message_get_interface: PFN_dbus_message_get_interface,
+    pub dbus_message_get_member: PFN_dbus_message_get_member,
+    pub dbus_message_iter_init: PFN_dbus_message_iter_init,
+    pub dbus_message_iter_init_append: PFN_dbus_message_iter_init_append,
+    pub dbus_message_iter_get_arg_type: PFN_dbus_message_iter_get_arg_type,
+    pub dbus_message_iter_get_basic: PFN_dbus_message_iter_get_basic,
+    pub dbus_message_iter_recurse: PFN_dbus_message_iter_recurse,
+    pub dbus_message_iter_next: PFN_dbus_message_iter_next,
+    pub dbus_message_iter_append_basic: PFN_dbus_message_iter_append_basic,
+    pub dbus_message_iter_open_container: PFN_dbus_message_iter_open_container,
+    pub dbus_message_iter_close_container: PFN_dbus_message_iter_close_container,
+
+    _keep_module_alive: Module,
+}
+
+impl LibDbus {
+    pub fn try_load() -> Option<LibDbus> {
+        let module = Module::load("libdbus-1.so.3").or_else( | _ | Module::load("libdbus-1.so")).ok()?;
+        Some(LibDbus {
+            dbus_error_init: module.get_symbol("dbus_error_init").ok()?,
+            dbus_error_free: module.get_symbol("dbus_error_free").ok()?,
+            dbus_error_is_set: module.get_symbol("dbus_error_is_set").ok()?,
+            dbus_bus_get_private: module.get_symbol("dbus_bus_get_private").ok()?,
+            dbus_bus_register: module.get_symbol("dbus_bus_register").ok()?,
+            dbus_bus_get_unique_name: module.get_symbol("dbus_bus_get_unique_name").ok()?,
+            dbus_connection_open_private: module.get_symbol("dbus_connection_open_private").ok()?,
+            dbus_connection_close: module.get_symbol("dbus_connection_close").ok()?,
+            dbus_connection_unref: module.get_symbol("dbus_connection_unref").ok()?,
+            dbus_connection_set_exit_on_disconnect: module.get_symbol("dbus_connection_set_exit_on_disconnect").ok()?,
+            dbus_connection_read_write: module.get_symbol("dbus_connection_read_write").ok()?,
+            dbus_connection_pop_message: module.get_symbol("dbus_connection_pop_message").ok()?,
+            dbus_connection_send: module.get_symbol("dbus_connection_send").ok()?,
+            dbus_connection_send_with_reply_and_block: module.get_symbol("dbus_connection_send_with_reply_and_block").ok()?,
+            dbus_connection_flush: module.get_symbol("dbus_connection_flush").ok()?,
+            dbus_message_new_method_call: module.get_symbol("dbus_message_new_method_call").ok()?,
+            dbus_message_new_method_return: module.get_symbol("dbus_message_new_method_return").ok()?,
+            dbus_message_new_error: module.get_symbol("dbus_message_new_error").ok()?,
+            dbus_message_new_signal: module.get_symbol("dbus_message_new_signal").ok()?,
+            dbus_message_unref: module.get_symbol("dbus_message_unref").ok()?,
+            dbus_message_get_type: module.get_symbol("dbus_message_get_type").ok()?,
+            dbus_message_get_path: module.get_symbol("dbus_message_get_path").ok()?,
+            dbus_message_get_interface: module.get_symbol("dbus_message_get_interface").ok()?,
+            dbus_message_get_member: module.get_symbol("dbus_message_get_member").ok()?,
+            dbus_message_iter_init: module.get_symbol("dbus_message_iter_init").ok()?,
+            dbus_message_iter_init_append: module.get_symbol("dbus_message_iter_init_append").ok()?,
+            dbus_message_iter_get_arg_type: module.get_symbol("dbus_message_iter_get_arg_type").ok()?,
+            dbus_message_iter_get_basic: module.get_symbol("dbus_message_iter_get_basic").ok()?,
+            dbus_message_iter_recurse: module.get_symbol("dbus_message_iter_recurse").ok()?,
+            dbus_message_iter_next: module.get_symbol("dbus_message_iter_next").ok()?,
+            dbus_message_iter_append_basic: module.get_symbol("dbus_message_iter_append_basic").ok()?,
+            dbus_message_iter_open_container: module.get_symbol("dbus_message_iter_open_container").ok()?,
+            dbus_message_iter_close_container: module.get_symbol("dbus_message_iter_close_container").ok()?,
+            _keep_module_alive: module,
+        })
+    }
+}
@@ -0,0 +1,415 @@
+use {
+    std::rc::Rc,
+    std::cell::RefCell,
+    std::collections::{BTreeMap, VecDeque},
+    self::super::{
+        headless_event::*,
+    },
+    self::super::super::{
+        linux_media::CxLinuxMedia,
+        http::make_http_request,
+    },
+    crate::{
+        cx_api::{CxOsOp, CxOsApi},
+        makepad_live_id::*,
+        makepad_math::*,
+        makepad_image_formats::ImageBuffer,
+        thread::SignalToUI,
+        event::{
+            TimerEvent,
+            Event,
+            WindowGeom,
+            WindowGeomChangeEvent,
+            NetworkResponseChannel,
+        },
+        window::WindowId,
+        pass::CxPassParent,
+        cx::{Cx, OsType,},
+        gpu_info::GpuPerformance,
+        os::cx_native::EventFlow,
+    }
+};
+
+// how many rendered frames are kept around for take_headless_frames, older ones are dropped
+const MAX_HEADLESS_FRAMES: usize = 16;
+
+// One rendered window pass, rows are top to bottom in 0xAARRGGBB
+pub struct HeadlessFrame {
+    pub window_id: WindowId,
+    pub repaint_id: u64,
+    pub time: f64,
+    pub image: ImageBuffer,
+}
+
+struct HeadlessTimer {
+    interval: f64,
+    next_time: f64,
+    repeats: bool,
+}
+
+// timers run on a virtual clock so headless runs are reproducible
+#[derive(Default)]
+pub struct HeadlessTimers {
+    time: f64,
+    timers: BTreeMap<u64, HeadlessTimer>,
+}
+
+impl HeadlessTimers {
+    pub fn time_now(&self) -> f64 {
+        self.time
+    }
+
+    pub fn start_timer(&mut self, timer_id: u64, interval: f64, repeats: bool) {
+        self.timers.insert(timer_id, HeadlessTimer {
+            interval,
+            next_time: self.time + interval,
+            repeats
+        });
+    }
+
+    pub fn stop_timer(&mut self, timer_id: u64) {
+        self.timers.remove(&timer_id);
+    }
+
+    pub fn advance(&mut self, dt: f64, out: &mut Vec<u64>) {
+        out.clear();
+        self.time += dt;
+        let time = self.time;
+        let mut due: Vec<(f64, u64)> = self.timers.iter()
+            .filter( | (_, timer) | timer.next_time <= time)
+            .map( | (timer_id, timer) | (timer.next_time, *timer_id))
+            .collect();
+        due.sort_by( | a, b | a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        for (_, timer_id) in due {
+            let timer = self.timers.get_mut(&timer_id).unwrap();
+            if timer.repeats {
+                // fire once per step, skipping the intervals we stepped over
+                if timer.interval > 0.0 {
+                    while timer.next_time <= time {
+                        timer.next_time += timer.interval;
+                    }
+                }
+                else {
+                    timer.next_time = time;
+                }
+            }
+            else {
+                self.timers.remove(&timer_id);
+            }
+            out.push(timer_id);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+}
+
+impl Cx {
+    pub fn event_loop(cx: Rc<RefCell<Cx >>) {
+        let mut cx = cx.borrow_mut();
+
+        let mut max_frames = None;
+        let mut frame_time = 1.0 / 60.0;
+        for arg in std::env::args() {
+            if arg.starts_with("-headless-frames=") {
+                max_frames = arg.trim_start_matches("-headless-frames=").parse::<usize>().ok();
+            }
+            if arg.starts_with("-headless-fps=") {
+                if let Ok(fps) = arg.trim_start_matches("-headless-fps=").parse::<f64>() {
+                    frame_time = 1.0 / fps.max(1.0);
+                }
+            }
+        }
+
+        cx.headless_startup();
+
+        let mut frames = 0;
+        loop {
+            let event_flow = cx.headless_step(frame_time);
+            frames += 1;
+            if event_flow == EventFlow::Exit {
+                break
+            }
+            if max_frames.map_or(false, | max_frames | frames >= max_frames) {
+                break
+            }
+            // nothing left that could change the output
+            if event_flow == EventFlow::Wait && cx.os.timers.is_empty() {
+                break
+            }
+        }
+    }
+
+    pub fn headless_startup(&mut self) {
+        self.os_type = OsType::LinuxHeadless;
+        self.gpu_info.performance = GpuPerformance::Tier1;
+
+        self.call_event_handler(&Event::Startup);
+        self.redraw_all();
+    }
+
+    // advances the virtual clock, fires the timers that came due and paints
+    pub fn headless_step(&mut self, dt: f64) -> EventFlow {
+        let mut timer_ids = Vec::new();
+        self.os.timers.advance(dt, &mut timer_ids);
+        let time = self.os.timers.time_now();
+
+        if SignalToUI::check_and_clear_ui_signal() {
+            self.handle_media_signals();
+            self.call_event_handler(&Event::Signal);
+        }
+        self.handle_networking_events();
+
+        for timer_id in timer_ids {
+            let event_flow = self.headless_event(HeadlessEvent::Timer(TimerEvent {
+                timer_id,
+                time: Some(time)
+            }));
+            if event_flow == EventFlow::Exit {
+                return EventFlow::Exit
+            }
+        }
+        self.headless_event(HeadlessEvent::Paint)
+    }
+
+    pub fn headless_event(&mut self, event: HeadlessEvent) -> EventFlow {
+        if let EventFlow::Exit = self.handle_platform_ops() {
+            return EventFlow::Exit
+        }
+
+        match event {
+            HeadlessEvent::Paint => {
+                if self.new_next_frames.len() != 0 {
+                    self.call_next_frame_event(self.os.timers.time_now());
+                }
+                if self.need_redrawing() {
+                    self.call_draw_event();
+                    self.headless_compile_shaders();
+                }
+                self.handle_repaint();
+            }
+            HeadlessEvent::MouseDown(e) => {
+                self.fingers.process_tap_count(
+                    e.abs,
+                    e.time
+                );
+                self.fingers.mouse_down(e.button, e.window_id);
+                self.call_event_handler(&Event::MouseDown(e.into()))
+            }
+            HeadlessEvent::MouseMove(e) => {
+                self.call_event_handler(&Event::MouseMove(e.into()));
+                self.fingers.cycle_hover_area(live_id!(mouse).into());
+                self.fingers.switch_captures();
+            }
+            HeadlessEvent::MouseUp(e) => {
+                let button = e.button;
+                self.call_event_handler(&Event::MouseUp(e.into()));
+                self.fingers.mouse_up(button);
+                self.fingers.cycle_hover_area(live_id!(mouse).into());
+            }
+            HeadlessEvent::Scroll(e) => {
+                self.call_event_handler(&Event::Scroll(e.into()))
+            }
+            HeadlessEvent::KeyDown(e) => {
+                self.keyboard.process_key_down(e.clone());
+                self.call_event_handler(&Event::KeyDown(e))
+            }
+            HeadlessEvent::KeyUp(e) => {
+                self.keyboard.process_key_up(e.clone());
+                self.call_event_handler(&Event::KeyUp(e))
+            }
+            HeadlessEvent::TextInput(e) => {
+                self.call_event_handler(&Event::TextInput(e))
+            }
+            HeadlessEvent::Timer(e) => {
+                self.call_event_handler(&Event::Timer(e))
+            }
+        }
+        if let EventFlow::Exit = self.handle_platform_ops() {
+            return EventFlow::Exit
+        }
+        if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 {
+            EventFlow::Poll
+        } else {
+            EventFlow::Wait
+        }
+    }
+
+    // the last frames rendered since the last call, oldest first
+    pub fn take_headless_frames(&mut self) -> Vec<HeadlessFrame> {
+        self.os.frames.drain(..).collect()
+    }
+
+    // overrides the size of all windows, before or after they are created
+    pub fn set_headless_window_size(&mut self, inner_size: DVec2, dpi_factor: f64) {
+        self.os.window_size = Some(inner_size);
+        self.os.dpi_factor = dpi_factor;
+        for window_id in self.os.windows.clone() {
+            let old_geom = self.windows[window_id].window_geom.clone();
+            let new_geom = self.headless_window_geom(inner_size);
+            self.windows[window_id].window_geom = new_geom.clone();
+            if let Some(main_pass_id) = self.windows[window_id].main_pass_id {
+                self.redraw_pass_and_child_passes(main_pass_id);
+            }
+            self.call_event_handler(&Event::WindowGeomChange(WindowGeomChangeEvent {
+                window_id,
+                old_geom,
+                new_geom
+            }));
+        }
+    }
+
+    fn headless_window_geom(&self, inner_size: DVec2) -> WindowGeom {
+        WindowGeom {
+            dpi_factor: self.os.dpi_factor,
+            can_fullscreen: false,
+            xr_is_presenting: false,
+            is_fullscreen: false,
+            is_topmost: false,
+            position: dvec2(0.0, 0.0),
+            inner_size,
+            outer_size: inner_size
+        }
+    }
+
+    pub(crate) fn handle_networking_events(&mut self) {
+        let mut out = Vec::new();
+        while let Ok(event) = self.os.network_response.receiver.try_recv() {
+            out.push(event);
+        }
+        if out.len()>0 {
+            self.call_event_handler(&Event::NetworkResponses(out))
+        }
+    }
+
+    pub (crate) fn handle_repaint(&mut self) {
+        let mut passes_todo = Vec::new();
+        self.compute_pass_repaint_order(&mut passes_todo);
+        self.repaint_id += 1;
+        let time = self.os.timers.time_now();
+        for pass_id in &passes_todo {
+            self.passes[*pass_id].set_time(time as f32);
+            match self.passes[*pass_id].parent.clone() {
+                CxPassParent::Window(window_id) => {
+                    if !self.windows[window_id].is_created {
+                        continue;
+                    }
+                    if let Some(image) = self.draw_pass_to_image(*pass_id) {
+                        if self.os.frames.len() == MAX_HEADLESS_FRAMES {
+                            self.os.frames.pop_front();
+                        }
+                        self.os.frames.push_back(HeadlessFrame {
+                            window_id,
+                            repaint_id: self.repaint_id,
+                            time,
+                            image
+                        });
+                    }
+                }
+                CxPassParent::Pass(_) => {
+                    self.draw_pass_to_magic_texture(*pass_id);
+                },
+                CxPassParent::None => {
+                    self.draw_pass_to_magic_texture(*pass_id);
+                }
+            }
+        }
+    }
+
+    fn handle_platform_ops(&mut self) -> EventFlow {
+        let mut ret = EventFlow::Poll;
+        while let Some(op) = self.platform_ops.pop() {
+            match op {
+                CxOsOp::CreateWindow(window_id) => {
+                    let inner_size = self.os.window_size.unwrap_or(
+                        self.windows[window_id].create_inner_size.unwrap_or(dvec2(800., 600.))
+                    );
+                    let window_geom = self.headless_window_geom(inner_size);
+                    let window = &mut self.windows[window_id];
+                    window.window_geom = window_geom;
+                    window.is_created = true;
+                    if !self.os.windows.contains(&window_id) {
+                        self.os.windows.push(window_id);
+                    }
+                },
+                CxOsOp::CloseWindow(window_id) => {
+                    self.windows[window_id].is_created = false;
+                    self.os.windows.retain( | w | *w != window_id);
+                    if self.os.windows.len() == 0 {
+                        ret = EventFlow::Exit
+                    }
+                },
+                CxOsOp::Quit => {
+                    ret = EventFlow::Exit
+                },
+                CxOsOp::StartTimer {timer_id, interval, repeats} => {
+                    self.os.timers.start_timer(timer_id, interval, repeats);
+                },
+                CxOsOp::StopTimer(timer_id) => {
+                    self.os.timers.stop_timer(timer_id);
+                },
+                CxOsOp::HttpRequest{request_id, request} => {
+                    make_http_request(request_id, request, self.os.network_response.sender.clone());
+                },
+                _ => ()
+            }
+        }
+        ret
+    }
+}
+
+impl CxOsApi for Cx {
+    fn init_cx_os(&mut self) {
+        for arg in std::env::args() {
+            if arg.starts_with("-headless-size=") {
+                let size = arg.trim_start_matches("-headless-size=");
+                if let Some((w, h)) = size.split_once('x') {
+                    if let (Ok(w), Ok(h)) = (w.parse(), h.parse()) {
+                        self.os.window_size = Some(dvec2(w, h));
+                    }
+                }
+            }
+            if arg.starts_with("-headless-dpi=") {
+                if let Ok(dpi_factor) = arg.trim_start_matches("-headless-dpi=").parse() {
+                    self.os.dpi_factor = dpi_factor;
+                }
+            }
+        }
+        self.live_expand();
+        self.live_scan_dependencies();
+        self.native_load_dependencies();
+    }
+
+    fn spawn_thread<F>(&mut self, f: F) where F: FnOnce() + Send + 'static {
+        std::thread::spawn(f);
+    }
+
+    fn seconds_since_app_start(&self)->f64{
+        self.os.timers.time_now()
+    }
+}
+
+pub struct CxOs {
+    pub (crate) media: CxLinuxMedia,
+    pub (crate) network_response: NetworkResponseChannel,
+    pub (crate) timers: HeadlessTimers,
+    pub (crate) window_size: Option<DVec2>,
+    pub (crate) dpi_factor: f64,
+    pub (crate) windows: Vec<WindowId>,
+    pub (crate) frames: VecDeque<HeadlessFrame>,
+}
+
+impl Default for CxOs {
+    fn default() -> Self {
+        Self {
+            media: Default::default(),
+            network_response: Default::default(),
+            timers: Default::default(),
+            window_size: None,
+            dpi_factor: 1.0,
+            windows: Vec::new(),
+            frames: VecDeque::new(),
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod linux_headless;
+pub mod headless_event;
+pub mod software;
@@ -0,0 +1,762 @@
+use {
+    std::mem,
+    crate::{
+        makepad_shader_compiler::{
+            DrawShaderPtr,
+            ShaderTy,
+            shader_ast::{Ident, DrawShaderFieldKind},
+            cpu_shader::{CpuShader, CpuShaderState, CpuShaderTextures, CpuFloat, CpuValue},
+        },
+        makepad_image_formats::ImageBuffer,
+        makepad_math::{Mat4, DVec2, Vec4},
+        cx::Cx,
+        texture::{Texture, TextureFormat, CxTexture},
+        pass::{PassClearColor, PassClearDepth, PassId},
+        draw_list::DrawListId,
+        draw_shader::DrawShaderInputs,
+    },
+};
+
+// A cpu side color buffer, rows are stored top to bottom
+#[derive(Clone, Default)]
+pub struct SoftSurface {
+    pub width: usize,
+    pub height: usize,
+    pub clamp: bool,
+    pub pixels: Vec<[f32; 4]>,
+}
+
+impl SoftSurface {
+    pub fn resize(&mut self, width: usize, height: usize, clamp: bool) -> bool {
+        self.clamp = clamp;
+        if self.width != width || self.height != height || self.pixels.len() != width * height {
+            self.width = width;
+            self.height = height;
+            self.pixels = vec![[0.0; 4]; width * height];
+            return true
+        }
+        false
+    }
+
+    pub fn clear(&mut self, color: Vec4) {
+        let mut c = [color.x, color.y, color.z, color.w];
+        if self.clamp {
+            c.iter_mut().for_each( | c | *c = c.clamp(0.0, 1.0));
+        }
+        self.pixels.iter_mut().for_each( | p | *p = c);
+    }
+
+    pub fn to_image_buffer(&self) -> ImageBuffer {
+        let mut image = ImageBuffer::new(self.width, self.height);
+        for (out, p) in image.data.iter_mut().zip(self.pixels.iter()) {
+            let c = | v: f32 | (v.clamp(0.0, 1.0) * 255.0 + 0.5) as u32;
+            *out = (c(p[3]) << 24) | (c(p[0]) << 16) | (c(p[1]) << 8) | c(p[2]);
+        }
+        image
+    }
+
+    fn downsample(&self) -> SoftSurface {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0.0; 4];
+                for (sx, sy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let px = (x * 2 + sx).min(self.width - 1);
+                    let py = (y * 2 + sy).min(self.height - 1);
+                    let p = self.pixels[py * self.width + px];
+                    for i in 0..4 {sum[i] += p[i] * 0.25}
+                }
+                pixels.push(sum);
+            }
+        }
+        SoftSurface {width, height, clamp: self.clamp, pixels}
+    }
+
+    fn texel(&self, x: isize, y: isize) -> [f32; 4] {
+        let x = x.clamp(0, self.width as isize - 1) as usize;
+        let y = y.clamp(0, self.height as isize - 1) as usize;
+        self.pixels[y * self.width + x]
+    }
+
+    fn sample_nearest(&self, u: f32, v: f32) -> [f32; 4] {
+        self.texel(
+            (u * self.width as f32).floor() as isize,
+            (v * self.height as f32).floor() as isize
+        )
+    }
+
+    fn sample_linear(&self, u: f32, v: f32) -> [f32; 4] {
+        let x = u * self.width as f32 - 0.5;
+        let y = v * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as isize, y0 as isize);
+        let a = self.texel(x0, y0);
+        let b = self.texel(x0 + 1, y0);
+        let c = self.texel(x0, y0 + 1);
+        let d = self.texel(x0 + 1, y0 + 1);
+        let mut out = [0.0; 4];
+        for i in 0..4 {
+            let top = a[i] + (b[i] - a[i]) * fx;
+            let bottom = c[i] + (d[i] - c[i]) * fx;
+            out[i] = top + (bottom - top) * fy;
+        }
+        out
+    }
+}
+
+#[derive(Clone)]
+pub struct CxOsDrawShader {
+    pub draw_shader_ptr: DrawShaderPtr,
+}
+
+#[derive(Clone, Default)]
+pub struct CxOsGeometry {
+}
+
+#[derive(Clone, Default)]
+pub struct CxOsView {
+}
+
+#[derive(Default, Clone)]
+pub struct CxOsDrawCall {
+}
+
+#[derive(Clone, Default)]
+pub struct CxOsTexture {
+    // vec textures keep their mip chain here, render targets only use level 0
+    pub levels: Vec<SoftSurface>,
+    pub depth: Vec<f32>,
+}
+
+#[derive(Default, Clone)]
+pub struct CxOsPass {
+    pub surface: SoftSurface,
+    pub depth: Vec<f32>,
+}
+
+// the color and depth buffer a pass draws into
+pub(crate) struct SoftTarget<'a> {
+    pub color: &'a mut SoftSurface,
+    pub depth: Option<&'a mut Vec<f32>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SoftFilter {
+    Nearest,
+    Linear,
+    Trilinear,
+}
+
+struct SoftTextures<'a> {
+    slots: Vec<Option<(&'a [SoftSurface], SoftFilter)>>,
+}
+
+impl<'a> CpuShaderTextures for SoftTextures<'a> {
+    fn sample2d(&self, slot: usize, x: CpuFloat, y: CpuFloat) -> [f32; 4] {
+        let Some(Some((levels, filter))) = self.slots.get(slot) else {
+            return [0.0; 4]
+        };
+        let Some(base) = levels.first() else {
+            return [0.0; 4]
+        };
+        if base.width == 0 || base.height == 0 {
+            return [0.0; 4]
+        }
+        match filter {
+            SoftFilter::Nearest => base.sample_nearest(x.v, y.v),
+            SoftFilter::Linear => base.sample_linear(x.v, y.v),
+            SoftFilter::Trilinear => {
+                // pick the mip level from the screen space derivatives of the uv
+                let (w, h) = (base.width as f32, base.height as f32);
+                let du = (x.dx * w).hypot(y.dx * h);
+                let dv = (x.dy * w).hypot(y.dy * h);
+                let lod = du.max(dv).max(1e-8).log2().clamp(0.0, (levels.len() - 1) as f32);
+                let l0 = lod.floor() as usize;
+                let l1 = (l0 + 1).min(levels.len() - 1);
+                let a = levels[l0].sample_linear(x.v, y.v);
+                if l0 == l1 {
+                    return a
+                }
+                let b = levels[l1].sample_linear(x.v, y.v);
+                let f = lod - l0 as f32;
+                let mut out = [0.0; 4];
+                for i in 0..4 {out[i] = a[i] + (b[i] - a[i]) * f}
+                out
+            }
+        }
+    }
+}
+
+// where each packed input ends up in the shader fields
+struct SoftInput {
+    field: usize,
+    offset: usize,
+    slots: usize,
+    ty: ShaderTy,
+}
+
+fn soft_inputs(shader: &CpuShader, inputs: &DrawShaderInputs) -> Vec<SoftInput> {
+    inputs.inputs.iter().filter_map( | input | {
+        Some(SoftInput {
+            field: shader.field_index(Ident(input.id)) ?,
+            offset: input.offset,
+            slots: input.slots,
+            ty: input.ty.clone(),
+        })
+    }).collect()
+}
+
+fn write_inputs(state: &mut CpuShaderState, inputs: &[SoftInput], data: &[f32]) {
+    for input in inputs {
+        let end = (input.offset + input.slots).min(data.len());
+        let start = input.offset.min(end);
+        state.fields[input.field] = CpuValue::from_slots(&input.ty, &data[start..end]);
+    }
+}
+
+// a transformed vertex, attributes are premultiplied with 1/w
+struct SoftVertex {
+    x: f32,
+    y: f32,
+    z: f32,
+    inv_w: f32,
+    attrs: Vec<f32>,
+}
+
+fn edge(ax: f32, ay: f32, bx: f32, by: f32, px: f32, py: f32) -> f32 {
+    (bx - ax) * (py - ay) - (by - ay) * (px - ax)
+}
+
+impl SoftTarget<'_> {
+    fn blend(&mut self, index: usize, src: [f32; 4]) {
+        let dst = &mut self.color.pixels[index];
+        let clamp = self.color.clamp;
+        let mut src = src;
+        if clamp {
+            src.iter_mut().for_each( | c | *c = c.clamp(0.0, 1.0));
+        }
+        let inv_a = 1.0 - src[3];
+        for i in 0..4 {
+            dst[i] = src[i] + dst[i] * inv_a;
+            if clamp {
+                dst[i] = dst[i].clamp(0.0, 1.0);
+            }
+        }
+    }
+}
+
+impl Cx {
+
+    pub (crate) fn render_view(
+        &mut self,
+        pass_id: PassId,
+        draw_list_id: DrawListId,
+        zbias: &mut f32,
+        zbias_step: f32,
+        target: &mut SoftTarget,
+    ) {
+        let draw_items_len = self.draw_lists[draw_list_id].draw_items.len();
+        self.draw_lists[draw_list_id].uniform_view_transform(&Mat4::identity());
+
+        for draw_item_id in 0..draw_items_len {
+            if let Some(sub_list_id) = self.draw_lists[draw_list_id].draw_items[draw_item_id].kind.sub_list() {
+                self.render_view(
+                    pass_id,
+                    sub_list_id,
+                    zbias,
+                    zbias_step,
+                    target,
+                );
+                continue;
+            }
+            let mut texture_ids = Vec::new();
+            {
+                let draw_item = &mut self.draw_lists[draw_list_id].draw_items[draw_item_id];
+                let draw_call = if let Some(draw_call) = draw_item.kind.draw_call_mut() {
+                    draw_call
+                }else {
+                    continue;
+                };
+                draw_call.instance_dirty = false;
+                draw_call.uniforms_dirty = false;
+                // update the zbias uniform if we have it.
+                draw_call.draw_uniforms.set_zbias(*zbias);
+                *zbias += zbias_step;
+                for slot in &draw_call.texture_slots {
+                    texture_ids.push(slot.as_ref().map( | texture | texture.texture_id()));
+                }
+            }
+            for texture_id in texture_ids.iter().flatten() {
+                let cxtexture = &mut self.textures[*texture_id];
+                if cxtexture.format.is_vec() {
+                    cxtexture.update_vec_texture();
+                }
+            }
+            self.render_draw_call(pass_id, draw_list_id, draw_item_id, &texture_ids, target);
+        }
+    }
+
+    fn render_draw_call(
+        &mut self,
+        pass_id: PassId,
+        draw_list_id: DrawListId,
+        draw_item_id: usize,
+        texture_ids: &[Option<crate::texture::TextureId>],
+        target: &mut SoftTarget,
+    ) {
+        let draw_list = &self.draw_lists[draw_list_id];
+        let draw_item = &draw_list.draw_items[draw_item_id];
+        let draw_call = if let Some(draw_call) = draw_item.kind.draw_call() {draw_call} else {return};
+        let sh = &self.draw_shaders.shaders[draw_call.draw_shader.draw_shader_id];
+        let Some(os_shader_id) = sh.os_shader_id else { // shader didnt compile somehow
+            return
+        };
+        let draw_shader_ptr = self.draw_shaders.os_shaders[os_shader_id].draw_shader_ptr;
+        let Some(draw_shader_def) = self.shader_registry.draw_shader_defs.get(&draw_shader_ptr) else {
+            return
+        };
+        let Some(shader) = CpuShader::new(&self.shader_registry, draw_shader_def) else {
+            return
+        };
+        let mapping = &sh.mapping;
+        let instances = draw_item.instances.as_ref().unwrap();
+        if mapping.instances.total_slots == 0 || instances.len() < mapping.instances.total_slots {
+            return
+        }
+        let Some(geometry_id) = draw_call.geometry_id else {
+            return
+        };
+        let geometry = &self.geometries[geometry_id];
+
+        // set up everything that stays the same for the whole drawcall
+        let mut base = shader.new_state();
+        write_inputs(&mut base, &soft_inputs(&shader, &mapping.pass_uniforms), self.passes[pass_id].pass_uniforms.as_slice());
+        write_inputs(&mut base, &soft_inputs(&shader, &mapping.view_uniforms), draw_list.draw_list_uniforms.as_slice());
+        write_inputs(&mut base, &soft_inputs(&shader, &mapping.draw_uniforms), draw_call.draw_uniforms.as_slice());
+        write_inputs(&mut base, &soft_inputs(&shader, &mapping.user_uniforms), &draw_call.user_uniforms);
+        for (index, input) in mapping.live_uniforms.inputs.iter().enumerate() {
+            if index < base.live_values.len() {
+                let end = (input.offset + input.slots).min(mapping.live_uniforms_buf.len());
+                base.live_values[index] = CpuValue::from_slots(&input.ty, &mapping.live_uniforms_buf[input.offset.min(end)..end]);
+            }
+        }
+        let mut textures = SoftTextures {slots: Vec::new()};
+        let mut texture_slot = 0;
+        for (index, field) in draw_shader_def.fields.iter().enumerate() {
+            if let DrawShaderFieldKind::Texture {..} = field.kind {
+                base.fields[index] = CpuValue::Texture(texture_slot);
+                let texture = texture_ids.get(texture_slot).cloned().flatten().map( | id | &self.textures[id]);
+                textures.slots.push(texture.map( | cxtexture | {
+                    let filter = match cxtexture.format {
+                        TextureFormat::VecMipBGRAu8_32 {..} => SoftFilter::Trilinear,
+                        _ if cxtexture.format.is_vec() => SoftFilter::Linear,
+                        _ => SoftFilter::Nearest
+                    };
+                    (&cxtexture.os.levels[..], filter)
+                }));
+                texture_slot += 1;
+            }
+        }
+
+        let geometry_inputs = soft_inputs(&shader, &mapping.geometries);
+        let instance_inputs = soft_inputs(&shader, &mapping.instances);
+        let varyings = shader.varying_fields();
+        let varying_tys: Vec<ShaderTy> = varyings.iter().map( | index | shader.field_ty(*index)).collect();
+        let geometry_slots = mapping.geometries.total_slots.max(1);
+        let vertex_count = geometry.vertices.len() / geometry_slots;
+
+        let (width, height) = (target.color.width, target.color.height);
+        let mut instance_state = base.clone();
+        let mut vertex_state = base.clone();
+        let mut pixel_state = base.clone();
+        let mut vertices: Vec<Option<SoftVertex >> = Vec::new();
+        let mut floats = Vec::new();
+        let mut varying_floats = Vec::new();
+
+        for instance in instances.chunks_exact(mapping.instances.total_slots) {
+            instance_state.fields.clone_from(&base.fields);
+            write_inputs(&mut instance_state, &instance_inputs, instance);
+
+            vertices.clear();
+            vertices.resize_with(vertex_count, || None);
+
+            for triangle in geometry.indices.chunks_exact(3) {
+                for index in triangle {
+                    let index = *index as usize;
+                    if index >= vertex_count || vertices[index].is_some() {
+                        continue;
+                    }
+                    vertex_state.fields.clone_from(&instance_state.fields);
+                    let start = index * geometry_slots;
+                    write_inputs(&mut vertex_state, &geometry_inputs, &geometry.vertices[start..start + geometry_slots]);
+                    let pos = shader.run_vertex(&mut vertex_state, &textures);
+                    let inv_w = if pos[3] != 0.0 {1.0 / pos[3]} else {0.0};
+                    floats.clear();
+                    for field in &varyings {
+                        vertex_state.fields[*field].push_floats(&mut floats);
+                    }
+                    vertices[index] = Some(SoftVertex {
+                        x: (pos[0] * inv_w + 1.0) * 0.5 * width as f32,
+                        y: (1.0 - pos[1] * inv_w) * 0.5 * height as f32,
+                        z: pos[2] * inv_w * 0.5 + 0.5,
+                        inv_w,
+                        attrs: floats.iter().map( | f | f.v * inv_w).collect(),
+                    });
+                }
+                let (Some(Some(v0)), Some(Some(v1)), Some(Some(v2))) = (
+                    vertices.get(triangle[0] as usize),
+                    vertices.get(triangle[1] as usize),
+                    vertices.get(triangle[2] as usize)
+                ) else {
+                    continue;
+                };
+                // no clipping against the near plane, just drop what is behind the camera
+                if v0.inv_w <= 0.0 || v1.inv_w <= 0.0 || v2.inv_w <= 0.0 {
+                    continue;
+                }
+                let area = edge(v0.x, v0.y, v1.x, v1.y, v2.x, v2.y);
+                if area == 0.0 || !area.is_finite() {
+                    continue;
+                }
+                let sign = area.signum();
+                let min_x = v0.x.min(v1.x).min(v2.x).floor().max(0.0) as usize;
+                let min_y = v0.y.min(v1.y).min(v2.y).floor().max(0.0) as usize;
+                let max_x = (v0.x.max(v1.x).max(v2.x).ceil() as isize).min(width as isize - 1);
+                let max_y = (v0.y.max(v1.y).max(v2.y).ceil() as isize).min(height as isize - 1);
+                if max_x < 0 || max_y < 0 {
+                    continue;
+                }
+
+                // the edge functions are affine, so their steps per pixel are constant
+                let edges = [(v1, v2), (v2, v0), (v0, v1)];
+                let steps: Vec<(f32, f32)> = edges.iter().map( | (a, b) | {
+                    (-(b.y - a.y) * sign, (b.x - a.x) * sign)
+                }).collect();
+                let inv_area = 1.0 / area.abs();
+
+                for py in min_y..=max_y as usize {
+                    for px in min_x..=max_x as usize {
+                        let (cx, cy) = (px as f32 + 0.5, py as f32 + 0.5);
+                        let mut l = [0.0f32; 3];
+                        let mut inside = true;
+                        for (i, (a, b)) in edges.iter().enumerate() {
+                            let w = edge(a.x, a.y, b.x, b.y, cx, cy) * sign;
+                            // top-left style tie breaking, so shared edges are only drawn once
+                            let (sx, sy) = steps[i];
+                            if w < 0.0 || (w == 0.0 && !(sx > 0.0 || (sx == 0.0 && sy > 0.0))) {
+                                inside = false;
+                                break;
+                            }
+                            l[i] = w * inv_area;
+                        }
+                        if !inside {
+                            continue;
+                        }
+                        let z = l[0] * v0.z + l[1] * v1.z + l[2] * v2.z;
+                        if !(0.0..=1.0).contains(&z) {
+                            continue;
+                        }
+                        let pixel_index = py * width + px;
+                        if let Some(depth) = &target.depth {
+                            if z > depth[pixel_index] {
+                                continue;
+                            }
+                        }
+
+                        // interpolate perspective correct at this pixel and its right and lower neighbour
+                        let dl_x = [steps[0].0 * inv_area, steps[1].0 * inv_area, steps[2].0 * inv_area];
+                        let dl_y = [steps[0].1 * inv_area, steps[1].1 * inv_area, steps[2].1 * inv_area];
+                        let lx = [l[0] + dl_x[0], l[1] + dl_x[1], l[2] + dl_x[2]];
+                        let ly = [l[0] + dl_y[0], l[1] + dl_y[1], l[2] + dl_y[2]];
+                        let inv = | l: &[f32; 3] | {
+                            let w = l[0] * v0.inv_w + l[1] * v1.inv_w + l[2] * v2.inv_w;
+                            if w != 0.0 {1.0 / w} else {0.0}
+                        };
+                        let (w, wx, wy) = (inv(&l), inv(&lx), inv(&ly));
+                        varying_floats.clear();
+                        for k in 0..v0.attrs.len() {
+                            let at = | l: &[f32; 3], w: f32 | (l[0] * v0.attrs[k] + l[1] * v1.attrs[k] + l[2] * v2.attrs[k]) * w;
+                            let v = at(&l, w);
+                            // glsl derivatives point up on the y axis
+                            varying_floats.push(CpuFloat {
+                                v,
+                                dx: at(&lx, wx) - v,
+                                dy: v - at(&ly, wy),
+                            });
+                        }
+                        pixel_state.fields.clone_from(&base.fields);
+                        let mut offset = 0;
+                        for (field, ty) in varyings.iter().zip(varying_tys.iter()) {
+                            let slots = ty.slots();
+                            let end = (offset + slots).min(varying_floats.len());
+                            pixel_state.fields[*field] = CpuValue::from_floats(ty, &varying_floats[offset.min(end)..end]);
+                            offset += slots;
+                        }
+                        let color = shader.run_pixel(&mut pixel_state, &textures);
+                        if let Some(depth) = &mut target.depth {
+                            depth[pixel_index] = z;
+                        }
+                        target.blend(pixel_index, color);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn setup_render_pass(&mut self, pass_id: PassId,) -> Option<DVec2> {
+
+        let dpi_factor = self.passes[pass_id].dpi_factor.unwrap();
+        let pass_rect = self.get_pass_rect(pass_id, dpi_factor).unwrap();
+        self.passes[pass_id].paint_dirty = false;
+
+        if pass_rect.size.x <0.5 || pass_rect.size.y < 0.5 {
+            return None
+        }
+
+        self.passes[pass_id].set_matrix(pass_rect.pos, pass_rect.size);
+        self.passes[pass_id].set_dpi_factor(dpi_factor);
+        Some(pass_rect.size)
+    }
+
+    // draws a window pass into the surface on the pass, and returns it as an image
+    pub (crate) fn draw_pass_to_image(&mut self, pass_id: PassId) -> Option<ImageBuffer> {
+        let draw_list_id = self.passes[pass_id].main_draw_list_id.unwrap();
+        let pass_size = self.setup_render_pass(pass_id) ?;
+        let dpi_factor = self.passes[pass_id].dpi_factor.unwrap();
+        let size = pass_size * dpi_factor;
+        let (width, height) = (size.x as usize, size.y as usize);
+
+        let clear_color = if self.passes[pass_id].color_textures.len() == 0 {
+            self.passes[pass_id].clear_color
+        }
+        else {
+            match self.passes[pass_id].color_textures[0].clear_color {
+                PassClearColor::InitWith(color) => color,
+                PassClearColor::ClearWith(color) => color
+            }
+        };
+        let clear_depth = match self.passes[pass_id].clear_depth {
+            PassClearDepth::InitWith(depth) => depth,
+            PassClearDepth::ClearWith(depth) => depth
+        };
+
+        let mut surface = mem::take(&mut self.passes[pass_id].os.surface);
+        let mut depth = mem::take(&mut self.passes[pass_id].os.depth);
+        let resized = surface.resize(width, height, true);
+        if resized || !self.passes[pass_id].dont_clear {
+            surface.clear(clear_color);
+            depth.clear();
+            depth.resize(width * height, clear_depth as f32);
+        }
+
+        let mut zbias = 0.0;
+        let zbias_step = self.passes[pass_id].zbias_step;
+        self.render_view(
+            pass_id,
+            draw_list_id,
+            &mut zbias,
+            zbias_step,
+            &mut SoftTarget {color: &mut surface, depth: Some(&mut depth)},
+        );
+
+        let image = surface.to_image_buffer();
+        self.passes[pass_id].os.surface = surface;
+        self.passes[pass_id].os.depth = depth;
+        Some(image)
+    }
+
+    pub fn draw_pass_to_texture(&mut self, pass_id: PassId, texture: &Texture) {
+        self.draw_pass_to_texture_inner(pass_id, Some(texture))
+    }
+
+    pub fn draw_pass_to_magic_texture(&mut self, pass_id: PassId) {
+        self.draw_pass_to_texture_inner(pass_id, None)
+    }
+
+    fn draw_pass_to_texture_inner(
+        &mut self,
+        pass_id: PassId,
+        maybe_texture: Option<&Texture>,
+    ) {
+        let draw_list_id = self.passes[pass_id].main_draw_list_id.unwrap();
+
+        let pass_size = if let Some(pz) = self.setup_render_pass(pass_id) {
+            pz
+        }
+        else {
+            return
+        };
+        let dpi_factor = self.passes[pass_id].dpi_factor.unwrap();
+        let size = dpi_factor * pass_size;
+        let (width, height) = (size.x as usize, size.y as usize);
+
+        let color_texture = if let Some(texture) = maybe_texture {
+            Some((texture.clone(), PassClearColor::ClearWith(self.passes[pass_id].clear_color)))
+        }
+        else {
+            self.passes[pass_id].color_textures.first().map( | ct | (ct.texture.clone(), ct.clear_color.clone()))
+        };
+
+        // only the first color attachment gets written, like gl_FragColor
+        let mut surface = SoftSurface::default();
+        let mut color_texture_id = None;
+        if let Some((texture, clear_color)) = color_texture {
+            let cxtexture = &mut self.textures[texture.texture_id()];
+            cxtexture.update_render_target(width, height);
+            let clear = match clear_color {
+                PassClearColor::InitWith(color) => if cxtexture.check_initial() {Some(color)} else {None},
+                PassClearColor::ClearWith(color) => Some(color)
+            };
+            surface = cxtexture.os.levels.pop().unwrap_or_default();
+            if let Some(color) = clear {
+                surface.clear(color);
+            }
+            color_texture_id = Some(texture.texture_id());
+        }
+        else {
+            surface.resize(width, height, true);
+        }
+
+        // attach/clear depth buffers, if any
+        let mut depth = None;
+        if let Some(depth_texture) = self.passes[pass_id].depth_texture.clone() {
+            let cxtexture = &mut self.textures[depth_texture.texture_id()];
+            cxtexture.update_depth_stencil(width, height);
+            let clear = match self.passes[pass_id].clear_depth {
+                PassClearDepth::InitWith(depth) => if cxtexture.check_initial() {Some(depth)} else {None},
+                PassClearDepth::ClearWith(depth) => Some(depth)
+            };
+            let mut values = mem::take(&mut cxtexture.os.depth);
+            if let Some(clear) = clear {
+                values.iter_mut().for_each( | v | *v = clear as f32);
+            }
+            depth = Some((depth_texture.texture_id(), values));
+        }
+
+        let mut zbias = 0.0;
+        let zbias_step = self.passes[pass_id].zbias_step;
+        self.render_view(
+            pass_id,
+            draw_list_id,
+            &mut zbias,
+            zbias_step,
+            &mut SoftTarget {
+                color: &mut surface,
+                depth: depth.as_mut().map( | (_, values) | values),
+            },
+        );
+
+        if let Some(texture_id) = color_texture_id {
+            self.textures[texture_id].os.levels.push(surface);
+        }
+        if let Some((texture_id, values)) = depth {
+            self.textures[texture_id].os.depth = values;
+        }
+    }
+
+    pub fn headless_compile_shaders(&mut self) {
+        for draw_shader_ptr in &self.draw_shaders.compile_set {
+            if let Some(item) = self.draw_shaders.ptr_to_item.get(&draw_shader_ptr) {
+                let cx_shader = &mut self.draw_shaders.shaders[item.draw_shader_id];
+                let draw_shader_def = self.shader_registry.draw_shader_defs.get(&draw_shader_ptr);
+                // the shader is interpreted directly from its definition
+                if CpuShader::new(&self.shader_registry, draw_shader_def.as_ref().unwrap()).is_none() {
+                    continue;
+                }
+                if cx_shader.mapping.flags.debug {
+                    crate::log!("headless: interpreting draw shader {:?}", draw_shader_ptr);
+                }
+                cx_shader.os_shader_id = self.draw_shaders.os_shaders.iter().position( | ds | ds.draw_shader_ptr == *draw_shader_ptr);
+                if cx_shader.os_shader_id.is_none() {
+                    cx_shader.os_shader_id = Some(self.draw_shaders.os_shaders.len());
+                    self.draw_shaders.os_shaders.push(CxOsDrawShader {draw_shader_ptr: *draw_shader_ptr});
+                }
+            }
+        }
+        self.draw_shaders.compile_set.clear();
+    }
+}
+
+impl CxTexture {
+
+    pub fn update_vec_texture(&mut self) {
+        if !self.alloc_vec() && !self.check_updated() && !self.os.levels.is_empty() {
+            return
+        }
+        let mut base = SoftSurface::default();
+        let unpack = | v: u32 | [
+            ((v >> 16) & 0xff) as f32 / 255.0,
+            ((v >> 8) & 0xff) as f32 / 255.0,
+            (v & 0xff) as f32 / 255.0,
+            ((v >> 24) & 0xff) as f32 / 255.0,
+        ];
+        let mut max_level = Some(0);
+        match &self.format {
+            TextureFormat::VecBGRAu8_32 {width, height, data} => {
+                base.resize(*width, *height, true);
+                for (p, v) in base.pixels.iter_mut().zip(data.iter()) {*p = unpack(*v)}
+            }
+            TextureFormat::VecMipBGRAu8_32 {width, height, data, max_level: level} => {
+                base.resize(*width, *height, true);
+                for (p, v) in base.pixels.iter_mut().zip(data.iter()) {*p = unpack(*v)}
+                max_level = *level;
+            }
+            TextureFormat::VecRGBAf32 {width, height, data} => {
+                base.resize(*width, *height, false);
+                for (p, v) in base.pixels.iter_mut().zip(data.chunks_exact(4)) {*p = [v[0], v[1], v[2], v[3]]}
+            }
+            TextureFormat::VecRu8 {width, height, data, unpack_row_length} => {
+                base.resize(*width, *height, true);
+                let stride = unpack_row_length.unwrap_or(*width);
+                for y in 0..*height {
+                    for x in 0..*width {
+                        let r = data.get(y * stride + x).cloned().unwrap_or(0) as f32 / 255.0;
+                        base.pixels[y * width + x] = [r, 0.0, 0.0, 1.0];
+                    }
+                }
+            }
+            TextureFormat::VecRGu8 {width, height, data, unpack_row_length} => {
+                base.resize(*width, *height, true);
+                let stride = unpack_row_length.unwrap_or(*width);
+                for y in 0..*height {
+                    for x in 0..*width {
+                        let i = (y * stride + x) * 2;
+                        let r = data.get(i).cloned().unwrap_or(0) as f32 / 255.0;
+                        let g = data.get(i + 1).cloned().unwrap_or(0) as f32 / 255.0;
+                        base.pixels[y * width + x] = [r, g, 0.0, 1.0];
+                    }
+                }
+            }
+            TextureFormat::VecRf32 {width, height, data} => {
+                base.resize(*width, *height, false);
+                for (p, v) in base.pixels.iter_mut().zip(data.iter()) {*p = [*v, 0.0, 0.0, 1.0]}
+            }
+            _ => ()
+        }
+        self.os.levels.clear();
+        let max_level = max_level.unwrap_or(usize::MAX);
+        while self.os.levels.len() < max_level && base.width > 1 && base.height > 1 && !base.pixels.is_empty() {
+            let next = base.downsample();
+            self.os.levels.push(mem::replace(&mut base, next));
+        }
+        self.os.levels.push(base);
+    }
+
+    pub fn update_render_target(&mut self, width: usize, height: usize) {
+        let clamp = matches!(self.format, TextureFormat::RenderBGRAu8 {..});
+        self.alloc_render(width, height);
+        if self.os.levels.len() != 1 {
+            self.os.levels = vec![SoftSurface::default()];
+        }
+        self.os.levels[0].resize(width, height, clamp);
+    }
+
+    fn update_depth_stencil(&mut self, width: usize, height: usize) {
+        if self.alloc_depth(width, height) || self.os.depth.len() != width * height {
+            self.os.depth = vec![1.0; width * height];
+        }
+    }
+}
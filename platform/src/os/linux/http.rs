@@ -0,0 +1,376 @@
+use {
+    std::net::{TcpStream, ToSocketAddrs},
+    std::io::{Read, Write},
+    std::sync::mpsc::Sender,
+    std::time::{Duration, Instant},
+    std::collections::BTreeMap,
+    crate::{
+        makepad_live_id::*,
+        thread::SignalToUI,
+        event::{
+            HttpRequest,
+            HttpResponse,
+            NetworkResponse,
+            NetworkResponseItem,
+        },
+    }
+};
+
+const HTTP_MAX_REDIRECTS: usize = 10;
+const HTTP_READ_BUFFER_SIZE: usize = 65536;
+
+#[derive(Clone, Copy, Debug)]
+pub struct HttpTimeouts {
+    pub connect: Duration,
+    /// How long a single read or write may block
+    pub io: Duration,
+    /// How long the whole request may take, redirects included
+    pub total: Duration,
+}
+
+impl Default for HttpTimeouts {
+    fn default() -> Self {
+        Self {
+            connect: Duration::from_secs(10),
+            io: Duration::from_secs(30),
+            total: Duration::from_secs(300),
+        }
+    }
+}
+
+// a minimal blocking HTTP/1.1 client, each request runs on its own thread
+pub fn make_http_request(request_id: LiveId, request: HttpRequest, networking_sender: Sender<NetworkResponseItem>) {
+    make_http_request_with_timeouts(request_id, request, networking_sender, HttpTimeouts::default())
+}
+
+pub fn make_http_request_with_timeouts(request_id: LiveId, request: HttpRequest, networking_sender: Sender<NetworkResponseItem>, timeouts: HttpTimeouts) {
+    std::thread::spawn(move || {
+        let response = match http_request_with_redirects(request_id, request, &networking_sender, timeouts) {
+            Ok(response) => NetworkResponse::HttpResponse(response),
+            Err(err) => NetworkResponse::HttpRequestError(err),
+        };
+        let _ = networking_sender.send(NetworkResponseItem {
+            request_id,
+            response
+        });
+        SignalToUI::set_ui_signal();
+    });
+}
+
+struct HttpTarget {
+    host: String,
+    port: u16,
+    path: String,
+}
+
+impl HttpTarget {
+    fn from_url(url: &str) -> Result<Self, String> {
+        let (proto, rest) = url.split_once("://").unwrap_or(("http", url));
+        match proto {
+            "http" => (),
+            "https" => return Err(format!("HttpRequest https is not supported on this platform: {}", url)),
+            _ => return Err(format!("HttpRequest unsupported protocol {}", proto))
+        }
+        // strip the fragment, its never sent to the server
+        let rest = rest.split_once('#').map(|(rest, _)| rest).unwrap_or(rest);
+        let (authority, path) = match rest.find(|c| c == '/' || c == '?') {
+            Some(pos) => (&rest[0..pos], &rest[pos..]),
+            None => (rest, "/")
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => {
+                (host, port.parse().map_err(|_| format!("HttpRequest invalid port in url {}", url))?)
+            }
+            _ => (authority, 80)
+        };
+        if host.is_empty() {
+            return Err(format!("HttpRequest no host in url {}", url))
+        }
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: if path.starts_with('/') {path.to_string()} else {format!("/{}", path)}
+        })
+    }
+
+    fn host_header(&self) -> String {
+        if self.port == 80 {self.host.clone()} else {format!("{}:{}", self.host, self.port)}
+    }
+
+    fn resolve_location(&self, location: &str) -> String {
+        if location.contains("://") {
+            location.to_string()
+        }
+        else if let Some(location) = location.strip_prefix("//") {
+            format!("http://{}", location)
+        }
+        else if location.starts_with('/') {
+            format!("http://{}{}", self.host_header(), location)
+        }
+        else {
+            let path = self.path.split_once('?').map(|(path, _)| path).unwrap_or(&self.path);
+            let dir = path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+            format!("http://{}{}/{}", self.host_header(), dir, location)
+        }
+    }
+}
+
+fn http_request_with_redirects(request_id: LiveId, request: HttpRequest, networking_sender: &Sender<NetworkResponseItem>, timeouts: HttpTimeouts) -> Result<HttpResponse, String> {
+    let deadline = Instant::now() + timeouts.total;
+    let mut url = request.url.clone();
+    let mut method = request.method.to_string();
+    let mut body = request.body;
+    for _ in 0..=HTTP_MAX_REDIRECTS {
+        let target = HttpTarget::from_url(&url)?;
+        let response = http_request_once(
+            request_id,
+            request.metadata_id,
+            &target,
+            method,
+            &request.headers,
+            body.as_deref(),
+            timeouts,
+            deadline,
+            networking_sender
+        )?;
+        let location = match response.status_code {
+            301 | 302 | 303 | 307 | 308 => response.headers.iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case("location"))
+                .and_then(|(_, values)| values.first().cloned()),
+            _ => None
+        };
+        let Some(location) = location else {
+            return Ok(response)
+        };
+        // 303 always switches to GET, 301/302 do so for POST like all browsers do
+        if response.status_code == 303 || (method == "POST" && response.status_code <= 302) {
+            if method != "HEAD" {
+                method = "GET";
+            }
+            body = None;
+        }
+        url = target.resolve_location(location.trim());
+    }
+    Err(format!("HttpRequest too many redirects for {}", request.url))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn http_request_once(
+    request_id: LiveId,
+    metadata_id: LiveId,
+    target: &HttpTarget,
+    method: &str,
+    headers: &BTreeMap<String, Vec<String>>,
+    body: Option<&[u8]>,
+    timeouts: HttpTimeouts,
+    deadline: Instant,
+    networking_sender: &Sender<NetworkResponseItem>
+) -> Result<HttpResponse, String> {
+    let host = target.host.trim_start_matches('[').trim_end_matches(']');
+    let addrs = (host, target.port).to_socket_addrs()
+        .map_err(|e| format!("HttpRequest cannot resolve {}: {}", target.host, e))?;
+    let mut last_err = format!("HttpRequest no addresses found for {}", target.host);
+    let mut stream = None;
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, timeouts.connect) {
+            Ok(s) => {
+                stream = Some(s);
+                break;
+            }
+            Err(e) => last_err = format!("HttpRequest cannot connect to {}: {}", addr, e)
+        }
+    }
+    let mut stream = stream.ok_or(last_err)?;
+    let _ = stream.set_write_timeout(Some(timeouts.io));
+    let _ = stream.set_nodelay(true);
+
+    let mut head = format!("{} {} HTTP/1.1\r\nHost: {}\r\n", method, target.path, target.host_header());
+    let has_header = | name: &str | headers.keys().any(|key| key.eq_ignore_ascii_case(name));
+    for (key, values) in headers {
+        // we always close the connection after a response, so we own these headers
+        if key.eq_ignore_ascii_case("host") || key.eq_ignore_ascii_case("content-length") || key.eq_ignore_ascii_case("connection") {
+            continue;
+        }
+        head.push_str(&format!("{}: {}\r\n", key, values.join(",")));
+    }
+    if let Some(body) = body {
+        head.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+    head.push_str("Connection: close\r\n");
+    if !has_header("user-agent") {
+        head.push_str("User-Agent: makepad\r\n");
+    }
+    head.push_str("\r\n");
+
+    stream.write_all(head.as_bytes()).map_err(|e| format!("HttpRequest error writing request: {}", e))?;
+    if let Some(body) = body {
+        stream.write_all(body).map_err(|e| format!("HttpRequest error writing body: {}", e))?;
+    }
+
+    let mut reader = HttpReader {
+        stream,
+        io_timeout: timeouts.io,
+        deadline,
+        buffer: Vec::new(),
+        eof: false,
+    };
+
+    // read the statusline and headers
+    let header_end = loop {
+        if let Some(pos) = reader.buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if reader.eof {
+            return Err("HttpRequest connection closed before response headers".to_string())
+        }
+        reader.fill()?;
+    };
+    let header_str = String::from_utf8_lossy(&reader.buffer[0..header_end]).to_string();
+    reader.buffer.drain(0..header_end + 4);
+
+    let mut lines = header_str.split("\r\n");
+    let status_line = lines.next().unwrap_or("");
+    let status_code: u16 = status_line.split(' ').nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| format!("HttpRequest invalid status line: {}", status_line))?;
+
+    let mut response = HttpResponse::new(metadata_id, status_code, String::new(), None);
+    for line in lines {
+        if let Some((key, value)) = line.split_once(':') {
+            response.set_header(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+    let header_value = | name: &str | response.headers.iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .and_then(|(_, values)| values.last().cloned());
+
+    let is_chunked = header_value("transfer-encoding")
+        .map(|v| v.to_ascii_lowercase().contains("chunked")).unwrap_or(false);
+    let content_length: Option<u64> = header_value("content-length")
+        .and_then(|v| v.trim().parse().ok());
+    let has_body = method != "HEAD" && status_code != 204 && status_code != 304 && !(100..200).contains(&status_code);
+
+    let progress = | loaded: u64 | {
+        let _ = networking_sender.send(NetworkResponseItem {
+            request_id,
+            response: NetworkResponse::HttpProgress {
+                loaded,
+                total: content_length.unwrap_or(0)
+            }
+        });
+        SignalToUI::set_ui_signal();
+    };
+
+    let mut body = Vec::new();
+    if has_body {
+        if is_chunked {
+            loop {
+                let line = reader.read_line()?;
+                let size_str = line.split(';').next().unwrap_or("").trim();
+                let size = usize::from_str_radix(size_str, 16)
+                    .map_err(|_| format!("HttpRequest invalid chunk size: {}", size_str))?;
+                if size == 0 {
+                    // skip the trailers
+                    while !reader.read_line()?.is_empty() {}
+                    break;
+                }
+                reader.read_exact_into(size, &mut body)?;
+                if !reader.read_line()?.is_empty() {
+                    return Err("HttpRequest malformed chunk terminator".to_string())
+                }
+                progress(body.len() as u64);
+            }
+        }
+        else if let Some(content_length) = content_length {
+            while (body.len() as u64) < content_length {
+                if reader.buffer.is_empty() {
+                    if reader.eof {
+                        return Err("HttpRequest connection closed before end of body".to_string())
+                    }
+                    reader.fill()?;
+                }
+                let take = reader.buffer.len().min((content_length - body.len() as u64) as usize);
+                body.extend(reader.buffer.drain(0..take));
+                progress(body.len() as u64);
+            }
+        }
+        else {
+            // no length given, the body runs until the connection closes
+            loop {
+                body.append(&mut reader.buffer);
+                if reader.eof {
+                    break;
+                }
+                reader.fill()?;
+                progress((body.len() + reader.buffer.len()) as u64);
+            }
+        }
+    }
+    response.body = Some(body);
+    Ok(response)
+}
+
+struct HttpReader {
+    stream: TcpStream,
+    io_timeout: Duration,
+    deadline: Instant,
+    buffer: Vec<u8>,
+    eof: bool,
+}
+
+impl HttpReader {
+    fn fill(&mut self) -> Result<(), String> {
+        // a read never blocks past the deadline of the whole request
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err("HttpRequest timed out".to_string())
+        }
+        let _ = self.stream.set_read_timeout(Some(left.min(self.io_timeout)));
+        let mut data = [0u8; HTTP_READ_BUFFER_SIZE];
+        match self.stream.read(&mut data) {
+            Ok(0) => {
+                self.eof = true;
+                Ok(())
+            }
+            Ok(n) => {
+                self.buffer.extend_from_slice(&data[0..n]);
+                Ok(())
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock || e.kind() == std::io::ErrorKind::TimedOut => {
+                Err("HttpRequest timed out".to_string())
+            }
+            Err(e) => Err(format!("HttpRequest error reading response: {}", e))
+        }
+    }
+
+    fn read_line(&mut self) -> Result<String, String> {
+        loop {
+            if let Some(pos) = self.buffer.windows(2).position(|w| w == b"\r\n") {
+                let line = String::from_utf8_lossy(&self.buffer[0..pos]).to_string();
+                self.buffer.drain(0..pos + 2);
+                return Ok(line)
+            }
+            if self.eof {
+                return Err("HttpRequest connection closed inside chunked body".to_string())
+            }
+            self.fill()?;
+        }
+    }
+
+    fn read_exact_into(&mut self, size: usize, out: &mut Vec<u8>) -> Result<(), String> {
+        let mut left = size;
+        while left > 0 {
+            if self.buffer.is_empty() {
+                if self.eof {
+                    return Err("HttpRequest connection closed inside chunked body".to_string())
+                }
+                self.fill()?;
+            }
+            let take = self.buffer.len().min(left);
+            out.extend(self.buffer.drain(0..take));
+            left -= take;
+        }
+        Ok(())
+    }
+}
@@ -1,13 +1,17 @@
-#[cfg(not(any(linux_direct, target_os="android")))]
+#[cfg(not(any(linux_direct, linux_headless, target_os="android")))]
 pub mod x11; 
 
 #[cfg(linux_direct)]
 pub mod direct;
 
+#[cfg(linux_headless)]
+pub mod headless;
+
 pub mod egl_sys;
 
 pub mod gl_sys;
 pub mod libc_sys;
+#[cfg(not(linux_headless))]
 pub mod opengl;
 
 #[cfg(not(target_os="android"))]
@@ -30,8 +34,15 @@ pub mod pulse_audio;
 #[cfg(not(target_os="android"))]
 pub mod pulse_sys;
 
+#[cfg(not(any(linux_direct, linux_headless, target_os="android")))]
+pub mod dbus_sys;
+#[cfg(not(any(linux_direct, linux_headless, target_os="android")))]
+pub mod atspi;
+
 #[cfg(not(target_os="android"))]
 mod web_socket;
+#[cfg(not(target_os="android"))]
+pub mod http;
 
 #[cfg(target_os="android")]
 pub mod android;
@@ -39,13 +50,20 @@ pub mod android;
 #[cfg(target_os="android")]
 pub(crate) use self::android::android::CxOs;
 
-#[cfg(not(any(linux_direct, target_os="android")))]
+#[cfg(not(any(linux_direct, linux_headless, target_os="android")))]
 pub(crate) use self::x11::linux_x11::*;
 
 
 #[cfg(linux_direct)]
 pub(crate) use self::direct::linux_direct::*;
 
+#[cfg(linux_headless)]
+pub(crate) use self::headless::{linux_headless::*, software::*};
+
+#[cfg(linux_headless)]
+pub use self::headless::{linux_headless::HeadlessFrame, headless_event::HeadlessEvent};
+
+#[cfg(not(linux_headless))]
 pub(crate) use self::opengl::*;
 
 #[cfg(not(target_os="android"))]
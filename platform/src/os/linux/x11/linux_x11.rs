@@ -2,6 +2,7 @@ use {
     std::cell::RefCell,
     std::time::Instant,
     std::rc::Rc,
+    std::sync::mpsc::{Receiver, TryRecvError},
     self::super::opengl_x11::{
         OpenglWindow,
         OpenglCx
@@ -11,14 +12,16 @@ use {
         x11::xlib_event::*,
         x11::xlib_app::*,
         x11::x11_sys,
-        linux_media::CxLinuxMedia
+        linux_media::CxLinuxMedia,
+        atspi::AtSpiBridge,
+        http::make_http_request,
     },
     crate::{
         cx_api::{CxOsOp, CxOsApi}, 
         makepad_math::dvec2,
         makepad_live_id::*,
         thread::SignalToUI,
-        event::Event,
+        event::{Event, NetworkResponseChannel},
         pass::CxPassParent,
         cx::{Cx, OsType,LinuxWindowParams}, 
         os::cx_stdin::{PollTimers},
@@ -65,6 +68,7 @@ impl Cx {
             return cx.borrow_mut().stdin_event_loop();
         }
         
+        cx.borrow_mut().os.atspi_connect = Some(AtSpiBridge::connect_in_background());
         cx.borrow_mut().call_event_handler(&Event::Startup);
         cx.borrow_mut().redraw_all();
         get_xlib_app_global().start_timer(0,0.008,true);
@@ -204,6 +208,8 @@ impl Cx {
                         self.handle_media_signals();
                         self.call_event_handler(&Event::Signal);
                     }
+                    self.handle_networking_events();
+                    self.handle_accessibility();
                 }
                 else{
                     self.call_event_handler(&Event::Timer(e))
@@ -220,6 +226,33 @@ impl Cx {
     }
 
     pub(crate) fn handle_networking_events(&mut self) {
+        let mut out = Vec::new();
+        while let Ok(event) = self.os.network_response.receiver.try_recv() {
+            out.push(event);
+        }
+        if out.len()>0 {
+            self.call_event_handler(&Event::NetworkResponses(out))
+        }
+    }
+    
+    fn handle_accessibility(&mut self) {
+        if let Some(atspi_connect) = &self.os.atspi_connect {
+            match atspi_connect.try_recv() {
+                Ok(atspi) => {
+                    self.os.atspi = atspi;
+                    self.os.atspi_connect = None;
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => self.os.atspi_connect = None,
+            }
+        }
+        // the trees that changed before we were connected are still in the changed list
+        if let Some(atspi) = &mut self.os.atspi {
+            for window_id in self.accessibility.take_changed() {
+                atspi.set_tree(window_id, self.accessibility.tree(window_id).cloned());
+            }
+            atspi.poll(&self.windows);
+        }
     }
     
     pub (crate) fn handle_repaint(&mut self, opengl_windows: &mut Vec<OpenglWindow>) {
@@ -336,8 +369,8 @@ impl Cx {
                 },
                 CxOsOp::UpdateMacosMenu(_menu) => {
                 },
-                CxOsOp::HttpRequest{request_id:_, request:_} => {
-                    todo!()
+                CxOsOp::HttpRequest{request_id, request} => {
+                    make_http_request(request_id, request, self.os.network_response.sender.clone());
                 },
                 CxOsOp::PrepareVideoPlayback(_, _, _, _, _) => todo!(),
                 CxOsOp::BeginVideoPlayback(_) => todo!(),
@@ -380,7 +413,11 @@ pub struct CxOs {
     pub(crate) media: CxLinuxMedia,
     pub (crate) stdin_timers: PollTimers,
     pub (crate) start_time: Option<Instant>,
+    pub (crate) network_response: NetworkResponseChannel,
     // HACK(eddyb) generalize this to EGL, properly.
     pub(super) opengl_cx: Option<OpenglCx>,
+    pub(super) atspi: Option<AtSpiBridge>,
+    // the bridge while it's still connecting
+    pub(super) atspi_connect: Option<Receiver<Option<AtSpiBridge>>>,
 }
 
@@ -19,6 +19,7 @@ use {
         cx_api::CxOsOp,
         cx::Cx,
         gl_sys,
+        os::linux::http::make_http_request,
     } 
 };
 
@@ -282,6 +283,9 @@ impl Cx {
                 CxOsOp::StopTimer(timer_id) => {
                     self.os.stdin_timers.timers.remove(&timer_id);
                 },
+                CxOsOp::HttpRequest{request_id, request} => {
+                    make_http_request(request_id, request, self.os.network_response.sender.clone());
+                },
                 _ => ()
                 /*
                 CxOsOp::CloseWindow(_window_id) => {},
@@ -0,0 +1,235 @@
+// Tests for the linux HTTP client against a server on a loopback socket.
+#![cfg(target_os = "linux")]
+
+use makepad_platform::{
+    event::{HttpMethod, HttpRequest, HttpResponse, NetworkResponse, NetworkResponseItem},
+    os::linux::http::{make_http_request, make_http_request_with_timeouts, HttpTimeouts},
+    LiveId,
+};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+// the request head and body, the body is read for as long as Content-Length says
+fn read_request(stream: &mut TcpStream) -> String {
+    let mut data = Vec::new();
+    let mut byte = [0u8];
+    while !data.ends_with(b"\r\n\r\n") {
+        if stream.read(&mut byte).unwrap_or(0) == 0 {
+            break
+        }
+        data.push(byte[0]);
+    }
+    let head = String::from_utf8(data).unwrap();
+    let len = head.lines()
+        .find_map( | line | line.strip_prefix("Content-Length: "))
+        .map_or(0, | len | len.parse().unwrap());
+    let mut body = vec![0u8; len];
+    stream.read_exact(&mut body).unwrap();
+    head + std::str::from_utf8(&body).unwrap()
+}
+
+// answers every connection with `respond`, and hands back the requests it got
+fn start_server(respond: impl Fn(&str, &mut TcpStream) + Send + 'static) -> (String, Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let request = read_request(&mut stream);
+            respond(&request, &mut stream);
+            let _ = tx.send(request);
+        }
+    });
+    (url, rx)
+}
+
+// runs a request and returns its progress and final response
+fn fetch(request: HttpRequest, timeouts: Option<HttpTimeouts>) -> (Vec<u64>, Result<HttpResponse, String>) {
+    let (tx, rx) = mpsc::channel::<NetworkResponseItem>();
+    match timeouts {
+        Some(timeouts) => make_http_request_with_timeouts(LiveId(1), request, tx, timeouts),
+        None => make_http_request(LiveId(1), request, tx),
+    }
+    let mut progress = Vec::new();
+    loop {
+        let item = rx.recv_timeout(Duration::from_secs(10)).expect("no response");
+        assert_eq!(item.request_id, LiveId(1));
+        match item.response {
+            NetworkResponse::HttpProgress {loaded, ..} => progress.push(loaded),
+            NetworkResponse::HttpResponse(response) => return (progress, Ok(response)),
+            NetworkResponse::HttpRequestError(err) => return (progress, Err(err)),
+        }
+    }
+}
+
+fn get(url: &str) -> Result<HttpResponse, String> {
+    fetch(HttpRequest::new(url.to_string(), HttpMethod::GET), None).1
+}
+
+fn header<'a>(response: &'a HttpResponse, name: &str) -> Option<&'a str> {
+    response.headers.get(name).map( | values | values[0].as_str())
+}
+
+#[test]
+fn reads_a_response_with_content_length() {
+    let (url, requests) = start_server( | _, stream | {
+        stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 11\r\nX-Test: a b\r\n\r\nhello world").unwrap();
+    });
+    let mut request = HttpRequest::new(format!("{}/path?q=1#fragment", url), HttpMethod::GET);
+    request.set_header("Accept".to_string(), "text/plain".to_string());
+    let (progress, response) = fetch(request, None);
+    let response = response.unwrap();
+    assert_eq!(response.status_code, 200);
+    assert_eq!(header(&response, "X-Test"), Some("a b"));
+    assert_eq!(response.get_string_body().as_deref(), Some("hello world"));
+    assert_eq!(progress.last(), Some(&11));
+
+    let request = requests.recv().unwrap();
+    let host = url.strip_prefix("http://").unwrap();
+    assert!(request.starts_with("GET /path?q=1 HTTP/1.1\r\n"));
+    assert!(request.contains(&format!("Host: {}\r\n", host)));
+    assert!(request.contains("Accept: text/plain\r\n"));
+    assert!(request.contains("Connection: close\r\n"));
+}
+
+#[test]
+fn reads_a_chunked_response() {
+    let (url, _) = start_server( | _, stream | {
+        // split up mid chunk, with an extension and a trailer
+        stream.write_all(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhel").unwrap();
+        stream.flush().unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+        stream.write_all(b"lo\r\n6;ext=1\r\n world\r\nA\r\n, chunked!\r\n0\r\nX-Trailer: 1\r\n\r\n").unwrap();
+    });
+    let (progress, response) = fetch(HttpRequest::new(url, HttpMethod::GET), None);
+    assert_eq!(response.unwrap().get_string_body().as_deref(), Some("hello world, chunked!"));
+    assert_eq!(progress, [5, 11, 21]);
+}
+
+#[test]
+fn reads_a_body_until_the_connection_closes() {
+    let (url, _) = start_server( | _, stream | {
+        stream.write_all(b"HTTP/1.1 200 OK\r\n\r\nno length").unwrap();
+    });
+    assert_eq!(get(&url).unwrap().get_string_body().as_deref(), Some("no length"));
+}
+
+#[test]
+fn sends_the_body_and_skips_it_on_head() {
+    let (url, requests) = start_server( | _, stream | {
+        stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\nbody").unwrap();
+    });
+    let mut request = HttpRequest::new(url.clone(), HttpMethod::POST);
+    request.set_body_string("payload");
+    assert_eq!(fetch(request, None).1.unwrap().get_string_body().as_deref(), Some("body"));
+    let request = requests.recv().unwrap();
+    assert!(request.contains("Content-Length: 7\r\n"));
+    assert!(request.ends_with("\r\n\r\npayload"));
+
+    let response = fetch(HttpRequest::new(url, HttpMethod::HEAD), None).1.unwrap();
+    assert_eq!(header(&response, "Content-Length"), Some("4"));
+    assert_eq!(response.body, Some(Vec::new()));
+}
+
+#[test]
+fn follows_redirects() {
+    let (url, requests) = start_server( | request, stream | {
+        let path = request.split(' ').nth(1).unwrap();
+        let host = request.lines().find_map( | line | line.strip_prefix("Host: ")).unwrap();
+        let response = match path {
+            "/a/start" => "HTTP/1.1 302 Found\r\nLocation: next\r\nContent-Length: 0\r\n\r\n".to_string(),
+            "/a/next" => "HTTP/1.1 307 Temporary Redirect\r\nLocation: /keep\r\nContent-Length: 0\r\n\r\n".to_string(),
+            "/keep" => format!("HTTP/1.1 301 Moved\r\nLocation: http://{}/done\r\n\r\n", host),
+            _ => "HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\ndone".to_string(),
+        };
+        stream.write_all(response.as_bytes()).unwrap();
+    });
+    let mut request = HttpRequest::new(format!("{}/a/start", url), HttpMethod::POST);
+    request.set_body_string("data");
+    let response = fetch(request, None).1.unwrap();
+    assert_eq!(response.status_code, 200);
+    assert_eq!(response.get_string_body().as_deref(), Some("done"));
+
+    let requests: Vec<String> = requests.iter().take(4).collect();
+    assert!(requests[0].starts_with("POST /a/start ") && requests[0].ends_with("data"));
+    // a 302 turns a POST into a GET without a body, which a 307 then keeps
+    assert!(requests[1].starts_with("GET /a/next ") && !requests[1].contains("Content-Length"));
+    assert!(requests[2].starts_with("GET /keep "));
+    assert!(requests[3].starts_with("GET /done "));
+}
+
+#[test]
+fn gives_up_on_redirect_loops() {
+    let (url, _) = start_server( | _, stream | {
+        stream.write_all(b"HTTP/1.1 302 Found\r\nLocation: /again\r\nContent-Length: 0\r\n\r\n").unwrap();
+    });
+    assert!(get(&url).unwrap_err().contains("too many redirects"));
+}
+
+#[test]
+fn reports_errors() {
+    // nothing listening
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    assert!(get(&format!("http://127.0.0.1:{}/", port)).unwrap_err().contains("cannot connect"));
+    assert!(get("https://127.0.0.1/").unwrap_err().contains("https is not supported"));
+    assert!(get("ftp://127.0.0.1/").unwrap_err().contains("unsupported protocol"));
+
+    let (url, _) = start_server( | _, _ | ());
+    assert!(get(&url).unwrap_err().contains("closed before response headers"));
+
+    let (url, _) = start_server( | _, stream | {
+        stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nshort").unwrap();
+    });
+    assert!(get(&url).unwrap_err().contains("closed before end of body"));
+
+    let (url, _) = start_server( | _, stream | {
+        stream.write_all(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\n").unwrap();
+    });
+    assert!(get(&url).unwrap_err().contains("invalid chunk size"));
+
+    let (url, _) = start_server( | _, stream | {
+        stream.write_all(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabcdef\r\n").unwrap();
+    });
+    assert!(get(&url).unwrap_err().contains("malformed chunk terminator"));
+
+    let (url, _) = start_server( | _, stream | {
+        stream.write_all(b"garbage\r\n\r\n").unwrap();
+    });
+    assert!(get(&url).unwrap_err().contains("invalid status line"));
+}
+
+#[test]
+fn times_out_on_a_stalled_server() {
+    let timeouts = HttpTimeouts {io: Duration::from_millis(200), ..Default::default()};
+    let (url, _) = start_server( | _, stream | {
+        stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nabc").unwrap();
+        std::thread::sleep(Duration::from_secs(2));
+    });
+    let started = Instant::now();
+    let (progress, response) = fetch(HttpRequest::new(url, HttpMethod::GET), Some(timeouts));
+    assert_eq!(response.unwrap_err(), "HttpRequest timed out");
+    assert_eq!(progress, [3]);
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+fn times_out_on_a_slow_server() {
+    // every read makes progress, but the whole request takes too long
+    let timeouts = HttpTimeouts {total: Duration::from_millis(300), ..Default::default()};
+    let (url, _) = start_server( | _, stream | {
+        let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\n");
+        for _ in 0..100 {
+            std::thread::sleep(Duration::from_millis(50));
+            if stream.write_all(b"x").is_err() {
+                break
+            }
+        }
+    });
+    let started = Instant::now();
+    let (_, response) = fetch(HttpRequest::new(url, HttpMethod::GET), Some(timeouts));
+    assert_eq!(response.unwrap_err(), "HttpRequest timed out");
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
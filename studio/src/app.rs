@@ -13,13 +13,20 @@ use crate::{
     makepad_platform::studio::{JumpToFile,EditFile, PatchFile},
     run_list::*,
     log_list::*,
+    search_list::*,
+    git_list::*,
     makepad_code_editor::text::{Position},
     build_manager::{
         build_manager::{
             BuildManager,
             BuildManagerAction
         },
-    }
+        build_protocol::{LogItem, LogItemBare, LogItemFix},
+    },
+    makepad_platform::log::LogLevel,
+    lsp::lsp_manager::{LspManager, LspAction},
+    search::search_manager::SearchManager,
+    git::git_manager::GitManager,
 }; 
 use std::fs::File;
 use std::io::Write;
@@ -37,6 +44,8 @@ live_design!{
 pub struct App {
     #[live] ui: WidgetRef,
     #[rust] data: AppData,
+    // the saved layout, restored once the file tree is there to find the files of editor tabs in
+    #[rust] state_to_restore: Option<PersistentState>,
 }
 
 impl LiveRegister for App{
@@ -45,6 +54,8 @@ impl LiveRegister for App{
         crate::makepad_code_editor::live_design(cx);
         crate::run_list::live_design(cx);
         crate::log_list::live_design(cx);
+        crate::search_list::live_design(cx);
+        crate::git_list::live_design(cx);
         crate::profiler::live_design(cx);
         crate::run_view::live_design(cx);
         crate::studio_editor::live_design(cx);
@@ -74,6 +85,9 @@ impl App {
 pub struct AppData{ 
     pub build_manager: BuildManager,
     pub file_system: FileSystem,
+    pub lsp_manager: LspManager,
+    pub search_manager: SearchManager,
+    pub git_manager: GitManager,
 }
 
 // all global app commands coming in from keybindings, and UI components
@@ -82,8 +96,12 @@ pub struct AppData{
 pub enum AppAction{
     JumpTo(JumpToFile),
     RedrawLog,
+    RedrawSearch,
+    RedrawGit,
     RedrawProfiler,
     RedrawFile(LiveId),
+    ApplyFixes(Vec<LogItemFix>),
+    ReplaceInFiles(String),
     FocusDesign(LiveId),
     EditFile(EditFile),
     PatchFile(PatchFile),
@@ -107,14 +125,26 @@ impl MatchEvent for App{
                 
         self.data.file_system.init(cx, &root_path);
         self.data.build_manager.init(cx, &root_path);
+        self.data.lsp_manager.init(&root_path);
+        self.data.search_manager.init(&root_path);
+        self.data.git_manager.init(&root_path);
         //self.data.build_manager.discover_external_ip(cx);
         self.data.build_manager.start_http_server();
+        
+        if let Ok(contents) = std::fs::read_to_string("makepad_state.ron") {
+            match PersistentState::deserialize_ron(&contents) {
+                Ok(state) => self.state_to_restore = Some(state),
+                Err(err) => error!("failed to load makepad_state.ron: {:?}", err)
+            }
+        }
     }
     
     fn handle_action(&mut self, cx:&mut Cx, action:&Action){
         let dock = self.ui.dock(id!(dock));
         let file_tree = self.ui.view(id!(file_tree));
         let log_list = self.ui.log_list(id!(log_list));
+        let search_list = self.ui.search_list(id!(search_list));
+        let git_list = self.ui.git_list(id!(git_list));
         let run_list = self.ui.view(id!(run_list));
         let profiler = self.ui.view(id!(profiler));
         match action.cast(){
@@ -171,6 +201,10 @@ impl MatchEvent for App{
                             self.data.file_system.request_save_file_for_file_node_id(file_id, true)
                         }
                     }
+                    else{
+                        // the designer edits files that aren't open in a tab as well
+                        self.data.file_system.replace_in_file(&ef.file_name, |_| vec![(start, end, ef.replace.into())]);
+                    }
                 }
             }
             AppAction::EditFile(ef)=>{
@@ -202,11 +236,27 @@ impl MatchEvent for App{
                             self.data.file_system.request_save_file_for_file_node_id(file_id, false)
                         }
                     }
+                    else{
+                        self.data.file_system.replace_in_file(&ef.file_name, |_| vec![(start, end, ef.replace.into())]);
+                    }
                 }
             }
             AppAction::RedrawFile(file_id)=>{
                 self.data.file_system.redraw_view_by_file_id(cx, file_id, &dock);
             }
+            AppAction::ApplyFixes(fixes)=>{
+                self.data.file_system.apply_fixes(&fixes);
+                // a fix only fits the text it was made for, so each one is used once
+                self.data.build_manager.remove_fixes(&fixes);
+                self.data.file_system.redraw_all_views(cx, &dock);
+                log_list.redraw(cx);
+            }
+            AppAction::ReplaceInFiles(replacement)=>{
+                self.data.search_manager.replace_all(&mut self.data.file_system, &replacement);
+                self.data.file_system.redraw_all_views(cx, &dock);
+                search_list.reset_scroll(cx);
+                search_list.redraw(cx);
+            }
             AppAction::ClearLog=>{
                 self.data.build_manager.clear_log(cx, &dock, &mut self.data.file_system);
                 log_list.reset_scroll(cx);
@@ -222,6 +272,13 @@ impl MatchEvent for App{
             AppAction::RedrawLog=>{
                 log_list.redraw(cx);
             }
+            AppAction::RedrawSearch=>{
+                search_list.redraw(cx);
+            }
+            AppAction::RedrawGit=>{
+                git_list.redraw(cx);
+                file_tree.redraw(cx);
+            }
             AppAction::StartRecompile=>{
                 self.data.build_manager.start_recompile(cx);
             }
@@ -323,6 +380,9 @@ impl MatchEvent for App{
         match action.cast(){
             FileSystemAction::TreeLoaded => {
                 file_tree.redraw(cx);
+                if let Some(state) = self.state_to_restore.take(){
+                    self.restore_state(cx, state);
+                }
                 //self.open_code_file_by_path(cx, "examples/slides/src/app.rs");
             }
             FileSystemAction::RecompileNeeded => {
@@ -333,8 +393,37 @@ impl MatchEvent for App{
                 self.data.build_manager.clear_log(cx, &dock, &mut self.data.file_system);
                 log_list.redraw(cx);
             }
+            FileSystemAction::DocumentOpened(file_id) => {
+                self.data.lsp_manager.open_file(file_id, &self.data.file_system);
+                self.data.git_manager.open_file(file_id, &self.data.file_system.file_node_path(file_id));
+            }
+            FileSystemAction::FileSaved(file_id) => {
+                self.data.lsp_manager.save_file(file_id);
+                self.data.git_manager.file_saved(&self.data.file_system, file_id);
+            }
             FileSystemAction::None=>()
         }
+        
+        match action.cast(){
+            LspAction::ShowLocations(locations) => {
+                let log = &mut self.data.build_manager.log;
+                log.push((live_id!(lsp), LogItem::Bare(LogItemBare {
+                    level: LogLevel::Log,
+                    line: format!("{} references", locations.len()),
+                })));
+                log.extend(locations.into_iter().map(|loc| (live_id!(lsp), LogItem::Location(loc))));
+                log_list.redraw(cx);
+            }
+            LspAction::ShowMessage(message) => {
+                let log = &mut self.data.build_manager.log;
+                log.extend(message.lines().map(|line| (live_id!(lsp), LogItem::Bare(LogItemBare {
+                    level: LogLevel::Log,
+                    line: line.to_string(),
+                }))));
+                log_list.redraw(cx);
+            }
+            LspAction::None=>()
+        }
                 
         match action.cast(){
             RunListAction::Create(..) => {
@@ -354,7 +443,17 @@ impl MatchEvent for App{
             match action.cast(){
                 CodeEditorAction::TextDidChange => {
                     // lets write the file
-                    self.data.file_system.request_save_file_for_tab_id(action.path.from_end(1), false)
+                    self.data.file_system.request_save_file_for_tab_id(action.path.from_end(1), false);
+                    if let Some(&file_id) = self.data.file_system.tab_id_to_file_node_id.get(&action.path.from_end(1)) {
+                        self.data.git_manager.update_diff(&mut self.data.file_system, file_id);
+                    }
+                }
+                CodeEditorAction::GutterMarkerClicked(line_index) => {
+                    if let Some(&file_id) = self.data.file_system.tab_id_to_file_node_id.get(&action.path.from_end(1)) {
+                        if self.data.git_manager.revert_hunk(&mut self.data.file_system, file_id, line_index) {
+                            self.data.file_system.redraw_view_by_file_id(cx, file_id, &dock);
+                        }
+                    }
                 }
                 CodeEditorAction::None=>{}
             }
@@ -471,6 +570,9 @@ impl AppMain for App {
         
         self.data.file_system.handle_event(cx, event, &self.ui);
         self.data.build_manager.handle_event(cx, event, &mut self.data.file_system); 
+        self.data.lsp_manager.handle_event(cx, event, &mut self.data.file_system);
+        self.data.search_manager.handle_event(cx, event);
+        self.data.git_manager.handle_event(cx, event, &mut self.data.file_system);
 
         // process events on all run_views
         let dock = self.ui.dock(id!(dock));
@@ -492,8 +594,21 @@ impl AppMain for App {
                 }
                 true 
             }); 
+            let editor_files = dock_items.iter().filter_map(|di| {
+                if let DockItemStore::Tab{id, kind,..} = di{
+                    if kind.0 == live_id!(StudioEditor){
+                        let file_id = self.data.file_system.tab_id_to_file_node_id.get(&id.0)?;
+                        return Some(EditorFileStore{
+                            tab_id: id.clone(),
+                            path: self.data.file_system.file_node_id_to_path(*file_id)?
+                        })
+                    }
+                }
+                None
+            }).collect();
             let state = PersistentState{
-                dock_items
+                dock_items,
+                editor_files
             };
             // alright lets save it to disk
             let saved = state.serialize_ron();
@@ -503,7 +618,48 @@ impl AppMain for App {
     }
 }
 
+impl App{
+    fn restore_state(&mut self, cx:&mut Cx, mut state:PersistentState){
+        let dock = self.ui.dock(id!(dock));
+        let file_system = &self.data.file_system;
+        // editor tabs whose file is gone are left out, the dock drops them from their tab bar
+        let mut editor_tabs = Vec::new();
+        state.dock_items.retain(|di| {
+            if let DockItemStore::Tab{id, kind,..} = di{
+                if kind.0 == live_id!(StudioEditor){
+                    let file_id = state.editor_files.iter()
+                        .find(|file| file.tab_id == *id)
+                        .and_then(|file| file_system.path_to_file_node_id(&file.path));
+                    if let Some(file_id) = file_id{
+                        editor_tabs.push((id.0, file_id));
+                        return true
+                    }
+                    return false
+                }
+            }
+            true
+        });
+        dock.load_state(cx, state.dock_items);
+        for (tab_id, file_id) in editor_tabs{
+            // the dock keeps its default layout when the saved one doesn't hold together
+            if dock.find_tab_bar_of_tab(tab_id).is_some(){
+                self.data.file_system.request_open_file(tab_id, file_id);
+            }
+        }
+        self.data.file_system.ensure_unique_tab_names(cx, &dock);
+    }
+}
+
+#[derive(Clone, Debug, SerRon, DeRon)]
+struct EditorFileStore{
+    tab_id: LiveIdStore,
+    path: String
+}
+
 #[derive(Clone, Debug, SerRon, DeRon)]
 struct PersistentState{
-    dock_items: Vec<DockItemStore>
+    dock_items: Vec<DockItemStore>,
+    // the file behind each editor tab, by path so it survives the file tree changing
+    #[serde(default)]
+    editor_files: Vec<EditorFileStore>
 }
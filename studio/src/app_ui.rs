@@ -8,6 +8,8 @@ live_design!{
     import makepad_studio::studio_file_tree::StudioFileTree;
     import makepad_studio::run_view::RunView;
     import makepad_studio::log_list::LogList;
+    import makepad_studio::search_list::SearchList;
+    import makepad_studio::git_list::GitList;
     import makepad_studio::run_list::RunList;
     import makepad_studio::profiler::Profiler;
 
@@ -256,6 +258,17 @@ live_design!{
                         svg_file: dep("crate://self/resources/icons/icon_profiler.svg"),
                     }
                 }
+                GitTab = <IconTab> {
+                    spacing: (THEME_SPACE_2)
+                    icon_walk: {
+                        width: 12.,
+                        margin: { top: 4. }
+                    }
+                    draw_icon: {
+                        color: (STUDIO_PALETTE_6)
+                        svg_file: dep("crate://self/resources/icons/icon_git.svg"),
+                    }
+                }
                 SearchFirstTab = <IconTab> {
                     spacing: (THEME_SPACE_2)
                     icon_walk: {
@@ -297,7 +310,7 @@ live_design!{
             }*/
 
             file_tree_tabs = Tabs {
-                tabs: [file_tree_tab, search, run_list_tab, outline_first],
+                tabs: [file_tree_tab, search, git, run_list_tab, outline_first],
                 selected: 0
             }
 
@@ -333,6 +346,12 @@ live_design!{
                 kind: Search
             }
 
+            git = Tab {
+                name: "Git"
+                template: GitTab,
+                kind: GitList
+            }
+
             run_first = Tab {
                 name: "App >"
                 template: RunFirstTab,
@@ -472,53 +491,12 @@ live_design!{
                 <RunList> {}
             }
             Search = <RectView> {
-            flow: Down,
-                <DockToolbar> {
-                    content = {
-                        padding: { right: (THEME_SPACE_2) }
-                        spacing: (THEME_SPACE_2)
-                        <TextInput> {
-                            width: Fill,
-                            empty_message: "Search",
-                        }
-
-                        <CheckBoxCustom> {
-                            text:""
-                            draw_check: { check_type: None }
-                            icon_walk: {width: 14.}
-                            draw_icon: {
-                                color: (THEME_COLOR_D_3),
-                                color_active: (THEME_COLOR_U_5),
-                                svg_file: dep("crate://self/resources/icons/icon_search_case_sensitive.svg"),
-                            }
-                        }
-                        <CheckBoxCustom> {
-                            text:""
-                            draw_check: { check_type: None }
-                            icon_walk: {width: 16.}
-                            draw_icon: {
-                                color: (THEME_COLOR_D_3),
-                                color_active: (THEME_COLOR_U_5),
-                                svg_file: dep("crate://self/resources/icons/icon_search_full_word.svg"),
-                            }
-                        }
-                        <CheckBoxCustom> {
-                            text:""
-                            draw_check: { check_type: None }
-                            icon_walk: {width: 12.}
-                            draw_icon: {
-                                color: (THEME_COLOR_D_3),
-                                color_active: (THEME_COLOR_U_5),
-                                svg_file: dep("crate://self/resources/icons/icon_search_regex.svg"),
-                            }
-                        }
-                    }
-                }
-                <View> {
-                    flow: Down
-                    margin: <THEME_MSPACE_2> {}
-                    <P> { text: "this does not work yet." }
-                }
+                flow: Down,
+                search_list = <SearchList> {}
+            }
+            GitList = <RectView> {
+                flow: Down,
+                git_list = <GitList> {}
             }
             RunView = <RunView> {}
             StudioFileTree = <View> {
@@ -0,0 +1,153 @@
+// A minimal language server that speaks just enough of the protocol over stdio to test the
+// language server client against. It keeps the documents in sync through incremental changes,
+// reports every occurrence of `error` as a diagnostic and gives fixed answers to everything else.
+
+use {
+    makepad_studio::{
+        lsp::lsp_protocol::*,
+        makepad_code_editor::text::Position,
+        makepad_micro_serde::*,
+    },
+    std::{
+        collections::HashMap,
+        io::{self, Write},
+    },
+};
+
+fn main() {
+    let stdin = io::stdin();
+    let mut stdin = stdin.lock();
+    let mut documents: HashMap<String, Vec<String>> = HashMap::new();
+    while let Ok(Some(body)) = read_message(&mut stdin) {
+        let Ok(message) = LspMessage::from_json(&body) else {
+            continue
+        };
+        match message {
+            LspMessage::Request {id, method, params} => {
+                let result = match method.as_str() {
+                    "initialize" => json_object([
+                        ("capabilities", json_object([
+                            ("textDocumentSync", JsonValue::U64(2)),
+                            ("completionProvider", json_object([])),
+                            ("definitionProvider", JsonValue::Bool(true)),
+                            ("referencesProvider", JsonValue::Bool(true)),
+                            ("hoverProvider", JsonValue::Bool(true)),
+                        ])),
+                    ]),
+                    "textDocument/completion" => JsonValue::Array(vec![
+                        json_object([("label", json_string("mock_function")), ("detail", json_string("fn()"))]),
+                        json_object([("label", json_string("mock_field"))]),
+                    ]),
+                    "textDocument/definition" => location(uri(&params), 0, 3, 0, 7),
+                    "textDocument/references" => JsonValue::Array(vec![
+                        location(uri(&params), 0, 3, 0, 7),
+                        location(uri(&params), 1, 4, 1, 8),
+                    ]),
+                    "textDocument/hover" => json_object([
+                        ("contents", json_object([
+                            ("kind", json_string("plaintext")),
+                            ("value", json_string("mock hover")),
+                        ])),
+                    ]),
+                    "shutdown" => JsonValue::Null,
+                    _ => {
+                        send(LspMessage::Response {id, result: Err(LspError {
+                            code: -32601,
+                            message: format!("unknown method {}", method),
+                        })});
+                        continue
+                    }
+                };
+                send(LspMessage::Response {id, result: Ok(result)});
+            }
+            LspMessage::Notification {method, params} => match method.as_str() {
+                "initialized" => {
+                    // the client has to answer requests from the server as well
+                    send(LspMessage::request(1, "workspace/configuration", json_object([
+                        ("items", JsonValue::Array(vec![json_object([("section", json_string("mock"))])])),
+                    ])));
+                }
+                "textDocument/didOpen" => {
+                    let uri = uri(&params);
+                    let text = params.key("textDocument").and_then(|document| document.key("text")).and_then(|text| text.string()).cloned().unwrap_or_default();
+                    documents.insert(uri.clone(), text.split('\n').map(|line| line.to_string()).collect());
+                    publish_diagnostics(&uri, &documents[&uri]);
+                }
+                "textDocument/didChange" => {
+                    let uri = uri(&params);
+                    let Some(lines) = documents.get_mut(&uri) else {
+                        continue
+                    };
+                    for change in params.key("contentChanges").and_then(|changes| changes.array()).into_iter().flatten() {
+                        apply_change(lines, change);
+                    }
+                    publish_diagnostics(&uri, lines);
+                }
+                "textDocument/didClose" => {
+                    documents.remove(&uri(&params));
+                }
+                "exit" => break,
+                _ => ()
+            }
+            LspMessage::Response {..} => ()
+        }
+    }
+}
+
+fn send(message: LspMessage) {
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(encode_message(&message.to_json()).as_bytes()).unwrap();
+    stdout.flush().unwrap();
+}
+
+fn uri(params: &JsonValue) -> String {
+    params.key("textDocument").and_then(|document| document.key("uri")).and_then(|uri| uri.string()).cloned().unwrap_or_default()
+}
+
+fn location(uri: String, start_line: usize, start_character: usize, end_line: usize, end_character: usize) -> JsonValue {
+    json_object([
+        ("uri", JsonValue::String(uri)),
+        ("range", LspRange {
+            start: LspPosition {line: start_line, character: start_character},
+            end: LspPosition {line: end_line, character: end_character},
+        }.to_json()),
+    ])
+}
+
+fn apply_change(lines: &mut Vec<String>, change: &JsonValue) {
+    let text = change.key("text").and_then(|text| text.string()).cloned().unwrap_or_default();
+    let Some(range) = change.key("range").and_then(LspRange::from_json) else {
+        *lines = text.split('\n').map(|line| line.to_string()).collect();
+        return
+    };
+    let start = range.start.to_position(lines);
+    let end = range.end.to_position(lines);
+    let string = format!(
+        "{}{}{}",
+        &lines[start.line_index][..start.byte_index],
+        text,
+        &lines[end.line_index][end.byte_index..]
+    );
+    lines.splice(start.line_index..=end.line_index, string.split('\n').map(|line| line.to_string()));
+}
+
+fn publish_diagnostics(uri: &str, lines: &[String]) {
+    let mut diagnostics = Vec::new();
+    for (line_index, line) in lines.iter().enumerate() {
+        for (byte_index, _) in line.match_indices("error") {
+            let start = LspPosition::from_position(lines, Position {line_index, byte_index});
+            diagnostics.push(json_object([
+                ("range", LspRange {
+                    start,
+                    end: LspPosition {line: start.line, character: start.character + 5},
+                }.to_json()),
+                ("severity", JsonValue::U64(1)),
+                ("message", json_string("found an error")),
+            ]));
+        }
+    }
+    send(LspMessage::notification("textDocument/publishDiagnostics", json_object([
+        ("uri", json_string(uri)),
+        ("diagnostics", JsonValue::Array(diagnostics)),
+    ])));
+}
@@ -2,6 +2,7 @@
 use {
     crate::{
         file_system::file_system::FileSystem,
+        lsp::lsp_manager::LSP_DECORATION_ID,
         makepad_micro_serde::*,
         makepad_widgets::*,
         makepad_platform::makepad_live_compiler::LiveFileChange,
@@ -233,9 +234,45 @@ impl BuildManager {
         self.active.builds.clear();
     }
     
+    /// The fixes of the log messages in `file_name` that `position` is in.
+    pub fn fixes_at(&self, file_name: &str, position: text::Position) -> Vec<LogItemFix> {
+        self.fixes_where(|loc| loc.file_name == file_name && loc.start <= position && position <= loc.end)
+    }
+
+    /// The fixes of all log messages in `file_name`.
+    pub fn fixes_in_file(&self, file_name: &str) -> Vec<LogItemFix> {
+        self.fixes_where(|loc| loc.file_name == file_name)
+    }
+
+    /// Forgets `fixes` once they are applied, so they can't be applied twice.
+    pub fn remove_fixes(&mut self, fixes: &[LogItemFix]) {
+        for (_, item) in &mut self.log {
+            if let LogItem::Location(loc) = item {
+                loc.fixes.retain(|fix| !fixes.contains(fix));
+            }
+        }
+    }
+
+    fn fixes_where(&self, f: impl Fn(&LogItemLocation) -> bool) -> Vec<LogItemFix> {
+        let mut fixes = Vec::new();
+        for (_, item) in &self.log {
+            if let LogItem::Location(loc) = item {
+                if f(loc) {
+                    // the same message shows up once for every build that hit it
+                    for fix in &loc.fixes {
+                        if !fixes.contains(fix) {
+                            fixes.push(fix.clone());
+                        }
+                    }
+                }
+            }
+        }
+        fixes
+    }
+
     pub fn clear_log(&mut self, cx: &mut Cx, dock: &DockRef, file_system: &mut FileSystem) {
-        // lets clear all log related decorations
-        file_system.clear_all_decorations();
+        // lets clear all log related decorations, the language server ones stay until it republishes
+        file_system.retain_all_decorations(|dec| dec.id == LSP_DECORATION_ID);
         file_system.redraw_all_views(cx, dock);
         self.log.clear();
         self.profile.clear();
@@ -383,7 +420,8 @@ impl BuildManager {
                                 file_name: item.file_name,
                                 start,
                                 end,
-                                message: item.message
+                                message: item.message,
+                                fixes: Vec::new()
                             })));
                             cx.action(AppAction::RedrawLog)
                         }
@@ -495,7 +533,9 @@ impl BuildManager {
         start_http_server(HttpServer {
             listen_address: addr,
             post_max_size: 1024 * 1024,
-            request: tx_request
+            request: tx_request,
+            static_files: None,
+            worker_threads: 4,
         });
         /*
         let rx_file_change = self.send_file_change.receiver();
@@ -147,7 +147,20 @@ pub struct LogItemLocation{
     pub file_name: String,
     pub start: Position,
     pub end: Position,
-    pub message: String
+    pub message: String,
+    pub fixes: Vec<LogItemFix>
+}
+
+/// A suggestion rustc marked as `MachineApplicable`, so it can be applied without review.
+/// Like rustc, its positions count columns in chars rather than bytes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogItemFix{
+    pub file_name: String,
+    pub start: Position,
+    pub end: Position,
+    pub replacement: String,
+    /// The text rustc saw from start to end, so a fix isn't applied to text that has changed since.
+    pub original: String
 }
 
 #[derive(Clone, Debug)]
@@ -401,7 +401,8 @@ pub trait MsgSender: Send {
     }
     
 
-    fn send_location_msg(&self, cmd_id: LiveId, level: LogLevel, file_name: String, start: Position, end: Position, message: String) {
+    #[allow(clippy::too_many_arguments)]
+    fn send_location_msg(&self, cmd_id: LiveId, level: LogLevel, file_name: String, start: Position, end: Position, message: String, fixes: Vec<LogItemFix>) {
         self.send_message(
             BuildClientMessageWrap{
                 cmd_id,
@@ -410,7 +411,8 @@ pub trait MsgSender: Send {
                 file_name: file_name.replace("\\","/"),
                 start,
                 end,
-                message
+                message,
+                fixes
             }))
         });
     }
@@ -436,7 +438,9 @@ pub trait MsgSender: Send {
             }
             if let Some(span) = msg.spans.iter().find( | span | span.is_primary) {
                
-                self.send_location_msg(cmd_id, level, span.file_name.clone(),span.start(), span.end(), msg.message.clone());
+                let mut fixes = Vec::new();
+                collect_fixes(&msg, &mut fixes);
+                self.send_location_msg(cmd_id, level, span.file_name.clone(),span.start(), span.end(), msg.message.clone(), fixes);
                 /*
                 if let Some(label) = &span.label {
                     self.send_location_msg(cmd_id, level, span.file_name.clone(), range, label.clone());
@@ -462,6 +466,24 @@ pub trait MsgSender: Send {
     }
 }
 
+// the machine applicable suggestions of a message, which rustc mostly puts in its children
+fn collect_fixes(msg: &RustcMessage, fixes: &mut Vec<LogItemFix>) {
+    for span in &msg.spans {
+        if let (Some(replacement), Some("MachineApplicable")) = (&span.suggested_replacement, span.suggestion_applicability.as_deref()) {
+            fixes.push(LogItemFix {
+                file_name: span.file_name.replace("\\","/"),
+                start: span.start(),
+                end: span.end(),
+                replacement: replacement.clone(),
+                original: span.highlighted_text()
+            });
+        }
+    }
+    for child in &msg.children {
+        collect_fixes(child, fixes);
+    }
+}
+
 impl<F: Clone + Fn(BuildClientMessageWrap) + Send + 'static> MsgSender for F {
     fn box_clone(&self) -> Box<dyn MsgSender> {
         Box::new(self.clone())
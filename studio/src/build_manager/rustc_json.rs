@@ -60,6 +60,15 @@ impl RustcSpan {
     pub fn length(&self) -> Length {
         self.end() - self.start()
     }
+
+    /// The source text the span covers, from the lines rustc quotes with it.
+    pub fn highlighted_text(&self) -> String {
+        self.text.iter().map(|line| {
+            let start = line.highlight_start.saturating_sub(1);
+            let end = line.highlight_end.saturating_sub(1).max(start);
+            line.text.chars().skip(start).take(end - start).collect::<String>()
+        }).collect::<Vec<_>>().join("\n")
+    }
 }
 
 #[derive(Clone, DeJson, Debug, Default)]
@@ -1,8 +1,9 @@
 use {
     std::collections::{HashMap, hash_map},
-    std::path::Path,
+    std::path::{Path, PathBuf},
     crate::{
-        makepad_code_editor::{Document, decoration::{Decoration, DecorationSet}, Session},
+        build_manager::build_protocol::LogItemFix,
+        makepad_code_editor::{Document, decoration::{Decoration, DecorationSet}, LanguageRegistry, Session, text::{Change, Position, Text}},
         makepad_platform::makepad_live_compiler::LiveFileChange,
         makepad_widgets::*,
         makepad_widgets::file_tree::*,
@@ -22,11 +23,14 @@ use {
 pub struct FileSystem {
     pub file_client: FileClient,
     pub root_path: String,
+    // where the file tree is on disk
+    pub root_dir: PathBuf,
     pub file_nodes: LiveIdMap<LiveId, FileNode>,
     pub path_to_file_node_id: HashMap<String, LiveId>,
     pub tab_id_to_file_node_id: HashMap<LiveId, LiveId>,
     pub tab_id_to_session: HashMap<LiveId, Session>,
-    pub open_documents: HashMap<LiveId, OpenDoc>
+    pub open_documents: HashMap<LiveId, OpenDoc>,
+    pub languages: LanguageRegistry,
 }
 
 pub enum OpenDoc {
@@ -59,11 +63,14 @@ pub enum FileSystemAction {
     TreeLoaded,
     RecompileNeeded,
     LiveReloadNeeded(LiveFileChange),
+    DocumentOpened(LiveId),
+    FileSaved(LiveId),
     None
 }
 
 impl FileSystem {
     pub fn init(&mut self, cx: &mut Cx, path:&Path) {
+        self.root_dir = path.to_path_buf();
         self.file_client.init(cx, path);
         self.reload_file_tree();
     }
@@ -81,6 +88,11 @@ impl FileSystem {
         self.path_to_file_node_id.get(path).cloned()
     }
     
+    // the path relative to the root, as path_to_file_node_id takes it
+    pub fn file_node_id_to_path(&self, file_node: LiveId) -> Option<String> {
+        self.path_to_file_node_id.iter().find(|(_, id)| **id == file_node).map(|(path, _)| path.clone())
+    }
+
     pub fn file_node_id_to_tab_id(&self, file_node: LiveId) -> Option<LiveId> {
         for (tab, id) in &self.tab_id_to_file_node_id {
             if *id == file_node {
@@ -116,7 +128,7 @@ impl FileSystem {
                         }
                         FileResponse::OpenFile(result) => {
                             match result {
-                                Ok((_unix_path, data, id)) => {
+                                Ok((unix_path, data, id)) => {
                                     let file_id = LiveId(id);
                                     let dock = ui.dock(id!(dock));
                                     for (tab_id, file_id) in &self.tab_id_to_file_node_id {
@@ -126,7 +138,10 @@ impl FileSystem {
                                     }
                                     if let Some(OpenDoc::Decorations(dec)) = self.open_documents.get(&file_id) {
                                         let dec = dec.clone();
-                                        self.open_documents.insert(file_id, OpenDoc::Document(Document::new(data.into(), dec)));
+                                        let text: Text = data.into();
+                                        let tokenizer = self.languages.tokenizer_for_path(&unix_path, text.as_lines().len());
+                                        self.open_documents.insert(file_id, OpenDoc::Document(Document::with_tokenizer(text, dec, tokenizer)));
+                                        cx.action(FileSystemAction::DocumentOpened(file_id));
                                     }else {panic!()}
                                     ui.redraw(cx);
                                 }
@@ -139,7 +154,8 @@ impl FileSystem {
                             }
                         }
                         FileResponse::SaveFile(result) => match result {
-                            Ok((path, old, new, _id, was_patch)) => {
+                            Ok((path, old, new, id, was_patch)) => {
+                                cx.action(FileSystemAction::FileSaved(LiveId(id)));
                                 // alright file has been saved
                                 // now we need to check if a live_design!{} changed or something outside it
                                 if old != new && !was_patch {
@@ -228,6 +244,63 @@ impl FileSystem {
         }
     }
     
+    /// Applies `fixes` from the build log, returning how many it applied. Open files are edited
+    /// through their session as one undo group per file, other files are patched on disk.
+    pub fn apply_fixes(&mut self, fixes: &[LogItemFix]) -> usize {
+        let mut file_names: Vec<&str> = fixes.iter().map(|fix| fix.file_name.as_str()).collect();
+        file_names.sort();
+        file_names.dedup();
+        let mut applied = 0;
+        for file_name in file_names {
+            let fixes: Vec<&LogItemFix> = fixes.iter().filter(|fix| fix.file_name == file_name).collect();
+            applied += self.replace_in_file(file_name, |lines| fix_replacements(lines, &fixes));
+        }
+        applied
+    }
+    
+    /// Replaces the ranges that `replacements` computes from the current lines of a file, in its
+    /// open document if there is one and on disk otherwise, and saves it. The ranges have to be
+    /// sorted and may not overlap. Returns how many ranges were replaced.
+    pub fn replace_in_file(&mut self, file_name: &str, replacements: impl FnOnce(&[String]) -> Vec<(Position, Position, Text)>) -> usize {
+        let Some(file_id) = self.path_to_file_node_id(file_name) else {
+            return 0
+        };
+        if let Some(OpenDoc::Document(document)) = self.open_documents.get(&file_id) {
+            let replacements = replacements(document.as_text().as_lines());
+            if replacements.is_empty() {
+                return 0
+            }
+            match self.file_node_id_to_tab_id(file_id) {
+                Some(tab_id) => self.get_session_mut(tab_id).unwrap().replace_ranges(&replacements),
+                // closing a tab keeps its document, but not its session
+                None => Session::new(document.clone()).replace_ranges(&replacements),
+            }
+            self.handle_sessions();
+            self.request_save_file_for_file_node_id(file_id, false);
+            replacements.len()
+        }
+        else {
+            let Ok(data) = std::fs::read_to_string(self.root_dir.join(file_name)) else {
+                return 0
+            };
+            let mut text: Text = data.into();
+            let replacements = replacements(text.as_lines());
+            if replacements.is_empty() {
+                return 0
+            }
+            for (start, end, replacement) in replacements.iter().rev() {
+                if start != end {
+                    text.apply_change(Change::Delete(*start, *end - *start));
+                }
+                if !replacement.is_empty() {
+                    text.apply_change(Change::Insert(*start, replacement.clone()));
+                }
+            }
+            self.file_client.send_request(FileRequest::SaveFile(file_name.to_string(), text.to_string(), file_id.0, false));
+            replacements.len()
+        }
+    }
+    
     pub fn clear_decorations(&mut self, file_node_id: &LiveId) {
         // ok lets see if we have a document
         // ifnot, we create a new one
@@ -279,20 +352,42 @@ impl FileSystem {
         };
     }
     
+    pub fn retain_decorations(&mut self, file_id: LiveId, f: impl FnMut(&Decoration) -> bool) {
+        match self.open_documents.get_mut(&file_id) {
+            Some(OpenDoc::Decorations(decs)) => decs.retain(f),
+            Some(OpenDoc::Document(doc)) => doc.retain_decorations(f),
+            None => ()
+        };
+    }
+    
+    pub fn retain_all_decorations(&mut self, mut f: impl FnMut(&Decoration) -> bool) {
+        for document in self.open_documents.values_mut() {
+            match document {
+                OpenDoc::Decorations(decs) => decs.retain(&mut f),
+                OpenDoc::Document(doc) => doc.retain_decorations(&mut f),
+            }
+        }
+    }
     
-    pub fn draw_file_node(&self, cx: &mut Cx2d, file_node_id: LiveId, file_tree: &mut FileTree) {
+    /// Draws a node and its children, with the status `status` returns for their paths and
+    /// whether they are folders.
+    pub fn draw_file_node(&self, cx: &mut Cx2d, file_node_id: LiveId, file_tree: &mut FileTree, status: &dyn Fn(&str, bool) -> Option<FileNodeStatus<'static>>) {
         if let Some(file_node) = self.file_nodes.get(&file_node_id) {
+            let node_status = match file_node.parent_edge {
+                Some(_) => status(&self.file_node_path(file_node_id), !file_node.is_file()),
+                None => None
+            };
             match &file_node.child_edges {
                 Some(child_edges) => {
-                    if file_tree.begin_folder(cx, file_node_id, &file_node.name).is_ok() {
+                    if file_tree.begin_folder_with_status(cx, file_node_id, &file_node.name, node_status).is_ok() {
                         for child_edge in child_edges {
-                            self.draw_file_node(cx, child_edge.file_node_id, file_tree);
+                            self.draw_file_node(cx, child_edge.file_node_id, file_tree, status);
                         }
                         file_tree.end_folder();
                     }
                 }
                 None => {
-                    file_tree.file(cx, file_node_id, &file_node.name);
+                    file_tree.file_with_status(cx, file_node_id, &file_node.name, node_status);
                 }
             }
         }
@@ -445,4 +540,50 @@ impl FileSystem {
             tree_data.root,
         );
     }
-}
\ No newline at end of file
+}
+
+/// The ranges `fixes` replace in `lines` in byte positions, sorted and without the ones that
+/// overlap or whose text isn't what rustc saw anymore.
+pub fn fix_replacements(lines: &[String], fixes: &[&LogItemFix]) -> Vec<(Position, Position, Text)> {
+    fn byte_position(lines: &[String], position: Position) -> Option<Position> {
+        let line = lines.get(position.line_index)?;
+        let byte_index = if position.byte_index == line.chars().count() {
+            line.len()
+        }
+        else {
+            line.char_indices().nth(position.byte_index)?.0
+        };
+        Some(Position {line_index: position.line_index, byte_index})
+    }
+    fn text_between(lines: &[String], start: Position, end: Position) -> Option<String> {
+        if start.line_index == end.line_index {
+            return Some(lines[start.line_index].get(start.byte_index..end.byte_index)?.to_string())
+        }
+        let mut text = lines[start.line_index].get(start.byte_index..)?.to_string();
+        for line in lines.get(start.line_index + 1..end.line_index)? {
+            text.push('\n');
+            text.push_str(line);
+        }
+        text.push('\n');
+        text.push_str(lines[end.line_index].get(..end.byte_index)?);
+        Some(text)
+    }
+    let mut replacements: Vec<(Position, Position, Text)> = fixes.iter().filter_map(|fix| {
+        let start = byte_position(lines, fix.start)?;
+        let end = byte_position(lines, fix.end)?;
+        if start > end || text_between(lines, start, end)? != fix.original {
+            return None
+        }
+        Some((start, end, fix.replacement.as_str().into()))
+    }).collect();
+    replacements.sort_by_key(|(start, end, _)| (*start, *end));
+    let mut end_of_previous = Position::default();
+    replacements.retain(|(start, end, _)| {
+        if *start < end_of_previous {
+            return false
+        }
+        end_of_previous = *end;
+        true
+    });
+    replacements
+}
@@ -0,0 +1,168 @@
+use crate::makepad_code_editor::{
+    decoration::{GutterMarker, GutterMarkerType},
+    text::{Position, Text},
+};
+
+// past this many edits a diff gives up on finding the shortest edit script, and reports the
+// lines in between as one changed hunk
+const MAX_EDIT_DISTANCE: usize = 1000;
+
+/// A run of changed lines: `old_len` lines from `old_start` in the old text were replaced by
+/// `new_len` lines from `new_start` in the new text.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GitHunk {
+    pub old_start: usize,
+    pub old_len: usize,
+    pub new_start: usize,
+    pub new_len: usize,
+}
+
+impl GitHunk {
+    pub fn marker(&self) -> GutterMarker {
+        GutterMarker {
+            start_line_index: self.new_start,
+            line_count: self.new_len,
+            ty: if self.new_len == 0 {
+                GutterMarkerType::Deleted
+            } else if self.old_len == 0 {
+                GutterMarkerType::Added
+            } else {
+                GutterMarkerType::Modified
+            },
+        }
+    }
+
+    pub fn contains_line(&self, line_index: usize) -> bool {
+        self.marker().contains_line(line_index)
+    }
+
+    /// The replacement in `new_lines` that turns this hunk back into `old_lines`.
+    pub fn revert(&self, old_lines: &[String], new_lines: &[String]) -> (Position, Position, Text) {
+        let old = &old_lines[self.old_start..self.old_start + self.old_len];
+        let new_end = self.new_start + self.new_len;
+        if new_end < new_lines.len() {
+            // the hunk ends in a newline, so whole lines can be replaced
+            let text: String = old.iter().map(|line| format!("{}\n", line)).collect();
+            return (
+                Position {line_index: self.new_start, byte_index: 0},
+                Position {line_index: new_end, byte_index: 0},
+                text.into(),
+            );
+        }
+        // the hunk runs to the end of the text, which has no newline after its last line
+        let last_line_index = new_lines.len() - 1;
+        let end = Position {line_index: last_line_index, byte_index: new_lines[last_line_index].len()};
+        if self.new_start == 0 {
+            return (Position::default(), end, old.join("\n").into());
+        }
+        let start = Position {line_index: self.new_start - 1, byte_index: new_lines[self.new_start - 1].len()};
+        let text: String = old.iter().map(|line| format!("\n{}", line)).collect();
+        (start, end, text.into())
+    }
+}
+
+/// Diffs two texts line by line and returns the changed hunks, in order.
+pub fn diff_lines(old: &[String], new: &[String]) -> Vec<GitHunk> {
+    let prefix_len = old.iter().zip(new).take_while(|(old, new)| old == new).count();
+    let suffix_len = old[prefix_len..]
+        .iter()
+        .rev()
+        .zip(new[prefix_len..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old_middle = &old[prefix_len..old.len() - suffix_len];
+    let new_middle = &new[prefix_len..new.len() - suffix_len];
+
+    let mut hunks = Vec::new();
+    let mut push_hunk = |old_start: usize, old_end: usize, new_start: usize, new_end: usize| {
+        if old_start < old_end || new_start < new_end {
+            hunks.push(GitHunk {
+                old_start: prefix_len + old_start,
+                old_len: old_end - old_start,
+                new_start: prefix_len + new_start,
+                new_len: new_end - new_start,
+            });
+        }
+    };
+    match common_lines(old_middle, new_middle) {
+        Some(common_lines) => {
+            let (mut old_index, mut new_index) = (0, 0);
+            for (old_common, new_common) in common_lines {
+                push_hunk(old_index, old_common, new_index, new_common);
+                old_index = old_common + 1;
+                new_index = new_common + 1;
+            }
+            push_hunk(old_index, old_middle.len(), new_index, new_middle.len());
+        }
+        None => push_hunk(0, old_middle.len(), 0, new_middle.len()),
+    }
+    hunks
+}
+
+// the indices of the lines that `old` and `new` have in common with the fewest edits in between,
+// found with Myers' algorithm, or none if that takes more than `MAX_EDIT_DISTANCE` edits
+fn common_lines(old: &[String], new: &[String]) -> Option<Vec<(usize, usize)>> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = (n + m) as usize;
+    let offset = max as isize + 1;
+    // the furthest x reached on each diagonal k = x - y
+    let mut v = vec![0isize; 2 * max + 3];
+    // v after each number of edits d, for the diagonals -d..=d
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    for d in 0..=max as isize {
+        if d as usize > MAX_EDIT_DISTANCE {
+            return None;
+        }
+        let mut done = false;
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[(offset + k - 1) as usize] < v[(offset + k + 1) as usize]) {
+                v[(offset + k + 1) as usize]
+            } else {
+                v[(offset + k - 1) as usize] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[(offset + k) as usize] = x;
+            if x >= n && y >= m {
+                done = true;
+                break;
+            }
+        }
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+        if done {
+            break;
+        }
+    }
+
+    let mut common_lines = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (1..trace.len() as isize).rev() {
+        let previous = &trace[d as usize - 1];
+        let get = |k: isize| previous[(k + d - 1) as usize];
+        let k = x - y;
+        let previous_k = if k == -d || (k != d && get(k - 1) < get(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let previous_x = get(previous_k);
+        let previous_y = previous_x - previous_k;
+        while x > previous_x && y > previous_y {
+            x -= 1;
+            y -= 1;
+            common_lines.push((x as usize, y as usize));
+        }
+        x = previous_x;
+        y = previous_y;
+    }
+    while x > 0 && y > 0 {
+        x -= 1;
+        y -= 1;
+        common_lines.push((x as usize, y as usize));
+    }
+    common_lines.reverse();
+    Some(common_lines)
+}
@@ -0,0 +1,263 @@
+use {
+    crate::{
+        app::AppAction,
+        file_system::file_system::{FileSystem, OpenDoc},
+        git::{
+            git_diff::{diff_lines, GitHunk},
+            git_status::{parse_status, GitFileStatus, GitStatusEntry},
+        },
+        makepad_code_editor::text::Text,
+        makepad_widgets::*,
+    },
+    std::{
+        collections::{HashMap, HashSet},
+        path::{Path, PathBuf},
+        process::Command,
+        sync::mpsc,
+    },
+};
+
+pub enum GitMessage {
+    Status {status_id: u64, entries: Vec<GitStatusEntry>},
+    HeadText {file_id: LiveId, text: Option<String>},
+    CommandDone {result: Result<String, String>, changes_head: bool},
+}
+
+// the work for the git thread, which runs it in the order it was asked for
+enum GitJob {
+    Status {status_id: u64},
+    HeadText {file_id: LiveId, object: String},
+    Command {args: Vec<String>, changes_head: bool, status_id: u64},
+}
+
+/// Runs the `git` command line tool on a background thread, for the status of the files in the
+/// file tree, the changed lines of the open files against HEAD, and staging and committing.
+/// The commands run one after the other, so a commit sees the files staged before it.
+#[derive(Default)]
+pub struct GitManager {
+    root_path: PathBuf,
+    receiver: ToUIReceiver<GitMessage>,
+    jobs: Option<mpsc::Sender<GitJob>>,
+    status_id: u64,
+    pub entries: Vec<GitStatusEntry>,
+    statuses: HashMap<String, GitFileStatus>,
+    changed_dirs: HashSet<String>,
+    // the lines of the open files at HEAD, for the ones that are in it
+    head_lines: HashMap<LiveId, Vec<String>>,
+    hunks: HashMap<LiveId, Vec<GitHunk>>,
+    // the output of the last command that was run, or its error
+    pub message: Option<String>,
+}
+
+impl GitManager {
+    pub fn init(&mut self, path: &Path) {
+        self.root_path = path.to_path_buf();
+        let (jobs, rx_jobs) = mpsc::channel();
+        self.jobs = Some(jobs);
+        let root_path = self.root_path.clone();
+        let sender = self.receiver.sender();
+        // stops when the manager and with it the job sender is dropped
+        std::thread::spawn(move || {
+            for job in rx_jobs {
+                match job {
+                    GitJob::Status {status_id} => {
+                        let entries = git_status(&root_path);
+                        let _ = sender.send(GitMessage::Status {status_id, entries});
+                    }
+                    GitJob::HeadText {file_id, object} => {
+                        let text = run_git(&root_path, &["show", &object]).ok();
+                        let _ = sender.send(GitMessage::HeadText {file_id, text});
+                    }
+                    GitJob::Command {args, changes_head, status_id} => {
+                        let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+                        let result = run_git(&root_path, &args);
+                        let _ = sender.send(GitMessage::CommandDone {result, changes_head});
+                        let entries = git_status(&root_path);
+                        let _ = sender.send(GitMessage::Status {status_id, entries});
+                    }
+                }
+            }
+        });
+        self.refresh_status();
+    }
+
+    fn send_job(&self, job: GitJob) {
+        if let Some(jobs) = &self.jobs {
+            let _ = jobs.send(job);
+        }
+    }
+
+    pub fn refresh_status(&mut self) {
+        self.status_id += 1;
+        self.send_job(GitJob::Status {status_id: self.status_id});
+    }
+
+    /// Loads the text `file_name` has at HEAD, to diff the open file against.
+    pub fn open_file(&mut self, file_id: LiveId, file_name: &str) {
+        self.send_job(GitJob::HeadText {file_id, object: format!("HEAD:./{}", file_name)});
+    }
+
+    pub fn file_status(&self, file_name: &str) -> Option<GitFileStatus> {
+        self.statuses.get(file_name).copied()
+    }
+
+    /// Whether any file below the directory `dir_name` has changed.
+    pub fn dir_has_changes(&self, dir_name: &str) -> bool {
+        self.changed_dirs.contains(dir_name)
+    }
+
+    /// Diffs the text of an open file against HEAD again and updates its gutter markers.
+    pub fn update_diff(&mut self, file_system: &mut FileSystem, file_id: LiveId) {
+        let Some(OpenDoc::Document(document)) = file_system.open_documents.get_mut(&file_id) else {
+            return
+        };
+        let hunks = match self.head_lines.get(&file_id) {
+            Some(head_lines) => diff_lines(head_lines, document.as_text().as_lines()),
+            None => Vec::new(),
+        };
+        document.set_gutter_markers(hunks.iter().map(|hunk| hunk.marker()).collect());
+        self.hunks.insert(file_id, hunks);
+    }
+
+    /// Gets the status again when saving an open file could have changed it, which is when the
+    /// file started or stopped matching HEAD.
+    pub fn file_saved(&mut self, file_system: &FileSystem, file_id: LiveId) {
+        if !self.head_lines.contains_key(&file_id) {
+            return
+        }
+        let has_changes = self.hunks.get(&file_id).is_some_and(|hunks| !hunks.is_empty());
+        if has_changes != self.file_status(&file_system.file_node_path(file_id)).is_some() {
+            self.refresh_status();
+        }
+    }
+
+    /// Reverts the changed lines around `line_index` in an open file to how they are at HEAD.
+    /// Returns whether there were any.
+    pub fn revert_hunk(&mut self, file_system: &mut FileSystem, file_id: LiveId, line_index: usize) -> bool {
+        let Some(head_lines) = self.head_lines.get(&file_id) else {
+            return false
+        };
+        let file_name = file_system.file_node_path(file_id);
+        let reverted = file_system.replace_in_file(&file_name, |lines| {
+            diff_lines(head_lines, lines)
+                .iter()
+                .find(|hunk| hunk.contains_line(line_index))
+                .map(|hunk| vec![hunk.revert(head_lines, lines)])
+                .unwrap_or_default()
+        });
+        self.update_diff(file_system, file_id);
+        reverted > 0
+    }
+
+    pub fn stage(&mut self, file_names: &[String]) {
+        let mut args = vec!["add", "--"];
+        args.extend(file_names.iter().map(|file_name| file_name.as_str()));
+        self.run_command(&args, false);
+    }
+
+    pub fn unstage(&mut self, file_names: &[String]) {
+        let mut args = vec!["restore", "--staged", "--"];
+        args.extend(file_names.iter().map(|file_name| file_name.as_str()));
+        self.run_command(&args, false);
+    }
+
+    /// Commits the staged files.
+    pub fn commit(&mut self, message: &str) {
+        self.run_command(&["commit", "-m", message], true);
+    }
+
+    // runs git with `args` and then gets the status again, as the command probably changed it
+    fn run_command(&mut self, args: &[&str], changes_head: bool) {
+        self.status_id += 1;
+        self.send_job(GitJob::Command {
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            changes_head,
+            status_id: self.status_id,
+        });
+    }
+
+    pub fn handle_event(&mut self, cx: &mut Cx, event: &Event, file_system: &mut FileSystem) {
+        if let Event::Signal = event {
+            while let Ok(message) = self.receiver.try_recv() {
+                match message {
+                    GitMessage::Status {status_id, entries} => {
+                        // a newer status is on its way
+                        if status_id != self.status_id {
+                            continue
+                        }
+                        self.set_entries(entries);
+                        cx.action(AppAction::RedrawGit);
+                    }
+                    GitMessage::HeadText {file_id, text} => {
+                        match text {
+                            Some(text) => {
+                                self.head_lines.insert(file_id, Text::from(text).as_lines().to_vec());
+                            }
+                            None => {
+                                self.head_lines.remove(&file_id);
+                            }
+                        }
+                        self.update_diff(file_system, file_id);
+                        cx.action(AppAction::RedrawFile(file_id));
+                    }
+                    GitMessage::CommandDone {result, changes_head} => {
+                        self.message = match &result {
+                            Ok(output) => output.lines().next().map(|line| line.to_string()),
+                            Err(error) => Some(error.clone()),
+                        };
+                        if result.is_ok() && changes_head {
+                            let file_ids: Vec<LiveId> = file_system.open_documents.keys().copied().collect();
+                            for file_id in file_ids {
+                                self.open_file(file_id, &file_system.file_node_path(file_id));
+                            }
+                        }
+                        cx.action(AppAction::RedrawGit);
+                    }
+                }
+            }
+        }
+    }
+
+    fn set_entries(&mut self, entries: Vec<GitStatusEntry>) {
+        self.statuses.clear();
+        self.changed_dirs.clear();
+        for entry in &entries {
+            let Some(status) = entry.status() else {
+                continue
+            };
+            self.statuses.insert(entry.file_name.clone(), status);
+            let mut dir_name = entry.file_name.as_str();
+            while let Some((parent, _)) = dir_name.rsplit_once('/') {
+                if !self.changed_dirs.insert(parent.to_string()) {
+                    break
+                }
+                dir_name = parent;
+            }
+        }
+        self.entries = entries;
+    }
+}
+
+// the status of the files below `root_path`, which is empty outside of a repository
+fn git_status(root_path: &Path) -> Vec<GitStatusEntry> {
+    let Ok(prefix) = run_git(root_path, &["rev-parse", "--show-prefix"]) else {
+        return Vec::new()
+    };
+    match run_git(root_path, &["status", "--porcelain=v1", "-z", "--untracked-files=all", "--", "."]) {
+        Ok(output) => parse_status(&output, prefix.trim_end()),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn run_git(root_path: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(root_path)
+        .output()
+        .map_err(|error| format!("cannot run git: {}", error))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
@@ -0,0 +1,87 @@
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GitFileStatus {
+    Modified,
+    Added,
+    Deleted,
+    Renamed,
+    Untracked,
+    Conflicted,
+}
+
+impl GitFileStatus {
+    fn from_code(code: char) -> Option<Self> {
+        match code {
+            'M' | 'T' => Some(Self::Modified),
+            'A' | 'C' => Some(Self::Added),
+            'D' => Some(Self::Deleted),
+            'R' => Some(Self::Renamed),
+            '?' => Some(Self::Untracked),
+            'U' => Some(Self::Conflicted),
+            _ => None,
+        }
+    }
+
+    /// The letter the file tree shows for this status.
+    pub fn letter(self) -> &'static str {
+        match self {
+            Self::Modified => "M",
+            Self::Added => "A",
+            Self::Deleted => "D",
+            Self::Renamed => "R",
+            Self::Untracked => "U",
+            Self::Conflicted => "!",
+        }
+    }
+}
+
+/// A file that `git status` reports, with its name relative to the directory git ran in.
+/// `staged` is the status of the file in the index, `unstaged` in the working tree.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GitStatusEntry {
+    pub file_name: String,
+    pub staged: Option<GitFileStatus>,
+    pub unstaged: Option<GitFileStatus>,
+}
+
+impl GitStatusEntry {
+    /// The status to show for the file, where changes in the working tree win over staged ones.
+    pub fn status(&self) -> Option<GitFileStatus> {
+        self.unstaged.or(self.staged)
+    }
+}
+
+/// Parses the output of `git status --porcelain=v1 -z`, keeping the files below `prefix`, the
+/// directory git ran in relative to the top of the repository, and making their names relative
+/// to it.
+pub fn parse_status(output: &str, prefix: &str) -> Vec<GitStatusEntry> {
+    let mut entries = Vec::new();
+    let mut fields = output.split('\0');
+    while let Some(field) = fields.next() {
+        let mut chars = field.chars();
+        let (Some(x), Some(y), Some(' ')) = (chars.next(), chars.next(), chars.next()) else {
+            continue
+        };
+        let path = chars.as_str();
+        // renames and copies are followed by the name the file had before
+        if matches!(x, 'R' | 'C') || matches!(y, 'R' | 'C') {
+            fields.next();
+        }
+        let Some(file_name) = path.strip_prefix(prefix) else {
+            continue
+        };
+        let is_conflicted = x == 'U' || y == 'U' || (x == y && matches!(x, 'A' | 'D'));
+        let (staged, unstaged) = if is_conflicted {
+            (Some(GitFileStatus::Conflicted), Some(GitFileStatus::Conflicted))
+        } else if x == '?' {
+            (None, Some(GitFileStatus::Untracked))
+        } else {
+            (GitFileStatus::from_code(x), GitFileStatus::from_code(y))
+        };
+        entries.push(GitStatusEntry {
+            file_name: file_name.to_string(),
+            staged,
+            unstaged,
+        });
+    }
+    entries
+}
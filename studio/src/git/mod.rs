@@ -0,0 +1,3 @@
+pub mod git_diff;
+pub mod git_manager;
+pub mod git_status;
@@ -0,0 +1,210 @@
+use {
+    crate::{
+        app::{AppAction, AppData},
+        git::git_status::{GitFileStatus, GitStatusEntry},
+        makepad_platform::studio::JumpToFile,
+        makepad_widgets::*,
+    },
+};
+
+live_design!{
+    import makepad_draw::shader::std::*;
+    import makepad_widgets::base::*;
+    import makepad_widgets::theme_desktop_dark::*;
+
+    GitItem = <View> {
+        height: Fit, width: Fill
+        padding: <THEME_MSPACE_1> {}
+        spacing: (THEME_SPACE_2)
+        align: { x: 0.0, y: 0.5 }
+        show_bg: true,
+        draw_bg: {
+            instance is_even: 0.0
+            fn pixel(self) -> vec4 {
+                return mix(
+                    THEME_COLOR_BG_EVEN,
+                    THEME_COLOR_BG_ODD,
+                    self.is_even
+                );
+            }
+        }
+    }
+
+    GitList = {{GitList}}{
+        height: Fill, width: Fill,
+        flow: Down,
+        <DockToolbar> {
+            content = {
+                padding: { right: (THEME_SPACE_2) }
+                spacing: (THEME_SPACE_2)
+                message = <TextInput> {
+                    width: Fill,
+                    empty_message: "Commit message",
+                }
+                commit = <ButtonFlat> { width: Fit, text: "Commit" }
+            }
+        }
+        <View> {
+            height: Fit, width: Fill,
+            padding: <THEME_MSPACE_2> {}
+            spacing: (THEME_SPACE_2)
+            align: { x: 0.0, y: 0.5 }
+            stage_all = <ButtonFlat> { width: Fit, text: "Stage All" }
+            unstage_all = <ButtonFlat> { width: Fit, text: "Unstage All" }
+            refresh = <ButtonFlat> { width: Fit, text: "Refresh" }
+            status = <Label> { width: Fill, draw_text: {color: (THEME_COLOR_TEXT_META)}, text: "" }
+        }
+        list = <PortalList> {
+            grab_key_focus: true
+            drag_scrolling: false
+            height: Fill, width: Fill,
+            flow: Down
+            Header = <GitItem> {
+                title = <Pbold> { width: Fill, margin: 0, padding: 0, text: "" }
+            }
+            File = <GitItem> {
+                padding: { left: 15., top: (THEME_SPACE_1), bottom: (THEME_SPACE_1), right: (THEME_SPACE_2) }
+                status = <P> { width: 15., margin: 0, padding: 0, draw_text: {color: (THEME_COLOR_TEXT_META)}, text: "" }
+                file_name = <LinkLabel> { width: Fill, padding: 0, margin: 0, text: "" }
+                toggle = <LinkLabel> { padding: 0, margin: 0, text: "" }
+            }
+            Empty = <GitItem> {
+                height: 25,
+            }
+        }
+    }
+}
+
+/// A row of the list: the header of the staged or the unstaged changes, or a file in them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum GitRow {
+    Header {staged: bool},
+    File {index: usize, staged: bool},
+}
+
+fn git_rows(entries: &[GitStatusEntry]) -> Vec<GitRow> {
+    let mut rows = Vec::new();
+    for staged in [true, false] {
+        let files: Vec<GitRow> = entries.iter().enumerate().filter(|(_, entry)| {
+            if staged {
+                entry.staged.is_some_and(|status| status != GitFileStatus::Conflicted)
+            }
+            else {
+                entry.unstaged.is_some()
+            }
+        }).map(|(index, _)| GitRow::File {index, staged}).collect();
+        if !files.is_empty() {
+            rows.push(GitRow::Header {staged});
+            rows.extend(files);
+        }
+    }
+    rows
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct GitList{
+    #[deref] view:View
+}
+
+impl GitList{
+    fn draw_entries(&mut self, cx: &mut Cx2d, list:&mut PortalList, entries:&[GitStatusEntry]){
+        let rows = git_rows(entries);
+        list.set_item_range(cx, 0, rows.len());
+        while let Some(item_id) = list.next_visible_item(cx) {
+            let is_even = if item_id & 1 == 0 {1.0} else {0.0};
+            match rows.get(item_id) {
+                Some(GitRow::Header {staged}) => {
+                    let item = list.item(cx, item_id, live_id!(Header)).unwrap().as_view();
+                    item.apply_over(cx, live!{
+                        title = {text: (if *staged {"Staged Changes"} else {"Changes"})}
+                        draw_bg: {is_even: (is_even)}
+                    });
+                    item.draw_all(cx, &mut Scope::empty());
+                }
+                Some(GitRow::File {index, staged}) => {
+                    let entry = &entries[*index];
+                    let status = if *staged {entry.staged} else {entry.unstaged};
+                    let item = list.item(cx, item_id, live_id!(File)).unwrap().as_view();
+                    item.apply_over(cx, live!{
+                        status = {text: (status.map(|status| status.letter()).unwrap_or(""))}
+                        file_name = {text: (&entry.file_name)}
+                        toggle = {text: (if *staged {"unstage"} else {"stage"})}
+                        draw_bg: {is_even: (is_even)}
+                    });
+                    item.draw_all(cx, &mut Scope::empty());
+                }
+                None => {
+                    let item = list.item(cx, item_id, live_id!(Empty)).unwrap().as_view();
+                    item.apply_over(cx, live!{draw_bg: {is_even: (is_even)}});
+                    item.draw_all(cx, &mut Scope::empty());
+                }
+            }
+        }
+    }
+}
+
+impl Widget for GitList {
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope:&mut Scope, walk:Walk)->DrawStep{
+        let git_manager = &scope.data.get::<AppData>().unwrap().git_manager;
+        let status = match &git_manager.message {
+            Some(message) => message.clone(),
+            None => format!("{} changed files", git_manager.entries.len()),
+        };
+        self.view.label(id!(status)).set_text(&status);
+        while let Some(step) = self.view.draw_walk(cx, scope, walk).step(){
+            if let Some(mut list) = step.as_portal_list().borrow_mut(){
+                self.draw_entries(cx, &mut list, &scope.data.get::<AppData>().unwrap().git_manager.entries)
+            }
+        }
+        DrawStep::done()
+    }
+
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope){
+        let list = self.view.portal_list(id!(list));
+        self.view.handle_event(cx, event, scope);
+        let git_manager = &mut scope.data.get_mut::<AppData>().unwrap().git_manager;
+        if let Event::Actions(actions) = event{
+            if self.view.button(id!(commit)).clicked(actions) {
+                let message = self.view.text_input(id!(message)).text();
+                if !message.trim().is_empty() {
+                    git_manager.commit(&message);
+                    self.view.text_input(id!(message)).set_text("");
+                    self.view.redraw(cx);
+                }
+            }
+            if self.view.button(id!(refresh)).clicked(actions) {
+                git_manager.message = None;
+                git_manager.refresh_status();
+            }
+            for staged in [false, true] {
+                let button = if staged {id!(unstage_all)} else {id!(stage_all)};
+                if self.view.button(button).clicked(actions) {
+                    let file_names: Vec<String> = git_rows(&git_manager.entries).into_iter().filter_map(|row| match row {
+                        GitRow::File {index, staged: is_staged} if is_staged == staged => Some(git_manager.entries[index].file_name.clone()),
+                        _ => None
+                    }).collect();
+                    if !file_names.is_empty() {
+                        if staged {git_manager.unstage(&file_names)} else {git_manager.stage(&file_names)}
+                    }
+                }
+            }
+            let rows = git_rows(&git_manager.entries);
+            for (item_id, item) in list.items_with_actions(actions) {
+                let Some(GitRow::File {index, staged}) = rows.get(item_id).copied() else {
+                    continue
+                };
+                let file_name = git_manager.entries[index].file_name.clone();
+                if item.link_label(id!(toggle)).pressed(actions) {
+                    if staged {git_manager.unstage(&[file_name])} else {git_manager.stage(&[file_name])}
+                }
+                else if item.link_label(id!(file_name)).pressed(actions) {
+                    cx.action(AppAction::JumpTo(JumpToFile{
+                        file_name,
+                        line: 0,
+                        column: 0
+                    }));
+                }
+            }
+        }
+    }
+}
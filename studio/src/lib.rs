@@ -4,10 +4,15 @@ pub mod app;
 pub mod app_ui;
 pub mod build_manager;
 pub mod file_system;
+pub mod git;
+pub mod git_list;
 pub mod studio_editor;
 pub mod studio_file_tree;
 pub mod log_list;
+pub mod lsp;
 pub mod run_list;
+pub mod search;
+pub mod search_list;
 pub mod run_view;
 pub mod profiler;
 //pub use makepad_code_editor;
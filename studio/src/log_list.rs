@@ -189,6 +189,12 @@ live_design!{
                 binary = <Label> {draw_text: {color: #5}, width: Fit, margin: {right: 4, top:0, bottom:0}, padding: 0, draw_text: {wrap: Word}}
                 location = <LinkLabel> {padding:0, margin: 0, text: ""}
                 body = <P> {width: Fill, margin: {left: 5, top:0, bottom:0}, padding: 0, draw_text: {wrap: Word}}
+                fixes = <View> {
+                    width: Fit, height: Fit,
+                    spacing: (THEME_SPACE_2)
+                    fix = <LinkLabel> {padding:0, margin: 0, text: "fix"}
+                    fix_file = <LinkLabel> {padding:0, margin: 0, text: "fix file"}
+                }
             }
             Bare = <LogItem> {
                 icon = <LogIcon> {},
@@ -257,6 +263,7 @@ impl LogList{
                             icon = {active_page: (map_level_to_icon(msg.level))},
                             body = {text: (&msg.message)}
                             location = {text: (format!("{}: {}:{}", msg.file_name, msg.start.line_index + 1, msg.start.byte_index + 1))}
+                            fixes = {visible: (!msg.fixes.is_empty())}
                             draw_bg: {is_even: (if is_even {1.0} else {0.0})}
                         });
                         item.draw_all(cx, &mut Scope::empty());
@@ -288,6 +295,14 @@ impl Widget for LogList {
         let data = scope.data.get::<AppData>().unwrap();
         if let Event::Actions(actions) = event{
             for (item_id, item) in log_list.items_with_actions(&actions) {
+                if let Some((_build_id, LogItem::Location(msg))) = data.build_manager.log.get(item_id) {
+                    if item.link_label(id!(fix)).pressed(actions) {
+                        cx.action(AppAction::ApplyFixes(msg.fixes.clone()));
+                    }
+                    if item.link_label(id!(fix_file)).pressed(actions) {
+                        cx.action(AppAction::ApplyFixes(data.build_manager.fixes_in_file(&msg.file_name)));
+                    }
+                }
                 if item.link_label(id!(location)).pressed(&actions) {
                     if let Some((_build_id, log_item)) = data.build_manager.log.get(item_id as usize) {
                         match log_item {
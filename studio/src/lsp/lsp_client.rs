@@ -0,0 +1,455 @@
+use {
+    crate::{
+        makepad_micro_serde::*,
+        makepad_platform::*,
+        makepad_code_editor::text::{Change, Edit, Position, Text},
+        lsp::lsp_protocol::*,
+    },
+    std::{
+        collections::HashMap,
+        fs,
+        io::{self, BufReader, Write},
+        path::Path,
+        process::{Child, Command, Stdio},
+        sync::mpsc::{self, Sender},
+        thread,
+        time::{Duration, Instant},
+    },
+};
+
+/// How to start the language server for files with one of `extensions`.
+#[derive(Clone, Debug)]
+pub struct LspServerConfig {
+    pub language_id: String,
+    pub extensions: Vec<String>,
+    pub command: String,
+    pub args: Vec<String>,
+}
+
+impl LspServerConfig {
+    pub fn rust_analyzer() -> Self {
+        Self {
+            language_id: "rust".to_string(),
+            extensions: vec!["rs".to_string()],
+            command: "rust-analyzer".to_string(),
+            args: Vec::new(),
+        }
+    }
+
+    /// Parses a server given on the command line as `--lsp=<extensions>=<command> [args]`, for
+    /// instance `--lsp=rs=rust-analyzer` or `--lsp=c,h=clangd --background-index`.
+    pub fn from_arg(arg: &str) -> Option<Self> {
+        let (extensions, command) = arg.strip_prefix("--lsp=")?.split_once('=')?;
+        let extensions: Vec<String> = extensions.split(',').map(|ext| ext.trim().to_string()).collect();
+        let mut args = command.split_whitespace().map(|arg| arg.to_string());
+        Some(Self {
+            language_id: language_id_for_extension(&extensions[0]),
+            extensions,
+            command: args.next()?,
+            args: args.collect(),
+        })
+    }
+
+    pub fn handles_path(&self, path: &str) -> bool {
+        Path::new(path)
+            .extension()
+            .is_some_and(|ext| self.extensions.iter().any(|e| ext.eq_ignore_ascii_case(e.as_str())))
+    }
+}
+
+fn language_id_for_extension(ext: &str) -> String {
+    match ext {
+        "rs" => "rust",
+        "c" | "h" => "c",
+        "cpp" | "cc" | "hpp" => "cpp",
+        "js" => "javascript",
+        "ts" => "typescript",
+        "py" => "python",
+        "md" => "markdown",
+        ext => ext,
+    }.to_string()
+}
+
+/// What a response from the server is the answer to.
+#[derive(Clone, Debug)]
+enum LspRequest {
+    Initialize,
+    Shutdown,
+    Completion {uri: String},
+    Definition,
+    References,
+    Hover {uri: String},
+}
+
+/// What came out of a message from the server, for the UI to act on.
+#[derive(Clone, Debug)]
+pub enum LspEvent {
+    Initialized,
+    Diagnostics {uri: String, diagnostics: Vec<LspDiagnostic>},
+    Completion {uri: String, request_id: u64, items: Vec<LspCompletionItem>},
+    Definition {request_id: u64, locations: Vec<LspLocation>},
+    References {request_id: u64, locations: Vec<LspLocation>},
+    Hover {uri: String, request_id: u64, text: String},
+    Message(String),
+    Exited,
+}
+
+// the text we last sent the server for a document, to compute incremental changes against
+struct LspDocument {
+    version: u64,
+    text: Text,
+}
+
+/// A language server running as a child process, talked to over its stdin and stdout.
+///
+/// Requests sent before the server has answered the initialize request are queued. Messages from
+/// the server arrive on `incoming`, and have to be passed to `handle_message`.
+pub struct LspClient {
+    pub config: LspServerConfig,
+    pub incoming: ToUIReceiver<Option<LspMessage>>,
+    child: Option<Child>,
+    outgoing: Sender<Option<String>>,
+    next_id: u64,
+    pending: HashMap<u64, LspRequest>,
+    is_initialized: bool,
+    queued: Vec<LspMessage>,
+    documents: HashMap<String, LspDocument>,
+}
+
+impl LspClient {
+    pub fn start(config: LspServerConfig, root_path: &Path) -> io::Result<Self> {
+        let mut child = Command::new(&config.command)
+            .args(&config.args)
+            .current_dir(root_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let mut stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        let incoming = ToUIReceiver::default();
+        let incoming_sender = incoming.sender();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            while let Ok(Some(body)) = read_message(&mut reader) {
+                match LspMessage::from_json(&body) {
+                    Ok(message) => if incoming_sender.send(Some(message)).is_err() {
+                        return
+                    }
+                    Err(err) => error!("Cannot parse language server message {}", err)
+                }
+            }
+            let _ = incoming_sender.send(None);
+        });
+
+        let (outgoing, outgoing_receiver) = mpsc::channel::<Option<String>>();
+        thread::spawn(move || {
+            // `None` closes stdin, which tells the server we are gone
+            while let Ok(Some(body)) = outgoing_receiver.recv() {
+                if stdin.write_all(encode_message(&body).as_bytes()).is_err() || stdin.flush().is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut client = Self {
+            config,
+            incoming,
+            child: Some(child),
+            outgoing,
+            next_id: 0,
+            pending: HashMap::new(),
+            is_initialized: false,
+            queued: Vec::new(),
+            documents: HashMap::new(),
+        };
+        let root_uri = path_to_uri(root_path);
+        let params = json_object([
+            ("processId", JsonValue::U64(std::process::id() as u64)),
+            ("rootUri", json_string(&root_uri)),
+            ("capabilities", client_capabilities()),
+            ("workspaceFolders", JsonValue::Array(vec![json_object([
+                ("uri", json_string(&root_uri)),
+                ("name", json_string(&root_path.file_name().unwrap_or_default().to_string_lossy())),
+            ])])),
+        ]);
+        let id = client.next_request_id(LspRequest::Initialize);
+        client.send_now(LspMessage::request(id, "initialize", params));
+        Ok(client)
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+
+    /// Handles all messages that have arrived so far.
+    pub fn poll(&mut self) -> Vec<LspEvent> {
+        let mut events = Vec::new();
+        while let Ok(message) = self.incoming.try_recv() {
+            events.extend(self.handle_message(message));
+        }
+        events
+    }
+
+    /// Waits for the next event, for up to `timeout`.
+    pub fn wait_for_event(&mut self, timeout: Duration) -> Option<LspEvent> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let timeout = deadline.checked_duration_since(Instant::now())?;
+            let message = self.incoming.receiver.recv_timeout(timeout).ok()?;
+            if let Some(event) = self.handle_message(message) {
+                return Some(event)
+            }
+        }
+    }
+
+    pub fn handle_message(&mut self, message: Option<LspMessage>) -> Option<LspEvent> {
+        let Some(message) = message else {
+            self.is_initialized = false;
+            return Some(LspEvent::Exited)
+        };
+        match message {
+            LspMessage::Response {id, result} => {
+                let request_id = id.u64()?;
+                let request = self.pending.remove(&request_id)?;
+                let result = match (request, result) {
+                    (LspRequest::Initialize, Err(err)) => {
+                        return Some(LspEvent::Message(format!("{} failed to initialize: {}", self.config.command, err.message)))
+                    }
+                    (_, Err(_)) => return None,
+                    (request, Ok(result)) => (request, result),
+                };
+                match result {
+                    (LspRequest::Initialize, _) => {
+                        self.is_initialized = true;
+                        self.send_now(LspMessage::notification("initialized", json_object([])));
+                        for message in std::mem::take(&mut self.queued) {
+                            self.send_now(message);
+                        }
+                        Some(LspEvent::Initialized)
+                    }
+                    (LspRequest::Shutdown, _) => {
+                        self.send_now(LspMessage::notification("exit", JsonValue::Null));
+                        None
+                    }
+                    (LspRequest::Completion {uri}, result) => Some(LspEvent::Completion {
+                        uri,
+                        request_id,
+                        items: LspCompletionItem::list_from_json(&result),
+                    }),
+                    (LspRequest::Definition, result) => Some(LspEvent::Definition {
+                        request_id,
+                        locations: LspLocation::list_from_json(&result),
+                    }),
+                    (LspRequest::References, result) => Some(LspEvent::References {
+                        request_id,
+                        locations: LspLocation::list_from_json(&result),
+                    }),
+                    (LspRequest::Hover {uri}, result) => Some(LspEvent::Hover {
+                        uri,
+                        request_id,
+                        text: hover_text_from_json(&result)?,
+                    }),
+                }
+            }
+            LspMessage::Request {id, method, params} => {
+                // servers ask us for things we don't keep, like settings, so we answer with nothing
+                let result = match method.as_str() {
+                    "workspace/configuration" => Ok(JsonValue::Array(
+                        params.key("items").and_then(|items| items.array()).map_or(Vec::new(), |items| {
+                            items.iter().map(|_| JsonValue::Null).collect()
+                        })
+                    )),
+                    "client/registerCapability" | "client/unregisterCapability" | "window/workDoneProgress/create" => Ok(JsonValue::Null),
+                    _ => Err(LspError {code: -32601, message: format!("method {} not supported", method)}),
+                };
+                self.send_now(LspMessage::Response {id, result});
+                None
+            }
+            LspMessage::Notification {method, params} => match method.as_str() {
+                "textDocument/publishDiagnostics" => Some(LspEvent::Diagnostics {
+                    uri: params.key("uri")?.string()?.clone(),
+                    diagnostics: params.key("diagnostics")?.array()?.iter().filter_map(LspDiagnostic::from_json).collect(),
+                }),
+                "window/showMessage" => Some(LspEvent::Message(params.key("message")?.string()?.clone())),
+                _ => None,
+            }
+        }
+    }
+
+    pub fn is_open(&self, uri: &str) -> bool {
+        self.documents.contains_key(uri)
+    }
+
+    pub fn did_open(&mut self, uri: &str, text: Text) {
+        self.send(LspMessage::notification("textDocument/didOpen", json_object([
+            ("textDocument", json_object([
+                ("uri", json_string(uri)),
+                ("languageId", json_string(&self.config.language_id)),
+                ("version", JsonValue::U64(0)),
+                ("text", json_string(&text.to_string())),
+            ])),
+        ])));
+        self.documents.insert(uri.to_string(), LspDocument {version: 0, text});
+    }
+
+    /// Sends `edits`, as they came from `Document::add_edit_listener`, as incremental changes.
+    pub fn did_change(&mut self, uri: &str, edits: &[Edit]) {
+        let Some(document) = self.documents.get_mut(uri) else {
+            return
+        };
+        let mut changes = Vec::new();
+        for edit in edits {
+            // every change is relative to the text after the ones before it, as in the protocol
+            let lines = document.text.as_lines();
+            let (start, end, text) = match &edit.change {
+                Change::Insert(position, text) => (*position, *position, text.to_string()),
+                Change::Delete(start, length) => (*start, *start + *length, String::new()),
+            };
+            changes.push(json_object([
+                ("range", LspRange {
+                    start: LspPosition::from_position(lines, start),
+                    end: LspPosition::from_position(lines, end),
+                }.to_json()),
+                ("text", JsonValue::String(text)),
+            ]));
+            document.text.apply_change(edit.change.clone());
+        }
+        if changes.is_empty() {
+            return
+        }
+        document.version += 1;
+        let version = document.version;
+        self.send(LspMessage::notification("textDocument/didChange", json_object([
+            ("textDocument", json_object([
+                ("uri", json_string(uri)),
+                ("version", JsonValue::U64(version)),
+            ])),
+            ("contentChanges", JsonValue::Array(changes)),
+        ])));
+    }
+
+    pub fn did_save(&mut self, uri: &str) {
+        if self.is_open(uri) {
+            self.send(LspMessage::notification("textDocument/didSave", json_object([
+                ("textDocument", json_object([("uri", json_string(uri))])),
+            ])));
+        }
+    }
+
+    pub fn did_close(&mut self, uri: &str) {
+        if self.documents.remove(uri).is_some() {
+            self.send(LspMessage::notification("textDocument/didClose", json_object([
+                ("textDocument", json_object([("uri", json_string(uri))])),
+            ])));
+        }
+    }
+
+    pub fn completion(&mut self, uri: &str, position: Position) -> Option<u64> {
+        self.position_request(LspRequest::Completion {uri: uri.to_string()}, "textDocument/completion", uri, position, [])
+    }
+
+    pub fn definition(&mut self, uri: &str, position: Position) -> Option<u64> {
+        self.position_request(LspRequest::Definition, "textDocument/definition", uri, position, [])
+    }
+
+    pub fn references(&mut self, uri: &str, position: Position) -> Option<u64> {
+        self.position_request(LspRequest::References, "textDocument/references", uri, position, [
+            ("context", json_object([("includeDeclaration", JsonValue::Bool(true))])),
+        ])
+    }
+
+    pub fn hover(&mut self, uri: &str, position: Position) -> Option<u64> {
+        self.position_request(LspRequest::Hover {uri: uri.to_string()}, "textDocument/hover", uri, position, [])
+    }
+
+    /// Converts a position the server sent into one in the text of `uri`, using the text we synced
+    /// if the document is open and the file on disk otherwise.
+    pub fn to_position(&self, uri: &str, position: LspPosition) -> Position {
+        if let Some(document) = self.documents.get(uri) {
+            return position.to_position(document.text.as_lines())
+        }
+        let text: Text = uri_to_path(uri)
+            .and_then(|path| fs::read_to_string(path).ok())
+            .unwrap_or_default()
+            .into();
+        position.to_position(text.as_lines())
+    }
+
+    /// The text of line `line_index` of `uri`, looked up like `to_position` does.
+    pub fn line(&self, uri: &str, line_index: usize) -> Option<String> {
+        if let Some(document) = self.documents.get(uri) {
+            return document.text.as_lines().get(line_index).cloned()
+        }
+        let text = fs::read_to_string(uri_to_path(uri)?).ok()?;
+        text.lines().nth(line_index).map(|line| line.to_string())
+    }
+
+    fn position_request<const N: usize>(
+        &mut self,
+        request: LspRequest,
+        method: &str,
+        uri: &str,
+        position: Position,
+        extra_params: [(&str, JsonValue); N],
+    ) -> Option<u64> {
+        let document = self.documents.get(uri)?;
+        let position = LspPosition::from_position(document.text.as_lines(), position);
+        let JsonValue::Object(mut params) = json_object([
+            ("textDocument", json_object([("uri", json_string(uri))])),
+            ("position", position.to_json()),
+        ]) else {
+            unreachable!()
+        };
+        params.extend(extra_params.into_iter().map(|(key, value)| (key.to_string(), value)));
+        let id = self.next_request_id(request);
+        self.send(LspMessage::request(id, method, JsonValue::Object(params)));
+        Some(id)
+    }
+
+    fn next_request_id(&mut self, request: LspRequest) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.pending.insert(id, request);
+        id
+    }
+
+    fn send(&mut self, message: LspMessage) {
+        if self.is_initialized {
+            self.send_now(message);
+        }
+        else {
+            self.queued.push(message);
+        }
+    }
+
+    fn send_now(&mut self, message: LspMessage) {
+        let _ = self.outgoing.send(Some(message.to_json()));
+    }
+}
+
+impl Drop for LspClient {
+    fn drop(&mut self) {
+        if self.is_initialized {
+            let id = self.next_request_id(LspRequest::Shutdown);
+            self.send_now(LspMessage::request(id, "shutdown", JsonValue::Null));
+            self.send_now(LspMessage::notification("exit", JsonValue::Null));
+        }
+        let _ = self.outgoing.send(None);
+        // give the server a moment to exit by itself before killing it
+        if let Some(mut child) = self.child.take() {
+            thread::spawn(move || {
+                let deadline = Instant::now() + Duration::from_secs(2);
+                while Instant::now() < deadline {
+                    if let Ok(Some(_)) = child.try_wait() {
+                        return
+                    }
+                    thread::sleep(Duration::from_millis(50));
+                }
+                let _ = child.kill();
+                let _ = child.wait();
+            });
+        }
+    }
+}
@@ -0,0 +1,325 @@
+use {
+    crate::{
+        app::AppAction,
+        file_system::file_system::{FileSystem, OpenDoc},
+        build_manager::build_protocol::LogItemLocation,
+        lsp::{
+            lsp_client::{LspClient, LspEvent, LspServerConfig},
+            lsp_protocol::*,
+        },
+        makepad_code_editor::{
+            decoration::{Decoration, DecorationType},
+            history::NewGroup,
+            regex::is_word_char,
+            selection::Affinity,
+            session::SelectionMode,
+            text::{Edit, Position},
+            Session,
+        },
+        makepad_platform::studio::JumpToFile,
+        makepad_widgets::*,
+    },
+    std::{
+        collections::{HashMap, HashSet},
+        path::{Path, PathBuf},
+        sync::mpsc::Receiver,
+    },
+};
+
+/// The id of the decorations for language server diagnostics, to tell them apart from the ones
+/// for build messages.
+pub const LSP_DECORATION_ID: usize = 1;
+
+// a file that is synced with a language server
+struct LspFile {
+    uri: String,
+    language_id: String,
+    edit_receiver: Receiver<Vec<Edit>>,
+}
+
+/// A completion popup in the editor of `tab_id`.
+pub struct LspCompletion {
+    pub tab_id: LiveId,
+    pub file_id: LiveId,
+    pub request_id: u64,
+    pub items: Vec<LspCompletionItem>,
+    pub selected: usize,
+}
+
+impl LspCompletion {
+    /// The items matching the word in front of the cursor.
+    pub fn visible_items(&self, prefix: &str) -> Vec<&LspCompletionItem> {
+        let prefix = prefix.to_lowercase();
+        self.items.iter().filter(|item| item.label.to_lowercase().contains(&prefix)).collect()
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum LspAction {
+    ShowLocations(Vec<LogItemLocation>),
+    ShowMessage(String),
+    None
+}
+
+/// Starts language servers for the files that are opened, keeps them in sync with the edits and
+/// turns what they send back into decorations, completions and jumps.
+#[derive(Default)]
+pub struct LspManager {
+    root_path: PathBuf,
+    configs: Vec<LspServerConfig>,
+    clients: HashMap<String, LspClient>,
+    failed_commands: HashSet<String>,
+    files: HashMap<LiveId, LspFile>,
+    definition_request: Option<u64>,
+    pub completion: Option<LspCompletion>,
+}
+
+impl LspManager {
+    pub fn init(&mut self, path: &Path) {
+        self.root_path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.configs = std::env::args().filter_map(|arg| LspServerConfig::from_arg(&arg)).collect();
+        if std::env::args().any(|arg| arg == "--no-lsp") {
+            self.configs.clear();
+        }
+        else if !self.configs.iter().any(|config| config.language_id == "rust") {
+            self.configs.push(LspServerConfig::rust_analyzer());
+        }
+    }
+
+    /// Syncs a document that was just opened with the language server for its language, starting
+    /// the server if it isn't running yet.
+    pub fn open_file(&mut self, file_id: LiveId, file_system: &FileSystem) {
+        let path = file_system.file_node_path(file_id);
+        let Some(config) = self.configs.iter().find(|config| config.handles_path(&path)) else {
+            return
+        };
+        let Some(OpenDoc::Document(document)) = file_system.open_documents.get(&file_id) else {
+            return
+        };
+        if !self.clients.contains_key(&config.language_id) {
+            if self.failed_commands.contains(&config.command) {
+                return
+            }
+            match LspClient::start(config.clone(), &self.root_path) {
+                Ok(client) => {
+                    self.clients.insert(config.language_id.clone(), client);
+                }
+                Err(err) => {
+                    log!("Cannot start language server {}: {}", config.command, err);
+                    self.failed_commands.insert(config.command.clone());
+                    return
+                }
+            }
+        }
+        let uri = path_to_uri(&self.root_path.join(&path));
+        let client = self.clients.get_mut(&config.language_id).unwrap();
+        if !client.is_open(&uri) {
+            client.did_open(&uri, document.as_text().clone());
+        }
+        self.files.insert(file_id, LspFile {
+            uri,
+            language_id: config.language_id.clone(),
+            edit_receiver: document.add_edit_listener(),
+        });
+    }
+
+    pub fn save_file(&mut self, file_id: LiveId) {
+        self.sync_edits();
+        if let Some(file) = self.files.get(&file_id) {
+            if let Some(client) = self.clients.get_mut(&file.language_id) {
+                client.did_save(&file.uri);
+            }
+        }
+    }
+
+    pub fn request_completion(&mut self, tab_id: LiveId, file_id: LiveId, position: Position) {
+        self.completion = None;
+        if let Some(request_id) = self.position_request(file_id, position, LspClient::completion) {
+            self.completion = Some(LspCompletion {
+                tab_id,
+                file_id,
+                request_id,
+                items: Vec::new(),
+                selected: 0,
+            });
+        }
+    }
+
+    pub fn request_definition(&mut self, file_id: LiveId, position: Position) {
+        self.definition_request = self.position_request(file_id, position, LspClient::definition);
+    }
+
+    pub fn request_references(&mut self, file_id: LiveId, position: Position) {
+        self.position_request(file_id, position, LspClient::references);
+    }
+
+    pub fn request_hover(&mut self, file_id: LiveId, position: Position) {
+        self.position_request(file_id, position, LspClient::hover);
+    }
+
+    /// Replaces the word in front of the cursor with the selected completion.
+    pub fn accept_completion(&mut self, session: &mut Session, prefix: &str) -> bool {
+        let Some(completion) = self.completion.take() else {
+            return false
+        };
+        let Some(item) = completion.visible_items(prefix).get(completion.selected).cloned().cloned() else {
+            return false
+        };
+        let cursor = session.selections()[session.last_added_selection_index().unwrap()].cursor.position;
+        let (start, end) = match (item.replace_range, self.files.get(&completion.file_id)) {
+            (Some(range), Some(file)) => {
+                let client = &self.clients[&file.language_id];
+                (client.to_position(&file.uri, range.start), client.to_position(&file.uri, range.end).max(cursor))
+            }
+            _ => (Position {line_index: cursor.line_index, byte_index: cursor.byte_index - prefix.len()}, cursor),
+        };
+        session.set_selection(start, Affinity::After, SelectionMode::Simple, NewGroup::Yes);
+        session.move_to(end, Affinity::Before, NewGroup::Yes);
+        session.paste(item.insert_text.into());
+        true
+    }
+
+    pub fn handle_event(&mut self, cx: &mut Cx, event: &Event, file_system: &mut FileSystem) {
+        self.sync_edits();
+        if let Event::Signal = event {
+            let mut events = Vec::new();
+            for (language_id, client) in &mut self.clients {
+                events.extend(client.poll().into_iter().map(|event| (language_id.clone(), event)));
+            }
+            for (language_id, event) in events {
+                self.handle_lsp_event(cx, &language_id, event, file_system);
+            }
+        }
+    }
+
+    fn handle_lsp_event(&mut self, cx: &mut Cx, language_id: &str, event: LspEvent, file_system: &mut FileSystem) {
+        match event {
+            LspEvent::Initialized => {}
+            LspEvent::Diagnostics {uri, diagnostics} => {
+                let Some(file_id) = self.uri_to_file_name(&uri).and_then(|file_name| file_system.path_to_file_node_id(&file_name)) else {
+                    return
+                };
+                let client = &self.clients[language_id];
+                file_system.retain_decorations(file_id, |decoration| decoration.id != LSP_DECORATION_ID);
+                for diagnostic in diagnostics {
+                    let ty = match diagnostic.severity {
+                        LspSeverity::Error => DecorationType::Error,
+                        LspSeverity::Warning => DecorationType::Warning,
+                        LspSeverity::Information | LspSeverity::Hint => continue,
+                    };
+                    file_system.add_decoration(file_id, Decoration::new(
+                        LSP_DECORATION_ID,
+                        client.to_position(&uri, diagnostic.range.start),
+                        client.to_position(&uri, diagnostic.range.end),
+                        ty
+                    ));
+                }
+                cx.action(AppAction::RedrawFile(file_id));
+            }
+            LspEvent::Completion {request_id, items, ..} => {
+                if let Some(completion) = &mut self.completion {
+                    if completion.request_id == request_id {
+                        if items.is_empty() {
+                            self.completion = None;
+                            return
+                        }
+                        completion.items = items;
+                        cx.action(AppAction::RedrawFile(completion.file_id));
+                    }
+                }
+            }
+            LspEvent::Definition {request_id, locations} => {
+                if self.definition_request != Some(request_id) {
+                    return
+                }
+                self.definition_request = None;
+                let Some(location) = locations.first() else {
+                    return
+                };
+                let position = self.clients[language_id].to_position(&location.uri, location.range.start);
+                match self.uri_to_file_name(&location.uri) {
+                    Some(file_name) => cx.action(AppAction::JumpTo(JumpToFile {
+                        file_name,
+                        line: position.line_index as u32,
+                        column: position.byte_index as u32,
+                    })),
+                    // definitions outside the project, like in the standard library, can't be opened
+                    None => cx.action(LspAction::ShowMessage(format!(
+                        "Definition is in {}:{}",
+                        uri_to_path(&location.uri).unwrap_or_default().display(),
+                        position.line_index + 1
+                    ))),
+                }
+            }
+            LspEvent::References {locations, ..} => {
+                let client = &self.clients[language_id];
+                let locations = locations.iter().filter_map(|location| {
+                    Some(LogItemLocation {
+                        level: LogLevel::Log,
+                        file_name: self.uri_to_file_name(&location.uri)?,
+                        start: client.to_position(&location.uri, location.range.start),
+                        end: client.to_position(&location.uri, location.range.end),
+                        message: client.line(&location.uri, location.range.start.line).unwrap_or_default().trim().to_string(),
+                        fixes: Vec::new(),
+                    })
+                }).collect();
+                cx.action(LspAction::ShowLocations(locations));
+            }
+            LspEvent::Hover {text, ..} => {
+                cx.action(LspAction::ShowMessage(text));
+            }
+            LspEvent::Message(message) => {
+                cx.action(LspAction::ShowMessage(message));
+            }
+            LspEvent::Exited => {
+                // files are opened with the server again when it is restarted
+                self.clients.remove(language_id);
+                self.files.retain(|_, file| file.language_id != language_id);
+                log!("Language server for {} exited", language_id);
+            }
+        }
+    }
+
+    // sends the edits made since the last call to the servers
+    fn sync_edits(&mut self) {
+        for file in self.files.values() {
+            while let Ok(edits) = file.edit_receiver.try_recv() {
+                if let Some(client) = self.clients.get_mut(&file.language_id) {
+                    client.did_change(&file.uri, &edits);
+                }
+            }
+        }
+    }
+
+    fn position_request(
+        &mut self,
+        file_id: LiveId,
+        position: Position,
+        request: fn(&mut LspClient, &str, Position) -> Option<u64>,
+    ) -> Option<u64> {
+        self.sync_edits();
+        let file = self.files.get(&file_id)?;
+        let client = self.clients.get_mut(&file.language_id)?;
+        request(client, &file.uri, position)
+    }
+
+    // the path of `uri` relative to the root, as the file system names files
+    fn uri_to_file_name(&self, uri: &str) -> Option<String> {
+        let path = uri_to_path(uri)?;
+        let path = path.strip_prefix(&self.root_path).ok()?;
+        Some(path.to_string_lossy().replace('\\', "/"))
+    }
+}
+
+/// The word in front of `position`, which completions are filtered by.
+pub fn completion_prefix(session: &Session, position: Position) -> String {
+    let text = session.document().as_text();
+    let line = &text.as_lines()[position.line_index][..position.byte_index];
+    let start = line
+        .char_indices()
+        .rev()
+        .take_while(|(_, char)| is_word_char(*char))
+        .last()
+        .map_or(line.len(), |(index, _)| index);
+    line[start..].to_string()
+}
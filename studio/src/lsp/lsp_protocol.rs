@@ -0,0 +1,388 @@
+use {
+    crate::{
+        makepad_micro_serde::*,
+        makepad_code_editor::text::Position,
+    },
+    std::{
+        collections::HashMap,
+        io::{self, BufRead},
+        path::{Path, PathBuf},
+    },
+};
+
+// JSON-RPC over stdio as the language server protocol uses it: every message is a JSON body
+// preceded by a Content-Length header.
+
+// anything bigger is a broken or misbehaving server, not a message worth allocating for
+const MAX_CONTENT_LENGTH: usize = 64 * 1024 * 1024;
+
+pub fn encode_message(body: &str) -> String {
+    format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+}
+
+/// Reads the body of the next message, or returns `None` when the stream has ended.
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None)
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let content_length = content_length
+        .filter(|content_length| *content_length <= MAX_CONTENT_LENGTH)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Content-Length too large"))?;
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+#[derive(Clone, Debug)]
+pub enum LspMessage {
+    Request {id: JsonValue, method: String, params: JsonValue},
+    Response {id: JsonValue, result: Result<JsonValue, LspError>},
+    Notification {method: String, params: JsonValue},
+}
+
+#[derive(Clone, Debug)]
+pub struct LspError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl LspMessage {
+    pub fn request(id: u64, method: &str, params: JsonValue) -> Self {
+        Self::Request {id: JsonValue::U64(id), method: method.to_string(), params}
+    }
+
+    pub fn notification(method: &str, params: JsonValue) -> Self {
+        Self::Notification {method: method.to_string(), params}
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let value = JsonValue::deserialize_json(json).map_err(|err| format!("{:?}", err))?;
+        let method = value.key("method").and_then(|method| method.string()).cloned();
+        let params = value.key("params").cloned().unwrap_or(JsonValue::Null);
+        match (value.key("id"), method) {
+            (Some(id), Some(method)) => Ok(Self::Request {id: id.clone(), method, params}),
+            (None, Some(method)) => Ok(Self::Notification {method, params}),
+            (Some(id), None) => {
+                let result = match value.key("error") {
+                    Some(error) => Err(LspError {
+                        code: match error.key("code") {
+                            Some(JsonValue::I64(code)) => *code,
+                            Some(JsonValue::U64(code)) => *code as i64,
+                            _ => 0
+                        },
+                        message: error.key("message").and_then(|message| message.string()).cloned().unwrap_or_default(),
+                    }),
+                    None => Ok(value.key("result").cloned().unwrap_or(JsonValue::Null))
+                };
+                Ok(Self::Response {id: id.clone(), result})
+            }
+            (None, None) => Err("message without id or method".to_string())
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut fields = vec![("jsonrpc", JsonValue::String("2.0".to_string()))];
+        match self {
+            Self::Request {id, method, params} => {
+                fields.push(("id", id.clone()));
+                fields.push(("method", JsonValue::String(method.clone())));
+                fields.push(("params", params.clone()));
+            }
+            Self::Response {id, result} => {
+                fields.push(("id", id.clone()));
+                match result {
+                    Ok(result) => fields.push(("result", result.clone())),
+                    Err(error) => fields.push(("error", json_object([
+                        ("code", JsonValue::I64(error.code)),
+                        ("message", JsonValue::String(error.message.clone())),
+                    ]))),
+                }
+            }
+            Self::Notification {method, params} => {
+                fields.push(("method", JsonValue::String(method.clone())));
+                fields.push(("params", params.clone()));
+            }
+        }
+        JsonValue::Object(fields.into_iter().map(|(key, value)| (key.to_string(), value)).collect()).serialize_json()
+    }
+}
+
+pub fn json_object<const N: usize>(fields: [(&str, JsonValue); N]) -> JsonValue {
+    JsonValue::Object(fields.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+}
+
+pub fn json_string(string: &str) -> JsonValue {
+    JsonValue::String(string.to_string())
+}
+
+/// A position as the protocol counts it, in UTF-16 code units from the start of the line.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct LspPosition {
+    pub line: usize,
+    pub character: usize,
+}
+
+impl LspPosition {
+    pub fn from_position(lines: &[String], position: Position) -> Self {
+        let character = lines.get(position.line_index).map_or(0, |line| {
+            line[..position.byte_index.min(line.len())].encode_utf16().count()
+        });
+        Self {line: position.line_index, character}
+    }
+
+    /// Positions past the end of a line or the text are clamped to it.
+    pub fn to_position(self, lines: &[String]) -> Position {
+        let Some(line) = lines.get(self.line) else {
+            return Position {
+                line_index: lines.len().saturating_sub(1),
+                byte_index: lines.last().map_or(0, |line| line.len()),
+            }
+        };
+        let mut character = 0;
+        let mut byte_index = line.len();
+        for (index, char) in line.char_indices() {
+            if character >= self.character {
+                byte_index = index;
+                break;
+            }
+            character += char.len_utf16();
+        }
+        Position {line_index: self.line, byte_index}
+    }
+
+    pub fn from_json(value: &JsonValue) -> Option<Self> {
+        Some(Self {
+            line: value.key("line")?.u64()? as usize,
+            character: value.key("character")?.u64()? as usize,
+        })
+    }
+
+    pub fn to_json(self) -> JsonValue {
+        json_object([
+            ("line", JsonValue::U64(self.line as u64)),
+            ("character", JsonValue::U64(self.character as u64)),
+        ])
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct LspRange {
+    pub start: LspPosition,
+    pub end: LspPosition,
+}
+
+impl LspRange {
+    pub fn from_json(value: &JsonValue) -> Option<Self> {
+        Some(Self {
+            start: LspPosition::from_json(value.key("start")?)?,
+            end: LspPosition::from_json(value.key("end")?)?,
+        })
+    }
+
+    pub fn to_json(self) -> JsonValue {
+        json_object([("start", self.start.to_json()), ("end", self.end.to_json())])
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LspSeverity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+#[derive(Clone, Debug)]
+pub struct LspDiagnostic {
+    pub range: LspRange,
+    pub severity: LspSeverity,
+    pub message: String,
+}
+
+impl LspDiagnostic {
+    pub fn from_json(value: &JsonValue) -> Option<Self> {
+        Some(Self {
+            range: LspRange::from_json(value.key("range")?)?,
+            // a missing severity is left to the client, we treat it as an error
+            severity: match value.key("severity").and_then(|severity| severity.u64()) {
+                Some(2) => LspSeverity::Warning,
+                Some(3) => LspSeverity::Information,
+                Some(4) => LspSeverity::Hint,
+                _ => LspSeverity::Error,
+            },
+            message: value.key("message")?.string()?.clone(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LspLocation {
+    pub uri: String,
+    pub range: LspRange,
+}
+
+impl LspLocation {
+    /// Parses the result of a definition or references request, which can be a location, a list of
+    /// locations or a list of location links.
+    pub fn list_from_json(value: &JsonValue) -> Vec<Self> {
+        match value {
+            JsonValue::Array(values) => values.iter().filter_map(Self::from_json).collect(),
+            JsonValue::Null => Vec::new(),
+            value => Self::from_json(value).into_iter().collect(),
+        }
+    }
+
+    fn from_json(value: &JsonValue) -> Option<Self> {
+        if let Some(uri) = value.key("targetUri") {
+            return Some(Self {
+                uri: uri.string()?.clone(),
+                range: LspRange::from_json(value.key("targetSelectionRange").or(value.key("targetRange"))?)?,
+            })
+        }
+        Some(Self {
+            uri: value.key("uri")?.string()?.clone(),
+            range: LspRange::from_json(value.key("range")?)?,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LspCompletionItem {
+    pub label: String,
+    pub detail: Option<String>,
+    /// The text to insert, and the range it replaces if the server gave one.
+    pub insert_text: String,
+    pub replace_range: Option<LspRange>,
+}
+
+impl LspCompletionItem {
+    /// Parses the result of a completion request, which can be a list of items or a completion
+    /// list.
+    pub fn list_from_json(value: &JsonValue) -> Vec<Self> {
+        let items = match value.key("items") {
+            Some(items) => items,
+            None => value,
+        };
+        items.array().map_or(Vec::new(), |items| items.iter().filter_map(Self::from_json).collect())
+    }
+
+    fn from_json(value: &JsonValue) -> Option<Self> {
+        let label = value.key("label")?.string()?.clone();
+        let text_edit = value.key("textEdit");
+        let replace_range = text_edit.and_then(|text_edit| {
+            // an insert/replace edit has two ranges, of which we use the replace one
+            LspRange::from_json(text_edit.key("replace").or(text_edit.key("range"))?)
+        });
+        let insert_text = text_edit
+            .and_then(|text_edit| text_edit.key("newText"))
+            .or(value.key("insertText"))
+            .and_then(|text| text.string())
+            .cloned()
+            .unwrap_or_else(|| label.clone());
+        Some(Self {
+            label,
+            detail: value.key("detail").and_then(|detail| detail.string()).cloned(),
+            insert_text,
+            replace_range,
+        })
+    }
+}
+
+/// Returns the text of a hover result, which can be markup content, a marked string or a list of
+/// marked strings.
+pub fn hover_text_from_json(value: &JsonValue) -> Option<String> {
+    fn marked_string(value: &JsonValue) -> Option<String> {
+        match value {
+            JsonValue::String(string) => Some(string.clone()),
+            value => value.key("value")?.string().cloned(),
+        }
+    }
+    let contents = value.key("contents")?;
+    let text = match contents.array() {
+        Some(contents) => contents.iter().filter_map(marked_string).collect::<Vec<_>>().join("\n\n"),
+        None => marked_string(contents)?,
+    };
+    (!text.trim().is_empty()).then_some(text)
+}
+
+pub fn path_to_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => uri.push(byte as char),
+            // keep windows drive letters readable
+            b':' => uri.push(':'),
+            byte => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let mut bytes = Vec::new();
+    let mut iter = path.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        }
+        else {
+            bytes.push(byte);
+        }
+    }
+    let path = String::from_utf8(bytes).ok()?;
+    // file:///C:/dir on windows
+    if path.as_bytes().get(2) == Some(&b':') {
+        return Some(PathBuf::from(&path[1..]))
+    }
+    Some(PathBuf::from(path))
+}
+
+/// The capabilities we tell the server about in the initialize request.
+pub fn client_capabilities() -> JsonValue {
+    json_object([
+        ("textDocument", json_object([
+            ("synchronization", json_object([
+                ("didSave", JsonValue::Bool(true)),
+            ])),
+            // snippets would need a snippet engine in the editor
+            ("completion", json_object([
+                ("completionItem", json_object([
+                    ("snippetSupport", JsonValue::Bool(false)),
+                ])),
+            ])),
+            ("hover", json_object([
+                ("contentFormat", JsonValue::Array(vec![json_string("plaintext")])),
+            ])),
+            ("definition", JsonValue::Object(HashMap::new())),
+            ("references", JsonValue::Object(HashMap::new())),
+            ("publishDiagnostics", JsonValue::Object(HashMap::new())),
+        ])),
+        ("general", json_object([
+            ("positionEncodings", JsonValue::Array(vec![json_string("utf-16")])),
+        ])),
+    ])
+}
@@ -0,0 +1,91 @@
+use {
+    crate::search::glob::Glob,
+    std::{path::Path, rc::Rc},
+};
+
+#[derive(Clone, Debug)]
+struct GitIgnoreRule {
+    glob: Glob,
+    negated: bool,
+    dir_only: bool,
+}
+
+/// The rules of one `.gitignore` file, which apply to the paths below the directory it is in.
+#[derive(Clone, Debug, Default)]
+pub struct GitIgnore {
+    rules: Vec<GitIgnoreRule>,
+}
+
+impl GitIgnore {
+    pub fn parse(source: &str) -> Self {
+        let mut rules = Vec::new();
+        for line in source.lines() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(line) => (true, line),
+                None => (false, line.strip_prefix('\\').unwrap_or(line)),
+            };
+            let (dir_only, line) = match line.strip_suffix('/') {
+                Some(line) => (true, line),
+                None => (false, line),
+            };
+            rules.push(GitIgnoreRule {
+                glob: Glob::new(line),
+                negated,
+                dir_only,
+            });
+        }
+        Self { rules }
+    }
+
+    pub fn load(dir: &Path) -> Option<Self> {
+        std::fs::read_to_string(dir.join(".gitignore")).ok().map(|source| Self::parse(&source))
+    }
+
+    /// Whether `path`, relative to the directory of this file, is ignored, not ignored because of
+    /// a negated rule, or not mentioned at all. Like git, the last rule that matches wins.
+    pub fn matches(&self, path: &str, is_dir: bool) -> Option<bool> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| (is_dir || !rule.dir_only) && rule.glob.is_match(path))
+            .map(|rule| !rule.negated)
+    }
+}
+
+/// The `.gitignore` files that apply in a directory, from the outermost to the innermost.
+#[derive(Clone, Debug, Default)]
+pub struct GitIgnoreStack {
+    // each file with the path of its directory relative to the root, ending in a slash
+    files: Vec<(String, Rc<GitIgnore>)>,
+}
+
+impl GitIgnoreStack {
+    /// The stack for the subdirectory `dir_path`, relative to the root, which has `gitignore`.
+    pub fn push(&self, dir_path: &str, gitignore: Option<GitIgnore>) -> Self {
+        let mut stack = self.clone();
+        if let Some(gitignore) = gitignore {
+            let prefix = if dir_path.is_empty() {
+                String::new()
+            } else {
+                format!("{}/", dir_path)
+            };
+            stack.files.push((prefix, Rc::new(gitignore)));
+        }
+        stack
+    }
+
+    pub fn is_ignored(&self, path: &str, is_dir: bool) -> bool {
+        for (prefix, gitignore) in self.files.iter().rev() {
+            if let Some(relative_path) = path.strip_prefix(prefix.as_str()) {
+                if let Some(is_ignored) = gitignore.matches(relative_path, is_dir) {
+                    return is_ignored;
+                }
+            }
+        }
+        false
+    }
+}
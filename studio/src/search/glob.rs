@@ -0,0 +1,139 @@
+/// A glob pattern over `/` separated paths, as used for `.gitignore` rules and the include and
+/// exclude filters of the search panel.
+///
+/// `*` matches within a path segment, `**` as a whole segment across segments, `?` any one char
+/// but `/`, and `[...]` one char from a class like `[a-z]` or `[!0-9]`. A backslash matches the
+/// char after it as is.
+#[derive(Clone, Debug)]
+pub struct Glob {
+    pattern: Vec<char>,
+    // patterns without a slash match the last path segment rather than the whole path
+    matches_name: bool,
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Self {
+        let pattern = pattern.trim();
+        let matches_name = !pattern.contains('/');
+        Self {
+            pattern: pattern.trim_start_matches('/').chars().collect(),
+            matches_name,
+        }
+    }
+
+    /// Parses a comma separated list of globs, ignoring empty entries.
+    pub fn list(patterns: &str) -> Vec<Self> {
+        patterns
+            .split(',')
+            .filter(|pattern| !pattern.trim().is_empty())
+            .map(Self::new)
+            .collect()
+    }
+
+    pub fn is_match(&self, path: &str) -> bool {
+        let path = if self.matches_name {
+            path.rsplit('/').next().unwrap_or(path)
+        } else {
+            path
+        };
+        let path: Vec<char> = path.chars().collect();
+        glob_match(&self.pattern, &path)
+    }
+}
+
+// Matches left to right, and on a mismatch goes back to the last `*` to let it take one more
+// char, or when that would leave its segment to the last `**`. Only those two are remembered, as
+// a later star can take whatever an earlier one could, so it never backtracks further. For that
+// `**` is only special as a whole segment, elsewhere it's a `*` like in .gitignore files.
+fn glob_match(pattern: &[char], path: &[char]) -> bool {
+    // the pattern index after the last `*` and the path index it matched up to, same for `**`
+    let mut star: Option<(usize, usize)> = None;
+    let mut globstar: Option<(usize, usize)> = None;
+    let (mut p, mut s) = (0, 0);
+    while p < pattern.len() || s < path.len() {
+        let char = path.get(s).copied();
+        // how far the pattern moves on when it matches the char at `s`
+        let step = match pattern.get(p) {
+            None => None,
+            Some('*') if pattern.get(p + 1) == Some(&'*')
+                && (p == 0 || pattern[p - 1] == '/')
+                && matches!(pattern.get(p + 2), None | Some('/')) => {
+                // `**/` also matches no directories at all
+                let len = if p + 2 < pattern.len() { 3 } else { 2 };
+                globstar = Some((p + len, s));
+                star = None;
+                p += len;
+                continue;
+            }
+            Some('*') => {
+                star = Some((p + 1, s));
+                p += 1;
+                continue;
+            }
+            Some('?') => char.filter(|char| *char != '/').map(|_| 1),
+            Some('[') => match match_class(&pattern[p + 1..], char) {
+                Some((is_match, len)) => (is_match && char.is_some()).then_some(1 + len),
+                // a `[` without a closing `]` is just a char
+                None => (char == Some('[')).then_some(1),
+            },
+            Some('\\') if p + 1 < pattern.len() => (char == Some(pattern[p + 1])).then_some(2),
+            Some(pattern_char) => (char == Some(*pattern_char)).then_some(1),
+        };
+        if let Some(step) = step {
+            p += step;
+            s += 1;
+            continue;
+        }
+        if let Some((star_p, star_s)) = star {
+            if path.get(star_s).is_some_and(|char| *char != '/') {
+                star = Some((star_p, star_s + 1));
+                p = star_p;
+                s = star_s + 1;
+                continue;
+            }
+        }
+        if let Some((globstar_p, globstar_s)) = globstar {
+            // `**/` has to take whole directories
+            let next = if pattern[globstar_p - 1] == '/' {
+                path[globstar_s..].iter().position(|char| *char == '/').map(|index| globstar_s + index + 1)
+            } else {
+                (globstar_s < path.len()).then_some(globstar_s + 1)
+            };
+            if let Some(next) = next {
+                globstar = Some((globstar_p, next));
+                star = None;
+                p = globstar_p;
+                s = next;
+                continue;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+// matches `char` against the class at the start of `pattern`, just after the `[`, and returns
+// whether it matched and how long the class is up to and including the `]`
+fn match_class(pattern: &[char], char: Option<char>) -> Option<(bool, usize)> {
+    let negated = matches!(pattern.first(), Some('!' | '^'));
+    let mut index = if negated { 1 } else { 0 };
+    let mut is_match = false;
+    let mut is_first = true;
+    loop {
+        let start = *pattern.get(index)?;
+        // a `]` right at the start is part of the class
+        if start == ']' && !is_first {
+            break;
+        }
+        is_first = false;
+        if pattern.get(index + 1) == Some(&'-') && pattern.get(index + 2).is_some_and(|end| *end != ']') {
+            let end = pattern[index + 2];
+            is_match |= char.is_some_and(|char| start <= char && char <= end);
+            index += 3;
+        } else {
+            is_match |= char == Some(start);
+            index += 1;
+        }
+    }
+    Some((is_match != negated && char != Some('/'), index + 1))
+}
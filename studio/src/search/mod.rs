@@ -0,0 +1,3 @@
+pub mod gitignore;
+pub mod glob;
+pub mod search_manager;
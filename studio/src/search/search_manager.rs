@@ -0,0 +1,286 @@
+use {
+    crate::{
+        app::AppAction,
+        file_system::file_system::FileSystem,
+        makepad_code_editor::{
+            search::{SearchError, SearchQuery, Searcher},
+            text::{Position, Text},
+        },
+        makepad_widgets::*,
+        search::{
+            gitignore::{GitIgnore, GitIgnoreStack},
+            glob::Glob,
+        },
+    },
+    std::{
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+    },
+};
+
+// files larger than this are not searched
+const MAX_FILE_SIZE: u64 = 4 * 1024 * 1024;
+// how much of a file is looked at to tell if it is binary
+const BINARY_CHECK_LEN: usize = 8000;
+
+/// What the search panel looks for: a query, and comma separated globs for the files to include
+/// and exclude. An empty include list includes every file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FileSearchQuery {
+    pub query: SearchQuery,
+    pub include: String,
+    pub exclude: String,
+}
+
+/// A match in a line. `start` and `end` are byte indices into `line`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchMatch {
+    pub line_index: usize,
+    pub start: usize,
+    pub end: usize,
+    pub line: String,
+}
+
+/// The matches in one file, whose name is relative to the root of the search.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchFileResult {
+    pub file_name: String,
+    pub matches: Vec<SearchMatch>,
+}
+
+pub enum SearchMessage {
+    File {search_id: u64, result: SearchFileResult},
+    Done {search_id: u64},
+    Error {search_id: u64, error: SearchError},
+}
+
+/// A row of the results list: a file, or a match in the file with that index.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SearchRow {
+    File(usize),
+    Match(usize, usize),
+}
+
+/// Searches the files under the root on a background thread and collects the results as they
+/// stream in.
+#[derive(Default)]
+pub struct SearchManager {
+    root_path: PathBuf,
+    search_id: u64,
+    cancel: Arc<AtomicBool>,
+    receiver: ToUIReceiver<SearchMessage>,
+    pub query: FileSearchQuery,
+    // shown instead of the result count, for errors in the query and after replacing
+    pub message: Option<String>,
+    pub results: Vec<SearchFileResult>,
+    pub rows: Vec<SearchRow>,
+    pub is_searching: bool,
+}
+
+impl SearchManager {
+    pub fn init(&mut self, path: &Path) {
+        self.root_path = path.to_path_buf();
+    }
+
+    /// Starts a search for `query`, cancelling the one that is running.
+    pub fn search(&mut self, query: FileSearchQuery) {
+        self.clear();
+        self.query = query.clone();
+        if query.query.pattern.is_empty() {
+            return
+        }
+        let mut searcher = match Searcher::new(&query.query) {
+            Ok(searcher) => searcher,
+            Err(error) => {
+                self.message = Some(error.to_string());
+                return
+            }
+        };
+        self.is_searching = true;
+        let search_id = self.search_id;
+        let cancel = self.cancel.clone();
+        let sender = self.receiver.sender();
+        let root_path = self.root_path.clone();
+        std::thread::spawn(move || {
+            let result = search_files(&root_path, &query, &mut searcher, &cancel, &mut |result| {
+                let _ = sender.send(SearchMessage::File {search_id, result});
+            });
+            if let Err(error) = result {
+                let _ = sender.send(SearchMessage::Error {search_id, error});
+            }
+            let _ = sender.send(SearchMessage::Done {search_id});
+        });
+    }
+
+    /// Cancels the running search and forgets the results.
+    pub fn clear(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        self.cancel = Arc::new(AtomicBool::new(false));
+        self.search_id += 1;
+        self.message = None;
+        self.results.clear();
+        self.rows.clear();
+        self.is_searching = false;
+    }
+
+    pub fn match_count(&self) -> usize {
+        self.results.iter().map(|result| result.matches.len()).sum()
+    }
+
+    pub fn handle_event(&mut self, cx: &mut Cx, event: &Event) {
+        if let Event::Signal = event {
+            let mut changed = false;
+            while let Ok(message) = self.receiver.try_recv() {
+                match message {
+                    SearchMessage::File {search_id, result} if search_id == self.search_id => {
+                        let file_index = self.results.len();
+                        self.rows.push(SearchRow::File(file_index));
+                        self.rows.extend((0..result.matches.len()).map(|match_index| SearchRow::Match(file_index, match_index)));
+                        self.results.push(result);
+                        changed = true;
+                    }
+                    SearchMessage::Done {search_id} if search_id == self.search_id => {
+                        self.is_searching = false;
+                        changed = true;
+                    }
+                    SearchMessage::Error {search_id, error} if search_id == self.search_id => {
+                        self.message = Some(error.to_string());
+                        changed = true;
+                    }
+                    // left over from a search that was cancelled
+                    _ => ()
+                }
+            }
+            if changed {
+                cx.action(AppAction::RedrawSearch);
+            }
+        }
+    }
+
+    /// Replaces every match of the current query in every file that has one with `replacement`,
+    /// which can refer to groups for regex queries. Files are searched again as they are now, so
+    /// edits made since the search are taken into account. Returns the number of matches and
+    /// files that were replaced. Files the search runs out of steps on are left alone.
+    pub fn replace_all(&mut self, file_system: &mut FileSystem, replacement: &str) -> (usize, usize) {
+        let Ok(mut searcher) = Searcher::new(&self.query.query) else {
+            return (0, 0)
+        };
+        let file_names: Vec<String> = self.results.iter().map(|result| result.file_name.clone()).collect();
+        let mut replaced = (0, 0);
+        for file_name in file_names {
+            let count = file_system.replace_in_file(&file_name, |lines| {
+                let matches = searcher.find_all_in_lines(lines).unwrap_or_default();
+                matches.into_iter().map(|(line_index, captures)| {
+                    let range = captures.range();
+                    (
+                        Position {line_index, byte_index: range.start},
+                        Position {line_index, byte_index: range.end},
+                        Text::from(searcher.expand_replacement(&lines[line_index], &captures, replacement).as_str()),
+                    )
+                }).collect()
+            });
+            if count > 0 {
+                replaced.0 += count;
+                replaced.1 += 1;
+            }
+        }
+        self.clear();
+        self.message = Some(format!("Replaced {} matches in {} files", replaced.0, replaced.1));
+        replaced
+    }
+}
+
+/// Calls `f` with the matches in every file under `root_path` that has any and is not ignored,
+/// binary or filtered out by the include and exclude globs of `query`, until `cancel` is set.
+/// Stops with an error at the first file the search runs out of steps on.
+pub fn search_files(root_path: &Path, query: &FileSearchQuery, searcher: &mut Searcher, cancel: &AtomicBool, f: &mut dyn FnMut(SearchFileResult)) -> Result<(), SearchError> {
+    let include = Glob::list(&query.include);
+    let exclude = Glob::list(&query.exclude);
+    let mut result = Ok(());
+    search_dir(root_path, "", &GitIgnoreStack::default(), &mut |file_name, path| {
+        if cancel.load(Ordering::Relaxed) {
+            return false
+        }
+        if (!include.is_empty() && !include.iter().any(|glob| glob.is_match(file_name)))
+            || exclude.iter().any(|glob| glob.is_match(file_name)) {
+            return true
+        }
+        if let Some(matches) = search_file(path, searcher) {
+            if searcher.is_too_complex() {
+                result = Err(SearchError::TooComplex);
+                return false
+            }
+            if !matches.is_empty() {
+                f(SearchFileResult {file_name: file_name.to_string(), matches});
+            }
+        }
+        true
+    });
+    result
+}
+
+// calls `f` with the name relative to the root and the path of every file under `dir` that is
+// not ignored, until it returns false
+fn search_dir(dir: &Path, dir_name: &str, gitignores: &GitIgnoreStack, f: &mut dyn FnMut(&str, &Path) -> bool) -> bool {
+    let gitignores = gitignores.push(dir_name, GitIgnore::load(dir));
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return true
+    };
+    let mut entries: Vec<_> = entries.filter_map(|entry| entry.ok()).collect();
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let Ok(name) = entry.file_name().into_string() else {
+            continue
+        };
+        let Ok(file_type) = entry.file_type() else {
+            continue
+        };
+        // skip what the file tree skips, so every result can be opened
+        if name.starts_with('.') || file_type.is_dir() && name == "target" || file_type.is_symlink() {
+            continue
+        }
+        let file_name = if dir_name.is_empty() {name} else {format!("{}/{}", dir_name, name)};
+        let is_dir = file_type.is_dir();
+        if gitignores.is_ignored(&file_name, is_dir) {
+            continue
+        }
+        let keep_going = if is_dir {
+            search_dir(&entry.path(), &file_name, &gitignores, f)
+        }
+        else {
+            f(&file_name, &entry.path())
+        };
+        if !keep_going {
+            return false
+        }
+    }
+    true
+}
+
+// the matches in the file at `path`, or none if it is binary or too large to search
+fn search_file(path: &Path, searcher: &mut Searcher) -> Option<Vec<SearchMatch>> {
+    if std::fs::metadata(path).ok()?.len() > MAX_FILE_SIZE {
+        return None
+    }
+    let data = std::fs::read(path).ok()?;
+    if data[..data.len().min(BINARY_CHECK_LEN)].contains(&0) {
+        return None
+    }
+    let data = String::from_utf8(data).ok()?;
+    searcher.set_budget(data.len());
+    let mut matches = Vec::new();
+    for (line_index, line) in data.lines().enumerate() {
+        searcher.for_each_in_line(line, |range| {
+            matches.push(SearchMatch {
+                line_index,
+                start: range.start,
+                end: range.end,
+                line: line.to_string(),
+            })
+        });
+    }
+    Some(matches)
+}
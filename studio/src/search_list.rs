@@ -0,0 +1,299 @@
+use {
+    crate::{
+        app::{AppAction, AppData},
+        makepad_code_editor::search::SearchQuery,
+        makepad_platform::studio::JumpToFile,
+        makepad_widgets::*,
+        search::search_manager::{FileSearchQuery, SearchManager, SearchRow},
+    },
+};
+
+live_design!{
+    import makepad_draw::shader::std::*;
+    import makepad_widgets::base::*;
+    import makepad_widgets::theme_desktop_dark::*;
+
+    SearchOption = <CheckBoxCustom> {
+        text:""
+        draw_check: { check_type: None }
+        draw_icon: {
+            color: (THEME_COLOR_D_3),
+            color_active: (THEME_COLOR_U_5),
+        }
+    }
+
+    SearchItem = <View> {
+        height: Fit, width: Fill
+        padding: <THEME_MSPACE_1> {}
+        spacing: (THEME_SPACE_2)
+        align: { x: 0.0, y: 0.0 }
+        show_bg: true,
+        cursor: Hand
+        draw_bg: {
+            instance is_even: 0.0
+            instance hover: 0.0
+            fn pixel(self) -> vec4 {
+                return mix(
+                    mix(
+                        THEME_COLOR_BG_EVEN,
+                        THEME_COLOR_BG_ODD,
+                        self.is_even
+                    ),
+                    THEME_COLOR_CTRL_HOVER,
+                    self.hover
+                );
+            }
+        }
+        animator: {
+            ignore_missing: true,
+            hover = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.1}}
+                    apply: {
+                        draw_bg: {hover: 0.0}
+                    }
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {
+                        draw_bg: {hover: 1.0}
+                    },
+                }
+            }
+        }
+    }
+
+    SearchList = {{SearchList}}{
+        height: Fill, width: Fill,
+        flow: Down,
+        <DockToolbar> {
+            content = {
+                padding: { right: (THEME_SPACE_2) }
+                spacing: (THEME_SPACE_2)
+                query = <TextInput> {
+                    width: Fill,
+                    empty_message: "Search",
+                }
+                case_sensitive = <SearchOption> {
+                    icon_walk: {width: 14.}
+                    draw_icon: {svg_file: dep("crate://self/resources/icons/icon_search_case_sensitive.svg")}
+                }
+                whole_word = <SearchOption> {
+                    icon_walk: {width: 16.}
+                    draw_icon: {svg_file: dep("crate://self/resources/icons/icon_search_full_word.svg")}
+                }
+                regex = <SearchOption> {
+                    icon_walk: {width: 12.}
+                    draw_icon: {svg_file: dep("crate://self/resources/icons/icon_search_regex.svg")}
+                }
+            }
+        }
+        <View> {
+            height: Fit, width: Fill,
+            flow: Down,
+            padding: <THEME_MSPACE_2> {}
+            spacing: (THEME_SPACE_1)
+            <View> {
+                height: Fit, width: Fill,
+                spacing: (THEME_SPACE_2)
+                align: { x: 0.0, y: 0.5 }
+                replace = <TextInput> {
+                    width: Fill,
+                    empty_message: "Replace",
+                }
+                replace_all = <ButtonFlat> { width: Fit, text: "Replace All" }
+            }
+            <View> {
+                height: Fit, width: Fill,
+                spacing: (THEME_SPACE_2)
+                include = <TextInput> {
+                    width: Fill,
+                    empty_message: "Files to include, like src/**, *.rs",
+                }
+                exclude = <TextInput> {
+                    width: Fill,
+                    empty_message: "Files to exclude",
+                }
+            }
+            status = <Label> { width: Fill, draw_text: {color: (THEME_COLOR_TEXT_META)}, text: "" }
+        }
+        list = <PortalList> {
+            grab_key_focus: true
+            drag_scrolling: false
+            height: Fill, width: Fill,
+            flow: Down
+            File = <SearchItem> {
+                file_name = <Pbold> { width: Fill, margin: 0, padding: 0, text: "" }
+                count = <P> { width: Fit, margin: 0, padding: 0, draw_text: {color: (THEME_COLOR_TEXT_META)}, text: "" }
+            }
+            Match = <SearchItem> {
+                padding: { left: 20., top: (THEME_SPACE_1), bottom: (THEME_SPACE_1), right: (THEME_SPACE_1) }
+                spacing: 0.
+                line_number = <P> { width: 40., margin: 0, padding: 0, draw_text: {color: (THEME_COLOR_TEXT_META)}, text: "" }
+                before = <P> { width: Fit, margin: 0, padding: 0, text: "" }
+                found = <Pbold> { width: Fit, margin: 0, padding: 0, draw_text: {color: (THEME_COLOR_U_5)}, text: "" }
+                after = <P> { width: Fit, margin: 0, padding: 0, text: "" }
+            }
+            Empty = <SearchItem> {
+                cursor: Default
+                height: 25,
+            }
+        }
+    }
+}
+
+// how much of a line is shown on either side of a match
+const MAX_CONTEXT_LEN: usize = 60;
+
+#[derive(Live, LiveHook, Widget)]
+pub struct SearchList{
+    #[deref] view:View
+}
+
+impl SearchList{
+    fn file_search_query(&mut self, cx: &Cx) -> FileSearchQuery {
+        FileSearchQuery {
+            query: SearchQuery {
+                pattern: self.view.text_input(id!(query)).text(),
+                case_sensitive: self.view.check_box(id!(case_sensitive)).selected(cx),
+                whole_word: self.view.check_box(id!(whole_word)).selected(cx),
+                regex: self.view.check_box(id!(regex)).selected(cx),
+            },
+            include: self.view.text_input(id!(include)).text(),
+            exclude: self.view.text_input(id!(exclude)).text(),
+        }
+    }
+
+    fn draw_results(&mut self, cx: &mut Cx2d, list:&mut PortalList, search_manager:&SearchManager){
+        list.set_item_range(cx, 0, search_manager.rows.len());
+        while let Some(item_id) = list.next_visible_item(cx) {
+            let is_even = if item_id & 1 == 0 {1.0} else {0.0};
+            match search_manager.rows.get(item_id) {
+                Some(SearchRow::File(file_index)) => {
+                    let result = &search_manager.results[*file_index];
+                    let item = list.item(cx, item_id, live_id!(File)).unwrap().as_view();
+                    item.apply_over(cx, live!{
+                        file_name = {text: (&result.file_name)}
+                        count = {text: (format!("{}", result.matches.len()))}
+                        draw_bg: {is_even: (is_even)}
+                    });
+                    item.draw_all(cx, &mut Scope::empty());
+                }
+                Some(SearchRow::Match(file_index, match_index)) => {
+                    let found = &search_manager.results[*file_index].matches[*match_index];
+                    let before = context_before(&found.line[..found.start]);
+                    let after = context_after(&found.line[found.end..]);
+                    let item = list.item(cx, item_id, live_id!(Match)).unwrap().as_view();
+                    item.apply_over(cx, live!{
+                        line_number = {text: (format!("{}", found.line_index + 1))}
+                        before = {text: (before)}
+                        found = {text: (&found.line[found.start..found.end])}
+                        after = {text: (after)}
+                        draw_bg: {is_even: (is_even)}
+                    });
+                    item.draw_all(cx, &mut Scope::empty());
+                }
+                None => {
+                    let item = list.item(cx, item_id, live_id!(Empty)).unwrap().as_view();
+                    item.apply_over(cx, live!{draw_bg: {is_even: (is_even)}});
+                    item.draw_all(cx, &mut Scope::empty());
+                }
+            }
+        }
+    }
+}
+
+fn context_before(text: &str) -> &str {
+    let text = text.trim_start();
+    match text.char_indices().rev().nth(MAX_CONTEXT_LEN) {
+        Some((index, char)) => &text[index + char.len_utf8()..],
+        None => text,
+    }
+}
+
+fn context_after(text: &str) -> &str {
+    match text.char_indices().nth(MAX_CONTEXT_LEN) {
+        Some((index, _)) => &text[..index],
+        None => text,
+    }
+}
+
+fn status_text(search_manager: &SearchManager) -> String {
+    if let Some(message) = &search_manager.message {
+        return message.clone()
+    }
+    if search_manager.query.query.pattern.is_empty() {
+        return String::new()
+    }
+    let status = format!("{} results in {} files", search_manager.match_count(), search_manager.results.len());
+    if search_manager.is_searching {
+        format!("{}, searching...", status)
+    }
+    else {
+        status
+    }
+}
+
+impl Widget for SearchList {
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope:&mut Scope, walk:Walk)->DrawStep{
+        let search_manager = &scope.data.get::<AppData>().unwrap().search_manager;
+        self.view.label(id!(status)).set_text(&status_text(search_manager));
+        while let Some(step) = self.view.draw_walk(cx, scope, walk).step(){
+            if let Some(mut list) = step.as_portal_list().borrow_mut(){
+                self.draw_results(cx, &mut list, &scope.data.get::<AppData>().unwrap().search_manager)
+            }
+        }
+        DrawStep::done()
+    }
+
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope){
+        let list = self.view.portal_list(id!(list));
+        self.view.handle_event(cx, event, scope);
+        let data = scope.data.get_mut::<AppData>().unwrap();
+        if let Event::Actions(actions) = event{
+            let query_changed = self.view.text_input(id!(query)).changed(actions).is_some()
+                || self.view.text_input(id!(include)).changed(actions).is_some()
+                || self.view.text_input(id!(exclude)).changed(actions).is_some()
+                || self.view.check_box(id!(case_sensitive)).changed(actions).is_some()
+                || self.view.check_box(id!(whole_word)).changed(actions).is_some()
+                || self.view.check_box(id!(regex)).changed(actions).is_some();
+            if query_changed {
+                data.search_manager.search(self.file_search_query(cx));
+                list.set_first_id_and_scroll(0, 0.0);
+                self.view.redraw(cx);
+            }
+            if self.view.button(id!(replace_all)).clicked(actions) {
+                cx.action(AppAction::ReplaceInFiles(self.view.text_input(id!(replace)).text()));
+            }
+            for (item_id, item) in list.items_with_actions(actions) {
+                if !item.as_view().finger_up(actions).is_some_and(|fe| fe.is_over) {
+                    continue
+                }
+                let (file_index, line, column) = match data.search_manager.rows.get(item_id) {
+                    Some(SearchRow::File(file_index)) => (*file_index, 0, 0),
+                    Some(SearchRow::Match(file_index, match_index)) => {
+                        let found = &data.search_manager.results[*file_index].matches[*match_index];
+                        (*file_index, found.line_index, found.start)
+                    }
+                    None => continue
+                };
+                cx.action(AppAction::JumpTo(JumpToFile{
+                    file_name: data.search_manager.results[file_index].file_name.clone(),
+                    line: line as u32,
+                    column: column as u32
+                }));
+            }
+        }
+    }
+}
+
+impl SearchListRef{
+    pub fn reset_scroll(&self, cx:&mut Cx){
+        if let Some(mut inner) = self.borrow_mut() {
+            let list = inner.view.portal_list(id!(list));
+            list.set_first_id_and_scroll(0,0.0);
+            list.redraw(cx);
+        }
+    }
+}
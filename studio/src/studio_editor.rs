@@ -1,9 +1,10 @@
-
 use {
     crate::{
-        app::{AppData},
+        app::{AppData, AppAction},
+        lsp::lsp_manager::{completion_prefix, LspManager},
         makepad_widgets::*,
-        makepad_code_editor::CodeEditor,
+        makepad_code_editor::{CodeEditor, Session},
+        makepad_code_editor::text::Position,
     },
     std::{
         env,
@@ -11,17 +12,35 @@ use {
 };
 
 live_design!{
+    import makepad_draw::shader::std::*;
+    import makepad_widgets::theme_desktop_dark::*;
     import makepad_code_editor::code_editor::CodeEditor;
-    
+
     StudioEditor = {{StudioEditor}}{
         editor: <CodeEditor>{
         }
+        draw_completion_bg: {
+            color: (THEME_COLOR_BG_ODD)
+        }
+        draw_completion_selected: {
+            color: (THEME_COLOR_CTRL_SELECTED)
+        }
+        draw_completion_text: {
+            text_style: <THEME_FONT_CODE> {}
+            color: (THEME_COLOR_TEXT_DEFAULT)
+        }
     }
-} 
- 
-#[derive(Live, LiveHook, Widget)] 
+}
+
+// the most completions the popup shows at once
+const MAX_VISIBLE_COMPLETIONS: usize = 10;
+
+#[derive(Live, LiveHook, Widget)]
 pub struct StudioEditor{
-    #[wrap] #[live] pub editor: CodeEditor
+    #[wrap] #[live] pub editor: CodeEditor,
+    #[live] draw_completion_bg: DrawColor,
+    #[live] draw_completion_selected: DrawColor,
+    #[live] draw_completion_text: DrawText,
 }
 
 impl Widget for StudioEditor {
@@ -31,19 +50,147 @@ impl Widget for StudioEditor {
         let app_scope = scope.data.get_mut::<AppData>().unwrap();
         if let Some(session) = app_scope.file_system.get_session_mut(session_id){
             self.editor.draw_walk_editor(cx, session, walk);
+            if let Some(completion) = &app_scope.lsp_manager.completion {
+                if completion.tab_id == session_id {
+                    let prefix = completion_prefix(session, cursor_position(session));
+                    let items = completion.visible_items(&prefix);
+                    if let Some(cursor_rect) = self.editor.cursor_rect() {
+                        self.draw_completion(cx, cursor_rect, &items.iter().map(|item| item.label.as_str()).collect::<Vec<_>>(), completion.selected);
+                    }
+                }
+            }
         }
         DrawStep::done()
     }
-    
+
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope){
         let session_id = scope.path.from_end(1);
         let data = scope.data.get_mut::<AppData>().unwrap();
         let uid = self.widget_uid();
+        let Some(&file_id) = data.file_system.tab_id_to_file_node_id.get(&session_id) else {
+            return
+        };
+        // quick fix the build messages at the cursor, or with shift all of them in the file
+        if let Event::KeyDown(KeyEvent {key_code: KeyCode::Period, modifiers, ..}) = event {
+            if (modifiers.control || modifiers.logo) && cx.has_key_focus(self.editor.area()) {
+                let file_name = data.file_system.file_node_path(file_id);
+                let fixes = if modifiers.shift {
+                    data.build_manager.fixes_in_file(&file_name)
+                }
+                else if let Some(session) = data.file_system.get_session_mut(session_id) {
+                    data.build_manager.fixes_at(&file_name, cursor_position(session))
+                }
+                else {
+                    Vec::new()
+                };
+                if !fixes.is_empty() {
+                    cx.action(AppAction::ApplyFixes(fixes));
+                }
+                return
+            }
+        }
         if let Some(session) = data.file_system.get_session_mut(session_id){
+            if let Event::KeyDown(ke) = event {
+                if cx.has_key_focus(self.editor.area()) && self.handle_lsp_key(cx, ke, session_id, file_id, session, &mut data.lsp_manager) {
+                    data.file_system.handle_sessions();
+                    return
+                }
+            }
             for action in self.editor.handle_event(cx, event, session){
                 cx.widget_action(uid, &scope.path, action);
             }
+            // typing refines the completions, moving the cursor away closes them
+            if let Some(completion) = &mut data.lsp_manager.completion {
+                if completion.tab_id == session_id {
+                    match event {
+                        Event::TextInput(_) | Event::KeyDown(KeyEvent {key_code: KeyCode::Backspace, ..}) => {
+                            completion.selected = 0;
+                            self.editor.redraw(cx);
+                        }
+                        Event::KeyDown(KeyEvent {
+                            key_code: KeyCode::ArrowLeft | KeyCode::ArrowRight | KeyCode::Home | KeyCode::End | KeyCode::PageUp | KeyCode::PageDown,
+                            ..
+                        }) | Event::MouseDown(_) => {
+                            data.lsp_manager.completion = None;
+                            self.editor.redraw(cx);
+                        }
+                        _ => ()
+                    }
+                }
+            }
             data.file_system.handle_sessions();
         }
     }
-}
\ No newline at end of file
+}
+
+impl StudioEditor {
+    // handles the language server key bindings, returns whether the key was used
+    fn handle_lsp_key(&mut self, cx: &mut Cx, ke: &KeyEvent, tab_id: LiveId, file_id: LiveId, session: &mut Session, lsp_manager: &mut LspManager) -> bool {
+        let position = cursor_position(session);
+        let command = ke.modifiers.control || ke.modifiers.logo;
+        if let Some(completion) = &mut lsp_manager.completion {
+            if completion.tab_id == tab_id {
+                let prefix = completion_prefix(session, position);
+                let len = completion.visible_items(&prefix).len();
+                match ke.key_code {
+                    KeyCode::ArrowUp if len > 0 => {
+                        completion.selected = (completion.selected + len - 1) % len;
+                    }
+                    KeyCode::ArrowDown if len > 0 => {
+                        completion.selected = (completion.selected + 1) % len;
+                    }
+                    KeyCode::ReturnKey | KeyCode::Tab if len > 0 => {
+                        lsp_manager.accept_completion(session, &prefix);
+                    }
+                    KeyCode::Escape => {
+                        lsp_manager.completion = None;
+                    }
+                    _ => return false
+                }
+                self.editor.redraw(cx);
+                return true
+            }
+        }
+        match ke.key_code {
+            KeyCode::Space if command => lsp_manager.request_completion(tab_id, file_id, position),
+            KeyCode::F12 if ke.modifiers.shift => lsp_manager.request_references(file_id, position),
+            KeyCode::F12 => lsp_manager.request_definition(file_id, position),
+            KeyCode::KeyI if command => lsp_manager.request_hover(file_id, position),
+            _ => return false
+        }
+        true
+    }
+
+    fn draw_completion(&mut self, cx: &mut Cx2d, cursor_rect: Rect, labels: &[&str], selected: usize) {
+        if labels.is_empty() {
+            return
+        }
+        // keep the selected item in view
+        let first = selected.saturating_sub(MAX_VISIBLE_COMPLETIONS - 1);
+        let labels = &labels[first..labels.len().min(first + MAX_VISIBLE_COMPLETIONS)];
+        let line_height = cursor_rect.size.y;
+        let char_width = cursor_rect.size.x;
+        let max_chars = labels.iter().map(|label| label.chars().count()).max().unwrap_or(0);
+        let rect = Rect {
+            pos: dvec2(cursor_rect.pos.x, cursor_rect.pos.y + line_height),
+            size: dvec2((max_chars + 2) as f64 * char_width, labels.len() as f64 * line_height),
+        };
+        // the popup goes on top of the text the editor drew
+        self.draw_completion_bg.new_draw_call(cx);
+        self.draw_completion_bg.draw_abs(cx, rect);
+        self.draw_completion_selected.new_draw_call(cx);
+        self.draw_completion_selected.draw_abs(cx, Rect {
+            pos: dvec2(rect.pos.x, rect.pos.y + (selected - first) as f64 * line_height),
+            size: dvec2(rect.size.x, line_height),
+        });
+        self.draw_completion_text.new_draw_call(cx);
+        for (index, label) in labels.iter().enumerate() {
+            self.draw_completion_text.draw_abs(cx, dvec2(rect.pos.x + char_width, rect.pos.y + index as f64 * line_height), label);
+        }
+    }
+}
+
+fn cursor_position(session: &Session) -> Position {
+    let selections = session.selections();
+    selections[session.last_added_selection_index().unwrap_or(0)].cursor.position
+}
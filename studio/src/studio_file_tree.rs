@@ -2,8 +2,9 @@
 use {
     crate::{
         app::{AppData},
+        git::git_status::GitFileStatus,
         makepad_widgets::*,
-        makepad_widgets::file_tree::FileTree,
+        makepad_widgets::file_tree::{FileNodeStatus, FileTree},
     },
 };
 
@@ -12,22 +13,49 @@ live_design!{
         
     StudioFileTree = {{StudioFileTree}}{
         file_tree: <FileTree>{}
+        modified_color: #E2C08D
+        added_color: #81B88B
+        deleted_color: #C74E39
+        untracked_color: #73C991
+        conflicted_color: #E4676B
     }
 } 
  
 #[derive(Live, LiveHook, Widget)] 
 pub struct StudioFileTree{
-    #[wrap] #[live] pub file_tree: FileTree
+    #[wrap] #[live] pub file_tree: FileTree,
+    #[live] modified_color: Vec4,
+    #[live] added_color: Vec4,
+    #[live] deleted_color: Vec4,
+    #[live] untracked_color: Vec4,
+    #[live] conflicted_color: Vec4,
 }
 
 impl Widget for StudioFileTree {
     fn draw_walk(&mut self, cx: &mut Cx2d, scope:&mut Scope, walk:Walk)->DrawStep{
         while self.file_tree.draw_walk(cx, scope, walk).is_step() {
             self.file_tree.set_folder_is_open(cx, live_id!(root).into(), true, Animate::No);
-            scope.data.get_mut::<AppData>().unwrap().file_system.draw_file_node(
+            let data = scope.data.get::<AppData>().unwrap();
+            let git_manager = &data.git_manager;
+            let status = |path: &str, is_folder: bool| {
+                if is_folder {
+                    return git_manager.dir_has_changes(path).then_some(FileNodeStatus {text: "•", color: self.modified_color})
+                }
+                let status = git_manager.file_status(path)?;
+                let color = match status {
+                    GitFileStatus::Modified | GitFileStatus::Renamed => self.modified_color,
+                    GitFileStatus::Added => self.added_color,
+                    GitFileStatus::Deleted => self.deleted_color,
+                    GitFileStatus::Untracked => self.untracked_color,
+                    GitFileStatus::Conflicted => self.conflicted_color,
+                };
+                Some(FileNodeStatus {text: status.letter(), color})
+            };
+            data.file_system.draw_file_node(
                 cx,
                 live_id!(root).into(),
-                &mut self.file_tree
+                &mut self.file_tree,
+                &status
             );
         }
         DrawStep::done()
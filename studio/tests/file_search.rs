@@ -0,0 +1,105 @@
+// Tests for the globs, .gitignore rules and directory walk behind the search panel.
+
+use {
+    makepad_studio::{
+        makepad_code_editor::search::{SearchQuery, Searcher},
+        search::{
+            gitignore::{GitIgnore, GitIgnoreStack},
+            glob::Glob,
+            search_manager::{search_files, FileSearchQuery, SearchFileResult},
+        },
+    },
+    std::{fs, path::{Path, PathBuf}, sync::atomic::AtomicBool},
+};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("makepad_studio_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn search(dir: &Path, query: FileSearchQuery) -> Vec<SearchFileResult> {
+    let mut searcher = Searcher::new(&query.query).unwrap();
+    let mut results = Vec::new();
+    search_files(dir, &query, &mut searcher, &AtomicBool::new(false), &mut |result| results.push(result)).unwrap();
+    results
+}
+
+#[test]
+fn matches_globs() {
+    assert!(Glob::new("*.rs").is_match("src/main.rs"));
+    assert!(!Glob::new("*.rs").is_match("src/main.rs.bak"));
+    assert!(Glob::new("src/*.rs").is_match("src/main.rs"));
+    assert!(!Glob::new("src/*.rs").is_match("src/bin/main.rs"));
+    assert!(Glob::new("src/**/*.rs").is_match("src/main.rs"));
+    assert!(Glob::new("src/**/*.rs").is_match("src/bin/tool/main.rs"));
+    assert!(Glob::new("/target").is_match("target"));
+    assert!(Glob::new("file?.[a-c]").is_match("file1.b"));
+    assert!(!Glob::new("file?.[!a-c]").is_match("file1.b"));
+    assert_eq!(Glob::list("*.rs, ,*.toml").len(), 2);
+    assert!(Glob::new("a/**").is_match("a/b/c"));
+    assert!(Glob::new("**/b/*.rs").is_match("a/x/b/main.rs"));
+    assert!(!Glob::new("**/b/*.rs").is_match("a/b/x/main.rs"));
+    assert!(!Glob::new("a/**b").is_match("a/x/yb") && Glob::new("a/**b").is_match("a/xyb"));
+    assert!(Glob::new("\\*.rs").is_match("*.rs") && !Glob::new("\\*.rs").is_match("a.rs"));
+    assert!(Glob::new("[a").is_match("[a") && !Glob::new("[a]").is_match(""));
+    // stars that could each take any part of a long path don't take long to fail
+    let path = format!("{}b", "a".repeat(100));
+    assert!(!Glob::new(&format!("{}c", "*a".repeat(20))).is_match(&path));
+    let path = format!("{}b", "a/".repeat(100));
+    assert!(!Glob::new(&format!("{}c", "**/a".repeat(20))).is_match(&path));
+}
+
+#[test]
+fn applies_gitignore_rules() {
+    let root = GitIgnoreStack::default().push("", Some(GitIgnore::parse("# build output\ntarget/\n*.log\n!keep.log\n")));
+    assert!(root.is_ignored("target", true));
+    assert!(!root.is_ignored("target", false));
+    assert!(root.is_ignored("logs/debug.log", false));
+    assert!(!root.is_ignored("logs/keep.log", false));
+
+    let nested = root.push("logs", Some(GitIgnore::parse("*.log\n")));
+    assert!(nested.is_ignored("logs/keep.log", false));
+    assert!(!nested.is_ignored("keep.log", false));
+}
+
+#[test]
+fn searches_files() {
+    let dir = temp_dir("search");
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::create_dir_all(dir.join("ignored")).unwrap();
+    fs::write(dir.join(".gitignore"), "ignored/\n").unwrap();
+    fs::write(dir.join("src/main.rs"), "fn main() {\n    let needle = 1;\n    needle + needle\n}\n").unwrap();
+    fs::write(dir.join("src/notes.txt"), "a needle\n").unwrap();
+    fs::write(dir.join("ignored/main.rs"), "needle\n").unwrap();
+    fs::write(dir.join("data.bin"), b"needle\0\x01\x02").unwrap();
+
+    let results = search(&dir, FileSearchQuery {
+        query: SearchQuery::literal("needle"),
+        ..FileSearchQuery::default()
+    });
+    let file_names: Vec<&str> = results.iter().map(|result| result.file_name.as_str()).collect();
+    assert_eq!(file_names, ["src/main.rs", "src/notes.txt"]);
+    let matches: Vec<(usize, usize)> = results[0].matches.iter().map(|found| (found.line_index, found.start)).collect();
+    assert_eq!(matches, [(1, 8), (2, 4), (2, 13)]);
+
+    let results = search(&dir, FileSearchQuery {
+        query: SearchQuery::regex(r"let \w+"),
+        include: "*.rs".to_string(),
+        ..FileSearchQuery::default()
+    });
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].matches[0].line, "    let needle = 1;");
+    assert_eq!(results[0].matches[0].end, 14);
+
+    let results = search(&dir, FileSearchQuery {
+        query: SearchQuery::literal("needle"),
+        exclude: "src/*.rs".to_string(),
+        ..FileSearchQuery::default()
+    });
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].file_name, "src/notes.txt");
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,84 @@
+// Tests for the line diff behind the gutter markers, reverting hunks and parsing `git status`.
+
+use makepad_studio::{
+    git::{
+        git_diff::{diff_lines, GitHunk},
+        git_status::{parse_status, GitFileStatus, GitStatusEntry},
+    },
+    makepad_code_editor::{
+        decoration::GutterMarkerType,
+        text::{Change, Text},
+    },
+};
+
+fn lines(text: &str) -> Vec<String> {
+    Text::from(text).as_lines().to_vec()
+}
+
+fn hunk(old_start: usize, old_len: usize, new_start: usize, new_len: usize) -> GitHunk {
+    GitHunk {old_start, old_len, new_start, new_len}
+}
+
+// reverts every hunk, back to front so the earlier ones stay where they are
+fn revert_all(old: &str, new: &str) -> String {
+    let (old_lines, new_lines) = (lines(old), lines(new));
+    let mut text = Text::from(new);
+    for hunk in diff_lines(&old_lines, &new_lines).iter().rev() {
+        let (start, end, replacement) = hunk.revert(&old_lines, &new_lines);
+        if start != end {
+            text.apply_change(Change::Delete(start, end - start));
+        }
+        if !replacement.is_empty() {
+            text.apply_change(Change::Insert(start, replacement));
+        }
+    }
+    text.to_string()
+}
+
+#[test]
+fn diffs_lines() {
+    assert_eq!(diff_lines(&lines("a\nb\nc"), &lines("a\nb\nc")), []);
+    assert_eq!(diff_lines(&lines("a\nb\nc"), &lines("a\nx\nc")), [hunk(1, 1, 1, 1)]);
+    assert_eq!(diff_lines(&lines("a\nc"), &lines("a\nb\nc")), [hunk(1, 0, 1, 1)]);
+    assert_eq!(diff_lines(&lines("a\nb\nc"), &lines("a\nc")), [hunk(1, 1, 1, 0)]);
+    assert_eq!(
+        diff_lines(&lines("a\nb\nc\nd\ne\nf"), &lines("x\na\nc\nd\ny\nf")),
+        [hunk(0, 0, 0, 1), hunk(1, 1, 2, 0), hunk(4, 1, 4, 1)]
+    );
+
+    let markers: Vec<GutterMarkerType> = diff_lines(&lines("a\nb\nc\nd"), &lines("a\nnew\nb\nd"))
+        .iter()
+        .map(|hunk| hunk.marker().ty)
+        .collect();
+    assert_eq!(markers, [GutterMarkerType::Added, GutterMarkerType::Deleted]);
+}
+
+#[test]
+fn reverts_hunks() {
+    let cases = [
+        ("a\nb\nc", "a\nx\nc"),
+        ("a\nb\nc", "a\nb\nc\nd\ne"),
+        ("a\nb\nc\n", "a\n"),
+        ("a\nb\nc", "x\ny"),
+        ("a\nb", ""),
+        ("", "a\nb\n"),
+        ("fn main() {\n    a();\n    b();\n}\n", "// header\nfn main() {\n    b();\n    c();\n}\n"),
+    ];
+    for (old, new) in cases {
+        assert_eq!(revert_all(old, new), old, "reverting {:?} to {:?}", new, old);
+    }
+}
+
+#[test]
+fn parses_status() {
+    let output = " M studio/src/app.rs\0A  studio/src/git/mod.rs\0?? studio/notes.txt\0R  studio/new.rs\0studio/old.rs\0UU studio/conflict.rs\0 M README.md\0";
+    let entry = |file_name: &str, staged, unstaged| GitStatusEntry {file_name: file_name.to_string(), staged, unstaged};
+    assert_eq!(parse_status(output, "studio/"), [
+        entry("src/app.rs", None, Some(GitFileStatus::Modified)),
+        entry("src/git/mod.rs", Some(GitFileStatus::Added), None),
+        entry("notes.txt", None, Some(GitFileStatus::Untracked)),
+        entry("new.rs", Some(GitFileStatus::Renamed), None),
+        entry("conflict.rs", Some(GitFileStatus::Conflicted), Some(GitFileStatus::Conflicted)),
+    ]);
+    assert_eq!(parse_status(output, "").len(), 6);
+}
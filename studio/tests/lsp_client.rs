@@ -0,0 +1,155 @@
+// Language server client tests. They run the client against the mock server in
+// src/bin/mock_lsp_server.rs.
+
+use {
+    makepad_studio::{
+        lsp::{
+            lsp_client::{LspClient, LspEvent, LspServerConfig},
+            lsp_protocol::*,
+        },
+        makepad_code_editor::text::{Change, Drift, Edit, Length, Position, Text},
+    },
+    std::time::Duration,
+};
+
+const URI: &str = "file:///project/src/main.rs";
+
+fn start() -> LspClient {
+    let config = LspServerConfig {
+        language_id: "rust".to_string(),
+        extensions: vec!["rs".to_string()],
+        command: env!("CARGO_BIN_EXE_mock_lsp_server").to_string(),
+        args: Vec::new(),
+    };
+    let mut client = LspClient::start(config, &std::env::temp_dir()).unwrap();
+    assert!(matches!(next_event(&mut client), LspEvent::Initialized));
+    client
+}
+
+fn next_event(client: &mut LspClient) -> LspEvent {
+    client.wait_for_event(Duration::from_secs(10)).expect("no event from the mock server")
+}
+
+fn next_diagnostics(client: &mut LspClient) -> Vec<(Position, Position)> {
+    match next_event(client) {
+        LspEvent::Diagnostics {uri, diagnostics} => {
+            assert_eq!(uri, URI);
+            diagnostics.iter().map(|diagnostic| {
+                assert_eq!(diagnostic.severity, LspSeverity::Error);
+                (client.to_position(&uri, diagnostic.range.start), client.to_position(&uri, diagnostic.range.end))
+            }).collect()
+        }
+        event => panic!("expected diagnostics, got {:?}", event),
+    }
+}
+
+fn pos(line_index: usize, byte_index: usize) -> Position {
+    Position {line_index, byte_index}
+}
+
+#[test]
+fn syncs_edits_incrementally() {
+    let mut client = start();
+    client.did_open(URI, Text::from("fn main() {\n    let é = 1;\n}"));
+    assert!(next_diagnostics(&mut client).is_empty());
+
+    // the server counts in UTF-16 code units, so the two byte `é` has to be mapped back and forth
+    client.did_change(URI, &[Edit {
+        change: Change::Insert(pos(1, 10), Text::from("error")),
+        drift: Drift::Before,
+    }]);
+    assert_eq!(next_diagnostics(&mut client), vec![(pos(1, 10), pos(1, 15))]);
+
+    // edits that span lines, applied one after the other
+    client.did_change(URI, &[
+        Edit {
+            change: Change::Insert(pos(0, 11), Text::from("\nerror")),
+            drift: Drift::Before,
+        },
+        Edit {
+            change: Change::Delete(pos(2, 10), Length {line_count: 0, byte_count: 5}),
+            drift: Drift::Before,
+        },
+    ]);
+    assert_eq!(next_diagnostics(&mut client), vec![(pos(1, 0), pos(1, 5))]);
+
+    client.did_change(URI, &[Edit {
+        change: Change::Delete(pos(0, 11), Length {line_count: 1, byte_count: 5}),
+        drift: Drift::Before,
+    }]);
+    assert!(next_diagnostics(&mut client).is_empty());
+}
+
+#[test]
+fn answers_requests() {
+    let mut client = start();
+    client.did_open(URI, Text::from("fn main() {\n    main();\n}"));
+    next_diagnostics(&mut client);
+
+    let request_id = client.completion(URI, pos(1, 6)).unwrap();
+    match next_event(&mut client) {
+        LspEvent::Completion {request_id: id, items, ..} => {
+            assert_eq!(id, request_id);
+            let labels: Vec<_> = items.iter().map(|item| item.label.as_str()).collect();
+            assert_eq!(labels, ["mock_function", "mock_field"]);
+            assert_eq!(items[0].detail.as_deref(), Some("fn()"));
+            assert_eq!(items[0].insert_text, "mock_function");
+        }
+        event => panic!("expected completions, got {:?}", event),
+    }
+
+    let request_id = client.definition(URI, pos(1, 6)).unwrap();
+    match next_event(&mut client) {
+        LspEvent::Definition {request_id: id, locations} => {
+            assert_eq!(id, request_id);
+            assert_eq!(locations.len(), 1);
+            assert_eq!(locations[0].uri, URI);
+            assert_eq!(client.to_position(URI, locations[0].range.start), pos(0, 3));
+        }
+        event => panic!("expected a definition, got {:?}", event),
+    }
+
+    client.references(URI, pos(0, 3)).unwrap();
+    match next_event(&mut client) {
+        LspEvent::References {locations, ..} => {
+            assert_eq!(locations.len(), 2);
+            assert_eq!(client.line(URI, locations[1].range.start.line).as_deref(), Some("    main();"));
+        }
+        event => panic!("expected references, got {:?}", event),
+    }
+
+    client.hover(URI, pos(0, 3)).unwrap();
+    match next_event(&mut client) {
+        LspEvent::Hover {text, ..} => assert_eq!(text, "mock hover"),
+        event => panic!("expected hover text, got {:?}", event),
+    }
+}
+
+#[test]
+fn parses_server_arguments() {
+    let config = LspServerConfig::from_arg("--lsp=c,h=clangd --background-index").unwrap();
+    assert_eq!(config.extensions, ["c", "h"]);
+    assert_eq!(config.command, "clangd");
+    assert_eq!(config.args, ["--background-index"]);
+    assert!(config.handles_path("src/main.h"));
+    assert!(!config.handles_path("src/main.rs"));
+    assert!(LspServerConfig::from_arg("--root=.").is_none());
+}
+
+#[test]
+fn converts_paths_and_uris() {
+    let uri = path_to_uri(std::path::Path::new("/project/src/my file.rs"));
+    assert_eq!(uri, "file:///project/src/my%20file.rs");
+    assert_eq!(uri_to_path(&uri).unwrap(), std::path::Path::new("/project/src/my file.rs"));
+}
+
+#[test]
+fn reads_messages() {
+    let mut input = format!("{}{}", encode_message("{}"), encode_message("[1]")).into_bytes();
+    input.extend_from_slice(b"Content-Length: 99999999999\r\n\r\n");
+    let mut reader = &input[..];
+    assert_eq!(read_message(&mut reader).unwrap().as_deref(), Some("{}"));
+    assert_eq!(read_message(&mut reader).unwrap().as_deref(), Some("[1]"));
+    assert!(read_message(&mut reader).is_err());
+    assert_eq!(read_message(&mut &b""[..]).unwrap(), None);
+}
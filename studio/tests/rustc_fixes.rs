@@ -0,0 +1,127 @@
+// Tests for picking the machine applicable suggestions out of rustc's json diagnostics, and
+// for applying them.
+
+use {
+    makepad_studio::{
+        build_manager::{
+            build_manager::BuildManager,
+            build_protocol::{BuildClientMessage, BuildClientMessageWrap, LogItem, LogItemFix, LogItemLocation},
+            build_server::MsgSender,
+            rustc_json::RustcCompilerMessage,
+        },
+        file_system::file_system::fix_replacements,
+        makepad_code_editor::text::{Change, Position, Text},
+        makepad_platform::log::LogLevel,
+        makepad_live_id::LiveId,
+        makepad_micro_serde::*,
+    },
+    std::sync::mpsc,
+};
+
+fn span(line: usize, column_start: usize, column_end: usize, is_primary: bool, suggestion: Option<(&str, &str)>) -> String {
+    let (replacement, applicability) = match suggestion {
+        Some((replacement, applicability)) => (format!("\"{}\"", replacement), format!("\"{}\"", applicability)),
+        None => ("null".to_string(), "null".to_string()),
+    };
+    format!(
+        r#"{{"file_name":"src\\main.rs","byte_start":0,"byte_end":0,"line_start":{line},"line_end":{line},"column_start":{column_start},"column_end":{column_end},"is_primary":{is_primary},"text":[{{"text":"    let x = 5;","highlight_start":{column_start},"highlight_end":{column_end}}}],"label":null,"suggested_replacement":{replacement},"suggestion_applicability":{applicability},"expansion":null}}"#
+    )
+}
+
+fn message(message: &str, spans: &[String], children: &[String]) -> String {
+    format!(
+        r#"{{"message":"{message}","code":null,"level":"warning","spans":[{}],"children":[{}],"rendered":null}}"#,
+        spans.join(","),
+        children.join(",")
+    )
+}
+
+#[test]
+fn collects_machine_applicable_fixes() {
+    let json = format!(
+        r#"{{"reason":"compiler-message","package_id":"app","manifest_path":"Cargo.toml","message":{}}}"#,
+        message("unused variable: `x`", &[span(2, 9, 10, true, None)], &[
+            message("if this is intentional, prefix it with an underscore", &[span(2, 9, 10, true, Some(("_x", "MachineApplicable")))], &[]),
+            message("or maybe remove it", &[span(2, 5, 15, true, Some(("", "MaybeIncorrect")))], &[]),
+        ])
+    );
+    let msg = RustcCompilerMessage::deserialize_json(&json).unwrap();
+
+    let (sender, receiver) = mpsc::channel();
+    let msg_sender = move |wrap: BuildClientMessageWrap| sender.send(wrap).unwrap();
+    msg_sender.process_compiler_message(LiveId(0), msg);
+
+    let Ok(BuildClientMessageWrap {message: BuildClientMessage::LogItem(LogItem::Location(loc)), ..}) = receiver.try_recv() else {
+        panic!("expected a location")
+    };
+    assert_eq!(loc.file_name, "src/main.rs");
+    assert_eq!(loc.fixes.len(), 1);
+    assert_eq!(loc.fixes[0].file_name, "src/main.rs");
+    assert_eq!(loc.fixes[0].start, Position {line_index: 1, byte_index: 8});
+    assert_eq!(loc.fixes[0].end, Position {line_index: 1, byte_index: 9});
+    assert_eq!(loc.fixes[0].replacement, "_x");
+    assert_eq!(loc.fixes[0].original, "x");
+}
+
+fn underscore_fix() -> LogItemFix {
+    LogItemFix {
+        file_name: "src/main.rs".to_string(),
+        start: Position {line_index: 1, byte_index: 8},
+        end: Position {line_index: 1, byte_index: 9},
+        replacement: "_x".to_string(),
+        original: "x".to_string(),
+    }
+}
+
+fn apply(text: &mut Text, fixes: &[&LogItemFix]) -> usize {
+    let replacements = fix_replacements(text.as_lines(), fixes);
+    for (start, end, replacement) in replacements.iter().rev() {
+        text.apply_change(Change::Delete(*start, *end - *start));
+        text.apply_change(Change::Insert(*start, replacement.clone()));
+    }
+    replacements.len()
+}
+
+#[test]
+fn applies_a_fix_only_to_the_text_it_was_made_for() {
+    let fix = underscore_fix();
+    let mut text: Text = "fn main() {\n    let x = 5;\n}".into();
+    assert_eq!(apply(&mut text, &[&fix]), 1);
+    assert_eq!(text.to_string(), "fn main() {\n    let _x = 5;\n}");
+    // applying it again would make `_xx`
+    assert_eq!(apply(&mut text, &[&fix]), 0);
+    assert_eq!(text.to_string(), "fn main() {\n    let _x = 5;\n}");
+}
+
+#[test]
+fn counts_fix_columns_in_chars() {
+    let fix = LogItemFix {
+        start: Position {line_index: 0, byte_index: 7},
+        end: Position {line_index: 0, byte_index: 8},
+        ..underscore_fix()
+    };
+    let mut text: Text = "let é; x".into();
+    assert_eq!(apply(&mut text, &[&fix]), 1);
+    assert_eq!(text.to_string(), "let é; _x");
+}
+
+#[test]
+fn forgets_applied_fixes() {
+    let mut build_manager = BuildManager::default();
+    let location = LogItemLocation {
+        level: LogLevel::Warning,
+        file_name: "src/main.rs".to_string(),
+        start: Position {line_index: 1, byte_index: 8},
+        end: Position {line_index: 1, byte_index: 9},
+        message: "unused variable: `x`".to_string(),
+        fixes: vec![underscore_fix()],
+    };
+    // every build that hits the warning logs it again
+    build_manager.log.push((LiveId(0), LogItem::Location(location.clone())));
+    build_manager.log.push((LiveId(1), LogItem::Location(location)));
+    let fixes = build_manager.fixes_in_file("src/main.rs");
+    assert_eq!(fixes, vec![underscore_fix()]);
+    build_manager.remove_fixes(&fixes);
+    assert!(build_manager.fixes_in_file("src/main.rs").is_empty());
+    assert!(build_manager.fixes_at("src/main.rs", Position {line_index: 1, byte_index: 8}).is_empty());
+}
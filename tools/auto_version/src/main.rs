@@ -68,18 +68,16 @@ fn main() {
         new_sha1: String,
     }
     
-    let target_deps = [
-        "dependencies.",
-        "target.wasm32-unknown-unknown.dependencies.",
-        "target.aarch64-apple-darwin.dependencies.",
-        "target.x86_64-apple-darwin.dependencies.",
-        "target.x86_64-apple-ios.dependencies.",
-        "target.aarch64-apple-ios-sim.dependencies.",
-        "target.aarch64-apple-ios.dependencies.",
-        "target.aarch64-unknown-linux-gnu.dependencies.",
-        "target.'cfg(windows)'.dependencies.makepad-futures-legacy.",
-        "target.'cfg(windows)'.dependencies.makepad-windows.",
-        "target.'cfg(windows)'.dependencies.windows-targets."
+    let target_deps: [&[&str]; 9] = [
+        &["dependencies"],
+        &["target", "wasm32-unknown-unknown", "dependencies"],
+        &["target", "aarch64-apple-darwin", "dependencies"],
+        &["target", "x86_64-apple-darwin", "dependencies"],
+        &["target", "x86_64-apple-ios", "dependencies"],
+        &["target", "aarch64-apple-ios-sim", "dependencies"],
+        &["target", "aarch64-apple-ios", "dependencies"],
+        &["target", "aarch64-unknown-linux-gnu", "dependencies"],
+        &["target", "cfg(windows)", "dependencies"],
     ];
     
     let mut ver_crates = Vec::new();
@@ -90,14 +88,14 @@ fn main() {
         
         let toml = makepad_toml_parser::parse_toml(&cargo_str).unwrap();
 
-        let old_sha1 = if let Some(Toml::Str(ver, _)) = toml.get("package.metadata.makepad-auto-version") {
+        let old_sha1 = if let Some(Toml::Str(ver, _)) = toml.path(&["package", "metadata", "makepad-auto-version"]) {
             ver.to_string()
         }
         else {
             continue;
         };
-        let package_name = toml.get("package.name").unwrap().clone().into_str().unwrap();
-        let package_version = toml.get("package.version").unwrap().clone().into_str().unwrap();
+        let package_name = toml.path(&["package", "name"]).unwrap().clone().into_str().unwrap();
+        let package_version = toml.path(&["package", "version"]).unwrap().clone().into_str().unwrap();
 
         // hash all the rs files
         let mut sha1 = sha1::Sha1::new();
@@ -109,11 +107,11 @@ fn main() {
         let new_sha1 = String::from_utf8(base64::base64_encode(&data, &base64::BASE64_URL_SAFE)).unwrap();
         let mut deps = Vec::new();
         // scan our toml file for all dependencies
-        for key in toml.keys() {
-            for pref in target_deps {
-                if let Some(pref) = key.strip_prefix(pref) {
-                    if let Some(dep) = pref.strip_suffix(".version") {
-                        println!("GOT DEP {}", dep.to_string());
+        for path in target_deps {
+            if let Some(Toml::Table(table, _)) = toml.path(path) {
+                for (dep, value) in table {
+                    if value.get("version").is_some() {
+                        println!("GOT DEP {}", dep);
                         deps.push(dep.to_string());
                     }
                 }
@@ -161,11 +159,12 @@ fn main() {
             let next_version = format!("0.{}.0", version + 1);
             //let next_version = format!("0.4.0");
             
-            patch_cargo(&c.cargo, "package.version", &next_version, write);
-            patch_cargo(&c.cargo, "package.metadata.makepad-auto-version", &c.new_sha1, write);
+            patch_cargo(&c.cargo, &["package", "version"], &next_version, write);
+            patch_cargo(&c.cargo, &["package", "metadata", "makepad-auto-version"], &c.new_sha1, write);
             // now lets version-up everyone elses dependency on this crate
-            for pref in target_deps {
-                let dep_version = format!("{}{}.version", pref, c.package_name);
+            for path in target_deps {
+                let mut dep_version = path.to_vec();
+                dep_version.extend_from_slice(&[&c.package_name, "version"]);
                 for o in &ver_crates {
                     patch_cargo(&o.cargo, &dep_version, &next_version, write);
                 }
@@ -176,21 +175,22 @@ fn main() {
     println!("Done");
 }
 
-fn patch_cargo(cargo: &Path, toml_path: &str, with: &str, write: bool) {
+fn patch_cargo(cargo: &Path, toml_path: &[&str], with: &str, write: bool) {
     let old_cargo = fs::read_to_string(cargo).unwrap();
     let toml = makepad_toml_parser::parse_toml(&old_cargo).unwrap();
     
-    if let Some(Toml::Str(_, span)) = toml.get(toml_path) {
+    if let Some(Toml::Str(_, span)) = toml.path(toml_path) {
         let mut new_cargo = String::new();
         for (i, c) in old_cargo.chars().enumerate() {
+            // the span includes the quotes, replace what is between them
+            if i <= span.start || i >= span.start + span.len - 1 {
+                new_cargo.push(c);
+            }
             if i == span.start {
                 for c in with.chars() {
                     new_cargo.push(c);
                 }
             }
-            if i < span.start || i >= span.start + span.len - 2 {
-                new_cargo.push(c);
-            }
         }
         // lets write it back to disk
         if write {
@@ -9,4 +9,12 @@ fn main() {
     let cwd = std::env::current_dir().unwrap();
     let mut file = File::create(path.join("makepad-widgets.path")).unwrap();
     file.write_all(&format!("{}", cwd.display()).as_bytes()).unwrap();
+    // the headless tests only build against the headless platform
+    println!("cargo:rustc-check-cfg=cfg(linux_headless)");
+    println!("cargo:rerun-if-env-changed=MAKEPAD");
+    if let Ok(configs) = env::var("MAKEPAD"){
+        if configs.split('+').any(|config| config == "linux_headless"){
+            println!("cargo:rustc-cfg=linux_headless");
+        }
+    }
 }
\ No newline at end of file
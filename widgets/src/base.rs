@@ -58,6 +58,7 @@ live_design!{
     import crate::designer_outline_tree::DesignerOutlineTreeBase;
     import crate::designer_outline_tree::DesignerOutlineTreeNodeBase;
     import crate::designer_toolbox::DesignerToolboxBase
+    import crate::designer_properties::DesignerPropertiesBase;
     import crate::color_picker::ColorPicker;
     
     import crate::bare_step::BareStep;
@@ -951,4 +952,5 @@ live_design!{
     DesignerOutlineTreeBase = <DesignerOutlineTreeBase> {}
     DesignerOutlineTreeNodeBase = <DesignerOutlineTreeNodeBase> {}
     DesignerToolboxBase = <DesignerToolboxBase> {}
+    DesignerPropertiesBase = <DesignerPropertiesBase> {}
 }
@@ -75,6 +75,11 @@ impl Widget for Button {
                     self.animator_play(cx, id!(hover.off));
                 }
             }
+            Hit::KeyDown(ke) if !ke.is_repeat && matches!(ke.key_code, KeyCode::ReturnKey | KeyCode::NumpadEnter | KeyCode::Space) => {
+                cx.widget_action(uid, &scope.path, ButtonAction::Pressed(ke.modifiers));
+                cx.widget_action(uid, &scope.path, ButtonAction::Clicked(ke.modifiers));
+                cx.widget_action(uid, &scope.path, ButtonAction::Released(ke.modifiers));
+            }
             _ => (),
         }
     }
@@ -85,9 +90,14 @@ impl Widget for Button {
         self.draw_text
             .draw_walk(cx, self.label_walk, Align::default(), self.text.as_ref());
         self.draw_bg.end(cx);
+        cx.add_nav_stop(self.draw_bg.area(), NavRole::Button, Margin::default());
         DrawStep::done()
     }
 
+    fn accessibility(&self, _cx: &Cx) -> Option<NavAccessible> {
+        Some(NavAccessible::new(self.draw_bg.area(), AccessibilityRole::Button, self.text.as_ref()))
+    }
+
     fn text(&self) -> String {
         self.text.as_ref().to_string()
     }
@@ -105,6 +115,8 @@ impl Button {
         self.draw_text
         .draw_walk(cx, self.label_walk, Align::default(), label);
         self.draw_bg.end(cx);
+        cx.add_nav_stop(self.draw_bg.area(), NavRole::Button, Margin::default());
+        cx.add_accessible(NavAccessible::new(self.draw_bg.area(), AccessibilityRole::Button, label));
     }
     
     
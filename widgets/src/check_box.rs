@@ -71,6 +71,18 @@ impl CheckBox {
         self.draw_icon.draw_walk(cx, self.icon_walk);
         self.draw_text.draw_walk(cx, self.label_walk, self.label_align, self.text.as_ref());
         self.draw_check.end(cx);
+        cx.add_nav_stop(self.draw_check.area(), NavRole::CheckBox, Margin::default());
+    }
+    
+    fn toggle(&mut self, cx: &mut Cx, uid: WidgetUid, scope: &mut Scope) {
+        if self.animator_in_state(cx, id!(selected.on)) {
+            self.animator_play(cx, id!(selected.off));
+            cx.widget_action(uid, &scope.path, CheckBoxAction::Change(false));
+        }
+        else {
+            self.animator_play(cx, id!(selected.on));
+            cx.widget_action(uid, &scope.path, CheckBoxAction::Change(true));
+        }
     }
 }
 
@@ -107,15 +119,11 @@ impl Widget for CheckBox {
                 self.animator_play(cx, id!(hover.off));
             },
             Hit::FingerDown(_fe) => {
-                if self.animator_in_state(cx, id!(selected.on)) {
-                    self.animator_play(cx, id!(selected.off));
-                    cx.widget_action(uid, &scope.path, CheckBoxAction::Change(false));
-                }
-                else {
-                    self.animator_play(cx, id!(selected.on));
-                    cx.widget_action(uid, &scope.path, CheckBoxAction::Change(true));
-                }
+                self.toggle(cx, uid, scope);
             },
+            Hit::KeyDown(ke) if !ke.is_repeat && matches!(ke.key_code, KeyCode::ReturnKey | KeyCode::NumpadEnter | KeyCode::Space) => {
+                self.toggle(cx, uid, scope);
+            }
             Hit::FingerUp(_fe) => {
                                 
             }
@@ -131,6 +139,13 @@ impl Widget for CheckBox {
         DrawStep::done()
     }
     
+    fn accessibility(&self, cx: &Cx) -> Option<NavAccessible> {
+        Some(NavAccessible::new(self.draw_check.area(), AccessibilityRole::CheckBox, self.text.as_ref()).with_state(AccessibilityState {
+            checked: Some(self.animator_in_state(cx, id!(selected.on))),
+            ..Default::default()
+        }))
+    }
+    
     fn text(&self) -> String {
         self.text.as_ref().to_string()
     }
@@ -198,7 +198,8 @@ impl ColorPicker {
     }
     
     
-    pub fn draw_color_picker(&mut self, cx: &mut Cx2d, rgba: Vec4, walk:Walk) {
+    /// Moves the pucks to `rgba`, unless they are being dragged. The alpha is ignored.
+    pub fn set_color(&mut self, rgba: Vec4) {
         if self.drag_mode == ColorPickerDragMode::None {
             // lets convert to rgba
             let old_rgba = self.to_rgba();
@@ -209,6 +210,10 @@ impl ColorPicker {
                 self.val = hsva.z;
             }
         }
+    }
+    
+    pub fn draw_color_picker(&mut self, cx: &mut Cx2d, rgba: Vec4, walk:Walk) {
+        self.set_color(rgba);
         //self.draw_wheel.shader = live_shader!(cx, self::shader_wheel);
         // i wanna draw a draw_wheel with 'width' set but height a fixed height.
         self.size = cx.turtle().rect().size.y;
@@ -270,7 +275,31 @@ impl Widget for ColorPicker {
     }
         
     fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
-        self.draw_color_picker(cx, vec4(1.0,0.0,0.0,1.0), walk);
+        self.draw_color_picker(cx, self.to_rgba(), walk);
         DrawStep::done()
     }
+}
+
+impl ColorPickerRef {
+    pub fn color(&self) -> Option<Vec4> {
+        Some(self.borrow()?.to_rgba())
+    }
+    
+    pub fn set_color(&self, cx: &mut Cx, rgba: Vec4) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_color(rgba);
+            inner.redraw(cx);
+        }
+    }
+    
+    pub fn changed(&self, actions: &Actions) -> Option<Vec4> {
+        if let ColorPickerAction::Change {rgba} = actions.find_widget_action_cast(self.widget_uid()) {
+            return Some(rgba)
+        }
+        None
+    }
+    
+    pub fn done_changing(&self, actions: &Actions) -> bool {
+        matches!(actions.find_widget_action_cast(self.widget_uid()), ColorPickerAction::DoneChanging)
+    }
 }
\ No newline at end of file
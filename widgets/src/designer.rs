@@ -32,6 +32,12 @@ impl LiveHook for Designer {
 }
 
 impl Designer{
+    /// Shows the properties of `component` in the property inspector.
+    fn select_component(&mut self, cx: &mut Cx, component: Option<LiveId>){
+        self.data.selected = component;
+        self.ui.widget(id!(properties)).redraw(cx);
+    }
+    
     fn studio_jump_to_component(&self, cx:&Cx, component:LiveId){
         if let Some(OutlineNode::Component{token_id,..}) =  self.data.node_map.get(&component){
             let file_id = token_id.file_id().unwrap();
@@ -65,6 +71,7 @@ impl WidgetMatchEvent for Designer{
             // select the right node in the filetree
             let path = self.data.construct_path(outline_id);
             outline_tree.select_and_show_node(cx, &path);
+            self.select_component(cx, Some(outline_id));
             // if we click with control
             if km.control || tap_count > 1{
                 self.studio_jump_to_component(cx, outline_id)
@@ -85,6 +92,7 @@ impl WidgetMatchEvent for Designer{
                             Cx::send_studio_message(AppToStudio::FocusDesign);
                         }
                         else{
+                            self.select_component(cx, None);
                             designer_view.select_component_and_redraw(cx, None);
                             designer_view.view_file_and_redraw(cx, outline_id);
                         }        
@@ -99,6 +107,7 @@ impl WidgetMatchEvent for Designer{
                         else{
                             // only select the file 
                             if let Some(file_id) = self.data.find_file_parent(outline_id){
+                                self.select_component(cx, Some(outline_id));
                                 designer_view.select_component_and_redraw(cx, Some(outline_id));
                                 designer_view.view_file_and_redraw(cx, file_id);
                             }
@@ -28,7 +28,7 @@ impl Widget for DesignerOutline {
         let file_tree = self.view.designer_outline_tree(id!(outline_tree));
         let data = scope.props.get::<DesignerData>().unwrap();
         
-        while let Some(next) = self.view.draw(cx, &mut Scope::empty()).step() {
+        while let Some(next) = self.view.draw(cx, &mut Scope::with_props(data)).step() {
             if let Some(mut file_tree) = file_tree.borrow_mut_if_eq(&next) {
                 if let OutlineNode::Virtual{children,..} = &data.node_map.get(&data.root).as_ref().unwrap(){
                     recur_nodes(cx,  &mut *file_tree, &data.node_map, children);
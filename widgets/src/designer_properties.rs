@@ -0,0 +1,660 @@
+use crate::{
+    makepad_derive_widget::*,
+    makepad_draw::*,
+    makepad_draw::makepad_live_compiler::{LiveToken, TokenWithSpan},
+    makepad_draw::makepad_live_tokenizer::Delim,
+    designer_data::*,
+    check_box::*,
+    color_picker::*,
+    drop_down::*,
+    label::*,
+    portal_list::*,
+    slider::*,
+    text_input::*,
+    view::View,
+    widget::*,
+};
+
+live_design!{
+    DesignerPropertiesBase = {{DesignerProperties}}{
+    }
+}
+
+/// How deep the inspector goes into the objects of a component, like `draw_bg: {color: ..}`.
+const MAX_PROPERTY_DEPTH: usize = 3;
+
+/// A property of the selected component, with the value it has after expansion.
+#[derive(Clone, Debug)]
+struct Property {
+    path: Vec<LiveId>,
+    value: PropertyValue,
+}
+
+#[derive(Clone, Debug)]
+enum PropertyValue {
+    Number {value: f64, integer: bool, range: (f64, f64)},
+    Size {value: Size, range: (f64, f64)},
+    Sides {value: Padding, range: (f64, f64)},
+    Align(Align),
+    Enum {value: LiveId, variants: Vec<LiveId>},
+    Bool(bool),
+    Text(String),
+    Color(Vec4),
+}
+
+/// A plain value as the DSL spells it, before the type of its field is taken into account.
+enum Scalar {
+    Number {value: f64, integer: bool},
+    Color(Vec4),
+    Bool(bool),
+    Id(LiveId),
+    Text(String),
+}
+
+impl Scalar {
+    fn from_node(registry: &LiveRegistry, index: usize, nodes: &[LiveNode]) -> Option<Self> {
+        Some(match &nodes[index].value {
+            LiveValue::Float32(v) => Self::Number {value: *v as f64, integer: false},
+            LiveValue::Float64(v) => Self::Number {value: *v, integer: false},
+            LiveValue::Int64(v) => Self::Number {value: *v as f64, integer: true},
+            LiveValue::Uint64(v) => Self::Number {value: *v as f64, integer: true},
+            LiveValue::Color(v) => Self::Color(Vec4::from_u32(*v)),
+            LiveValue::Vec4(v) => Self::Color(*v),
+            LiveValue::Bool(v) => Self::Bool(*v),
+            LiveValue::Id(v) | LiveValue::BareEnum(v) => Self::Id(*v),
+            LiveValue::Str(_) | LiveValue::String(_) | LiveValue::InlineString(_) => {
+                Self::Text(registry.live_node_as_string(&nodes[index])?)
+            }
+            LiveValue::Expr {..} => match live_eval(registry, index, &mut (index + 1), nodes).ok()? {
+                LiveEval::Float64(v) => Self::Number {value: v, integer: false},
+                LiveEval::Int64(v) => Self::Number {value: v as f64, integer: true},
+                LiveEval::Vec4(v) => Self::Color(v),
+                LiveEval::Bool(v) => Self::Bool(v),
+                LiveEval::String(v) => Self::Text(v.to_string()),
+                _ => return None
+            }
+            _ => return None
+        })
+    }
+
+    fn number(registry: &LiveRegistry, index: usize, nodes: &[LiveNode]) -> Option<f64> {
+        match Self::from_node(registry, index, nodes)? {
+            Self::Number {value, ..} => Some(value),
+            _ => None
+        }
+    }
+}
+
+impl PropertyValue {
+    fn from_node(registry: &LiveRegistry, type_info: Option<&LiveTypeInfo>, index: usize, nodes: &[LiveNode]) -> Option<Self> {
+        let type_name = type_info.map( | info | info.type_name);
+        match type_name {
+            Some(live_id!(Size)) => {
+                let value = match &nodes[index].value {
+                    LiveValue::TupleEnum(live_id!(Fixed)) => Size::Fixed(Scalar::number(registry, index + 1, nodes)?),
+                    _ => match Scalar::from_node(registry, index, nodes)? {
+                        Scalar::Number {value, ..} => Size::Fixed(value),
+                        Scalar::Id(live_id!(Fill)) => Size::Fill,
+                        Scalar::Id(live_id!(Fit)) => Size::Fit,
+                        Scalar::Id(live_id!(All)) => Size::All,
+                        _ => return None
+                    }
+                };
+                let fixed = if let Size::Fixed(v) = value {v} else {0.0};
+                return Some(Self::Size {value, range: length_range(fixed, 1000.0)})
+            }
+            Some(live_id!(Margin)) | Some(live_id!(Padding)) => {
+                let value = if let Some(v) = Scalar::number(registry, index, nodes) {
+                    Padding {left: v, top: v, right: v, bottom: v}
+                }
+                else if matches!(nodes[index].value, LiveValue::Object | LiveValue::Clone {..}) {
+                    let side = | id: LiveId | nodes.child_by_name(index, id.as_field())
+                        .and_then( | child | Scalar::number(registry, child, nodes))
+                        .unwrap_or(0.0);
+                    Padding {left: side(live_id!(left)), top: side(live_id!(top)), right: side(live_id!(right)), bottom: side(live_id!(bottom))}
+                }
+                else {
+                    return None
+                };
+                let max = value.left.max(value.top).max(value.right).max(value.bottom);
+                return Some(Self::Sides {value, range: length_range(max, 50.0)})
+            }
+            Some(live_id!(Align)) => {
+                if !matches!(nodes[index].value, LiveValue::Object | LiveValue::Clone {..}) {
+                    return None
+                }
+                let axis = | id: LiveId | nodes.child_by_name(index, id.as_field())
+                    .and_then( | child | Scalar::number(registry, child, nodes))
+                    .unwrap_or(0.0);
+                return Some(Self::Align(Align {x: axis(live_id!(x)), y: axis(live_id!(y))}))
+            }
+            _ => ()
+        }
+        Some(match Scalar::from_node(registry, index, nodes)? {
+            Scalar::Number {value, integer} => {
+                let integer = type_name.map_or(integer, | name | INTEGER_TYPES.contains(&name));
+                Self::Number {value, integer, range: number_range(value, integer)}
+            }
+            Scalar::Id(value) => Self::Enum {
+                value,
+                variants: registry.enum_variants.get(&type_info?.live_type)?.clone()
+            },
+            Scalar::Color(value) => Self::Color(value),
+            Scalar::Bool(value) => Self::Bool(value),
+            Scalar::Text(value) => Self::Text(value),
+        })
+    }
+
+    fn template(&self) -> LiveId {
+        match self {
+            Self::Number {..} => live_id!(Number),
+            Self::Size {..} => live_id!(Size),
+            Self::Sides {..} => live_id!(Sides),
+            Self::Align(_) => live_id!(Align),
+            Self::Enum {..} => live_id!(Enum),
+            Self::Bool(_) => live_id!(Bool),
+            Self::Text(_) => live_id!(Text),
+            Self::Color(_) => live_id!(Color),
+        }
+    }
+
+    /// Keeps the slider range of the value this one replaces, so a slider doesn't rescale
+    /// under the mouse while it writes new values into the source.
+    fn keep_range(&mut self, old: &PropertyValue) {
+        let (range, value, old_range) = match (self, old) {
+            (Self::Number {range, value, ..}, Self::Number {range: old_range, ..}) => (range, *value, old_range),
+            (Self::Size {range, value, ..}, Self::Size {range: old_range, ..}) => {
+                (range, if let Size::Fixed(v) = value {*v} else {0.0}, old_range)
+            }
+            (Self::Sides {range, value, ..}, Self::Sides {range: old_range, ..}) => {
+                (range, value.left.max(value.top).max(value.right).max(value.bottom), old_range)
+            }
+            _ => return
+        };
+        *range = (old_range.0.min(value), old_range.1.max(value));
+    }
+
+    /// Puts the value into the widgets of a row made from `template()`.
+    fn sync_row(&self, cx: &mut Cx, item: &WidgetRef) {
+        fn sync_slider(cx: &mut Cx, slider: SliderRef, value: f64, range: (f64, f64), integer: bool) {
+            slider.apply_over(cx, live!{
+                min: (range.0),
+                max: (range.1),
+                step: (if integer {1.0} else {0.0}),
+                precision: (if integer {0usize} else {2usize})
+            });
+            slider.set_text(&value.to_string());
+        }
+        match self {
+            Self::Number {value, integer, range} => {
+                sync_slider(cx, item.slider(id!(value)), *value, *range, *integer);
+            }
+            Self::Size {value, range} => {
+                let (kind, fixed) = match value {
+                    Size::Fill => (0, 0.0),
+                    Size::Fit => (1, 0.0),
+                    Size::Fixed(v) => (2, *v),
+                    Size::All => (3, 0.0),
+                };
+                item.drop_down(id!(kind)).set_selected_item(kind);
+                sync_slider(cx, item.slider(id!(value)), fixed, *range, false);
+            }
+            Self::Sides {value, range} => {
+                for (id, side) in [(live_id!(left), value.left), (live_id!(top), value.top), (live_id!(right), value.right), (live_id!(bottom), value.bottom)] {
+                    sync_slider(cx, item.slider(&[id]), side, *range, false);
+                }
+            }
+            Self::Align(value) => {
+                sync_slider(cx, item.slider(id!(x)), value.x, (0.0, 1.0), false);
+                sync_slider(cx, item.slider(id!(y)), value.y, (0.0, 1.0), false);
+            }
+            Self::Enum {value, variants} => {
+                let drop_down = item.drop_down(id!(value));
+                drop_down.set_labels(variants.iter().map( | variant | variant.to_string()).collect());
+                drop_down.set_selected_item(variants.iter().position( | variant | variant == value).unwrap_or(0));
+            }
+            Self::Bool(value) => {
+                item.check_box(id!(value)).set_selected(cx, *value);
+            }
+            Self::Text(value) => {
+                item.text_input(id!(value)).set_text(value);
+            }
+            Self::Color(value) => {
+                item.color_picker(id!(value)).set_color(cx, *value);
+                item.text_input(id!(hex)).set_text(&color_literal(*value));
+            }
+        }
+        item.redraw(cx);
+    }
+
+    /// The source text for the edit the widgets of a row just made, if they made one.
+    fn edited(&self, item: &WidgetRef, actions: &Actions) -> Option<String> {
+        match self {
+            Self::Number {integer, ..} => {
+                item.slider(id!(value)).slided(actions).map( | v | number_literal(v, *integer))
+            }
+            Self::Size {value, ..} => {
+                if let Some(kind) = item.drop_down(id!(kind)).selected(actions) {
+                    return Some(match kind {
+                        0 => "Fill".to_string(),
+                        1 => "Fit".to_string(),
+                        3 => "All".to_string(),
+                        _ => number_literal(match value {
+                            Size::Fixed(v) => *v,
+                            _ => item.slider(id!(value)).value().filter( | v | *v > 0.0).unwrap_or(100.0)
+                        }, false)
+                    })
+                }
+                item.slider(id!(value)).slided(actions).map( | v | number_literal(v, false))
+            }
+            Self::Sides {value, ..} => {
+                let mut sides = *value;
+                let mut changed = false;
+                for (id, side) in [(live_id!(left), &mut sides.left), (live_id!(top), &mut sides.top), (live_id!(right), &mut sides.right), (live_id!(bottom), &mut sides.bottom)] {
+                    if let Some(v) = item.slider(&[id]).slided(actions) {
+                        *side = v;
+                        changed = true;
+                    }
+                }
+                changed.then(|| format!(
+                    "{{left: {}, top: {}, right: {}, bottom: {}}}",
+                    number_literal(sides.left, false),
+                    number_literal(sides.top, false),
+                    number_literal(sides.right, false),
+                    number_literal(sides.bottom, false)
+                ))
+            }
+            Self::Align(value) => {
+                let x = item.slider(id!(x)).slided(actions);
+                let y = item.slider(id!(y)).slided(actions);
+                if x.is_none() && y.is_none() {
+                    return None
+                }
+                Some(format!(
+                    "{{x: {}, y: {}}}",
+                    number_literal(x.unwrap_or(value.x), false),
+                    number_literal(y.unwrap_or(value.y), false)
+                ))
+            }
+            Self::Enum {variants, ..} => {
+                item.drop_down(id!(value)).selected(actions)
+                    .and_then( | index | variants.get(index))
+                    .map( | variant | variant.to_string())
+            }
+            Self::Bool(_) => {
+                item.check_box(id!(value)).changed(actions).map( | v | v.to_string())
+            }
+            Self::Text(_) => {
+                item.text_input(id!(value)).returned(actions).map( | v | string_literal(&v))
+            }
+            Self::Color(value) => {
+                if let Some(rgba) = item.color_picker(id!(value)).changed(actions) {
+                    return Some(color_literal(vec4(rgba.x, rgba.y, rgba.z, value.w)))
+                }
+                item.text_input(id!(hex)).returned(actions).map( | hex | {
+                    let hex = hex.trim();
+                    if hex.starts_with('#') {hex.to_string()} else {format!("#{}", hex)}
+                })
+            }
+        }
+    }
+
+    /// Whether the edit that is going on in the widgets of a row is done, so the next one
+    /// goes into a new undo group.
+    fn edit_done(&self, item: &WidgetRef, actions: &Actions) -> bool {
+        let slider_done = | slider: SliderRef | matches!(
+            actions.find_widget_action_cast(slider.widget_uid()),
+            SliderAction::EndSlide | SliderAction::TextSlide(_)
+        );
+        match self {
+            Self::Number {..} | Self::Size {..} => slider_done(item.slider(id!(value))),
+            Self::Sides {..} => [id!(left), id!(top), id!(right), id!(bottom)].into_iter().any( | id | slider_done(item.slider(id))),
+            Self::Align(_) => slider_done(item.slider(id!(x))) || slider_done(item.slider(id!(y))),
+            Self::Color(_) => item.color_picker(id!(value)).done_changing(actions),
+            Self::Enum {..} | Self::Bool(_) | Self::Text(_) => true,
+        }
+    }
+}
+
+const INTEGER_TYPES: [LiveId; 6] = [live_id!(i32), live_id!(u32), live_id!(i64), live_id!(u64), live_id!(usize), live_id!(isize)];
+
+/// A slider range around `value`. Floats between 0 and 1 are mostly factors, like the `x`
+/// of an `Align`, so they get just that range.
+fn number_range(value: f64, integer: bool) -> (f64, f64) {
+    if !integer && (0.0..=1.0).contains(&value) {
+        return (0.0, 1.0)
+    }
+    let max = (value.abs() * 2.0).max(100.0);
+    (if value < 0.0 {-max} else {0.0}, max)
+}
+
+fn length_range(value: f64, max: f64) -> (f64, f64) {
+    (0.0, (value * 2.0).max(max))
+}
+
+fn number_literal(value: f64, integer: bool) -> String {
+    if integer {
+        return format!("{}", value.round() as i64)
+    }
+    let text = format!("{:.2}", value);
+    let text = text.trim_end_matches('0');
+    if text.ends_with('.') {format!("{}0", text)} else {text.to_string()}
+}
+
+fn color_literal(value: Vec4) -> String {
+    let rgba = value.to_u32();
+    if rgba & 0xff == 0xff {format!("#{:06x}", rgba >> 8)} else {format!("#{:08x}", rgba)}
+}
+
+fn string_literal(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Finds the field `id` in `type_info`, or in the fields it derefs to.
+fn find_field(type_info: &LiveTypeInfo, id: LiveId) -> Option<&LiveTypeField> {
+    type_info.fields.iter().find( | field | field.id == id).or_else(|| {
+        type_info.fields.iter()
+            .filter( | field | field.live_field_kind == LiveFieldKind::Deref)
+            .find_map( | field | find_field(&field.live_type_info, id))
+    })
+}
+
+/// The editable properties of the component at `ptr`, in the order its expanded node lists them.
+fn component_properties(cx: &mut Cx, ptr: LivePtr) -> Vec<Property> {
+    // the walk and layout of a widget sit right in its body, but aren't fields of its type
+    let mut walk_layout = Walk::live_type_info(cx).fields;
+    walk_layout.extend(Layout::live_type_info(cx).fields);
+    let registry = cx.live_registry.borrow();
+    let (nodes, index) = registry.ptr_to_nodes_index(ptr);
+    let type_info = match &nodes[index].value {
+        LiveValue::Class {live_type, ..} => registry.live_type_infos.get(live_type),
+        _ => None
+    };
+    let mut properties = Vec::new();
+    collect_properties(&registry, type_info, &walk_layout, &mut Vec::new(), index, nodes, &mut properties);
+    properties
+}
+
+fn collect_properties(
+    registry: &LiveRegistry,
+    type_info: Option<&LiveTypeInfo>,
+    walk_layout: &[LiveTypeField],
+    path: &mut Vec<LiveId>,
+    parent: usize,
+    nodes: &[LiveNode],
+    out: &mut Vec<Property>
+) {
+    let mut child = nodes.first_child(parent);
+    while let Some(index) = child {
+        child = nodes.next_child(index);
+        let node = &nodes[index];
+        if node.origin.prop_type() != LivePropType::Field || node.id == live_id!(animator) {
+            continue
+        }
+        let field = match type_info {
+            Some(type_info) => {
+                let field = find_field(type_info, node.id)
+                    .or_else(|| path.is_empty().then(|| walk_layout.iter().find( | field | field.id == node.id)).flatten());
+                match field {
+                    Some(field) if field.live_field_kind == LiveFieldKind::Animator => continue,
+                    Some(field) => Some(field),
+                    // without a field to take it, a value is only for the shaders to read
+                    None => continue
+                }
+            }
+            None => None
+        };
+        path.push(node.id);
+        if let Some(value) = PropertyValue::from_node(registry, field.map( | field | &field.live_type_info), index, nodes) {
+            out.push(Property {path: path.clone(), value});
+        }
+        else if path.len() < MAX_PROPERTY_DEPTH && matches!(node.value, LiveValue::Object | LiveValue::Clone {..} | LiveValue::Class {..}) {
+            let type_info = match &node.value {
+                LiveValue::Class {live_type, ..} => registry.live_type_infos.get(live_type),
+                _ => field.map( | field | &field.live_type_info)
+            };
+            collect_properties(registry, type_info, walk_layout, path, index, nodes, out);
+        }
+        path.pop();
+    }
+}
+
+/// Where a property is in the tokens of a component, see `find_value`.
+enum ValueTokens {
+    Found(std::ops::Range<usize>),
+    /// The first `matched` ids of the path are there, and the rest has to go into the body
+    /// that opens at token `open`.
+    Missing {open: usize, matched: usize},
+}
+
+/// The token that opens the body of the component whose name or class starts at `origin`.
+fn body_open(tokens: &[TokenWithSpan], origin: usize) -> Option<usize> {
+    let open = (origin..tokens.len()).find( | i | tokens[*i].token.is_open_delim(Delim::Brace))?;
+    // a `{{RustType}}` class is no body
+    if tokens.get(open + 1)?.token.is_open_delim(Delim::Brace) {
+        let close = group_end(tokens, open);
+        return (close..tokens.len()).find( | i | tokens[*i].token.is_open_delim(Delim::Brace))
+    }
+    Some(open)
+}
+
+/// The token after the close that matches the open at `open`.
+fn group_end(tokens: &[TokenWithSpan], open: usize) -> usize {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        if token.token.is_open() {
+            depth += 1;
+        }
+        else if token.token.is_close() {
+            depth -= 1;
+            if depth == 0 {
+                return i + 1
+            }
+        }
+    }
+    tokens.len()
+}
+
+/// The token after the value that starts at `start`.
+fn value_end(tokens: &[TokenWithSpan], start: usize) -> usize {
+    let mut i = start;
+    if tokens[i].token.is_punct_id(live_id!(-)) {
+        i += 1;
+    }
+    if tokens[i].token.is_punct_id(live_id!(<)) {
+        // `<Class> {..}`
+        while i < tokens.len() && !tokens[i].token.is_punct_id(live_id!(>)) {
+            i += 1;
+        }
+        i += 1;
+        if tokens.get(i).is_some_and( | token | token.token.is_open()) {
+            return group_end(tokens, i)
+        }
+        return i
+    }
+    if tokens[i].token.is_ident() && tokens.get(i + 1).is_some_and( | token | token.token.is_open()) {
+        // `Fixed(10.0)` and `dep(..)`
+        i += 1;
+    }
+    if tokens[i].token.is_open() {
+        return group_end(tokens, i)
+    }
+    i + 1
+}
+
+/// Finds the value of `path` in the body that opens at `open`, going into the object values
+/// and class bodies on the way. The last one wins, like it does when the DSL is applied.
+fn find_value(tokens: &[TokenWithSpan], open: usize, path: &[LiveId], matched: usize) -> Option<ValueTokens> {
+    let mut found = None;
+    let mut depth = 0;
+    let mut i = open + 1;
+    while i < tokens.len() {
+        let token = &tokens[i].token;
+        if token.is_open() {
+            depth += 1;
+        }
+        else if token.is_close() {
+            if depth == 0 {
+                break
+            }
+            depth -= 1;
+        }
+        else if depth == 0 && *token == LiveToken::Ident(path[0]) && tokens.get(i + 1).is_some_and( | next | next.token.is_punct_id(live_id!(:))) {
+            found = Some(i + 2);
+        }
+        i += 1;
+    }
+    let Some(start) = found else {
+        return Some(ValueTokens::Missing {open, matched})
+    };
+    let end = value_end(tokens, start);
+    if path.len() == 1 {
+        return Some(ValueTokens::Found(start..end))
+    }
+    // the rest of the path has to be in an object or a class body
+    let inner = (start..end).find( | i | tokens[*i].token.is_open_delim(Delim::Brace))?;
+    find_value(tokens, inner, &path[1..], matched + 1)
+}
+
+/// The text for `path: value`, nesting objects for all but the last id.
+fn nested_value(path: &[LiveId], value: &str) -> String {
+    match path {
+        [id] => format!("{}: {}", id, value),
+        [id, rest @ ..] => format!("{}: {{{}}}", id, nested_value(rest, value)),
+        [] => value.to_string()
+    }
+}
+
+/// Edits the live properties of the component selected in the designer, with an editor that
+/// fits the type of each of them. Edits go into the source of the component right away.
+#[derive(Live, LiveHook, Widget)]
+pub struct DesignerProperties {
+    #[deref] view: View,
+    #[rust] component: Option<(LiveId, LivePtr)>,
+    #[rust] properties: Vec<Property>,
+    #[rust] synced: Vec<bool>,
+    #[rust] undo_group: Option<u64>,
+}
+
+impl DesignerProperties {
+    fn update_properties(&mut self, cx: &mut Cx, data: &DesignerData) {
+        let component = data.selected.and_then( | id | match data.node_map.get(&id) {
+            Some(OutlineNode::Component {ptr, name, ..}) => Some((id, *ptr, name.clone())),
+            _ => None
+        }).filter(|(_, ptr, _)| {
+            // the outline catches up with a live edit a little after the registry does
+            cx.live_registry.borrow().file_id_to_file(ptr.file_id).generation == ptr.generation
+        });
+        if component.as_ref().map(|(id, ptr, _)| (*id, *ptr)) == self.component {
+            return
+        }
+        let mut properties = Vec::new();
+        if let Some((id, ptr, _)) = &component {
+            properties = component_properties(cx, *ptr);
+            if self.component.is_some_and(|(old_id, _)| old_id == *id) {
+                for property in &mut properties {
+                    if let Some(old) = self.properties.iter().find( | old | old.path == property.path) {
+                        property.value.keep_range(&old.value);
+                    }
+                }
+            }
+        }
+        self.component = component.as_ref().map(|(id, ptr, _)| (*id, *ptr));
+        self.synced = vec![false; properties.len()];
+        self.properties = properties;
+        let title = component.map_or("Properties".to_string(), |(_, _, name)| name);
+        self.view.label(id!(title)).set_text(&title);
+        self.view.redraw(cx);
+    }
+
+    fn draw_properties(&mut self, cx: &mut Cx2d, list: &mut PortalList) {
+        list.set_item_range(cx, 0, self.properties.len());
+        while let Some(item_id) = list.next_visible_item(cx) {
+            let Some(property) = self.properties.get(item_id) else {
+                continue
+            };
+            let Some((item, existed)) = list.item_with_existed(cx, item_id, property.value.template()) else {
+                continue
+            };
+            if !existed || !self.synced[item_id] {
+                item.label(id!(label)).set_text(&property.path.iter().map( | id | id.to_string()).collect::<Vec<_>>().join("."));
+                property.value.sync_row(cx, &item);
+                self.synced[item_id] = true;
+            }
+            item.draw_all(cx, &mut Scope::empty());
+        }
+    }
+
+    /// Replaces the value of the property at `path` in the source of the selected component
+    /// with `value`, or adds the property to its body when it only inherits it.
+    fn write_property(&mut self, cx: &mut Cx, data: &DesignerData, path: &[LiveId], value: &str) {
+        let Some((id, _)) = self.component else {
+            return
+        };
+        let Some(OutlineNode::Component {token_id, ..}) = data.node_map.get(&id) else {
+            return
+        };
+        let Some(file_id) = token_id.file_id() else {
+            return
+        };
+        let (tokens, replace) = {
+            let registry = cx.live_registry.borrow();
+            let tokens = &registry.file_id_to_file(file_id).original.tokens;
+            let Some(open) = body_open(tokens, token_id.token_index()) else {
+                return
+            };
+            match find_value(tokens, open, path, 0) {
+                Some(ValueTokens::Found(range)) => (range, value.to_string()),
+                Some(ValueTokens::Missing {open, matched}) => {
+                    let text = nested_value(&path[matched..], value);
+                    let next = &tokens[open + 1];
+                    let replace = if next.span.start.line > tokens[open].span.start.line {
+                        format!("{}\n{}", text, " ".repeat(next.span.start.column as usize))
+                    }
+                    else if next.token.is_close() {
+                        text
+                    }
+                    else {
+                        format!("{}, ", text)
+                    };
+                    (open + 1..open + 1, replace)
+                }
+                None => return
+            }
+        };
+        let undo_group = *self.undo_group.get_or_insert_with(|| LiveId::unique().0);
+        cx.patch_live_tokens(file_id, tokens, replace, undo_group);
+    }
+}
+
+impl Widget for DesignerProperties {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        let data = scope.props.get::<DesignerData>().unwrap();
+        self.update_properties(cx, data);
+        let list = self.view.portal_list(id!(list));
+        self.view.handle_event(cx, event, &mut Scope::empty());
+        if let Event::Actions(actions) = event {
+            for (item_id, item) in list.items_with_actions(actions) {
+                let Some(property) = self.properties.get(item_id).cloned() else {
+                    continue
+                };
+                if let Some(value) = property.value.edited(&item, actions) {
+                    self.write_property(cx, data, &property.path, &value);
+                }
+                if property.value.edit_done(&item, actions) {
+                    self.undo_group = None;
+                }
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.update_properties(cx, scope.props.get::<DesignerData>().unwrap());
+        while let Some(step) = self.view.draw_walk(cx, &mut Scope::empty(), walk).step() {
+            if let Some(mut list) = step.as_portal_list().borrow_mut() {
+                self.draw_properties(cx, &mut list);
+            }
+        }
+        DrawStep::done()
+    }
+}
@@ -227,7 +227,7 @@ pub enum DockItem {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct LiveIdStore(pub LiveId);
 
 impl SerRon for LiveIdStore {
@@ -249,7 +249,7 @@ impl DeRon for LiveIdStore {
             DeRonTok::U64(value)=>LiveId(value),
             DeRonTok::I64(value)=>LiveId(value as u64),            DeRonTok::F64(value)=>LiveId(value as u64),
             DeRonTok::Ident=>{
-                LiveId::from_str_with_lut(&s.identbuf).unwrap()
+                LiveId::from_str_with_lut(&s.identbuf).map_err(|_| s.err_parse("liveid"))?
             }
             _=>{ // err
                 return Err(s.err_token("liveid"))
@@ -260,8 +260,36 @@ impl DeRon for LiveIdStore {
     }
 }
 
+impl SerJson for LiveIdStore {
+    fn ser_json(&self, _d: usize, s: &mut SerJsonState) {
+        self.0.as_string(|v|{
+            if let Some(v) = v{
+                s.label(v);
+            }
+            else{
+                s.out.push_str(&self.0.0.to_string());
+            }
+        });
+    }
+}
 
-#[derive(Clone, Debug, SerRon, DeRon)]
+impl DeJson for LiveIdStore {
+    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<LiveIdStore, DeJsonErr> {
+        let liveid = match s.tok{
+            DeJsonTok::U64(value)=>LiveId(value),
+            DeJsonTok::Str=>{
+                LiveId::from_str_with_lut(&s.strbuf).map_err(|_| s.err_parse("liveid"))?
+            }
+            _=>{ // err
+                return Err(s.err_token("liveid"))
+            }
+        };
+        s.next_tok(i) ?;
+        Ok(LiveIdStore(liveid))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, SerRon, DeRon, SerJson, DeJson)]
 pub enum DockItemStore{
     Splitter {
         id: LiveIdStore,
@@ -507,8 +535,115 @@ impl Dock {
         out
     }
     
-    pub fn from_store_item(&mut self, _store:&[DockItemStore]){
+    pub fn from_store_item(&mut self, cx: &mut Cx, store:&[DockItemStore]){
+        let mut dock_items = HashMap::new();
+        for item in store{
+            match item{
+                DockItemStore::Splitter{
+                    id,
+                    axis,
+                    align,
+                    a,
+                    b
+                }=>{
+                    dock_items.insert(id.0, DockItem::Splitter{
+                        axis: *axis,
+                        align: *align,
+                        a: a.0,
+                        b: b.0
+                    });
+                }
+                DockItemStore::Tabs{
+                    id,
+                    tabs,
+                    selected,
+                    closable
+                }=>{
+                    dock_items.insert(id.0, DockItem::Tabs{
+                        tabs: tabs.iter().map(|v| v.0).collect(),
+                        selected: *selected,
+                        closable: *closable
+                    });
+                }
+                DockItemStore::Tab{
+                    id,
+                    name,
+                    template,
+                    kind
+                }=>{
+                    dock_items.insert(id.0, DockItem::Tab{
+                        name: name.clone(),
+                        template: template.0,
+                        kind: kind.0
+                    });
+                }
+            }
+        }
+        if !dock_items.contains_key(&live_id!(root)){
+            warning!("Dock layout has no root item, keeping the current layout");
+            return
+        }
+        // every splitter side has to be a splitter or tab bar of its own, reached only once from root
+        let mut stack = vec![live_id!(root)];
+        let mut seen = Vec::new();
+        while let Some(id) = stack.pop(){
+            if seen.contains(&id){
+                warning!("Dock layout reaches item {} twice, keeping the current layout", id);
+                return
+            }
+            seen.push(id);
+            match dock_items.get(&id){
+                Some(DockItem::Splitter{a, b, ..})=>{
+                    stack.push(*a);
+                    stack.push(*b);
+                }
+                Some(DockItem::Tabs{..})=>(),
+                _=>{
+                    warning!("Dock layout refers to a missing splitter or tab bar {}, keeping the current layout", id);
+                    return
+                }
+            }
+        }
+        // tabs that were filtered out of the store leave dangling references behind
+        let tab_ids:Vec<LiveId> = dock_items.iter().filter_map(|(id, item)|{
+            if let DockItem::Tab{..} = item{Some(*id)} else {None}
+        }).collect();
+        for item in dock_items.values_mut(){
+            if let DockItem::Tabs{tabs, selected, ..} = item{
+                tabs.retain(|id| tab_ids.contains(id));
+                if *selected >= tabs.len(){
+                    *selected = tabs.len().saturating_sub(1);
+                }
+            }
+        }
+        self.dock_items = dock_items;
         
+        // keep the widgets of tabs that survived with the same kind, drop the rest
+        let dock_items = &self.dock_items;
+        self.items.retain(|id, (kind, _)|{
+            if let Some(DockItem::Tab{kind:new_kind, ..}) = dock_items.get(id){
+                return new_kind == kind
+            }
+            false
+        });
+        // tab bars and splitters are recreated on the next draw
+        self.tab_bars.clear();
+        self.splitters.clear();
+        self.drop_state = None;
+        self.needs_save = false;
+        
+        let mut items = Vec::new();
+        for (item_id, item) in self.dock_items.iter() {
+            if let DockItem::Tab {kind, ..} = item {
+                if self.templates.contains_key(kind){
+                    items.push((*item_id, *kind));
+                }
+            }
+        }
+        for (item_id, kind) in items {
+            self.item_or_create(cx, item_id, kind);
+        }
+        self.area.redraw(cx);
     }
     
     pub fn item(&mut self, entry_id: LiveId) -> Option<WidgetRef> {
@@ -1212,6 +1347,12 @@ impl DockRef {
         LiveId(0)
     }
         
+    pub fn load_state(&self, cx: &mut Cx, dock_items: Vec<DockItemStore>) {
+        if let Some(mut dock) = self.borrow_mut() {
+            dock.from_store_item(cx, &dock_items);
+        }
+    }
+    
     pub fn needs_save(&self)->Option<Vec<DockItemStore>>{
         if let Some(mut dock) = self.borrow_mut() {
             if dock.needs_save{
@@ -176,6 +176,17 @@ impl DropDown {
 
 impl Widget for DropDown {
     
+    fn accessibility(&self, _cx: &Cx) -> Option<NavAccessible> {
+        let mut accessible = NavAccessible::new(self.draw_bg.area(), AccessibilityRole::ComboBox, "").with_state(AccessibilityState {
+            expanded: Some(self.is_open),
+            ..Default::default()
+        });
+        if let Some(label) = self.labels.get(self.selected_item) {
+            accessible = accessible.with_value(label.clone());
+        }
+        Some(accessible)
+    }
+    
     fn widget_to_data(&self, _cx: &mut Cx, actions: &Actions, nodes: &mut LiveNodeVec, path: &[LiveId]) -> bool {
         match actions.find_widget_action_cast(self.widget_uid()) {
             DropDownAction::Select(_, value) => {
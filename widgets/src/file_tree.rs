@@ -62,6 +62,7 @@ pub struct FileTreeNode {
     #[live] draw_bg: DrawBgQuad,
     #[live] draw_icon: DrawIconQuad,
     #[live] draw_name: DrawNameText,
+    #[live] draw_status: DrawNameText,
     #[live] check_box: CheckBox,
     #[layout] layout: Layout,
     
@@ -101,11 +102,21 @@ pub struct FileTree {
     #[rust] open_nodes: HashSet<LiveId>,
     
     #[rust] tree_nodes: ComponentMap<LiveId, (FileTreeNode, LiveId)>,
+    // every node of the last draw that isn't folded away, for moving the selection by keyboard
+    #[rust] node_order: Vec<FileTreeEntry>,
     
     #[rust] count: usize,
     #[rust] stack: Vec<f64>,
 }
 
+#[derive(Clone, Copy)]
+struct FileTreeEntry {
+    node_id: LiveId,
+    is_folder: bool,
+    y: f64,
+    height: f64,
+}
+
 impl LiveHook for FileTree {
     fn after_apply(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) {
         for (_, (tree_node, id)) in self.tree_nodes.iter_mut() {
@@ -125,6 +136,13 @@ pub enum FileTreeAction {
     ShouldFileStartDrag(LiveId),
 }
 
+/// A short text drawn at the end of a node, like its version control status.
+#[derive(Clone, Copy, Debug)]
+pub struct FileNodeStatus<'a> {
+    pub text: &'a str,
+    pub color: Vec4,
+}
+
 pub enum FileTreeNodeAction {
     WasClicked,
     Opening,
@@ -141,9 +159,13 @@ impl FileTreeNode {
         self.draw_icon.scale = scale as f32;
         self.draw_icon.is_even = is_even;
         self.draw_name.font_scale = scale;
+        self.draw_status.scale = scale as f32;
+        self.draw_status.is_even = is_even;
+        self.draw_status.font_scale = scale;
     }
     
-    pub fn draw_folder(&mut self, cx: &mut Cx2d, name: &str, is_even: f32, node_height: f64, depth: usize, scale: f64) {
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_folder(&mut self, cx: &mut Cx2d, name: &str, status: Option<FileNodeStatus>, is_even: f32, node_height: f64, depth: usize, scale: f64) {
         self.set_draw_state(is_even, scale);
         
         self.draw_bg.begin(cx, Walk::size(Size::Fill, Size::Fixed(scale * node_height)), self.layout);
@@ -153,10 +175,12 @@ impl FileTreeNode {
         self.draw_icon.draw_walk(cx, self.icon_walk);
         
         self.draw_name.draw_walk(cx, Walk::fit(), Align::default(), name);
+        self.draw_status(cx, status);
         self.draw_bg.end(cx);
     }
     
-    pub fn draw_file(&mut self, cx: &mut Cx2d, name: &str, is_even: f32, node_height: f64, depth: usize, scale: f64) {
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_file(&mut self, cx: &mut Cx2d, name: &str, status: Option<FileNodeStatus>, is_even: f32, node_height: f64, depth: usize, scale: f64) {
         self.set_draw_state(is_even, scale);
         
         self.draw_bg.begin(cx, Walk::size(Size::Fill, Size::Fixed(scale * node_height)), self.layout);
@@ -164,9 +188,23 @@ impl FileTreeNode {
         cx.walk_turtle(self.indent_walk(depth));
         
         self.draw_name.draw_walk(cx, Walk::fit(), Align::default(), name);
+        self.draw_status(cx, status);
         self.draw_bg.end(cx);
     }
     
+    // draws the status at the end of the node
+    fn draw_status(&mut self, cx: &mut Cx2d, status: Option<FileNodeStatus>) {
+        if let Some(status) = status {
+            cx.walk_turtle(Walk::size(Size::Fill, Size::Fixed(0.0)));
+            self.draw_status.color = status.color;
+            let walk = Walk {
+                margin: Margin {right: 5.0, ..Margin::default()},
+                ..Walk::fit()
+            };
+            self.draw_status.draw_walk(cx, walk, Align::default(), status.text);
+        }
+    }
+    
     fn indent_walk(&self, depth: usize) -> Walk {
         Walk {
             abs_pos: None,
@@ -178,6 +216,7 @@ impl FileTreeNode {
                 right: depth as f64 * 4.0,
                 bottom: 0.0,
             },
+            ..Walk::default()
         }
     }
     
@@ -239,6 +278,7 @@ impl FileTree {
     pub fn begin(&mut self, cx: &mut Cx2d, walk: Walk) {
         self.scroll_bars.begin(cx, walk, self.layout);
         self.count = 0;
+        self.node_order.clear();
     }
     
     pub fn end(&mut self, cx: &mut Cx2d) {
@@ -255,6 +295,7 @@ impl FileTree {
         
         self.draw_scroll_shadow.draw(cx, dvec2(0., 0.));
         self.scroll_bars.end(cx);
+        cx.add_nav_stop(self.scroll_bars.area(), NavRole::Tree, Margin::default());
         
         let selected_node_id = self.selected_node_id;
         self.tree_nodes.retain_visible_and( | node_id, _ | Some(*node_id) == selected_node_id);
@@ -264,6 +305,13 @@ impl FileTree {
         if count % 2 == 1 {0.0}else {1.0}
     }
     
+    fn push_entry(&mut self, node_id: LiveId, is_folder: bool, scale: f64) {
+        if scale > 0.2 {
+            let y = self.node_order.last().map_or(self.layout.padding.top, | last | last.y + last.height);
+            self.node_order.push(FileTreeEntry {node_id, is_folder, y, height: self.node_height * scale});
+        }
+    }
+    
     pub fn should_node_draw(&mut self, cx: &mut Cx2d) -> bool {
         let scale = self.stack.last().cloned().unwrap_or(1.0);
         let height = self.node_height * scale;
@@ -282,6 +330,16 @@ impl FileTree {
         cx: &mut Cx2d,
         node_id: LiveId,
         name: &str,
+    ) -> Result<(), ()> {
+        self.begin_folder_with_status(cx, node_id, name, None)
+    }
+    
+    pub fn begin_folder_with_status(
+        &mut self,
+        cx: &mut Cx2d,
+        node_id: LiveId,
+        name: &str,
+        status: Option<FileNodeStatus>,
     ) -> Result<(), ()> {
         let scale = self.stack.last().cloned().unwrap_or(1.0);
         
@@ -290,17 +348,25 @@ impl FileTree {
         }
         
         let is_open = self.open_nodes.contains(&node_id);
+        self.push_entry(node_id, true, scale);
         
         if self.should_node_draw(cx) {
             let folder_node = self.folder_node;
+            let is_selected = self.selected_node_id == Some(node_id);
             let (tree_node, _) = self.tree_nodes.get_or_insert(cx, node_id, | cx | {
                 let mut tree_node = FileTreeNode::new_from_ptr(cx, folder_node);
                 if is_open {
                     tree_node.set_folder_is_open(cx, true, Animate::No)
                 }
+                tree_node.set_is_selected(cx, is_selected, Animate::No);
                 (tree_node, live_id!(folder_node))
             });
-            tree_node.draw_folder(cx, name, Self::is_even(self.count), self.node_height, self.stack.len(), scale);
+            tree_node.draw_folder(cx, name, status, Self::is_even(self.count), self.node_height, self.stack.len(), scale);
+            cx.add_accessible(NavAccessible::new(tree_node.draw_bg.area(), AccessibilityRole::TreeItem, name).with_state(AccessibilityState {
+                selected: is_selected,
+                expanded: Some(is_open),
+                ..Default::default()
+            }));
             self.stack.push(tree_node.opened as f64 * scale);
             if tree_node.opened <= 0.001 {
                 self.end_folder();
@@ -323,17 +389,29 @@ impl FileTree {
     }
     
     pub fn file(&mut self, cx: &mut Cx2d, node_id: LiveId, name: &str) {
+        self.file_with_status(cx, node_id, name, None)
+    }
+    
+    pub fn file_with_status(&mut self, cx: &mut Cx2d, node_id: LiveId, name: &str, status: Option<FileNodeStatus>) {
         let scale = self.stack.last().cloned().unwrap_or(1.0);
         
         if scale > 0.2 {
             self.count += 1;
         }
+        self.push_entry(node_id, false, scale);
         if self.should_node_draw(cx) {
             let file_node = self.file_node;
+            let is_selected = self.selected_node_id == Some(node_id);
             let (tree_node, _) = self.tree_nodes.get_or_insert(cx, node_id, | cx | {
-                (FileTreeNode::new_from_ptr(cx, file_node), live_id!(file_node))
+                let mut tree_node = FileTreeNode::new_from_ptr(cx, file_node);
+                tree_node.set_is_selected(cx, is_selected, Animate::No);
+                (tree_node, live_id!(file_node))
             });
-            tree_node.draw_file(cx, name, Self::is_even(self.count), self.node_height, self.stack.len(), scale);
+            tree_node.draw_file(cx, name, status, Self::is_even(self.count), self.node_height, self.stack.len(), scale);
+            cx.add_accessible(NavAccessible::new(tree_node.draw_bg.area(), AccessibilityRole::TreeItem, name).with_state(AccessibilityState {
+                selected: is_selected,
+                ..Default::default()
+            }));
         }
     }
     
@@ -372,6 +450,36 @@ impl FileTree {
         }
     }
     
+    fn select_node(&mut self, cx: &mut Cx, node_id: LiveId) {
+        if let Some(last_selected) = self.selected_node_id {
+            if last_selected != node_id {
+                if let Some((tree_node, _)) = self.tree_nodes.get_mut(&last_selected) {
+                    tree_node.set_is_selected(cx, false, Animate::Yes);
+                }
+            }
+        }
+        self.selected_node_id = Some(node_id);
+    }
+    
+    // moves the selection to the next or previous node and keeps it in view
+    fn select_by_key(&mut self, cx: &mut Cx, delta: isize) {
+        let current = self.selected_node_id.and_then( | id | self.node_order.iter().position( | e | e.node_id == id));
+        let index = match current {
+            Some(current) => (current as isize + delta).clamp(0, self.node_order.len() as isize - 1) as usize,
+            None => 0
+        };
+        let Some(entry) = self.node_order.get(index).copied() else {
+            return
+        };
+        self.select_node(cx, entry.node_id);
+        if let Some((tree_node, _)) = self.tree_nodes.get_mut(&entry.node_id) {
+            tree_node.set_is_selected(cx, true, Animate::Yes);
+            tree_node.set_is_focussed(cx, true, Animate::Yes);
+        }
+        self.scroll_bars.scroll_into_view(cx, Rect {pos: dvec2(0.0, entry.y), size: dvec2(0.0, entry.height)});
+        self.scroll_bars.redraw(cx);
+    }
+    
     pub fn start_dragging_file_node(
         &mut self,
         cx: &mut Cx,
@@ -418,12 +526,7 @@ impl Widget for FileTree {
                 }
                 FileTreeNodeAction::WasClicked => {
                     cx.set_key_focus(self.scroll_bars.area());
-                    if let Some(last_selected) = self.selected_node_id {
-                        if last_selected != node_id {
-                            self.tree_nodes.get_mut(&last_selected).unwrap().0.set_is_selected(cx, false, Animate::Yes);
-                        }
-                    }
-                    self.selected_node_id = Some(node_id);
+                    self.select_node(cx, node_id);
                     if self.is_folder(node_id){
                         cx.widget_action(uid, &scope.path, FileTreeAction::FolderClicked(node_id));
                     }
@@ -441,19 +544,42 @@ impl Widget for FileTree {
                 
         match event.hits(cx, self.scroll_bars.area()) {
             Hit::KeyFocus(_) => {
-                if let Some(node_id) = self.selected_node_id {
-                    self.tree_nodes.get_mut(&node_id).unwrap().0.set_is_focussed(cx, true, Animate::Yes);
+                if let Some((tree_node, _)) = self.selected_node_id.and_then( | id | self.tree_nodes.get_mut(&id)) {
+                    tree_node.set_is_focussed(cx, true, Animate::Yes);
                 }
             }
             Hit::KeyFocusLost(_) => {
-                if let Some(node_id) = self.selected_node_id {
-                    self.tree_nodes.get_mut(&node_id).unwrap().0.set_is_focussed(cx, false, Animate::Yes);
+                if let Some((tree_node, _)) = self.selected_node_id.and_then( | id | self.tree_nodes.get_mut(&id)) {
+                    tree_node.set_is_focussed(cx, false, Animate::Yes);
                 }
             }
+            Hit::KeyDown(ke) => match ke.key_code {
+                KeyCode::ArrowDown => self.select_by_key(cx, 1),
+                KeyCode::ArrowUp => self.select_by_key(cx, -1),
+                KeyCode::ReturnKey | KeyCode::NumpadEnter | KeyCode::Space if !ke.is_repeat => {
+                    let Some(entry) = self.selected_node_id.and_then( | id | self.node_order.iter().find( | e | e.node_id == id)).copied() else {
+                        return
+                    };
+                    if entry.is_folder {
+                        let is_open = self.open_nodes.contains(&entry.node_id);
+                        self.set_folder_is_open(cx, entry.node_id, !is_open, Animate::Yes);
+                        self.scroll_bars.redraw(cx);
+                        cx.widget_action(uid, &scope.path, FileTreeAction::FolderClicked(entry.node_id));
+                    }
+                    else {
+                        cx.widget_action(uid, &scope.path, FileTreeAction::FileClicked(entry.node_id));
+                    }
+                }
+                _ => ()
+            }
             _ => ()
         }
     }
     
+    fn accessibility(&self, _cx: &Cx) -> Option<NavAccessible> {
+        Some(NavAccessible::new(self.scroll_bars.area(), AccessibilityRole::Tree, ""))
+    }
+    
     fn draw_walk(&mut self, cx: &mut Cx2d, _scope:&mut Scope,walk: Walk) -> DrawStep {
         if self.draw_state.begin(cx, ()) {
             self.begin(cx, walk);
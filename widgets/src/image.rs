@@ -13,8 +13,8 @@ live_design!{
 pub struct Image {
     #[walk] walk: Walk,
     #[redraw] #[live] draw_bg: DrawQuad,
-    #[live] min_width: i64,
-    #[live] min_height: i64,
+    #[live] placeholder_width: i64,
+    #[live] placeholder_height: i64,
     #[live(1.0)] width_scale: f64,
     #[live] fit: ImageFit,
     #[live] source: LiveDependency,
@@ -68,12 +68,12 @@ impl Image {
         let dpi = cx.current_dpi_factor();
         let (width, height) = if let Some(image_texture) = &self.texture {
             self.draw_bg.draw_vars.set_texture(0, image_texture);
-            let (width,height) = image_texture.get_format(cx).vec_width_height().unwrap_or((self.min_width as usize, self.min_height as usize));
+            let (width,height) = image_texture.get_format(cx).vec_width_height().unwrap_or((self.placeholder_width as usize, self.placeholder_height as usize));
             (width as f64 * self.width_scale, height as f64)
         }
         else {
             self.draw_bg.draw_vars.empty_texture(0);
-            (self.min_width as f64 / dpi, self.min_height as f64 / dpi)
+            (self.placeholder_width as f64 / dpi, self.placeholder_height as f64 / dpi)
         };
         
         let aspect = width / height;
@@ -22,6 +22,10 @@ pub struct Label {
 
 impl Widget for Label {
 
+    fn accessibility(&self, _cx: &Cx) -> Option<NavAccessible> {
+        Some(NavAccessible::new(self.draw_text.area(), AccessibilityRole::Label, self.text.as_ref()))
+    }
+    
     fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk:Walk)->DrawStep{
         self.draw_text.draw_walk(cx, walk.with_add_padding(self.padding), self.align, self.text.as_ref());
         DrawStep::done()
@@ -77,6 +77,7 @@ pub mod designer_view;
 pub mod designer_outline;
 pub mod designer_data;
 pub mod designer_toolbox;
+pub mod designer_properties;
 
 pub use crate::{
     data_binding::{DataBindingStore, DataBindingMap},
@@ -194,4 +195,5 @@ pub fn live_design(cx: &mut Cx) {
     crate::designer_outline::live_design(cx);
     crate::designer_outline_tree::live_design(cx);
     crate::designer_toolbox::live_design(cx);
+    crate::designer_properties::live_design(cx);
 }
@@ -5,6 +5,7 @@ use {
         makepad_draw::*,
         widget::*,
         text_flow::TextFlow,
+        link_label::*,
     },
     std::rc::Rc,
 };
@@ -16,6 +17,12 @@ live_design!{
     }
 } 
 
+#[derive(Clone, Debug, DefaultNone)]
+pub enum MarkdownAction {
+    None,
+    LinkNavigated(String),
+}
+
 #[derive(Live, Widget)]
 pub struct Markdown{
     #[deref] text_flow: TextFlow,
@@ -33,14 +40,30 @@ impl LiveHook for Markdown{
  
 impl Widget for Markdown {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
-        self.text_flow.handle_event(cx, event, scope);
+        let actions = cx.capture_actions(|cx| self.text_flow.handle_event(cx, event, scope));
+        if !actions.is_empty() {
+            // the link labels are drawn per node, keyed by its index
+            for (index, node) in self.doc.nodes.iter().enumerate() {
+                if let MarkdownNode::Link{url_start, end, ..} = node {
+                    let link = self.text_flow.existing_item(LiveId(index as u64), live_id!(link));
+                    if link.is_some_and(|link| link.as_link_label().clicked(&actions)) {
+                        let url = self.doc.decoded[*url_start..*end].to_string();
+                        cx.widget_action(self.widget_uid(), &scope.path, MarkdownAction::LinkNavigated(url));
+                    }
+                }
+            }
+            cx.extend_actions(actions);
+        }
     } 
     
     fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk:Walk)->DrawStep{
         let tf = &mut self.text_flow;
         tf.begin(cx, walk); 
+        let mut table_header = false;
+        let mut table_column = 0;
+        let mut column_widths = Vec::new();
         // alright lets walk the markdown
-        for node in &self.doc.nodes{
+        for (index, node) in self.doc.nodes.iter().enumerate(){
             match node{
                 MarkdownNode::BeginHead{level}=>{
                     cx.turtle_new_line_with_spacing(self.paragraph_spacing);
@@ -68,6 +91,8 @@ impl Widget for Markdown {
                 MarkdownNode::BeginListItem{label}=>{
                     cx.turtle_new_line();
                     let str = match label{
+                        // the checkbox of a task takes the place of its label
+                        _ if matches!(self.doc.nodes.get(index + 1), Some(MarkdownNode::TaskCheckbox{..}))=>"",
                         MarkdownListLabel::Plus=>"+",
                         MarkdownListLabel::Minus=>"-",
                         MarkdownListLabel::Star=>"*",
@@ -81,12 +106,69 @@ impl Widget for Markdown {
                 MarkdownNode::EndListItem=>{
                     tf.end_list_item(cx);
                 },
-                MarkdownNode::Link{start, url_start, end}=>{
-                    tf.draw_text(cx, "Link[name:");
-                    tf.draw_text(cx, &self.doc.decoded[*start..*url_start]);
-                    tf.draw_text(cx, ", url:");
-                    tf.draw_text(cx, &self.doc.decoded[*url_start..*end]);
-                    tf.draw_text(cx, " ]");
+                MarkdownNode::TaskCheckbox{checked}=>{
+                    tf.draw_checkbox(cx, *checked);
+                },
+                MarkdownNode::BeginTable{columns}=>{
+                    cx.turtle_new_line_with_spacing(self.paragraph_spacing);
+                    tf.begin_table(cx);
+                    // columns get a share of the width matching their widest cell
+                    let width = cx.turtle().padded_rect().size.x;
+                    let weights = table_column_weights(&self.doc, index, *columns);
+                    let total:f64 = weights.iter().sum();
+                    column_widths = weights.iter().map(|weight| width * weight / total).collect();
+                },
+                MarkdownNode::EndTable=>{
+                    tf.end_table(cx);
+                },
+                MarkdownNode::BeginTableRow{header}=>{
+                    table_header = *header;
+                    table_column = 0;
+                    tf.begin_table_row(cx);
+                    if table_header{
+                        tf.bold.push();
+                    }
+                },
+                MarkdownNode::EndTableRow=>{
+                    tf.end_table_row(cx);
+                    if table_header{
+                        tf.bold.pop();
+                        tf.sep(cx);
+                    }
+                },
+                MarkdownNode::BeginTableCell{align}=>{
+                    let align = match align{
+                        MarkdownTableAlign::None | MarkdownTableAlign::Left=>0.0,
+                        MarkdownTableAlign::Center=>0.5,
+                        MarkdownTableAlign::Right=>1.0,
+                    };
+                    let width = column_widths.get(table_column).copied().unwrap_or(f64::NAN);
+                    tf.begin_table_cell(cx, width, align);
+                },
+                MarkdownNode::EndTableCell=>{
+                    tf.end_table_cell(cx);
+                    table_column += 1;
+                },
+                MarkdownNode::FootnoteRef{start, end}=>{
+                    tf.push_size_rel_scale(0.7);
+                    tf.draw_text(cx, "[");
+                    tf.draw_text(cx, &self.doc.decoded[*start..*end]);
+                    tf.draw_text(cx, "]");
+                    tf.font_sizes.pop();
+                },
+                MarkdownNode::BeginFootnoteDef{start, end}=>{
+                    cx.turtle_new_line();
+                    let label = format!("[{}]", &self.doc.decoded[*start..*end]);
+                    tf.begin_list_item(cx, &label, 2.0);
+                },
+                MarkdownNode::EndFootnoteDef=>{
+                    tf.end_list_item(cx);
+                },
+                MarkdownNode::Link{start, url_start, ..}=>{
+                    if let Some(item) = tf.item(cx, LiveId(index as u64), live_id!(link)){
+                        item.set_text(&self.doc.decoded[*start..*url_start]);
+                        item.draw_all(cx, &mut Scope::empty());
+                    }
                 },
                 MarkdownNode::Image{start, url_start, end}=>{
                     tf.draw_text(cx, "Image[name:");
@@ -102,11 +184,11 @@ impl Widget for Markdown {
                 MarkdownNode::EndQuote=>{
                     tf.end_quote(cx);
                 },
-                MarkdownNode::BeginUnderline=>{
-                    tf.underline.push();
+                MarkdownNode::BeginStrikethrough=>{
+                    tf.strikethrough.push();
                 },
-                MarkdownNode::EndUnderline=>{
-                    tf.underline.pop();
+                MarkdownNode::EndStrikethrough=>{
+                    tf.strikethrough.pop();
                 },
                 MarkdownNode::BeginInlineCode=>{
                     tf.fixed.push();
@@ -175,10 +257,44 @@ impl Markdown {
     }
 }
 
+// the character count of the widest cell in each column of the table starting at index
+fn table_column_weights(doc:&MarkdownDoc, index:usize, columns:usize)->Vec<f64>{
+    let mut weights = vec![3.0; columns];
+    let mut column = 0;
+    let mut len = 0;
+    for node in &doc.nodes[index..]{
+        match node{
+            MarkdownNode::EndTable=>break,
+            MarkdownNode::BeginTableRow{..}=>column = 0,
+            MarkdownNode::BeginTableCell{..}=>len = 0,
+            MarkdownNode::Text{start, end} | MarkdownNode::Link{start, url_start: end, ..}=>{
+                len += doc.decoded[*start..*end].chars().count();
+            }
+            MarkdownNode::EndTableCell=>{
+                if let Some(weight) = weights.get_mut(column){
+                    *weight = weight.max(len as f64);
+                }
+                column += 1;
+            }
+            _=>()
+        }
+    }
+    weights
+}
+
 impl MarkdownRef {
     pub fn set_text(&mut self, v:&str) {
         let Some(mut inner) = self.borrow_mut() else { return };
         inner.set_text(v)
     }
+
+    /// The url of the link that was clicked, if any
+    pub fn link_clicked(&self, actions:&Actions) -> Option<String> {
+        if let MarkdownAction::LinkNavigated(url) = actions.find_widget_action(self.widget_uid()).cast() {
+            Some(url)
+        } else {
+            None
+        }
+    }
 }
  
\ No newline at end of file
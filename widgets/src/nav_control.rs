@@ -3,7 +3,7 @@ use crate::makepad_draw::*;
 
 live_design!{
     import makepad_draw::shader::std::*;
-    
+
     NavControlBase = {{NavControl}} {}
 }
 
@@ -12,11 +12,15 @@ pub struct NavControl {
     #[live] draw_list: DrawList2d,
     #[live] draw_focus: DrawQuad,
     #[live] draw_text: DrawText,
-    #[rust] _recent_focus: Area,
+    #[live(2.0)] focus_ring_grow: f64,
+    // move the focus with the arrow keys (and gamepad dpad) to the nearest stop on screen
+    #[live(true)] spatial_nav: bool,
+    #[rust] nav_focus: Area,
+    #[rust] focus_ring: bool,
 }
 
 impl NavControl {
-    
+
     pub fn send_trigger_to_scroll_stack(cx: &mut Cx, stack:Vec<Area>){
         let mut prev_area = None;
         for next_area in stack{
@@ -29,55 +33,90 @@ impl NavControl {
             prev_area = Some(next_area);
         }
     }
-    
+
+    // the stop that has the key focus, or holds what has it (like the text input in a slider)
+    fn focused_stop(cx: &Cx, stops: &[NavStopRect]) -> Option<usize> {
+        if let Some(index) = stops.iter().position( | stop | cx.has_key_focus(stop.stop.area)) {
+            return Some(index)
+        }
+        let focus = cx.key_focus();
+        if focus.is_empty() || !focus.is_valid(cx) {
+            return None
+        }
+        let center = focus.rect(cx).center();
+        stops.iter().rposition( | stop | stop.rect.contains(center))
+    }
+
+    fn focus_stop(&mut self, cx: &mut Cx, stop: &NavStopRect) {
+        let mut stack = stop.scroll_stack.clone();
+        stack.push(stop.stop.area);
+        Self::send_trigger_to_scroll_stack(cx, stack);
+        cx.set_key_focus(stop.stop.area);
+        self.nav_focus = stop.stop.area;
+        self.focus_ring = true;
+        self.draw_list.redraw(cx);
+    }
+
     pub fn handle_event(&mut self, cx: &mut Cx, event: &Event, root: DrawListId) {
         match event {
-            Event::KeyDown(ke) => match ke.key_code {
-                KeyCode::Tab => {
-                    if ke.modifiers.shift {
-                        let mut prev_area = Area::Empty;
-                        if let Some((prev_area, scroll_stack)) = Cx2d::iterate_nav_stops(cx, root, | cx, stop | {
-                            if cx.has_key_focus(stop.area) {
-                                return Some(prev_area);
-                            }
-                            prev_area = stop.area;
-                            None
-                        }) {
-                            if !prev_area.is_empty() {
-                                Self::send_trigger_to_scroll_stack(cx, scroll_stack);
-                                cx.set_key_focus(prev_area);
-                            }
+            Event::KeyDown(ke) if ke.key_code == KeyCode::Tab && !ke.modifiers.control && !ke.modifiers.alt && !ke.modifiers.logo => {
+                let stops = Cx2d::nav_stop_rects(cx, root);
+                let current = Self::focused_stop(cx, &stops);
+                // don't take Tab away from something else that has the focus, like the code editor
+                if current.is_none() && !cx.has_key_focus(Area::Empty) {
+                    return
+                }
+                if let Some(next) = nav_next(&stops, current, ke.modifiers.shift) {
+                    self.focus_stop(cx, &stops[next]);
+                }
+            }
+            Event::KeyDown(ke) if self.spatial_nav && !ke.modifiers.any() => {
+                let direction = match ke.key_code {
+                    KeyCode::ArrowUp => NavDirection::Up,
+                    KeyCode::ArrowDown => NavDirection::Down,
+                    KeyCode::ArrowLeft => NavDirection::Left,
+                    KeyCode::ArrowRight => NavDirection::Right,
+                    _ => return
+                };
+                let stops = Cx2d::nav_stop_rects(cx, root);
+                match Self::focused_stop(cx, &stops) {
+                    Some(current) => {
+                        if stops[current].stop.role.uses_arrow(direction) {
+                            return
+                        }
+                        if let Some(next) = nav_nearest(&stops, current, direction) {
+                            self.focus_stop(cx, &stops[next]);
                         }
                     }
-                    else {
-                        let mut next_stop = false;
-                        if let Some((next_area, scroll_stack)) = Cx2d::iterate_nav_stops(cx, root, | cx, stop | {
-                            if next_stop {
-                                return Some(stop.area)
-                            }
-                            if cx.has_key_focus(stop.area) {
-                                next_stop = true;
-                            }
-                            None
-                        }) {
-                            Self::send_trigger_to_scroll_stack(cx, scroll_stack);
-                            cx.set_key_focus(next_area);
+                    None => if cx.has_key_focus(Area::Empty) {
+                        if let Some(first) = nav_next(&stops, None, false) {
+                            self.focus_stop(cx, &stops[first]);
                         }
                     }
                 }
-                _ => ()
-            },
+            }
+            Event::KeyFocus(kf) => {
+                // only show the ring when the focus got there by navigating
+                let focus_ring = kf.focus == self.nav_focus;
+                if focus_ring != self.focus_ring {
+                    self.focus_ring = focus_ring;
+                    self.draw_list.redraw(cx);
+                }
+            }
             _ => ()
         }
     }
-    
+
     pub fn draw(&mut self, cx: &mut Cx2d) {
-        if !self.draw_list.begin(cx, Walk::default()).is_redrawing() {
-            return
+        self.draw_list.begin_overlay_last(cx);
+        let focus = cx.key_focus();
+        if self.focus_ring && focus.is_valid(cx) {
+            let rect = focus.clipped_rect(cx);
+            if rect.size.x > 0.0 && rect.size.y > 0.0 {
+                self.draw_focus.draw_abs(cx, rect.add_margin(dvec2(self.focus_ring_grow, self.focus_ring_grow)));
+            }
         }
-        
         self.draw_list.end(cx);
     }
 }
 
-
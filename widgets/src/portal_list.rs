@@ -319,7 +319,8 @@ impl PortalList {
                                 abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y + self.first_scroll)),
                                 margin: Default::default(),
                                 width: Size::Fill,
-                                height: Size::Fit
+                                height: Size::Fit,
+                                ..Walk::default()
                             }, layout);
                         }
                         Vec2Index::X => {
@@ -327,7 +328,8 @@ impl PortalList {
                                 abs_pos: Some(dvec2(viewport.pos.x + self.first_scroll, viewport.pos.y)),
                                 margin: Default::default(),
                                 width: Size::Fit,
-                                height: Size::Fill
+                                height: Size::Fill,
+                                ..Walk::default()
                             }, layout);
                         }
                     }
@@ -360,7 +362,8 @@ impl PortalList {
                                         abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y)),
                                         margin: Default::default(),
                                         width: Size::Fill,
-                                        height: Size::Fit
+                                        height: Size::Fit,
+                                        ..Walk::default()
                                     }, layout);
                                 }
                                 Vec2Index::X => {
@@ -368,7 +371,8 @@ impl PortalList {
                                         abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y)),
                                         margin: Default::default(),
                                         width: Size::Fit,
-                                        height: Size::Fill
+                                        height: Size::Fill,
+                                        ..Walk::default()
                                     }, layout);
                                 }
                             }
@@ -399,7 +403,8 @@ impl PortalList {
                                 abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y + pos + rect.size.index(vi))),
                                 margin: Default::default(),
                                 width: Size::Fill,
-                                height: Size::Fit
+                                height: Size::Fit,
+                                ..Walk::default()
                             }, layout);
                         }
                         Vec2Index::X => {
@@ -407,7 +412,8 @@ impl PortalList {
                                 abs_pos: Some(dvec2(viewport.pos.x + pos + rect.size.index(vi), viewport.pos.y)),
                                 margin: Default::default(),
                                 width: Size::Fit,
-                                height: Size::Fill
+                                height: Size::Fill,
+                                ..Walk::default()
                             }, layout);
                         }
                     }
@@ -441,7 +447,8 @@ impl PortalList {
                                     abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y + total_height)),
                                     margin: Default::default(),
                                     width: Size::Fill,
-                                    height: Size::Fit
+                                    height: Size::Fit,
+                                    ..Walk::default()
                                 }, Layout::flow_down());
                                 return Some(last_index + 1);
                             }
@@ -466,7 +473,8 @@ impl PortalList {
                         abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y)),
                         margin: Default::default(),
                         width: Size::Fill,
-                        height: Size::Fit
+                        height: Size::Fit,
+                        ..Walk::default()
                     }, Layout::flow_down());
                     
                     return Some(index - 1);
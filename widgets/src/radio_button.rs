@@ -97,6 +97,7 @@ impl RadioButton {
         }
         self.draw_text.draw_walk(cx, self.label_walk, self.label_align, self.text.as_ref());
         self.draw_radio.end(cx);
+        cx.add_nav_stop(self.draw_radio.area(), NavRole::RadioButton, Margin::default());
     }
         
 }
@@ -137,6 +138,11 @@ impl Widget for RadioButton {
                     cx.widget_action(uid, &scope.path, RadioButtonAction::Clicked);
                 }
             },
+            Hit::KeyDown(ke) if !ke.is_repeat && matches!(ke.key_code, KeyCode::ReturnKey | KeyCode::NumpadEnter | KeyCode::Space)
+                && self.animator_in_state(cx, id!(selected.off)) => {
+                self.animator_play(cx, id!(selected.on));
+                cx.widget_action(uid, &scope.path, RadioButtonAction::Clicked);
+            }
             Hit::FingerUp(_fe) => {
                                 
             }
@@ -152,6 +158,13 @@ impl Widget for RadioButton {
         DrawStep::done()
     }
     
+    fn accessibility(&self, cx: &Cx) -> Option<NavAccessible> {
+        Some(NavAccessible::new(self.draw_radio.area(), AccessibilityRole::RadioButton, self.text.as_ref()).with_state(AccessibilityState {
+            checked: Some(self.animator_in_state(cx, id!(selected.on))),
+            ..Default::default()
+        }))
+    }
+    
     fn text(&self) -> String {
         self.text.as_ref().to_string()
     }
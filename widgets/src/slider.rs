@@ -25,6 +25,7 @@ impl LiveHook for Slider{
     fn after_new_from_doc(&mut self, _cx:&mut Cx){
         self.set_internal(self.default);
         self.update_text_input();
+        self.text_input.nav_stop = false;
     }
 }
 
@@ -126,6 +127,15 @@ impl Slider {
         }
         
         self.draw_slider.end(cx);
+        cx.add_nav_stop(self.draw_slider.area(), NavRole::Slider, Margin::default());
+    }
+    
+    fn slide_to(&mut self, cx: &mut Cx, uid: WidgetUid, scope: &mut Scope, external: f64) {
+        if self.set_internal(external.max(self.min).min(self.max)) {
+            self.draw_slider.redraw(cx);
+            self.update_text_input_and_redraw(cx);
+            cx.widget_action(uid, &scope.path, SliderAction::Slide(self.to_external()));
+        }
     }
 }
 
@@ -200,6 +210,22 @@ impl Widget for Slider {
                     cx.widget_action(uid, &scope.path, SliderAction::Slide(self.to_external()));
                 }
             }
+            Hit::KeyDown(ke) => {
+                let step = if self.step != 0.0 {self.step} else {(self.max - self.min) / 100.0};
+                match ke.key_code {
+                    KeyCode::ArrowLeft => self.slide_to(cx, uid, scope, self.to_external() - step),
+                    KeyCode::ArrowRight => self.slide_to(cx, uid, scope, self.to_external() + step),
+                    KeyCode::Home => self.slide_to(cx, uid, scope, self.min),
+                    KeyCode::End => self.slide_to(cx, uid, scope, self.max),
+                    KeyCode::ReturnKey | KeyCode::NumpadEnter if !ke.is_repeat => {
+                        // edit the value as text
+                        self.text_input.set_key_focus(cx);
+                        self.text_input.select_all();
+                        self.text_input.redraw(cx);
+                    }
+                    _ => ()
+                }
+            }
             _ => ()
         }
     }
@@ -230,6 +256,10 @@ impl Widget for Slider {
         }
     }
     
+    fn accessibility(&self, _cx: &Cx) -> Option<NavAccessible> {
+        Some(NavAccessible::new(self.draw_slider.area(), AccessibilityRole::Slider, &self.text).with_value(self.text_input.text.clone()))
+    }
+    
     fn text(&self) -> String {
         format!("{}", self.to_external())
     }
@@ -69,6 +69,11 @@ impl Tab {
         self.draw_name.draw_walk(cx, Walk::fit(), Align::default(), name);
         //cx.turtle_align_y();
         self.draw_bg.end(cx);
+        cx.add_nav_stop(self.draw_bg.area(), NavRole::Tab, Margin::default());
+        cx.add_accessible(NavAccessible::new(self.draw_bg.area(), AccessibilityRole::Tab, name).with_state(AccessibilityState {
+            selected: self.is_selected,
+            ..Default::default()
+        }));
         
         //if self.is_dragged {
         //    self.draw_drag.draw_abs(cx, self.draw_bg.area().get_clipped_rect(cx));
@@ -117,6 +122,9 @@ impl Tab {
             Hit::FingerDown(_) => {
                 dispatch_action(cx, TabAction::WasPressed);
             }
+            Hit::KeyDown(ke) if !ke.is_repeat && matches!(ke.key_code, KeyCode::ReturnKey | KeyCode::NumpadEnter | KeyCode::Space) => {
+                dispatch_action(cx, TabAction::WasPressed);
+            }
             _ => {}
         }
     }
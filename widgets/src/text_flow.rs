@@ -23,7 +23,9 @@ pub enum FlowBlockType {
     Code = shader_enum(3),
     InlineCode = shader_enum(4),
     Underline = shader_enum(5),
-    Strikethrough = shader_enum(6)
+    Strikethrough = shader_enum(6),
+    Checkbox = shader_enum(7),
+    CheckboxChecked = shader_enum(8)
 }
 
 #[derive(Live, LiveHook, LiveRegister)]
@@ -95,6 +97,9 @@ pub struct TextFlow {
     #[live] list_item_walk: Walk,
     #[live] inline_code_padding: Padding,
     #[live] inline_code_margin: Margin,
+    #[live] table_layout: Layout,
+    #[live] table_walk: Walk,
+    #[live] table_cell_layout: Layout,
         
     #[redraw] #[rust] area:Area,
     #[rust] draw_state: DrawStateWrap<DrawState>,
@@ -265,6 +270,76 @@ impl TextFlow{
         self.draw_block.end(cx);
     }
     
+    pub fn draw_checkbox(&mut self, cx:&mut Cx2d, checked:bool){
+        let fs = self.font_sizes.last().unwrap_or(&self.font_size);
+        self.draw_normal.text_style.font_size = *fs;
+        let size = self.draw_normal.get_font_size();
+        self.draw_block.block_type = if checked{
+            FlowBlockType::CheckboxChecked
+        }
+        else{
+            FlowBlockType::Checkbox
+        };
+        self.draw_block.draw_walk(cx, Walk{
+            margin: Margin{right: size * 0.5, ..Margin::default()},
+            ..Walk::fixed(size, size)
+        });
+    }
+    
+    pub fn begin_table(&mut self, cx:&mut Cx2d){
+        cx.begin_turtle(self.table_walk, self.table_layout);
+    }
+    
+    pub fn end_table(&mut self, cx:&mut Cx2d){
+        cx.end_turtle();
+    }
+    
+    pub fn begin_table_row(&mut self, cx:&mut Cx2d){
+        cx.begin_turtle(Walk::size(Size::Fill, Size::Fit), Layout{
+            flow: Flow::Right,
+            ..Layout::default()
+        });
+    }
+    
+    pub fn end_table_row(&mut self, cx:&mut Cx2d){
+        cx.end_turtle();
+    }
+    
+    pub fn begin_table_cell(&mut self, cx:&mut Cx2d, width:f64, align:f64){
+        // a RightWrap turtle doesn't align its items, so we wrap it in an aligned one.
+        // left aligned cells wrap their text, the others keep it on a single line
+        if width.is_nan(){
+            cx.begin_turtle(Walk::fit(), self.table_cell_layout);
+            cx.begin_turtle(Walk::fit(), Layout{
+                flow: Flow::RightWrap,
+                line_spacing: self.layout.line_spacing,
+                ..Layout::default()
+            });
+            return
+        }
+        cx.begin_turtle(Walk::size(Size::Fixed(width), Size::Fit), Layout{
+            flow: Flow::Right,
+            align: Align{x: align, y: 0.0},
+            ..self.table_cell_layout
+        });
+        let walk = if align == 0.0{
+            Walk::size(Size::Fixed(cx.turtle().padded_rect().size.x), Size::Fit)
+        }
+        else{
+            Walk::fit()
+        };
+        cx.begin_turtle(walk, Layout{
+            flow: Flow::RightWrap,
+            line_spacing: self.layout.line_spacing,
+            ..Layout::default()
+        });
+    }
+    
+    pub fn end_table_cell(&mut self, cx:&mut Cx2d){
+        cx.end_turtle();
+        cx.end_turtle();
+    }
+    
     pub fn item(&mut self, cx: &mut Cx, entry_id: LiveId, template: LiveId) -> Option<WidgetRef> {
         if let Some(ptr) = self.templates.get(&template) {
             let entry = self.items.get_or_insert(cx, (entry_id, template), | cx | {
@@ -275,6 +350,10 @@ impl TextFlow{
         None 
     }
         
+    pub fn existing_item(&self, entry_id: LiveId, template: LiveId) -> Option<WidgetRef> {
+        self.items.get(&(entry_id, template)).cloned()
+    }
+        
     pub fn clear_items(&mut self){
         self.items.clear();
     }
@@ -58,6 +58,8 @@ pub struct TextInput {
     #[live] secret: bool,
     #[live] on_focus_select_all: bool,
     #[live] pub read_only: bool,
+    // widgets embedding a text input (like the slider) register their own stop instead
+    #[live(true)] pub nav_stop: bool,
     
     //#[live] label_walk: Walk,
     
@@ -346,6 +348,17 @@ impl Widget for TextInput {
     }
     
     
+    fn accessibility(&self, _cx: &Cx) -> Option<NavAccessible> {
+        let accessible = NavAccessible::new(self.draw_bg.area(), AccessibilityRole::TextInput, &self.empty_message).with_state(AccessibilityState {
+            secret: self.secret,
+            ..Default::default()
+        });
+        if self.secret {
+            return Some(accessible)
+        }
+        Some(accessible.with_value(self.text.clone()))
+    }
+    
     fn text(&self) -> String {
         self.text.clone()
     }
@@ -642,7 +655,9 @@ impl TextInput {
             }
         }
         
-        cx.add_nav_stop(self.draw_bg.area(), NavRole::TextInput, Margin::default())
+        if self.nav_stop {
+            cx.add_nav_stop(self.draw_bg.area(), NavRole::TextInput, Margin::default())
+        }
     }
 }
 
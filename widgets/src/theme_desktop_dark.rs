@@ -394,6 +394,91 @@ live_design! {
     // Spacer = <View> { width: Fill, height: Fill }
     Filler = <View> { width: Fill, height: Fill }
 
+    LinkLabel = <LinkLabelBase> {
+        // TODO: adda  focus states
+        instance hover: 0.0
+        instance pressed: 0.0
+
+        width: Fit, height: Fit,
+        padding: { top: (THEME_SPACE_2), bottom: 2. }
+        spacing: 7.5,
+        align: {x: 0., y: 0.}
+
+        label_walk: { width: Fit, height: Fit, },
+
+        draw_bg: {
+            instance pressed: 0.0
+            instance hover: 0.0
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let offset_y = 1.0
+                sdf.move_to(0., self.rect_size.y - offset_y);
+                sdf.line_to(self.rect_size.x, self.rect_size.y - offset_y);
+                return sdf.stroke(mix(
+                    THEME_COLOR_TEXT_DEFAULT,
+                    THEME_COLOR_TEXT_PRESSED,
+                    self.pressed
+                ), mix(.7, 1., self.hover));
+            }
+        }
+
+        draw_text: {
+            wrap: Word
+            instance pressed: 0.0
+            instance hover: 0.0
+            text_style: <THEME_FONT_REGULAR> {
+                font_size: (THEME_FONT_SIZE_P)
+            }
+            fn get_color(self) -> vec4 {
+                return mix(
+                    mix(
+                        THEME_COLOR_TEXT_DEFAULT,
+                        THEME_COLOR_TEXT_HOVER,
+                        self.hover
+                    ),
+                    THEME_COLOR_TEXT_PRESSED,
+                    self.pressed
+                )
+            }
+        }
+
+        animator: {
+            hover = {
+                default: off,
+                off = {
+                    from: {all: Forward {duration: 0.1}}
+                    apply: {
+                        draw_bg: {pressed: 0.0, hover: 0.0}
+                        draw_icon: {pressed: 0.0, hover: 0.0}
+                        draw_text: {pressed: 0.0, hover: 0.0}
+                    }
+                }
+
+                on = {
+                    from: {
+                        all: Forward {duration: 0.1}
+                        pressed: Forward {duration: 0.01}
+                    }
+                    apply: {
+                        draw_bg: {pressed: 0.0, hover: [{time: 0.0, value: 1.0}],}
+                        draw_icon: {pressed: 0.0, hover: [{time: 0.0, value: 1.0}],}
+                        draw_text: {pressed: 0.0, hover: [{time: 0.0, value: 1.0}],}
+                    }
+                }
+
+                pressed = {
+                    from: {all: Forward {duration: 0.2}}
+                    apply: {
+                        draw_bg: {pressed: [{time: 0.0, value: 1.0}], hover: 1.0,}
+                        draw_icon: {pressed: [{time: 0.0, value: 1.0}], hover: 1.0,}
+                        draw_text: {pressed: [{time: 0.0, value: 1.0}], hover: 1.0,}
+                    }
+                }
+            }
+        }
+
+    }
+
     HtmlLink = <HtmlLinkBase> {
         width: Fit, height: Fit,
         align: {x: 0., y: 0.}
@@ -719,11 +804,17 @@ live_design! {
             height: Fit, width: Fill,
         }
 
+        table_layout: { flow: Down }
+        table_walk: { width: Fill, height: Fit }
+        table_cell_layout: { padding: <THEME_MSPACE_1> {} }
+
         sep_walk: {
             width: Fill, height: 4.
             margin: <THEME_MSPACE_V_3> {}
         }
 
+        link = <LinkLabel> { padding: 0., margin: 0. }
+
         draw_block: {
             line_color: (THEME_COLOR_TEXT_DEFAULT)
             sep_color: (THEME_COLOR_DIVIDER)
@@ -808,6 +899,33 @@ live_design! {
                         sdf.fill(self.line_color);
                         return sdf.result;
                     }
+                    FlowBlockType::Checkbox => {
+                        sdf.box(
+                            1.,
+                            1.,
+                            self.rect_size.x - 2.,
+                            self.rect_size.y - 2.,
+                            2.
+                        );
+                        sdf.stroke(self.line_color, 1.);
+                        return sdf.result;
+                    }
+                    FlowBlockType::CheckboxChecked => {
+                        sdf.box(
+                            1.,
+                            1.,
+                            self.rect_size.x - 2.,
+                            self.rect_size.y - 2.,
+                            2.
+                        );
+                        sdf.fill(self.line_color);
+                        let sz = self.rect_size.x;
+                        sdf.move_to(sz * 0.25, sz * 0.5);
+                        sdf.line_to(sz * 0.45, sz * 0.7);
+                        sdf.line_to(sz * 0.75, sz * 0.3);
+                        sdf.stroke(self.code_color, 1.5);
+                        return sdf.result;
+                    }
                 }
                 return #f00
             }
@@ -1646,7 +1764,16 @@ live_design! {
     NavControl = <NavControlBase> {
         draw_focus: {
             fn pixel(self) -> vec4 {
-                return #000f
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.box(
+                    1.,
+                    1.,
+                    self.rect_size.x - 2.,
+                    self.rect_size.y - 2.,
+                    THEME_CORNER_RADIUS
+                );
+                sdf.stroke(THEME_COLOR_TEXT_FOCUSED, 1.5);
+                return sdf.result
             }
         }
         draw_text: {
@@ -2551,6 +2678,17 @@ live_design! {
             }
         }
 
+        draw_status: {
+            fn get_color(self) -> vec4 {
+                return self.color * self.scale
+            }
+
+            text_style: <THEME_FONT_REGULAR> {
+                font_size: (THEME_FONT_SIZE_P)
+                top_drop: 1.2,
+            }
+        }
+
         icon_walk: {
             width: (THEME_DATA_ICON_WIDTH - 2), height: (THEME_DATA_ICON_HEIGHT),
             margin: { right: 3.0 }
@@ -2793,91 +2931,6 @@ live_design! {
         }
     }
 
-    LinkLabel = <LinkLabelBase> {
-        // TODO: adda  focus states
-        instance hover: 0.0
-        instance pressed: 0.0
-
-        width: Fit, height: Fit,
-        padding: { top: (THEME_SPACE_2), bottom: 2. }
-        spacing: 7.5,
-        align: {x: 0., y: 0.}
-
-        label_walk: { width: Fit, height: Fit, },
-
-        draw_bg: {
-            instance pressed: 0.0
-            instance hover: 0.0
-            fn pixel(self) -> vec4 {
-                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
-                let offset_y = 1.0
-                sdf.move_to(0., self.rect_size.y - offset_y);
-                sdf.line_to(self.rect_size.x, self.rect_size.y - offset_y);
-                return sdf.stroke(mix(
-                    THEME_COLOR_TEXT_DEFAULT,
-                    THEME_COLOR_TEXT_PRESSED,
-                    self.pressed
-                ), mix(.7, 1., self.hover));
-            }
-        }
-
-        draw_text: {
-            wrap: Word
-            instance pressed: 0.0
-            instance hover: 0.0
-            text_style: <THEME_FONT_REGULAR> {
-                font_size: (THEME_FONT_SIZE_P)
-            }
-            fn get_color(self) -> vec4 {
-                return mix(
-                    mix(
-                        THEME_COLOR_TEXT_DEFAULT,
-                        THEME_COLOR_TEXT_HOVER,
-                        self.hover
-                    ),
-                    THEME_COLOR_TEXT_PRESSED,
-                    self.pressed
-                )
-            }
-        }
-
-        animator: {
-            hover = {
-                default: off,
-                off = {
-                    from: {all: Forward {duration: 0.1}}
-                    apply: {
-                        draw_bg: {pressed: 0.0, hover: 0.0}
-                        draw_icon: {pressed: 0.0, hover: 0.0}
-                        draw_text: {pressed: 0.0, hover: 0.0}
-                    }
-                }
-
-                on = {
-                    from: {
-                        all: Forward {duration: 0.1}
-                        pressed: Forward {duration: 0.01}
-                    }
-                    apply: {
-                        draw_bg: {pressed: 0.0, hover: [{time: 0.0, value: 1.0}],}
-                        draw_icon: {pressed: 0.0, hover: [{time: 0.0, value: 1.0}],}
-                        draw_text: {pressed: 0.0, hover: [{time: 0.0, value: 1.0}],}
-                    }
-                }
-
-                pressed = {
-                    from: {all: Forward {duration: 0.2}}
-                    apply: {
-                        draw_bg: {pressed: [{time: 0.0, value: 1.0}], hover: 1.0,}
-                        draw_icon: {pressed: [{time: 0.0, value: 1.0}], hover: 1.0,}
-                        draw_text: {pressed: [{time: 0.0, value: 1.0}], hover: 1.0,}
-                    }
-                }
-            }
-        }
-
-    }
-
     LinkLabelIcon = <LinkLabel> {
         padding: { bottom: 2. }
         label_walk: { margin: { left: -5. }},
@@ -4030,6 +4083,65 @@ live_design! {
 
     DesignerOutline = <DesignerOutlineBase>{ }
 
+    DesignerPropertyRow = <View> {
+        width: Fill, height: Fit,
+        padding: <THEME_MSPACE_H_2> {}
+        spacing: (THEME_SPACE_2)
+        align: { x: 0.0, y: 0.5 }
+        label = <P> { width: 90., margin: 0., text: "" }
+    }
+
+    DesignerPropertySlider = <Slider> { width: Fill, text: "" }
+
+    DesignerProperties = <DesignerPropertiesBase> {
+        width: Fill, height: Fill,
+        flow: Down,
+        <DockToolbar> {
+            content = {
+                title = <Pbold> { width: Fill, margin: {left: (THEME_SPACE_1)}, text: "Properties" }
+            }
+        }
+        list = <PortalList> {
+            width: Fill, height: Fill,
+            flow: Down,
+            drag_scrolling: false
+            Number = <DesignerPropertyRow> {
+                value = <DesignerPropertySlider> {}
+            }
+            Size = <DesignerPropertyRow> {
+                kind = <DropDown> { width: 60., labels: ["Fill", "Fit", "Fixed", "All"] }
+                value = <DesignerPropertySlider> {}
+            }
+            Sides = <DesignerPropertyRow> {
+                flow: Down,
+                align: { x: 0.0, y: 0.0 }
+                spacing: 0.
+                left = <DesignerPropertySlider> { text: "left" }
+                top = <DesignerPropertySlider> { text: "top" }
+                right = <DesignerPropertySlider> { text: "right" }
+                bottom = <DesignerPropertySlider> { text: "bottom" }
+            }
+            Align = <DesignerPropertyRow> {
+                x = <DesignerPropertySlider> { text: "x" }
+                y = <DesignerPropertySlider> { text: "y" }
+            }
+            Enum = <DesignerPropertyRow> {
+                value = <DropDown> { width: Fill }
+            }
+            Bool = <DesignerPropertyRow> {
+                value = <CheckBox> { text: "" }
+            }
+            Text = <DesignerPropertyRow> {
+                value = <TextInput> { width: Fill }
+            }
+            Color = <DesignerPropertyRow> {
+                align: { x: 0.0, y: 0.0 }
+                value = <ColorPicker> { width: 90., height: 90. }
+                hex = <TextInput> { width: Fill }
+            }
+        }
+    }
+
     Vr = <View> {
         width: Fit, height: 27.,
         flow: Right,
@@ -4565,8 +4677,17 @@ live_design! {
                             }
                         }
                     }
-                    outline_tree = <DesignerOutlineTree>{
+                    <Splitter> {
+                        axis: Vertical,
+                        align: FromB(350.0),
+                        a: <View> {
+                            outline_tree = <DesignerOutlineTree>{
 
+                            }
+                        }
+                        b: <View> {
+                            properties = <DesignerProperties> {}
+                        }
                     }
                 }
             }
@@ -66,6 +66,12 @@ pub trait Widget: WidgetNode {
     fn is_visible(&self) -> bool {
         true
     }
+    
+    /// Describes the widget for the accessibility tree, asked for once it is drawn. A widget
+    /// that returns `None` only shows up there if it adds a nav stop.
+    fn accessibility(&self, _cx: &Cx) -> Option<NavAccessible> {
+        None
+    }
 
     fn draw_all(&mut self, cx: &mut Cx2d, scope: &mut Scope) {
         while self.draw(cx, scope).is_step() {};
@@ -433,13 +439,15 @@ impl WidgetRef {
                 }
                 return DrawStep::make_step_here(nd);
             }
+            Self::add_accessible(&*inner.widget, cx);
         }
         DrawStep::done()
     }
     
     pub fn draw_walk_all(&self, cx: &mut Cx2d, scope:&mut Scope, walk: Walk)  {
         if let Some(inner) = self.0.borrow_mut().as_mut() {
-            inner.widget.draw_walk_all(cx, scope, walk)
+            inner.widget.draw_walk_all(cx, scope, walk);
+            Self::add_accessible(&*inner.widget, cx);
         }
     }
     
@@ -451,10 +459,18 @@ impl WidgetRef {
                 }
                 return DrawStep::make_step_here(nd);
             }
+            Self::add_accessible(&*inner.widget, cx);
         }
         DrawStep::done()
     }
     
+    // a widget is done drawing, put its description in the nav tree
+    fn add_accessible(widget: &dyn Widget, cx: &mut Cx2d) {
+        if let Some(accessible) = widget.accessibility(cx) {
+            cx.add_accessible(accessible);
+        }
+    }
+    
     pub fn walk(&self, cx:&mut Cx) -> Walk {
         if let Some(inner) = self.0.borrow_mut().as_mut() {
             return inner.widget.walk(cx)
@@ -137,9 +137,20 @@ impl Window {
         Redrawing::yes()
     }
     
+    // hands the accessibility tree of what was just drawn to the platform
+    fn update_accessibility(&mut self, cx: &mut Cx2d) {
+        let window_id = self.window.window_id();
+        let mut window = AccessibilityNode::new(AccessibilityRole::Window, &cx.windows[window_id].create_title);
+        window.bounds = Rect {pos: DVec2::default(), size: self.window.get_inner_size(cx)};
+        let tree = Cx2d::accessibility_tree(cx, self.main_draw_list.draw_list_id(), window);
+        cx.accessibility.set_tree(window_id, tree);
+    }
+    
     pub fn end(&mut self, cx: &mut Cx2d) {
         //while self.frame.draw_widget_continue(cx).is_not_done() {}
         self.debug_view.draw(cx);
+        self.nav_control.draw(cx);
+        self.update_accessibility(cx);
         
         // lets draw our cursor
         if let OsType::LinuxDirect = cx.os_type() {
@@ -202,6 +213,7 @@ impl Widget for Window {
             Event::WindowCloseRequested(ev) => ev.window_id != self.window.window_id(),
             Event::WindowClosed(ev) => {
                 if ev.window_id == self.window.window_id() {
+                    cx.accessibility.remove_tree(ev.window_id);
                     cx.widget_action(uid, &scope.path, WindowAction::WindowClosed)
                 }
                 true
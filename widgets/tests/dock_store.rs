@@ -0,0 +1,162 @@
+// Round-trip tests for saving and restoring a Dock layout through DockItemStore.
+
+use makepad_widgets::{
+    *,
+    makepad_micro_serde::*,
+    splitter::{SplitterAxis, SplitterAlign},
+};
+
+fn id(name: &str) -> LiveIdStore {
+    LiveIdStore(LiveId::from_str_with_lut(name).unwrap())
+}
+
+fn new_dock() -> (Cx, Dock) {
+    let mut cx = Cx::new(Box::new( | _, _ | {}));
+    makepad_widgets::live_design(&mut cx);
+    cx.live_expand();
+    let dock = Dock::new(&mut cx);
+    (cx, dock)
+}
+
+fn layout() -> Vec<DockItemStore> {
+    vec![
+        DockItemStore::Splitter {
+            id: id("root"),
+            axis: SplitterAxis::Horizontal,
+            align: SplitterAlign::FromA(250.0),
+            a: id("file_tabs"),
+            b: id("split1"),
+        },
+        DockItemStore::Splitter {
+            id: id("split1"),
+            axis: SplitterAxis::Vertical,
+            align: SplitterAlign::Weighted(0.75),
+            a: id("edit_tabs"),
+            b: id("log_tabs"),
+        },
+        DockItemStore::Tabs {
+            id: id("file_tabs"),
+            tabs: vec![id("file_tree")],
+            selected: 0,
+            closable: false,
+        },
+        DockItemStore::Tabs {
+            id: id("edit_tabs"),
+            // a generated tab id that has no name in the interner
+            tabs: vec![id("welcome"), LiveIdStore(LiveId(0x1234_5678_9abc)), id("notes")],
+            selected: 2,
+            closable: true,
+        },
+        DockItemStore::Tabs {
+            id: id("log_tabs"),
+            tabs: vec![id("log")],
+            selected: 0,
+            closable: true,
+        },
+        DockItemStore::Tab {id: id("file_tree"), name: "Files".to_string(), template: id("PermanentTab"), kind: id("FileTree")},
+        DockItemStore::Tab {id: id("welcome"), name: "Welcome".to_string(), template: id("PermanentTab"), kind: id("Welcome")},
+        DockItemStore::Tab {id: LiveIdStore(LiveId(0x1234_5678_9abc)), name: "main.rs".to_string(), template: id("CloseableTab"), kind: id("Editor")},
+        DockItemStore::Tab {id: id("notes"), name: "Notes \"draft\"".to_string(), template: id("CloseableTab"), kind: id("Editor")},
+        DockItemStore::Tab {id: id("log"), name: "Log".to_string(), template: id("PermanentTab"), kind: id("LogList")},
+    ]
+}
+
+fn sorted(mut items: Vec<DockItemStore>) -> Vec<DockItemStore> {
+    items.sort_by_key( | item | match item {
+        DockItemStore::Splitter {id, ..} | DockItemStore::Tabs {id, ..} | DockItemStore::Tab {id, ..} => id.0.0
+    });
+    items
+}
+
+#[test]
+fn restores_layout_from_store() {
+    let (mut cx, mut dock) = new_dock();
+    dock.from_store_item(&mut cx, &layout());
+    assert_eq!(sorted(dock.to_store_items()), sorted(layout()));
+}
+
+#[test]
+fn ron_round_trip() {
+    let (mut cx, mut dock) = new_dock();
+    dock.from_store_item(&mut cx, &layout());
+    let saved = dock.to_store_items().serialize_ron();
+
+    let loaded = Vec::<DockItemStore>::deserialize_ron(&saved).unwrap();
+    let mut restored = Dock::new(&mut cx);
+    restored.from_store_item(&mut cx, &loaded);
+    assert_eq!(sorted(restored.to_store_items()), sorted(layout()));
+}
+
+#[test]
+fn json_round_trip() {
+    let (mut cx, mut dock) = new_dock();
+    dock.from_store_item(&mut cx, &layout());
+    let saved = dock.to_store_items().serialize_json();
+
+    let loaded = Vec::<DockItemStore>::deserialize_json(&saved).unwrap();
+    let mut restored = Dock::new(&mut cx);
+    restored.from_store_item(&mut cx, &loaded);
+    assert_eq!(sorted(restored.to_store_items()), sorted(layout()));
+}
+
+#[test]
+fn drops_tabs_missing_from_store() {
+    let (mut cx, mut dock) = new_dock();
+    let mut store = layout();
+    store.retain( | item | !matches!(item, DockItemStore::Tab {id: tab_id, ..} if *tab_id == id("notes")));
+    dock.from_store_item(&mut cx, &store);
+
+    let edit_tabs = dock.to_store_items().into_iter().find( | item | matches!(item, DockItemStore::Tabs {id: tabs_id, ..} if *tabs_id == id("edit_tabs")));
+    assert_eq!(edit_tabs, Some(DockItemStore::Tabs {
+        id: id("edit_tabs"),
+        tabs: vec![id("welcome"), LiveIdStore(LiveId(0x1234_5678_9abc))],
+        selected: 1,
+        closable: true,
+    }));
+}
+
+#[test]
+fn keeps_layout_without_root() {
+    let (mut cx, mut dock) = new_dock();
+    dock.from_store_item(&mut cx, &layout());
+    let mut store = layout();
+    store.retain( | item | !matches!(item, DockItemStore::Splitter {id: split_id, ..} if *split_id == id("root")));
+    dock.from_store_item(&mut cx, &store);
+    assert_eq!(sorted(dock.to_store_items()), sorted(layout()));
+}
+
+#[test]
+fn keeps_layout_with_missing_or_repeated_items() {
+    let (mut cx, mut dock) = new_dock();
+    dock.from_store_item(&mut cx, &layout());
+
+    // a splitter side that isn't in the store
+    let mut store = layout();
+    store.retain( | item | !matches!(item, DockItemStore::Tabs {id: tabs_id, ..} if *tabs_id == id("log_tabs")));
+    dock.from_store_item(&mut cx, &store);
+    assert_eq!(sorted(dock.to_store_items()), sorted(layout()));
+
+    // a splitter side that is a tab instead of a tab bar
+    let mut store = layout();
+    for item in &mut store {
+        if let DockItemStore::Splitter {id: split_id, b, ..} = item {
+            if *split_id == id("split1") {
+                *b = id("log");
+            }
+        }
+    }
+    dock.from_store_item(&mut cx, &store);
+    assert_eq!(sorted(dock.to_store_items()), sorted(layout()));
+
+    // a splitter that contains itself
+    let mut store = layout();
+    for item in &mut store {
+        if let DockItemStore::Splitter {id: split_id, a, ..} = item {
+            if *split_id == id("split1") {
+                *a = id("root");
+            }
+        }
+    }
+    dock.from_store_item(&mut cx, &store);
+    assert_eq!(sorted(dock.to_store_items()), sorted(layout()));
+}
@@ -0,0 +1,253 @@
+// Renders widgets on the headless platform and checks the captured frames.
+// Only builds with MAKEPAD=linux_headless.
+#![cfg(linux_headless)]
+
+use makepad_widgets::{*, makepad_platform::makepad_image_formats::ImageBuffer};
+
+// runs an app through the headless event loop like app_main does
+fn run<A: AppMain + LiveNew + 'static>(register: fn(&mut Cx)) -> Cx {
+    let app = std::rc::Rc::new(std::cell::RefCell::new(None));
+    let cx = std::rc::Rc::new(std::cell::RefCell::new(Cx::new(Box::new(move | cx, event | {
+        if let Event::Startup = event {
+            *app.borrow_mut() = Some(A::new_main(cx));
+        }
+        <dyn AppMain>::handle_event(app.borrow_mut().as_mut().unwrap(), cx, event);
+    }))));
+    makepad_widgets::live_design(&mut cx.borrow_mut());
+    register(&mut cx.borrow_mut());
+    cx.borrow_mut().init_cx_os();
+    Cx::event_loop(cx.clone());
+    std::rc::Rc::try_unwrap(cx).ok().unwrap().into_inner()
+}
+
+// the last frame the app rendered
+fn screenshot(cx: &mut Cx) -> ImageBuffer {
+    cx.take_headless_frames().pop().expect("no frame was rendered").image
+}
+
+fn pixel(image: &ImageBuffer, x: usize, y: usize) -> u32 {
+    image.data[y * image.width + x] & 0xffffff
+}
+
+mod views {
+    use makepad_widgets::*;
+
+    live_design!{
+        import makepad_widgets::base::*;
+        import makepad_widgets::theme_desktop_dark::*;
+
+        App = {{App}} {
+            ui: <Root> {
+                main_window = <Window> {
+                    window: {inner_size: vec2(64, 48)}
+                    body = <View> {
+                        flow: Down
+                        <View> {
+                            width: Fill, height: 16
+                            show_bg: true
+                            draw_bg: {color: #f00}
+                        }
+                        <View> {
+                            width: 32, height: Fill
+                            show_bg: true
+                            draw_bg: {color: #00f}
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[derive(Live, LiveHook, LiveRegister)]
+    pub struct App {
+        #[live] ui: WidgetRef,
+    }
+
+    impl AppMain for App {
+        fn handle_event(&mut self, cx: &mut Cx, event: &Event) {
+            self.ui.handle_event(cx, event, &mut Scope::empty());
+        }
+    }
+}
+
+#[test]
+fn event_loop_keeps_rendered_frames() {
+    let mut cx = run::<views::App>(views::live_design);
+    let frames = cx.take_headless_frames();
+    let image = &frames.last().expect("no frame was rendered").image;
+    assert_eq!((image.width, image.height), (64, 48));
+    assert_eq!(pixel(image, 40, 8), 0xff0000);
+    assert_eq!(pixel(image, 8, 30), 0x0000ff);
+    assert_ne!(pixel(image, 40, 30), 0x0000ff);
+    // taking them empties the list
+    assert!(cx.take_headless_frames().is_empty());
+}
+
+mod dock {
+    use makepad_widgets::*;
+
+    live_design!{
+        import makepad_widgets::base::*;
+        import makepad_widgets::theme_desktop_dark::*;
+
+        App = {{App}} {
+            ui: <Root> {
+                main_window = <Window> {
+                    window: {inner_size: vec2(200, 120)}
+                    body = <Dock> {
+                        width: Fill, height: Fill
+                        root = Splitter {
+                            axis: Horizontal,
+                            align: FromA(100.0),
+                            a: left_tabs,
+                            b: right_tabs
+                        }
+                        left_tabs = Tabs {
+                            tabs: [red_tab],
+                            selected: 0
+                        }
+                        right_tabs = Tabs {
+                            tabs: [blue_tab, green_tab],
+                            selected: 1
+                        }
+                        red_tab = Tab {name: "Red", template: PermanentTab, kind: Red}
+                        blue_tab = Tab {name: "Blue", template: PermanentTab, kind: Blue}
+                        green_tab = Tab {name: "Green", template: PermanentTab, kind: Green}
+                        Red = <View> {width: Fill, height: Fill, show_bg: true, draw_bg: {color: #f00}}
+                        Blue = <View> {width: Fill, height: Fill, show_bg: true, draw_bg: {color: #00f}}
+                        Green = <View> {width: Fill, height: Fill, show_bg: true, draw_bg: {color: #0f0}}
+                    }
+                }
+            }
+        }
+    }
+
+    #[derive(Live, LiveHook, LiveRegister)]
+    pub struct App {
+        #[live] ui: WidgetRef,
+    }
+
+    impl AppMain for App {
+        fn handle_event(&mut self, cx: &mut Cx, event: &Event) {
+            self.ui.handle_event(cx, event, &mut Scope::empty());
+        }
+    }
+}
+
+#[test]
+fn dock_draws_the_selected_tab_of_each_side() {
+    let mut cx = run::<dock::App>(dock::live_design);
+    let image = screenshot(&mut cx);
+    assert_eq!((image.width, image.height), (200, 120));
+    // the panels below the tab bars
+    assert_eq!(pixel(&image, 50, 100), 0xff0000);
+    assert_eq!(pixel(&image, 150, 100), 0x00ff00);
+    assert!(!(0..200).any( | x | pixel(&image, x, 100) == 0x0000ff));
+    // the tab bars and the splitter between the sides are neither
+    assert!(![0xff0000, 0x00ff00].contains(&pixel(&image, 50, 2)));
+    assert!(![0xff0000, 0x00ff00].contains(&pixel(&image, 100, 100)));
+}
+
+mod portal_list {
+    use makepad_widgets::*;
+
+    live_design!{
+        import makepad_widgets::base::*;
+        import makepad_widgets::theme_desktop_dark::*;
+
+        App = {{App}} {
+            ui: <Root> {
+                main_window = <Window> {
+                    window: {inner_size: vec2(40, 100)}
+                    body = <View> {
+                        list = <PortalList> {
+                            width: Fill, height: Fill
+                            Red = <View> {width: Fill, height: 10, show_bg: true, draw_bg: {color: #f00}}
+                            Blue = <View> {width: Fill, height: 10, show_bg: true, draw_bg: {color: #00f}}
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[derive(Live, LiveHook, LiveRegister)]
+    pub struct App {
+        #[live] ui: WidgetRef,
+    }
+
+    impl AppMain for App {
+        fn handle_event(&mut self, cx: &mut Cx, event: &Event) {
+            let scope = &mut Scope::empty();
+            if let Event::Draw(event) = event {
+                let cx = &mut Cx2d::new(cx, event);
+                while let Some(item) = self.ui.draw(cx, scope).step() {
+                    if let Some(mut list) = item.as_portal_list().borrow_mut() {
+                        // more items than fit, only the visible ones are drawn
+                        list.set_item_range(cx, 0, 1000);
+                        while let Some(item_id) = list.next_visible_item(cx) {
+                            let template = if item_id % 2 == 0 {live_id!(Red)} else {live_id!(Blue)};
+                            list.item(cx, item_id, template).unwrap().draw_all(cx, scope);
+                        }
+                    }
+                }
+                return
+            }
+            self.ui.handle_event(cx, event, scope);
+        }
+    }
+}
+
+#[test]
+fn portal_list_draws_the_visible_items() {
+    let mut cx = run::<portal_list::App>(portal_list::live_design);
+    let image = screenshot(&mut cx);
+    for row in 0..10 {
+        let color = if row % 2 == 0 {0xff0000} else {0x0000ff};
+        assert_eq!(pixel(&image, 10, row * 10 + 5), color, "row {}", row);
+    }
+}
+
+mod text_input {
+    use makepad_widgets::*;
+
+    live_design!{
+        import makepad_widgets::base::*;
+        import makepad_widgets::theme_desktop_dark::*;
+
+        App = {{App}} {
+            ui: <Root> {
+                main_window = <Window> {
+                    window: {inner_size: vec2(120, 80)}
+                    body = <View> {
+                        flow: Down
+                        filled = <TextInput> {width: 120, height: 40, text: "MMMM"}
+                        empty = <TextInput> {width: 120, height: 40, text: ""}
+                    }
+                }
+            }
+        }
+    }
+
+    #[derive(Live, LiveHook, LiveRegister)]
+    pub struct App {
+        #[live] ui: WidgetRef,
+    }
+
+    impl AppMain for App {
+        fn handle_event(&mut self, cx: &mut Cx, event: &Event) {
+            self.ui.handle_event(cx, event, &mut Scope::empty());
+        }
+    }
+}
+
+#[test]
+fn text_input_draws_its_text() {
+    let mut cx = run::<text_input::App>(text_input::live_design);
+    let image = screenshot(&mut cx);
+    // both inputs are drawn the same, except for the glyphs at the start of the filled one
+    let differs = | x: usize, y: usize | pixel(&image, x, y) != pixel(&image, x, y + 40);
+    let glyph_pixels = (0..40).flat_map( | y | (0..120).map(move | x | (x, y))).filter( | &(x, y) | differs(x, y)).count();
+    assert!(glyph_pixels > 20, "only {} pixels of text", glyph_pixels);
+    assert!(!(0..40).any( | y | (80..120).any( | x | differs(x, y))));
+}